@@ -0,0 +1,51 @@
+//! sRGB to CIELAB conversion of a 1024x1024 image: the bulk path used before
+//! clustering against palette one pixel at a time. Compare builds with and
+//! without the `simd` feature:
+//!
+//! ```sh
+//! cargo bench --bench convert
+//! cargo bench --bench convert --no-default-features
+//! ```
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use nuri::color::{srgb_to_lab_bulk, Color};
+use palette::Lab;
+
+const SIDE: usize = 1024;
+
+/// Every byte value in every channel, in a pattern that defeats caching.
+fn wallpaper() -> Vec<u8> {
+    (0..SIDE * SIDE * 3)
+        .map(|i| (i.wrapping_mul(2_654_435_761) >> 13) as u8)
+        .collect()
+}
+
+fn srgb_to_lab(c: &mut Criterion) {
+    let rgb = wallpaper();
+    let mut group = c.benchmark_group("srgb_to_lab");
+    group.throughput(Throughput::Elements((SIDE * SIDE) as u64));
+
+    group.bench_function("bulk", |b| {
+        let mut out = Vec::with_capacity(SIDE * SIDE);
+        b.iter(|| {
+            out.clear();
+            srgb_to_lab_bulk(black_box(&rgb), &mut out);
+            black_box(&out);
+        })
+    });
+    group.bench_function("per_pixel", |b| {
+        b.iter(|| {
+            let out: Vec<Lab> = black_box(&rgb)
+                .chunks_exact(3)
+                .map(|p| Color::new(p[0], p[1], p[2]).to_lab())
+                .collect();
+            black_box(out)
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, srgb_to_lab);
+criterion_main!(benches);
@@ -0,0 +1,93 @@
+//! The pipeline stage by stage: loading and preparing a wallpaper,
+//! extraction at several `k`, and slot assignment. `nuri bench <image>`
+//! gives the same breakdown for a real wallpaper in one run.
+//!
+//! ```sh
+//! cargo bench --bench pipeline
+//! cargo bench --bench pipeline -- extract   # one group
+//! ```
+
+use std::hint::black_box;
+use std::path::PathBuf;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use image::{Rgb, RgbImage};
+use nuri::cli::ThemeMode;
+use nuri::pipeline::assign::assign_slots;
+use nuri::pipeline::contrast::{enforce_contrast, DEFAULT_ACCENT_CONTRAST};
+use nuri::pipeline::extract::{
+    extract_colors_with_seed, load_image_with, LoadOptions, DEFAULT_SEED,
+};
+
+/// A 1920x1080 wallpaper: a dusk gradient with a few saturated blobs, saved
+/// once as PNG to the temp directory.
+fn wallpaper() -> PathBuf {
+    let path = std::env::temp_dir().join("nuri-bench-wallpaper.png");
+    if !path.exists() {
+        let blobs = [
+            (400.0, 300.0, [230, 90, 40]),
+            (1300.0, 700.0, [40, 180, 120]),
+            (1600.0, 200.0, [200, 60, 180]),
+        ];
+        RgbImage::from_fn(1920, 1080, |x, y| {
+            let (fx, fy) = (x as f32, y as f32);
+            for (bx, by, color) in blobs {
+                if (fx - bx).powi(2) + (fy - by).powi(2) < 150.0 * 150.0 {
+                    return Rgb(color);
+                }
+            }
+            let t = fy / 1080.0;
+            Rgb([
+                (20.0 + 120.0 * t) as u8,
+                (30.0 + 60.0 * t + 20.0 * (fx / 1920.0)) as u8,
+                (90.0 - 50.0 * t) as u8,
+            ])
+        })
+        .save(&path)
+        .expect("cannot write the benchmark wallpaper");
+    }
+    path
+}
+
+fn load_and_prepare(c: &mut Criterion) {
+    let path = wallpaper();
+    let options = LoadOptions::default();
+    c.bench_function("load_and_prepare", |b| {
+        b.iter(|| load_image_with(black_box(&path), &options).unwrap())
+    });
+}
+
+fn extract(c: &mut Criterion) {
+    let pixels = load_image_with(&wallpaper(), &LoadOptions::default())
+        .unwrap()
+        .pixels;
+    let mut group = c.benchmark_group("extract");
+    for k in [4, 8, 16, 32] {
+        group.bench_with_input(BenchmarkId::from_parameter(k), &k, |b, &k| {
+            b.iter(|| extract_colors_with_seed(black_box(&pixels), k, DEFAULT_SEED))
+        });
+    }
+    group.finish();
+}
+
+fn assign(c: &mut Criterion) {
+    let pixels = load_image_with(&wallpaper(), &LoadOptions::default())
+        .unwrap()
+        .pixels;
+    let colors = extract_colors_with_seed(&pixels, 16, DEFAULT_SEED);
+    let mut group = c.benchmark_group("assign");
+    group.bench_function("slots", |b| {
+        b.iter(|| assign_slots(black_box(&colors), ThemeMode::Dark))
+    });
+    group.bench_function("slots_and_contrast", |b| {
+        b.iter(|| {
+            let mut palette = assign_slots(black_box(&colors), ThemeMode::Dark);
+            enforce_contrast(&mut palette, DEFAULT_ACCENT_CONTRAST);
+            palette
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, load_and_prepare, extract, assign);
+criterion_main!(benches);
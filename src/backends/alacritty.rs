@@ -0,0 +1,204 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+
+use crate::pipeline::assign::AnsiPalette;
+
+use super::{config_home, ThemeBackend, GENERATED_MARKER};
+
+/// ANSI color names in slot order, shared by the `normal` and `bright` tables.
+const COLOR_NAMES: [&str; 8] = [
+    "black", "red", "green", "yellow", "blue", "magenta", "cyan", "white",
+];
+
+/// Alacritty terminal theme backend (TOML format).
+pub struct AlacrittyBackend;
+
+impl ThemeBackend for AlacrittyBackend {
+    fn name(&self) -> &str {
+        "Alacritty"
+    }
+
+    fn serialize(&self, palette: &AnsiPalette, _theme_name: &str) -> String {
+        let p = palette;
+        let mut out = String::new();
+
+        out.push_str(&format!("# {GENERATED_MARKER}\n\n"));
+        out.push_str("[colors.primary]\n");
+        out.push_str(&format!("background = \"{}\"\n", p.background.to_hex()));
+        out.push_str(&format!("foreground = \"{}\"\n", p.foreground.to_hex()));
+        out.push_str(&format!(
+            "dim_foreground = \"{}\"\n",
+            p.dim_foreground().to_hex()
+        ));
+        out.push('\n');
+
+        out.push_str("[colors.cursor]\n");
+        out.push_str(&format!("text = \"{}\"\n", p.cursor_text.to_hex()));
+        out.push_str(&format!("cursor = \"{}\"\n", p.cursor_color.to_hex()));
+        out.push('\n');
+
+        out.push_str("[colors.selection]\n");
+        out.push_str(&format!("text = \"{}\"\n", p.selection_fg.to_hex()));
+        out.push_str(&format!("background = \"{}\"\n", p.selection_bg.to_hex()));
+        out.push('\n');
+
+        out.push_str("[colors.normal]\n");
+        for (name, color) in COLOR_NAMES.iter().zip(&p.slots[0..8]) {
+            out.push_str(&format!("{} = \"{}\"\n", name, color.to_hex()));
+        }
+        out.push('\n');
+
+        out.push_str("[colors.bright]\n");
+        for (name, color) in COLOR_NAMES.iter().zip(&p.slots[8..16]) {
+            out.push_str(&format!("{} = \"{}\"\n", name, color.to_hex()));
+        }
+        out.push('\n');
+
+        out.push_str("[colors.dim]\n");
+        for (name, color) in COLOR_NAMES.iter().zip(p.dim_colors()) {
+            out.push_str(&format!("{} = \"{}\"\n", name, color.to_hex()));
+        }
+
+        out
+    }
+
+    fn install_path(&self, theme_name: &str) -> crate::error::Result<PathBuf> {
+        Ok(themes_dir()?.join(format!("{}.toml", theme_name)))
+    }
+
+    fn extension(&self) -> &str {
+        ".toml"
+    }
+}
+
+/// Resolve the Alacritty themes directory.
+fn themes_dir() -> Result<PathBuf> {
+    Ok(config_home().join("alacritty").join("themes"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::ThemeMode;
+    use crate::color::Color;
+    use crate::pipeline::assign::assign_slots;
+    use crate::pipeline::extract::ExtractedColor;
+    use palette::Oklch;
+
+    fn make_extracted(l: f32, chroma: f32, hue: f32, weight: f32) -> ExtractedColor {
+        ExtractedColor {
+            color: Color::from_oklch(Oklch::new(l, chroma, hue)),
+            weight,
+        }
+    }
+
+    fn test_palette() -> AnsiPalette {
+        let colors = vec![
+            make_extracted(0.60, 0.20, 25.0, 0.12),
+            make_extracted(0.60, 0.20, 145.0, 0.12),
+            make_extracted(0.70, 0.20, 90.0, 0.12),
+            make_extracted(0.55, 0.20, 260.0, 0.12),
+            make_extracted(0.60, 0.20, 325.0, 0.12),
+            make_extracted(0.65, 0.20, 195.0, 0.10),
+            make_extracted(0.10, 0.01, 0.0, 0.15),
+            make_extracted(0.95, 0.01, 0.0, 0.15),
+        ];
+        assign_slots(&colors, ThemeMode::Dark)
+    }
+
+    #[test]
+    fn all_tables_present_in_order() {
+        let output = AlacrittyBackend.serialize(&test_palette(), "test");
+        let tables: Vec<&str> = output.lines().filter(|l| l.starts_with('[')).collect();
+        assert_eq!(
+            tables,
+            [
+                "[colors.primary]",
+                "[colors.cursor]",
+                "[colors.selection]",
+                "[colors.normal]",
+                "[colors.bright]",
+                "[colors.dim]",
+            ]
+        );
+    }
+
+    #[test]
+    fn dim_table_holds_dim_variants() {
+        let palette = test_palette();
+        let output = AlacrittyBackend.serialize(&palette, "test");
+        let (_, dim) = output.split_once("[colors.dim]").unwrap();
+        for (name, color) in COLOR_NAMES.iter().zip(palette.dim_colors()) {
+            assert!(dim.contains(&format!("{name} = \"{}\"", color.to_hex())));
+        }
+        let dim_fg = palette.dim_foreground().to_hex();
+        assert!(output.contains(&format!("dim_foreground = \"{dim_fg}\"")));
+    }
+
+    #[test]
+    fn normal_and_bright_map_to_slots() {
+        let palette = test_palette();
+        let output = AlacrittyBackend.serialize(&palette, "test");
+        let (normal, bright) = output.split_once("[colors.bright]").unwrap();
+
+        for (i, name) in COLOR_NAMES.iter().enumerate() {
+            let normal_line = format!("{} = \"{}\"", name, palette.slots[i].to_hex());
+            let bright_line = format!("{} = \"{}\"", name, palette.slots[i + 8].to_hex());
+            assert!(normal.contains(&normal_line), "missing '{normal_line}'");
+            assert!(bright.contains(&bright_line), "missing '{bright_line}'");
+        }
+    }
+
+    #[test]
+    fn hex_values_are_lowercase_and_quoted() {
+        let output = AlacrittyBackend.serialize(&test_palette(), "test");
+
+        let value_re = regex::Regex::new(r##"^\w+ = "#[0-9a-f]{6}"$"##).unwrap();
+        for line in output.lines() {
+            if line.is_empty() || line.starts_with('[') || line.starts_with("# ") {
+                continue;
+            }
+            assert!(value_re.is_match(line), "invalid value line: '{line}'");
+        }
+    }
+
+    #[test]
+    fn write_to_creates_file() {
+        let backend = AlacrittyBackend;
+        let palette = test_palette();
+        let dir = std::env::temp_dir().join("nuri-test-alacritty-backend");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("test-theme.toml");
+
+        backend.write_to(&palette, "test-theme", &path).unwrap();
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(content, backend.serialize(&palette, "test-theme"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn install_creates_correct_path() {
+        let temp_dir = std::env::temp_dir().join("nuri-test-alacritty-install");
+        std::env::set_var("XDG_CONFIG_HOME", &temp_dir);
+
+        let backend = AlacrittyBackend;
+        let palette = test_palette();
+        let result = backend.install(&palette, "my-theme").unwrap();
+
+        let expected_path = temp_dir
+            .join("alacritty")
+            .join("themes")
+            .join("my-theme.toml");
+        assert_eq!(result, expected_path);
+        assert!(expected_path.exists());
+
+        let content = std::fs::read_to_string(&expected_path).unwrap();
+        assert_eq!(content, backend.serialize(&palette, "my-theme"));
+
+        std::fs::remove_dir_all(&temp_dir).unwrap();
+        std::env::remove_var("XDG_CONFIG_HOME");
+    }
+}
@@ -0,0 +1,185 @@
+use std::path::PathBuf;
+
+use crate::color::Color;
+use crate::error::Result;
+use crate::pipeline::assign::AnsiPalette;
+
+use super::{data_home, ThemeBackend, GENERATED_MARKER};
+
+/// base16 scheme backend (tinted-theming YAML), for use with base16 templates.
+pub struct Base16Backend;
+
+/// Base24 scheme backend: base16 plus two darker backgrounds and the bright
+/// accents as `base10`-`base17`.
+pub struct Base24Backend;
+
+impl ThemeBackend for Base16Backend {
+    fn name(&self) -> &str {
+        "Base16"
+    }
+
+    fn serialize(&self, palette: &AnsiPalette, theme_name: &str) -> String {
+        scheme(palette, theme_name, "base16", &base16(palette))
+    }
+
+    fn install_path(&self, theme_name: &str) -> Result<PathBuf> {
+        Ok(schemes_dir("base16").join(format!("{theme_name}.yaml")))
+    }
+
+    fn extension(&self) -> &str {
+        ".yaml"
+    }
+}
+
+impl ThemeBackend for Base24Backend {
+    fn name(&self) -> &str {
+        "Base24"
+    }
+
+    fn serialize(&self, palette: &AnsiPalette, theme_name: &str) -> String {
+        scheme(palette, theme_name, "base24", &base24(palette))
+    }
+
+    fn install_path(&self, theme_name: &str) -> Result<PathBuf> {
+        Ok(schemes_dir("base24").join(format!("{theme_name}.yaml")))
+    }
+
+    fn extension(&self) -> &str {
+        ".yaml"
+    }
+}
+
+/// The sixteen base16 colors, `base00`-`base0F`.
+///
+/// Backgrounds and accents come straight from the palette using the same
+/// mapping base16-shell uses for ANSI slots, so the scheme imports back to
+/// the same colors; the shades in between are interpolated, and the
+/// orange/brown accents base16 has but ANSI lacks are mixed from red and
+/// yellow.
+fn base16(palette: &AnsiPalette) -> Vec<Color> {
+    let p = palette;
+    let orange = p.slots[1].mix(p.slots[3], 0.5);
+    vec![
+        p.background,
+        p.background.mix(p.selection_bg, 0.5),
+        p.selection_bg,
+        p.slots[8],
+        p.slots[8].mix(p.foreground, 0.5),
+        p.foreground,
+        p.foreground.mix(p.slots[15], 0.5),
+        p.slots[15],
+        p.slots[1],
+        orange,
+        p.slots[3],
+        p.slots[2],
+        p.slots[6],
+        p.slots[4],
+        p.slots[5],
+        orange.adjust_lightness(-0.15).adjust_chroma(-0.04),
+    ]
+}
+
+/// The base16 colors followed by Base24's `base10`-`base17`.
+fn base24(palette: &AnsiPalette) -> Vec<Color> {
+    let p = palette;
+    // "Darker" backgrounds step away from the foreground, so they get lighter
+    // on light themes
+    let step = if is_light(p) { 0.04 } else { -0.04 };
+    let mut colors = base16(p);
+    colors.extend([
+        p.background.adjust_lightness(step),
+        p.background.adjust_lightness(2.0 * step),
+        p.slots[9],
+        p.slots[11],
+        p.slots[10],
+        p.slots[14],
+        p.slots[12],
+        p.slots[13],
+    ]);
+    colors
+}
+
+/// Render a tinted-theming scheme file.
+fn scheme(palette: &AnsiPalette, theme_name: &str, system: &str, colors: &[Color]) -> String {
+    let variant = if is_light(palette) { "light" } else { "dark" };
+    let mut out = String::new();
+    out.push_str(&format!("# {GENERATED_MARKER}\n"));
+    out.push_str(&format!("system: \"{system}\"\n"));
+    out.push_str(&format!("name: \"{}\"\n", theme_name.replace('"', "'")));
+    out.push_str("author: \"nuri\"\n");
+    out.push_str(&format!("variant: \"{variant}\"\n"));
+    out.push_str("palette:\n");
+    for (i, color) in colors.iter().enumerate() {
+        out.push_str(&format!("  base{i:02X}: \"{}\"\n", color.to_hex()));
+    }
+    out
+}
+
+fn is_light(palette: &AnsiPalette) -> bool {
+    palette.background.relative_luminance() > palette.foreground.relative_luminance()
+}
+
+/// Where tinty looks for custom schemes of the given system.
+fn schemes_dir(system: &str) -> PathBuf {
+    data_home()
+        .join("tinted-theming")
+        .join("tinty")
+        .join("custom-schemes")
+        .join(system)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::ThemeMode;
+    use crate::import::{parse_palette, ThemeFormat};
+    use crate::pipeline::assign::assign_slots;
+    use crate::pipeline::extract::ExtractedColor;
+    use palette::Oklch;
+
+    fn test_palette(mode: ThemeMode) -> AnsiPalette {
+        let colors: Vec<ExtractedColor> = [25.0, 145.0, 90.0, 250.0, 320.0, 195.0]
+            .into_iter()
+            .map(|hue| ExtractedColor {
+                color: Color::from_oklch(Oklch::new(0.65, 0.15, hue)),
+                weight: 0.15,
+            })
+            .collect();
+        assign_slots(&colors, mode)
+    }
+
+    #[test]
+    fn base16_scheme_imports_back() {
+        let p = test_palette(ThemeMode::Dark);
+        let out = Base16Backend.serialize(&p, "sunset");
+        assert!(out.contains("system: \"base16\""));
+        assert!(out.contains("name: \"sunset\""));
+        assert!(out.contains("variant: \"dark\""));
+        assert!(out.contains("  base0F: \"#"));
+        assert!(!out.contains("base10"));
+
+        let imported = parse_palette(&out, ThemeFormat::Base16).unwrap();
+        assert_eq!(imported.background, p.background);
+        assert_eq!(imported.foreground, p.foreground);
+        assert_eq!(imported.selection_bg, p.selection_bg);
+        for i in [1, 2, 3, 4, 5, 6, 8, 15] {
+            assert_eq!(imported.slots[i], p.slots[i], "slot {i}");
+        }
+    }
+
+    #[test]
+    fn base24_adds_backgrounds_and_brights() {
+        let p = test_palette(ThemeMode::Light);
+        let out = Base24Backend.serialize(&p, "sunset");
+        assert!(out.contains("system: \"base24\""));
+        assert!(out.contains("variant: \"light\""));
+        assert_eq!(out.matches("  base").count(), 24);
+        assert!(out.contains(&format!("  base12: \"{}\"", p.slots[9].to_hex())));
+        assert!(out.contains(&format!("  base17: \"{}\"", p.slots[13].to_hex())));
+
+        // Extra backgrounds move away from the foreground
+        let colors = base24(&p);
+        assert!(colors[0x11].relative_luminance() >= colors[0x10].relative_luminance());
+        assert!(colors[0x10].relative_luminance() >= p.background.relative_luminance());
+    }
+}
@@ -0,0 +1,106 @@
+use std::path::PathBuf;
+
+use crate::color::Color;
+use crate::error::Result;
+use crate::pipeline::assign::AnsiPalette;
+
+use super::{config_home, ThemeBackend, GENERATED_MARKER};
+
+/// btop system monitor theme backend (`.theme`).
+pub struct BtopBackend;
+
+impl ThemeBackend for BtopBackend {
+    fn name(&self) -> &str {
+        "btop"
+    }
+
+    fn serialize(&self, palette: &AnsiPalette, _theme_name: &str) -> String {
+        let p = palette;
+        let keys = [
+            ("main_bg", p.background),
+            ("main_fg", p.foreground),
+            ("title", p.foreground),
+            ("hi_fg", p.slots[4]),
+            ("selected_bg", p.selection_bg),
+            ("selected_fg", p.selection_fg),
+            ("inactive_fg", p.slots[8]),
+            ("graph_text", p.foreground),
+            ("meter_bg", p.selection_bg),
+            ("proc_misc", p.slots[6]),
+            ("cpu_box", p.slots[8]),
+            ("mem_box", p.slots[8]),
+            ("net_box", p.slots[8]),
+            ("proc_box", p.slots[8]),
+            ("div_line", p.slots[8]),
+        ];
+        let gradients = [
+            ("temp", p.slots[1]),
+            ("cpu", p.slots[2]),
+            ("free", p.slots[6]),
+            ("cached", p.slots[4]),
+            ("available", p.slots[3]),
+            ("used", p.slots[1]),
+            ("download", p.slots[4]),
+            ("upload", p.slots[5]),
+            ("process", p.slots[2]),
+        ];
+
+        let mut out = String::new();
+        out.push_str(&format!("# {GENERATED_MARKER}\n"));
+        for (key, color) in keys {
+            push_key(&mut out, key, color);
+        }
+        for (name, accent) in gradients {
+            let [start, mid, end] = ramp(p, accent);
+            push_key(&mut out, &format!("{name}_start"), start);
+            push_key(&mut out, &format!("{name}_mid"), mid);
+            push_key(&mut out, &format!("{name}_end"), end);
+        }
+        out
+    }
+
+    fn install_path(&self, theme_name: &str) -> Result<PathBuf> {
+        Ok(config_home()
+            .join("btop")
+            .join("themes")
+            .join(format!("{theme_name}.theme")))
+    }
+
+    fn extension(&self) -> &str {
+        ".theme"
+    }
+}
+
+fn push_key(out: &mut String, key: &str, color: Color) {
+    out.push_str(&format!("theme[{key}]=\"{}\"\n", color.to_hex()));
+}
+
+/// A gradient through `accent`: low values sit close to the background and
+/// high values stand out from it, on dark and light themes alike.
+fn ramp(palette: &AnsiPalette, accent: Color) -> [Color; 3] {
+    [
+        accent.mix(palette.background, 0.45),
+        accent,
+        accent.mix(palette.foreground, 0.35),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backends::{light_test_palette, test_palette};
+
+    #[test]
+    fn gradients_ramp_away_from_background() {
+        for (mode, p) in [("dark", test_palette()), ("light", light_test_palette())] {
+            let output = BtopBackend.serialize(&p, "test");
+            assert!(output.contains(&format!("theme[main_bg]=\"{}\"\n", p.background.to_hex())));
+            assert!(output.contains(&format!("theme[cpu_mid]=\"{}\"\n", p.slots[2].to_hex())));
+
+            let [start, _, end] = ramp(&p, p.slots[2]);
+            let contrast = |c: Color| Color::contrast_ratio(&c, &p.background);
+            assert!(contrast(start) < contrast(p.slots[2]), "{mode}");
+            assert!(contrast(end) > contrast(p.slots[2]), "{mode}");
+        }
+    }
+}
@@ -0,0 +1,101 @@
+use std::path::PathBuf;
+
+use crate::color::Color;
+use crate::error::Result;
+use crate::pipeline::assign::AnsiPalette;
+
+use super::{config_home, ThemeBackend, GENERATED_MARKER};
+
+/// git-delta backend: a `[delta "nuri"]` feature block, to `[include]` from
+/// the git config and enable with `features = nuri`.
+pub struct DeltaBackend;
+
+impl ThemeBackend for DeltaBackend {
+    fn name(&self) -> &str {
+        "delta"
+    }
+
+    fn serialize(&self, palette: &AnsiPalette, _theme_name: &str) -> String {
+        let p = palette;
+        let dark = p.background.relative_luminance() < p.foreground.relative_luminance();
+        let (red, green, blue) = (p.slots[1], p.slots[2], p.slots[4]);
+        // Changed lines get a tint of red/green over the background, changed
+        // words a stronger one, so syntax highlighting stays readable on top
+        let tint = |color: Color, amount| quote(p.background.mix(color, amount));
+        let dim = quote(p.slots[8]);
+        let styles = [
+            ("dark", dark.to_string()),
+            ("plus-style", format!("syntax {}", tint(green, 0.2))),
+            ("plus-emph-style", format!("syntax {}", tint(green, 0.4))),
+            ("minus-style", format!("syntax {}", tint(red, 0.2))),
+            ("minus-emph-style", format!("syntax {}", tint(red, 0.4))),
+            ("line-numbers-plus-style", quote(green)),
+            ("line-numbers-minus-style", quote(red)),
+            ("line-numbers-zero-style", dim.clone()),
+            ("line-numbers-left-style", dim.clone()),
+            ("line-numbers-right-style", dim),
+            ("file-style", format!("{} bold", quote(blue))),
+            ("file-decoration-style", format!("{} ul", quote(blue))),
+            ("hunk-header-style", "file line-number syntax".to_string()),
+            (
+                "hunk-header-decoration-style",
+                format!("{} box", quote(blue)),
+            ),
+            (
+                "commit-decoration-style",
+                format!("{} box", quote(p.slots[3])),
+            ),
+        ];
+
+        let mut out = String::new();
+        out.push_str(&format!("# {GENERATED_MARKER}\n"));
+        out.push_str("[delta \"nuri\"]\n");
+        for (key, value) in styles {
+            out.push_str(&format!("    {key} = {value}\n"));
+        }
+        out
+    }
+
+    fn install_path(&self, _theme_name: &str) -> Result<PathBuf> {
+        Ok(config_home().join("git").join("nuri-delta.gitconfig"))
+    }
+
+    fn fixed_path(&self) -> bool {
+        true
+    }
+
+    fn extension(&self) -> &str {
+        ".gitconfig"
+    }
+}
+
+/// A color as delta reads it inside a style string.
+fn quote(color: Color) -> String {
+    format!("\"{}\"", color.to_hex())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backends::light_test_palette;
+
+    #[test]
+    fn writes_feature_block() {
+        let p = light_test_palette();
+        let output = DeltaBackend.serialize(&p, "test");
+        assert!(output.contains("[delta \"nuri\"]\n"));
+        assert!(output.contains("    dark = false\n"));
+        assert!(output.contains("    line-numbers-minus-style = \"#cc241d\"\n"));
+        assert!(output.contains(&format!(
+            "    line-numbers-plus-style = \"{}\"\n",
+            p.slots[2].to_hex()
+        )));
+        let plus = output
+            .lines()
+            .find_map(|l| l.strip_prefix("    plus-style = syntax \""))
+            .unwrap();
+        let tint = Color::from_hex(plus.trim_end_matches('"')).unwrap();
+        assert_ne!(tint, p.background);
+        assert_ne!(tint, p.slots[2]);
+    }
+}
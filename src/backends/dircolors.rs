@@ -0,0 +1,176 @@
+use std::path::PathBuf;
+
+use crate::color::Color;
+use crate::error::Result;
+use crate::pipeline::assign::AnsiPalette;
+
+use super::{config_home, ThemeBackend, GENERATED_MARKER};
+
+/// File extensions colored by category, with the ANSI slot each uses.
+const EXTENSIONS: [(usize, &[&str]); 5] = [
+    // Archives
+    (
+        1,
+        &[
+            "tar", "tgz", "gz", "bz2", "xz", "zst", "zip", "7z", "rar", "deb", "rpm",
+        ],
+    ),
+    // Images
+    (
+        5,
+        &[
+            "png", "jpg", "jpeg", "gif", "webp", "bmp", "svg", "tif", "tiff",
+        ],
+    ),
+    // Audio and video
+    (
+        13,
+        &[
+            "mp3", "flac", "ogg", "wav", "opus", "mp4", "mkv", "webm", "mov", "avi",
+        ],
+    ),
+    // Documents
+    (3, &["pdf", "md", "txt", "doc", "docx", "odt", "epub"]),
+    // Backups and temporary files
+    (8, &["bak", "tmp", "swp", "orig", "log"]),
+];
+
+/// `ls` colors backend: a `dircolors` database coloring file types and
+/// extension categories with the palette's accents, plus an `EZA_COLORS`
+/// export installed alongside it.
+pub struct DircolorsBackend;
+
+impl ThemeBackend for DircolorsBackend {
+    fn name(&self) -> &str {
+        "dircolors"
+    }
+
+    fn serialize(&self, palette: &AnsiPalette, _theme_name: &str) -> String {
+        let mut out = String::new();
+        out.push_str(&format!("# {GENERATED_MARKER}\n"));
+        out.push_str("# eval \"$(dircolors ~/.config/nuri/dircolors)\"\n\n");
+        for (keyword, _, code) in file_types(palette) {
+            out.push_str(&format!("{keyword} {code}\n"));
+        }
+        out.push('\n');
+        for (ext, code) in extensions(palette) {
+            out.push_str(&format!(".{ext} {code}\n"));
+        }
+        out
+    }
+
+    fn install_path(&self, _theme_name: &str) -> Result<PathBuf> {
+        Ok(config_home().join("nuri").join("dircolors"))
+    }
+
+    fn fixed_path(&self) -> bool {
+        true
+    }
+
+    fn extension(&self) -> &str {
+        ""
+    }
+
+    fn companions(&self, palette: &AnsiPalette, _theme_name: &str) -> Vec<(String, String)> {
+        let mut out = String::new();
+        out.push_str(&format!("# {GENERATED_MARKER}\n"));
+        out.push_str(&format!("export EZA_COLORS='{}'\n", eza_colors(palette)));
+        vec![("eza-colors.sh".to_string(), out)]
+    }
+
+    fn companion_names(&self) -> Vec<&str> {
+        vec!["eza-colors.sh"]
+    }
+}
+
+/// File types as `dircolors` keyword, LS_COLORS key and SGR code.
+pub(super) fn file_types(palette: &AnsiPalette) -> [(&'static str, &'static str, String); 9] {
+    let p = palette;
+    [
+        ("DIR", "di", bold(p.slots[4])),
+        ("LINK", "ln", fg(p.slots[6])),
+        ("EXEC", "ex", bold(p.slots[2])),
+        ("FIFO", "pi", fg(p.slots[3])),
+        ("SOCK", "so", fg(p.slots[5])),
+        ("BLK", "bd", bold(p.slots[3])),
+        ("CHR", "cd", bold(p.slots[3])),
+        ("ORPHAN", "or", bold(p.slots[1])),
+        ("MISSING", "mi", bold(p.slots[1])),
+    ]
+}
+
+pub(super) fn extensions(palette: &AnsiPalette) -> Vec<(&'static str, String)> {
+    EXTENSIONS
+        .iter()
+        .flat_map(|&(slot, exts)| exts.iter().map(move |&ext| (ext, fg(palette.slots[slot]))))
+        .collect()
+}
+
+/// The database as an `EZA_COLORS` value: LS_COLORS keys for the same file
+/// types and extensions, plus eza's own permission and size columns.
+fn eza_colors(palette: &AnsiPalette) -> String {
+    let p = palette;
+    let mut entries: Vec<String> = file_types(p)
+        .into_iter()
+        .map(|(_, key, code)| format!("{key}={code}"))
+        .collect();
+    entries.extend(
+        extensions(p)
+            .into_iter()
+            .map(|(ext, code)| format!("*.{ext}={code}")),
+    );
+    entries.extend([
+        format!("ur={}", fg(p.slots[3])),
+        format!("uw={}", fg(p.slots[1])),
+        format!("ux={}", fg(p.slots[2])),
+        format!("sn={}", fg(p.slots[2])),
+        format!("sb={}", fg(p.slots[2])),
+        format!("da={}", fg(p.slots[8])),
+    ]);
+    entries.join(":")
+}
+
+/// Truecolor foreground SGR code.
+pub(super) fn fg(color: Color) -> String {
+    format!("38;2;{};{};{}", color.r, color.g, color.b)
+}
+
+fn bold(color: Color) -> String {
+    format!("01;{}", fg(color))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backends::test_palette;
+
+    #[test]
+    fn database_colors_types_and_extensions() {
+        let p = test_palette();
+        let output = DircolorsBackend.serialize(&p, "test");
+        assert!(output.contains("DIR 01;38;2;69;133;136\n"));
+        let blue = p.slots[4];
+        assert!(output.contains(&format!("DIR 01;38;2;{};{};{}\n", blue.r, blue.g, blue.b)));
+        let red = p.slots[1];
+        assert!(output.contains(&format!(".zip 38;2;{};{};{}\n", red.r, red.g, red.b)));
+        // Every entry is a keyword or extension followed by a valid SGR code
+        for line in output
+            .lines()
+            .filter(|l| !l.is_empty() && !l.starts_with('#'))
+        {
+            let (_, code) = line.split_once(' ').unwrap();
+            assert!(code.split(';').all(|n| n.parse::<u8>().is_ok()), "{line}");
+        }
+    }
+
+    #[test]
+    fn eza_colors_use_ls_keys() {
+        let p = test_palette();
+        let colors = eza_colors(&p);
+        assert!(colors.starts_with(&format!("di={}:", bold(p.slots[4]))));
+        assert!(colors.contains(&format!(":*.png={}:", fg(p.slots[5]))));
+        assert!(colors.contains(":ux="));
+        let (_, script) = &DircolorsBackend.companions(&p, "test")[0];
+        assert!(script.contains(&format!("export EZA_COLORS='{colors}'\n")));
+    }
+}
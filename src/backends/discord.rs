@@ -0,0 +1,98 @@
+use std::path::PathBuf;
+
+use crate::error::Result;
+use crate::pipeline::assign::AnsiPalette;
+
+use super::{ConfigDir, ThemeBackend, GENERATED_MARKER};
+
+/// BetterDiscord theme backend (`.theme.css`): Discord's color variables set
+/// from the palette, for BetterDiscord or any client mod that loads its themes.
+pub struct DiscordBackend;
+
+impl ThemeBackend for DiscordBackend {
+    fn name(&self) -> &str {
+        "Discord"
+    }
+
+    fn serialize(&self, palette: &AnsiPalette, theme_name: &str) -> String {
+        let p = palette;
+        let (bg, fg) = (p.background, p.foreground);
+        let accent = p.slots[4];
+        let variables = [
+            ("background-primary", bg),
+            ("background-secondary", bg.mix(fg, 0.05)),
+            ("background-secondary-alt", bg.mix(fg, 0.08)),
+            ("background-tertiary", bg.mix(fg, 0.03)),
+            ("background-floating", bg.mix(fg, 0.03)),
+            ("background-accent", accent),
+            ("background-modifier-hover", bg.mix(fg, 0.1)),
+            ("background-modifier-active", bg.mix(fg, 0.14)),
+            ("background-modifier-selected", p.selection_bg),
+            ("channeltextarea-background", bg.mix(fg, 0.08)),
+            ("text-normal", fg),
+            ("text-muted", p.slots[8]),
+            ("text-link", accent),
+            ("header-primary", fg),
+            ("header-secondary", fg.mix(p.slots[8], 0.5)),
+            ("interactive-normal", fg.mix(p.slots[8], 0.5)),
+            ("interactive-hover", fg),
+            ("interactive-active", fg),
+            ("interactive-muted", p.slots[8]),
+            ("channels-default", p.slots[8]),
+            ("brand-experiment", accent),
+            ("brand-500", accent),
+            ("status-positive", p.slots[2]),
+            ("status-warning", p.slots[3]),
+            ("status-danger", p.slots[1]),
+            ("scrollbar-auto-thumb", bg.mix(fg, 0.2)),
+            ("scrollbar-auto-track", bg.mix(fg, 0.05)),
+        ];
+
+        // BetterDiscord only loads files whose first line opens the META block
+        let mut out = String::new();
+        out.push_str("/**\n");
+        out.push_str(&format!(" * {GENERATED_MARKER}\n"));
+        out.push_str(&format!(" * @name {theme_name}\n"));
+        out.push_str(" * @author nuri\n");
+        out.push_str(" * @description Colors generated from a wallpaper.\n");
+        out.push_str(&format!(" * @version {}\n", env!("CARGO_PKG_VERSION")));
+        out.push_str(" */\n\n");
+        out.push_str(".theme-dark,\n.theme-light {\n");
+        for (name, color) in variables {
+            out.push_str(&format!("  --{name}: {};\n", color.to_hex()));
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    fn install_path(&self, theme_name: &str) -> Result<PathBuf> {
+        Ok(ConfigDir::Native
+            .resolve()
+            .join("BetterDiscord")
+            .join("themes")
+            .join(format!("{theme_name}.theme.css")))
+    }
+
+    fn extension(&self) -> &str {
+        ".theme.css"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backends::test_palette;
+
+    #[test]
+    fn meta_block_opens_the_file() {
+        let p = test_palette();
+        let output = DiscordBackend.serialize(&p, "sunset");
+        assert!(output.starts_with("/**\n"));
+        assert!(output.contains(" * @name sunset\n"));
+        assert!(output.contains(&format!(
+            "  --background-primary: {};\n",
+            p.background.to_hex()
+        )));
+        assert!(output.contains(&format!("  --brand-experiment: {};\n", p.slots[4].to_hex())));
+    }
+}
@@ -0,0 +1,115 @@
+use std::path::PathBuf;
+
+use crate::config::SLOT_NAMES;
+use crate::error::Result;
+use crate::pipeline::assign::AnsiPalette;
+
+use super::{config_home, ThemeBackend, GENERATED_MARKER};
+
+/// Shell environment backend: exports every palette color as a `NURI_*`
+/// variable for prompts and scripts. The theme is a POSIX `colors.sh` for
+/// bash and zsh; `colors.fish` is installed alongside it.
+pub struct EnvBackend;
+
+impl ThemeBackend for EnvBackend {
+    fn name(&self) -> &str {
+        "env"
+    }
+
+    fn serialize(&self, palette: &AnsiPalette, theme_name: &str) -> String {
+        let mut out = String::new();
+        out.push_str(&format!("# {GENERATED_MARKER}\n"));
+        for (name, value) in variables(palette, theme_name) {
+            out.push_str(&format!(
+                "export {name}='{}'\n",
+                value.replace('\'', "'\\''")
+            ));
+        }
+        out
+    }
+
+    fn install_path(&self, _theme_name: &str) -> Result<PathBuf> {
+        Ok(config_home().join("nuri").join("colors.sh"))
+    }
+
+    fn fixed_path(&self) -> bool {
+        true
+    }
+
+    fn extension(&self) -> &str {
+        ".sh"
+    }
+
+    fn companions(&self, palette: &AnsiPalette, theme_name: &str) -> Vec<(String, String)> {
+        let mut out = String::new();
+        out.push_str(&format!("# {GENERATED_MARKER}\n"));
+        for (name, value) in variables(palette, theme_name) {
+            let value = value.replace('\\', "\\\\").replace('\'', "\\'");
+            out.push_str(&format!("set -gx {name} '{value}'\n"));
+        }
+        vec![("colors.fish".to_string(), out)]
+    }
+
+    fn companion_names(&self) -> Vec<&str> {
+        vec!["colors.fish"]
+    }
+}
+
+/// Variable names and values: the theme name, the special colors, then
+/// `NURI_COLOR0`-`15` and the same slots by name (`NURI_BRIGHT_RED`, ...).
+fn variables(palette: &AnsiPalette, theme_name: &str) -> Vec<(String, String)> {
+    let p = palette;
+    let mut vars = vec![("NURI_THEME".to_string(), theme_name.to_string())];
+    for (name, color) in [
+        ("BG", p.background),
+        ("FG", p.foreground),
+        ("CURSOR", p.cursor_color),
+        ("CURSOR_TEXT", p.cursor_text),
+        ("SELECTION_BG", p.selection_bg),
+        ("SELECTION_FG", p.selection_fg),
+    ] {
+        vars.push((format!("NURI_{name}"), color.to_hex()));
+    }
+    for (i, color) in p.slots.iter().enumerate() {
+        vars.push((format!("NURI_COLOR{i}"), color.to_hex()));
+    }
+    for (name, color) in SLOT_NAMES.iter().zip(p.slots) {
+        let name = name.to_uppercase().replace('-', "_");
+        vars.push((format!("NURI_{name}"), color.to_hex()));
+    }
+    vars
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backends::test_palette;
+
+    #[test]
+    fn exports_colors_for_posix_shells() {
+        let p = test_palette();
+        let output = EnvBackend.serialize(&p, "it's");
+        assert!(output.contains("export NURI_THEME='it'\\''s'\n"));
+        assert!(output.contains("export NURI_BRIGHT_RED='#fb4934'\n"));
+        assert!(output.contains(&format!("export NURI_BG='{}'\n", p.background.to_hex())));
+        assert!(output.contains(&format!("export NURI_COLOR15='{}'\n", p.slots[15].to_hex())));
+        assert!(output.contains(&format!(
+            "export NURI_BRIGHT_RED='{}'\n",
+            p.slots[9].to_hex()
+        )));
+    }
+
+    #[test]
+    fn fish_companion_sets_the_same_variables() {
+        let p = test_palette();
+        let posix = EnvBackend.serialize(&p, "it's");
+        let (name, fish) = &EnvBackend.companions(&p, "it's")[0];
+        assert_eq!(name, "colors.fish");
+        assert!(fish.contains("set -gx NURI_THEME 'it\\'s'\n"));
+        assert!(fish.contains(&format!("set -gx NURI_FG '{}'\n", p.foreground.to_hex())));
+        assert_eq!(
+            posix.matches("export ").count(),
+            fish.matches("set -gx ").count()
+        );
+    }
+}
@@ -0,0 +1,369 @@
+use std::path::PathBuf;
+
+use crate::color::Color;
+use crate::error::Result;
+use crate::pipeline::assign::AnsiPalette;
+
+use super::dircolors::{extensions, fg, file_types};
+use super::{config_home, ThemeBackend, GENERATED_MARKER};
+
+/// Body of the ranger colorscheme. ranger draws with curses color numbers, so
+/// roles map to ANSI slots and take their colors from the terminal theme.
+const RANGER_SCHEME: &str = "\
+from ranger.gui.color import bold, default_colors, normal, reverse
+from ranger.gui.colorscheme import ColorScheme
+
+
+class Nuri(ColorScheme):
+    progress_bar_color = 4
+
+    def use(self, context):
+        fg, bg, attr = default_colors
+
+        if context.reset:
+            return default_colors
+
+        elif context.in_browser:
+            attr = reverse if context.selected else normal
+            if context.empty or context.error:
+                fg = 1
+            if context.border:
+                fg = 8
+            if context.media:
+                fg = 5 if context.image else 13
+            if context.container:
+                fg = 1
+            if context.document:
+                fg = 3
+            if context.directory:
+                attr |= bold
+                fg = 4
+            elif context.executable and not any(
+                (context.media, context.container, context.fifo, context.socket)
+            ):
+                attr |= bold
+                fg = 2
+            if context.socket:
+                attr |= bold
+                fg = 5
+            if context.fifo or context.device:
+                fg = 3
+            if context.link:
+                fg = 6 if context.good else 1
+            if context.tag_marker and not context.selected:
+                attr |= bold
+                fg = 1
+            if not context.selected and (context.cut or context.copied):
+                attr |= bold
+                fg = 8
+            if context.main_column and context.marked:
+                attr |= bold
+                fg = 3
+            if context.inactive_pane:
+                fg = 8
+
+        elif context.in_titlebar:
+            attr |= bold
+            if context.hostname:
+                fg = 1 if context.bad else 2
+            elif context.directory:
+                fg = 4
+            elif context.tab and context.good:
+                bg = 4
+                fg = 0
+            elif context.link:
+                fg = 6
+
+        elif context.in_statusbar:
+            if context.permissions:
+                fg = 6 if context.good else 5
+            if context.marked:
+                attr |= bold | reverse
+                fg = 3
+            if context.message and context.bad:
+                attr |= bold
+                fg = 1
+            if context.loaded:
+                bg = self.progress_bar_color
+            if context.vcsinfo:
+                fg = 4
+
+        if context.text and context.highlight:
+            attr |= reverse
+
+        if context.in_taskview:
+            if context.title:
+                fg = 4
+            if context.selected:
+                attr |= reverse
+            if context.loaded:
+                bg = self.progress_bar_color if context.selected else 8
+
+        return fg, bg, attr
+";
+
+/// yazi flavor (`flavor.toml`), installed as the `nuri` flavor to select with
+/// `[flavor] dark = "nuri"` in `theme.toml`.
+pub struct YaziBackend;
+
+/// ranger colorscheme, selected with `set colorscheme nuri` in `rc.conf`.
+pub struct RangerBackend;
+
+/// lf file colors (`~/.config/lf/colors`), plus a `nuri.lfrc` of cursor and
+/// border formats to `source` from `lfrc`.
+pub struct LfBackend;
+
+impl ThemeBackend for YaziBackend {
+    fn name(&self) -> &str {
+        "yazi"
+    }
+
+    fn serialize(&self, palette: &AnsiPalette, _theme_name: &str) -> String {
+        let p = palette;
+        let (bg, fg) = (hex(p.background), hex(p.foreground));
+        let raised = hex(p.background.mix(p.foreground, 0.08));
+        let dim = hex(p.slots[8]);
+        let [red, green, yellow, blue, magenta, cyan] = [1, 2, 3, 4, 5, 6].map(|i| hex(p.slots[i]));
+        let (sel_bg, sel_fg) = (hex(p.selection_bg), hex(p.selection_fg));
+        let mode = |accent: &str| {
+            (
+                format!("{{ fg = {bg}, bg = {accent}, bold = true }}"),
+                format!("{{ fg = {accent}, bg = {raised} }}"),
+            )
+        };
+
+        let mut out = String::new();
+        out.push_str(&format!("# {GENERATED_MARKER}\n\n"));
+        out.push_str("[mgr]\n");
+        out.push_str(&format!("cwd = {{ fg = {cyan} }}\n"));
+        out.push_str(&format!("hovered = {{ fg = {sel_fg}, bg = {sel_bg} }}\n"));
+        out.push_str("preview_hovered = { underline = true }\n");
+        out.push_str(&format!(
+            "find_keyword = {{ fg = {yellow}, bold = true, underline = true }}\n"
+        ));
+        out.push_str(&format!(
+            "find_position = {{ fg = {magenta}, bold = true }}\n"
+        ));
+        for (marker, color) in [
+            ("copied", &green),
+            ("cut", &red),
+            ("marked", &cyan),
+            ("selected", &yellow),
+        ] {
+            out.push_str(&format!(
+                "marker_{marker} = {{ fg = {color}, bg = {color} }}\n"
+            ));
+        }
+        out.push_str(&format!("tab_active = {{ fg = {bg}, bg = {blue} }}\n"));
+        out.push_str(&format!("tab_inactive = {{ fg = {fg}, bg = {raised} }}\n"));
+        out.push_str(&format!("border_style = {{ fg = {dim} }}\n"));
+
+        out.push_str("\n[mode]\n");
+        for (name, accent) in [("normal", &blue), ("select", &green), ("unset", &magenta)] {
+            let (main, alt) = mode(accent);
+            out.push_str(&format!("{name}_main = {main}\n"));
+            out.push_str(&format!("{name}_alt = {alt}\n"));
+        }
+
+        out.push_str("\n[status]\n");
+        out.push_str(&format!(
+            "progress_normal = {{ fg = {blue}, bg = {raised} }}\n"
+        ));
+        out.push_str(&format!(
+            "progress_error = {{ fg = {red}, bg = {raised} }}\n"
+        ));
+        for (perm, color) in [
+            ("type", &blue),
+            ("read", &yellow),
+            ("write", &red),
+            ("exec", &green),
+            ("sep", &dim),
+        ] {
+            out.push_str(&format!("perm_{perm} = {{ fg = {color} }}\n"));
+        }
+
+        out.push_str("\n[filetype]\nrules = [\n");
+        for (rule, color) in [
+            ("mime = \"image/*\"", &magenta),
+            ("mime = \"{audio,video}/*\"", &hex(p.slots[13])),
+            (
+                "mime = \"application/{zip,gzip,x-tar,x-bzip2,x-7z-compressed,x-rar,x-xz,zstd}\"",
+                &red,
+            ),
+            ("mime = \"application/{pdf,epub+zip}\"", &yellow),
+            ("url = \"*\", is = \"orphan\"", &red),
+            ("url = \"*\", is = \"exec\"", &green),
+            ("url = \"*/\"", &blue),
+        ] {
+            out.push_str(&format!("  {{ {rule}, fg = {color} }},\n"));
+        }
+        out.push_str("]\n");
+        out
+    }
+
+    fn install_path(&self, _theme_name: &str) -> Result<PathBuf> {
+        Ok(config_home()
+            .join("yazi")
+            .join("flavors")
+            .join("nuri.yazi")
+            .join("flavor.toml"))
+    }
+
+    fn fixed_path(&self) -> bool {
+        true
+    }
+
+    fn extension(&self) -> &str {
+        ".toml"
+    }
+}
+
+impl ThemeBackend for RangerBackend {
+    fn name(&self) -> &str {
+        "ranger"
+    }
+
+    fn serialize(&self, _palette: &AnsiPalette, _theme_name: &str) -> String {
+        format!("# {GENERATED_MARKER}\n\n{RANGER_SCHEME}")
+    }
+
+    fn install_path(&self, _theme_name: &str) -> Result<PathBuf> {
+        Ok(config_home()
+            .join("ranger")
+            .join("colorschemes")
+            .join("nuri.py"))
+    }
+
+    fn fixed_path(&self) -> bool {
+        true
+    }
+
+    fn extension(&self) -> &str {
+        ".py"
+    }
+}
+
+impl ThemeBackend for LfBackend {
+    fn name(&self) -> &str {
+        "lf"
+    }
+
+    fn serialize(&self, palette: &AnsiPalette, _theme_name: &str) -> String {
+        let mut out = String::new();
+        out.push_str(&format!("# {GENERATED_MARKER}\n\n"));
+        for (_, key, code) in file_types(palette) {
+            out.push_str(&format!("{key} {code}\n"));
+        }
+        out.push('\n');
+        for (ext, code) in extensions(palette) {
+            out.push_str(&format!("*.{ext} {code}\n"));
+        }
+        out
+    }
+
+    fn install_path(&self, _theme_name: &str) -> Result<PathBuf> {
+        Ok(config_home().join("lf").join("colors"))
+    }
+
+    fn fixed_path(&self) -> bool {
+        true
+    }
+
+    fn extension(&self) -> &str {
+        ""
+    }
+
+    fn companions(&self, palette: &AnsiPalette, _theme_name: &str) -> Vec<(String, String)> {
+        let p = palette;
+        let raised = p.background.mix(p.foreground, 0.08);
+        let formats = [
+            (
+                "cursoractivefmt",
+                format!("{};{}", fg(p.selection_fg), bg(p.selection_bg)),
+            ),
+            (
+                "cursorparentfmt",
+                format!("{};{}", fg(p.foreground), bg(raised)),
+            ),
+            ("cursorpreviewfmt", "4".to_string()),
+            ("borderfmt", fg(p.slots[8])),
+        ];
+        let mut out = String::new();
+        out.push_str(&format!("# {GENERATED_MARKER}\n"));
+        for (option, code) in formats {
+            out.push_str(&format!("set {option} \"\\033[{code}m\"\n"));
+        }
+        vec![("nuri.lfrc".to_string(), out)]
+    }
+
+    fn companion_names(&self) -> Vec<&str> {
+        vec!["nuri.lfrc"]
+    }
+}
+
+fn hex(color: Color) -> String {
+    format!("\"{}\"", color.to_hex())
+}
+
+/// Truecolor background SGR code.
+fn bg(color: Color) -> String {
+    format!("48;2;{};{};{}", color.r, color.g, color.b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backends::test_palette;
+
+    #[test]
+    fn yazi_flavor_maps_selection_and_file_types() {
+        let p = test_palette();
+        let output = YaziBackend.serialize(&p, "test");
+        assert!(output.contains("  { url = \"*/\", fg = \"#458588\" },\n"));
+        assert!(output.contains(&format!(
+            "hovered = {{ fg = \"{}\", bg = \"{}\" }}\n",
+            p.selection_fg.to_hex(),
+            p.selection_bg.to_hex()
+        )));
+        assert!(output.contains(&format!(
+            "  {{ url = \"*/\", fg = \"{}\" }},\n",
+            p.slots[4].to_hex()
+        )));
+        assert!(output.contains(&format!(
+            "  {{ url = \"*\", is = \"exec\", fg = \"{}\" }},\n",
+            p.slots[2].to_hex()
+        )));
+        for section in ["[mgr]", "[mode]", "[status]", "[filetype]"] {
+            assert!(output.contains(section), "{section}");
+        }
+    }
+
+    #[test]
+    fn ranger_scheme_defines_the_nuri_class() {
+        let output = RangerBackend.serialize(&test_palette(), "test");
+        assert!(output.contains("class Nuri(ColorScheme):"));
+        // Tabs would break the indentation Python relies on
+        assert!(!output.contains('\t'));
+    }
+
+    #[test]
+    fn lf_colors_and_cursor_formats() {
+        let p = test_palette();
+        let output = LfBackend.serialize(&p, "test");
+        assert!(output.contains("di 01;38;2;69;133;136\n"));
+        let blue = p.slots[4];
+        assert!(output.contains(&format!("di 01;38;2;{};{};{}\n", blue.r, blue.g, blue.b)));
+        assert!(output.contains("*.zip 38;2;"));
+
+        let (name, lfrc) = &LfBackend.companions(&p, "test")[0];
+        assert_eq!(name, "nuri.lfrc");
+        let sel = p.selection_bg;
+        assert!(lfrc.contains(&format!(
+            "set cursoractivefmt \"\\033[{};48;2;{};{};{}m\"\n",
+            fg(p.selection_fg),
+            sel.r,
+            sel.g,
+            sel.b
+        )));
+    }
+}
@@ -2,57 +2,46 @@ use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result};
 
+use crate::config::Config;
 use crate::pipeline::assign::AnsiPalette;
 
-use super::ThemeBackend;
+use super::{config_home, Target, ThemeBackend, GENERATED_MARKER};
 
 /// Ghostty terminal theme backend.
 pub struct GhosttyBackend;
 
+/// Ghostty backend that also writes palette entries 16-255 (`--extended`).
+pub struct GhosttyExtendedBackend;
+
 impl ThemeBackend for GhosttyBackend {
     fn name(&self) -> &str {
         "Ghostty"
     }
 
     fn serialize(&self, palette: &AnsiPalette, _theme_name: &str) -> String {
-        let p = palette;
-        let mut out = String::new();
-
-        out.push_str(&format!("background = {}\n", p.background.to_hex()));
-        out.push_str(&format!("foreground = {}\n", p.foreground.to_hex()));
-        out.push_str(&format!("cursor-color = {}\n", p.cursor_color.to_hex()));
-        out.push_str(&format!("cursor-text = {}\n", p.cursor_text.to_hex()));
-        out.push_str(&format!(
-            "selection-background = {}\n",
-            p.selection_bg.to_hex()
-        ));
-        out.push_str(&format!(
-            "selection-foreground = {}\n",
-            p.selection_fg.to_hex()
-        ));
-
-        for (i, color) in p.slots.iter().enumerate() {
-            out.push_str(&format!("palette = {}={}\n", i, color.to_hex()));
-        }
+        serialize(palette, false)
+    }
+
+    fn install_path(&self, theme_name: &str) -> crate::error::Result<PathBuf> {
+        Ok(theme_path(theme_name)?)
+    }
 
-        out
+    fn extension(&self) -> &str {
+        ""
     }
+}
 
-    fn install(&self, palette: &AnsiPalette, theme_name: &str) -> Result<PathBuf> {
-        let dir = themes_dir()?;
-        std::fs::create_dir_all(&dir)
-            .with_context(|| format!("failed to create themes directory: {}", dir.display()))?;
+impl ThemeBackend for GhosttyExtendedBackend {
+    fn name(&self) -> &str {
+        "Ghostty"
+    }
 
-        let path = dir.join(theme_name);
-        self.write_to(palette, theme_name, &path)?;
-        Ok(path)
+    fn serialize(&self, palette: &AnsiPalette, _theme_name: &str) -> String {
+        serialize(palette, true)
     }
 
-    fn write_to(&self, palette: &AnsiPalette, theme_name: &str, path: &Path) -> Result<()> {
-        let content = self.serialize(palette, theme_name);
-        std::fs::write(path, content)
-            .with_context(|| format!("failed to write theme to {}", path.display()))?;
-        Ok(())
+    fn install_path(&self, theme_name: &str) -> crate::error::Result<PathBuf> {
+        GhosttyBackend.install_path(theme_name)
     }
 
     fn extension(&self) -> &str {
@@ -60,15 +49,40 @@ impl ThemeBackend for GhosttyBackend {
     }
 }
 
+/// Theme file text; `extended` adds palette entries 16-255.
+fn serialize(palette: &AnsiPalette, extended: bool) -> String {
+    let p = palette;
+    let mut out = String::new();
+
+    out.push_str(&format!("# {GENERATED_MARKER}\n"));
+    out.push_str(&format!("background = {}\n", p.background.to_hex()));
+    out.push_str(&format!("foreground = {}\n", p.foreground.to_hex()));
+    out.push_str(&format!("cursor-color = {}\n", p.cursor_color.to_hex()));
+    out.push_str(&format!("cursor-text = {}\n", p.cursor_text.to_hex()));
+    out.push_str(&format!(
+        "selection-background = {}\n",
+        p.selection_bg.to_hex()
+    ));
+    out.push_str(&format!(
+        "selection-foreground = {}\n",
+        p.selection_fg.to_hex()
+    ));
+
+    for (i, color) in p.slots.iter().enumerate() {
+        out.push_str(&format!("palette = {}={}\n", i, color.to_hex()));
+    }
+    if extended {
+        for (i, color) in p.extended_colors().iter().enumerate() {
+            out.push_str(&format!("palette = {}={}\n", i + 16, color.to_hex()));
+        }
+    }
+
+    out
+}
+
 /// Resolve the Ghostty themes directory.
 fn themes_dir() -> Result<PathBuf> {
-    let config_home = std::env::var("XDG_CONFIG_HOME")
-        .map(PathBuf::from)
-        .unwrap_or_else(|_| {
-            let home = std::env::var("HOME").unwrap_or_else(|_| "~".to_string());
-            PathBuf::from(home).join(".config")
-        });
-    Ok(config_home.join("ghostty").join("themes"))
+    Ok(config_home().join("ghostty").join("themes"))
 }
 
 /// Resolve the full path where a theme with the given name would be installed.
@@ -76,6 +90,126 @@ pub fn theme_path(name: &str) -> Result<PathBuf> {
     Ok(themes_dir()?.join(name))
 }
 
+/// Ghostty's main config file.
+pub fn config_path() -> PathBuf {
+    config_home().join("ghostty").join("config")
+}
+
+/// How Ghostty's `theme` option should refer to an installed theme: its name
+/// for the standard themes directory, its full path for a configured one.
+pub fn theme_ref(name: &str, config: &Config) -> Result<String> {
+    if config.output_dir(Target::Ghostty).is_some() {
+        let path = config.install_path(Target::Ghostty, name)?;
+        Ok(path.display().to_string())
+    } else {
+        Ok(name.to_string())
+    }
+}
+
+/// Themes the `theme` key of `config` selects: one, or a `light:` and a
+/// `dark:` one. The last `theme` line wins, as in Ghostty.
+pub fn active_themes(config: &str) -> Vec<String> {
+    let Some(value) = config
+        .lines()
+        .filter(|l| is_theme_line(l))
+        .filter_map(|l| l.split_once('=').map(|(_, v)| v.trim()))
+        .next_back()
+    else {
+        return Vec::new();
+    };
+    value
+        .split(',')
+        .map(|part| {
+            let part = part.trim();
+            let part = part.strip_prefix("light:").unwrap_or(part);
+            part.strip_prefix("dark:")
+                .unwrap_or(part)
+                .trim()
+                .to_string()
+        })
+        .filter(|part| !part.is_empty())
+        .collect()
+}
+
+/// Return `config` with its `theme` set to `theme`: the first `theme = ...`
+/// line is replaced and any later ones (which would override it) removed, or
+/// a new line is appended when there is none.
+pub fn set_theme(config: &str, theme: &str) -> String {
+    let line = format!("theme = {theme}");
+    let mut out = String::new();
+    let mut replaced = false;
+    for l in config.lines() {
+        if is_theme_line(l) {
+            if !replaced {
+                out.push_str(&line);
+                out.push('\n');
+                replaced = true;
+            }
+            continue;
+        }
+        out.push_str(l);
+        out.push('\n');
+    }
+    if !replaced {
+        out.push_str(&line);
+        out.push('\n');
+    }
+    out
+}
+
+/// Whether a config line sets the `theme` key.
+fn is_theme_line(line: &str) -> bool {
+    line.trim_start()
+        .strip_prefix("theme")
+        .is_some_and(|rest| rest.trim_start().starts_with('='))
+}
+
+/// Set `theme` in Ghostty's config at `path`, creating the file if needed.
+///
+/// An existing config is first copied to `<path>.bak`; the backup path is
+/// returned when one was made.
+pub fn activate(path: &Path, theme: &str) -> Result<Option<PathBuf>> {
+    let existing = match std::fs::read_to_string(path) {
+        Ok(text) => Some(text),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+        Err(e) => {
+            return Err(e).with_context(|| format!("failed to read {}", path.display()));
+        }
+    };
+    let backup = match &existing {
+        Some(text) => {
+            let backup = path.with_extension("bak");
+            std::fs::write(&backup, text)
+                .with_context(|| format!("failed to back up {}", path.display()))?;
+            Some(backup)
+        }
+        None => {
+            if let Some(dir) = path.parent() {
+                std::fs::create_dir_all(dir)
+                    .with_context(|| format!("failed to create directory: {}", dir.display()))?;
+            }
+            None
+        }
+    };
+    let updated = set_theme(existing.as_deref().unwrap_or(""), theme);
+    std::fs::write(path, updated).with_context(|| format!("failed to write {}", path.display()))?;
+    Ok(backup)
+}
+
+/// Ask running Ghostty instances to reload their config (SIGUSR2).
+///
+/// Returns whether any process was signalled; always `false` off Unix.
+pub fn reload() -> bool {
+    if cfg!(unix) {
+        std::process::Command::new("pkill")
+            .args(["-USR2", "-x", "ghostty"])
+            .status()
+            .is_ok_and(|s| s.success())
+    } else {
+        false
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -106,28 +240,39 @@ mod tests {
         assign_slots(&colors, ThemeMode::Dark)
     }
 
+    #[test]
+    fn extended_writes_the_256_color_palette() {
+        let palette = test_palette();
+        let output = GhosttyExtendedBackend.serialize(&palette, "test");
+        assert_eq!(output.lines().count(), 23 + 240);
+        let extended = palette.extended_colors();
+        assert!(output.contains(&format!("palette = 16={}\n", extended[0].to_hex())));
+        assert!(output.ends_with(&format!("palette = 255={}\n", extended[239].to_hex())));
+    }
+
     #[test]
     fn serialization_format_is_correct() {
         let backend = GhosttyBackend;
         let output = backend.serialize(&test_palette(), "test");
         let lines: Vec<&str> = output.lines().collect();
 
-        assert_eq!(lines.len(), 22, "expected 22 lines, got {}", lines.len());
+        assert_eq!(lines.len(), 23, "expected 23 lines, got {}", lines.len());
 
-        assert!(lines[0].starts_with("background = #"));
-        assert!(lines[1].starts_with("foreground = #"));
-        assert!(lines[2].starts_with("cursor-color = #"));
-        assert!(lines[3].starts_with("cursor-text = #"));
-        assert!(lines[4].starts_with("selection-background = #"));
-        assert!(lines[5].starts_with("selection-foreground = #"));
+        assert_eq!(lines[0], "# Generated by nuri");
+        assert!(lines[1].starts_with("background = #"));
+        assert!(lines[2].starts_with("foreground = #"));
+        assert!(lines[3].starts_with("cursor-color = #"));
+        assert!(lines[4].starts_with("cursor-text = #"));
+        assert!(lines[5].starts_with("selection-background = #"));
+        assert!(lines[6].starts_with("selection-foreground = #"));
 
         for i in 0..16 {
-            let line = lines[6 + i];
+            let line = lines[7 + i];
             let expected_prefix = format!("palette = {}=#", i);
             assert!(
                 line.starts_with(&expected_prefix),
                 "line {} should start with '{expected_prefix}', got '{line}'",
-                6 + i
+                7 + i
             );
         }
     }
@@ -157,7 +302,7 @@ mod tests {
         let backend = GhosttyBackend;
         let output = backend.serialize(&test_palette(), "test");
 
-        for line in output.lines() {
+        for line in output.lines().filter(|l| !l.starts_with("# ")) {
             if let Some(hex_start) = line.find('#') {
                 let hex = &line[hex_start..hex_start + 7];
                 assert_eq!(
@@ -174,7 +319,7 @@ mod tests {
         let backend = GhosttyBackend;
         let output = backend.serialize(&test_palette(), "test");
 
-        for line in output.lines() {
+        for line in output.lines().filter(|l| !l.starts_with("# ")) {
             if let Some(hex_start) = line.find('#') {
                 let hex = &line[hex_start..hex_start + 7];
                 assert_eq!(hex.len(), 7);
@@ -222,4 +367,56 @@ mod tests {
         std::fs::remove_dir_all(&temp_dir).unwrap();
         std::env::remove_var("XDG_CONFIG_HOME");
     }
+
+    #[test]
+    fn set_theme_replaces_existing_line() {
+        let config = "font-size = 12\ntheme = old\nwindow-padding-x = 4\n";
+        assert_eq!(
+            set_theme(config, "sunset"),
+            "font-size = 12\ntheme = sunset\nwindow-padding-x = 4\n"
+        );
+    }
+
+    #[test]
+    fn set_theme_drops_later_overrides_and_keeps_comments() {
+        let config = "# theme = commented\ntheme=a\ntheme = b\ntheme-extra = x\n";
+        assert_eq!(
+            set_theme(config, "c"),
+            "# theme = commented\ntheme = c\ntheme-extra = x\n"
+        );
+    }
+
+    #[test]
+    fn set_theme_appends_when_missing() {
+        assert_eq!(set_theme("", "x"), "theme = x\n");
+        assert_eq!(
+            set_theme("font-size = 12", "x"),
+            "font-size = 12\ntheme = x\n"
+        );
+    }
+
+    #[test]
+    fn active_themes_reads_the_last_theme_line() {
+        assert!(active_themes("font-size = 12\n").is_empty());
+        assert_eq!(active_themes("theme = a\ntheme = b\n"), ["b"]);
+        assert_eq!(
+            active_themes("theme = light:sunset-light,dark:sunset-dark\n"),
+            ["sunset-light", "sunset-dark"]
+        );
+    }
+
+    #[test]
+    fn activate_backs_up_existing_config() {
+        let dir = std::env::temp_dir().join("nuri-test-ghostty-activate");
+        let _ = std::fs::remove_dir_all(&dir);
+        let path = dir.join("config");
+
+        assert_eq!(activate(&path, "first").unwrap(), None);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "theme = first\n");
+
+        let backup = activate(&path, "second").unwrap().unwrap();
+        assert_eq!(std::fs::read_to_string(&backup).unwrap(), "theme = first\n");
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "theme = second\n");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
 }
@@ -0,0 +1,100 @@
+use std::path::PathBuf;
+
+use crate::error::Result;
+use crate::pipeline::assign::AnsiPalette;
+
+use super::{config_home, ThemeBackend, GENERATED_MARKER};
+
+/// GNOME Terminal profile backend: a dconf keyfile of profile color keys.
+///
+/// GNOME Terminal keeps profiles in dconf rather than files, so the output is
+/// loaded into a profile with `dconf load` (see the file's header).
+pub struct GnomeTerminalBackend;
+
+impl ThemeBackend for GnomeTerminalBackend {
+    fn name(&self) -> &str {
+        "GNOME Terminal"
+    }
+
+    fn serialize(&self, palette: &AnsiPalette, theme_name: &str) -> String {
+        let p = palette;
+        let palette_list = p
+            .slots
+            .iter()
+            .map(|c| format!("'{}'", c.to_hex()))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let mut out = String::new();
+        out.push_str(&format!("# {GENERATED_MARKER}\n"));
+        out.push_str("# Load into a profile (the default one here) with:\n");
+        out.push_str(
+            "#   id=$(gsettings get org.gnome.Terminal.ProfilesList default | tr -d \\')\n",
+        );
+        out.push_str(&format!(
+            "#   dconf load /org/gnome/terminal/legacy/profiles:/:$id/ < {}.dconf\n",
+            theme_name
+        ));
+        out.push_str("[/]\n");
+        out.push_str(&format!(
+            "visible-name='{}'\n",
+            theme_name.replace('\\', "\\\\").replace('\'', "\\'")
+        ));
+        out.push_str("use-theme-colors=false\n");
+        out.push_str(&format!("background-color='{}'\n", p.background.to_hex()));
+        out.push_str(&format!("foreground-color='{}'\n", p.foreground.to_hex()));
+        out.push_str("bold-color-same-as-fg=true\n");
+        out.push_str("cursor-colors-set=true\n");
+        out.push_str(&format!(
+            "cursor-background-color='{}'\n",
+            p.cursor_color.to_hex()
+        ));
+        out.push_str(&format!(
+            "cursor-foreground-color='{}'\n",
+            p.cursor_text.to_hex()
+        ));
+        out.push_str("highlight-colors-set=true\n");
+        out.push_str(&format!(
+            "highlight-background-color='{}'\n",
+            p.selection_bg.to_hex()
+        ));
+        out.push_str(&format!(
+            "highlight-foreground-color='{}'\n",
+            p.selection_fg.to_hex()
+        ));
+        out.push_str(&format!("palette=[{palette_list}]\n"));
+        out
+    }
+
+    fn install_path(&self, theme_name: &str) -> Result<PathBuf> {
+        Ok(config_home()
+            .join("nuri")
+            .join("gnome-terminal")
+            .join(format!("{theme_name}.dconf")))
+    }
+
+    fn extension(&self) -> &str {
+        ".dconf"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backends::test_palette;
+
+    #[test]
+    fn writes_profile_keys() {
+        let p = test_palette();
+        let output = GnomeTerminalBackend.serialize(&p, "it's");
+        assert!(output.contains("\n[/]\n"));
+        assert!(output.contains("visible-name='it\\'s'\n"));
+        assert!(output.contains(&format!("background-color='{}'\n", p.background.to_hex())));
+        let palette = output
+            .lines()
+            .find_map(|l| l.strip_prefix("palette=["))
+            .unwrap();
+        assert_eq!(palette.matches("'#").count(), 16);
+        assert!(palette.starts_with(&format!("'{}', ", p.slots[0].to_hex())));
+    }
+}
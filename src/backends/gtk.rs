@@ -0,0 +1,111 @@
+use std::path::PathBuf;
+
+use crate::color::Color;
+use crate::error::Result;
+use crate::pipeline::assign::AnsiPalette;
+
+use super::{config_home, ThemeBackend, GENERATED_MARKER};
+
+/// GTK color backend: `@define-color` overrides of the libadwaita (GTK 4)
+/// and GTK 3 theme colors, to `@import` from `gtk.css`.
+///
+/// Installs next to `gtk.css` rather than over it, since that file often holds
+/// the user's own tweaks.
+pub struct GtkBackend;
+
+impl ThemeBackend for GtkBackend {
+    fn name(&self) -> &str {
+        "GTK"
+    }
+
+    fn serialize(&self, palette: &AnsiPalette, _theme_name: &str) -> String {
+        let p = palette;
+        let raised = p.background.mix(p.foreground, 0.06);
+        let card = p.background.mix(p.foreground, 0.04);
+        let libadwaita = [
+            ("accent_color", p.slots[4]),
+            ("accent_bg_color", p.slots[4]),
+            ("accent_fg_color", p.background),
+            ("destructive_color", p.slots[1]),
+            ("destructive_bg_color", p.slots[1]),
+            ("destructive_fg_color", p.background),
+            ("success_color", p.slots[2]),
+            ("warning_color", p.slots[3]),
+            ("error_color", p.slots[1]),
+            ("window_bg_color", p.background),
+            ("window_fg_color", p.foreground),
+            ("view_bg_color", p.background),
+            ("view_fg_color", p.foreground),
+            ("headerbar_bg_color", raised),
+            ("headerbar_fg_color", p.foreground),
+            ("card_bg_color", card),
+            ("card_fg_color", p.foreground),
+            ("popover_bg_color", raised),
+            ("popover_fg_color", p.foreground),
+            ("dialog_bg_color", raised),
+            ("dialog_fg_color", p.foreground),
+        ];
+        let gtk3 = [
+            ("theme_bg_color", p.background),
+            ("theme_fg_color", p.foreground),
+            ("theme_base_color", p.background),
+            ("theme_text_color", p.foreground),
+            ("theme_selected_bg_color", p.slots[4]),
+            ("theme_selected_fg_color", p.background),
+            ("insensitive_fg_color", p.slots[8]),
+            ("borders", raised),
+        ];
+
+        let mut out = String::new();
+        out.push_str(&format!("/* {GENERATED_MARKER} */\n\n"));
+        out.push_str("/* libadwaita (GTK 4) */\n");
+        push_colors(&mut out, &libadwaita);
+        out.push_str("\n/* GTK 3 themes */\n");
+        push_colors(&mut out, &gtk3);
+        out
+    }
+
+    fn install_path(&self, _theme_name: &str) -> Result<PathBuf> {
+        Ok(config_home().join("gtk-4.0").join("nuri-colors.css"))
+    }
+
+    fn fixed_path(&self) -> bool {
+        true
+    }
+
+    fn extension(&self) -> &str {
+        ".css"
+    }
+}
+
+fn push_colors(out: &mut String, colors: &[(&str, Color)]) {
+    for (name, color) in colors {
+        out.push_str(&format!("@define-color {name} {};\n", color.to_hex()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backends::light_test_palette;
+
+    #[test]
+    fn defines_adwaita_and_gtk3_colors() {
+        let p = light_test_palette();
+        let output = GtkBackend.serialize(&p, "test");
+        assert!(output.starts_with("/* Generated by nuri */\n"));
+        assert!(output.contains("@define-color accent_bg_color #458588;\n"));
+        for (name, color) in [
+            ("accent_bg_color", p.slots[4]),
+            ("window_bg_color", p.background),
+            ("window_fg_color", p.foreground),
+            ("theme_bg_color", p.background),
+            ("theme_selected_bg_color", p.slots[4]),
+        ] {
+            assert!(
+                output.contains(&format!("@define-color {name} {};\n", color.to_hex())),
+                "missing {name}"
+            );
+        }
+    }
+}
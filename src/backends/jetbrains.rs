@@ -0,0 +1,197 @@
+use std::path::PathBuf;
+
+use crate::color::Color;
+use crate::error::Result;
+use crate::pipeline::assign::AnsiPalette;
+
+use super::{config_home, ThemeBackend, GENERATED_MARKER};
+
+/// Console attribute keys for ANSI colors 0-15.
+const CONSOLE_KEYS: [&str; 16] = [
+    "CONSOLE_BLACK_OUTPUT",
+    "CONSOLE_RED_OUTPUT",
+    "CONSOLE_GREEN_OUTPUT",
+    "CONSOLE_YELLOW_OUTPUT",
+    "CONSOLE_BLUE_OUTPUT",
+    "CONSOLE_MAGENTA_OUTPUT",
+    "CONSOLE_CYAN_OUTPUT",
+    "CONSOLE_GRAY_OUTPUT",
+    "CONSOLE_DARKGRAY_OUTPUT",
+    "CONSOLE_RED_BRIGHT_OUTPUT",
+    "CONSOLE_GREEN_BRIGHT_OUTPUT",
+    "CONSOLE_YELLOW_BRIGHT_OUTPUT",
+    "CONSOLE_BLUE_BRIGHT_OUTPUT",
+    "CONSOLE_MAGENTA_BRIGHT_OUTPUT",
+    "CONSOLE_CYAN_BRIGHT_OUTPUT",
+    "CONSOLE_WHITE_OUTPUT",
+];
+
+/// `fontType` value for italic text.
+const ITALIC: u8 = 2;
+
+/// JetBrains IDE color scheme backend (`.icls`): editor colors, console ANSI
+/// colors and the language-default syntax attributes, on top of Darcula or
+/// Default (light).
+///
+/// IDE config directories are versioned, so installing saves the scheme under
+/// `~/.config/nuri/jetbrains/` for Settings | Editor | Color Scheme | Import.
+pub struct JetbrainsBackend;
+
+impl ThemeBackend for JetbrainsBackend {
+    fn name(&self) -> &str {
+        "JetBrains"
+    }
+
+    fn serialize(&self, palette: &AnsiPalette, theme_name: &str) -> String {
+        let p = palette;
+        let dark = p.background.relative_luminance() < p.foreground.relative_luminance();
+        let parent = if dark { "Darcula" } else { "Default" };
+        let raised = p.background.mix(p.foreground, 0.06);
+        let colors = [
+            ("CARET_COLOR", p.cursor_color),
+            ("CARET_ROW_COLOR", raised),
+            ("CONSOLE_BACKGROUND_KEY", p.background),
+            ("GUTTER_BACKGROUND", p.background),
+            ("INDENT_GUIDE", p.background.mix(p.foreground, 0.15)),
+            ("LINE_NUMBERS_COLOR", p.slots[8]),
+            ("LINE_NUMBER_ON_CARET_ROW_COLOR", p.foreground),
+            ("SELECTION_BACKGROUND", p.selection_bg),
+            ("SELECTION_FOREGROUND", p.selection_fg),
+        ];
+        let syntax = [
+            ("DEFAULT_KEYWORD", p.slots[5], 0),
+            ("DEFAULT_STRING", p.slots[2], 0),
+            ("DEFAULT_NUMBER", p.slots[3], 0),
+            ("DEFAULT_CONSTANT", p.slots[3], 0),
+            ("DEFAULT_LINE_COMMENT", p.slots[8], ITALIC),
+            ("DEFAULT_BLOCK_COMMENT", p.slots[8], ITALIC),
+            ("DEFAULT_DOC_COMMENT", p.slots[8], ITALIC),
+            ("DEFAULT_FUNCTION_DECLARATION", p.slots[4], 0),
+            ("DEFAULT_FUNCTION_CALL", p.slots[4], 0),
+            ("DEFAULT_CLASS_NAME", p.slots[3], 0),
+            ("DEFAULT_INTERFACE_NAME", p.slots[3], 0),
+            ("DEFAULT_METADATA", p.slots[13], 0),
+            ("DEFAULT_OPERATION_SIGN", p.slots[14], 0),
+            ("DEFAULT_INSTANCE_FIELD", p.slots[6], 0),
+            ("DEFAULT_STATIC_FIELD", p.slots[6], ITALIC),
+            ("DEFAULT_VALID_STRING_ESCAPE", p.slots[14], 0),
+        ];
+
+        let mut out = String::new();
+        out.push_str(&format!("<!-- {GENERATED_MARKER} -->\n"));
+        out.push_str(&format!(
+            "<scheme name=\"{}\" version=\"142\" parent_scheme=\"{parent}\">\n",
+            xml_escape(theme_name)
+        ));
+        out.push_str("  <colors>\n");
+        for (name, color) in colors {
+            out.push_str(&format!(
+                "    <option name=\"{name}\" value=\"{}\" />\n",
+                hex(color)
+            ));
+        }
+        out.push_str("  </colors>\n");
+        out.push_str("  <attributes>\n");
+        push_attribute(&mut out, "TEXT", p.foreground, Some(p.background), 0);
+        push_attribute(&mut out, "CONSOLE_NORMAL_OUTPUT", p.foreground, None, 0);
+        push_attribute(&mut out, "CONSOLE_ERROR_OUTPUT", p.slots[1], None, 0);
+        push_attribute(&mut out, "CONSOLE_USER_INPUT", p.slots[2], None, 0);
+        push_attribute(&mut out, "CONSOLE_SYSTEM_OUTPUT", p.slots[8], None, 0);
+        for (key, color) in CONSOLE_KEYS.iter().zip(p.slots) {
+            push_attribute(&mut out, key, color, None, 0);
+        }
+        for (key, color, font_type) in syntax {
+            push_attribute(&mut out, key, color, None, font_type);
+        }
+        out.push_str("  </attributes>\n");
+        out.push_str("</scheme>\n");
+        out
+    }
+
+    fn install_path(&self, theme_name: &str) -> Result<PathBuf> {
+        Ok(config_home()
+            .join("nuri")
+            .join("jetbrains")
+            .join(format!("{theme_name}.icls")))
+    }
+
+    fn extension(&self) -> &str {
+        ".icls"
+    }
+}
+
+fn push_attribute(
+    out: &mut String,
+    key: &str,
+    foreground: Color,
+    background: Option<Color>,
+    font_type: u8,
+) {
+    out.push_str(&format!("    <option name=\"{key}\">\n      <value>\n"));
+    out.push_str(&format!(
+        "        <option name=\"FOREGROUND\" value=\"{}\" />\n",
+        hex(foreground)
+    ));
+    if let Some(background) = background {
+        out.push_str(&format!(
+            "        <option name=\"BACKGROUND\" value=\"{}\" />\n",
+            hex(background)
+        ));
+    }
+    if font_type != 0 {
+        out.push_str(&format!(
+            "        <option name=\"FONT_TYPE\" value=\"{font_type}\" />\n"
+        ));
+    }
+    out.push_str("      </value>\n    </option>\n");
+}
+
+/// Colors in `.icls` files are bare `rrggbb`.
+fn hex(color: Color) -> String {
+    color.to_hex().trim_start_matches('#').to_string()
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backends::{light_test_palette, test_palette};
+
+    #[test]
+    fn maps_console_and_editor_colors() {
+        let p = test_palette();
+        let output = JetbrainsBackend.serialize(&p, "test");
+        assert!(output.contains("parent_scheme=\"Darcula\""));
+        assert!(output.contains(&format!(
+            "<option name=\"CONSOLE_BACKGROUND_KEY\" value=\"{}\" />",
+            hex(p.background)
+        )));
+        for (key, color) in CONSOLE_KEYS.iter().zip(p.slots) {
+            assert!(
+                output.contains(&format!(
+                    "<option name=\"{key}\">\n      <value>\n        <option name=\"FOREGROUND\" value=\"{}\" />",
+                    hex(color)
+                )),
+                "{key}"
+            );
+        }
+        // Tags balance
+        assert_eq!(
+            output.matches("<option name=").count(),
+            output.matches(" />").count() + output.matches("</option>").count()
+        );
+    }
+
+    #[test]
+    fn light_palette_extends_default_scheme() {
+        let output = JetbrainsBackend.serialize(&light_test_palette(), "a \"b\" & c");
+        assert!(output.contains("<scheme name=\"a &quot;b&quot; &amp; c\""));
+        assert!(output.contains("parent_scheme=\"Default\""));
+    }
+}
@@ -0,0 +1,137 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::pipeline::assign::AnsiPalette;
+
+use super::{config_home, ThemeBackend, GENERATED_MARKER};
+
+/// Serialized shape: the theme name followed by every palette field.
+#[derive(Serialize)]
+struct JsonTheme<'a> {
+    #[serde(rename = "$comment")]
+    comment: &'a str,
+    name: &'a str,
+    #[serde(flatten)]
+    palette: &'a AnsiPalette,
+}
+
+/// Raw palette dump backend (JSON), for scripts and other theming tools.
+pub struct JsonBackend;
+
+impl ThemeBackend for JsonBackend {
+    fn name(&self) -> &str {
+        "JSON"
+    }
+
+    fn serialize(&self, palette: &AnsiPalette, theme_name: &str) -> String {
+        let theme = JsonTheme {
+            comment: GENERATED_MARKER,
+            name: theme_name,
+            palette,
+        };
+        // Serializing plain strings and hex colors cannot fail.
+        let mut out = serde_json::to_string_pretty(&theme).unwrap_or_default();
+        out.push('\n');
+        out
+    }
+
+    fn install_path(&self, theme_name: &str) -> crate::error::Result<PathBuf> {
+        Ok(themes_dir()?.join(format!("{}.json", theme_name)))
+    }
+
+    fn extension(&self) -> &str {
+        ".json"
+    }
+}
+
+/// Resolve the directory nuri keeps its own JSON palettes in.
+fn themes_dir() -> Result<PathBuf> {
+    Ok(config_home().join("nuri").join("themes"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::ThemeMode;
+    use crate::color::Color;
+    use crate::pipeline::assign::assign_slots;
+    use crate::pipeline::extract::ExtractedColor;
+    use palette::Oklch;
+
+    fn make_extracted(l: f32, chroma: f32, hue: f32, weight: f32) -> ExtractedColor {
+        ExtractedColor {
+            color: Color::from_oklch(Oklch::new(l, chroma, hue)),
+            weight,
+        }
+    }
+
+    fn test_palette() -> AnsiPalette {
+        let colors = vec![
+            make_extracted(0.60, 0.20, 25.0, 0.12),
+            make_extracted(0.60, 0.20, 145.0, 0.12),
+            make_extracted(0.70, 0.20, 90.0, 0.12),
+            make_extracted(0.55, 0.20, 260.0, 0.12),
+            make_extracted(0.60, 0.20, 325.0, 0.12),
+            make_extracted(0.65, 0.20, 195.0, 0.10),
+            make_extracted(0.10, 0.01, 0.0, 0.15),
+            make_extracted(0.95, 0.01, 0.0, 0.15),
+        ];
+        assign_slots(&colors, ThemeMode::Dark)
+    }
+
+    #[test]
+    fn output_contains_all_fields() {
+        let output = JsonBackend.serialize(&test_palette(), "test");
+        let value: serde_json::Value = serde_json::from_str(&output).unwrap();
+
+        assert_eq!(value["name"], "test");
+        assert_eq!(value["slots"].as_array().unwrap().len(), 16);
+        for key in [
+            "background",
+            "foreground",
+            "cursor_color",
+            "cursor_text",
+            "selection_bg",
+            "selection_fg",
+        ] {
+            assert!(value[key].is_string(), "missing key '{key}'");
+        }
+    }
+
+    #[test]
+    fn round_trips_through_ansi_palette() {
+        let palette = test_palette();
+        let output = JsonBackend.serialize(&palette, "test");
+        let parsed: AnsiPalette = serde_json::from_str(&output).unwrap();
+        assert_eq!(parsed, palette);
+    }
+
+    #[test]
+    fn theme_name_is_escaped() {
+        let output = JsonBackend.serialize(&test_palette(), "say \"hi\"");
+        let value: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(value["name"], "say \"hi\"");
+    }
+
+    #[test]
+    fn install_creates_correct_path() {
+        let temp_dir = std::env::temp_dir().join("nuri-test-json-install");
+        std::env::set_var("XDG_CONFIG_HOME", &temp_dir);
+
+        let backend = JsonBackend;
+        let palette = test_palette();
+        let result = backend.install(&palette, "my-theme").unwrap();
+
+        let expected_path = temp_dir.join("nuri").join("themes").join("my-theme.json");
+        assert_eq!(result, expected_path);
+        assert!(expected_path.exists());
+
+        let content = std::fs::read_to_string(&expected_path).unwrap();
+        assert_eq!(content, backend.serialize(&palette, "my-theme"));
+
+        std::fs::remove_dir_all(&temp_dir).unwrap();
+        std::env::remove_var("XDG_CONFIG_HOME");
+    }
+}
@@ -0,0 +1,135 @@
+use std::path::PathBuf;
+
+use crate::color::Color;
+use crate::error::Result;
+use crate::pipeline::assign::AnsiPalette;
+
+use super::{config_home, ThemeBackend, GENERATED_MARKER};
+
+/// Kakoune colorscheme backend (`.kak` face definitions).
+pub struct KakouneBackend;
+
+impl ThemeBackend for KakouneBackend {
+    fn name(&self) -> &str {
+        "Kakoune"
+    }
+
+    fn serialize(&self, palette: &AnsiPalette, _theme_name: &str) -> String {
+        let p = palette;
+        let c = |color: Color| kak(color);
+        let (bg, fg) = (c(p.background), c(p.foreground));
+        let raised = c(p.background.mix(p.foreground, 0.08));
+        let dim = c(p.slots[8]);
+        let (red, green, yellow, blue) =
+            (c(p.slots[1]), c(p.slots[2]), c(p.slots[3]), c(p.slots[4]));
+        let (magenta, cyan) = (c(p.slots[5]), c(p.slots[6]));
+        let selection = format!("{},{}", c(p.selection_fg), c(p.selection_bg));
+        let cursor = format!("{},{}", c(p.cursor_text), c(p.cursor_color));
+
+        let faces: Vec<(&str, String)> = vec![
+            // Syntax
+            ("value", magenta.clone()),
+            ("type", yellow.clone()),
+            ("variable", cyan.clone()),
+            ("module", cyan.clone()),
+            ("function", blue.clone()),
+            ("string", green.clone()),
+            ("keyword", magenta.clone()),
+            ("operator", c(p.slots[14])),
+            ("attribute", yellow.clone()),
+            ("comment", format!("{dim}+i")),
+            ("documentation", dim.clone()),
+            ("meta", c(p.slots[13])),
+            ("builtin", format!("{}+b", c(p.slots[12]))),
+            // Markup
+            ("title", format!("{blue}+b")),
+            ("header", cyan.clone()),
+            ("mono", green.clone()),
+            ("block", magenta.clone()),
+            ("link", format!("{cyan}+u")),
+            ("bullet", yellow.clone()),
+            ("list", fg.clone()),
+            // Interface
+            ("Default", format!("{fg},{bg}")),
+            ("PrimarySelection", format!("{selection}+fg")),
+            ("SecondarySelection", format!("{fg},{raised}+fg")),
+            ("PrimaryCursor", format!("{cursor}+fg")),
+            ("SecondaryCursor", format!("{bg},{dim}+fg")),
+            ("PrimaryCursorEol", format!("{bg},{cyan}+fg")),
+            ("SecondaryCursorEol", format!("{bg},{dim}+fg")),
+            ("LineNumbers", format!("{dim},{bg}")),
+            ("LineNumberCursor", format!("{fg},{bg}+b")),
+            ("LineNumbersWrapped", format!("{bg},{bg}")),
+            ("MenuForeground", format!("{bg},{blue}")),
+            ("MenuBackground", format!("{fg},{raised}")),
+            ("MenuInfo", dim.clone()),
+            ("Information", format!("{fg},{raised}")),
+            ("Error", format!("{bg},{red}")),
+            ("DiagnosticError", red.clone()),
+            ("DiagnosticWarning", yellow.clone()),
+            ("StatusLine", format!("{fg},{raised}")),
+            ("StatusLineMode", format!("{yellow},{raised}")),
+            ("StatusLineInfo", format!("{blue},{raised}")),
+            ("StatusLineValue", format!("{green},{raised}")),
+            ("StatusCursor", cursor),
+            ("Prompt", format!("{blue},{raised}")),
+            ("MatchingChar", format!("{fg},{}+b", c(p.selection_bg))),
+            ("Whitespace", format!("{dim}+f")),
+            ("BufferPadding", format!("{dim},{bg}")),
+        ];
+
+        let mut out = String::new();
+        out.push_str(&format!("# {GENERATED_MARKER}\n"));
+        for (face, spec) in faces {
+            out.push_str(&format!("face global {face} {spec}\n"));
+        }
+        out
+    }
+
+    fn install_path(&self, theme_name: &str) -> Result<PathBuf> {
+        Ok(config_home()
+            .join("kak")
+            .join("colors")
+            .join(format!("{theme_name}.kak")))
+    }
+
+    fn extension(&self) -> &str {
+        ".kak"
+    }
+}
+
+/// A color in Kakoune's `rgb:rrggbb` form.
+fn kak(color: Color) -> String {
+    format!("rgb:{}", color.to_hex().trim_start_matches('#'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backends::test_palette;
+
+    #[test]
+    fn defines_interface_and_syntax_faces() {
+        let p = test_palette();
+        let output = KakouneBackend.serialize(&p, "test");
+        assert!(output.contains(&format!(
+            "face global Default {},{}\n",
+            kak(p.foreground),
+            kak(p.background)
+        )));
+        assert!(output.contains(&format!(
+            "face global PrimarySelection {},{}+fg\n",
+            kak(p.selection_fg),
+            kak(p.selection_bg)
+        )));
+        for face in ["MenuForeground", "string", "keyword", "comment"] {
+            assert!(
+                output.contains(&format!("face global {face} rgb:")),
+                "{face}"
+            );
+        }
+        for line in output.lines().skip(1) {
+            assert_eq!(line.split_whitespace().count(), 4, "{line}");
+        }
+    }
+}
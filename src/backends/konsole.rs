@@ -0,0 +1,87 @@
+use std::path::PathBuf;
+
+use crate::color::Color;
+use crate::error::Result;
+use crate::pipeline::assign::AnsiPalette;
+
+use super::{data_home, ThemeBackend, GENERATED_MARKER};
+
+/// KDE Konsole color scheme backend (`.colorscheme` INI).
+pub struct KonsoleBackend;
+
+impl ThemeBackend for KonsoleBackend {
+    fn name(&self) -> &str {
+        "Konsole"
+    }
+
+    fn serialize(&self, palette: &AnsiPalette, theme_name: &str) -> String {
+        let p = palette;
+        let mut out = String::new();
+        out.push_str(&format!("# {GENERATED_MARKER}\n"));
+        out.push_str("[General]\n");
+        out.push_str(&format!("Description={theme_name}\n"));
+        out.push_str("Opacity=1\n");
+
+        // Konsole has no cursor or selection colors; only the terminal colors
+        let sections = [
+            ("Background", p.background),
+            ("BackgroundIntense", p.background),
+            ("Foreground", p.foreground),
+            ("ForegroundIntense", p.slots[15]),
+            ("BackgroundFaint", p.background),
+            ("ForegroundFaint", p.dim_foreground()),
+        ];
+        for (section, color) in sections {
+            push_section(&mut out, section, color);
+        }
+        for i in 0..8 {
+            push_section(&mut out, &format!("Color{i}"), p.slots[i]);
+            push_section(&mut out, &format!("Color{i}Intense"), p.slots[i + 8]);
+            push_section(&mut out, &format!("Color{i}Faint"), p.dim_colors()[i]);
+        }
+        out
+    }
+
+    fn install_path(&self, theme_name: &str) -> Result<PathBuf> {
+        Ok(data_home()
+            .join("konsole")
+            .join(format!("{theme_name}.colorscheme")))
+    }
+
+    fn extension(&self) -> &str {
+        ".colorscheme"
+    }
+}
+
+fn push_section(out: &mut String, section: &str, color: Color) {
+    out.push_str(&format!(
+        "\n[{section}]\nColor={},{},{}\n",
+        color.r, color.g, color.b
+    ));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backends::test_palette;
+
+    #[test]
+    fn writes_every_color_section() {
+        let p = test_palette();
+        let output = KonsoleBackend.serialize(&p, "sunset");
+        assert!(output.contains("[General]\nDescription=sunset\n"));
+        assert_eq!(output.matches("\nColor=").count(), 30);
+        let bg = p.background;
+        assert!(output.contains(&format!("[Background]\nColor={},{},{}\n", bg.r, bg.g, bg.b)));
+        let c12 = p.slots[12];
+        assert!(output.contains(&format!(
+            "[Color4Intense]\nColor={},{},{}\n",
+            c12.r, c12.g, c12.b
+        )));
+        let faint = p.dim_colors()[1];
+        assert!(output.contains(&format!(
+            "[Color1Faint]\nColor={},{},{}\n",
+            faint.r, faint.g, faint.b
+        )));
+    }
+}
@@ -0,0 +1,81 @@
+use std::path::PathBuf;
+
+use crate::color::Color;
+use crate::error::Result;
+use crate::pipeline::assign::AnsiPalette;
+
+use super::{ConfigDir, ThemeBackend, GENERATED_MARKER};
+
+/// lazygit theme backend: a `gui.theme` config section, merged over the main
+/// `config.yml` through `LG_CONFIG_FILE`.
+pub struct LazygitBackend;
+
+impl ThemeBackend for LazygitBackend {
+    fn name(&self) -> &str {
+        "lazygit"
+    }
+
+    fn serialize(&self, palette: &AnsiPalette, _theme_name: &str) -> String {
+        let p = palette;
+        let entries: [(&str, Color, Option<&str>); 10] = [
+            ("activeBorderColor", p.slots[4], Some("bold")),
+            ("inactiveBorderColor", p.slots[8], None),
+            ("searchingActiveBorderColor", p.slots[3], Some("bold")),
+            ("optionsTextColor", p.slots[4], None),
+            ("selectedLineBgColor", p.selection_bg, None),
+            ("inactiveViewSelectedLineBgColor", p.selection_bg, None),
+            ("cherryPickedCommitFgColor", p.slots[4], None),
+            ("cherryPickedCommitBgColor", p.slots[6], None),
+            ("unstagedChangesColor", p.slots[1], None),
+            ("defaultFgColor", p.foreground, None),
+        ];
+
+        let mut out = String::new();
+        out.push_str(&format!("# {GENERATED_MARKER}\n"));
+        out.push_str("gui:\n");
+        out.push_str("  theme:\n");
+        for (key, color, attribute) in entries {
+            out.push_str(&format!("    {key}:\n"));
+            out.push_str(&format!("      - \"{}\"\n", color.to_hex()));
+            if let Some(attribute) = attribute {
+                out.push_str(&format!("      - {attribute}\n"));
+            }
+        }
+        out
+    }
+
+    fn install_path(&self, _theme_name: &str) -> Result<PathBuf> {
+        // lazygit reads ~/Library/Application Support on macOS
+        Ok(ConfigDir::Native.resolve().join("lazygit").join("nuri.yml"))
+    }
+
+    fn fixed_path(&self) -> bool {
+        true
+    }
+
+    fn extension(&self) -> &str {
+        ".yml"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backends::test_palette;
+
+    #[test]
+    fn writes_theme_section() {
+        let p = test_palette();
+        let output = LazygitBackend.serialize(&p, "test");
+        assert!(output.contains("gui:\n  theme:\n"));
+        assert!(output.contains("    activeBorderColor:\n      - \"#458588\"\n"));
+        assert!(output.contains(&format!(
+            "    activeBorderColor:\n      - \"{}\"\n      - bold\n",
+            p.slots[4].to_hex()
+        )));
+        assert!(output.contains(&format!(
+            "    selectedLineBgColor:\n      - \"{}\"\n",
+            p.selection_bg.to_hex()
+        )));
+    }
+}
@@ -1,11 +1,42 @@
+pub mod alacritty;
+pub mod base16;
+pub mod btop;
+pub mod delta;
+pub mod dircolors;
+pub mod discord;
+pub mod env;
+pub mod file_managers;
 pub mod ghostty;
+pub mod gnome_terminal;
+pub mod gtk;
+pub mod jetbrains;
+pub mod json;
+pub mod kakoune;
+pub mod konsole;
+pub mod lazygit;
 pub mod neovim;
+pub mod notifications;
+pub mod obsidian;
+pub mod pywal;
+pub mod qt;
+pub mod qutebrowser;
+pub mod registry;
+pub mod rofi;
+pub mod template;
+pub mod tui_apps;
+pub mod waybar;
+pub mod wezterm;
+pub mod windows_terminal;
+pub mod wm;
+pub mod zathura;
 pub mod zellij;
 
+use std::collections::HashMap;
+use std::io::{ErrorKind, Write};
 use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 
-use anyhow::Result;
-
+use crate::error::{NuriError, Result};
 use crate::pipeline::assign::AnsiPalette;
 
 /// A theme output backend that serializes an `AnsiPalette` into a target format.
@@ -16,42 +47,698 @@ pub trait ThemeBackend {
     /// Serialize the palette into the target format.
     fn serialize(&self, palette: &AnsiPalette, theme_name: &str) -> String;
 
+    /// Path inside the target's standard config directory where `install`
+    /// writes a theme with the given name.
+    fn install_path(&self, theme_name: &str) -> Result<PathBuf>;
+
+    /// Whether [`install_path`](Self::install_path) ignores the theme name.
+    ///
+    /// Some applications read one file, such as Waybar's `colors.css` or
+    /// pywal's `colors.json`, so every theme installs to the same path and
+    /// replaces the last. Those files often sit in a directory the user keeps
+    /// their own config in, so nuri writes the theme name into the header
+    /// and only lists or removes the file it wrote.
+    fn fixed_path(&self) -> bool {
+        false
+    }
+
     /// Install the theme to the target's standard config directory.
     /// Returns the path where the theme was written.
-    fn install(&self, palette: &AnsiPalette, theme_name: &str) -> Result<PathBuf>;
+    fn install(&self, palette: &AnsiPalette, theme_name: &str) -> Result<PathBuf> {
+        let path = self.install_path(theme_name)?;
+        self.install_at(palette, theme_name, &path)?;
+        Ok(path)
+    }
+
+    /// Write the theme to `path`, creating its parent directory first.
+    fn install_at(&self, palette: &AnsiPalette, theme_name: &str, path: &Path) -> Result<()> {
+        self.install_at_with(palette, theme_name, path, Overwrite::Allow)
+    }
+
+    /// Like [`install_at`](Self::install_at), with an explicit policy for an
+    /// existing file.
+    fn install_at_with(
+        &self,
+        palette: &AnsiPalette,
+        theme_name: &str,
+        path: &Path,
+        overwrite: Overwrite,
+    ) -> Result<()> {
+        let dir = path.parent().unwrap_or(Path::new(""));
+        std::fs::create_dir_all(dir).map_err(|source| NuriError::InstallPathNotWritable {
+            path: dir.into(),
+            source,
+        })?;
+        self.write_to_with(palette, theme_name, path, overwrite)?;
+        for (file_name, content) in self.companions(palette, theme_name) {
+            let companion = dir.join(file_name);
+            std::fs::write(&companion, content).map_err(|source| {
+                NuriError::InstallPathNotWritable {
+                    path: companion,
+                    source,
+                }
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Extra files installed next to the theme, as `(file name, content)`
+    /// pairs. They are always replaced. Most formats are a single file.
+    fn companions(&self, _palette: &AnsiPalette, _theme_name: &str) -> Vec<(String, String)> {
+        Vec::new()
+    }
+
+    /// File names of the [`companions`](Self::companions), for removing them
+    /// with the theme.
+    fn companion_names(&self) -> Vec<&str> {
+        Vec::new()
+    }
 
     /// Write the theme to an arbitrary path.
-    fn write_to(&self, palette: &AnsiPalette, theme_name: &str, path: &Path) -> Result<()>;
+    fn write_to(&self, palette: &AnsiPalette, theme_name: &str, path: &Path) -> Result<()> {
+        self.write_to_with(palette, theme_name, path, Overwrite::Allow)
+    }
+
+    /// Like [`write_to`](Self::write_to), with an explicit policy for an
+    /// existing file. [`Overwrite::Refuse`] creates the file atomically and
+    /// errors if it already exists.
+    fn write_to_with(
+        &self,
+        palette: &AnsiPalette,
+        theme_name: &str,
+        path: &Path,
+        overwrite: Overwrite,
+    ) -> Result<()> {
+        let mut content = self.serialize(palette, theme_name);
+        if self.fixed_path() && generated_name(&content).is_none() {
+            content = stamp_header(&content, &[format!("name: {theme_name}")]);
+        }
+        let result = match overwrite {
+            Overwrite::Allow => std::fs::write(path, content),
+            Overwrite::Refuse => std::fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(path)
+                .and_then(|mut file| file.write_all(content.as_bytes())),
+        };
+        result.map_err(|source| {
+            if source.kind() == ErrorKind::AlreadyExists {
+                NuriError::ThemeExists { path: path.into() }
+            } else {
+                NuriError::InstallPathNotWritable {
+                    path: path.into(),
+                    source,
+                }
+            }
+        })
+    }
 
     /// File extension for this backend (e.g., ".kdl"), or empty string for none.
     fn extension(&self) -> &str;
 }
 
+/// Text every backend writes into a header comment (or a `$comment` key for
+/// JSON formats) so installed themes can be recognized as nuri's.
+pub const GENERATED_MARKER: &str = "Generated by nuri";
+
+/// Whether theme file `content` carries nuri's header.
+pub fn is_generated(content: &str) -> bool {
+    content
+        .lines()
+        .take(5)
+        .any(|line| line.contains(GENERATED_MARKER))
+}
+
+/// Insert `lines` into a theme's header, right after the `Generated by nuri`
+/// marker line and in the same comment style. JSON formats get the lines
+/// appended to their `$comment` string.
+pub fn stamp_header(theme: &str, lines: &[String]) -> String {
+    let mut out = String::with_capacity(theme.len() + 512);
+    let mut stamped = false;
+    for line in theme.lines() {
+        let marker = (!stamped).then(|| line.find(GENERATED_MARKER)).flatten();
+        let Some(pos) = marker else {
+            out.push_str(line);
+            out.push('\n');
+            continue;
+        };
+        stamped = true;
+        let prefix = &line[..pos];
+        if prefix.trim_start().starts_with("\"$comment\"") {
+            let mut comment = GENERATED_MARKER.to_string();
+            for l in lines {
+                comment.push('\n');
+                comment.push_str(l);
+            }
+            let value = serde_json::to_string(&comment).unwrap_or_default();
+            let key = &prefix[..prefix.len() - 1];
+            let rest = &line[pos + GENERATED_MARKER.len() + 1..];
+            out.push_str(&format!("{key}{value}{rest}\n"));
+        } else {
+            out.push_str(line);
+            out.push('\n');
+            // Block comments (`/* ... */`, `<!-- ... -->`) are closed on
+            // every line
+            let suffix = match line[pos + GENERATED_MARKER.len()..].trim() {
+                "*/" => " */",
+                "-->" => " -->",
+                _ => "",
+            };
+            for l in lines {
+                out.push_str(&format!("{prefix}{l}{suffix}\n"));
+            }
+        }
+    }
+    out
+}
+
+/// `key: value` fields from a theme's comment lines, or from its `$comment`
+/// string for JSON formats. The first occurrence of a key wins.
+pub fn header_fields(theme: &str) -> HashMap<String, String> {
+    let json_comment = serde_json::from_str::<serde_json::Value>(theme)
+        .ok()
+        .and_then(|v| v.get("$comment")?.as_str().map(str::to_string));
+    let text = json_comment.as_deref().unwrap_or(theme);
+
+    let mut fields = HashMap::new();
+    for line in text.lines() {
+        let line = line.trim_start();
+        let Some(body) = ["#", "//", "--", "/*", "<!--", "*"]
+            .iter()
+            .find_map(|p| line.strip_prefix(p))
+            .or(json_comment.as_ref().map(|_| line))
+        else {
+            continue;
+        };
+        let body = body.trim();
+        let body = ["*/", "-->"]
+            .iter()
+            .find_map(|s| body.strip_suffix(s))
+            .unwrap_or(body)
+            .trim_end();
+        if let Some((key, value)) = body.split_once(": ") {
+            fields.entry(key.to_string()).or_insert(value.to_string());
+        }
+    }
+    fields
+}
+
+/// Theme name in the header of a file nuri wrote, or `None` for files it
+/// didn't write.
+pub fn generated_name(content: &str) -> Option<String> {
+    if !is_generated(content) {
+        return None;
+    }
+    header_fields(content).remove("name")
+}
+
+/// Themes `backend` installed in `dir`, as (name, path) sorted by name.
+///
+/// For a [fixed-path](ThemeBackend::fixed_path) backend that is at most its
+/// one file, and only when nuri wrote it.
+pub fn installed_by(backend: &dyn ThemeBackend, dir: &Path) -> Vec<(String, PathBuf)> {
+    if !backend.fixed_path() {
+        return installed_themes(dir, backend.extension());
+    }
+    let Some(file_name) = backend
+        .install_path("")
+        .ok()
+        .and_then(|p| Some(p.file_name()?.to_os_string()))
+    else {
+        return Vec::new();
+    };
+    let path = dir.join(&file_name);
+    let content = std::fs::read_to_string(&path).unwrap_or_default();
+    if !is_generated(&content) {
+        return Vec::new();
+    }
+    // Installs from before the name was recorded go by the file name
+    let name = generated_name(&content).unwrap_or_else(|| file_name.to_string_lossy().into_owned());
+    vec![(name, path)]
+}
+
+/// Delete an installed theme file along with its companions.
+pub fn uninstall(backend: &dyn ThemeBackend, path: &Path) -> std::io::Result<()> {
+    std::fs::remove_file(path)?;
+    let dir = path.parent().unwrap_or(Path::new(""));
+    for file_name in backend.companion_names() {
+        match std::fs::remove_file(dir.join(file_name)) {
+            Err(e) if e.kind() != ErrorKind::NotFound => return Err(e),
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Themes in an install directory, as (name, path) sorted by name. Names are
+/// file names without `extension`; other files are skipped.
+pub fn installed_themes(dir: &Path, extension: &str) -> Vec<(String, PathBuf)> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut themes: Vec<(String, PathBuf)> = entries
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().is_file())
+        .filter_map(|e| {
+            let file_name = e.file_name().to_string_lossy().into_owned();
+            let name = if extension.is_empty() {
+                file_name
+            } else {
+                file_name.strip_suffix(extension)?.to_string()
+            };
+            Some((name, e.path()))
+        })
+        .collect();
+    themes.sort();
+    themes
+}
+
+/// What to do when a theme file already exists at the destination.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Overwrite {
+    /// Replace the existing file.
+    #[default]
+    Allow,
+    /// Leave the existing file alone and return an error.
+    Refuse,
+}
+
 /// Supported output targets.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, clap::ValueEnum, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum Target {
     Ghostty,
     Zellij,
     Neovim,
+    Alacritty,
+    Wezterm,
+    WindowsTerminal,
+    /// KDE Konsole `.colorscheme`.
+    Konsole,
+    /// GNOME Terminal profile colors (dconf keyfile).
+    GnomeTerminal,
+    /// base16 / tinted-theming scheme YAML.
+    Base16,
+    /// Base24 scheme YAML (base16 plus bright accents).
+    Base24,
+    /// pywal cache files (`~/.cache/wal/colors.json`, `colors.sh`, `colors`).
+    Pywal,
+    /// Rofi launcher theme (`.rasi`).
+    Rofi,
+    /// Waybar `@define-color` stylesheet (`colors.css`).
+    Waybar,
+    /// Hyprland border colors and variables (`hypr/colors.conf`).
+    Hyprland,
+    /// sway/i3 `client.*` colors and variables (`sway/colors`).
+    Sway,
+    /// Dunst notification colors (`dunstrc.d` drop-in).
+    Dunst,
+    /// Mako notification colors (`mako/colors` include).
+    Mako,
+    /// GTK `@define-color` overrides (`gtk-4.0/nuri-colors.css`).
+    Gtk,
+    /// qt5ct color scheme.
+    Qt,
+    /// lazygit `gui.theme` config section.
+    Lazygit,
+    /// git-delta `[delta "nuri"]` feature.
+    Delta,
+    /// btop system monitor theme.
+    Btop,
+    /// Zathura `set` color include.
+    Zathura,
+    /// qutebrowser `c.colors` settings.
+    Qutebrowser,
+    /// `dircolors` database for `ls`, plus `EZA_COLORS`.
+    Dircolors,
+    /// Kakoune colorscheme (`.kak`).
+    Kakoune,
+    /// JetBrains IDE color scheme (`.icls`).
+    Jetbrains,
+    /// Obsidian CSS snippet.
+    Obsidian,
+    /// BetterDiscord theme (`.theme.css`).
+    Discord,
+    /// yazi flavor (`flavor.toml`).
+    Yazi,
+    /// ranger colorscheme.
+    Ranger,
+    /// lf file colors.
+    Lf,
+    /// cava visualizer colors.
+    Cava,
+    /// ncmpcpp color settings.
+    Ncmpcpp,
+    /// newsboat color lines.
+    Newsboat,
+    /// Shell script exporting `NURI_*` color variables.
+    Env,
+    Json,
+    /// A user-supplied template (`--template`).
+    Template,
+}
+
+impl Target {
+    /// All supported targets, in declaration order.
+    pub fn all() -> &'static [Target] {
+        <Target as clap::ValueEnum>::value_variants()
+    }
+
+    /// The target's CLI name (e.g. `windows-terminal`), its key in a
+    /// [`Registry`](registry::Registry).
+    pub fn key(self) -> String {
+        clap::ValueEnum::to_possible_value(&self)
+            .map(|v| v.get_name().to_string())
+            .unwrap_or_default()
+    }
+
+    /// The built-in target called `key`.
+    pub fn from_key(key: &str) -> Option<Target> {
+        Self::all().iter().copied().find(|t| t.key() == key)
+    }
+
+    /// The backend's human-readable name (e.g. "Windows Terminal").
+    pub fn name(self) -> String {
+        get_backend(self).map_or_else(|_| self.key(), |backend| backend.name().to_string())
+    }
+}
+
+/// Return the built-in backend for a given target, with default options.
+pub fn get_backend(target: Target) -> Result<Box<dyn ThemeBackend>> {
+    static BUILTIN: OnceLock<registry::Registry> = OnceLock::new();
+    BUILTIN
+        .get_or_init(registry::Registry::builtin)
+        .get(&target.key(), &registry::BackendOptions::default())
 }
 
-/// Return the backend for a given target.
-pub fn get_backend(target: Target) -> Box<dyn ThemeBackend> {
-    match target {
-        Target::Ghostty => Box::new(ghostty::GhosttyBackend),
-        Target::Zellij => Box::new(zellij::ZellijBackend),
-        Target::Neovim => Box::new(neovim::NeovimBackend),
+/// Environment variable that moves every target's config directory, and
+/// nuri's own, under one directory; `--config-dir` sets it. The data and
+/// cache homes (`data_home`, `cache_home`) don't follow it.
+pub const CONFIG_DIR_ENV: &str = "NURI_CONFIG_DIR";
+
+/// Where an application keeps its config on each platform. Targets pick
+/// the one their application reads; `$NURI_CONFIG_DIR` overrides them all.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ConfigDir {
+    /// `$XDG_CONFIG_HOME` or `~/.config` everywhere, Windows included.
+    Xdg,
+    /// Like [`Xdg`](Self::Xdg), but `%APPDATA%` on Windows. Most targets.
+    XdgOrAppData,
+    /// Like [`Xdg`](Self::Xdg), but `%LOCALAPPDATA%` on Windows (Neovim).
+    XdgOrLocalAppData,
+    /// The platform's own: XDG on Linux, `~/Library/Application Support`
+    /// on macOS, `%APPDATA%` on Windows.
+    Native,
+}
+
+impl ConfigDir {
+    /// The directory on this machine.
+    pub(crate) fn resolve(self) -> PathBuf {
+        self.resolve_from(
+            std::env::var_os(CONFIG_DIR_ENV).map(PathBuf::from),
+            std::env::var_os("XDG_CONFIG_HOME").map(PathBuf::from),
+        )
+    }
+
+    /// [`resolve`](Self::resolve) with the two environment variables given.
+    fn resolve_from(self, override_dir: Option<PathBuf>, xdg: Option<PathBuf>) -> PathBuf {
+        if let Some(dir) = override_dir.filter(|d| !d.as_os_str().is_empty()) {
+            return dir;
+        }
+        let platform = match self {
+            ConfigDir::Xdg => None,
+            ConfigDir::XdgOrAppData if cfg!(windows) => dirs::config_dir(),
+            ConfigDir::XdgOrLocalAppData if cfg!(windows) => dirs::config_local_dir(),
+            ConfigDir::Native if cfg!(any(windows, target_os = "macos")) => dirs::config_dir(),
+            _ => None,
+        };
+        platform
+            .or(xdg)
+            .unwrap_or_else(|| home_dir().join(".config"))
+    }
+}
+
+/// The config home most targets install into: `$NURI_CONFIG_DIR`, then
+/// `$XDG_CONFIG_HOME`, then `~/.config` (`%APPDATA%` on Windows).
+pub(crate) fn config_home() -> PathBuf {
+    ConfigDir::XdgOrAppData.resolve()
+}
+
+/// Resolve the cache home (`$XDG_CACHE_HOME`, falling back to the
+/// platform's: `~/.cache`, `~/Library/Caches` or `%LOCALAPPDATA%`).
+pub(crate) fn cache_home() -> PathBuf {
+    std::env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .or_else(dirs::cache_dir)
+        .unwrap_or_else(|| home_dir().join(".cache"))
+}
+
+/// Resolve the XDG data home (`$XDG_DATA_HOME`, falling back to `~/.local/share`).
+pub(crate) fn data_home() -> PathBuf {
+    std::env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| home_dir().join(".local").join("share"))
+}
+
+/// The user's home directory, or `~` if it can't be found.
+pub(crate) fn home_dir() -> PathBuf {
+    dirs::home_dir().unwrap_or_else(|| PathBuf::from("~"))
+}
+
+/// A dark palette with a different color in every slot, so backend tests
+/// catch a color written under the wrong name.
+#[cfg(test)]
+pub(crate) fn test_palette() -> AnsiPalette {
+    let hex = |hex: &str| crate::color::Color::from_hex(hex).expect("valid hex");
+    AnsiPalette {
+        slots: [
+            "#1d2021", "#cc241d", "#98971a", "#d79921", "#458588", "#b16286", "#689d6a", "#a89984",
+            "#928374", "#fb4934", "#b8bb26", "#fabd2f", "#83a598", "#d3869b", "#8ec07c", "#ebdbb2",
+        ]
+        .map(hex),
+        background: hex("#282828"),
+        foreground: hex("#fbf1c7"),
+        cursor_color: hex("#fe8019"),
+        cursor_text: hex("#32302f"),
+        selection_bg: hex("#504945"),
+        selection_fg: hex("#f9f5d7"),
+    }
+}
+
+/// [`test_palette`] with a light background.
+#[cfg(test)]
+pub(crate) fn light_test_palette() -> AnsiPalette {
+    let p = test_palette();
+    AnsiPalette {
+        background: p.foreground,
+        foreground: p.background,
+        cursor_color: p.cursor_text,
+        cursor_text: p.cursor_color,
+        selection_bg: p.selection_fg,
+        selection_fg: p.selection_bg,
+        ..p
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::color::Color;
 
     #[test]
     fn get_backend_returns_correct_name() {
-        assert_eq!(get_backend(Target::Ghostty).name(), "Ghostty");
-        assert_eq!(get_backend(Target::Zellij).name(), "Zellij");
-        assert_eq!(get_backend(Target::Neovim).name(), "Neovim");
+        assert_eq!(get_backend(Target::Ghostty).unwrap().name(), "Ghostty");
+        assert_eq!(get_backend(Target::Zellij).unwrap().name(), "Zellij");
+        assert_eq!(get_backend(Target::Neovim).unwrap().name(), "Neovim");
+        assert_eq!(get_backend(Target::Alacritty).unwrap().name(), "Alacritty");
+        assert_eq!(get_backend(Target::Wezterm).unwrap().name(), "WezTerm");
+        assert_eq!(
+            get_backend(Target::WindowsTerminal).unwrap().name(),
+            "Windows Terminal"
+        );
+        assert_eq!(get_backend(Target::Base16).unwrap().name(), "Base16");
+        assert_eq!(get_backend(Target::Base24).unwrap().name(), "Base24");
+        assert_eq!(get_backend(Target::Pywal).unwrap().name(), "pywal");
+        assert_eq!(get_backend(Target::Rofi).unwrap().name(), "Rofi");
+        assert_eq!(get_backend(Target::Waybar).unwrap().name(), "Waybar");
+        assert_eq!(get_backend(Target::Hyprland).unwrap().name(), "Hyprland");
+        assert_eq!(get_backend(Target::Sway).unwrap().name(), "Sway");
+        assert_eq!(get_backend(Target::Dunst).unwrap().name(), "Dunst");
+        assert_eq!(get_backend(Target::Mako).unwrap().name(), "Mako");
+        assert_eq!(get_backend(Target::Gtk).unwrap().name(), "GTK");
+        assert_eq!(get_backend(Target::Qt).unwrap().name(), "Qt");
+        assert_eq!(get_backend(Target::Konsole).unwrap().name(), "Konsole");
+        assert_eq!(
+            get_backend(Target::GnomeTerminal).unwrap().name(),
+            "GNOME Terminal"
+        );
+        assert_eq!(get_backend(Target::Lazygit).unwrap().name(), "lazygit");
+        assert_eq!(get_backend(Target::Delta).unwrap().name(), "delta");
+        assert_eq!(get_backend(Target::Btop).unwrap().name(), "btop");
+        assert_eq!(get_backend(Target::Zathura).unwrap().name(), "Zathura");
+        assert_eq!(
+            get_backend(Target::Qutebrowser).unwrap().name(),
+            "qutebrowser"
+        );
+        assert_eq!(get_backend(Target::Dircolors).unwrap().name(), "dircolors");
+        assert_eq!(get_backend(Target::Kakoune).unwrap().name(), "Kakoune");
+        assert_eq!(get_backend(Target::Jetbrains).unwrap().name(), "JetBrains");
+        assert_eq!(get_backend(Target::Obsidian).unwrap().name(), "Obsidian");
+        assert_eq!(get_backend(Target::Discord).unwrap().name(), "Discord");
+        assert_eq!(get_backend(Target::Yazi).unwrap().name(), "yazi");
+        assert_eq!(get_backend(Target::Ranger).unwrap().name(), "ranger");
+        assert_eq!(get_backend(Target::Lf).unwrap().name(), "lf");
+        assert_eq!(get_backend(Target::Cava).unwrap().name(), "cava");
+        assert_eq!(get_backend(Target::Ncmpcpp).unwrap().name(), "ncmpcpp");
+        assert_eq!(get_backend(Target::Newsboat).unwrap().name(), "newsboat");
+        assert_eq!(get_backend(Target::Env).unwrap().name(), "env");
+        assert_eq!(get_backend(Target::Json).unwrap().name(), "JSON");
+    }
+
+    #[test]
+    fn every_backend_writes_the_marker() {
+        let palette = AnsiPalette {
+            slots: [Color::new(0, 0, 0); 16],
+            background: Color::new(0, 0, 0),
+            foreground: Color::new(255, 255, 255),
+            cursor_color: Color::new(255, 255, 255),
+            cursor_text: Color::new(0, 0, 0),
+            selection_bg: Color::new(50, 50, 50),
+            selection_fg: Color::new(255, 255, 255),
+        };
+        for &target in Target::all() {
+            let output = get_backend(target).unwrap().serialize(&palette, "test");
+            assert!(is_generated(&output), "{target:?} output lacks the marker");
+        }
+        assert!(!is_generated("background = #000000\n"));
+    }
+
+    #[test]
+    fn config_dir_override_beats_xdg() {
+        let xdg = || Some(PathBuf::from("/xdg"));
+        for dir in [
+            ConfigDir::Xdg,
+            ConfigDir::XdgOrAppData,
+            ConfigDir::XdgOrLocalAppData,
+            ConfigDir::Native,
+        ] {
+            assert_eq!(
+                dir.resolve_from(Some(PathBuf::from("/dotfiles")), xdg()),
+                Path::new("/dotfiles")
+            );
+            // An empty override is no override
+            let unset = dir.resolve_from(Some(PathBuf::new()), xdg());
+            assert_ne!(unset, Path::new(""));
+        }
+        assert_eq!(ConfigDir::Xdg.resolve_from(None, xdg()), Path::new("/xdg"));
+        assert_eq!(
+            ConfigDir::Xdg.resolve_from(None, None),
+            home_dir().join(".config")
+        );
+        if cfg!(target_os = "linux") {
+            assert_eq!(
+                ConfigDir::Native.resolve_from(None, xdg()),
+                Path::new("/xdg")
+            );
+        }
+    }
+
+    #[test]
+    fn refuse_keeps_existing_file() {
+        let dir = std::env::temp_dir().join("nuri-test-overwrite-refuse");
+        let _ = std::fs::remove_dir_all(&dir);
+        let path = dir.join("theme.json");
+        let backend = get_backend(Target::Json).unwrap();
+        let palette = AnsiPalette {
+            slots: [Color::new(0, 0, 0); 16],
+            background: Color::new(0, 0, 0),
+            foreground: Color::new(255, 255, 255),
+            cursor_color: Color::new(255, 255, 255),
+            cursor_text: Color::new(0, 0, 0),
+            selection_bg: Color::new(50, 50, 50),
+            selection_fg: Color::new(255, 255, 255),
+        };
+
+        backend
+            .install_at_with(&palette, "a", &path, Overwrite::Refuse)
+            .unwrap();
+        let first = std::fs::read_to_string(&path).unwrap();
+        let err = backend
+            .install_at_with(&palette, "b", &path, Overwrite::Refuse)
+            .unwrap_err();
+        assert!(err.to_string().contains("already exists"), "{err}");
+        assert!(matches!(err, NuriError::ThemeExists { .. }), "{err:?}");
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), first);
+
+        backend
+            .install_at_with(&palette, "b", &path, Overwrite::Allow)
+            .unwrap();
+        assert_ne!(std::fs::read_to_string(&path).unwrap(), first);
+
+        // A file where the theme's directory should be
+        let err = backend
+            .install_at(&palette, "c", &path.join("theme.json"))
+            .unwrap_err();
+        assert!(
+            matches!(&err, NuriError::InstallPathNotWritable { path: p, .. } if *p == path),
+            "{err:?}"
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn fixed_path_files_name_their_theme() {
+        let dir = std::env::temp_dir().join("nuri-test-fixed-path");
+        let palette = AnsiPalette {
+            slots: [Color::new(0, 0, 0); 16],
+            background: Color::new(0, 0, 0),
+            foreground: Color::new(255, 255, 255),
+            cursor_color: Color::new(255, 255, 255),
+            cursor_text: Color::new(0, 0, 0),
+            selection_bg: Color::new(50, 50, 50),
+            selection_fg: Color::new(255, 255, 255),
+        };
+        let fixed: Vec<Target> = Target::all()
+            .iter()
+            .copied()
+            .filter(|&t| get_backend(t).unwrap().fixed_path())
+            .collect();
+        assert!(fixed.contains(&Target::Waybar) && fixed.contains(&Target::Pywal));
+        assert!(!fixed.contains(&Target::Ghostty));
+        for target in fixed {
+            let _ = std::fs::remove_dir_all(&dir);
+            let backend = get_backend(target).unwrap();
+            let file_name = backend.install_path("x").unwrap();
+            let path = dir.join(file_name.file_name().unwrap());
+            backend.install_at(&palette, "sunset", &path).unwrap();
+
+            let content = std::fs::read_to_string(&path).unwrap();
+            assert_eq!(
+                generated_name(&content).as_deref(),
+                Some("sunset"),
+                "{target:?}"
+            );
+            assert_eq!(
+                installed_by(backend.as_ref(), &dir),
+                vec![("sunset".to_string(), path.clone())]
+            );
+            let companions: Vec<String> = backend
+                .companions(&palette, "sunset")
+                .into_iter()
+                .map(|(name, _)| name)
+                .collect();
+            assert_eq!(backend.companion_names(), companions, "{target:?}");
+
+            uninstall(backend.as_ref(), &path).unwrap();
+            assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0, "{target:?}");
+        }
+        let _ = std::fs::remove_dir_all(&dir);
+
+        // Only the file nuri wrote counts, not the rest of the directory
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("colors.css"), "@define-color bg #000000;\n").unwrap();
+        std::fs::write(dir.join("style.css"), "/* Generated by nuri */\n").unwrap();
+        assert!(installed_by(&waybar::WaybarBackend, &dir).is_empty());
+        let _ = std::fs::remove_dir_all(&dir);
     }
 }
@@ -1,12 +1,12 @@
-use std::path::{Path, PathBuf};
+use std::path::PathBuf;
 
-use anyhow::{Context, Result};
+use anyhow::Result;
 use palette::Oklch;
 
 use crate::color::Color;
 use crate::pipeline::assign::AnsiPalette;
 
-use super::ThemeBackend;
+use super::{ConfigDir, ThemeBackend};
 
 /// Neovim colorscheme backend (Lua format).
 pub struct NeovimBackend;
@@ -58,22 +58,9 @@ impl ThemeBackend for NeovimBackend {
         out
     }
 
-    fn install(&self, palette: &AnsiPalette, theme_name: &str) -> Result<PathBuf> {
+    fn install_path(&self, theme_name: &str) -> crate::error::Result<PathBuf> {
         let safe_name = sanitize_name(theme_name);
-        let dir = colors_dir()?;
-        std::fs::create_dir_all(&dir)
-            .with_context(|| format!("failed to create colors directory: {}", dir.display()))?;
-
-        let path = dir.join(format!("{}.lua", safe_name));
-        self.write_to(palette, theme_name, &path)?;
-        Ok(path)
-    }
-
-    fn write_to(&self, palette: &AnsiPalette, theme_name: &str, path: &Path) -> Result<()> {
-        let content = self.serialize(palette, theme_name);
-        std::fs::write(path, content)
-            .with_context(|| format!("failed to write theme to {}", path.display()))?;
-        Ok(())
+        Ok(colors_dir()?.join(format!("{}.lua", safe_name)))
     }
 
     fn extension(&self) -> &str {
@@ -95,9 +82,10 @@ fn sanitize_name(name: &str) -> String {
         })
         .collect();
     if sanitized != name.to_lowercase() {
-        eprintln!(
-            "warning: theme name sanitized for Neovim: '{}' -> '{}'",
-            name, sanitized
+        log::warn!(
+            "theme name sanitized for Neovim: '{}' -> '{}'",
+            name,
+            sanitized
         );
     }
     sanitized
@@ -324,15 +312,14 @@ fn write_treesitter_groups(out: &mut String) {
     }
 }
 
-/// Resolve the Neovim plugins directory.
+/// Resolve the Neovim plugins directory (`stdpath("config")`, which is
+/// under `%LOCALAPPDATA%` on Windows).
 fn colors_dir() -> Result<PathBuf> {
-    let config_home = std::env::var("XDG_CONFIG_HOME")
-        .map(PathBuf::from)
-        .unwrap_or_else(|_| {
-            let home = std::env::var("HOME").unwrap_or_else(|_| "~".to_string());
-            PathBuf::from(home).join(".config")
-        });
-    Ok(config_home.join("nvim").join("lua").join("plugins"))
+    Ok(ConfigDir::XdgOrLocalAppData
+        .resolve()
+        .join("nvim")
+        .join("lua")
+        .join("plugins"))
 }
 
 #[cfg(test)]
@@ -0,0 +1,142 @@
+use std::path::PathBuf;
+
+use crate::color::Color;
+use crate::error::Result;
+use crate::pipeline::assign::AnsiPalette;
+
+use super::{config_home, ThemeBackend, GENERATED_MARKER};
+
+/// Dunst notification colors, as a `dunstrc.d` drop-in (dunst 1.7+) that
+/// overrides the colors of the main `dunstrc`.
+pub struct DunstBackend;
+
+/// Mako notification colors, for `include=~/.config/mako/colors` in the mako
+/// config.
+pub struct MakoBackend;
+
+/// Background, text and frame colors for low, normal and critical urgency.
+fn urgencies(palette: &AnsiPalette) -> [(Color, Color, Color); 3] {
+    let p = palette;
+    [
+        (p.background, p.slots[8], p.slots[8]),
+        (p.background, p.foreground, p.slots[4]),
+        (p.background, p.foreground, p.slots[1]),
+    ]
+}
+
+impl ThemeBackend for DunstBackend {
+    fn name(&self) -> &str {
+        "Dunst"
+    }
+
+    fn serialize(&self, palette: &AnsiPalette, _theme_name: &str) -> String {
+        let mut out = String::new();
+        out.push_str(&format!("# {GENERATED_MARKER}\n\n"));
+        out.push_str("[global]\n");
+        out.push_str("    separator_color = frame\n");
+        out.push_str(&format!(
+            "    highlight = \"{}\"\n",
+            palette.slots[4].to_hex()
+        ));
+        let sections = ["urgency_low", "urgency_normal", "urgency_critical"];
+        for (section, (background, foreground, frame)) in sections.iter().zip(urgencies(palette)) {
+            out.push_str(&format!("\n[{section}]\n"));
+            out.push_str(&format!("    background = \"{}\"\n", background.to_hex()));
+            out.push_str(&format!("    foreground = \"{}\"\n", foreground.to_hex()));
+            out.push_str(&format!("    frame_color = \"{}\"\n", frame.to_hex()));
+        }
+        out
+    }
+
+    fn install_path(&self, _theme_name: &str) -> Result<PathBuf> {
+        Ok(config_home()
+            .join("dunst")
+            .join("dunstrc.d")
+            .join("90-nuri.conf"))
+    }
+
+    fn fixed_path(&self) -> bool {
+        true
+    }
+
+    fn extension(&self) -> &str {
+        ".conf"
+    }
+}
+
+impl ThemeBackend for MakoBackend {
+    fn name(&self) -> &str {
+        "Mako"
+    }
+
+    fn serialize(&self, palette: &AnsiPalette, _theme_name: &str) -> String {
+        let [low, normal, critical] = urgencies(palette);
+        let mut out = String::new();
+        out.push_str(&format!("# {GENERATED_MARKER}\n\n"));
+        push_mako_colors(&mut out, normal);
+        out.push_str(&format!(
+            "progress-color=over {}\n",
+            palette.selection_bg.to_hex()
+        ));
+        out.push_str("\n[urgency=low]\n");
+        push_mako_colors(&mut out, low);
+        out.push_str("\n[urgency=critical]\n");
+        push_mako_colors(&mut out, critical);
+        out
+    }
+
+    fn install_path(&self, _theme_name: &str) -> Result<PathBuf> {
+        Ok(config_home().join("mako").join("colors"))
+    }
+
+    fn fixed_path(&self) -> bool {
+        true
+    }
+
+    fn extension(&self) -> &str {
+        ""
+    }
+}
+
+fn push_mako_colors(out: &mut String, (background, text, border): (Color, Color, Color)) {
+    out.push_str(&format!("background-color={}\n", background.to_hex()));
+    out.push_str(&format!("text-color={}\n", text.to_hex()));
+    out.push_str(&format!("border-color={}\n", border.to_hex()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backends::test_palette;
+
+    #[test]
+    fn dunst_colors_each_urgency() {
+        let p = test_palette();
+        let output = DunstBackend.serialize(&p, "test");
+        assert!(output.contains("[urgency_critical]\n    background = \"#282828\"\n"));
+        for section in ["[urgency_low]", "[urgency_normal]", "[urgency_critical]"] {
+            let body = output.split(section).nth(1).expect(section);
+            let body = body.split("\n[").next().unwrap();
+            for key in ["background", "foreground", "frame_color"] {
+                assert!(
+                    body.contains(&format!("    {key} = \"#")),
+                    "{section} {key}"
+                );
+            }
+        }
+        let critical = output.split("[urgency_critical]").nth(1).unwrap();
+        assert!(critical.contains(&format!("frame_color = \"{}\"", p.slots[1].to_hex())));
+    }
+
+    #[test]
+    fn mako_overrides_by_urgency() {
+        let p = test_palette();
+        let output = MakoBackend.serialize(&p, "test");
+        assert!(output.contains("border-color=#458588\n"));
+        let (global, sections) = output.split_once("\n[urgency=low]").unwrap();
+        assert!(global.contains(&format!("border-color={}\n", p.slots[4].to_hex())));
+        assert!(global.contains(&format!("text-color={}\n", p.foreground.to_hex())));
+        let critical = sections.split("[urgency=critical]").nth(1).unwrap();
+        assert!(critical.contains(&format!("border-color={}\n", p.slots[1].to_hex())));
+    }
+}
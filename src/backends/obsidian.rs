@@ -0,0 +1,143 @@
+use std::path::PathBuf;
+
+use crate::color::Color;
+use crate::error::Result;
+use crate::pipeline::assign::AnsiPalette;
+
+use super::{config_home, ThemeBackend, GENERATED_MARKER};
+
+/// Obsidian CSS snippet backend: overrides the app's color variables in both
+/// the light and dark base themes.
+///
+/// Snippets live inside each vault, so installing writes one file to symlink
+/// into `<vault>/.obsidian/snippets/`.
+pub struct ObsidianBackend;
+
+impl ThemeBackend for ObsidianBackend {
+    fn name(&self) -> &str {
+        "Obsidian"
+    }
+
+    fn serialize(&self, palette: &AnsiPalette, _theme_name: &str) -> String {
+        let p = palette;
+        let (bg, fg) = (p.background, p.foreground);
+        let accent = p.slots[4];
+        let variables = [
+            ("background-primary", bg),
+            ("background-primary-alt", bg.mix(fg, 0.04)),
+            ("background-secondary", bg.mix(fg, 0.06)),
+            ("background-secondary-alt", bg.mix(fg, 0.1)),
+            ("background-modifier-border", bg.mix(fg, 0.15)),
+            ("background-modifier-hover", bg.mix(fg, 0.08)),
+            ("text-normal", fg),
+            ("text-muted", fg.mix(p.slots[8], 0.5)),
+            ("text-faint", p.slots[8]),
+            ("text-accent", accent),
+            ("text-accent-hover", p.slots[12]),
+            ("text-on-accent", bg),
+            ("text-selection", p.selection_bg),
+            ("text-highlight-bg", p.slots[3].mix(bg, 0.6)),
+            ("text-error", p.slots[1]),
+            ("interactive-accent", accent),
+            ("interactive-accent-hover", p.slots[12]),
+            ("caret-color", p.cursor_color),
+            ("color-red", p.slots[1]),
+            ("color-orange", p.slots[1].mix(p.slots[3], 0.5)),
+            ("color-yellow", p.slots[3]),
+            ("color-green", p.slots[2]),
+            ("color-cyan", p.slots[6]),
+            ("color-blue", p.slots[4]),
+            ("color-purple", p.slots[5]),
+            ("color-pink", p.slots[13]),
+            ("h1-color", accent),
+            ("h2-color", p.slots[5]),
+            ("h3-color", p.slots[6]),
+            ("link-color", accent),
+            ("link-external-color", p.slots[6]),
+            ("tag-color", p.slots[2]),
+        ];
+
+        let mut out = String::new();
+        out.push_str(&format!("/* {GENERATED_MARKER} */\n\n"));
+        out.push_str("body.theme-dark,\nbody.theme-light {\n");
+        for (name, color) in variables {
+            out.push_str(&format!("  --{name}: {};\n", color.to_hex()));
+        }
+        // Accent tints are computed from the HSL components
+        let (h, s, l) = hsl(accent);
+        out.push_str(&format!("  --accent-h: {h};\n"));
+        out.push_str(&format!("  --accent-s: {s}%;\n"));
+        out.push_str(&format!("  --accent-l: {l}%;\n"));
+        out.push_str("}\n");
+        out
+    }
+
+    fn install_path(&self, _theme_name: &str) -> Result<PathBuf> {
+        Ok(config_home().join("nuri").join("obsidian").join("nuri.css"))
+    }
+
+    fn fixed_path(&self) -> bool {
+        true
+    }
+
+    fn extension(&self) -> &str {
+        ".css"
+    }
+}
+
+/// Hue in degrees and saturation and lightness in percent, all rounded.
+fn hsl(color: Color) -> (u32, u32, u32) {
+    let [r, g, b] = [color.r, color.g, color.b].map(|c| c as f32 / 255.0);
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let l = (max + min) / 2.0;
+    let d = max - min;
+    if d == 0.0 {
+        return (0, 0, (l * 100.0).round() as u32);
+    }
+    let s = d / (1.0 - (2.0 * l - 1.0).abs());
+    let h = if max == r {
+        ((g - b) / d).rem_euclid(6.0)
+    } else if max == g {
+        (b - r) / d + 2.0
+    } else {
+        (r - g) / d + 4.0
+    };
+    (
+        (h * 60.0).round() as u32 % 360,
+        (s * 100.0).round() as u32,
+        (l * 100.0).round() as u32,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backends::test_palette;
+
+    #[test]
+    fn overrides_both_base_themes() {
+        let p = test_palette();
+        let output = ObsidianBackend.serialize(&p, "test");
+        assert!(output.contains("body.theme-dark,\nbody.theme-light {\n"));
+        assert!(output.contains("  --interactive-accent: #458588;\n"));
+        assert!(output.contains(&format!(
+            "  --background-primary: {};\n",
+            p.background.to_hex()
+        )));
+        assert!(output.contains(&format!("  --text-normal: {};\n", p.foreground.to_hex())));
+        assert!(output.contains(&format!(
+            "  --interactive-accent: {};\n",
+            p.slots[4].to_hex()
+        )));
+        assert!(output.trim_end().ends_with('}'));
+    }
+
+    #[test]
+    fn hsl_components() {
+        assert_eq!(hsl(Color::new(255, 0, 0)), (0, 100, 50));
+        assert_eq!(hsl(Color::new(0, 0, 255)), (240, 100, 50));
+        assert_eq!(hsl(Color::new(128, 128, 128)), (0, 0, 50));
+        assert_eq!(hsl(Color::new(51, 204, 153)), (160, 60, 50));
+    }
+}
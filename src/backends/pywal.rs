@@ -0,0 +1,149 @@
+use std::path::PathBuf;
+
+use crate::error::Result;
+use crate::pipeline::assign::AnsiPalette;
+
+use super::{home_dir, ThemeBackend, GENERATED_MARKER};
+
+/// pywal cache backend: `colors.json`, plus `colors.sh` and the plain
+/// `colors` list installed alongside it, so tools that read pywal's cache
+/// pick up the palette.
+pub struct PywalBackend;
+
+impl ThemeBackend for PywalBackend {
+    fn name(&self) -> &str {
+        "pywal"
+    }
+
+    fn serialize(&self, palette: &AnsiPalette, _theme_name: &str) -> String {
+        let p = palette;
+        let mut out = String::new();
+        out.push_str("{\n");
+        out.push_str(&format!("    \"$comment\": \"{GENERATED_MARKER}\",\n"));
+        // pywal writes "None" when the colors did not come from its own wallpaper
+        out.push_str("    \"wallpaper\": \"None\",\n");
+        out.push_str("    \"alpha\": \"100\",\n");
+        out.push_str("    \"special\": {\n");
+        out.push_str(&format!(
+            "        \"background\": \"{}\",\n",
+            p.background.to_hex()
+        ));
+        out.push_str(&format!(
+            "        \"foreground\": \"{}\",\n",
+            p.foreground.to_hex()
+        ));
+        out.push_str(&format!(
+            "        \"cursor\": \"{}\"\n",
+            p.cursor_color.to_hex()
+        ));
+        out.push_str("    },\n");
+        out.push_str("    \"colors\": {\n");
+        for (i, color) in p.slots.iter().enumerate() {
+            let comma = if i < 15 { "," } else { "" };
+            out.push_str(&format!(
+                "        \"color{i}\": \"{}\"{comma}\n",
+                color.to_hex()
+            ));
+        }
+        out.push_str("    }\n");
+        out.push_str("}\n");
+        out
+    }
+
+    fn install_path(&self, _theme_name: &str) -> Result<PathBuf> {
+        Ok(cache_dir().join("colors.json"))
+    }
+
+    fn fixed_path(&self) -> bool {
+        true
+    }
+
+    fn extension(&self) -> &str {
+        ".json"
+    }
+
+    fn companions(&self, palette: &AnsiPalette, _theme_name: &str) -> Vec<(String, String)> {
+        vec![
+            ("colors.sh".to_string(), shell_exports(palette)),
+            ("colors".to_string(), plain_list(palette)),
+        ]
+    }
+
+    fn companion_names(&self) -> Vec<&str> {
+        vec!["colors.sh", "colors"]
+    }
+}
+
+/// `colors.sh`: every color as an exported shell variable, for sourcing from
+/// shell startup files and scripts.
+fn shell_exports(palette: &AnsiPalette) -> String {
+    let p = palette;
+    let mut out = String::new();
+    out.push_str(&format!("# {GENERATED_MARKER}\n\n"));
+    out.push_str("# Special\n");
+    out.push_str(&format!("export background='{}'\n", p.background.to_hex()));
+    out.push_str(&format!("export foreground='{}'\n", p.foreground.to_hex()));
+    out.push_str(&format!("export cursor='{}'\n", p.cursor_color.to_hex()));
+    out.push_str("\n# Colors\n");
+    for (i, color) in p.slots.iter().enumerate() {
+        out.push_str(&format!("export color{i}='{}'\n", color.to_hex()));
+    }
+    out
+}
+
+/// `colors`: the sixteen slots one per line, read by line number, so it has
+/// no header.
+fn plain_list(palette: &AnsiPalette) -> String {
+    palette
+        .slots
+        .iter()
+        .map(|color| format!("{}\n", color.to_hex()))
+        .collect()
+}
+
+/// pywal's cache directory (`$PYWAL_CACHE_DIR`, falling back to `~/.cache/wal`).
+fn cache_dir() -> PathBuf {
+    std::env::var("PYWAL_CACHE_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| home_dir().join(".cache").join("wal"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backends::test_palette;
+
+    #[test]
+    fn colors_json_matches_pywal_layout() {
+        let p = test_palette();
+        let out = PywalBackend.serialize(&p, "sunset");
+        let json: serde_json::Value = serde_json::from_str(&out).unwrap();
+        assert_eq!(json["special"]["background"], p.background.to_hex());
+        assert_eq!(json["special"]["cursor"], p.cursor_color.to_hex());
+        assert_eq!(json["colors"]["color0"], p.slots[0].to_hex());
+        assert_eq!(json["colors"]["color15"], p.slots[15].to_hex());
+        assert_eq!(json["colors"].as_object().unwrap().len(), 16);
+        assert_eq!(json["colors"]["color4"], "#458588");
+        assert_eq!(json["special"]["foreground"], "#fbf1c7");
+    }
+
+    #[test]
+    fn installs_shell_exports_and_plain_list() {
+        let p = test_palette();
+        let dir = std::env::temp_dir().join("nuri-test-pywal");
+        let _ = std::fs::remove_dir_all(&dir);
+        PywalBackend
+            .install_at(&p, "sunset", &dir.join("colors.json"))
+            .unwrap();
+
+        let sh = std::fs::read_to_string(dir.join("colors.sh")).unwrap();
+        assert!(sh.contains(&format!("export color4='{}'\n", p.slots[4].to_hex())));
+        assert!(sh.contains(&format!("export background='{}'\n", p.background.to_hex())));
+        let list = std::fs::read_to_string(dir.join("colors")).unwrap();
+        let lines: Vec<&str> = list.lines().collect();
+        assert_eq!(lines.len(), 16);
+        assert_eq!(lines[1], p.slots[1].to_hex());
+        assert!(dir.join("colors.json").is_file());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
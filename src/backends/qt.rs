@@ -0,0 +1,112 @@
+use std::path::PathBuf;
+
+use crate::color::Color;
+use crate::error::Result;
+use crate::pipeline::assign::AnsiPalette;
+
+use super::{config_home, ThemeBackend, GENERATED_MARKER};
+
+/// qt5ct color scheme backend. qt6ct reads the same format; copy the file to
+/// `~/.config/qt6ct/colors/` for Qt 6 apps.
+pub struct QtBackend;
+
+impl ThemeBackend for QtBackend {
+    fn name(&self) -> &str {
+        "Qt"
+    }
+
+    fn serialize(&self, palette: &AnsiPalette, _theme_name: &str) -> String {
+        let p = palette;
+        let active = roles(p);
+        let mut disabled = active;
+        // WindowText, Text, ButtonText and Highlight fade out when disabled
+        for role in [0, 6, 8] {
+            disabled[role] = p.slots[8];
+        }
+        disabled[12] = p.selection_bg;
+
+        let mut out = String::new();
+        out.push_str(&format!("# {GENERATED_MARKER}\n"));
+        out.push_str("[ColorScheme]\n");
+        out.push_str(&format!("active_colors={}\n", join(&active)));
+        out.push_str(&format!("disabled_colors={}\n", join(&disabled)));
+        out.push_str(&format!("inactive_colors={}\n", join(&active)));
+        out
+    }
+
+    fn install_path(&self, theme_name: &str) -> Result<PathBuf> {
+        Ok(config_home()
+            .join("qt5ct")
+            .join("colors")
+            .join(format!("{theme_name}.conf")))
+    }
+
+    fn extension(&self) -> &str {
+        ".conf"
+    }
+}
+
+/// Colors for each `QPalette::ColorRole` in qt5ct's order: WindowText,
+/// Button, Light, Midlight, Dark, Mid, Text, BrightText, ButtonText, Base,
+/// Window, Shadow, Highlight, HighlightedText, Link, LinkVisited,
+/// AlternateBase, NoRole, ToolTipBase, ToolTipText, PlaceholderText.
+fn roles(palette: &AnsiPalette) -> [Color; 21] {
+    let p = palette;
+    let black = Color::new(0, 0, 0);
+    let raised = p.background.mix(p.foreground, 0.06);
+    [
+        p.foreground,
+        raised,
+        p.background.mix(p.foreground, 0.2),
+        p.background.mix(p.foreground, 0.1),
+        p.background.mix(black, 0.5),
+        p.background.mix(black, 0.3),
+        p.foreground,
+        p.slots[15],
+        p.foreground,
+        p.background,
+        raised,
+        black,
+        p.slots[4],
+        p.background,
+        p.slots[4],
+        p.slots[5],
+        p.background.mix(p.foreground, 0.04),
+        p.background,
+        raised,
+        p.foreground,
+        p.slots[8],
+    ]
+}
+
+fn join(colors: &[Color]) -> String {
+    colors
+        .iter()
+        .map(|c| c.to_hex())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backends::test_palette;
+
+    #[test]
+    fn writes_every_color_group() {
+        let p = test_palette();
+        let output = QtBackend.serialize(&p, "test");
+        assert!(output.contains("[ColorScheme]\n"));
+        for group in ["active_colors=", "disabled_colors=", "inactive_colors="] {
+            let line = output
+                .lines()
+                .find(|l| l.starts_with(group))
+                .unwrap_or_else(|| panic!("missing {group}"));
+            let values: Vec<&str> = line[group.len()..].split(", ").collect();
+            assert_eq!(values.len(), 21, "{line}");
+            assert!(values.iter().all(|v| Color::from_hex(v).is_ok()), "{line}");
+        }
+        let active = output.lines().find(|l| l.starts_with("active")).unwrap();
+        assert!(active.starts_with(&format!("active_colors={}, ", p.foreground.to_hex())));
+    }
+}
@@ -0,0 +1,103 @@
+use std::path::PathBuf;
+
+use crate::color::Color;
+use crate::error::Result;
+use crate::pipeline::assign::AnsiPalette;
+
+use super::{config_home, ThemeBackend, GENERATED_MARKER};
+
+/// qutebrowser color backend: `c.colors.*` settings for tabs, the status bar,
+/// completion and hints, for `config.source('nuri-colors.py')` in `config.py`.
+pub struct QutebrowserBackend;
+
+impl ThemeBackend for QutebrowserBackend {
+    fn name(&self) -> &str {
+        "qutebrowser"
+    }
+
+    fn serialize(&self, palette: &AnsiPalette, _theme_name: &str) -> String {
+        let p = palette;
+        let (bg, fg) = (p.background, p.foreground);
+        let raised = bg.mix(fg, 0.06);
+        let (red, green, yellow, blue) = (p.slots[1], p.slots[2], p.slots[3], p.slots[4]);
+        let settings: [(&str, Color); 33] = [
+            ("tabs.bar.bg", bg),
+            ("tabs.even.bg", raised),
+            ("tabs.odd.bg", raised),
+            ("tabs.even.fg", p.slots[8]),
+            ("tabs.odd.fg", p.slots[8]),
+            ("tabs.selected.even.bg", bg),
+            ("tabs.selected.odd.bg", bg),
+            ("tabs.selected.even.fg", fg),
+            ("tabs.selected.odd.fg", fg),
+            ("tabs.indicator.start", blue),
+            ("tabs.indicator.stop", green),
+            ("tabs.indicator.error", red),
+            ("statusbar.normal.bg", raised),
+            ("statusbar.normal.fg", fg),
+            ("statusbar.insert.bg", green),
+            ("statusbar.insert.fg", bg),
+            ("statusbar.command.bg", raised),
+            ("statusbar.command.fg", fg),
+            ("statusbar.url.fg", fg),
+            ("statusbar.url.success.http.fg", yellow),
+            ("statusbar.url.success.https.fg", green),
+            ("statusbar.url.error.fg", red),
+            ("completion.fg", fg),
+            ("completion.odd.bg", bg),
+            ("completion.even.bg", raised),
+            ("completion.category.bg", bg),
+            ("completion.category.fg", blue),
+            ("completion.item.selected.bg", p.selection_bg),
+            ("completion.item.selected.fg", p.selection_fg),
+            ("completion.match.fg", yellow),
+            ("hints.bg", yellow),
+            ("hints.fg", bg),
+            ("hints.match.fg", blue),
+        ];
+
+        let mut out = String::new();
+        out.push_str(&format!("# {GENERATED_MARKER}\n"));
+        for (setting, color) in settings {
+            out.push_str(&format!("c.colors.{setting} = \"{}\"\n", color.to_hex()));
+        }
+        out
+    }
+
+    fn install_path(&self, _theme_name: &str) -> Result<PathBuf> {
+        Ok(config_home().join("qutebrowser").join("nuri-colors.py"))
+    }
+
+    fn fixed_path(&self) -> bool {
+        true
+    }
+
+    fn extension(&self) -> &str {
+        ".py"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backends::test_palette;
+
+    #[test]
+    fn maps_ui_parts_to_palette() {
+        let p = test_palette();
+        let output = QutebrowserBackend.serialize(&p, "test");
+        assert!(output.contains("c.colors.hints.bg = \"#d79921\"\n"));
+        for (setting, color) in [
+            ("tabs.bar.bg", p.background),
+            ("statusbar.normal.fg", p.foreground),
+            ("completion.item.selected.bg", p.selection_bg),
+            ("hints.bg", p.slots[3]),
+        ] {
+            assert!(
+                output.contains(&format!("c.colors.{setting} = \"{}\"\n", color.to_hex())),
+                "missing {setting}"
+            );
+        }
+        assert!(output.lines().skip(1).all(|l| l.starts_with("c.colors.")));
+    }
+}
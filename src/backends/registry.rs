@@ -0,0 +1,317 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+
+use super::template::TemplateBackend;
+use super::{
+    alacritty, base16, btop, delta, dircolors, discord, env, file_managers, ghostty,
+    gnome_terminal, gtk, jetbrains, json, kakoune, konsole, lazygit, neovim, notifications,
+    obsidian, pywal, qt, qutebrowser, rofi, tui_apps, waybar, wezterm, windows_terminal, wm,
+    zathura, zellij, Target, ThemeBackend,
+};
+use crate::error::NuriError;
+
+/// Per-backend options passed to a backend's factory when it is built.
+#[derive(Debug, Clone, Default)]
+pub struct BackendOptions {
+    /// Template file, for template backends.
+    pub template: Option<PathBuf>,
+    /// Also write colors 16-255, for backends that support them.
+    pub extended: bool,
+}
+
+/// Builds a backend from its options.
+pub type Factory = Box<dyn Fn(&BackendOptions) -> Result<Box<dyn ThemeBackend>> + Send + Sync>;
+
+/// Backends by target name. Starts from the built-in targets; library users
+/// can [`register`](Self::register) their own, and user templates are picked
+/// up with [`discover_templates`](Self::discover_templates).
+#[derive(Default)]
+pub struct Registry {
+    factories: BTreeMap<String, Factory>,
+    /// Discovered template files by name.
+    templates: BTreeMap<String, PathBuf>,
+}
+
+impl std::fmt::Debug for Registry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Registry")
+            .field("names", &self.names().collect::<Vec<_>>())
+            .field("templates", &self.templates)
+            .finish()
+    }
+}
+
+impl Registry {
+    /// An empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A registry holding every built-in [`Target`].
+    pub fn builtin() -> Self {
+        let mut registry = Self::new();
+        registry.register(Target::Ghostty.key(), |options| {
+            Ok(if options.extended {
+                Box::new(ghostty::GhosttyExtendedBackend)
+            } else {
+                Box::new(ghostty::GhosttyBackend)
+            })
+        });
+        registry.register(Target::Zellij.key(), |_| {
+            Ok(Box::new(zellij::ZellijBackend))
+        });
+        registry.register(Target::Neovim.key(), |_| {
+            Ok(Box::new(neovim::NeovimBackend))
+        });
+        registry.register(Target::Alacritty.key(), |_| {
+            Ok(Box::new(alacritty::AlacrittyBackend))
+        });
+        registry.register(Target::Wezterm.key(), |options| {
+            Ok(if options.extended {
+                Box::new(wezterm::WeztermExtendedBackend)
+            } else {
+                Box::new(wezterm::WeztermBackend)
+            })
+        });
+        registry.register(Target::WindowsTerminal.key(), |_| {
+            Ok(Box::new(windows_terminal::WindowsTerminalBackend))
+        });
+        registry.register(Target::Konsole.key(), |_| {
+            Ok(Box::new(konsole::KonsoleBackend))
+        });
+        registry.register(Target::GnomeTerminal.key(), |_| {
+            Ok(Box::new(gnome_terminal::GnomeTerminalBackend))
+        });
+        registry.register(Target::Base16.key(), |_| {
+            Ok(Box::new(base16::Base16Backend))
+        });
+        registry.register(Target::Base24.key(), |_| {
+            Ok(Box::new(base16::Base24Backend))
+        });
+        registry.register(Target::Pywal.key(), |_| Ok(Box::new(pywal::PywalBackend)));
+        registry.register(Target::Rofi.key(), |_| Ok(Box::new(rofi::RofiBackend)));
+        registry.register(Target::Waybar.key(), |_| {
+            Ok(Box::new(waybar::WaybarBackend))
+        });
+        registry.register(Target::Hyprland.key(), |_| {
+            Ok(Box::new(wm::HyprlandBackend))
+        });
+        registry.register(Target::Sway.key(), |_| Ok(Box::new(wm::SwayBackend)));
+        registry.register(Target::Dunst.key(), |_| {
+            Ok(Box::new(notifications::DunstBackend))
+        });
+        registry.register(Target::Mako.key(), |_| {
+            Ok(Box::new(notifications::MakoBackend))
+        });
+        registry.register(Target::Gtk.key(), |_| Ok(Box::new(gtk::GtkBackend)));
+        registry.register(Target::Qt.key(), |_| Ok(Box::new(qt::QtBackend)));
+        registry.register(Target::Lazygit.key(), |_| {
+            Ok(Box::new(lazygit::LazygitBackend))
+        });
+        registry.register(Target::Delta.key(), |_| Ok(Box::new(delta::DeltaBackend)));
+        registry.register(Target::Btop.key(), |_| Ok(Box::new(btop::BtopBackend)));
+        registry.register(Target::Zathura.key(), |_| {
+            Ok(Box::new(zathura::ZathuraBackend))
+        });
+        registry.register(Target::Qutebrowser.key(), |_| {
+            Ok(Box::new(qutebrowser::QutebrowserBackend))
+        });
+        registry.register(Target::Dircolors.key(), |_| {
+            Ok(Box::new(dircolors::DircolorsBackend))
+        });
+        registry.register(Target::Kakoune.key(), |_| {
+            Ok(Box::new(kakoune::KakouneBackend))
+        });
+        registry.register(Target::Jetbrains.key(), |_| {
+            Ok(Box::new(jetbrains::JetbrainsBackend))
+        });
+        registry.register(Target::Obsidian.key(), |_| {
+            Ok(Box::new(obsidian::ObsidianBackend))
+        });
+        registry.register(Target::Discord.key(), |_| {
+            Ok(Box::new(discord::DiscordBackend))
+        });
+        registry.register(Target::Yazi.key(), |_| {
+            Ok(Box::new(file_managers::YaziBackend))
+        });
+        registry.register(Target::Ranger.key(), |_| {
+            Ok(Box::new(file_managers::RangerBackend))
+        });
+        registry.register(Target::Lf.key(), |_| Ok(Box::new(file_managers::LfBackend)));
+        registry.register(Target::Cava.key(), |_| Ok(Box::new(tui_apps::CavaBackend)));
+        registry.register(Target::Ncmpcpp.key(), |_| {
+            Ok(Box::new(tui_apps::NcmpcppBackend))
+        });
+        registry.register(Target::Newsboat.key(), |_| {
+            Ok(Box::new(tui_apps::NewsboatBackend))
+        });
+        registry.register(Target::Env.key(), |_| Ok(Box::new(env::EnvBackend)));
+        registry.register(Target::Json.key(), |_| Ok(Box::new(json::JsonBackend)));
+        registry.register(Target::Template.key(), |options| {
+            Ok(match &options.template {
+                Some(path) => Box::new(TemplateBackend::load(path)?),
+                None => Box::new(TemplateBackend::default()),
+            })
+        });
+        registry
+    }
+
+    /// Register (or replace) the backend called `name`.
+    pub fn register(
+        &mut self,
+        name: impl Into<String>,
+        factory: impl Fn(&BackendOptions) -> Result<Box<dyn ThemeBackend>> + Send + Sync + 'static,
+    ) {
+        self.factories.insert(name.into(), Box::new(factory));
+    }
+
+    /// Register every file in `dir` as a template backend named after the
+    /// file up to its first dot (`kitty.conf.tmpl` -> `kitty`). Names already
+    /// taken are skipped, so templates never shadow built-in targets. A
+    /// missing directory registers nothing.
+    pub fn discover_templates(&mut self, dir: &Path) {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return;
+        };
+        let mut paths: Vec<PathBuf> = entries
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| p.is_file())
+            .collect();
+        paths.sort();
+        for path in paths {
+            let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+            let name = file_name.split('.').next().unwrap_or_default().to_string();
+            if name.is_empty() || self.factories.contains_key(&name) {
+                continue;
+            }
+            let template = path.clone();
+            self.register(name.clone(), move |_| {
+                Ok(Box::new(TemplateBackend::load(&template)?))
+            });
+            self.templates.insert(name, path);
+        }
+    }
+
+    /// Build the backend called `name`.
+    pub fn get(
+        &self,
+        name: &str,
+        options: &BackendOptions,
+    ) -> crate::error::Result<Box<dyn ThemeBackend>> {
+        let Some(factory) = self.factories.get(name) else {
+            return Err(NuriError::UnknownTarget {
+                name: name.to_string(),
+                available: self.names().map(str::to_string).collect(),
+            });
+        };
+        Ok(factory(options)?)
+    }
+
+    /// Whether a backend is registered as `name`.
+    pub fn contains(&self, name: &str) -> bool {
+        self.factories.contains_key(name)
+    }
+
+    /// Names of all registered backends, sorted.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.factories.keys().map(String::as_str)
+    }
+
+    /// Discovered templates and their files, sorted by name.
+    pub fn templates(&self) -> impl Iterator<Item = (&str, &Path)> {
+        self.templates
+            .iter()
+            .map(|(name, path)| (name.as_str(), path.as_path()))
+    }
+
+    /// File of the discovered template called `name`.
+    pub fn template(&self, name: &str) -> Option<&Path> {
+        self.templates.get(name).map(PathBuf::as_path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::assign::AnsiPalette;
+
+    struct Plain;
+
+    impl ThemeBackend for Plain {
+        fn name(&self) -> &str {
+            "Plain"
+        }
+        fn serialize(&self, palette: &AnsiPalette, _theme_name: &str) -> String {
+            palette.background.to_hex()
+        }
+        fn install_path(&self, theme_name: &str) -> crate::error::Result<PathBuf> {
+            Ok(PathBuf::from(theme_name))
+        }
+        fn extension(&self) -> &str {
+            ""
+        }
+    }
+
+    #[test]
+    fn builtin_registers_every_target() {
+        let registry = Registry::builtin();
+        for &target in Target::all() {
+            assert!(
+                registry
+                    .get(&target.key(), &BackendOptions::default())
+                    .is_ok(),
+                "{target:?}"
+            );
+        }
+        let err = registry
+            .get("kitty", &BackendOptions::default())
+            .err()
+            .unwrap();
+        assert!(
+            matches!(&err, NuriError::UnknownTarget { name, available }
+                if name == "kitty" && available.iter().any(|n| n == "ghostty")),
+            "{err:?}"
+        );
+    }
+
+    #[test]
+    fn registers_custom_backends() {
+        let mut registry = Registry::builtin();
+        registry.register("plain", |_| Ok(Box::new(Plain)));
+        let backend = registry.get("plain", &BackendOptions::default()).unwrap();
+        assert_eq!(backend.name(), "Plain");
+        assert!(registry.names().any(|n| n == "plain"));
+    }
+
+    #[test]
+    fn discovers_user_templates() {
+        let dir = std::env::temp_dir().join("nuri-test-registry-templates");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("kitty.conf.tmpl"), "bg {{background}}\n").unwrap();
+        std::fs::write(dir.join("ghostty.tmpl"), "{{background}}\n").unwrap();
+
+        let mut registry = Registry::builtin();
+        registry.discover_templates(&dir);
+        let kitty = registry.get("kitty", &BackendOptions::default()).unwrap();
+        assert_eq!(kitty.name(), "Template");
+        assert_eq!(kitty.extension(), ".conf");
+        assert_eq!(
+            registry.template("kitty"),
+            Some(dir.join("kitty.conf.tmpl").as_path())
+        );
+        // Built-in names are never shadowed
+        assert_eq!(
+            registry
+                .get("ghostty", &BackendOptions::default())
+                .unwrap()
+                .name(),
+            "Ghostty"
+        );
+        assert_eq!(registry.templates().count(), 1);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
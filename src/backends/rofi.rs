@@ -0,0 +1,158 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+
+use crate::pipeline::assign::AnsiPalette;
+
+use super::{config_home, ThemeBackend, GENERATED_MARKER};
+
+/// Rofi launcher theme backend (`.rasi`).
+///
+/// Defines the color variables rofi's default theme reads (`background`,
+/// `selected-normal-background`, `urgent-foreground`, ...) and applies them to
+/// the window and list elements, so the theme works on its own or imported
+/// into a layout theme.
+pub struct RofiBackend;
+
+impl ThemeBackend for RofiBackend {
+    fn name(&self) -> &str {
+        "Rofi"
+    }
+
+    fn serialize(&self, palette: &AnsiPalette, _theme_name: &str) -> String {
+        let p = palette;
+        let variables = [
+            ("background", p.background.to_hex()),
+            ("foreground", p.foreground.to_hex()),
+            ("lightbg", p.selection_bg.to_hex()),
+            ("lightfg", p.slots[15].to_hex()),
+            ("red", p.slots[1].to_hex()),
+            ("blue", p.slots[4].to_hex()),
+            ("border-color", p.slots[4].to_hex()),
+            ("separatorcolor", p.slots[8].to_hex()),
+            ("background-color", "transparent".to_string()),
+            ("normal-background", "@background".to_string()),
+            ("normal-foreground", "@foreground".to_string()),
+            ("alternate-normal-background", "@lightbg".to_string()),
+            ("alternate-normal-foreground", "@foreground".to_string()),
+            ("selected-normal-background", p.slots[4].to_hex()),
+            ("selected-normal-foreground", "@background".to_string()),
+            ("urgent-background", "@background".to_string()),
+            ("urgent-foreground", p.slots[1].to_hex()),
+            ("alternate-urgent-background", "@lightbg".to_string()),
+            ("alternate-urgent-foreground", p.slots[1].to_hex()),
+            ("selected-urgent-background", p.slots[1].to_hex()),
+            ("selected-urgent-foreground", "@background".to_string()),
+            ("active-background", "@background".to_string()),
+            ("active-foreground", p.slots[2].to_hex()),
+            ("alternate-active-background", "@lightbg".to_string()),
+            ("alternate-active-foreground", p.slots[2].to_hex()),
+            ("selected-active-background", p.slots[2].to_hex()),
+            ("selected-active-foreground", "@background".to_string()),
+        ];
+
+        let mut out = String::new();
+        out.push_str(&format!("// {GENERATED_MARKER}\n"));
+        out.push_str("* {\n");
+        for (name, value) in &variables {
+            out.push_str(&format!("    {:<29}{value};\n", format!("{name}:")));
+        }
+        out.push_str("}\n");
+
+        let rules: [(&str, &[&str]); 6] = [
+            (
+                "window",
+                &[
+                    "background-color: @background;",
+                    "border-color: @border-color;",
+                ],
+            ),
+            ("mainbox", &["background-color: @background;"]),
+            ("inputbar", &["text-color: @normal-foreground;"]),
+            ("prompt", &["text-color: @blue;"]),
+            ("entry", &["text-color: @normal-foreground;"]),
+            ("listview", &["border-color: @separatorcolor;"]),
+        ];
+        for (selector, declarations) in rules {
+            out.push_str(&format!("\n{selector} {{\n"));
+            for declaration in declarations {
+                out.push_str(&format!("    {declaration}\n"));
+            }
+            out.push_str("}\n");
+        }
+        for state in ["normal", "urgent", "active"] {
+            for (row, prefix) in [
+                ("normal", ""),
+                ("alternate", "alternate-"),
+                ("selected", "selected-"),
+            ] {
+                out.push_str(&format!("\nelement {row}.{state} {{\n"));
+                out.push_str(&format!(
+                    "    background-color: @{prefix}{state}-background;\n"
+                ));
+                out.push_str(&format!("    text-color: @{prefix}{state}-foreground;\n"));
+                out.push_str("}\n");
+            }
+        }
+        out.push_str("\nelement-text, element-icon {\n    text-color: inherit;\n}\n");
+
+        out
+    }
+
+    fn install_path(&self, theme_name: &str) -> crate::error::Result<PathBuf> {
+        Ok(themes_dir()?.join(format!("{}.rasi", theme_name)))
+    }
+
+    fn extension(&self) -> &str {
+        ".rasi"
+    }
+}
+
+/// Resolve the rofi themes directory.
+fn themes_dir() -> Result<PathBuf> {
+    Ok(config_home().join("rofi").join("themes"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backends::test_palette;
+
+    #[test]
+    fn defines_state_colors() {
+        let p = test_palette();
+        let output = RofiBackend.serialize(&p, "test");
+        assert!(output.starts_with("// Generated by nuri\n* {\n"));
+        for (name, color) in [
+            ("background", p.background),
+            ("foreground", p.foreground),
+            ("selected-normal-background", p.slots[4]),
+            ("urgent-foreground", p.slots[1]),
+            ("active-foreground", p.slots[2]),
+        ] {
+            let line = output
+                .lines()
+                .find(|l| l.trim_start().starts_with(&format!("{name}:")))
+                .unwrap_or_else(|| panic!("missing {name}"));
+            assert!(line.ends_with(&format!("{};", color.to_hex())), "{line}");
+        }
+    }
+
+    #[test]
+    fn every_referenced_variable_is_defined() {
+        let output = RofiBackend.serialize(&test_palette(), "test");
+        let defined: Vec<&str> = output
+            .lines()
+            .filter_map(|l| l.trim().split_once(':'))
+            .map(|(name, _)| name)
+            .collect();
+        for reference in output.split('@').skip(1) {
+            let name: String = reference
+                .chars()
+                .take_while(|c| c.is_ascii_alphanumeric() || *c == '-')
+                .collect();
+            assert!(defined.contains(&name.as_str()), "@{name} is undefined");
+        }
+        assert_eq!(output.matches('{').count(), output.matches('}').count());
+    }
+}
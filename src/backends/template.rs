@@ -0,0 +1,245 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, bail, Context, Result};
+
+use crate::color::Color;
+use crate::config::{is_slot_name, slot_color};
+use crate::pipeline::assign::AnsiPalette;
+
+use super::ThemeBackend;
+
+/// Template used when no `--template` file is given: every color as a
+/// `key=value` line, handy as a starting point for writing your own.
+const DEFAULT_TEMPLATE: &str = "\
+# Generated by nuri
+# {{name}}
+background={{background}}
+foreground={{foreground}}
+cursor={{cursor}}
+cursor-text={{cursor-text}}
+selection-bg={{selection-bg}}
+selection-fg={{selection-fg}}
+color0={{color0}}
+color1={{color1}}
+color2={{color2}}
+color3={{color3}}
+color4={{color4}}
+color5={{color5}}
+color6={{color6}}
+color7={{color7}}
+color8={{color8}}
+color9={{color9}}
+color10={{color10}}
+color11={{color11}}
+color12={{color12}}
+color13={{color13}}
+color14={{color14}}
+color15={{color15}}
+";
+
+/// User-supplied template backend.
+///
+/// Placeholders are `{{color0}}`-`{{color15}}`, any `[slots]` name
+/// (`{{red}}`, `{{background}}`, `{{selection-bg}}`, ...) and `{{name}}`.
+/// Colors render as `#rrggbb`; a suffix picks another format:
+/// `.strip` (`rrggbb`), `.rgb` (`r,g,b`), or `.r`/`.g`/`.b` (one decimal channel).
+pub struct TemplateBackend {
+    segments: Vec<Segment>,
+    extension: String,
+}
+
+enum Segment {
+    Text(String),
+    Name,
+    Color { slot: String, format: Format },
+}
+
+#[derive(Clone, Copy)]
+enum Format {
+    Hex,
+    Strip,
+    Rgb,
+    Channel(usize),
+}
+
+impl Default for TemplateBackend {
+    fn default() -> Self {
+        Self::parse(DEFAULT_TEMPLATE, "").expect("built-in template is valid")
+    }
+}
+
+impl TemplateBackend {
+    /// Load a template file. The output extension is the template's, minus a
+    /// trailing `.tmpl`: `kitty.conf.tmpl` writes `.conf` files.
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read template: {}", path.display()))?;
+        let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+        let stem = file_name.strip_suffix(".tmpl").unwrap_or(&file_name);
+        let extension = Path::new(stem)
+            .extension()
+            .map(|ext| format!(".{}", ext.to_string_lossy()))
+            .unwrap_or_default();
+        Self::parse(&text, &extension)
+            .with_context(|| format!("invalid template: {}", path.display()))
+    }
+
+    /// Parse template text, rejecting unknown placeholders up front so
+    /// rendering cannot fail.
+    pub fn parse(text: &str, extension: &str) -> Result<Self> {
+        let mut segments = Vec::new();
+        let mut rest = text;
+        while let Some(start) = rest.find("{{") {
+            if start > 0 {
+                segments.push(Segment::Text(rest[..start].to_string()));
+            }
+            let line = text[..text.len() - rest.len() + start]
+                .matches('\n')
+                .count()
+                + 1;
+            let Some(len) = rest[start + 2..].find("}}") else {
+                bail!("line {line}: unclosed '{{{{'");
+            };
+            let placeholder = rest[start + 2..start + 2 + len].trim();
+            segments
+                .push(parse_placeholder(placeholder).with_context(|| {
+                    format!("line {line}: unknown placeholder '{placeholder}'")
+                })?);
+            rest = &rest[start + 2 + len + 2..];
+        }
+        if !rest.is_empty() {
+            segments.push(Segment::Text(rest.to_string()));
+        }
+        Ok(Self {
+            segments,
+            extension: extension.to_string(),
+        })
+    }
+}
+
+fn parse_placeholder(placeholder: &str) -> Option<Segment> {
+    if placeholder == "name" {
+        return Some(Segment::Name);
+    }
+    let (slot, format) = match placeholder.split_once('.') {
+        Some((slot, format)) => (slot, format),
+        None => (placeholder, "hex"),
+    };
+    let format = match format {
+        "hex" => Format::Hex,
+        "strip" => Format::Strip,
+        "rgb" => Format::Rgb,
+        "r" => Format::Channel(0),
+        "g" => Format::Channel(1),
+        "b" => Format::Channel(2),
+        _ => return None,
+    };
+    let slot = match slot.strip_prefix("color") {
+        Some(index) if index.parse::<usize>().is_ok_and(|i| i < 16) => index.to_string(),
+        _ => slot.to_string(),
+    };
+    is_slot_name(&slot).then_some(())?;
+    Some(Segment::Color { slot, format })
+}
+
+fn render_color(color: Color, format: Format) -> String {
+    match format {
+        Format::Hex => color.to_hex(),
+        Format::Strip => color.to_hex().trim_start_matches('#').to_string(),
+        Format::Rgb => format!("{},{},{}", color.r, color.g, color.b),
+        Format::Channel(i) => [color.r, color.g, color.b][i].to_string(),
+    }
+}
+
+impl ThemeBackend for TemplateBackend {
+    fn name(&self) -> &str {
+        "Template"
+    }
+
+    fn serialize(&self, palette: &AnsiPalette, theme_name: &str) -> String {
+        let mut out = String::new();
+        for segment in &self.segments {
+            match segment {
+                Segment::Text(text) => out.push_str(text),
+                Segment::Name => out.push_str(theme_name),
+                Segment::Color { slot, format } => {
+                    if let Some(color) = slot_color(palette, slot) {
+                        out.push_str(&render_color(color, *format));
+                    }
+                }
+            }
+        }
+        out
+    }
+
+    fn install_path(&self, _theme_name: &str) -> crate::error::Result<PathBuf> {
+        Err(anyhow!(
+            "the template target has no standard directory; set `template` under \
+             [output-dirs] in the config, or use --output"
+        )
+        .into())
+    }
+
+    fn extension(&self) -> &str {
+        &self.extension
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backends::test_palette;
+
+    #[test]
+    fn renders_placeholders_and_formats() {
+        let p = test_palette();
+        let backend = TemplateBackend::parse(
+            "bg={{ background }} fg={{foreground.strip}} blue={{color4.rgb}} \
+             r={{red.r}} sel={{selection-bg}} name={{name}}\n",
+            ".conf",
+        )
+        .unwrap();
+        let out = backend.serialize(&p, "sunset");
+        let c4 = p.slots[4];
+        assert_eq!(
+            out,
+            format!(
+                "bg={} fg={} blue={},{},{} r={} sel={} name=sunset\n",
+                p.background.to_hex(),
+                p.foreground.to_hex().trim_start_matches('#'),
+                c4.r,
+                c4.g,
+                c4.b,
+                p.slots[1].r,
+                p.selection_bg.to_hex()
+            )
+        );
+        assert_eq!(backend.extension(), ".conf");
+    }
+
+    #[test]
+    fn rejects_unknown_placeholders() {
+        let Err(err) = TemplateBackend::parse("a\nb {{color16}}\n", "") else {
+            panic!("accepted {{{{color16}}}}");
+        };
+        assert!(format!("{err:#}").contains("line 2"), "{err:#}");
+        assert!(TemplateBackend::parse("{{red.hsl}}", "").is_err());
+        assert!(TemplateBackend::parse("{{background", "").is_err());
+    }
+
+    #[test]
+    fn extension_comes_from_template_name() {
+        let dir = std::env::temp_dir().join("nuri-test-template-ext");
+        std::fs::create_dir_all(&dir).unwrap();
+        for (file, ext) in [
+            ("kitty.conf.tmpl", ".conf"),
+            ("colors.tmpl", ""),
+            ("x.css", ".css"),
+        ] {
+            let path = dir.join(file);
+            std::fs::write(&path, "{{background}}").unwrap();
+            assert_eq!(TemplateBackend::load(&path).unwrap().extension(), ext);
+        }
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
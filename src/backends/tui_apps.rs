@@ -0,0 +1,232 @@
+use std::path::PathBuf;
+
+use crate::color::Color;
+use crate::error::Result;
+use crate::pipeline::assign::AnsiPalette;
+
+use super::{config_home, ThemeBackend, GENERATED_MARKER};
+
+/// ncmpcpp's names for ANSI colors 0-7.
+const COLOR_NAMES: [&str; 8] = [
+    "black", "red", "green", "yellow", "blue", "magenta", "cyan", "white",
+];
+
+/// Number of cava gradient stops.
+const CAVA_STOPS: usize = 8;
+
+/// cava visualizer colors: a bar gradient ramping through the palette's most
+/// vivid accent, as a config to run with `cava -p ~/.config/cava/nuri.conf`
+/// (cava fills in defaults for everything else).
+pub struct CavaBackend;
+
+/// ncmpcpp color settings, loaded after the main config with
+/// `ncmpcpp -c ~/.config/ncmpcpp/config -c ~/.config/ncmpcpp/nuri-colors`.
+/// ncmpcpp only knows the terminal's ANSI colors, so it follows an installed
+/// terminal theme.
+pub struct NcmpcppBackend;
+
+/// newsboat `color` and `highlight` lines for `include nuri-colors` in the
+/// newsboat config. Like ncmpcpp, it uses the terminal's ANSI colors.
+pub struct NewsboatBackend;
+
+impl ThemeBackend for CavaBackend {
+    fn name(&self) -> &str {
+        "cava"
+    }
+
+    fn serialize(&self, palette: &AnsiPalette, _theme_name: &str) -> String {
+        let p = palette;
+        let [accent, _] = vivid_accents(p);
+        let mut out = String::new();
+        out.push_str(&format!("# {GENERATED_MARKER}\n\n"));
+        out.push_str("[color]\n");
+        out.push_str(&format!("background = '{}'\n", p.background.to_hex()));
+        out.push_str(&format!("foreground = '{}'\n", p.slots[accent].to_hex()));
+        out.push_str("gradient = 1\n");
+        out.push_str(&format!("gradient_count = {CAVA_STOPS}\n"));
+        for (i, color) in gradient(p, p.slots[accent]).iter().enumerate() {
+            out.push_str(&format!(
+                "gradient_color_{} = '{}'\n",
+                i + 1,
+                color.to_hex()
+            ));
+        }
+        out
+    }
+
+    fn install_path(&self, _theme_name: &str) -> Result<PathBuf> {
+        Ok(config_home().join("cava").join("nuri.conf"))
+    }
+
+    fn fixed_path(&self) -> bool {
+        true
+    }
+
+    fn extension(&self) -> &str {
+        ".conf"
+    }
+}
+
+impl ThemeBackend for NcmpcppBackend {
+    fn name(&self) -> &str {
+        "ncmpcpp"
+    }
+
+    fn serialize(&self, palette: &AnsiPalette, _theme_name: &str) -> String {
+        let [primary, secondary] = vivid_accents(palette).map(|slot| COLOR_NAMES[slot]);
+        let settings = [
+            ("colors_enabled", "yes".to_string()),
+            ("main_window_color", "default".to_string()),
+            ("header_window_color", primary.to_string()),
+            ("volume_color", primary.to_string()),
+            ("state_line_color", secondary.to_string()),
+            ("state_flags_color", secondary.to_string()),
+            ("progressbar_color", "black".to_string()),
+            ("progressbar_elapsed_color", primary.to_string()),
+            ("statusbar_color", "default".to_string()),
+            ("statusbar_time_color", primary.to_string()),
+            ("player_state_color", secondary.to_string()),
+            ("alternative_ui_separator_color", secondary.to_string()),
+            ("window_border_color", primary.to_string()),
+            ("active_window_border", secondary.to_string()),
+            ("color1", "white".to_string()),
+            ("color2", primary.to_string()),
+            ("current_item_prefix", format!("\"$({primary})$r\"")),
+            ("current_item_suffix", "\"$/r$(end)\"".to_string()),
+        ];
+        let mut out = String::new();
+        out.push_str(&format!("# {GENERATED_MARKER}\n\n"));
+        for (key, value) in settings {
+            out.push_str(&format!("{key} = {value}\n"));
+        }
+        out
+    }
+
+    fn install_path(&self, _theme_name: &str) -> Result<PathBuf> {
+        Ok(config_home().join("ncmpcpp").join("nuri-colors"))
+    }
+
+    fn fixed_path(&self) -> bool {
+        true
+    }
+
+    fn extension(&self) -> &str {
+        ""
+    }
+}
+
+impl ThemeBackend for NewsboatBackend {
+    fn name(&self) -> &str {
+        "newsboat"
+    }
+
+    fn serialize(&self, palette: &AnsiPalette, _theme_name: &str) -> String {
+        let accents = vivid_accents(palette);
+        let [primary, secondary] = accents.map(|slot| format!("color{slot}"));
+        let bright = format!("color{}", accents[0] + 8);
+        let colors = [
+            ("background", "default", "default", ""),
+            ("listnormal", "default", "default", ""),
+            ("listnormal_unread", bright.as_str(), "default", " bold"),
+            ("listfocus", "color0", primary.as_str(), ""),
+            ("listfocus_unread", "color0", primary.as_str(), " bold"),
+            ("info", "color0", secondary.as_str(), " bold"),
+            ("article", "default", "default", ""),
+        ];
+        let mut out = String::new();
+        out.push_str(&format!("# {GENERATED_MARKER}\n\n"));
+        for (element, fg, bg, attr) in colors {
+            out.push_str(&format!("color {element} {fg} {bg}{attr}\n"));
+        }
+        out.push('\n');
+        out.push_str(&format!(
+            "highlight article \"^(Feed|Title|Author|Link|Date):\" {primary} default bold\n"
+        ));
+        out.push_str(&format!(
+            "highlight article \"https?://[^ ]+\" {secondary} default underline\n"
+        ));
+        out
+    }
+
+    fn install_path(&self, _theme_name: &str) -> Result<PathBuf> {
+        Ok(config_home().join("newsboat").join("nuri-colors"))
+    }
+
+    fn fixed_path(&self) -> bool {
+        true
+    }
+
+    fn extension(&self) -> &str {
+        ""
+    }
+}
+
+/// The two accent slots (1-6) with the most chroma, most vivid first: the
+/// colors that carry the wallpaper's character.
+fn vivid_accents(palette: &AnsiPalette) -> [usize; 2] {
+    let mut accents: Vec<usize> = (1..=6).collect();
+    accents.sort_by(|&a, &b| {
+        let chroma = |slot: usize| palette.slots[slot].to_oklch().chroma;
+        chroma(b).total_cmp(&chroma(a))
+    });
+    [accents[0], accents[1]]
+}
+
+/// Bar colors from bottom to top: from the accent dimmed toward the
+/// background up to the accent lifted toward the foreground.
+fn gradient(palette: &AnsiPalette, accent: Color) -> [Color; CAVA_STOPS] {
+    let low = accent.mix(palette.background, 0.45);
+    let high = accent.mix(palette.foreground, 0.35);
+    let half = (CAVA_STOPS - 1) as f32 / 2.0;
+    std::array::from_fn(|i| {
+        let t = i as f32 / half;
+        if t <= 1.0 {
+            low.mix(accent, t)
+        } else {
+            accent.mix(high, t - 1.0)
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backends::test_palette;
+
+    #[test]
+    fn vivid_accents_are_distinct_and_ordered() {
+        let p = test_palette();
+        let [a, b] = vivid_accents(&p);
+        assert_ne!(a, b);
+        assert!((1..=6).contains(&a) && (1..=6).contains(&b));
+        for slot in 1..=6 {
+            assert!(p.slots[a].to_oklch().chroma >= p.slots[slot].to_oklch().chroma);
+        }
+    }
+
+    #[test]
+    fn cava_gradient_ramps_through_the_accent() {
+        let p = test_palette();
+        let output = CavaBackend.serialize(&p, "test");
+        assert!(output.contains(&format!("gradient_count = {CAVA_STOPS}\n")));
+        assert!(output.contains(&format!("gradient_color_{CAVA_STOPS} = '")));
+        assert!(!output.contains(&format!("gradient_color_{} ", CAVA_STOPS + 1)));
+
+        let accent = p.slots[vivid_accents(&p)[0]];
+        let stops = gradient(&p, accent);
+        let contrast = |c: Color| Color::contrast_ratio(&c, &p.background);
+        assert!(stops
+            .windows(2)
+            .all(|w| contrast(w[0]) <= contrast(w[1]) + 0.01));
+    }
+
+    #[test]
+    fn ncmpcpp_and_newsboat_use_the_vivid_accent() {
+        let p = test_palette();
+        let [primary, _] = vivid_accents(&p);
+        let ncmpcpp = NcmpcppBackend.serialize(&p, "test");
+        assert!(ncmpcpp.contains(&format!("header_window_color = {}\n", COLOR_NAMES[primary])));
+        let newsboat = NewsboatBackend.serialize(&p, "test");
+        assert!(newsboat.contains(&format!("color listfocus color0 color{primary}\n")));
+    }
+}
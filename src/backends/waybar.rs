@@ -0,0 +1,88 @@
+use std::path::PathBuf;
+
+use crate::color::Color;
+use crate::error::Result;
+use crate::pipeline::assign::AnsiPalette;
+
+use super::{config_home, ThemeBackend, GENERATED_MARKER};
+
+/// Waybar color backend: a `colors.css` of GTK `@define-color` entries to
+/// `@import` from a Waybar stylesheet.
+pub struct WaybarBackend;
+
+impl ThemeBackend for WaybarBackend {
+    fn name(&self) -> &str {
+        "Waybar"
+    }
+
+    fn serialize(&self, palette: &AnsiPalette, _theme_name: &str) -> String {
+        let mut out = String::new();
+        out.push_str(&format!("/* {GENERATED_MARKER} */\n\n"));
+        for (name, color) in semantic_colors(palette) {
+            out.push_str(&format!("@define-color {name} {};\n", color.to_hex()));
+        }
+        out.push('\n');
+        for (i, color) in palette.slots.iter().enumerate() {
+            out.push_str(&format!("@define-color color{i} {};\n", color.to_hex()));
+        }
+        out
+    }
+
+    fn install_path(&self, _theme_name: &str) -> Result<PathBuf> {
+        Ok(config_home().join("waybar").join("colors.css"))
+    }
+
+    fn fixed_path(&self) -> bool {
+        true
+    }
+
+    fn extension(&self) -> &str {
+        ".css"
+    }
+}
+
+/// Named colors for stylesheets that style by role rather than ANSI slot.
+fn semantic_colors(palette: &AnsiPalette) -> [(&'static str, Color); 8] {
+    let p = palette;
+    [
+        ("background", p.background),
+        ("foreground", p.foreground),
+        ("cursor", p.cursor_color),
+        ("selection_bg", p.selection_bg),
+        ("selection_fg", p.selection_fg),
+        ("accent", p.slots[4]),
+        ("warning", p.slots[3]),
+        ("critical", p.slots[1]),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backends::test_palette;
+
+    #[test]
+    fn defines_slots_and_semantic_names() {
+        let p = test_palette();
+        let output = WaybarBackend.serialize(&p, "test");
+
+        assert!(output.starts_with("/* Generated by nuri */\n"));
+        assert_eq!(output.matches("@define-color ").count(), 24);
+        assert!(output.contains(
+            "@define-color accent #458588;\n@define-color warning #d79921;\n\
+             @define-color critical #cc241d;\n"
+        ));
+        for (line, color) in [
+            ("@define-color background", p.background),
+            ("@define-color accent", p.slots[4]),
+            ("@define-color warning", p.slots[3]),
+            ("@define-color critical", p.slots[1]),
+            ("@define-color color15", p.slots[15]),
+        ] {
+            assert!(
+                output.contains(&format!("{line} {};\n", color.to_hex())),
+                "missing {line}"
+            );
+        }
+    }
+}
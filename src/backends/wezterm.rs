@@ -0,0 +1,220 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+
+use crate::color::Color;
+use crate::pipeline::assign::AnsiPalette;
+
+use super::{ConfigDir, ThemeBackend};
+
+/// WezTerm color scheme backend (Lua table).
+pub struct WeztermBackend;
+
+/// WezTerm backend that also writes colors 16-255 as `indexed` (`--extended`).
+pub struct WeztermExtendedBackend;
+
+impl ThemeBackend for WeztermBackend {
+    fn name(&self) -> &str {
+        "WezTerm"
+    }
+
+    fn serialize(&self, palette: &AnsiPalette, theme_name: &str) -> String {
+        serialize(palette, theme_name, false)
+    }
+
+    fn install_path(&self, theme_name: &str) -> crate::error::Result<PathBuf> {
+        Ok(colors_dir()?.join(format!("{}.lua", theme_name)))
+    }
+
+    fn extension(&self) -> &str {
+        ".lua"
+    }
+}
+
+impl ThemeBackend for WeztermExtendedBackend {
+    fn name(&self) -> &str {
+        "WezTerm"
+    }
+
+    fn serialize(&self, palette: &AnsiPalette, theme_name: &str) -> String {
+        serialize(palette, theme_name, true)
+    }
+
+    fn install_path(&self, theme_name: &str) -> crate::error::Result<PathBuf> {
+        WeztermBackend.install_path(theme_name)
+    }
+
+    fn extension(&self) -> &str {
+        ".lua"
+    }
+}
+
+/// Lua color scheme text; `extended` adds colors 16-255 as `indexed`.
+fn serialize(palette: &AnsiPalette, theme_name: &str, extended: bool) -> String {
+    let p = palette;
+    let mut out = String::new();
+
+    // Header with a usage hint, since WezTerm only auto-loads TOML schemes
+    out.push_str(&format!("-- nuri: {}\n", theme_name));
+    out.push_str("-- Generated by nuri\n");
+    out.push_str("-- Load it from wezterm.lua with:\n");
+    out.push_str(&format!(
+            "--   config.color_schemes = {{ [\"{0}\"] = dofile(wezterm.config_dir .. \"/colors/{0}.lua\") }}\n",
+            theme_name
+        ));
+    out.push_str(&format!("--   config.color_scheme = \"{}\"\n", theme_name));
+    out.push('\n');
+
+    out.push_str("return {\n");
+    let entries = [
+        ("foreground", p.foreground),
+        ("background", p.background),
+        ("cursor_bg", p.cursor_color),
+        ("cursor_fg", p.cursor_text),
+        ("cursor_border", p.cursor_color),
+        ("selection_fg", p.selection_fg),
+        ("selection_bg", p.selection_bg),
+    ];
+    for (key, color) in &entries {
+        out.push_str(&format!("  {} = \"{}\",\n", key, color.to_hex()));
+    }
+    write_color_list(&mut out, "ansi", &p.slots[0..8]);
+    write_color_list(&mut out, "brights", &p.slots[8..16]);
+    if extended {
+        out.push_str("  indexed = {\n");
+        for (i, color) in p.extended_colors().iter().enumerate() {
+            out.push_str(&format!("    [{}] = \"{}\",\n", i + 16, color.to_hex()));
+        }
+        out.push_str("  },\n");
+    }
+    out.push_str("}\n");
+
+    out
+}
+
+/// Write a Lua array of eight hex strings, e.g. `ansi = { "#000000", ... },`.
+fn write_color_list(out: &mut String, key: &str, colors: &[Color]) {
+    out.push_str(&format!("  {} = {{\n", key));
+    for color in colors {
+        out.push_str(&format!("    \"{}\",\n", color.to_hex()));
+    }
+    out.push_str("  },\n");
+}
+
+/// Resolve the WezTerm colors directory, which is under `~/.config` on
+/// Windows too.
+fn colors_dir() -> Result<PathBuf> {
+    Ok(ConfigDir::Xdg.resolve().join("wezterm").join("colors"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::ThemeMode;
+    use crate::pipeline::assign::assign_slots;
+    use crate::pipeline::extract::ExtractedColor;
+    use palette::Oklch;
+
+    fn make_extracted(l: f32, chroma: f32, hue: f32, weight: f32) -> ExtractedColor {
+        ExtractedColor {
+            color: Color::from_oklch(Oklch::new(l, chroma, hue)),
+            weight,
+        }
+    }
+
+    fn test_palette() -> AnsiPalette {
+        let colors = vec![
+            make_extracted(0.60, 0.20, 25.0, 0.12),
+            make_extracted(0.60, 0.20, 145.0, 0.12),
+            make_extracted(0.70, 0.20, 90.0, 0.12),
+            make_extracted(0.55, 0.20, 260.0, 0.12),
+            make_extracted(0.60, 0.20, 325.0, 0.12),
+            make_extracted(0.65, 0.20, 195.0, 0.10),
+            make_extracted(0.10, 0.01, 0.0, 0.15),
+            make_extracted(0.95, 0.01, 0.0, 0.15),
+        ];
+        assign_slots(&colors, ThemeMode::Dark)
+    }
+
+    #[test]
+    fn output_returns_table() {
+        let output = WeztermBackend.serialize(&test_palette(), "test");
+        assert!(output.starts_with("-- nuri: test\n"));
+        assert!(output.contains("\nreturn {\n"));
+        assert!(output.ends_with("}\n"));
+    }
+
+    #[test]
+    fn ansi_and_brights_have_eight_entries_in_slot_order() {
+        let palette = test_palette();
+        let output = WeztermBackend.serialize(&palette, "test");
+
+        let (_, rest) = output.split_once("  ansi = {\n").unwrap();
+        let (ansi, rest) = rest.split_once("  },\n").unwrap();
+        let (_, rest) = rest.split_once("  brights = {\n").unwrap();
+        let (brights, _) = rest.split_once("  },\n").unwrap();
+
+        let expected = |range: std::ops::Range<usize>| -> String {
+            palette.slots[range]
+                .iter()
+                .map(|c| format!("    \"{}\",\n", c.to_hex()))
+                .collect()
+        };
+        assert_eq!(ansi, expected(0..8));
+        assert_eq!(brights, expected(8..16));
+    }
+
+    #[test]
+    fn extended_adds_indexed_colors() {
+        let palette = test_palette();
+        let plain = WeztermBackend.serialize(&palette, "test");
+        assert!(!plain.contains("indexed"));
+        let output = WeztermExtendedBackend.serialize(&palette, "test");
+        let (_, indexed) = output.split_once("  indexed = {\n").unwrap();
+        assert_eq!(indexed.matches("    [").count(), 240);
+        let last = palette.extended_colors()[239].to_hex();
+        assert!(indexed.contains(&format!("    [255] = \"{last}\",\n")));
+    }
+
+    #[test]
+    fn special_colors_present() {
+        let palette = test_palette();
+        let output = WeztermBackend.serialize(&palette, "test");
+        for (key, color) in [
+            ("foreground", palette.foreground),
+            ("background", palette.background),
+            ("cursor_bg", palette.cursor_color),
+            ("selection_bg", palette.selection_bg),
+        ] {
+            let needle = format!("  {key} = \"{}\",", color.to_hex());
+            assert!(output.contains(&needle), "missing '{needle}'");
+        }
+    }
+
+    #[test]
+    fn balanced_braces() {
+        let output = WeztermBackend.serialize(&test_palette(), "test");
+        let body = output.split_once("return").unwrap().1;
+        assert_eq!(body.matches('{').count(), body.matches('}').count());
+    }
+
+    #[test]
+    fn install_creates_correct_path() {
+        let temp_dir = std::env::temp_dir().join("nuri-test-wezterm-install");
+        std::env::set_var("XDG_CONFIG_HOME", &temp_dir);
+
+        let backend = WeztermBackend;
+        let palette = test_palette();
+        let result = backend.install(&palette, "my-theme").unwrap();
+
+        let expected_path = temp_dir.join("wezterm").join("colors").join("my-theme.lua");
+        assert_eq!(result, expected_path);
+        assert!(expected_path.exists());
+
+        let content = std::fs::read_to_string(&expected_path).unwrap();
+        assert_eq!(content, backend.serialize(&palette, "my-theme"));
+
+        std::fs::remove_dir_all(&temp_dir).unwrap();
+        std::env::remove_var("XDG_CONFIG_HOME");
+    }
+}
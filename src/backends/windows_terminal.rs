@@ -0,0 +1,189 @@
+use std::path::PathBuf;
+
+use anyhow::{bail, Result};
+use serde::{Serialize, Serializer};
+
+use crate::pipeline::assign::AnsiPalette;
+
+use super::{ConfigDir, ThemeBackend, GENERATED_MARKER};
+
+/// Windows Terminal scheme keys for ANSI slots 0-15.
+const SLOT_KEYS: [&str; 16] = [
+    "black",
+    "red",
+    "green",
+    "yellow",
+    "blue",
+    "purple",
+    "cyan",
+    "white",
+    "brightBlack",
+    "brightRed",
+    "brightGreen",
+    "brightYellow",
+    "brightBlue",
+    "brightPurple",
+    "brightCyan",
+    "brightWhite",
+];
+
+/// Serialized shape: a settings fragment holding one color scheme.
+#[derive(Serialize)]
+struct Fragment<'a> {
+    #[serde(rename = "$comment")]
+    comment: &'a str,
+    schemes: [Scheme<'a>; 1],
+}
+
+/// Scheme keys and values, kept in the order Windows Terminal documents them.
+struct Scheme<'a>(Vec<(&'a str, String)>);
+
+impl Serialize for Scheme<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_map(self.0.iter().map(|(key, value)| (key, value)))
+    }
+}
+
+/// Windows Terminal color scheme backend (JSON fragment).
+pub struct WindowsTerminalBackend;
+
+impl ThemeBackend for WindowsTerminalBackend {
+    fn name(&self) -> &str {
+        "Windows Terminal"
+    }
+
+    fn serialize(&self, palette: &AnsiPalette, theme_name: &str) -> String {
+        let p = palette;
+        let mut entries: Vec<(&str, String)> = vec![("name", theme_name.to_string())];
+        entries.push(("background", p.background.to_hex()));
+        entries.push(("foreground", p.foreground.to_hex()));
+        entries.push(("cursorColor", p.cursor_color.to_hex()));
+        entries.push(("selectionBackground", p.selection_bg.to_hex()));
+        for (key, color) in SLOT_KEYS.iter().zip(&p.slots) {
+            entries.push((key, color.to_hex()));
+        }
+
+        let fragment = Fragment {
+            comment: GENERATED_MARKER,
+            schemes: [Scheme(entries)],
+        };
+        // Serializing plain strings cannot fail.
+        let mut out = serde_json::to_string_pretty(&fragment).unwrap_or_default();
+        out.push('\n');
+        out
+    }
+
+    fn install_path(&self, theme_name: &str) -> crate::error::Result<PathBuf> {
+        Ok(fragments_dir()?.join(format!("{}.json", theme_name)))
+    }
+
+    fn extension(&self) -> &str {
+        ".json"
+    }
+}
+
+/// Resolve the Windows Terminal fragments directory for nuri.
+///
+/// Fragments live in `%LOCALAPPDATA%\Microsoft\Windows Terminal\Fragments\<app>`
+/// and are only picked up on Windows, so installing elsewhere is an error.
+fn fragments_dir() -> Result<PathBuf> {
+    if !cfg!(windows) {
+        bail!("Windows Terminal themes can only be installed on Windows; use --output instead");
+    }
+    Ok(ConfigDir::XdgOrLocalAppData
+        .resolve()
+        .join("Microsoft")
+        .join("Windows Terminal")
+        .join("Fragments")
+        .join("nuri"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::ThemeMode;
+    use crate::color::Color;
+    use crate::pipeline::assign::assign_slots;
+    use crate::pipeline::extract::ExtractedColor;
+    use palette::Oklch;
+
+    fn make_extracted(l: f32, chroma: f32, hue: f32, weight: f32) -> ExtractedColor {
+        ExtractedColor {
+            color: Color::from_oklch(Oklch::new(l, chroma, hue)),
+            weight,
+        }
+    }
+
+    fn test_palette() -> AnsiPalette {
+        let colors = vec![
+            make_extracted(0.60, 0.20, 25.0, 0.12),
+            make_extracted(0.60, 0.20, 145.0, 0.12),
+            make_extracted(0.70, 0.20, 90.0, 0.12),
+            make_extracted(0.55, 0.20, 260.0, 0.12),
+            make_extracted(0.60, 0.20, 325.0, 0.12),
+            make_extracted(0.65, 0.20, 195.0, 0.10),
+            make_extracted(0.10, 0.01, 0.0, 0.15),
+            make_extracted(0.95, 0.01, 0.0, 0.15),
+        ];
+        assign_slots(&colors, ThemeMode::Dark)
+    }
+
+    #[test]
+    fn all_scheme_keys_present() {
+        let output = WindowsTerminalBackend.serialize(&test_palette(), "test");
+        let required = [
+            "name",
+            "background",
+            "foreground",
+            "cursorColor",
+            "selectionBackground",
+        ];
+        for key in required.iter().chain(SLOT_KEYS.iter()) {
+            assert!(
+                output.contains(&format!("\"{key}\": \"")),
+                "missing key '{key}'"
+            );
+        }
+    }
+
+    #[test]
+    fn slots_map_to_camel_case_keys() {
+        let palette = test_palette();
+        let output = WindowsTerminalBackend.serialize(&palette, "test");
+        let needle = format!("\"brightBlack\": \"{}\"", palette.slots[8].to_hex());
+        assert!(output.contains(&needle), "missing '{needle}'");
+        let needle = format!("\"purple\": \"{}\"", palette.slots[5].to_hex());
+        assert!(output.contains(&needle), "missing '{needle}'");
+    }
+
+    #[test]
+    fn last_entry_has_no_trailing_comma() {
+        let output = WindowsTerminalBackend.serialize(&test_palette(), "test");
+        let lines: Vec<&str> = output.lines().collect();
+        let last_entry = lines[lines.len() - 4];
+        assert!(last_entry.contains("brightWhite"));
+        assert!(!last_entry.ends_with(','));
+    }
+
+    #[test]
+    fn name_is_json_escaped() {
+        let output = WindowsTerminalBackend.serialize(&test_palette(), "my \"quoted\" theme");
+        assert!(output.contains(r#""name": "my \"quoted\" theme","#));
+
+        let name = r#"C:\themes\"dusk"\"#;
+        let output = WindowsTerminalBackend.serialize(&test_palette(), name);
+        let value: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(value["schemes"][0]["name"], name);
+        assert_eq!(value["$comment"], GENERATED_MARKER);
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn install_errors_off_windows() {
+        let err = WindowsTerminalBackend
+            .install(&test_palette(), "test")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("only be installed on Windows"), "got: {err}");
+    }
+}
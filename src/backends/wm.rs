@@ -0,0 +1,165 @@
+use std::path::PathBuf;
+
+use crate::color::Color;
+use crate::error::Result;
+use crate::pipeline::assign::AnsiPalette;
+
+use super::{config_home, ThemeBackend, GENERATED_MARKER};
+
+/// sway `client.*` colors, written in terms of the variables set above them.
+const SWAY_CLIENTS: &str = "\
+# class                 border        background    text          indicator     child_border
+client.focused          $accent       $accent       $background   $color6       $accent
+client.focused_inactive $color8       $selection_bg $foreground   $color8       $color8
+client.unfocused        $background   $background   $color8       $background   $background
+client.urgent           $color1       $color1       $background   $color1       $color1
+client.placeholder      $background   $background   $foreground   $background   $background
+client.background       $background
+";
+
+/// Hyprland color backend: `$variables` for every color plus window border
+/// colors, for `source = ~/.config/hypr/colors.conf`.
+pub struct HyprlandBackend;
+
+/// sway/i3 color backend: `set $variables` for every color plus `client.*`
+/// border colors, for `include ~/.config/sway/colors` (i3 4.20+ reads the
+/// same syntax).
+pub struct SwayBackend;
+
+impl ThemeBackend for HyprlandBackend {
+    fn name(&self) -> &str {
+        "Hyprland"
+    }
+
+    fn serialize(&self, palette: &AnsiPalette, _theme_name: &str) -> String {
+        let mut out = String::new();
+        out.push_str(&format!("# {GENERATED_MARKER}\n\n"));
+        for (name, color) in variables(palette) {
+            out.push_str(&format!("${name} = rgb({})\n", strip(color)));
+        }
+        out.push_str("\ngeneral {\n");
+        out.push_str("    col.active_border = $accent $color6 45deg\n");
+        out.push_str("    col.inactive_border = $color8\n");
+        out.push_str("}\n");
+        out.push_str("\ngroup {\n");
+        out.push_str("    col.border_active = $accent\n");
+        out.push_str("    col.border_inactive = $color8\n");
+        out.push_str("}\n");
+        out
+    }
+
+    fn install_path(&self, _theme_name: &str) -> Result<PathBuf> {
+        Ok(config_home().join("hypr").join("colors.conf"))
+    }
+
+    fn fixed_path(&self) -> bool {
+        true
+    }
+
+    fn extension(&self) -> &str {
+        ".conf"
+    }
+}
+
+impl ThemeBackend for SwayBackend {
+    fn name(&self) -> &str {
+        "Sway"
+    }
+
+    fn serialize(&self, palette: &AnsiPalette, _theme_name: &str) -> String {
+        let mut out = String::new();
+        out.push_str(&format!("# {GENERATED_MARKER}\n\n"));
+        for (name, color) in variables(palette) {
+            out.push_str(&format!("set ${name} {}\n", color.to_hex()));
+        }
+        out.push('\n');
+        out.push_str(SWAY_CLIENTS);
+        out
+    }
+
+    fn install_path(&self, _theme_name: &str) -> Result<PathBuf> {
+        Ok(config_home().join("sway").join("colors"))
+    }
+
+    fn fixed_path(&self) -> bool {
+        true
+    }
+
+    fn extension(&self) -> &str {
+        ""
+    }
+}
+
+/// Variables both formats define, for bars and other config to reuse.
+fn variables(palette: &AnsiPalette) -> Vec<(String, Color)> {
+    let p = palette;
+    let mut vars = vec![
+        ("background".to_string(), p.background),
+        ("foreground".to_string(), p.foreground),
+        ("selection_bg".to_string(), p.selection_bg),
+        ("accent".to_string(), p.slots[4]),
+    ];
+    vars.extend(
+        p.slots
+            .iter()
+            .enumerate()
+            .map(|(i, &color)| (format!("color{i}"), color)),
+    );
+    vars
+}
+
+/// `rrggbb`, as Hyprland's `rgb()` takes it.
+fn strip(color: Color) -> String {
+    color.to_hex().trim_start_matches('#').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backends::test_palette;
+
+    /// Every `$name` used must have a definition line.
+    fn assert_variables_defined(output: &str, define: &str) {
+        for word in output.split_whitespace().filter(|w| w.starts_with('$')) {
+            assert!(
+                output
+                    .lines()
+                    .any(|l| l.starts_with(&format!("{define}{word} "))),
+                "{word} is used but not defined:\n{output}"
+            );
+        }
+    }
+
+    #[test]
+    fn hyprland_sets_border_colors() {
+        let p = test_palette();
+        let output = HyprlandBackend.serialize(&p, "test");
+        assert!(output.contains("$accent = rgb(458588)\n"));
+        assert!(output.contains(&format!("$accent = rgb({})\n", strip(p.slots[4]))));
+        assert!(output.contains(&format!("$color15 = rgb({})\n", strip(p.slots[15]))));
+        assert!(output.contains("    col.active_border = $accent"));
+        assert_variables_defined(&output, "");
+    }
+
+    #[test]
+    fn sway_sets_client_colors() {
+        let p = test_palette();
+        let output = SwayBackend.serialize(&p, "test");
+        assert!(output.contains("set $background #282828\n"));
+        assert!(output.contains(&format!("set $background {}\n", p.background.to_hex())));
+        for class in [
+            "focused",
+            "focused_inactive",
+            "unfocused",
+            "urgent",
+            "placeholder",
+        ] {
+            let line = output
+                .lines()
+                .find(|l| l.starts_with(&format!("client.{class} ")))
+                .unwrap_or_else(|| panic!("missing client.{class}"));
+            assert_eq!(line.split_whitespace().count(), 6, "{line}");
+        }
+        assert_variables_defined(&output, "set ");
+    }
+}
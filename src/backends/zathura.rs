@@ -0,0 +1,95 @@
+use std::path::PathBuf;
+
+use crate::color::Color;
+use crate::error::Result;
+use crate::pipeline::assign::AnsiPalette;
+
+use super::{config_home, ThemeBackend, GENERATED_MARKER};
+
+/// Zathura color backend: `set` lines for the UI and recolor mode, for
+/// `include nuri-colors` in `zathurarc`.
+pub struct ZathuraBackend;
+
+impl ThemeBackend for ZathuraBackend {
+    fn name(&self) -> &str {
+        "Zathura"
+    }
+
+    fn serialize(&self, palette: &AnsiPalette, _theme_name: &str) -> String {
+        let p = palette;
+        let raised = p.background.mix(p.foreground, 0.06);
+        let options: [(&str, Color); 26] = [
+            ("default-bg", p.background),
+            ("default-fg", p.foreground),
+            ("statusbar-bg", raised),
+            ("statusbar-fg", p.foreground),
+            ("inputbar-bg", p.background),
+            ("inputbar-fg", p.foreground),
+            ("notification-bg", p.background),
+            ("notification-fg", p.foreground),
+            ("notification-error-bg", p.background),
+            ("notification-error-fg", p.slots[1]),
+            ("notification-warning-bg", p.background),
+            ("notification-warning-fg", p.slots[3]),
+            ("highlight-color", p.slots[3]),
+            ("highlight-active-color", p.slots[4]),
+            ("completion-bg", raised),
+            ("completion-fg", p.foreground),
+            ("completion-group-bg", p.background),
+            ("completion-group-fg", p.slots[4]),
+            ("completion-highlight-bg", p.selection_bg),
+            ("completion-highlight-fg", p.selection_fg),
+            ("index-bg", p.background),
+            ("index-fg", p.foreground),
+            ("index-active-bg", p.selection_bg),
+            ("index-active-fg", p.selection_fg),
+            // Recolor mode (`:set recolor`) paints pages in the theme's colors
+            ("recolor-lightcolor", p.background),
+            ("recolor-darkcolor", p.foreground),
+        ];
+
+        let mut out = String::new();
+        out.push_str(&format!("# {GENERATED_MARKER}\n"));
+        for (option, color) in options {
+            out.push_str(&format!("set {option} \"{}\"\n", color.to_hex()));
+        }
+        out
+    }
+
+    fn install_path(&self, _theme_name: &str) -> Result<PathBuf> {
+        Ok(config_home().join("zathura").join("nuri-colors"))
+    }
+
+    fn fixed_path(&self) -> bool {
+        true
+    }
+
+    fn extension(&self) -> &str {
+        ""
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backends::test_palette;
+
+    #[test]
+    fn sets_ui_and_recolor_colors() {
+        let p = test_palette();
+        let output = ZathuraBackend.serialize(&p, "test");
+        assert!(output.contains("set default-bg \"#282828\"\n"));
+        for (option, color) in [
+            ("default-bg", p.background),
+            ("recolor-lightcolor", p.background),
+            ("recolor-darkcolor", p.foreground),
+            ("completion-highlight-bg", p.selection_bg),
+        ] {
+            assert!(
+                output.contains(&format!("set {option} \"{}\"\n", color.to_hex())),
+                "missing {option}"
+            );
+        }
+        assert!(output.contains("set statusbar-bg \"#"));
+    }
+}
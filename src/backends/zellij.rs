@@ -1,12 +1,12 @@
-use std::path::{Path, PathBuf};
+use std::path::PathBuf;
 
-use anyhow::{Context, Result};
+use anyhow::Result;
 use palette::Oklch;
 
 use crate::color::Color;
 use crate::pipeline::assign::AnsiPalette;
 
-use super::ThemeBackend;
+use super::{config_home, ThemeBackend, GENERATED_MARKER};
 
 /// Zellij terminal multiplexer theme backend (KDL format).
 pub struct ZellijBackend;
@@ -20,6 +20,7 @@ impl ThemeBackend for ZellijBackend {
         let orange = derive_orange(palette);
 
         let mut out = String::new();
+        out.push_str(&format!("// {GENERATED_MARKER}\n"));
         out.push_str("themes {\n");
         out.push_str(&format!("    {} {{\n", theme_name));
         out.push_str(&format!("        fg \"{}\"\n", palette.foreground.to_hex()));
@@ -54,21 +55,8 @@ impl ThemeBackend for ZellijBackend {
         out
     }
 
-    fn install(&self, palette: &AnsiPalette, theme_name: &str) -> Result<PathBuf> {
-        let dir = themes_dir()?;
-        std::fs::create_dir_all(&dir)
-            .with_context(|| format!("failed to create themes directory: {}", dir.display()))?;
-
-        let path = dir.join(format!("{}.kdl", theme_name));
-        self.write_to(palette, theme_name, &path)?;
-        Ok(path)
-    }
-
-    fn write_to(&self, palette: &AnsiPalette, theme_name: &str, path: &Path) -> Result<()> {
-        let content = self.serialize(palette, theme_name);
-        std::fs::write(path, content)
-            .with_context(|| format!("failed to write theme to {}", path.display()))?;
-        Ok(())
+    fn install_path(&self, theme_name: &str) -> crate::error::Result<PathBuf> {
+        Ok(themes_dir()?.join(format!("{}.kdl", theme_name)))
     }
 
     fn extension(&self) -> &str {
@@ -91,13 +79,7 @@ fn derive_orange(palette: &AnsiPalette) -> Color {
 
 /// Resolve the Zellij themes directory.
 fn themes_dir() -> Result<PathBuf> {
-    let config_home = std::env::var("XDG_CONFIG_HOME")
-        .map(PathBuf::from)
-        .unwrap_or_else(|_| {
-            let home = std::env::var("HOME").unwrap_or_else(|_| "~".to_string());
-            PathBuf::from(home).join(".config")
-        });
-    Ok(config_home.join("zellij").join("themes"))
+    Ok(config_home().join("zellij").join("themes"))
 }
 
 #[cfg(test)]
@@ -201,7 +183,7 @@ mod tests {
         let backend = ZellijBackend;
         let output = backend.serialize(&test_palette(), "test");
 
-        assert!(output.starts_with("themes {"));
+        assert!(output.starts_with("// Generated by nuri\nthemes {"));
         assert!(output.contains("    test {"));
         assert!(output.ends_with("}\n"));
 
@@ -0,0 +1,223 @@
+//! `nuri batch`: find the images in a directory, run them several at a time
+//! and summarize what happened to each.
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+use anyhow::{Context, Result};
+
+use crate::watch::is_wallpaper;
+
+/// What happened to one image.
+#[derive(Debug)]
+pub struct Outcome {
+    pub image: PathBuf,
+    pub theme: String,
+    /// The error, formatted with its causes, if the image failed.
+    pub error: Option<String>,
+}
+
+/// Images in `dir` (and its subdirectories if `recursive`) whose file name
+/// matches `glob`, sorted by path.
+pub fn find_images(dir: &Path, recursive: bool, glob: Option<&str>) -> Result<Vec<PathBuf>> {
+    let mut images = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let entries =
+            std::fs::read_dir(&dir).with_context(|| format!("cannot read {}", dir.display()))?;
+        for entry in entries {
+            let path = entry?.path();
+            if path.is_dir() {
+                if recursive {
+                    pending.push(path);
+                }
+                continue;
+            }
+            let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+            if is_wallpaper(&path) && glob.is_none_or(|pattern| glob_matches(pattern, name)) {
+                images.push(path);
+            }
+        }
+    }
+    images.sort();
+    Ok(images)
+}
+
+/// Whether `name` matches `pattern`, where `*` stands for any run of
+/// characters and `?` for exactly one. Case-sensitive.
+pub fn glob_matches(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    // Position after the last `*` and the name position it was tried at
+    let (mut p, mut n) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p + 1, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            // Let the last `*` swallow one more character and retry
+            _ => match star {
+                Some((after, tried)) => {
+                    p = after;
+                    n = tried + 1;
+                    star = Some((after, tried + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Run `f` on every item using up to `jobs` threads, returning the results
+/// in the items' order.
+pub fn run_parallel<T: Sync, R: Send>(
+    items: &[T],
+    jobs: usize,
+    f: impl Fn(&T) -> R + Sync,
+) -> Vec<R> {
+    let next = AtomicUsize::new(0);
+    let results = Mutex::new((0..items.len()).map(|_| None).collect::<Vec<_>>());
+    thread::scope(|scope| {
+        for _ in 0..jobs.clamp(1, items.len().max(1)) {
+            scope.spawn(|| loop {
+                let i = next.fetch_add(1, Ordering::Relaxed);
+                let Some(item) = items.get(i) else {
+                    break;
+                };
+                let result = f(item);
+                results.lock().unwrap_or_else(|e| e.into_inner())[i] = Some(result);
+            });
+        }
+    });
+    results
+        .into_inner()
+        .unwrap_or_else(|e| e.into_inner())
+        .into_iter()
+        .flatten()
+        .collect()
+}
+
+/// Summary of a batch as a table: one row per image with its theme name and
+/// `ok` or the error, then the totals.
+pub fn summary(outcomes: &[Outcome], dir: &Path) -> String {
+    let rows: Vec<[String; 3]> = outcomes
+        .iter()
+        .map(|outcome| {
+            let image = outcome.image.strip_prefix(dir).unwrap_or(&outcome.image);
+            let result = match &outcome.error {
+                Some(error) => format!("failed: {error}"),
+                None => "ok".to_string(),
+            };
+            [image.display().to_string(), outcome.theme.clone(), result]
+        })
+        .collect();
+    let header = ["IMAGE", "THEME", "RESULT"].map(String::from);
+    let width = |col: usize| {
+        rows.iter()
+            .chain([&header])
+            .map(|row| row[col].chars().count())
+            .max()
+            .unwrap_or(0)
+    };
+    let (image_width, theme_width) = (width(0), width(1));
+    let mut out = String::new();
+    for [image, theme, result] in [&header].into_iter().chain(&rows) {
+        out.push_str(&format!(
+            "{image:<image_width$}  {theme:<theme_width$}  {result}\n"
+        ));
+    }
+    let failed = outcomes.iter().filter(|o| o.error.is_some()).count();
+    out.push_str(&format!(
+        "\n{} generated, {failed} failed\n",
+        outcomes.len() - failed
+    ));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glob_supports_stars_and_question_marks() {
+        assert!(glob_matches("*.png", "sunset.png"));
+        assert!(!glob_matches("*.png", "sunset.jpg"));
+        assert!(glob_matches("sun*", "sunset.jpg"));
+        assert!(glob_matches("*set*", "sunset.jpg"));
+        assert!(glob_matches("img-??.jpg", "img-01.jpg"));
+        assert!(!glob_matches("img-??.jpg", "img-1.jpg"));
+        assert!(glob_matches("a*b*c", "aXbYbZc"));
+        assert!(!glob_matches("a*b*c", "aXbYbZ"));
+        assert!(glob_matches("*", ""));
+    }
+
+    #[test]
+    fn finds_images_sorted_optionally_recursive() {
+        let dir = std::env::temp_dir().join("nuri-test-batch-find");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("nested")).unwrap();
+        for file in ["b.png", "a.jpg", "notes.txt", "nested/c.png"] {
+            std::fs::write(dir.join(file), "").unwrap();
+        }
+
+        let names = |images: Vec<PathBuf>| -> Vec<String> {
+            images
+                .iter()
+                .map(|p| p.strip_prefix(&dir).unwrap().display().to_string())
+                .collect()
+        };
+        assert_eq!(
+            names(find_images(&dir, false, None).unwrap()),
+            ["a.jpg", "b.png"]
+        );
+        assert_eq!(
+            names(find_images(&dir, true, Some("*.png")).unwrap()),
+            ["b.png", "nested/c.png"]
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn parallel_results_keep_item_order() {
+        let items: Vec<u64> = (0..50).collect();
+        assert_eq!(
+            run_parallel(&items, 4, |&n| n * 2),
+            (0..50).map(|n| n * 2).collect::<Vec<_>>()
+        );
+        assert!(run_parallel(&[] as &[u8], 4, |&n| n).is_empty());
+    }
+
+    #[test]
+    fn summary_aligns_columns_and_counts_failures() {
+        let dir = Path::new("/walls");
+        let outcomes = [
+            Outcome {
+                image: dir.join("sunset.png"),
+                theme: "sunset".into(),
+                error: None,
+            },
+            Outcome {
+                image: dir.join("broken.jpg"),
+                theme: "broken".into(),
+                error: Some("cannot decode".into()),
+            },
+        ];
+        assert_eq!(
+            summary(&outcomes, dir),
+            "IMAGE       THEME   RESULT\n\
+             sunset.png  sunset  ok\n\
+             broken.jpg  broken  failed: cannot decode\n\
+             \n1 generated, 1 failed\n"
+        );
+    }
+}
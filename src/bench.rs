@@ -0,0 +1,206 @@
+//! `nuri bench <image>` (hidden): run the pipeline on one image a few times
+//! and time each stage, so performance work has a baseline to compare
+//! against. Nothing is cached and the config file is not read, so runs on
+//! different machines or branches measure the same work.
+
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Result};
+
+use crate::pipeline::assign::assign_slots;
+use crate::pipeline::contrast::{enforce_contrast, DEFAULT_ACCENT_CONTRAST};
+use crate::pipeline::detect::detect_mode;
+use crate::pipeline::extract::{
+    check_colors, decode_frames_with, prepare_frames, refine, sample_pixels, LoadOptions, Sampling,
+    DEFAULT_MERGE_DELTA_E,
+};
+use crate::pipeline::quantize::Algorithm;
+use crate::pipeline::weighting::{emphasize, restore_area, Weighting};
+
+/// What to run the pipeline with.
+#[derive(Debug, Clone)]
+pub struct BenchOptions {
+    pub load: LoadOptions,
+    pub algorithm: Algorithm,
+    pub k: usize,
+    pub sampling: Sampling,
+    pub sample_size: usize,
+    pub weighting: Weighting,
+    pub seed: u64,
+}
+
+/// Stages in pipeline order, as named in the table.
+const STAGES: [&str; 7] = [
+    "decode", "lab", "sample", "cluster", "refine", "detect", "palette",
+];
+
+/// Timings of every run, and what the last one worked on.
+#[derive(Debug)]
+pub struct Bench {
+    pub width: usize,
+    pub height: usize,
+    pub frames: usize,
+    pub sampled: usize,
+    pub colors: usize,
+    /// One row per run, one duration per [`STAGES`] entry.
+    pub runs: Vec<[Duration; 7]>,
+}
+
+/// Run the pipeline `runs` times on `path`.
+pub fn run(path: &Path, options: &BenchOptions, runs: usize) -> Result<Bench> {
+    if runs == 0 {
+        bail!("--runs must be at least 1");
+    }
+    check_colors(options.k)?;
+    let mut bench = Bench {
+        width: 0,
+        height: 0,
+        frames: 0,
+        sampled: 0,
+        colors: 0,
+        runs: Vec::with_capacity(runs),
+    };
+    for _ in 0..runs {
+        let mut times = [Duration::ZERO; 7];
+        let mut clock = Instant::now();
+        let mut lap = |stage: usize| {
+            times[stage] = clock.elapsed();
+            clock = Instant::now();
+        };
+
+        let decoded = decode_frames_with(path, &options.load)?;
+        lap(0);
+        let image = prepare_frames(&decoded);
+        lap(1);
+        let weighted = emphasize(&image, options.weighting);
+        let sample = sample_pixels(
+            &weighted,
+            options.sampling,
+            options.sample_size,
+            options.seed,
+        );
+        lap(2);
+        let mut colors = options
+            .algorithm
+            .quantizer()
+            .quantize(&sample, options.k, options.seed);
+        lap(3);
+        if options.weighting != Weighting::Area {
+            colors = restore_area(colors, &image.pixels);
+        }
+        let colors = refine(colors, DEFAULT_MERGE_DELTA_E, 0.0);
+        lap(4);
+        let mode = detect_mode(&image.pixels);
+        lap(5);
+        let mut palette = assign_slots(&colors, mode);
+        enforce_contrast(&mut palette, DEFAULT_ACCENT_CONTRAST);
+        lap(6);
+
+        bench.runs.push(times);
+        bench.width = image.width;
+        bench.height = image.height;
+        bench.frames = image.frames;
+        bench.sampled = sample.len();
+        bench.colors = colors.len();
+    }
+    Ok(bench)
+}
+
+impl Bench {
+    /// The median and fastest time of each stage and of whole runs, with
+    /// each stage's share of the median total.
+    pub fn table(&self) -> String {
+        let column =
+            |stage: usize| -> Vec<Duration> { self.runs.iter().map(|r| r[stage]).collect() };
+        let totals: Vec<Duration> = self.runs.iter().map(|r| r.iter().sum()).collect();
+        let total_median = median(totals.clone());
+
+        let mut out = format!(
+            "{}x{} ({} frame{}), {} pixels clustered into {} colors, {} run{}\n\n",
+            self.width,
+            self.height,
+            self.frames,
+            if self.frames == 1 { "" } else { "s" },
+            self.sampled,
+            self.colors,
+            self.runs.len(),
+            if self.runs.len() == 1 { "" } else { "s" },
+        );
+        out.push_str(&format!(
+            "{:<8}  {:>10}  {:>10}  {:>6}\n",
+            "STAGE", "MEDIAN", "MIN", "SHARE"
+        ));
+        let rows = STAGES
+            .iter()
+            .enumerate()
+            .map(|(i, name)| (*name, column(i)))
+            .chain([("total", totals)]);
+        for (name, times) in rows {
+            let median = median(times.clone());
+            let min = times.iter().min().copied().unwrap_or_default();
+            let share = if total_median.is_zero() {
+                0.0
+            } else {
+                median.as_secs_f64() / total_median.as_secs_f64() * 100.0
+            };
+            out.push_str(&format!(
+                "{name:<8}  {:>10}  {:>10}  {share:>5.1}%\n",
+                millis(median),
+                millis(min)
+            ));
+        }
+        out
+    }
+}
+
+fn median(mut times: Vec<Duration>) -> Duration {
+    times.sort();
+    times.get(times.len() / 2).copied().unwrap_or_default()
+}
+
+fn millis(d: Duration) -> String {
+    format!("{:.2} ms", d.as_secs_f64() * 1000.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::extract::{DEFAULT_SAMPLE_SIZE, DEFAULT_SEED};
+
+    #[test]
+    fn times_every_stage_of_every_run() {
+        let dir = std::env::temp_dir().join("nuri_test_bench");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("wallpaper.png");
+        image::RgbImage::from_fn(40, 30, |x, y| {
+            image::Rgb([(x * 6) as u8, (y * 8) as u8, 90])
+        })
+        .save(&path)
+        .unwrap();
+
+        let options = BenchOptions {
+            load: LoadOptions::default(),
+            algorithm: Algorithm::Kmeans,
+            k: 8,
+            sampling: Sampling::All,
+            sample_size: DEFAULT_SAMPLE_SIZE,
+            weighting: Weighting::Area,
+            seed: DEFAULT_SEED,
+        };
+        let bench = run(&path, &options, 3).unwrap();
+        assert_eq!(bench.runs.len(), 3);
+        assert_eq!((bench.width, bench.height, bench.sampled), (40, 30, 1200));
+
+        let table = bench.table();
+        assert!(table.starts_with("40x30 (1 frame), 1200 pixels"), "{table}");
+        for stage in STAGES.iter().chain(&["total"]) {
+            assert!(
+                table.lines().any(|line| line.starts_with(stage)),
+                "{stage} missing:\n{table}"
+            );
+        }
+        assert!(run(&path, &options, 0).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,240 @@
+//! Cache of extracted colors in `~/.cache/nuri/`, so re-running on the same
+//! image (watch, regenerate, TUI re-extraction) skips clustering. Entries are
+//! keyed by the image's content hash and every setting that shapes the
+//! result; `--no-cache` bypasses it and `nuri cache clear` empties it.
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+
+use crate::backends::cache_home;
+use crate::pipeline::crop::Region;
+use crate::pipeline::extract::{ExtractedColor, ResizeFilter, Sampling};
+use crate::pipeline::quantize::Algorithm;
+use crate::pipeline::weighting::Weighting;
+use crate::provenance::value_name;
+
+/// What a quantizer's output depends on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Key {
+    /// Content hash of the image file (see [`crate::provenance::hash_file`]).
+    pub image_hash: String,
+    /// `--max-dim`, the size the image is resized to fit.
+    pub max_dim: u32,
+    /// `--resize-filter`.
+    pub resize_filter: ResizeFilter,
+    /// `--max-memory` in bytes, which decides between decoding the whole
+    /// image and a streamed downscale (or animation frames and the first one).
+    pub max_memory: u64,
+    /// Animation frames pooled; 1 for still images.
+    pub frames: usize,
+    /// `--frame-stride`, for animations.
+    pub frame_stride: Option<usize>,
+    /// `--frame-interval`, for videos.
+    pub frame_interval: Option<Duration>,
+    /// `--crop` or `--focus`.
+    pub region: Option<Region>,
+    pub algorithm: Algorithm,
+    pub sampling: Sampling,
+    pub sample_size: usize,
+    pub weighting: Weighting,
+    pub k: usize,
+    pub seed: u64,
+}
+
+impl Key {
+    /// The entry's file name; every field is in it, so keys never collide.
+    /// It starts with the nuri version, since extraction changes between
+    /// releases would otherwise serve stale colors.
+    fn file_name(&self) -> String {
+        let hash = self.image_hash.rsplit(':').next().unwrap_or_default();
+        let size = format!(
+            "{}-{}-m{}",
+            self.max_dim,
+            value_name(&self.resize_filter),
+            self.max_memory
+        );
+        let mut frames = format!("f{}", self.frames);
+        if let Some(stride) = self.frame_stride {
+            frames.push_str(&format!("x{stride}"));
+        }
+        if let Some(interval) = self.frame_interval {
+            frames.push_str(&format!("i{}ms", interval.as_millis()));
+        }
+        match self.region {
+            Some(Region::Crop(crop)) => frames.push_str(&format!("-crop{crop}")),
+            Some(Region::Focus(focus)) => frames.push_str(&format!("-{}", value_name(&focus))),
+            None => {}
+        }
+        format!(
+            "v{}-{hash}-{size}-{frames}-{}-{}-{}-{}-k{}-s{}.json",
+            env!("CARGO_PKG_VERSION"),
+            value_name(&self.algorithm),
+            value_name(&self.sampling),
+            self.sample_size,
+            value_name(&self.weighting),
+            self.k,
+            self.seed
+        )
+    }
+}
+
+/// `nuri` under the cache home: `$XDG_CACHE_HOME`, `~/.cache`,
+/// `~/Library/Caches` or `%LOCALAPPDATA%`.
+pub fn dir() -> PathBuf {
+    cache_home().join("nuri")
+}
+
+/// The colors stored for `key`, if any. Unreadable entries count as misses.
+pub fn load(key: &Key) -> Option<Vec<ExtractedColor>> {
+    load_from(&dir(), key)
+}
+
+/// Store `colors` for `key`.
+pub fn store(key: &Key, colors: &[ExtractedColor]) -> Result<()> {
+    store_in(&dir(), key, colors)
+}
+
+/// Remove every cached entry, returning how many there were.
+pub fn clear() -> Result<usize> {
+    clear_dir(&dir())
+}
+
+/// The colors for `key` from the cache, or from `quantize` (stored for next
+/// time) on a miss or without a key.
+pub fn cached(
+    key: Option<&Key>,
+    quantize: impl FnOnce() -> Vec<ExtractedColor>,
+) -> Vec<ExtractedColor> {
+    let Some(key) = key else {
+        return quantize();
+    };
+    if let Some(colors) = load(key) {
+        log::debug!("using cached colors ({})", key.file_name());
+        return colors;
+    }
+    let colors = quantize();
+    if let Err(e) = store(key, &colors) {
+        log::debug!("cannot cache colors: {e:#}");
+    }
+    colors
+}
+
+fn load_from(dir: &Path, key: &Key) -> Option<Vec<ExtractedColor>> {
+    let json = std::fs::read_to_string(dir.join(key.file_name())).ok()?;
+    serde_json::from_str(&json).ok()
+}
+
+fn store_in(dir: &Path, key: &Key, colors: &[ExtractedColor]) -> Result<()> {
+    std::fs::create_dir_all(dir).with_context(|| format!("cannot create {}", dir.display()))?;
+    let path = dir.join(key.file_name());
+    // Write then rename, so a concurrent run never reads half an entry
+    let partial = path.with_extension(format!("{}.tmp", std::process::id()));
+    std::fs::write(&partial, serde_json::to_string(colors)?)
+        .with_context(|| format!("cannot write {}", partial.display()))?;
+    std::fs::rename(&partial, &path).with_context(|| format!("cannot write {}", path.display()))?;
+    Ok(())
+}
+
+fn clear_dir(dir: &Path) -> Result<usize> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(e).with_context(|| format!("cannot read {}", dir.display())),
+    };
+    let mut removed = 0;
+    for entry in entries {
+        let path = entry?.path();
+        if path.extension().is_some_and(|ext| ext == "json") {
+            std::fs::remove_file(&path)
+                .with_context(|| format!("cannot remove {}", path.display()))?;
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+    use crate::pipeline::crop::Focus;
+
+    fn key(k: usize) -> Key {
+        Key {
+            image_hash: "fnv1a64:0123456789abcdef".to_string(),
+            max_dim: 256,
+            resize_filter: ResizeFilter::Lanczos,
+            max_memory: 256 * 1024 * 1024,
+            frames: 1,
+            frame_stride: None,
+            frame_interval: None,
+            region: None,
+            algorithm: Algorithm::Kmeans,
+            sampling: Sampling::default(),
+            sample_size: 16_384,
+            weighting: Weighting::Area,
+            k,
+            seed: 42,
+        }
+    }
+
+    #[test]
+    fn file_name_spells_out_the_key() {
+        assert_eq!(
+            key(16).file_name(),
+            format!(
+                "v{}-0123456789abcdef-256-lanczos-m268435456-f1-kmeans-all-16384-area-k16-s42.json",
+                env!("CARGO_PKG_VERSION")
+            )
+        );
+        let animated = Key {
+            frames: 8,
+            frame_stride: Some(3),
+            ..key(16)
+        };
+        assert!(animated.file_name().contains("-f8x3-"));
+        let focused = Key {
+            region: Some(Region::Focus(Focus::Top)),
+            ..key(16)
+        };
+        assert!(focused.file_name().contains("-f1-top-kmeans-"));
+        let fast = Key {
+            max_dim: 512,
+            resize_filter: ResizeFilter::Nearest,
+            ..key(16)
+        };
+        assert!(fast
+            .file_name()
+            .contains("-0123456789abcdef-512-nearest-m268435456-f1-"));
+        // The decode path depends on the memory budget
+        let streamed = Key {
+            max_memory: 1024,
+            ..key(16)
+        };
+        assert_ne!(streamed.file_name(), key(16).file_name());
+    }
+
+    #[test]
+    fn stores_loads_and_clears_entries() {
+        let dir = std::env::temp_dir().join("nuri-test-cache");
+        let _ = std::fs::remove_dir_all(&dir);
+        let colors = vec![ExtractedColor {
+            color: Color::new(0x12, 0x34, 0x56),
+            weight: 0.75,
+        }];
+
+        assert!(load_from(&dir, &key(16)).is_none());
+        store_in(&dir, &key(16), &colors).unwrap();
+        let loaded = load_from(&dir, &key(16)).unwrap();
+        assert_eq!(loaded[0].color, colors[0].color);
+        assert_eq!(loaded[0].weight, 0.75);
+        assert!(load_from(&dir, &key(8)).is_none());
+
+        assert_eq!(clear_dir(&dir).unwrap(), 1);
+        assert!(load_from(&dir, &key(16)).is_none());
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(clear_dir(&dir).unwrap(), 0);
+    }
+}
@@ -0,0 +1,453 @@
+//! Shareable palette cards: the source wallpaper, the theme name and every
+//! palette color with its hex code, as SVG or PNG.
+
+use std::io::Cursor;
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use image::imageops::FilterType;
+use image::{ImageFormat, Rgb, RgbImage};
+
+use crate::color::Color;
+use crate::pipeline::assign::AnsiPalette;
+use crate::pipeline::extract::PreparedImage;
+
+const WIDTH: u32 = 560;
+const PADDING: u32 = 24;
+/// Largest thumbnail; wallpapers are scaled to fit, keeping their aspect.
+const THUMB_MAX: (u32, u32) = (WIDTH - 2 * PADDING, 288);
+const SWATCH: u32 = 60;
+const GAP: u32 = 4;
+/// Height of the background/foreground/cursor/selection strip.
+const SPECIAL_HEIGHT: u32 = 40;
+/// Pixel size of one bitmap font dot in the title.
+const TITLE_SCALE: u32 = 3;
+
+/// Write a palette card to `path`, as SVG or PNG by its extension.
+pub fn export(
+    path: &Path,
+    palette: &AnsiPalette,
+    name: &str,
+    source: Option<&PreparedImage>,
+) -> Result<()> {
+    let thumbnail = source.map(thumbnail);
+    let ext = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    match ext.as_str() {
+        "svg" => std::fs::write(path, svg(palette, name, thumbnail.as_ref())?)
+            .with_context(|| format!("failed to write {}", path.display())),
+        "png" => png(palette, name, thumbnail.as_ref())
+            .save_with_format(path, ImageFormat::Png)
+            .with_context(|| format!("failed to write {}", path.display())),
+        _ => bail!(
+            "unsupported palette card format '{}': use a .svg or .png path",
+            path.display()
+        ),
+    }
+}
+
+/// Positions of each part of the card, shared by both renderers.
+struct Layout {
+    thumb: Option<(u32, u32, u32, u32)>,
+    title_y: u32,
+    special_y: u32,
+    slots_y: u32,
+    height: u32,
+}
+
+impl Layout {
+    fn new(thumbnail: Option<&RgbImage>) -> Self {
+        let mut y = PADDING;
+        let thumb = thumbnail.map(|t| {
+            let x = (WIDTH - t.width()) / 2;
+            let rect = (x, y, t.width(), t.height());
+            y += t.height() + PADDING;
+            rect
+        });
+        let title_y = y;
+        y += 7 * TITLE_SCALE + PADDING;
+        let special_y = y;
+        y += SPECIAL_HEIGHT + GAP * 2;
+        let slots_y = y;
+        y += 2 * SWATCH + GAP + PADDING;
+        Self {
+            thumb,
+            title_y,
+            special_y,
+            slots_y,
+            height: y,
+        }
+    }
+
+    /// Left edge and width of the `i`th of `n` equal cells across the card.
+    fn cell(i: u32, n: u32) -> (u32, u32) {
+        let inner = WIDTH - 2 * PADDING;
+        let width = (inner - GAP * (n - 1)) / n;
+        (PADDING + i * (width + GAP), width)
+    }
+
+    /// Rectangle of ANSI slot `i`: colors 0-7 on the first row, 8-15 below.
+    fn slot(&self, i: usize) -> (u32, u32, u32, u32) {
+        let (x, w) = Self::cell(i as u32 % 8, 8);
+        let y = self.slots_y + (i as u32 / 8) * (SWATCH + GAP);
+        (x, y, w, SWATCH)
+    }
+
+    fn special(&self, i: usize) -> (u32, u32, u32, u32) {
+        let (x, w) = Self::cell(i as u32, 4);
+        (x, self.special_y, w, SPECIAL_HEIGHT)
+    }
+}
+
+fn specials(palette: &AnsiPalette) -> [(&'static str, Color); 4] {
+    [
+        ("bg", palette.background),
+        ("fg", palette.foreground),
+        ("cursor", palette.cursor_color),
+        ("selection", palette.selection_bg),
+    ]
+}
+
+/// Swatch edge, so colors close to the card background stay visible.
+fn outline_color(palette: &AnsiPalette) -> Color {
+    palette.background.mix(palette.foreground, 0.2)
+}
+
+/// Black or white, whichever reads better on `bg`.
+pub(crate) fn label_color(bg: Color) -> Color {
+    if bg.relative_luminance() > 0.4 {
+        Color::new(0, 0, 0)
+    } else {
+        Color::new(255, 255, 255)
+    }
+}
+
+/// The wallpaper scaled to fit the thumbnail area.
+pub(crate) fn thumbnail(image: &PreparedImage) -> RgbImage {
+    let mut rgb = RgbImage::new(image.width as u32, image.height as u32);
+    for (pixel, lab) in rgb.pixels_mut().zip(&image.pixels) {
+        let c = Color::from_lab(*lab);
+        *pixel = Rgb([c.r, c.g, c.b]);
+    }
+    let (max_w, max_h) = THUMB_MAX;
+    let scale = (max_w as f32 / rgb.width() as f32).min(max_h as f32 / rgb.height() as f32);
+    let w = ((rgb.width() as f32 * scale).round() as u32).clamp(1, max_w);
+    let h = ((rgb.height() as f32 * scale).round() as u32).clamp(1, max_h);
+    image::imageops::resize(&rgb, w, h, FilterType::Triangle)
+}
+
+fn svg(palette: &AnsiPalette, name: &str, thumbnail: Option<&RgbImage>) -> Result<String> {
+    let layout = Layout::new(thumbnail);
+    let mut out = String::new();
+    out.push_str(&format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{WIDTH}\" height=\"{h}\" \
+         viewBox=\"0 0 {WIDTH} {h}\" font-family=\"monospace\">\n",
+        h = layout.height
+    ));
+    out.push_str(&format!(
+        "  <rect width=\"100%\" height=\"100%\" fill=\"{}\"/>\n",
+        palette.background.to_hex()
+    ));
+    if let (Some(image), Some((x, y, w, h))) = (thumbnail, layout.thumb) {
+        out.push_str(&format!(
+            "  <image x=\"{x}\" y=\"{y}\" width=\"{w}\" height=\"{h}\" href=\"{}\"/>\n",
+            png_data_uri(image)?
+        ));
+    }
+    out.push_str(&format!(
+        "  <text x=\"{PADDING}\" y=\"{}\" font-size=\"{}\" font-weight=\"bold\" fill=\"{}\">{}</text>\n",
+        layout.title_y + 7 * TITLE_SCALE,
+        8 * TITLE_SCALE,
+        palette.foreground.to_hex(),
+        xml_escape(name)
+    ));
+    let swatches = specials(palette)
+        .into_iter()
+        .enumerate()
+        .map(|(i, (label, color))| {
+            (
+                layout.special(i),
+                format!("{label} {}", color.to_hex()),
+                color,
+            )
+        })
+        .chain(
+            palette
+                .slots
+                .iter()
+                .enumerate()
+                .map(|(i, &color)| (layout.slot(i), color.to_hex(), color)),
+        );
+    let edge = outline_color(palette).to_hex();
+    for ((x, y, w, h), label, color) in swatches {
+        out.push_str(&format!(
+            "  <rect x=\"{x}\" y=\"{y}\" width=\"{w}\" height=\"{h}\" rx=\"4\" fill=\"{}\" stroke=\"{edge}\"/>\n",
+            color.to_hex()
+        ));
+        out.push_str(&format!(
+            "  <text x=\"{}\" y=\"{}\" font-size=\"10\" text-anchor=\"middle\" fill=\"{}\">{label}</text>\n",
+            x + w / 2,
+            y + h - 6,
+            label_color(color).to_hex()
+        ));
+    }
+    out.push_str("</svg>\n");
+    Ok(out)
+}
+
+fn png(palette: &AnsiPalette, name: &str, thumbnail: Option<&RgbImage>) -> RgbImage {
+    let layout = Layout::new(thumbnail);
+    let mut card = RgbImage::from_pixel(WIDTH, layout.height, rgb(palette.background));
+    if let (Some(image), Some((x, y, _, _))) = (thumbnail, layout.thumb) {
+        image::imageops::replace(&mut card, image, x.into(), y.into());
+    }
+    draw_text(
+        &mut card,
+        PADDING,
+        layout.title_y,
+        TITLE_SCALE,
+        name,
+        palette.foreground,
+    );
+    let edge = outline_color(palette);
+    for (i, (label, color)) in specials(palette).into_iter().enumerate() {
+        let rect = layout.special(i);
+        swatch(&mut card, rect, color, edge);
+        draw_label(
+            &mut card,
+            rect,
+            &format!("{label} {}", color.to_hex()),
+            color,
+        );
+    }
+    for (i, &color) in palette.slots.iter().enumerate() {
+        let rect = layout.slot(i);
+        swatch(&mut card, rect, color, edge);
+        draw_label(&mut card, rect, &color.to_hex(), color);
+    }
+    card
+}
+
+fn rgb(color: Color) -> Rgb<u8> {
+    Rgb([color.r, color.g, color.b])
+}
+
+fn fill(card: &mut RgbImage, (x, y, w, h): (u32, u32, u32, u32), color: Color) {
+    for py in y..y + h {
+        for px in x..x + w {
+            card.put_pixel(px, py, rgb(color));
+        }
+    }
+}
+
+/// A swatch filled with `color` inside a one-pixel `edge`.
+fn swatch(card: &mut RgbImage, (x, y, w, h): (u32, u32, u32, u32), color: Color, edge: Color) {
+    fill(card, (x, y, w, h), edge);
+    fill(card, (x + 1, y + 1, w - 2, h - 2), color);
+}
+
+/// A label centered along the bottom of a swatch.
+fn draw_label(card: &mut RgbImage, (x, y, w, h): (u32, u32, u32, u32), text: &str, bg: Color) {
+    let width = text.chars().count() as u32 * 6 - 1;
+    let left = x + w.saturating_sub(width) / 2;
+    draw_text(card, left, y + h - 12, 1, text, label_color(bg));
+}
+
+/// Draw `text` with the built-in 5x7 font, each dot `scale` pixels square.
+/// Text running past the right edge is clipped.
+fn draw_text(card: &mut RgbImage, x: u32, y: u32, scale: u32, text: &str, color: Color) {
+    for (i, ch) in text.chars().enumerate() {
+        let left = x + i as u32 * 6 * scale;
+        for (row, bits) in glyph(ch).iter().enumerate() {
+            for col in 0..5 {
+                if bits & (0x10 >> col) == 0 {
+                    continue;
+                }
+                let (gx, gy) = (left + col * scale, y + row as u32 * scale);
+                for py in gy..gy + scale {
+                    for px in gx..gx + scale {
+                        if px < card.width() && py < card.height() {
+                            card.put_pixel(px, py, rgb(color));
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Rows of a 5x7 glyph, high bit on the left. Lowercase letters draw as
+/// capitals and anything else missing as `?`.
+fn glyph(ch: char) -> [u8; 7] {
+    match ch.to_ascii_uppercase() {
+        ' ' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+        '0' => [0x0E, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0E],
+        '1' => [0x04, 0x0C, 0x04, 0x04, 0x04, 0x04, 0x0E],
+        '2' => [0x0E, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1F],
+        '3' => [0x1F, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0E],
+        '4' => [0x02, 0x06, 0x0A, 0x12, 0x1F, 0x02, 0x02],
+        '5' => [0x1F, 0x10, 0x1E, 0x01, 0x01, 0x11, 0x0E],
+        '6' => [0x06, 0x08, 0x10, 0x1E, 0x11, 0x11, 0x0E],
+        '7' => [0x1F, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08],
+        '8' => [0x0E, 0x11, 0x11, 0x0E, 0x11, 0x11, 0x0E],
+        '9' => [0x0E, 0x11, 0x11, 0x0F, 0x01, 0x02, 0x0C],
+        'A' => [0x0E, 0x11, 0x11, 0x11, 0x1F, 0x11, 0x11],
+        'B' => [0x1E, 0x11, 0x11, 0x1E, 0x11, 0x11, 0x1E],
+        'C' => [0x0E, 0x11, 0x10, 0x10, 0x10, 0x11, 0x0E],
+        'D' => [0x1C, 0x12, 0x11, 0x11, 0x11, 0x12, 0x1C],
+        'E' => [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x1F],
+        'F' => [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x10],
+        'G' => [0x0E, 0x11, 0x10, 0x17, 0x11, 0x11, 0x0F],
+        'H' => [0x11, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11],
+        'I' => [0x0E, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0E],
+        'J' => [0x07, 0x02, 0x02, 0x02, 0x02, 0x12, 0x0C],
+        'K' => [0x11, 0x12, 0x14, 0x18, 0x14, 0x12, 0x11],
+        'L' => [0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x1F],
+        'M' => [0x11, 0x1B, 0x15, 0x15, 0x11, 0x11, 0x11],
+        'N' => [0x11, 0x11, 0x19, 0x15, 0x13, 0x11, 0x11],
+        'O' => [0x0E, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E],
+        'P' => [0x1E, 0x11, 0x11, 0x1E, 0x10, 0x10, 0x10],
+        'Q' => [0x0E, 0x11, 0x11, 0x11, 0x15, 0x12, 0x0D],
+        'R' => [0x1E, 0x11, 0x11, 0x1E, 0x14, 0x12, 0x11],
+        'S' => [0x0F, 0x10, 0x10, 0x0E, 0x01, 0x01, 0x1E],
+        'T' => [0x1F, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04],
+        'U' => [0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E],
+        'V' => [0x11, 0x11, 0x11, 0x11, 0x11, 0x0A, 0x04],
+        'W' => [0x11, 0x11, 0x11, 0x15, 0x15, 0x15, 0x0A],
+        'X' => [0x11, 0x11, 0x0A, 0x04, 0x0A, 0x11, 0x11],
+        'Y' => [0x11, 0x11, 0x11, 0x0A, 0x04, 0x04, 0x04],
+        'Z' => [0x1F, 0x01, 0x02, 0x04, 0x08, 0x10, 0x1F],
+        '#' => [0x0A, 0x0A, 0x1F, 0x0A, 0x1F, 0x0A, 0x0A],
+        '-' => [0x00, 0x00, 0x00, 0x1F, 0x00, 0x00, 0x00],
+        '_' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x1F],
+        '.' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C],
+        ':' => [0x00, 0x0C, 0x0C, 0x00, 0x0C, 0x0C, 0x00],
+        '/' => [0x00, 0x01, 0x02, 0x04, 0x08, 0x10, 0x00],
+        _ => [0x0E, 0x11, 0x01, 0x02, 0x04, 0x00, 0x04],
+    }
+}
+
+/// `image` as an inline `data:` URI, for embedding in SVG or HTML.
+pub(crate) fn png_data_uri(image: &RgbImage) -> Result<String> {
+    let mut png = Vec::new();
+    image
+        .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+        .context("failed to encode the wallpaper thumbnail")?;
+    Ok(format!("data:image/png;base64,{}", base64(&png)))
+}
+
+pub(crate) fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, &b)| n | u32::from(b) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 0x3F) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+pub(crate) fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::ThemeMode;
+    use crate::pipeline::assign::assign_slots;
+    use crate::pipeline::extract::ExtractedColor;
+
+    fn test_palette() -> AnsiPalette {
+        let colors = vec![ExtractedColor {
+            color: Color::new(200, 120, 60),
+            weight: 1.0,
+        }];
+        assign_slots(&colors, ThemeMode::Dark)
+    }
+
+    fn test_image() -> PreparedImage {
+        let lab = Color::new(10, 120, 200).to_lab();
+        PreparedImage {
+            width: 40,
+            height: 20,
+            pixels: vec![lab; 800],
+            frames: 1,
+        }
+    }
+
+    #[test]
+    fn base64_matches_rfc4648_vectors() {
+        for (input, expected) in [
+            ("", ""),
+            ("f", "Zg=="),
+            ("fo", "Zm8="),
+            ("foo", "Zm9v"),
+            ("foob", "Zm9vYg=="),
+            ("fooba", "Zm9vYmE="),
+            ("foobar", "Zm9vYmFy"),
+        ] {
+            assert_eq!(base64(input.as_bytes()), expected);
+        }
+    }
+
+    #[test]
+    fn svg_lists_every_color_and_embeds_the_thumbnail() {
+        let p = test_palette();
+        let thumb = thumbnail(&test_image());
+        let svg = svg(&p, "a <b>", Some(&thumb)).unwrap();
+        assert!(svg.starts_with("<svg "));
+        assert!(svg.contains(">a &lt;b&gt;</text>"));
+        assert!(svg.contains("href=\"data:image/png;base64,iVBOR"));
+        for color in p.slots {
+            assert!(svg.contains(&format!(">{}</text>", color.to_hex())));
+        }
+        assert!(svg.contains(&format!(">bg {}</text>", p.background.to_hex())));
+    }
+
+    #[test]
+    fn thumbnail_keeps_the_aspect_ratio() {
+        let thumb = thumbnail(&test_image());
+        assert_eq!(
+            (thumb.width(), thumb.height()),
+            (THUMB_MAX.0, THUMB_MAX.0 / 2)
+        );
+    }
+
+    #[test]
+    fn png_paints_swatches() {
+        let p = test_palette();
+        let card = png(&p, "test", None);
+        let layout = Layout::new(None);
+        assert_eq!((card.width(), card.height()), (WIDTH, layout.height));
+        for i in [0, 7, 8, 15] {
+            let (x, y, _, _) = layout.slot(i);
+            assert_eq!(*card.get_pixel(x + 1, y + 1), rgb(p.slots[i]));
+        }
+        // The title is drawn in the foreground color
+        let title = (PADDING..PADDING + 4 * 6 * TITLE_SCALE).any(|x| {
+            (layout.title_y..layout.title_y + 7 * TITLE_SCALE)
+                .any(|y| *card.get_pixel(x, y) == rgb(p.foreground))
+        });
+        assert!(title);
+    }
+
+    #[test]
+    fn export_rejects_other_formats() {
+        let err = export(Path::new("card.jpg"), &test_palette(), "x", None).unwrap_err();
+        assert!(err.to_string().contains(".svg or .png"));
+    }
+}
@@ -0,0 +1,215 @@
+//! Accessibility audit for `nuri check`: WCAG contrast of every color against
+//! its background and accents too close to tell apart.
+
+use crate::color::Color;
+use crate::config::SLOT_NAMES;
+use crate::pipeline::assign::AnsiPalette;
+use crate::preview::swatch;
+
+/// WCAG AA minimum for normal text.
+const AA: f32 = 4.5;
+
+/// WCAG AA minimum for large text.
+const AA_LARGE: f32 = 3.0;
+
+/// WCAG AAA minimum for normal text.
+const AAA: f32 = 7.0;
+
+/// WCAG AAA minimum for large text.
+const AAA_LARGE: f32 = 4.5;
+
+/// Accents closer than this delta-E (CIE76) are hard to tell apart in use.
+pub const SIMILAR_DELTA_E: f32 = 10.0;
+
+/// One text color on its background.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContrastRow {
+    pub label: String,
+    pub fg: Color,
+    pub bg: Color,
+    pub ratio: f32,
+    /// Ratio the color must reach, or `None` for colors not meant as text on
+    /// the background (ANSI black).
+    pub required: Option<f32>,
+}
+
+impl ContrastRow {
+    pub fn passes(&self) -> bool {
+        self.required.is_none_or(|min| self.ratio >= min)
+    }
+}
+
+/// Two accent slots that are nearly the same color.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimilarPair {
+    pub a: usize,
+    pub b: usize,
+    pub delta_e: f32,
+}
+
+/// Contrast and distinctness results for one palette.
+#[derive(Debug, Clone)]
+pub struct Report {
+    pub contrast: Vec<ContrastRow>,
+    pub similar: Vec<SimilarPair>,
+}
+
+impl Report {
+    /// Audit `palette`. Text colors (foreground, selection, cursor, accents,
+    /// white) must reach AA for normal text; bright black, used for comments
+    /// and dimmed text, must reach AA for large text. Accent pairs within the
+    /// normal (1-6) and bright (9-14) ranges must be at least
+    /// [`SIMILAR_DELTA_E`] apart.
+    pub fn new(palette: &AnsiPalette) -> Report {
+        let p = palette;
+        let row = |label: &str, fg: Color, bg: Color, required: Option<f32>| ContrastRow {
+            label: label.to_string(),
+            fg,
+            bg,
+            ratio: Color::contrast_ratio(&fg, &bg),
+            required,
+        };
+        let mut contrast = vec![
+            row("foreground", p.foreground, p.background, Some(AA)),
+            row("selection", p.selection_fg, p.selection_bg, Some(AA)),
+            row("cursor", p.cursor_text, p.cursor_color, Some(AA)),
+        ];
+        for (i, name) in SLOT_NAMES.iter().enumerate() {
+            let required = match i {
+                0 => None,
+                8 => Some(AA_LARGE),
+                _ => Some(AA),
+            };
+            contrast.push(row(name, p.slots[i], p.background, required));
+        }
+
+        let mut similar = Vec::new();
+        for range in [1..=6, 9..=14] {
+            for a in range.clone() {
+                for b in a + 1..=*range.end() {
+                    let delta_e = p.slots[a].delta_e(p.slots[b]);
+                    if delta_e < SIMILAR_DELTA_E {
+                        similar.push(SimilarPair { a, b, delta_e });
+                    }
+                }
+            }
+        }
+        Report { contrast, similar }
+    }
+
+    /// Number of failed checks: contrast rows below their requirement plus
+    /// similar accent pairs.
+    pub fn failures(&self) -> usize {
+        self.contrast.iter().filter(|row| !row.passes()).count() + self.similar.len()
+    }
+
+    /// Print the report. `swatches` adds colored sample cells.
+    pub fn print(&self, swatches: bool) {
+        let width = self
+            .contrast
+            .iter()
+            .map(|row| row.label.len())
+            .max()
+            .unwrap_or(0);
+        println!();
+        println!(
+            "  {:<width$}  {}  ratio  AA    AA-L  AAA   AAA-L",
+            "contrast",
+            if swatches { "   " } else { "" }
+        );
+        for row in &self.contrast {
+            let cell = if swatches {
+                swatch(&[row.fg]) + " "
+            } else {
+                String::new()
+            };
+            let levels: Vec<&str> = [AA, AA_LARGE, AAA, AAA_LARGE]
+                .iter()
+                .map(|&min| if row.ratio >= min { "pass" } else { "fail" })
+                .collect();
+            let verdict = match row.required {
+                Some(min) if row.ratio < min => format!("  FAIL: needs {min:.1}:1"),
+                _ => String::new(),
+            };
+            println!(
+                "  {:<width$}  {cell}{:>5.2}:1  {}{verdict}",
+                row.label,
+                row.ratio,
+                levels.join("  ")
+            );
+        }
+        println!();
+        if self.similar.is_empty() {
+            println!("  Accents: all pairs at least \u{394}E {SIMILAR_DELTA_E:.0} apart");
+        } else {
+            for pair in &self.similar {
+                println!(
+                    "  FAIL: {} and {} are too similar (\u{394}E {:.1}, needs {SIMILAR_DELTA_E:.0})",
+                    SLOT_NAMES[pair.a], SLOT_NAMES[pair.b], pair.delta_e
+                );
+            }
+        }
+        println!();
+        match self.failures() {
+            0 => println!("  All checks passed"),
+            n => println!("  {n} check(s) failed"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::ThemeMode;
+    use crate::pipeline::assign::assign_slots;
+    use crate::pipeline::contrast::{enforce_contrast, DEFAULT_ACCENT_CONTRAST};
+    use crate::pipeline::extract::ExtractedColor;
+
+    fn test_palette() -> AnsiPalette {
+        let colors = vec![ExtractedColor {
+            color: Color::new(90, 110, 200),
+            weight: 1.0,
+        }];
+        let mut palette = assign_slots(&colors, ThemeMode::Dark);
+        enforce_contrast(&mut palette, DEFAULT_ACCENT_CONTRAST);
+        palette
+    }
+
+    #[test]
+    fn enforced_palette_passes_contrast() {
+        let report = Report::new(&test_palette());
+        assert_eq!(report.contrast.len(), 19);
+        // enforce_contrast covers the foreground and the slots, not selection
+        for row in report.contrast.iter().filter(|r| r.label != "selection") {
+            assert!(row.passes(), "{row:?}");
+        }
+    }
+
+    #[test]
+    fn dim_accent_fails_contrast() {
+        let mut palette = test_palette();
+        palette.slots[1] = palette.background;
+        let report = Report::new(&palette);
+        let red = report.contrast.iter().find(|r| r.label == "red").unwrap();
+        assert!(!red.passes());
+        assert!(report.failures() >= 1);
+        // ANSI black is exempt even when it matches the background
+        palette.slots[0] = palette.background;
+        let black = &Report::new(&palette).contrast[3];
+        assert_eq!(black.label, "black");
+        assert!(black.passes());
+    }
+
+    #[test]
+    fn flags_near_identical_accents() {
+        let mut palette = test_palette();
+        palette.slots[5] = palette.slots[4].adjust_lightness(0.01);
+        let report = Report::new(&palette);
+        assert!(report
+            .similar
+            .iter()
+            .any(|pair| (pair.a, pair.b) == (4, 5) && pair.delta_e < SIMILAR_DELTA_E));
+        // Normal and bright variants of one hue are not compared
+        assert!(report.similar.iter().all(|pair| pair.b - pair.a < 8));
+    }
+}
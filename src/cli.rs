@@ -1,59 +1,741 @@
 use std::path::PathBuf;
 
-use clap::Parser;
+use clap::{Parser, Subcommand};
+use clap_complete::Shell;
 
 use crate::backends::Target;
+use crate::color::{Color, ColorBlindness};
+use crate::config::is_slot_name;
+use crate::naming::NameTemplate;
+use crate::pipeline::crop::{Crop, Focus};
+use crate::pipeline::extract::{
+    check_colors, ResizeFilter, Sampling, DEFAULT_COLORS, MAX_COLORS, MIN_COLORS,
+};
+use crate::pipeline::profile::BgFrom;
+use crate::pipeline::quantize::Algorithm;
+use crate::pipeline::style::PaletteStyle;
+use crate::pipeline::weighting::Weighting;
+use crate::report::ReportFormat;
+use crate::term::ColorChoice;
 
 /// Generate color themes from wallpaper images.
+///
+/// `nuri <IMAGE> [OPTIONS]` is shorthand for `nuri generate <IMAGE> [OPTIONS]`.
 #[derive(Parser, Debug)]
-#[command(name = "nuri", version, about)]
-pub struct Args {
-    /// Path to the input image
+#[command(
+    name = "nuri",
+    version,
+    about,
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true,
+    arg_required_else_help = true
+)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    #[command(flatten)]
+    pub generate: Option<Args>,
+
+    /// Resolve every target's config directory, and nuri's own, under DIR
+    /// instead of the platform's (~/.config, ~/Library/Application Support,
+    /// %APPDATA%). Same as setting NURI_CONFIG_DIR. Konsole, base16, base24
+    /// (tinty) and pywal install into the data or cache home, and the color
+    /// cache stays in the cache home; they don't move
+    #[arg(long, global = true, value_name = "DIR")]
+    pub config_dir: Option<PathBuf>,
+
+    /// Color previews, swatches and diagnostics: on a terminal unless
+    /// NO_COLOR is set (auto), always or never
+    #[arg(long, global = true, value_enum, value_name = "WHEN", default_value_t)]
+    pub color: ColorChoice,
+}
+
+impl Cli {
+    /// Resolve the bare `nuri <IMAGE>` form into `generate`.
+    pub fn into_command(self) -> Option<Command> {
+        self.command.or(self.generate.map(Command::Generate))
+    }
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Generate a theme from an image (the default command)
+    Generate(Args),
+
+    /// Generate a theme and install it to each target's config directory
+    Install(Args),
+
+    /// Regenerate and install the theme whenever a wallpaper file, or the newest
+    /// image in a directory, changes
+    Watch(Args),
+
+    /// Generate a theme for every image in a directory, named after each
+    /// file, and print a summary
+    Batch(BatchArgs),
+
+    /// List installed themes, marking the ones nuri generated
+    List(ManageArgs),
+
+    /// Browse installed themes in a TUI gallery: preview, activate, duplicate
+    /// into the editor or delete
+    Browse(ManageArgs),
+
+    /// Remove an installed theme from each target's config directory
+    Remove {
+        /// Theme name
+        name: String,
+
+        /// Also remove themes that nuri did not generate
+        #[arg(long)]
+        force: bool,
+
+        #[command(flatten)]
+        manage: ManageArgs,
+    },
+
+    /// List output targets, including user templates in ~/.config/nuri/templates
+    Targets {
+        /// Config file to load instead of ~/.config/nuri/config.toml
+        #[arg(long, value_name = "PATH")]
+        config: Option<PathBuf>,
+    },
+
+    /// Convert an existing theme file (Ghostty, Alacritty, base16, JSON) to other targets
+    Import(ImportArgs),
+
+    /// Re-run the pipeline for a theme from the provenance header nuri wrote into it
+    Regenerate {
+        /// Theme file generated by nuri
+        theme: PathBuf,
+
+        /// Write the regenerated theme here instead of replacing the original
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Config file to load instead of ~/.config/nuri/config.toml
+        #[arg(long, value_name = "PATH")]
+        config: Option<PathBuf>,
+    },
+
+    /// Open an existing theme file in the TUI
+    Edit {
+        /// Theme file to edit
+        theme: PathBuf,
+
+        /// Target theme format(s) to save to, comma-separated
+        #[arg(short = 't', long, value_enum, value_delimiter = ',')]
+        target: Vec<Target>,
+
+        /// Config file to load instead of ~/.config/nuri/config.toml
+        #[arg(long, value_name = "PATH")]
+        config: Option<PathBuf>,
+    },
+
+    /// Print a colored preview of a theme file or image
+    Preview {
+        /// Theme file or image
+        theme: PathBuf,
+
+        /// Show the preview as seen with a color vision deficiency
+        #[arg(long, value_enum, value_name = "TYPE")]
+        simulate: Option<ColorBlindness>,
+    },
+
+    /// Try a theme on this terminal: recolor it, wait for a key (or
+    /// --duration), then put the previous colors back
+    Try {
+        /// Theme file, installed theme name or image
+        theme: String,
+
+        /// Restore the colors after this many seconds instead of waiting for
+        /// a key
+        #[arg(long, value_name = "SECONDS")]
+        duration: Option<f64>,
+
+        /// Config file to load instead of ~/.config/nuri/config.toml
+        #[arg(long, value_name = "PATH")]
+        config: Option<PathBuf>,
+    },
+
+    /// Audit a theme's contrast and accent distinctness; exits non-zero on
+    /// failures
+    Check {
+        /// Theme file, installed theme name or image
+        theme: String,
+
+        /// Config file to load instead of ~/.config/nuri/config.toml
+        #[arg(long, value_name = "PATH")]
+        config: Option<PathBuf>,
+    },
+
+    /// Manage the cache of extracted colors in ~/.cache/nuri
+    Cache {
+        #[command(subcommand)]
+        action: CacheAction,
+    },
+
+    /// Compare two themes slot by slot, with color distances
+    Diff {
+        /// First theme: a file or an installed theme name
+        a: String,
+
+        /// Second theme: a file or an installed theme name
+        b: String,
+
+        /// Config file to load instead of ~/.config/nuri/config.toml
+        #[arg(long, value_name = "PATH")]
+        config: Option<PathBuf>,
+    },
+
+    /// Print a shell completion script
+    ///
+    /// e.g. `nuri completions zsh > ~/.zfunc/_nuri`, or
+    /// `source <(nuri completions bash)` in ~/.bashrc
+    Completions {
+        /// Shell to complete in
+        #[arg(value_enum)]
+        shell: Shell,
+    },
+
+    /// Print the nuri(1) man page, or write a page per subcommand into a
+    /// directory
+    Manpage {
+        /// Write nuri.1, nuri-generate.1, ... into this directory instead
+        #[arg(long, value_name = "DIR")]
+        out_dir: Option<PathBuf>,
+    },
+
+    /// Time each pipeline stage on an image
+    #[command(hide = true)]
+    Bench(BenchArgs),
+}
+
+/// What `nuri cache` does.
+#[derive(Subcommand, Debug)]
+pub enum CacheAction {
+    /// Remove every cached extraction
+    Clear,
+}
+
+/// Options for `nuri batch`: the generate options, applied to every image,
+/// with IMAGE naming the directory to process.
+#[derive(clap::Args, Debug)]
+pub struct BatchArgs {
+    #[command(flatten)]
+    pub args: Args,
+
+    /// Also process images in subdirectories
+    #[arg(short, long)]
+    pub recursive: bool,
+
+    /// Only process images whose file name matches this pattern (`*` and
+    /// `?` wildcards), e.g. `*.png`
+    #[arg(long, value_name = "PATTERN")]
+    pub glob: Option<String>,
+
+    /// Images processed at once (defaults to the number of CPUs)
+    #[arg(short, long, value_name = "N")]
+    pub jobs: Option<usize>,
+}
+
+/// Options for `nuri bench`. The config file is not read, so timings only
+/// depend on these.
+#[derive(clap::Args, Debug)]
+pub struct BenchArgs {
+    /// Image to run the pipeline on
     pub image: PathBuf,
 
+    /// Times to run it; the table shows the median and fastest run
+    #[arg(short = 'n', long, default_value_t = 5, value_name = "N")]
+    pub runs: usize,
+
+    #[arg(
+        short = 'k',
+        long = "colors",
+        value_name = "K",
+        default_value_t = DEFAULT_COLORS,
+        value_parser = parse_colors,
+        help = colors_help(),
+        long_help = colors_long_help()
+    )]
+    pub colors: usize,
+
+    /// Color extraction algorithm
+    #[arg(long, value_enum, default_value_t)]
+    pub algorithm: Algorithm,
+
+    /// Pixel sampling before clustering
+    #[arg(long, value_enum, default_value_t)]
+    pub sampling: Sampling,
+
+    /// Pixel weighting before clustering
+    #[arg(long, value_enum, default_value_t)]
+    pub weighting: Weighting,
+
+    /// Resize the image to fit within this many pixels per side [default: 256]
+    #[arg(long, value_name = "PX")]
+    pub max_dim: Option<u32>,
+
+    /// Filter for that resize
+    #[arg(long, value_enum, default_value_t)]
+    pub resize_filter: ResizeFilter,
+}
+
+/// Options shared by commands that manage installed themes.
+#[derive(clap::Args, Debug)]
+pub struct ManageArgs {
+    /// Target(s) to act on, comma-separated (defaults to all)
+    #[arg(short = 't', long, value_enum, value_delimiter = ',')]
+    pub target: Vec<Target>,
+
+    /// Config file to load instead of ~/.config/nuri/config.toml
+    #[arg(long, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+}
+
+/// Options for converting an existing theme file.
+#[derive(clap::Args, Debug)]
+pub struct ImportArgs {
+    /// Theme file: Ghostty (no extension or .conf), Alacritty .toml, base16 .yaml, or nuri .json
+    pub theme: PathBuf,
+
+    /// Theme name (defaults to the theme's filename stem)
+    #[arg(short, long)]
+    pub name: Option<String>,
+
+    /// Write theme to this file instead of stdout
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+
+    /// Target theme format(s), comma-separated (e.g. zellij,neovim), or the
+    /// name of a template in ~/.config/nuri/templates
+    #[arg(short = 't', long, value_parser = TargetParser, value_delimiter = ',')]
+    pub target: Vec<String>,
+
+    /// Install theme to the target's standard config directory
+    #[arg(long, conflicts_with = "output")]
+    pub install: bool,
+
+    /// Error instead of overwriting an existing theme file
+    #[arg(long)]
+    pub no_clobber: bool,
+
+    /// Overwrite existing theme files, even if the config sets no-clobber
+    #[arg(long, conflicts_with = "no_clobber")]
+    pub force: bool,
+
+    /// Config file to load instead of ~/.config/nuri/config.toml
+    #[arg(long, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+}
+
+/// Options for generating a theme from an image.
+#[derive(clap::Args, Debug, Clone, Default)]
+pub struct Args {
+    /// Path or http(s) URL of the input image, or - to read it from stdin
+    #[arg(required_unless_present = "from_wallpaper")]
+    pub image: Option<PathBuf>,
+
+    /// Use the desktop's current wallpaper instead of an image path
+    #[arg(long, conflicts_with = "image")]
+    pub from_wallpaper: bool,
+
     /// Theme name (defaults to image filename stem)
     #[arg(short, long)]
     pub name: Option<String>,
 
+    /// Build the theme name from placeholders: {stem}, {mode}, {date}, {hash}
+    /// (e.g. "{stem}-{mode}")
+    #[arg(long, value_name = "TEMPLATE", conflicts_with = "name", value_parser = parse_name_template)]
+    pub name_template: Option<NameTemplate>,
+
     /// Force dark or light mode (auto-detected if omitted)
     #[arg(short, long, value_enum)]
     pub mode: Option<ThemeMode>,
 
+    /// Pin a slot to an exact color, e.g. `1=#ff5555` or `background=#101014` (repeatable)
+    #[arg(long = "set", value_name = "SLOT=COLOR", value_parser = parse_slot_override)]
+    pub set: Vec<(String, Color)>,
+
+    /// Keep these colors from the theme being replaced (the --output file or
+    /// the installed theme), e.g. `1,4,background`
+    #[arg(long, value_name = "SLOTS", value_delimiter = ',', value_parser = parse_slot_name)]
+    pub lock: Vec<String>,
+
+    /// Theme to read --lock colors from instead of the replaced one; set by
+    /// `regenerate`, which may write to a different file
+    #[arg(skip)]
+    pub lock_from: Option<PathBuf>,
+
+    /// Generate both a `<name>-dark` and a `<name>-light` theme (needs --install,
+    /// --output or --out-dir)
+    #[arg(long, conflicts_with_all = ["mode", "tui", "apply_live"])]
+    pub both: bool,
+
     /// Write theme to this file instead of stdout
     #[arg(short, long)]
     pub output: Option<PathBuf>,
 
-    /// Target theme format(s), comma-separated (e.g. ghostty,zellij)
-    #[arg(short = 't', long, value_enum, value_delimiter = ',')]
-    pub target: Vec<Target>,
+    /// Write each target's theme into <DIR>/<target>/, named after the theme
+    #[arg(long, value_name = "DIR", conflicts_with_all = ["output", "install", "tui"])]
+    pub out_dir: Option<PathBuf>,
+
+    /// Target theme format(s), comma-separated (e.g. ghostty,zellij), or the
+    /// name of a template in ~/.config/nuri/templates
+    #[arg(short = 't', long, value_parser = TargetParser, value_delimiter = ',')]
+    pub target: Vec<String>,
+
+    /// Template file (or the name of one in ~/.config/nuri/templates) for the
+    /// template target; implies --target template when no target is given.
+    /// Placeholders: {{color0}}, {{background}}, {{red.rgb}}, ...
+    #[arg(long, value_name = "PATH")]
+    pub template: Option<PathBuf>,
 
     /// Install theme to the target's standard config directory
     #[arg(long, conflicts_with = "output")]
     pub install: bool,
 
+    /// Set the installed theme in Ghostty's config (backed up to config.bak) and reload Ghostty
+    #[arg(long, conflicts_with = "tui")]
+    pub activate: bool,
+
     /// Print a colored terminal preview of the palette
     #[arg(long)]
     pub preview: bool,
 
+    /// Print the TUI's screen (image info, palette and preview scene) once as
+    /// ANSI text instead of opening it
+    #[arg(long, conflicts_with = "tui")]
+    pub preview_full: bool,
+
+    /// Print each slot's nearest named color and delta-E, note them in the
+    /// theme's header and explain the pipeline's decisions on stderr (-vv
+    /// adds every candidate considered)
+    #[arg(short, long, action = clap::ArgAction::Count)]
+    pub verbose: u8,
+
+    /// Print nothing but errors: no progress line, warnings or status
+    /// messages
+    #[arg(short, long, conflicts_with = "verbose")]
+    pub quiet: bool,
+
+    /// Show --preview and the TUI as seen with a color vision deficiency (the
+    /// generated theme is unchanged)
+    #[arg(long, value_enum, value_name = "TYPE")]
+    pub simulate: Option<ColorBlindness>,
+
+    /// Also write a palette card (wallpaper, name, swatches and hex codes) to
+    /// this .svg or .png file
+    #[arg(long, value_name = "PATH", conflicts_with = "tui")]
+    pub export_card: Option<PathBuf>,
+
+    /// Also write a standalone HTML preview (palette, sample terminal session
+    /// and contrast ratios) to this file
+    #[arg(long, value_name = "PATH", conflicts_with = "tui")]
+    pub export_html: Option<PathBuf>,
+
+    /// Print a report of the run to stdout: extracted colors, how each slot
+    /// got its color, contrast results and the files written. The theme must
+    /// go to a file (--output, --out-dir or --install)
+    #[arg(
+        long,
+        value_enum,
+        value_name = "FORMAT",
+        conflicts_with_all = ["tui", "preview", "preview_full"]
+    )]
+    pub report: Option<ReportFormat>,
+
     /// Launch interactive TUI mode
     #[arg(long)]
     pub tui: bool,
 
-    /// Number of K-means clusters
-    #[arg(short = 'k', long = "colors", default_value_t = 16)]
-    pub colors: usize,
+    /// In the TUI, show this installed Ghostty theme (or theme file) side by
+    /// side with the new palette
+    #[arg(long, value_name = "THEME", requires = "tui")]
+    pub compare: Option<String>,
+
+    /// Push the palette into the running terminal via OSC escape sequences
+    #[arg(long)]
+    pub apply_live: bool,
+
+    #[arg(
+        short = 'k',
+        long = "colors",
+        value_name = "K",
+        value_parser = parse_colors,
+        help = format!("{} [default: {DEFAULT_COLORS}]", colors_help()),
+        long_help = format!("{}\n\n[default: {DEFAULT_COLORS}]", colors_long_help())
+    )]
+    pub colors: Option<usize>,
+
+    /// Minimum accent contrast ratio against background [default: 4.5]
+    #[arg(long)]
+    pub min_contrast: Option<f32>,
+
+    /// Memory cap in MiB for decoding the image; larger PNGs are streamed [default: 256]
+    #[arg(long, value_name = "MIB")]
+    pub max_memory: Option<u64>,
+
+    /// Resize the image to fit within this many pixels per side before
+    /// extraction; larger is more accurate on detailed wallpapers [default: 256]
+    #[arg(long, value_name = "PX")]
+    pub max_dim: Option<u32>,
+
+    /// Filter for that resize; nearest is much faster for batch runs
+    /// [default: lanczos]
+    #[arg(long, value_enum, value_name = "FILTER")]
+    pub resize_filter: Option<ResizeFilter>,
+
+    /// Frames of an animated GIF, PNG or WebP to pool, spread over the
+    /// animation [default: 8]
+    #[arg(long, value_name = "N")]
+    pub frames: Option<usize>,
+
+    /// Take every N-th frame from the start instead of spreading --frames
+    /// over the whole animation
+    #[arg(long, value_name = "N")]
+    pub frame_stride: Option<usize>,
+
+    /// Take a video frame every this many seconds instead of spreading
+    /// --frames over the whole video (needs the video feature)
+    #[arg(long, value_name = "SECONDS")]
+    pub frame_interval: Option<f64>,
+
+    /// Extract colors only from this rectangle of the image, in its pixels
+    /// (e.g. the part behind the terminal)
+    #[arg(long, value_name = "X,Y,W,H", value_parser = parse_crop)]
+    pub crop: Option<Crop>,
+
+    /// Extract colors only from this half of the image (center: the middle
+    /// half both ways)
+    #[arg(long, value_enum, conflicts_with = "crop")]
+    pub focus: Option<Focus>,
+
+    /// Color extraction algorithm [default: kmeans]
+    #[arg(long, value_enum)]
+    pub algorithm: Option<Algorithm>,
+
+    /// Seed for K-means initialization and pixel sampling [default: 42]
+    #[arg(long)]
+    pub seed: Option<u64>,
+
+    /// Don't read or write the cache of extracted colors (~/.cache/nuri)
+    #[arg(long)]
+    pub no_cache: bool,
+
+    /// Merge extracted colors closer than this delta-E, so near-duplicates
+    /// don't crowd out distinct hues [default: 5]
+    #[arg(long, value_name = "DE")]
+    pub merge_delta_e: Option<f32>,
+
+    /// Drop extracted colors covering less than this percentage of the
+    /// image [default: 0]
+    #[arg(long, value_name = "PERCENT")]
+    pub min_weight: Option<f32>,
+
+    /// Pixel sampling before clustering, to bound K-means cost [default: all]
+    #[arg(long, value_enum)]
+    pub sampling: Option<Sampling>,
 
-    /// Minimum accent contrast ratio against background
-    #[arg(long, default_value_t = 4.5)]
-    pub min_contrast: f32,
+    /// Number of pixels kept by --sampling [default: 16384]
+    #[arg(long, value_name = "N")]
+    pub sample_size: Option<usize>,
 
-    /// Error instead of overwriting when installing an existing theme
+    /// Pixel weighting before clustering; chroma and saliency let small vivid
+    /// details become accents [default: area]
+    #[arg(long, value_enum)]
+    pub weighting: Option<Weighting>,
+
+    /// Accent style(s), comma-separated and applied in order: pastel, vibrant,
+    /// muted, monochrome
+    #[arg(long, value_enum, value_delimiter = ',')]
+    pub style: Vec<PaletteStyle>,
+
+    /// Adjust accent lightness and chroma so red/green and blue/magenta stay
+    /// distinguishable with color vision deficiencies
+    #[arg(long)]
+    pub colorblind_safe: bool,
+
+    /// Accent assignment profile: a built-in (default, gruvbox, nord,
+    /// solarized), a TOML file, or the name of one in
+    /// ~/.config/nuri/profiles
+    #[arg(long, value_name = "NAME|PATH")]
+    pub profile: Option<String>,
+
+    /// Minimum delta-E between the six accents; closer ones are rotated
+    /// toward their target hues, 0 disables [default: 10]
+    #[arg(long, value_name = "DELTA_E")]
+    pub min_separation: Option<f32>,
+
+    /// Background source: darkest (lightest in light mode) extracted color,
+    /// a tint of the dominant hue, or a tint of --bg-color [default: darkest]
+    #[arg(long, value_enum)]
+    pub bg_from: Option<BgFrom>,
+
+    /// Color tinting the background with --bg-from custom
+    #[arg(long, value_name = "COLOR", value_parser = parse_color)]
+    pub bg_color: Option<Color>,
+
+    /// Oklch lightness of the background, 0-1
+    #[arg(long, value_name = "L")]
+    pub bg_lightness: Option<f32>,
+
+    /// Oklch lightness of the foreground, 0-1; white follows it
+    #[arg(long, value_name = "L")]
+    pub fg_lightness: Option<f32>,
+
+    /// Scale the lightness gap between background and text colors: below 1
+    /// for a soft theme, above 1 for high contrast [default: 1]
+    #[arg(long, value_name = "FACTOR")]
+    pub contrast_envelope: Option<f32>,
+
+    /// Also write colors 16-255, derived from the theme, for targets that
+    /// support them (ghostty, wezterm)
+    #[arg(long)]
+    pub extended: bool,
+
+    /// Config file to load instead of ~/.config/nuri/config.toml
+    #[arg(long, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+
+    /// Error instead of overwriting an existing theme file
     #[arg(long)]
     pub no_clobber: bool,
+
+    /// Overwrite existing theme files, even if the config sets no-clobber
+    #[arg(long, conflicts_with = "no_clobber")]
+    pub force: bool,
+}
+
+/// Parse a `#rrggbb` color value.
+fn parse_color(s: &str) -> Result<Color, String> {
+    Color::from_hex(s.trim()).map_err(|e| e.to_string())
+}
+
+/// Parse a `--crop X,Y,W,H` value.
+fn parse_crop(s: &str) -> Result<Crop, String> {
+    Crop::parse(s).map_err(|e| e.to_string())
+}
+
+/// `-k/--colors` help, from the limits [`parse_colors`] enforces.
+fn colors_help() -> String {
+    format!("Colors to extract (clusters), {MIN_COLORS} to {MAX_COLORS}")
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+/// `-k/--colors --help`: what a larger or smaller count does to the accents.
+fn colors_long_help() -> String {
+    format!(
+        "{}\n\n\
+         The image is clustered into this many colors, and the background, foreground and six \
+         accent hues are picked from them. More clusters give small but vivid areas a cluster \
+         of their own, so accents are more diverse and more of them come from the image; fewer \
+         fold those areas into the dominant colors, and accents with no matching hue are \
+         synthesized. Below {MIN_COLORS} most accents would be synthesized; past about 32 \
+         clusters mostly split shades of colors already found, and extraction gets slower.",
+        colors_help()
+    )
+}
+
+/// Parses `--target`: any name is accepted here, since user templates are
+/// only known once the config is loaded, but help and completions list the
+/// built-in targets.
+#[derive(Debug, Clone, Copy)]
+struct TargetParser;
+
+impl clap::builder::TypedValueParser for TargetParser {
+    type Value = String;
+
+    fn parse_ref(
+        &self,
+        cmd: &clap::Command,
+        arg: Option<&clap::Arg>,
+        value: &std::ffi::OsStr,
+    ) -> Result<String, clap::Error> {
+        clap::builder::StringValueParser::new().parse_ref(cmd, arg, value)
+    }
+
+    fn possible_values(
+        &self,
+    ) -> Option<Box<dyn Iterator<Item = clap::builder::PossibleValue> + '_>> {
+        Some(Box::new(
+            Target::all()
+                .iter()
+                .filter_map(clap::ValueEnum::to_possible_value),
+        ))
+    }
+}
+
+/// Parse a `-k/--colors` value within [`MIN_COLORS`] and [`MAX_COLORS`].
+fn parse_colors(s: &str) -> Result<usize, String> {
+    let k = s
+        .parse()
+        .map_err(|_| format!("'{s}' is not a number of colors"))?;
+    check_colors(k).map_err(|e| e.to_string())
+}
+
+/// Parse a `--name-template` value.
+fn parse_name_template(s: &str) -> Result<NameTemplate, String> {
+    NameTemplate::parse(s).map_err(|e| e.to_string())
+}
+
+/// Parse a `--set SLOT=COLOR` value.
+fn parse_slot_override(s: &str) -> Result<(String, Color), String> {
+    let (slot, color) = s
+        .split_once('=')
+        .ok_or_else(|| format!("expected SLOT=COLOR, got '{s}'"))?;
+    let slot = parse_slot_name(slot)?;
+    let color = Color::from_hex(color.trim()).map_err(|e| e.to_string())?;
+    Ok((slot, color))
+}
+
+fn parse_slot_name(s: &str) -> Result<String, String> {
+    let slot = s.trim();
+    if !is_slot_name(slot) {
+        return Err(format!(
+            "unknown slot '{slot}' (use 0-15, an ANSI name like red or bright-blue, \
+             or background/foreground/cursor/cursor-text/selection-bg/selection-fg)"
+        ));
+    }
+    Ok(slot.to_string())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum ThemeMode {
     Dark,
     Light,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slot_override_parses_index_and_names() {
+        assert_eq!(
+            parse_slot_override("1=#ff5555").unwrap(),
+            ("1".to_string(), Color::new(0xff, 0x55, 0x55))
+        );
+        assert_eq!(
+            parse_slot_override("background=101014").unwrap(),
+            ("background".to_string(), Color::new(0x10, 0x10, 0x14))
+        );
+    }
+
+    #[test]
+    fn slot_override_rejects_bad_input() {
+        assert!(parse_slot_override("1").is_err());
+        assert!(parse_slot_override("16=#ffffff").is_err());
+        assert!(parse_slot_override("orange=#ffffff").is_err());
+        assert!(parse_slot_override("red=#fff").is_err());
+    }
+
+    #[test]
+    fn lock_takes_slot_names() {
+        assert_eq!(parse_slot_name(" bright-red ").unwrap(), "bright-red");
+        assert_eq!(parse_slot_name("cursor-text").unwrap(), "cursor-text");
+        assert!(parse_slot_name("16").is_err());
+        assert!(parse_slot_name("orange").is_err());
+    }
+}
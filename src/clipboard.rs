@@ -0,0 +1,65 @@
+use std::io::Write;
+
+use anyhow::{Context, Result};
+
+use crate::card::base64;
+
+/// Where copied text ended up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CopyMethod {
+    /// The system clipboard, through the display server.
+    System,
+    /// OSC 52, handed to the terminal (works over SSH).
+    Osc52,
+}
+
+/// Build the OSC 52 sequence that asks the terminal to put `text` on the
+/// clipboard.
+pub fn osc52_sequence(text: &str) -> String {
+    format!("\x1b]52;c;{}\x07", base64(text.as_bytes()))
+}
+
+/// Whether nuri runs over SSH, where the local display server (if any) is
+/// not the user's.
+fn in_ssh_session() -> bool {
+    std::env::var_os("SSH_TTY").is_some() || std::env::var_os("SSH_CONNECTION").is_some()
+}
+
+/// Keeps a system clipboard handle open; on X11 and Wayland the copied text
+/// is only served while it lives.
+#[derive(Default)]
+pub struct Clipboard {
+    system: Option<arboard::Clipboard>,
+}
+
+impl Clipboard {
+    /// Copy `text` to the system clipboard, falling back to OSC 52 through
+    /// `out` over SSH or when no system clipboard is available.
+    pub fn copy(&mut self, text: &str, out: &mut impl Write) -> Result<CopyMethod> {
+        if !in_ssh_session() && self.copy_system(text).is_ok() {
+            return Ok(CopyMethod::System);
+        }
+        out.write_all(osc52_sequence(text).as_bytes())
+            .and_then(|_| out.flush())
+            .context("failed to write the clipboard sequence to the terminal")?;
+        Ok(CopyMethod::Osc52)
+    }
+
+    fn copy_system(&mut self, text: &str) -> Result<(), arboard::Error> {
+        let clipboard = match &mut self.system {
+            Some(clipboard) => clipboard,
+            None => self.system.insert(arboard::Clipboard::new()?),
+        };
+        clipboard.set_text(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn osc52_sequence_base64_encodes_the_text() {
+        assert_eq!(osc52_sequence("#ff5555"), "\x1b]52;c;I2ZmNTU1NQ==\x07");
+    }
+}
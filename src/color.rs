@@ -1,9 +1,64 @@
-use anyhow::{bail, Result};
-use palette::{FromColor, IntoColor, Lab, Oklch, Srgb};
+use anyhow::{bail, Context, Result};
+use palette::convert::FromColorUnclamped;
+use palette::{FromColor, IntoColor, Lab, LinSrgb, Oklab, Oklch, Srgb};
+use serde::{Deserialize, Serialize};
+
+/// A dichromatic color vision deficiency, for simulating how a palette looks
+/// to people who have it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ColorBlindness {
+    /// Missing green cones: red and green are confused (the most common).
+    Deuteranopia,
+    /// Missing red cones: red and green are confused and reds look dark.
+    Protanopia,
+    /// Missing blue cones: blue and green, yellow and pink are confused.
+    Tritanopia,
+}
+
+impl ColorBlindness {
+    pub const ALL: [ColorBlindness; 3] = [
+        ColorBlindness::Deuteranopia,
+        ColorBlindness::Protanopia,
+        ColorBlindness::Tritanopia,
+    ];
+
+    /// Lowercase name, as accepted by `--simulate`.
+    pub fn name(self) -> &'static str {
+        match self {
+            ColorBlindness::Deuteranopia => "deuteranopia",
+            ColorBlindness::Protanopia => "protanopia",
+            ColorBlindness::Tritanopia => "tritanopia",
+        }
+    }
+
+    /// Machado, Oliveira & Fernandes (2009) simulation matrix at full
+    /// severity, applied to linear RGB.
+    fn matrix(self) -> [[f32; 3]; 3] {
+        match self {
+            ColorBlindness::Deuteranopia => [
+                [0.367322, 0.860646, -0.227968],
+                [0.280085, 0.672501, 0.047413],
+                [-0.011820, 0.042940, 0.968881],
+            ],
+            ColorBlindness::Protanopia => [
+                [0.152286, 1.052583, -0.204868],
+                [0.114503, 0.786281, 0.099216],
+                [-0.003882, -0.048116, 1.051998],
+            ],
+            ColorBlindness::Tritanopia => [
+                [1.255528, -0.076749, -0.178779],
+                [-0.078411, 0.930809, 0.147602],
+                [0.004733, 0.691367, 0.303900],
+            ],
+        }
+    }
+}
 
 /// Core color type used throughout the pipeline.
 /// Wraps sRGB u8 components and provides conversions to perceptual color spaces.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Serializes as a lowercase `#rrggbb` string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
 pub struct Color {
     pub r: u8,
     pub g: u8,
@@ -16,7 +71,6 @@ impl Color {
     }
 
     /// Parse a hex color string like `#ff8800` or `#FF8800`.
-    #[allow(dead_code)]
     pub fn from_hex(hex: &str) -> Result<Self> {
         let hex = hex.strip_prefix('#').unwrap_or(hex);
         if hex.len() != 6 {
@@ -25,12 +79,55 @@ impl Color {
                 hex.len()
             );
         }
+        if !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+            bail!("invalid hex color: '{hex}' contains non-hex characters");
+        }
         let r = u8::from_str_radix(&hex[0..2], 16)?;
         let g = u8::from_str_radix(&hex[2..4], 16)?;
         let b = u8::from_str_radix(&hex[4..6], 16)?;
         Ok(Self { r, g, b })
     }
 
+    /// Parse Oklch lightness, chroma and hue separated by spaces, like
+    /// `0.72 0.14 28.4`. Lightness is 0-1, chroma 0-0.4, hue in degrees.
+    /// Colors outside sRGB lose chroma as in [`Color::from_oklch`].
+    pub fn from_oklch_str(text: &str) -> Result<Self> {
+        let values = text
+            .split_whitespace()
+            .map(|v| {
+                v.parse::<f32>()
+                    .ok()
+                    .filter(|v| v.is_finite())
+                    .with_context(|| format!("invalid Oklch value '{v}'"))
+            })
+            .collect::<Result<Vec<f32>>>()?;
+        let [l, chroma, hue] = values[..] else {
+            bail!(
+                "invalid Oklch color: expected lightness, chroma and hue, got {} value(s)",
+                values.len()
+            );
+        };
+        if !(0.0..=1.0).contains(&l) {
+            bail!("invalid Oklch lightness {l}: must be between 0 and 1");
+        }
+        if !(0.0..=0.4).contains(&chroma) {
+            bail!("invalid Oklch chroma {chroma}: must be between 0 and 0.4");
+        }
+        Ok(Self::from_oklch(Oklch::new(l, chroma, hue)))
+    }
+
+    /// Oklch lightness, chroma and hue as [`Color::from_oklch_str`] reads
+    /// them.
+    pub fn to_oklch_string(self) -> String {
+        let oklch = self.to_oklch();
+        format!(
+            "{:.3} {:.3} {:.1}",
+            oklch.l,
+            oklch.chroma,
+            oklch.hue.into_positive_degrees()
+        )
+    }
+
     /// Serialize to lowercase hex `#rrggbb`.
     pub fn to_hex(self) -> String {
         format!("#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
@@ -70,8 +167,19 @@ impl Color {
     }
 
     /// Create from Oklch.
+    ///
+    /// Out-of-gamut colors are mapped into sRGB by reducing chroma at
+    /// constant lightness and hue, rather than clipping each channel, which
+    /// would shift the hue.
     pub fn from_oklch(oklch: Oklch) -> Self {
-        let srgb_f32: Srgb<f32> = Srgb::from_color(oklch);
+        let l = oklch.l.clamp(0.0, 1.0);
+        let hue = oklch.hue.into_degrees();
+        let chroma = oklch.chroma.max(0.0);
+        let srgb_f32: Srgb<f32> = if in_gamut(l, chroma, hue) {
+            Srgb::from_color(Oklch::new(l, chroma, hue))
+        } else {
+            Srgb::from_color(Oklch::new(l, fit_chroma(l, chroma, hue), hue))
+        };
         Self::from_srgb_f32_clamped(srgb_f32)
     }
 
@@ -126,6 +234,336 @@ impl Color {
         oklch.chroma = (oklch.chroma + delta).clamp(0.0, 0.4);
         Color::from_oklch(oklch)
     }
+
+    /// Interpolate toward `other` in Oklab: `t = 0` is `self`, `t = 1` is
+    /// `other`. A straight line rather than an arc around the Oklch hue
+    /// circle, so mixing toward a gray fades the chroma without swinging the
+    /// hue; the result is gamut-mapped like [`from_oklch`](Self::from_oklch).
+    pub fn mix(self, other: Color, t: f32) -> Color {
+        let oklab = |color: Color| -> Oklab {
+            let srgb_f32: Srgb<f32> = color.to_srgb_u8().into_format();
+            srgb_f32.into_color()
+        };
+        let (a, b) = (oklab(self), oklab(other));
+        let mixed = Oklab::new(
+            a.l + (b.l - a.l) * t,
+            a.a + (b.a - a.a) * t,
+            a.b + (b.b - a.b) * t,
+        );
+        Color::from_oklch(Oklch::from_color(mixed))
+    }
+
+    /// CIE76 color difference: Euclidean distance in Lab. Around 2.3 is a
+    /// just-noticeable difference.
+    pub fn delta_e(self, other: Color) -> f32 {
+        let (a, b) = (self.to_lab(), other.to_lab());
+        ((a.l - b.l).powi(2) + (a.a - b.a).powi(2) + (a.b - b.b).powi(2)).sqrt()
+    }
+
+    /// Nearest CSS/X11 named color and its delta-E from this color.
+    pub fn nearest_named(self) -> (&'static str, f32) {
+        NAMED_COLORS
+            .iter()
+            .map(|&(name, r, g, b)| (name, self.delta_e(Color::new(r, g, b))))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .expect("named color table is not empty")
+    }
+
+    /// Rotate Oklch hue by `degrees`. Positive = counter-clockwise (red → yellow).
+    /// Lightness and chroma are preserved.
+    pub fn adjust_hue(self, degrees: f32) -> Color {
+        let mut oklch = self.to_oklch();
+        oklch.hue += degrees;
+        Color::from_oklch(oklch)
+    }
+
+    /// How this color appears with the given color vision deficiency.
+    pub fn simulate(self, kind: ColorBlindness) -> Color {
+        let srgb_f32: Srgb<f32> = self.to_srgb_u8().into_format();
+        let lin = srgb_f32.into_linear();
+        let [r, g, b] = kind
+            .matrix()
+            .map(|row| row[0] * lin.red + row[1] * lin.green + row[2] * lin.blue);
+        Self::from_srgb_f32_clamped(Srgb::from_linear(LinSrgb::new(
+            r.clamp(0.0, 1.0),
+            g.clamp(0.0, 1.0),
+            b.clamp(0.0, 1.0),
+        )))
+    }
+}
+
+/// CSS named colors (the X11 color names xterm accepts, minus aliases such
+/// as `aqua` and `grey`), for [`Color::nearest_named`].
+const NAMED_COLORS: [(&str, u8, u8, u8); 139] = [
+    ("black", 0x00, 0x00, 0x00),
+    ("dimgray", 0x69, 0x69, 0x69),
+    ("gray", 0x80, 0x80, 0x80),
+    ("darkgray", 0xa9, 0xa9, 0xa9),
+    ("silver", 0xc0, 0xc0, 0xc0),
+    ("lightgray", 0xd3, 0xd3, 0xd3),
+    ("gainsboro", 0xdc, 0xdc, 0xdc),
+    ("whitesmoke", 0xf5, 0xf5, 0xf5),
+    ("white", 0xff, 0xff, 0xff),
+    ("snow", 0xff, 0xfa, 0xfa),
+    ("ivory", 0xff, 0xff, 0xf0),
+    ("floralwhite", 0xff, 0xfa, 0xf0),
+    ("seashell", 0xff, 0xf5, 0xee),
+    ("ghostwhite", 0xf8, 0xf8, 0xff),
+    ("mintcream", 0xf5, 0xff, 0xfa),
+    ("azure", 0xf0, 0xff, 0xff),
+    ("aliceblue", 0xf0, 0xf8, 0xff),
+    ("honeydew", 0xf0, 0xff, 0xf0),
+    ("lavenderblush", 0xff, 0xf0, 0xf5),
+    ("oldlace", 0xfd, 0xf5, 0xe6),
+    ("linen", 0xfa, 0xf0, 0xe6),
+    ("beige", 0xf5, 0xf5, 0xdc),
+    ("antiquewhite", 0xfa, 0xeb, 0xd7),
+    ("cornsilk", 0xff, 0xf8, 0xdc),
+    ("lightyellow", 0xff, 0xff, 0xe0),
+    ("lemonchiffon", 0xff, 0xfa, 0xcd),
+    ("lightgoldenrodyellow", 0xfa, 0xfa, 0xd2),
+    ("papayawhip", 0xff, 0xef, 0xd5),
+    ("blanchedalmond", 0xff, 0xeb, 0xcd),
+    ("bisque", 0xff, 0xe4, 0xc4),
+    ("moccasin", 0xff, 0xe4, 0xb5),
+    ("navajowhite", 0xff, 0xde, 0xad),
+    ("peachpuff", 0xff, 0xda, 0xb9),
+    ("wheat", 0xf5, 0xde, 0xb3),
+    ("mistyrose", 0xff, 0xe4, 0xe1),
+    ("pink", 0xff, 0xc0, 0xcb),
+    ("lightpink", 0xff, 0xb6, 0xc1),
+    ("hotpink", 0xff, 0x69, 0xb4),
+    ("deeppink", 0xff, 0x14, 0x93),
+    ("palevioletred", 0xdb, 0x70, 0x93),
+    ("mediumvioletred", 0xc7, 0x15, 0x85),
+    ("lightsalmon", 0xff, 0xa0, 0x7a),
+    ("salmon", 0xfa, 0x80, 0x72),
+    ("darksalmon", 0xe9, 0x96, 0x7a),
+    ("lightcoral", 0xf0, 0x80, 0x80),
+    ("indianred", 0xcd, 0x5c, 0x5c),
+    ("crimson", 0xdc, 0x14, 0x3c),
+    ("red", 0xff, 0x00, 0x00),
+    ("firebrick", 0xb2, 0x22, 0x22),
+    ("darkred", 0x8b, 0x00, 0x00),
+    ("maroon", 0x80, 0x00, 0x00),
+    ("brown", 0xa5, 0x2a, 0x2a),
+    ("sienna", 0xa0, 0x52, 0x2d),
+    ("saddlebrown", 0x8b, 0x45, 0x13),
+    ("chocolate", 0xd2, 0x69, 0x1e),
+    ("peru", 0xcd, 0x85, 0x3f),
+    ("sandybrown", 0xf4, 0xa4, 0x60),
+    ("burlywood", 0xde, 0xb8, 0x87),
+    ("tan", 0xd2, 0xb4, 0x8c),
+    ("rosybrown", 0xbc, 0x8f, 0x8f),
+    ("coral", 0xff, 0x7f, 0x50),
+    ("tomato", 0xff, 0x63, 0x47),
+    ("orangered", 0xff, 0x45, 0x00),
+    ("darkorange", 0xff, 0x8c, 0x00),
+    ("orange", 0xff, 0xa5, 0x00),
+    ("gold", 0xff, 0xd7, 0x00),
+    ("goldenrod", 0xda, 0xa5, 0x20),
+    ("darkgoldenrod", 0xb8, 0x86, 0x0b),
+    ("yellow", 0xff, 0xff, 0x00),
+    ("khaki", 0xf0, 0xe6, 0x8c),
+    ("palegoldenrod", 0xee, 0xe8, 0xaa),
+    ("darkkhaki", 0xbd, 0xb7, 0x6b),
+    ("olive", 0x80, 0x80, 0x00),
+    ("olivedrab", 0x6b, 0x8e, 0x23),
+    ("yellowgreen", 0x9a, 0xcd, 0x32),
+    ("darkolivegreen", 0x55, 0x6b, 0x2f),
+    ("greenyellow", 0xad, 0xff, 0x2f),
+    ("chartreuse", 0x7f, 0xff, 0x00),
+    ("lawngreen", 0x7c, 0xfc, 0x00),
+    ("lime", 0x00, 0xff, 0x00),
+    ("limegreen", 0x32, 0xcd, 0x32),
+    ("palegreen", 0x98, 0xfb, 0x98),
+    ("lightgreen", 0x90, 0xee, 0x90),
+    ("mediumspringgreen", 0x00, 0xfa, 0x9a),
+    ("springgreen", 0x00, 0xff, 0x7f),
+    ("mediumseagreen", 0x3c, 0xb3, 0x71),
+    ("seagreen", 0x2e, 0x8b, 0x57),
+    ("forestgreen", 0x22, 0x8b, 0x22),
+    ("green", 0x00, 0x80, 0x00),
+    ("darkgreen", 0x00, 0x64, 0x00),
+    ("darkseagreen", 0x8f, 0xbc, 0x8f),
+    ("mediumaquamarine", 0x66, 0xcd, 0xaa),
+    ("aquamarine", 0x7f, 0xff, 0xd4),
+    ("turquoise", 0x40, 0xe0, 0xd0),
+    ("mediumturquoise", 0x48, 0xd1, 0xcc),
+    ("darkturquoise", 0x00, 0xce, 0xd1),
+    ("lightseagreen", 0x20, 0xb2, 0xaa),
+    ("darkcyan", 0x00, 0x8b, 0x8b),
+    ("teal", 0x00, 0x80, 0x80),
+    ("cyan", 0x00, 0xff, 0xff),
+    ("lightcyan", 0xe0, 0xff, 0xff),
+    ("paleturquoise", 0xaf, 0xee, 0xee),
+    ("cadetblue", 0x5f, 0x9e, 0xa0),
+    ("powderblue", 0xb0, 0xe0, 0xe6),
+    ("lightblue", 0xad, 0xd8, 0xe6),
+    ("skyblue", 0x87, 0xce, 0xeb),
+    ("lightskyblue", 0x87, 0xce, 0xfa),
+    ("deepskyblue", 0x00, 0xbf, 0xff),
+    ("dodgerblue", 0x1e, 0x90, 0xff),
+    ("cornflowerblue", 0x64, 0x95, 0xed),
+    ("steelblue", 0x46, 0x82, 0xb4),
+    ("lightsteelblue", 0xb0, 0xc4, 0xde),
+    ("royalblue", 0x41, 0x69, 0xe1),
+    ("blue", 0x00, 0x00, 0xff),
+    ("mediumblue", 0x00, 0x00, 0xcd),
+    ("darkblue", 0x00, 0x00, 0x8b),
+    ("navy", 0x00, 0x00, 0x80),
+    ("midnightblue", 0x19, 0x19, 0x70),
+    ("slategray", 0x70, 0x80, 0x90),
+    ("lightslategray", 0x77, 0x88, 0x99),
+    ("darkslategray", 0x2f, 0x4f, 0x4f),
+    ("lavender", 0xe6, 0xe6, 0xfa),
+    ("thistle", 0xd8, 0xbf, 0xd8),
+    ("plum", 0xdd, 0xa0, 0xdd),
+    ("violet", 0xee, 0x82, 0xee),
+    ("orchid", 0xda, 0x70, 0xd6),
+    ("magenta", 0xff, 0x00, 0xff),
+    ("mediumorchid", 0xba, 0x55, 0xd3),
+    ("mediumpurple", 0x93, 0x70, 0xdb),
+    ("rebeccapurple", 0x66, 0x33, 0x99),
+    ("blueviolet", 0x8a, 0x2b, 0xe2),
+    ("darkviolet", 0x94, 0x00, 0xd3),
+    ("darkorchid", 0x99, 0x32, 0xcc),
+    ("darkmagenta", 0x8b, 0x00, 0x8b),
+    ("purple", 0x80, 0x00, 0x80),
+    ("indigo", 0x4b, 0x00, 0x82),
+    ("darkslateblue", 0x48, 0x3d, 0x8b),
+    ("slateblue", 0x6a, 0x5a, 0xcd),
+    ("mediumslateblue", 0x7b, 0x68, 0xee),
+];
+
+/// Largest Oklch chroma at lightness `l` and `hue` that stays inside sRGB.
+pub fn max_chroma(l: f32, hue: f32) -> f32 {
+    fit_chroma(l.clamp(0.0, 1.0), MAX_CHROMA, hue)
+}
+
+/// Upper bound of Oklch chroma inside sRGB.
+const MAX_CHROMA: f32 = 0.4;
+
+/// Whether an Oklch color converts to sRGB without clipping.
+fn in_gamut(l: f32, chroma: f32, hue: f32) -> bool {
+    let rgb = Srgb::from_color_unclamped(Oklch::new(l, chroma, hue));
+    [rgb.red, rgb.green, rgb.blue]
+        .iter()
+        .all(|v| (-1e-4..=1.0 + 1e-4).contains(v))
+}
+
+/// Binary-search the largest in-gamut chroma up to `chroma`.
+fn fit_chroma(l: f32, chroma: f32, hue: f32) -> f32 {
+    let (mut low, mut high) = (0.0, chroma);
+    for _ in 0..20 {
+        let mid = (low + high) / 2.0;
+        if in_gamut(l, mid, hue) {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+    low
+}
+
+/// [`Color::to_lab`] for a whole image: appends the CIELAB value of each
+/// three bytes of `rgb` to `out`. With the `simd` feature, eight pixels are
+/// converted at a time and only the last few go through palette.
+pub fn srgb_to_lab_bulk(rgb: &[u8], out: &mut Vec<Lab>) {
+    out.reserve(rgb.len() / 3);
+    #[cfg(feature = "simd")]
+    let rgb = simd::srgb_to_lab(rgb, out);
+    out.extend(rgb.chunks_exact(3).map(|p| -> Lab {
+        let srgb: Srgb<f32> = Srgb::new(p[0], p[1], p[2]).into_format();
+        srgb.into_color()
+    }));
+}
+
+#[cfg(feature = "simd")]
+mod simd {
+    use std::sync::OnceLock;
+
+    use palette::{Lab, Srgb};
+    use wide::{f32x8, CmpGt};
+
+    const LANES: usize = 8;
+
+    /// Linear sRGB to XYZ, each row divided by the D65 white point's
+    /// component, as palette converts it.
+    const XYZ: [[f32; 3]; 3] = [
+        [
+            0.412_456_4 / 0.950_47,
+            0.357_576_1 / 0.950_47,
+            0.180_437_5 / 0.950_47,
+        ],
+        [0.212_672_9, 0.715_152_2, 0.072_175],
+        [
+            0.019_333_9 / 1.088_83,
+            0.119_192 / 1.088_83,
+            0.950_304_1 / 1.088_83,
+        ],
+    ];
+    /// `(6/29)^3`: below it, the CIELAB curve is linear.
+    const EPSILON: f32 = 216.0 / 24389.0;
+    /// `(29/3)^3`.
+    const KAPPA: f32 = 24389.0 / 27.0;
+
+    /// The linear value of every sRGB byte, so decoding the transfer curve
+    /// is a lookup rather than a `powf` per channel.
+    fn linear_table() -> &'static [f32; 256] {
+        static TABLE: OnceLock<[f32; 256]> = OnceLock::new();
+        TABLE.get_or_init(|| {
+            std::array::from_fn(|i| {
+                Srgb::new(i as u8, 0, 0)
+                    .into_format::<f32>()
+                    .into_linear()
+                    .red
+            })
+        })
+    }
+
+    /// Convert every whole group of eight pixels, returning the bytes left
+    /// over.
+    pub(super) fn srgb_to_lab<'a>(rgb: &'a [u8], out: &mut Vec<Lab>) -> &'a [u8] {
+        let table = linear_table();
+        let mut chunks = rgb.chunks_exact(3 * LANES);
+        for chunk in &mut chunks {
+            let channel = |c: usize| {
+                f32x8::new(std::array::from_fn(|i| {
+                    table[usize::from(chunk[3 * i + c])]
+                }))
+            };
+            let (r, g, b) = (channel(0), channel(1), channel(2));
+            let [fx, fy, fz] = XYZ.map(|row| lab_curve(r * row[0] + g * row[1] + b * row[2]));
+
+            let l = (fy * 116.0 - 16.0).to_array();
+            let a = ((fx - fy) * 500.0).to_array();
+            let b = ((fy - fz) * 200.0).to_array();
+            out.extend((0..LANES).map(|i| Lab::new(l[i], a[i], b[i])));
+        }
+        chunks.remainder()
+    }
+
+    /// CIELAB's compressive `f(t)`: a cube root, linear near black.
+    fn lab_curve(t: f32x8) -> f32x8 {
+        let linear = t * (KAPPA / 116.0) + 16.0 / 116.0;
+        t.simd_gt(f32x8::splat(EPSILON))
+            .blend(t.powf(1.0 / 3.0), linear)
+    }
+}
+
+impl From<Color> for String {
+    fn from(c: Color) -> Self {
+        c.to_hex()
+    }
+}
+
+impl TryFrom<String> for Color {
+    type Error = anyhow::Error;
+
+    fn try_from(s: String) -> Result<Self> {
+        Color::from_hex(&s)
+    }
 }
 
 impl std::fmt::Display for Color {
@@ -176,6 +614,34 @@ mod tests {
         assert!(Color::from_hex("#gggggg").is_err());
     }
 
+    #[test]
+    fn hex_rejects_sign_and_non_ascii() {
+        assert!(Color::from_hex("+fffff").is_err());
+        assert!(Color::from_hex("aé000").is_err());
+    }
+
+    #[test]
+    fn oklch_string_round_trip() {
+        let color = Color::new(200, 100, 50);
+        let text = color.to_oklch_string();
+        let parsed = Color::from_oklch_str(&text).unwrap();
+        assert!(color.delta_e(parsed) < 0.5, "{text} parsed as {parsed:?}");
+        assert_eq!(
+            Color::from_oklch_str("  1   0 0 ").unwrap(),
+            Color::new(255, 255, 255)
+        );
+    }
+
+    #[test]
+    fn oklch_string_rejects_bad_input() {
+        assert!(Color::from_oklch_str("0.5 0.1").is_err());
+        assert!(Color::from_oklch_str("0.5 0.1 20 4").is_err());
+        assert!(Color::from_oklch_str("1.5 0.1 20").is_err());
+        assert!(Color::from_oklch_str("0.5 0.5 20").is_err());
+        assert!(Color::from_oklch_str("0.5 x 20").is_err());
+        assert!(Color::from_oklch_str("0.5 0.1 NaN").is_err());
+    }
+
     #[test]
     fn srgb_to_lab_round_trip() {
         let colors = [
@@ -212,6 +678,31 @@ mod tests {
         }
     }
 
+    #[test]
+    fn bulk_lab_conversion_matches_to_lab() {
+        // A coarse sweep of the RGB cube, plus a few pixels past the last
+        // whole group of eight
+        let mut colors: Vec<Color> = (0..=255u8)
+            .step_by(5)
+            .flat_map(|r| (0..=255u8).step_by(15).map(move |g| (r, g)))
+            .flat_map(|(r, g)| (0..=255u8).step_by(15).map(move |b| Color::new(r, g, b)))
+            .collect();
+        colors.extend([BLACK, WHITE, Color::new(1, 2, 3)]);
+        let rgb: Vec<u8> = colors.iter().flat_map(|c| [c.r, c.g, c.b]).collect();
+
+        let mut labs = vec![Lab::new(0.0, 0.0, 0.0)];
+        srgb_to_lab_bulk(&rgb, &mut labs);
+        assert_eq!(labs.len(), colors.len() + 1);
+        for (color, lab) in colors.iter().zip(&labs[1..]) {
+            let expected = color.to_lab();
+            let error = (lab.l - expected.l)
+                .abs()
+                .max((lab.a - expected.a).abs())
+                .max((lab.b - expected.b).abs());
+            assert!(error < 1e-2, "{color}: {lab:?} vs {expected:?}");
+        }
+    }
+
     #[test]
     fn srgb_to_oklch_round_trip() {
         let colors = [
@@ -290,6 +781,28 @@ mod tests {
         );
     }
 
+    #[test]
+    fn mix_interpolates_between_endpoints() {
+        let white = Color::new(255, 255, 255);
+        assert_eq!(BLACK.mix(white, 0.0), BLACK);
+        assert_eq!(BLACK.mix(white, 1.0), white);
+        let mid = BLACK.mix(white, 0.5);
+        assert!(mid.r == mid.g && mid.g == mid.b, "{mid}");
+        assert!(mid.r > 64 && mid.r < 192, "{mid}");
+    }
+
+    #[test]
+    fn mix_toward_gray_keeps_the_hue() {
+        let blue = Color::new(0x40, 0x60, 0xf0);
+        let gray = Color::new(0x1e, 0x1e, 0x1e);
+        let hue = |c: Color| f32::from(c.to_oklch().hue);
+        for t in [0.25, 0.5, 0.75] {
+            let mixed = blue.mix(gray, t);
+            assert!((hue(mixed) - hue(blue)).abs() < 2.0, "{mixed} at {t}");
+            assert!(mixed.to_oklch().chroma < blue.to_oklch().chroma);
+        }
+    }
+
     #[test]
     fn relative_luminance_black() {
         assert!(BLACK.relative_luminance() < 0.001);
@@ -328,14 +841,98 @@ mod tests {
         // Hue should stay approximately the same
         let hue_diff = (f32::from(original_oklch.hue) - f32::from(adjusted_oklch.hue)).abs();
         assert!(
-            hue_diff < 5.0 || hue_diff > 355.0,
+            !(5.0..=355.0).contains(&hue_diff),
             "hue should be preserved, diff was {hue_diff}"
         );
     }
 
+    #[test]
+    fn out_of_gamut_oklch_keeps_hue_and_lightness() {
+        for hue in [25.0, 110.0, 145.0, 200.0, 265.0, 330.0] {
+            let color = Color::from_oklch(Oklch::new(0.7, 0.35, hue));
+            let oklch = color.to_oklch();
+            let drift = (oklch.hue.into_positive_degrees() - hue).abs();
+            assert!(
+                drift.min(360.0 - drift) < 2.0,
+                "hue {hue}: got {drift:.1}° off"
+            );
+            assert!((oklch.l - 0.7).abs() < 0.01, "hue {hue}: l {:.3}", oklch.l);
+        }
+    }
+
+    #[test]
+    fn max_chroma_stays_in_gamut() {
+        for hue in [0.0, 90.0, 145.0, 260.0] {
+            let c = max_chroma(0.7, hue);
+            assert!(c > 0.05);
+            assert!(in_gamut(0.7, c, hue));
+            assert!(!in_gamut(0.7, c * 1.1, hue));
+        }
+    }
+
+    #[test]
+    fn nearest_named_matches_exact_and_close_colors() {
+        assert_eq!(Color::new(255, 0, 0).nearest_named(), ("red", 0.0));
+        assert_eq!(Color::new(0, 128, 128).nearest_named(), ("teal", 0.0));
+        let (name, delta_e) = Color::new(180, 36, 36).nearest_named();
+        assert_eq!(name, "firebrick");
+        assert!(delta_e > 0.0 && delta_e < 3.0);
+    }
+
+    #[test]
+    fn adjust_hue_rotates_and_wraps() {
+        let color = Color::from_oklch(Oklch::new(0.65, 0.12, 350.0));
+        let rotated = color.adjust_hue(20.0);
+        let hue = rotated.to_oklch().hue.into_positive_degrees();
+        assert!(
+            (hue - 10.0).abs() < 3.0,
+            "hue should wrap past 360° to ~10°, got {hue:.1}°"
+        );
+        assert!((rotated.to_oklch().l - 0.65).abs() < 0.01);
+    }
+
+    #[test]
+    fn delta_e_is_zero_for_equal_and_symmetric() {
+        let a = Color::new(200, 80, 60);
+        let b = Color::new(60, 120, 200);
+        assert_eq!(a.delta_e(a), 0.0);
+        assert!((a.delta_e(b) - b.delta_e(a)).abs() < 1e-4);
+        // Black to white spans the full Lab lightness range
+        assert!((BLACK.delta_e(WHITE) - 100.0).abs() < 0.5);
+    }
+
+    #[test]
+    fn simulation_keeps_grays_and_merges_red_green() {
+        let gray = Color::new(128, 128, 128);
+        for kind in ColorBlindness::ALL {
+            assert!(gray.simulate(kind).delta_e(gray) < 1.0, "{kind:?}");
+            assert!(WHITE.simulate(kind).delta_e(WHITE) < 1.0, "{kind:?}");
+        }
+        // Red and green are far apart normally but close without green or red cones
+        let red = Color::new(200, 60, 50);
+        let green = Color::new(60, 160, 60);
+        let normal = red.delta_e(green);
+        for kind in [ColorBlindness::Deuteranopia, ColorBlindness::Protanopia] {
+            let simulated = red.simulate(kind).delta_e(green.simulate(kind));
+            assert!(
+                simulated < normal / 2.0,
+                "{kind:?}: {simulated} vs {normal}"
+            );
+        }
+    }
+
     #[test]
     fn display_matches_to_hex() {
         let color = Color::new(171, 205, 239);
         assert_eq!(format!("{color}"), color.to_hex());
     }
+
+    #[test]
+    fn serde_uses_hex_strings() {
+        let color = Color::new(171, 205, 239);
+        let json = serde_json::to_string(&color).unwrap();
+        assert_eq!(json, "\"#abcdef\"");
+        assert_eq!(serde_json::from_str::<Color>(&json).unwrap(), color);
+        assert!(serde_json::from_str::<Color>("\"#abc\"").is_err());
+    }
 }
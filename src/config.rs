@@ -0,0 +1,609 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+
+use crate::backends::registry::{BackendOptions, Registry};
+use crate::backends::{config_home, Target, ThemeBackend};
+use crate::cli::ThemeMode;
+use crate::color::Color;
+use crate::error::NuriError;
+use crate::naming::NameTemplate;
+use crate::pipeline::assign::AnsiPalette;
+use crate::pipeline::crop::{Crop, Focus, Region};
+use crate::pipeline::extract::{ResizeFilter, Sampling};
+use crate::pipeline::profile::{check_separation, BgFrom, Profile};
+use crate::pipeline::quantize::Algorithm;
+use crate::pipeline::style::PaletteStyle;
+use crate::pipeline::weighting::Weighting;
+
+/// Slot names accepted in `[slots]`, in ANSI order (0-15).
+pub(crate) const SLOT_NAMES: [&str; 16] = [
+    "black",
+    "red",
+    "green",
+    "yellow",
+    "blue",
+    "magenta",
+    "cyan",
+    "white",
+    "bright-black",
+    "bright-red",
+    "bright-green",
+    "bright-yellow",
+    "bright-blue",
+    "bright-magenta",
+    "bright-cyan",
+    "bright-white",
+];
+
+/// TUI actions that can be rebound in `[keys]`, with their default key.
+const KEY_ACTIONS: [(&str, char); 31] = [
+    ("quit", 'q'),
+    ("help", '?'),
+    ("dark", 'd'),
+    ("light", 'l'),
+    ("regenerate", 'r'),
+    ("lighter", '+'),
+    ("darker", '-'),
+    ("desaturate", 's'),
+    ("saturate", 'S'),
+    ("hue-left", 'h'),
+    ("hue-right", 'H'),
+    ("candidates", 'c'),
+    ("hex", '#'),
+    ("install", 'w'),
+    ("live", 'a'),
+    ("simulate", 'v'),
+    ("background", 'b'),
+    ("foreground", 'f'),
+    ("cursor", 'u'),
+    ("selection", 'x'),
+    ("fewer-colors", 'k'),
+    ("more-colors", 'K'),
+    ("lock", 'L'),
+    ("copy", 'y'),
+    ("copy-palette", 'Y'),
+    ("scene", 'p'),
+    ("compare", 'C'),
+    ("export", 'e'),
+    ("warnings", 'W'),
+    ("step", 'z'),
+    ("oklch", 'o'),
+];
+
+/// User defaults loaded from `~/.config/nuri/config.toml`.
+///
+/// Every field is optional; CLI flags take precedence over anything set here.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Config {
+    /// Targets used when `--target` is not given.
+    pub targets: Vec<Target>,
+    /// Force dark or light mode.
+    pub mode: Option<ThemeMode>,
+    /// Number of K-means clusters.
+    pub colors: Option<usize>,
+    /// Minimum accent contrast ratio.
+    pub min_contrast: Option<f32>,
+    /// Memory cap in MiB for image decoding.
+    pub max_memory: Option<u64>,
+    /// Longest side images are resized to before extraction.
+    pub max_dim: Option<u32>,
+    /// Filter for that resize.
+    pub resize_filter: Option<ResizeFilter>,
+    /// Frames pooled from animated images.
+    pub frames: Option<usize>,
+    /// Take every n-th frame of an animation.
+    pub frame_stride: Option<usize>,
+    /// Seconds between sampled video frames.
+    pub frame_interval: Option<f64>,
+    /// Rectangle of the image colors are extracted from.
+    pub crop: Option<Crop>,
+    /// Half of the image colors are extracted from.
+    pub focus: Option<Focus>,
+    /// Refuse to overwrite existing theme files unless `--force` is given.
+    pub no_clobber: bool,
+    /// Color extraction algorithm.
+    pub algorithm: Option<Algorithm>,
+    /// Delta-E under which extracted colors are merged.
+    pub merge_delta_e: Option<f32>,
+    /// Percentage of the image an extracted color must cover to be kept.
+    pub min_weight: Option<f32>,
+    /// Pixel sampling strategy before clustering.
+    pub sampling: Option<Sampling>,
+    /// Number of pixels kept by the sampling strategy.
+    pub sample_size: Option<usize>,
+    /// Pixel weighting before clustering.
+    pub weighting: Option<Weighting>,
+    /// Accent styles applied after slot assignment.
+    pub style: Vec<PaletteStyle>,
+    /// Keep red/green and blue/magenta distinguishable with colorblindness.
+    pub colorblind_safe: bool,
+    /// Accent assignment profile: a built-in name, a TOML file, or the name
+    /// of one in the profiles directory.
+    pub profile: Option<String>,
+    /// Minimum delta-E between accents, replacing the profile's.
+    pub min_separation: Option<f32>,
+    /// Background source, replacing the profile's.
+    pub bg_from: Option<BgFrom>,
+    /// Color tinting the background with `bg-from = "custom"`.
+    pub bg_color: Option<Color>,
+    /// Oklch lightness of the background.
+    pub bg_lightness: Option<f32>,
+    /// Oklch lightness of the foreground.
+    pub fg_lightness: Option<f32>,
+    /// Scale of the background-to-text lightness gap.
+    pub contrast_envelope: Option<f32>,
+    /// Write colors 16-255 for targets that support them.
+    pub extended: bool,
+    /// Theme name pattern used when `--name` is not given.
+    pub name_template: Option<NameTemplate>,
+    /// Template rendered by the `template` target: a file, or the name of one
+    /// in the templates directory.
+    pub template: Option<PathBuf>,
+    /// Per-target install directories, replacing the standard ones.
+    pub output_dirs: HashMap<Target, PathBuf>,
+    /// Exact colors forced into palette slots after generation.
+    pub slots: BTreeMap<String, Color>,
+    /// TUI keybinding overrides: action name -> key.
+    pub keys: BTreeMap<String, char>,
+    /// `--set` pins from the command line, applied after `[slots]`.
+    #[serde(skip)]
+    pub pins: Vec<(String, Color)>,
+    /// Backends, built on first use and shared by clones.
+    #[serde(skip)]
+    registry: Arc<OnceLock<Registry>>,
+}
+
+impl Config {
+    /// Default config location: `nuri/config.toml` under the config home
+    /// (`$NURI_CONFIG_DIR`, `$XDG_CONFIG_HOME` or `~/.config`, `%APPDATA%` on Windows).
+    pub fn default_path() -> PathBuf {
+        config_home().join("nuri").join("config.toml")
+    }
+
+    /// Load the config from `path`, or from the default location when `None`.
+    ///
+    /// A missing default file yields an empty config; a missing explicit path
+    /// is an error.
+    pub fn load(path: Option<&Path>) -> Result<Config> {
+        let (path, explicit) = match path {
+            Some(p) => (p.to_path_buf(), true),
+            None => (Self::default_path(), false),
+        };
+        if !explicit && !path.exists() {
+            return Ok(Config::default());
+        }
+        let text = std::fs::read_to_string(&path)
+            .with_context(|| format!("failed to read config: {}", path.display()))?;
+        Self::parse(&text).with_context(|| format!("invalid config: {}", path.display()))
+    }
+
+    /// Parse and validate config text.
+    pub fn parse(text: &str) -> Result<Config> {
+        let config: Config = toml::from_str(text)?;
+        for name in config.slots.keys() {
+            if !is_slot_name(name) {
+                bail!("unknown slot '{name}' in [slots]");
+            }
+        }
+        for action in config.keys.keys() {
+            if !KEY_ACTIONS.iter().any(|(a, _)| a == action) {
+                bail!("unknown action '{action}' in [keys]");
+            }
+        }
+        if config.crop.is_some() && config.focus.is_some() {
+            bail!("crop and focus both pick the part of the image to use; set only one");
+        }
+        Ok(config)
+    }
+
+    /// The configured region of interest, from `crop` or `focus`.
+    pub fn region(&self) -> Option<Region> {
+        self.crop
+            .map(Region::Crop)
+            .or(self.focus.map(Region::Focus))
+    }
+
+    /// Install directory configured for `target`, with `~` expanded.
+    pub fn output_dir(&self, target: Target) -> Option<PathBuf> {
+        self.output_dirs.get(&target).map(|p| expand_tilde(p))
+    }
+
+    /// User templates directory: `$XDG_CONFIG_HOME/nuri/templates`.
+    pub fn templates_dir() -> PathBuf {
+        config_home().join("nuri").join("templates")
+    }
+
+    /// User assignment profiles directory: `$XDG_CONFIG_HOME/nuri/profiles`.
+    pub fn profiles_dir() -> PathBuf {
+        config_home().join("nuri").join("profiles")
+    }
+
+    /// The configured assignment profile, resolving a bare name against the
+    /// built-in profiles and then the profiles directory, or the default
+    /// profile when none is set. `min-separation`, the `bg-*` keys,
+    /// `fg-lightness` and `contrast-envelope` override the profile's.
+    pub fn assignment_profile(&self) -> Result<Profile> {
+        let mut profile = match &self.profile {
+            Some(spec) => Self::find_profile(spec)?,
+            None => Profile::default(),
+        };
+        if let Some(min_separation) = self.min_separation {
+            profile.min_separation = check_separation(min_separation)?;
+        }
+        if let Some(bg_from) = self.bg_from {
+            profile.bg_from = bg_from;
+        }
+        profile.bg_color = self.bg_color.or(profile.bg_color);
+        profile.bg_lightness = self.bg_lightness.or(profile.bg_lightness);
+        profile.fg_lightness = self.fg_lightness.or(profile.fg_lightness);
+        if let Some(envelope) = self.contrast_envelope {
+            profile.contrast_envelope = envelope;
+        }
+        profile.check_background()?;
+        Ok(profile)
+    }
+
+    fn find_profile(spec: &str) -> Result<Profile> {
+        if let Some(profile) = Profile::builtin(spec) {
+            return Ok(profile);
+        }
+        let path = expand_tilde(Path::new(spec));
+        if path.is_file() {
+            return Profile::load(&path);
+        }
+        let named = Self::profiles_dir().join(format!("{spec}.toml"));
+        if named.is_file() {
+            return Profile::load(&named);
+        }
+        let builtins: Vec<&str> = Profile::builtin_names().collect();
+        bail!(
+            "unknown profile '{spec}': not a file, a profile in {}, or a built-in ({})",
+            Self::profiles_dir().display(),
+            builtins.join(", ")
+        );
+    }
+
+    /// Built-in backends plus the templates found in
+    /// [`templates_dir`](Self::templates_dir), discovered once per config.
+    pub fn registry(&self) -> &Registry {
+        self.registry.get_or_init(|| {
+            let mut registry = Registry::builtin();
+            registry.discover_templates(&Self::templates_dir());
+            registry
+        })
+    }
+
+    /// The backend for `target`. The template target renders the configured
+    /// `template`, resolving a bare name against the templates directory, or
+    /// the built-in template when none is set.
+    pub fn backend(&self, target: Target) -> Result<Box<dyn ThemeBackend>> {
+        let registry = self.registry();
+        let template = self.template.as_ref().map(|template| {
+            let name = template.to_string_lossy();
+            match registry.template(&name) {
+                Some(path) if !template.is_file() => path.to_path_buf(),
+                _ => expand_tilde(template),
+            }
+        });
+        let options = BackendOptions {
+            template: template.filter(|_| target == Target::Template),
+            extended: self.extended,
+        };
+        Ok(registry.get(&target.key(), &options)?)
+    }
+
+    /// Resolve `--target` names against the registry: built-in targets by
+    /// key, discovered templates as the template target rendering that file.
+    /// Returns the targets and the template a name picked, if any.
+    pub fn resolve_targets(&self, names: &[String]) -> Result<(Vec<Target>, Option<PathBuf>)> {
+        let registry = self.registry();
+        let mut targets = Vec::new();
+        let mut picked: Option<(&str, &Path)> = None;
+        for name in names {
+            if let Some(target) = Target::from_key(name) {
+                targets.push(target);
+                continue;
+            }
+            let Some(path) = registry.template(name) else {
+                return Err(NuriError::UnknownTarget {
+                    name: name.clone(),
+                    available: registry.names().map(str::to_string).collect(),
+                }
+                .into());
+            };
+            match picked {
+                Some((other, _)) if other != name => {
+                    bail!("--target {other} and {name} are both templates; only one template can be rendered per run")
+                }
+                _ => picked = Some((name, path)),
+            }
+            targets.push(Target::Template);
+        }
+        Ok((targets, picked.map(|(_, path)| path.to_path_buf())))
+    }
+
+    /// Where installing `target` should write, honoring `[output-dirs]`.
+    pub fn install_path(&self, target: Target, theme_name: &str) -> Result<PathBuf> {
+        let backend = self.backend(target)?;
+        let Some(dir) = self.output_dir(target) else {
+            return Ok(backend.install_path(theme_name)?);
+        };
+        // Keep the backend's file naming (e.g. Neovim's sanitized name) when it
+        // can be computed; the standard directory itself may be unavailable.
+        let file_name = backend
+            .install_path(theme_name)
+            .ok()
+            .and_then(|p| p.file_name().map(|f| f.to_os_string()))
+            .unwrap_or_else(|| format!("{theme_name}{}", backend.extension()).into());
+        Ok(dir.join(file_name))
+    }
+
+    /// Force the configured `[slots]` colors, then any `--set` pins, into `palette`.
+    pub fn apply_slot_overrides(&self, palette: &mut AnsiPalette) {
+        let pins = self.pins.iter().map(|(name, color)| (name, color));
+        for (name, &color) in self.slots.iter().chain(pins) {
+            match slot_target(name) {
+                Some(SlotTarget::Ansi(i)) => palette.slots[i] = color,
+                Some(SlotTarget::Background) => palette.background = color,
+                Some(SlotTarget::Foreground) => palette.foreground = color,
+                Some(SlotTarget::Cursor) => palette.cursor_color = color,
+                Some(SlotTarget::CursorText) => palette.cursor_text = color,
+                Some(SlotTarget::SelectionBg) => palette.selection_bg = color,
+                Some(SlotTarget::SelectionFg) => palette.selection_fg = color,
+                None => {}
+            }
+        }
+    }
+
+    /// Map from a rebound key to the default key of the same action.
+    pub fn key_remap(&self) -> HashMap<char, char> {
+        self.keys
+            .iter()
+            .filter_map(|(action, &key)| {
+                KEY_ACTIONS
+                    .iter()
+                    .find(|(a, _)| a == action)
+                    .map(|&(_, default)| (key, default))
+            })
+            .collect()
+    }
+}
+
+/// Whether `name` addresses a palette field: `0`-`15`, an ANSI color name
+/// (`red`, `bright-blue`, ...) or a special color (`background`, `cursor`, ...).
+pub fn is_slot_name(name: &str) -> bool {
+    slot_target(name).is_some()
+}
+
+/// The palette color addressed by a `[slots]`-style name, if the name is valid.
+pub fn slot_color(palette: &AnsiPalette, name: &str) -> Option<Color> {
+    Some(match slot_target(name)? {
+        SlotTarget::Ansi(i) => palette.slots[i],
+        SlotTarget::Background => palette.background,
+        SlotTarget::Foreground => palette.foreground,
+        SlotTarget::Cursor => palette.cursor_color,
+        SlotTarget::CursorText => palette.cursor_text,
+        SlotTarget::SelectionBg => palette.selection_bg,
+        SlotTarget::SelectionFg => palette.selection_fg,
+    })
+}
+
+/// A palette field addressed by a `[slots]` key.
+enum SlotTarget {
+    Ansi(usize),
+    Background,
+    Foreground,
+    Cursor,
+    CursorText,
+    SelectionBg,
+    SelectionFg,
+}
+
+/// Resolve a `[slots]` key: an index `0`-`15`, an ANSI name, or a special color.
+fn slot_target(name: &str) -> Option<SlotTarget> {
+    if let Ok(i) = name.parse::<usize>() {
+        return (i < 16).then_some(SlotTarget::Ansi(i));
+    }
+    if let Some(i) = SLOT_NAMES.iter().position(|&n| n == name) {
+        return Some(SlotTarget::Ansi(i));
+    }
+    match name {
+        "background" => Some(SlotTarget::Background),
+        "foreground" => Some(SlotTarget::Foreground),
+        "cursor" => Some(SlotTarget::Cursor),
+        "cursor-text" => Some(SlotTarget::CursorText),
+        "selection-bg" => Some(SlotTarget::SelectionBg),
+        "selection-fg" => Some(SlotTarget::SelectionFg),
+        _ => None,
+    }
+}
+
+/// Expand a leading `~` to the user's home directory.
+fn expand_tilde(path: &Path) -> PathBuf {
+    match path.strip_prefix("~") {
+        Ok(rest) => {
+            let home = std::env::var("HOME").unwrap_or_else(|_| "~".to_string());
+            PathBuf::from(home).join(rest)
+        }
+        Err(_) => path.to_path_buf(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_config_is_default() {
+        let config = Config::parse("").unwrap();
+        assert!(config.targets.is_empty());
+        assert!(config.mode.is_none());
+        assert!(config.colors.is_none());
+        assert!(config.slots.is_empty());
+    }
+
+    #[test]
+    fn parses_all_sections() {
+        let config = Config::parse(
+            r##"
+targets = ["ghostty", "windows-terminal"]
+mode = "light"
+colors = 12
+min-contrast = 7.0
+max-memory = 512
+max-dim = 512
+resize-filter = "nearest"
+frames = 4
+frame-stride = 10
+frame-interval = 2.5
+crop = "0,200,1920,880"
+no-clobber = true
+algorithm = "median-cut"
+merge-delta-e = 8.0
+min-weight = 0.5
+sampling = "saturation"
+sample-size = 4096
+template = "~/templates/kitty.conf.tmpl"
+weighting = "saliency"
+
+[output-dirs]
+zellij = "~/dotfiles/zellij/themes"
+
+[slots]
+red = "#ff0000"
+12 = "#0000ff"
+background = "#101010"
+
+[keys]
+regenerate = "g"
+"##,
+        )
+        .unwrap();
+
+        assert_eq!(config.targets, [Target::Ghostty, Target::WindowsTerminal]);
+        assert_eq!(config.mode, Some(ThemeMode::Light));
+        assert_eq!(config.colors, Some(12));
+        assert_eq!(config.min_contrast, Some(7.0));
+        assert_eq!(config.max_memory, Some(512));
+        assert_eq!(config.merge_delta_e, Some(8.0));
+        assert_eq!(config.min_weight, Some(0.5));
+        assert_eq!(config.max_dim, Some(512));
+        assert_eq!(config.resize_filter, Some(ResizeFilter::Nearest));
+        assert_eq!(config.frames, Some(4));
+        assert_eq!(config.frame_stride, Some(10));
+        assert_eq!(config.frame_interval, Some(2.5));
+        assert_eq!(
+            config.region(),
+            Some(Region::Crop(Crop::parse("0,200,1920,880").unwrap()))
+        );
+        assert!(Config::parse("crop = \"0,0,10,10\"\nfocus = \"top\"").is_err());
+        assert!(config.no_clobber);
+        assert_eq!(config.algorithm, Some(Algorithm::MedianCut));
+        assert_eq!(config.sampling, Some(Sampling::Saturation));
+        assert_eq!(config.sample_size, Some(4096));
+        assert_eq!(
+            config.template.as_deref(),
+            Some(Path::new("~/templates/kitty.conf.tmpl"))
+        );
+        assert_eq!(config.weighting, Some(Weighting::Saliency));
+        assert!(config.output_dir(Target::Zellij).is_some());
+        assert!(config.output_dir(Target::Ghostty).is_none());
+        assert!(config
+            .install_path(Target::Zellij, "sunset")
+            .unwrap()
+            .ends_with("dotfiles/zellij/themes/sunset.kdl"));
+        assert_eq!(config.key_remap().get(&'g'), Some(&'r'));
+    }
+
+    #[test]
+    fn unknown_fields_are_rejected() {
+        assert!(Config::parse("colours = 12").is_err());
+        assert!(Config::parse("[slots]\npurple = \"#ff00ff\"").is_err());
+        assert!(Config::parse("[slots]\n16 = \"#ff00ff\"").is_err());
+        assert!(Config::parse("[slots]\nred = \"#ff00\"").is_err());
+        assert!(Config::parse("[keys]\nfly = \"f\"").is_err());
+        assert!(Config::parse("targets = [\"kitty\"]").is_err());
+    }
+
+    #[test]
+    fn slot_overrides_are_applied() {
+        let config =
+            Config::parse("[slots]\nred = \"#ff0000\"\n9 = \"#ff8080\"\ncursor = \"#00ff00\"\n")
+                .unwrap();
+        let mut palette = AnsiPalette {
+            slots: [Color::new(0, 0, 0); 16],
+            background: Color::new(0, 0, 0),
+            foreground: Color::new(255, 255, 255),
+            cursor_color: Color::new(255, 255, 255),
+            cursor_text: Color::new(0, 0, 0),
+            selection_bg: Color::new(50, 50, 50),
+            selection_fg: Color::new(255, 255, 255),
+        };
+        config.apply_slot_overrides(&mut palette);
+        assert_eq!(palette.slots[1], Color::new(255, 0, 0));
+        assert_eq!(palette.slots[9], Color::new(255, 128, 128));
+        assert_eq!(palette.cursor_color, Color::new(0, 255, 0));
+    }
+
+    #[test]
+    fn pins_win_over_slots_table() {
+        let mut config = Config::parse("[slots]\nred = \"#ff0000\"\n").unwrap();
+        config.pins.push(("1".to_string(), Color::new(0, 0, 255)));
+        let mut palette = AnsiPalette {
+            slots: [Color::new(0, 0, 0); 16],
+            background: Color::new(0, 0, 0),
+            foreground: Color::new(255, 255, 255),
+            cursor_color: Color::new(255, 255, 255),
+            cursor_text: Color::new(0, 0, 0),
+            selection_bg: Color::new(50, 50, 50),
+            selection_fg: Color::new(255, 255, 255),
+        };
+        config.apply_slot_overrides(&mut palette);
+        assert_eq!(palette.slots[1], Color::new(0, 0, 255));
+    }
+
+    #[test]
+    fn resolves_assignment_profiles() {
+        assert_eq!(
+            Config::default().assignment_profile().unwrap(),
+            Profile::default()
+        );
+        let named = Config::parse("profile = \"gruvbox\"").unwrap();
+        assert_eq!(
+            named.assignment_profile().unwrap(),
+            Profile::builtin("gruvbox").unwrap()
+        );
+
+        let path = std::env::temp_dir().join("nuri-test-profile.toml");
+        std::fs::write(&path, "[blue]\nhue = 230\n").unwrap();
+        let file = Config {
+            profile: Some(path.to_string_lossy().into_owned()),
+            ..Config::default()
+        };
+        assert_eq!(file.assignment_profile().unwrap().accents[3].hue, 230.0);
+        let _ = std::fs::remove_file(&path);
+
+        let unknown = Config::parse("profile = \"no-such-profile\"").unwrap();
+        assert!(unknown.assignment_profile().is_err());
+
+        let separated = Config::parse("profile = \"nord\"\nmin-separation = 4.0").unwrap();
+        assert_eq!(separated.assignment_profile().unwrap().min_separation, 4.0);
+
+        let custom = Config::parse("bg-from = \"custom\"").unwrap();
+        assert!(custom.assignment_profile().is_err());
+        let custom = Config::parse("bg-from = \"custom\"\nbg-color = \"#202020\"").unwrap();
+        assert_eq!(custom.assignment_profile().unwrap().bg_from, BgFrom::Custom);
+    }
+
+    #[test]
+    fn missing_explicit_path_errors() {
+        let missing = std::env::temp_dir().join("nuri-test-config-missing.toml");
+        let _ = std::fs::remove_file(&missing);
+        assert!(Config::load(Some(&missing)).is_err());
+    }
+}
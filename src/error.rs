@@ -0,0 +1,172 @@
+//! [`NuriError`], the error type of the library API: loading images,
+//! [`Palette::from_image`](crate::Palette::from_image) and installing or
+//! writing themes through a [`ThemeBackend`](crate::ThemeBackend). Callers
+//! can match on what went wrong instead of parsing messages; the binary
+//! keeps using anyhow and gets the same messages through `?`.
+
+use std::io;
+use std::path::PathBuf;
+
+use crate::pipeline::formats::supported_formats;
+
+/// Result of the library API.
+pub type Result<T, E = NuriError> = std::result::Result<T, E>;
+
+/// What went wrong in the library API.
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum NuriError {
+    /// The image or video does not exist.
+    #[error("file not found: {}", .path.display())]
+    ImageNotFound { path: PathBuf },
+
+    /// The image exists but cannot be opened.
+    #[error("permission denied: cannot read {}. Check file permissions.", .path.display())]
+    PermissionDenied { path: PathBuf },
+
+    /// The file is not an image this build recognizes, or it is corrupt.
+    #[error(
+        "unsupported or corrupt image: {}. Supported formats: {}",
+        .path.display(),
+        supported_formats()
+    )]
+    UnsupportedFormat {
+        path: PathBuf,
+        #[source]
+        source: Option<Box<dyn std::error::Error + Send + Sync>>,
+    },
+
+    /// The file is in a format whose decoder is behind a cargo feature that
+    /// this build was compiled without.
+    #[error(
+        "{} is a {format}, which this build of nuri can't decode; rebuild it with \
+         `--features {feature}`. Supported formats: {}",
+        .path.display(),
+        supported_formats()
+    )]
+    MissingFeature {
+        path: PathBuf,
+        /// What the file is, e.g. `HEIC image` or `video`.
+        format: String,
+        /// The cargo feature that adds a decoder.
+        feature: &'static str,
+    },
+
+    /// Decoding the image would take more memory than
+    /// [`LoadOptions::max_memory`](crate::pipeline::extract::LoadOptions::max_memory).
+    #[error(
+        "image too large: decoding {} exceeds the {} MiB memory cap. Raise --max-memory, or \
+         convert it to PNG for streaming decode.",
+        .path.display(),
+        .max_memory / (1024 * 1024)
+    )]
+    ImageTooLarge { path: PathBuf, max_memory: u64 },
+
+    /// A theme or its directory could not be written.
+    #[error("cannot write {}", .path.display())]
+    InstallPathNotWritable {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+
+    /// A theme is already at the destination and overwriting was refused
+    /// with [`Overwrite::Refuse`](crate::backends::Overwrite::Refuse).
+    #[error(
+        "theme already exists at {}; remove it or pass --force to overwrite",
+        .path.display()
+    )]
+    ThemeExists { path: PathBuf },
+
+    /// No backend is registered under this target name.
+    #[error("unknown target '{name}'; available: {}", .available.join(", "))]
+    UnknownTarget {
+        name: String,
+        /// Every registered target name, sorted.
+        available: Vec<String>,
+    },
+
+    /// Anything else, with its chain of causes.
+    #[error(transparent)]
+    Other(anyhow::Error),
+}
+
+impl NuriError {
+    /// The error for an image that failed to open or decode with `source`,
+    /// by what's wrong with the file.
+    pub(crate) fn open(
+        path: impl Into<PathBuf>,
+        source: impl Into<Box<dyn std::error::Error + Send + Sync>>,
+    ) -> NuriError {
+        let path = path.into();
+        if !path.exists() {
+            NuriError::ImageNotFound { path }
+        } else if path
+            .metadata()
+            .map(|m| m.permissions().readonly())
+            .unwrap_or(false)
+            || std::fs::File::open(&path).is_err()
+        {
+            NuriError::PermissionDenied { path }
+        } else {
+            NuriError::UnsupportedFormat {
+                path,
+                source: Some(source.into()),
+            }
+        }
+    }
+}
+
+/// Internal code works with anyhow and raises `NuriError`s where the cause
+/// is known; at the API boundary that error is taken back out, and anything
+/// else becomes [`NuriError::Other`].
+impl From<anyhow::Error> for NuriError {
+    fn from(error: anyhow::Error) -> Self {
+        error
+            .downcast::<NuriError>()
+            .unwrap_or_else(NuriError::Other)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn typed_errors_survive_a_trip_through_anyhow() {
+        let path = PathBuf::from("/nonexistent/wallpaper.png");
+        let wrapped: anyhow::Error = NuriError::ThemeExists { path: path.clone() }.into();
+        let wrapped = wrapped.context("while installing");
+        assert!(matches!(
+            NuriError::from(wrapped),
+            NuriError::ThemeExists { path: p } if p == path
+        ));
+
+        let other = NuriError::from(anyhow::anyhow!("no standard directory"));
+        assert!(matches!(other, NuriError::Other(_)));
+        assert_eq!(other.to_string(), "no standard directory");
+    }
+
+    #[test]
+    fn open_errors_say_what_is_wrong_with_the_file() {
+        let missing = NuriError::open("/nonexistent/wallpaper.png", "io error");
+        assert!(matches!(missing, NuriError::ImageNotFound { .. }));
+        assert_eq!(
+            missing.to_string(),
+            "file not found: /nonexistent/wallpaper.png"
+        );
+
+        let dir = std::env::temp_dir().join("nuri_test_error");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("notes.txt");
+        std::fs::write(&path, "not an image").unwrap();
+        let corrupt = NuriError::open(&path, "format could not be determined");
+        assert!(matches!(corrupt, NuriError::UnsupportedFormat { .. }));
+        let source = std::error::Error::source(&corrupt).unwrap();
+        assert_eq!(source.to_string(), "format could not be determined");
+        // The context survives too, for anyhow's `Caused by` list
+        let chained = anyhow::Error::from(corrupt).context("loading");
+        assert_eq!(chained.chain().count(), 3);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,212 @@
+use std::io::{IsTerminal, Read};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+
+use crate::pipeline::formats::{self, Format};
+
+/// Largest image accepted from a URL or stdin.
+pub const MAX_DOWNLOAD_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Give up on downloads that take longer than this in total.
+const TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Whether `input` is an `http://` or `https://` URL rather than a file path.
+pub fn is_url(input: &Path) -> bool {
+    input
+        .to_str()
+        .is_some_and(|s| s.starts_with("http://") || s.starts_with("https://"))
+}
+
+/// Whether `input` is `-`, meaning image bytes on stdin.
+pub fn is_stdin(input: &Path) -> bool {
+    input == Path::new("-")
+}
+
+/// A downloaded or piped image in its own temporary directory, removed on drop.
+#[derive(Debug)]
+pub struct TempImage {
+    /// The image file.
+    pub path: PathBuf,
+    _dir: tempfile::TempDir,
+}
+
+/// Download the image at `url`, refusing non-image content types and bodies
+/// over `max_bytes`.
+pub fn download(url: &str, max_bytes: u64) -> Result<TempImage> {
+    let agent: ureq::Agent = ureq::Agent::config_builder()
+        .timeout_global(Some(TIMEOUT))
+        .build()
+        .into();
+    let mut response = agent
+        .get(url)
+        .call()
+        .with_context(|| format!("failed to download {url}"))?;
+
+    let body = response.body_mut();
+    let mime = body.mime_type().unwrap_or_default().to_ascii_lowercase();
+    // Some hosts serve images as octet-stream; the bytes are checked below
+    if !mime.is_empty() && !mime.starts_with("image/") && mime != "application/octet-stream" {
+        bail!("{url} is not an image (content type {mime})");
+    }
+    if body.content_length().is_some_and(|len| len > max_bytes) {
+        bail!("{url} is larger than {} MiB", max_bytes / (1024 * 1024));
+    }
+    let bytes = body
+        .with_config()
+        .limit(max_bytes)
+        .read_to_vec()
+        .map_err(|e| match e {
+            ureq::Error::BodyExceedsLimit(_) => {
+                anyhow::anyhow!("{url} is larger than {} MiB", max_bytes / (1024 * 1024))
+            }
+            e => anyhow::Error::new(e).context(format!("failed to download {url}")),
+        })?;
+    let extension =
+        image_extension(&bytes).with_context(|| format!("{url} is not a supported image"))?;
+    save(&bytes, &file_name(url, extension))
+}
+
+/// Read an image piped to stdin (e.g. `grim - | nuri -`), detecting its format
+/// from magic bytes. Bodies over `max_bytes` are refused.
+pub fn read_stdin(max_bytes: u64) -> Result<TempImage> {
+    let stdin = std::io::stdin();
+    if stdin.is_terminal() {
+        bail!("no image on stdin; pipe one in, e.g. `grim - | nuri -`");
+    }
+    let mut bytes = Vec::new();
+    stdin
+        .lock()
+        .take(max_bytes + 1)
+        .read_to_end(&mut bytes)
+        .context("failed to read image from stdin")?;
+    if bytes.len() as u64 > max_bytes {
+        bail!(
+            "image on stdin is larger than {} MiB",
+            max_bytes / (1024 * 1024)
+        );
+    }
+    if bytes.is_empty() {
+        bail!("no image on stdin");
+    }
+    let extension = image_extension(&bytes).context("stdin is not a supported image")?;
+    save(&bytes, &format!("stdin.{extension}"))
+}
+
+impl TempImage {
+    /// A path for `name` in a fresh temporary directory; nothing is written.
+    /// The directory gets an unpredictable name and, on Unix, mode 0700, so
+    /// other users can neither guess nor plant files in it.
+    pub(crate) fn new(name: &str) -> Result<TempImage> {
+        let mut builder = tempfile::Builder::new();
+        builder.prefix("nuri-");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            builder.permissions(std::fs::Permissions::from_mode(0o700));
+        }
+        let dir = builder
+            .tempdir()
+            .context("failed to create a temporary directory")?;
+        Ok(TempImage {
+            path: dir.path().join(name),
+            _dir: dir,
+        })
+    }
+}
+
+/// Extension for image `bytes`, by magic number: formats the image crate
+/// knows, then HEIC, AVIF and JPEG XL.
+fn image_extension(bytes: &[u8]) -> Option<&'static str> {
+    match image::guess_format(bytes) {
+        Ok(format) => Some(format.extensions_str()[0]),
+        Err(_) => formats::sniff_bytes(bytes).map(Format::extension),
+    }
+}
+
+/// Write `bytes` to `name` in a fresh temporary directory.
+fn save(bytes: &[u8], name: &str) -> Result<TempImage> {
+    let image = TempImage::new(name)?;
+    std::fs::write(&image.path, bytes)
+        .with_context(|| format!("failed to write {}", image.path.display()))?;
+    Ok(image)
+}
+
+/// File name for a download: the URL's last path segment, with `extension`
+/// added when it has none, so the theme name defaults to something readable.
+fn file_name(url: &str, extension: &str) -> String {
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    let segment = path
+        .split("://")
+        .nth(1)
+        .and_then(|rest| rest.split_once('/'))
+        .and_then(|(_, path)| path.rsplit('/').next())
+        .unwrap_or_default();
+    let name: String = segment
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || "-_.".contains(c) {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    let name = name.trim_matches(['.', '-']);
+    match name {
+        "" => format!("wallpaper.{extension}"),
+        name if Path::new(name).extension().is_some() => name.to_string(),
+        name => format!("{name}.{extension}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recognizes_urls() {
+        assert!(is_url(Path::new("https://example.com/a.jpg")));
+        assert!(is_url(Path::new("http://localhost:8080/a.png")));
+        assert!(!is_url(Path::new("/home/me/a.jpg")));
+        assert!(!is_url(Path::new("https.jpg")));
+        assert!(is_stdin(Path::new("-")));
+        assert!(!is_stdin(Path::new("./-")));
+    }
+
+    #[test]
+    fn temp_images_get_private_unique_directories() {
+        let a = TempImage::new("a.png").unwrap();
+        let b = TempImage::new("a.png").unwrap();
+        assert_ne!(a.path, b.path);
+        let dir = a.path.parent().unwrap().to_path_buf();
+        assert!(dir.is_dir());
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&dir).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o700);
+        }
+        drop(a);
+        assert!(!dir.exists());
+    }
+
+    #[test]
+    fn names_downloads_after_the_url() {
+        assert_eq!(
+            file_name("https://w.wallhaven.cc/full/abc/sunset.jpg?w=1920", "png"),
+            "sunset.jpg"
+        );
+        assert_eq!(
+            file_name("https://images.unsplash.com/photo-123#top", "jpg"),
+            "photo-123.jpg"
+        );
+        assert_eq!(file_name("https://example.com/", "png"), "wallpaper.png");
+        assert_eq!(file_name("https://example.com", "png"), "wallpaper.png");
+        assert_eq!(
+            file_name("https://example.com/my%20wall.webp", "png"),
+            "my-20wall.webp"
+        );
+    }
+}
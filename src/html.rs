@@ -0,0 +1,333 @@
+//! Standalone HTML preview page: the wallpaper, the full palette, a simulated
+//! terminal session and WCAG contrast ratios, for reviewing a theme in a
+//! browser before installing it.
+
+use std::path::Path;
+
+use anyhow::{Context, Result};
+
+use crate::card::{label_color, png_data_uri, thumbnail, xml_escape};
+use crate::color::Color;
+use crate::pipeline::assign::AnsiPalette;
+use crate::pipeline::extract::PreparedImage;
+
+/// ANSI color names for the palette grid and contrast table.
+const SLOT_LABELS: [&str; 16] = [
+    "black",
+    "red",
+    "green",
+    "yellow",
+    "blue",
+    "magenta",
+    "cyan",
+    "white",
+    "bright black",
+    "bright red",
+    "bright green",
+    "bright yellow",
+    "bright blue",
+    "bright magenta",
+    "bright cyan",
+    "bright white",
+];
+
+/// How a span of the simulated session is drawn.
+#[derive(Clone, Copy)]
+enum Style {
+    Text,
+    Slot(usize),
+    Selection,
+    Cursor,
+}
+
+use Style::{Cursor, Selection, Slot, Text};
+
+/// The simulated session: a prompt, `ls`, git and cargo output, using every
+/// ANSI color plus the selection and cursor.
+const SESSION: &[&[(Style, &str)]] = &[
+    &[
+        (Slot(2), "me@host"),
+        (Text, " "),
+        (Slot(4), "~/src/nuri"),
+        (Text, " "),
+        (Slot(5), "(main)"),
+        (Text, " $ ls -F"),
+    ],
+    &[
+        (Slot(4), "src/"),
+        (Text, "  "),
+        (Slot(12), "target/"),
+        (Text, "  Cargo.toml  "),
+        (Slot(2), "build.sh*"),
+        (Text, "  "),
+        (Slot(6), "latest@"),
+        (Text, "  "),
+        (Slot(5), "sunset.png"),
+        (Text, "  "),
+        (Slot(1), "backup.tar.gz"),
+    ],
+    &[
+        (Slot(2), "me@host"),
+        (Text, " "),
+        (Slot(4), "~/src/nuri"),
+        (Text, " "),
+        (Slot(5), "(main)"),
+        (Text, " $ git diff --stat && git diff"),
+    ],
+    &[
+        (Text, " src/main.rs | 4 "),
+        (Slot(2), "++"),
+        (Slot(1), "--"),
+    ],
+    &[(Slot(6), "@@ -12,4 +12,4 @@"), (Slot(8), " fn main() {")],
+    &[(Slot(1), "-    let theme = \"old\";")],
+    &[(Slot(2), "+    let theme = \"nuri\";")],
+    &[
+        (Text, "     // "),
+        (Selection, "selected text"),
+        (Text, " stays readable"),
+    ],
+    &[
+        (Slot(2), "me@host"),
+        (Text, " "),
+        (Slot(4), "~/src/nuri"),
+        (Text, " "),
+        (Slot(5), "(main)"),
+        (Text, " $ cargo test"),
+    ],
+    &[(Slot(10), "   Compiling"), (Text, " nuri v0.2.0")],
+    &[
+        (Slot(11), "warning"),
+        (Text, ": unused variable: "),
+        (Slot(3), "`seed`"),
+    ],
+    &[
+        (Slot(9), "error[E0308]"),
+        (Text, ": mismatched types, expected "),
+        (Slot(13), "`u8`"),
+        (Text, " found "),
+        (Slot(14), "`f32`"),
+    ],
+    &[
+        (Text, "test result: "),
+        (Slot(2), "ok"),
+        (Slot(8), ". 42 passed; 0 failed"),
+    ],
+    &[
+        (Slot(2), "me@host"),
+        (Text, " "),
+        (Slot(4), "~/src/nuri"),
+        (Text, " "),
+        (Slot(5), "(main)"),
+        (Text, " $ "),
+        (Slot(15), "echo"),
+        (Text, " "),
+        (Slot(7), "done"),
+        (Text, " "),
+        (Cursor, " "),
+    ],
+];
+
+/// Write the preview page for `palette` to `path`.
+pub fn export(
+    path: &Path,
+    palette: &AnsiPalette,
+    name: &str,
+    source: Option<&PreparedImage>,
+) -> Result<()> {
+    let page = render(palette, name, source)?;
+    std::fs::write(path, page).with_context(|| format!("failed to write {}", path.display()))
+}
+
+fn render(palette: &AnsiPalette, name: &str, source: Option<&PreparedImage>) -> Result<String> {
+    let p = palette;
+    let name = xml_escape(name);
+    let mut out = String::new();
+    out.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+    out.push_str(&format!("<title>{name} - nuri</title>\n<style>\n"));
+    out.push_str(&format!(
+        "body {{ margin: 2rem auto; max-width: 52rem; padding: 0 1rem; font-family: \
+         ui-monospace, monospace; background: {}; color: {}; }}\n",
+        p.background.to_hex(),
+        p.foreground.to_hex()
+    ));
+    out.push_str(
+        "img { max-width: 100%; border-radius: 6px; }\n\
+         .grid { display: grid; grid-template-columns: repeat(8, 1fr); gap: 4px; }\n\
+         .swatch { height: 4.5rem; border-radius: 4px; padding: 4px; font-size: 0.7rem; \
+         display: flex; flex-direction: column; justify-content: flex-end; }\n\
+         pre { padding: 1rem; border-radius: 6px; line-height: 1.4; overflow-x: auto; }\n\
+         table { border-collapse: collapse; width: 100%; }\n\
+         td, th { text-align: left; padding: 0.25rem 0.5rem; }\n",
+    );
+    out.push_str("</style>\n</head>\n<body>\n");
+    out.push_str(&format!("<h1>{name}</h1>\n"));
+    if let Some(image) = source {
+        out.push_str(&format!(
+            "<img src=\"{}\" alt=\"source wallpaper\">\n",
+            png_data_uri(&thumbnail(image))?
+        ));
+    }
+
+    out.push_str("<h2>Palette</h2>\n<div class=\"grid\">\n");
+    let specials = [
+        ("background", p.background),
+        ("foreground", p.foreground),
+        ("cursor", p.cursor_color),
+        ("cursor text", p.cursor_text),
+        ("selection bg", p.selection_bg),
+        ("selection fg", p.selection_fg),
+    ];
+    let labeled = SLOT_LABELS
+        .iter()
+        .enumerate()
+        .map(|(i, label)| (format!("{i} {label}"), p.slots[i]))
+        .chain(specials.map(|(label, color)| (label.to_string(), color)));
+    for (label, color) in labeled {
+        out.push_str(&format!(
+            "<div class=\"swatch\" style=\"background: {hex}; color: {text}; \
+             outline: 1px solid {edge};\">{label}<br>{hex}</div>\n",
+            hex = color.to_hex(),
+            text = label_color(color).to_hex(),
+            edge = p.background.mix(p.foreground, 0.2).to_hex()
+        ));
+    }
+    out.push_str("</div>\n");
+
+    out.push_str("<h2>Terminal</h2>\n");
+    out.push_str(&format!(
+        "<pre style=\"background: {}; color: {}; outline: 1px solid {};\">",
+        p.background.to_hex(),
+        p.foreground.to_hex(),
+        p.background.mix(p.foreground, 0.2).to_hex()
+    ));
+    for line in SESSION {
+        for &(style, text) in *line {
+            out.push_str(&span(p, style, text));
+        }
+        out.push('\n');
+    }
+    out.push_str("</pre>\n");
+
+    out.push_str("<h2>Contrast</h2>\n<table>\n");
+    out.push_str("<tr><th>Pair</th><th>Sample</th><th>Ratio</th><th>WCAG</th></tr>\n");
+    for (label, fg, bg) in contrast_pairs(p) {
+        let ratio = Color::contrast_ratio(&fg, &bg);
+        out.push_str(&format!(
+            "<tr><td>{label}</td><td style=\"background: {}; color: {};\">Sample text</td>\
+             <td>{ratio:.2}:1</td><td>{}</td></tr>\n",
+            bg.to_hex(),
+            fg.to_hex(),
+            wcag_level(ratio)
+        ));
+    }
+    out.push_str("</table>\n");
+    out.push_str(&format!(
+        "<p>Generated by nuri {}</p>\n</body>\n</html>\n",
+        env!("CARGO_PKG_VERSION")
+    ));
+    Ok(out)
+}
+
+fn span(palette: &AnsiPalette, style: Style, text: &str) -> String {
+    let text = xml_escape(text);
+    let css = match style {
+        Text => return text,
+        Slot(i) => format!("color: {}", palette.slots[i].to_hex()),
+        Selection => format!(
+            "color: {}; background: {}",
+            palette.selection_fg.to_hex(),
+            palette.selection_bg.to_hex()
+        ),
+        Cursor => format!(
+            "color: {}; background: {}",
+            palette.cursor_text.to_hex(),
+            palette.cursor_color.to_hex()
+        ),
+    };
+    format!("<span style=\"{css}\">{text}</span>")
+}
+
+/// Text pairs as they appear in a terminal: the main text, selection and
+/// cursor, then every ANSI color on the background.
+fn contrast_pairs(palette: &AnsiPalette) -> Vec<(String, Color, Color)> {
+    let p = palette;
+    let mut pairs = vec![
+        ("foreground".to_string(), p.foreground, p.background),
+        ("selection".to_string(), p.selection_fg, p.selection_bg),
+        ("cursor".to_string(), p.cursor_text, p.cursor_color),
+    ];
+    for (i, label) in SLOT_LABELS.iter().enumerate() {
+        pairs.push((format!("{i} {label}"), p.slots[i], p.background));
+    }
+    pairs
+}
+
+/// WCAG 2 conformance of a text contrast ratio.
+fn wcag_level(ratio: f32) -> &'static str {
+    if ratio >= 7.0 {
+        "AAA"
+    } else if ratio >= 4.5 {
+        "AA"
+    } else if ratio >= 3.0 {
+        "AA large"
+    } else {
+        "fail"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::ThemeMode;
+    use crate::pipeline::assign::assign_slots;
+    use crate::pipeline::extract::ExtractedColor;
+
+    fn test_palette() -> AnsiPalette {
+        let colors = vec![ExtractedColor {
+            color: Color::new(90, 110, 200),
+            weight: 1.0,
+        }];
+        assign_slots(&colors, ThemeMode::Dark)
+    }
+
+    #[test]
+    fn page_shows_palette_session_and_contrast() {
+        let p = test_palette();
+        let page = render(&p, "dusk & dawn", None).unwrap();
+        assert!(page.starts_with("<!DOCTYPE html>"));
+        assert!(page.contains("<h1>dusk &amp; dawn</h1>"));
+        assert!(!page.contains("<img"));
+        for color in p.slots {
+            assert!(page.contains(&format!("<br>{}</div>", color.to_hex())));
+        }
+        let ratio = Color::contrast_ratio(&p.foreground, &p.background);
+        assert!(page.contains("<td>foreground</td>"));
+        assert!(page.contains(&format!("<td>{ratio:.2}:1</td>")));
+        assert!(page.contains(&format!(
+            "<span style=\"color: {}; background: {}\"> </span>",
+            p.cursor_text.to_hex(),
+            p.cursor_color.to_hex()
+        )));
+        assert!(page.trim_end().ends_with("</html>"));
+    }
+
+    #[test]
+    fn session_uses_every_ansi_color() {
+        for i in 1..16 {
+            let used = SESSION
+                .iter()
+                .flat_map(|line| line.iter())
+                .any(|(style, _)| matches!(style, Slot(s) if *s == i));
+            assert!(used, "slot {i} is not shown in the session");
+        }
+    }
+
+    #[test]
+    fn wcag_levels() {
+        assert_eq!(wcag_level(21.0), "AAA");
+        assert_eq!(wcag_level(4.5), "AA");
+        assert_eq!(wcag_level(3.2), "AA large");
+        assert_eq!(wcag_level(1.5), "fail");
+    }
+}
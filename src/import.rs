@@ -0,0 +1,405 @@
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+
+use crate::color::Color;
+use crate::pipeline::assign::AnsiPalette;
+
+/// Alacritty color names in slot order, shared by `normal` and `bright`.
+const ALACRITTY_NAMES: [&str; 8] = [
+    "black", "red", "green", "yellow", "blue", "magenta", "cyan", "white",
+];
+
+/// base16 key feeding each ANSI slot (the base16-shell mapping).
+const BASE16_SLOTS: [&str; 16] = [
+    "base00", "base08", "base0B", "base0A", "base0D", "base0E", "base0C", "base05", "base03",
+    "base08", "base0B", "base0A", "base0D", "base0E", "base0C", "base07",
+];
+
+/// Theme formats `load_palette` can read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThemeFormat {
+    /// nuri's own JSON dump (`--target json`).
+    Json,
+    /// Ghostty `key = value` theme.
+    Ghostty,
+    /// Alacritty TOML `[colors.*]` tables.
+    Alacritty,
+    /// base16 / tinted-theming YAML scheme.
+    Base16,
+}
+
+impl ThemeFormat {
+    /// Guess the format from the file extension. Ghostty themes usually have
+    /// no extension at all.
+    pub fn from_path(path: &Path) -> Option<ThemeFormat> {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("json") => Some(ThemeFormat::Json),
+            Some("toml") => Some(ThemeFormat::Alacritty),
+            Some("yaml") | Some("yml") => Some(ThemeFormat::Base16),
+            Some("conf") | None => Some(ThemeFormat::Ghostty),
+            Some(_) => None,
+        }
+    }
+}
+
+/// Whether `path` looks like a theme file `load_palette` understands.
+pub fn is_theme_file(path: &Path) -> bool {
+    ThemeFormat::from_path(path).is_some()
+}
+
+/// Read a palette back from a theme file, picking the parser by extension.
+pub fn load_palette(path: &Path) -> Result<AnsiPalette> {
+    let Some(format) = ThemeFormat::from_path(path) else {
+        bail!(
+            "unsupported theme format: {} (expected Ghostty, Alacritty .toml, base16 .yaml or nuri .json)",
+            path.display()
+        );
+    };
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read theme: {}", path.display()))?;
+    parse_palette(&text, format)
+        .with_context(|| format!("invalid {format:?} theme: {}", path.display()))
+}
+
+/// Parse theme text in the given format.
+pub fn parse_palette(text: &str, format: ThemeFormat) -> Result<AnsiPalette> {
+    match format {
+        ThemeFormat::Json => Ok(serde_json::from_str(text)?),
+        ThemeFormat::Ghostty => parse_ghostty(text),
+        ThemeFormat::Alacritty => parse_alacritty(text),
+        ThemeFormat::Base16 => parse_base16(text),
+    }
+}
+
+/// Palette fields collected while parsing; specials fall back to slot colors.
+#[derive(Default)]
+struct PartialPalette {
+    slots: [Option<Color>; 16],
+    background: Option<Color>,
+    foreground: Option<Color>,
+    cursor_color: Option<Color>,
+    cursor_text: Option<Color>,
+    selection_bg: Option<Color>,
+    selection_fg: Option<Color>,
+}
+
+impl PartialPalette {
+    fn finish(self) -> Result<AnsiPalette> {
+        let missing: Vec<String> = (0..16)
+            .filter(|&i| self.slots[i].is_none())
+            .map(|i| i.to_string())
+            .collect();
+        if !missing.is_empty() {
+            bail!("missing palette entries: {}", missing.join(", "));
+        }
+        let slots = self.slots.map(|c| c.unwrap_or(Color::new(0, 0, 0)));
+        let background = self.background.unwrap_or(slots[0]);
+        let foreground = self.foreground.unwrap_or(slots[7]);
+        Ok(AnsiPalette {
+            slots,
+            background,
+            foreground,
+            cursor_color: self.cursor_color.unwrap_or(foreground),
+            cursor_text: self.cursor_text.unwrap_or(background),
+            selection_bg: self.selection_bg.unwrap_or(slots[8]),
+            selection_fg: self.selection_fg.unwrap_or(foreground),
+        })
+    }
+}
+
+/// Every distinct `#rrggbb` color in `text`, in order of first appearance.
+///
+/// A format-agnostic fallback for files no parser understands (Zellij, Neovim,
+/// WezTerm, Windows Terminal).
+pub fn scan_colors(text: &str) -> Vec<Color> {
+    let mut colors: Vec<Color> = Vec::new();
+    for (i, _) in text.match_indices('#') {
+        let Some(hex) = text.get(i + 1..i + 7) else {
+            continue;
+        };
+        let whole = !text[i + 7..].starts_with(|c: char| c.is_ascii_hexdigit());
+        if let (true, Ok(color)) = (whole, Color::from_hex(hex)) {
+            if !colors.contains(&color) {
+                colors.push(color);
+            }
+        }
+    }
+    colors
+}
+
+/// Parse a color written as `#rrggbb`, `rrggbb` or `0xrrggbb`.
+fn parse_color(value: &str) -> Result<Color> {
+    let value = value.trim().trim_matches('"').trim_matches('\'');
+    Color::from_hex(value.strip_prefix("0x").unwrap_or(value))
+}
+
+fn parse_ghostty(text: &str) -> Result<AnsiPalette> {
+    let mut p = PartialPalette::default();
+    for (n, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let value = value.trim();
+        let (field, color) = match key.trim() {
+            "palette" => {
+                let (index, color) = value
+                    .split_once('=')
+                    .with_context(|| format!("line {}: expected palette = N=#rrggbb", n + 1))?;
+                let index: usize = index
+                    .trim()
+                    .parse()
+                    .ok()
+                    .filter(|&i| i < 16)
+                    .with_context(|| format!("line {}: palette index must be 0-15", n + 1))?;
+                (&mut p.slots[index], color)
+            }
+            "background" => (&mut p.background, value),
+            "foreground" => (&mut p.foreground, value),
+            "cursor-color" => (&mut p.cursor_color, value),
+            "cursor-text" => (&mut p.cursor_text, value),
+            "selection-background" => (&mut p.selection_bg, value),
+            "selection-foreground" => (&mut p.selection_fg, value),
+            _ => continue,
+        };
+        *field = Some(parse_color(color).with_context(|| format!("line {}", n + 1))?);
+    }
+    p.finish()
+}
+
+fn parse_alacritty(text: &str) -> Result<AnsiPalette> {
+    let doc: toml::Table = toml::from_str(text)?;
+    let colors = doc
+        .get("colors")
+        .and_then(|v| v.as_table())
+        .context("missing [colors] table")?;
+    // Alacritty also allows named references like "CellForeground"; those
+    // are left unset so the fallbacks apply.
+    let get = |table: &str, key: &str| -> Option<Color> {
+        colors
+            .get(table)?
+            .get(key)?
+            .as_str()
+            .and_then(|s| parse_color(s).ok())
+    };
+
+    let mut p = PartialPalette::default();
+    for (i, name) in ALACRITTY_NAMES.iter().enumerate() {
+        p.slots[i] = get("normal", name);
+        p.slots[i + 8] = get("bright", name);
+    }
+    p.background = get("primary", "background");
+    p.foreground = get("primary", "foreground");
+    p.cursor_color = get("cursor", "cursor");
+    p.cursor_text = get("cursor", "text");
+    p.selection_bg = get("selection", "background");
+    p.selection_fg = get("selection", "text");
+    p.finish()
+}
+
+/// Index of a `base00`-`base0F` key.
+fn base16_index(key: &str) -> Option<usize> {
+    let digit = key.strip_prefix("base0")?;
+    let mut chars = digit.chars();
+    let index = chars.next()?.to_digit(16)?;
+    chars.next().is_none().then_some(index as usize)
+}
+
+/// The scalar at the start of a YAML value: a quoted string, or the first
+/// bare word (dropping any trailing `# comment`).
+fn yaml_scalar(value: &str) -> &str {
+    let value = value.trim();
+    for quote in ['"', '\''] {
+        if let Some(rest) = value.strip_prefix(quote) {
+            return rest.split(quote).next().unwrap_or(rest);
+        }
+    }
+    value.split_whitespace().next().unwrap_or("")
+}
+
+fn parse_base16(text: &str) -> Result<AnsiPalette> {
+    // Scheme files are flat `baseXX: "rrggbb"` pairs, optionally nested under
+    // `palette:` (tinted-theming), so a line scan covers both layouts.
+    let mut base = [None; 16];
+    for line in text.lines() {
+        let Some((key, value)) = line.trim().split_once(':') else {
+            continue;
+        };
+        let key = key.trim().trim_matches('"');
+        let Some(index) = base16_index(key) else {
+            continue;
+        };
+        base[index] = Some(
+            parse_color(yaml_scalar(value)).with_context(|| format!("invalid color for {key}"))?,
+        );
+    }
+
+    let lookup = |key: &str| base16_index(key).and_then(|i| base[i]);
+    let mut p = PartialPalette::default();
+    for (i, key) in BASE16_SLOTS.iter().enumerate() {
+        p.slots[i] = lookup(key);
+    }
+    p.background = lookup("base00");
+    p.foreground = lookup("base05");
+    p.cursor_color = lookup("base05");
+    p.cursor_text = lookup("base00");
+    p.selection_bg = lookup("base02");
+    p.selection_fg = lookup("base05");
+    p.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backends::alacritty::AlacrittyBackend;
+    use crate::backends::ghostty::GhosttyBackend;
+    use crate::backends::json::JsonBackend;
+    use crate::backends::ThemeBackend;
+    use crate::cli::ThemeMode;
+    use crate::color::Color;
+    use crate::pipeline::assign::assign_slots;
+    use crate::pipeline::extract::ExtractedColor;
+    use palette::Oklch;
+
+    fn make_extracted(l: f32, chroma: f32, hue: f32, weight: f32) -> ExtractedColor {
+        ExtractedColor {
+            color: Color::from_oklch(Oklch::new(l, chroma, hue)),
+            weight,
+        }
+    }
+
+    fn test_palette() -> AnsiPalette {
+        let colors = vec![
+            make_extracted(0.60, 0.20, 25.0, 0.12),
+            make_extracted(0.60, 0.20, 145.0, 0.12),
+            make_extracted(0.70, 0.20, 90.0, 0.12),
+            make_extracted(0.55, 0.20, 260.0, 0.12),
+            make_extracted(0.60, 0.20, 325.0, 0.12),
+            make_extracted(0.65, 0.20, 195.0, 0.10),
+            make_extracted(0.10, 0.01, 0.0, 0.15),
+            make_extracted(0.95, 0.01, 0.0, 0.15),
+        ];
+        assign_slots(&colors, ThemeMode::Dark)
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let dir = std::env::temp_dir().join("nuri-test-import-json");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("theme.json");
+        let palette = test_palette();
+        JsonBackend.write_to(&palette, "theme", &path).unwrap();
+
+        assert_eq!(load_palette(&path).unwrap(), palette);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn unknown_extension_errors() {
+        let dir = std::env::temp_dir().join("nuri-test-import-unknown");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("theme.txt");
+        std::fs::write(&path, "hello").unwrap();
+
+        assert!(load_palette(&path).is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn ghostty_round_trip() {
+        let palette = test_palette();
+        let text = GhosttyBackend.serialize(&palette, "test");
+        assert_eq!(parse_palette(&text, ThemeFormat::Ghostty).unwrap(), palette);
+    }
+
+    #[test]
+    fn alacritty_round_trip() {
+        let palette = test_palette();
+        let text = AlacrittyBackend.serialize(&palette, "test");
+        assert_eq!(
+            parse_palette(&text, ThemeFormat::Alacritty).unwrap(),
+            palette
+        );
+    }
+
+    #[test]
+    fn ghostty_specials_fall_back_to_slots() {
+        let text: String = (0..16)
+            .map(|i| format!("palette = {i}=#{:02x}0000\n", i * 10))
+            .collect();
+        let palette = parse_palette(&text, ThemeFormat::Ghostty).unwrap();
+        assert_eq!(palette.background, palette.slots[0]);
+        assert_eq!(palette.foreground, palette.slots[7]);
+        assert_eq!(palette.cursor_text, palette.background);
+    }
+
+    #[test]
+    fn ghostty_missing_slots_error() {
+        let err = parse_palette("palette = 0=#000000\n", ThemeFormat::Ghostty).unwrap_err();
+        assert!(err.to_string().contains("missing palette entries"));
+    }
+
+    #[test]
+    fn base16_scheme_maps_to_ansi() {
+        let text = r##"
+scheme: "Test"
+author: "nobody"
+base00: "181818" # background
+base01: "282828"
+base02: "383838"
+base03: "585858"
+base04: "b8b8b8"
+base05: "d8d8d8"
+base06: "e8e8e8"
+base07: "f8f8f8"
+base08: "ab4642"
+base09: "dc9656"
+base0A: "f7ca88"
+base0B: "a1b56c"
+base0C: "86c1b9"
+base0D: "7cafc2"
+base0E: "ba8baf"
+base0F: '#a16946'
+"##;
+        let palette = parse_palette(text, ThemeFormat::Base16).unwrap();
+        assert_eq!(palette.background.to_hex(), "#181818");
+        assert_eq!(palette.foreground.to_hex(), "#d8d8d8");
+        assert_eq!(palette.selection_bg.to_hex(), "#383838");
+        assert_eq!(palette.slots[1].to_hex(), "#ab4642");
+        assert_eq!(palette.slots[4].to_hex(), "#7cafc2");
+        assert_eq!(palette.slots[8].to_hex(), "#585858");
+        assert_eq!(palette.slots[15].to_hex(), "#f8f8f8");
+    }
+
+    #[test]
+    fn scan_colors_finds_distinct_hex_values() {
+        let text = "fg \"#AABBCC\"\nbg = '#000000' # #aabbcc again\nid #12345678 #abc\n";
+        assert_eq!(
+            scan_colors(text),
+            [Color::new(0xaa, 0xbb, 0xcc), Color::new(0, 0, 0)]
+        );
+    }
+
+    #[test]
+    fn format_is_guessed_from_extension() {
+        assert_eq!(
+            ThemeFormat::from_path(Path::new("nord")),
+            Some(ThemeFormat::Ghostty)
+        );
+        assert_eq!(
+            ThemeFormat::from_path(Path::new("nord.toml")),
+            Some(ThemeFormat::Alacritty)
+        );
+        assert_eq!(
+            ThemeFormat::from_path(Path::new("nord.yml")),
+            Some(ThemeFormat::Base16)
+        );
+        assert_eq!(ThemeFormat::from_path(Path::new("nord.png")), None);
+    }
+}
@@ -1,6 +1,50 @@
+//! Generate terminal, multiplexer and editor color themes from wallpaper images.
+//!
+//! ```no_run
+//! use std::path::Path;
+//!
+//! use nuri::{get_backend, Palette, PaletteOptions, Target};
+//!
+//! let palette = Palette::from_image(Path::new("wallpaper.jpg"), &PaletteOptions::default())?;
+//! let theme = get_backend(Target::Ghostty)?.serialize(&palette, "wallpaper");
+//! println!("{theme}");
+//! # Ok::<(), nuri::NuriError>(())
+//! ```
+
 pub mod backends;
+pub mod batch;
+pub mod bench;
+pub mod cache;
+pub mod card;
+pub mod check;
 pub mod cli;
+pub mod clipboard;
 pub mod color;
+pub mod config;
+pub mod error;
+pub mod fetch;
+pub mod html;
+pub mod import;
+pub mod live;
+pub mod logging;
+pub mod naming;
 pub mod pipeline;
 pub mod preview;
+pub mod progress;
+pub mod provenance;
+pub mod report;
+pub mod shell;
+pub mod term;
 pub mod tui;
+#[cfg(feature = "video")]
+pub mod video;
+pub mod wallpaper;
+pub mod watch;
+
+pub use backends::{get_backend, Target, ThemeBackend};
+pub use cli::ThemeMode;
+pub use color::Color;
+pub use error::NuriError;
+pub use pipeline::assign::AnsiPalette;
+pub use pipeline::assign::AnsiPalette as Palette;
+pub use pipeline::PaletteOptions;
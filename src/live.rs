@@ -0,0 +1,402 @@
+use std::io::{self, IsTerminal, Read, Write};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Context, Result};
+
+use crate::color::Color;
+use crate::pipeline::assign::AnsiPalette;
+
+/// OSC terminator (BEL), accepted by every terminal that supports OSC colors.
+const BEL: &str = "\x07";
+
+/// Reset the 16 ANSI colors, foreground, background and cursor to the
+/// terminal's configured defaults (OSC 104/110/111/112).
+const RESET_SEQUENCE: &str = "\x1b]104\x07\x1b]110\x07\x1b]111\x07\x1b]112\x07";
+
+/// Format a color as an X11 `rgb:rr/gg/bb` spec used by OSC color sequences.
+fn rgb_spec(c: &Color) -> String {
+    format!("rgb:{:02x}/{:02x}/{:02x}", c.r, c.g, c.b)
+}
+
+/// Build the escape sequences that push `palette` into the running terminal:
+/// OSC 4 for the 16 ANSI slots, then OSC 10/11/12 for foreground, background
+/// and cursor.
+pub fn apply_sequence(palette: &AnsiPalette) -> String {
+    let mut out = String::new();
+    for (i, color) in palette.slots.iter().enumerate() {
+        out.push_str(&format!("\x1b]4;{i};{}{BEL}", rgb_spec(color)));
+    }
+    out.push_str(&format!("\x1b]10;{}{BEL}", rgb_spec(&palette.foreground)));
+    out.push_str(&format!("\x1b]11;{}{BEL}", rgb_spec(&palette.background)));
+    out.push_str(&format!("\x1b]12;{}{BEL}", rgb_spec(&palette.cursor_color)));
+    out
+}
+
+/// Escape sequences that restore the terminal's original colors.
+pub fn reset_sequence() -> &'static str {
+    RESET_SEQUENCE
+}
+
+/// Write the palette to the terminal behind `out`.
+pub fn apply(palette: &AnsiPalette, out: &mut impl Write) -> Result<()> {
+    out.write_all(apply_sequence(palette).as_bytes())
+        .and_then(|_| out.flush())
+        .context("failed to write color sequences to the terminal")
+}
+
+/// Restore the terminal behind `out` to its configured colors.
+pub fn reset(out: &mut impl Write) -> Result<()> {
+    out.write_all(RESET_SEQUENCE.as_bytes())
+        .and_then(|_| out.flush())
+        .context("failed to write color reset sequences to the terminal")
+}
+
+/// How long to wait for the terminal to report its colors.
+const QUERY_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// The terminal's colors before `nuri try` changed them; `None` where it
+/// didn't answer the query.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SavedColors {
+    pub slots: [Option<Color>; 16],
+    pub foreground: Option<Color>,
+    pub background: Option<Color>,
+    pub cursor: Option<Color>,
+}
+
+/// Ask for the 16 ANSI slots (OSC 4), foreground, background and cursor
+/// (OSC 10/11/12), then for the device attributes (DA1). Every terminal
+/// answers DA1, and answers in order, so its reply marks the end of the
+/// color reports even when the terminal ignores the color queries.
+pub fn query_sequence() -> String {
+    let mut out = String::new();
+    for i in 0..16 {
+        out.push_str(&format!("\x1b]4;{i};?{BEL}"));
+    }
+    for code in [10, 11, 12] {
+        out.push_str(&format!("\x1b]{code};?{BEL}"));
+    }
+    out.push_str("\x1b[c");
+    out
+}
+
+/// Whether `replies` include the DA1 answer that ends them.
+fn replies_done(replies: &[u8]) -> bool {
+    replies
+        .windows(3)
+        .position(|w| w == b"\x1b[?")
+        .is_some_and(|start| replies[start..].contains(&b'c'))
+}
+
+/// Whether `input` holds a key press rather than only replies to
+/// [`query_sequence`], which can still arrive after [`QUERY_TIMEOUT`]: OSC
+/// color reports and the DA1 answer (`ESC [ ? ... c`). `in_reply` carries a
+/// report cut off at the end of one read over to the next.
+fn has_keypress(input: &[u8], in_reply: &mut bool) -> bool {
+    let mut rest = input;
+    while !rest.is_empty() {
+        if *in_reply {
+            // Reports end at BEL or ST (ESC \) and never contain a backslash
+            match rest.iter().position(|&b| b == b'\x07' || b == b'\\') {
+                Some(end) => {
+                    rest = &rest[end + 1..];
+                    *in_reply = false;
+                }
+                None => return false,
+            }
+        } else if let Some(report) = rest.strip_prefix(b"\x1b]") {
+            rest = report;
+            *in_reply = true;
+        } else if let Some(attributes) = rest.strip_prefix(b"\x1b[?") {
+            match attributes.iter().position(|b| (0x40..=0x7e).contains(b)) {
+                Some(end) => rest = &attributes[end + 1..],
+                None => return false,
+            }
+        } else {
+            return true;
+        }
+    }
+    false
+}
+
+/// The colors reported in `replies`: `ESC ] 4 ; N ; rgb:R/G/B` and
+/// `ESC ] 1x ; rgb:R/G/B`, each ended by BEL or ST.
+pub fn parse_replies(replies: &str) -> SavedColors {
+    let mut saved = SavedColors::default();
+    for report in replies.split("\x1b]").skip(1) {
+        let report = report.split(['\x07', '\x1b']).next().unwrap_or_default();
+        let mut fields = report.split(';');
+        let target = match fields.next() {
+            Some("4") => match fields.next().and_then(|i| i.parse::<usize>().ok()) {
+                Some(i) if i < 16 => &mut saved.slots[i],
+                _ => continue,
+            },
+            Some("10") => &mut saved.foreground,
+            Some("11") => &mut saved.background,
+            Some("12") => &mut saved.cursor,
+            _ => continue,
+        };
+        if let Some(color) = fields.next().and_then(parse_spec) {
+            *target = Some(color);
+        }
+    }
+    saved
+}
+
+/// An X11 `rgb:R/G/B` spec with 1 to 4 hex digits per channel, as
+/// terminals report colors.
+fn parse_spec(spec: &str) -> Option<Color> {
+    let channels = spec.strip_prefix("rgb:")?;
+    let mut values = channels.split('/').map(|hex| {
+        let digits = u32::try_from(hex.len())
+            .ok()
+            .filter(|n| (1..=4).contains(n))?;
+        let value = u32::from_str_radix(hex, 16).ok()?;
+        let max = 16u32.pow(digits) - 1;
+        u8::try_from((value * 255 + max / 2) / max).ok()
+    });
+    let (r, g, b) = (values.next()??, values.next()??, values.next()??);
+    values.next().is_none().then_some(Color::new(r, g, b))
+}
+
+/// Escape sequences that put back the colors in `saved`, resetting to the
+/// configured default anything the terminal didn't report.
+pub fn restore_sequence(saved: &SavedColors) -> String {
+    let mut out = String::new();
+    for (i, color) in saved.slots.iter().enumerate() {
+        match color {
+            Some(color) => out.push_str(&format!("\x1b]4;{i};{}{BEL}", rgb_spec(color))),
+            None => out.push_str(&format!("\x1b]104;{i}{BEL}")),
+        }
+    }
+    for (code, color) in [
+        (10, saved.foreground),
+        (11, saved.background),
+        (12, saved.cursor),
+    ] {
+        match color {
+            Some(color) => out.push_str(&format!("\x1b]{code};{}{BEL}", rgb_spec(&color))),
+            None => out.push_str(&format!("\x1b]{}{BEL}", code + 100)),
+        }
+    }
+    out
+}
+
+/// Lines drawn in the terminal's own colors while a palette is on trial:
+/// both rows of slots as cells and some text in each accent.
+fn trial_sample() -> String {
+    let mut out = String::from("\r\n  ");
+    for code in (40..48).chain(100..108) {
+        if code == 100 {
+            out.push_str("\x1b[0m\r\n  ");
+        }
+        out.push_str(&format!("\x1b[{code}m     "));
+    }
+    out.push_str("\x1b[0m\r\n\r\n  ");
+    for (code, name) in (31..37).zip(["red", "green", "yellow", "blue", "magenta", "cyan"]) {
+        out.push_str(&format!("\x1b[{code}m{name}\x1b[0m "));
+    }
+    out.push_str("\r\n\r\n");
+    out
+}
+
+/// Put `palette` on the current terminal until a key is pressed, or for
+/// `duration`, then put back the colors it had: `nuri try`. Needs stdin and
+/// stdout on the terminal.
+pub fn audition(palette: &AnsiPalette, duration: Option<Duration>) -> Result<()> {
+    if !io::stdin().is_terminal() || !io::stdout().is_terminal() {
+        bail!("nuri try recolors the terminal it runs in; run it in one, not through a pipe");
+    }
+    crossterm::terminal::enable_raw_mode().context("cannot switch the terminal to raw mode")?;
+    let result = audition_raw(palette, duration);
+    let _ = crossterm::terminal::disable_raw_mode();
+    // Logged only now; raw mode would leave the lines unaligned
+    if result.as_ref().is_ok_and(|&reported| reported == 0) {
+        log::warn!("the terminal didn't report its colors, so they were reset to its defaults");
+    }
+    result.map(drop)
+}
+
+/// [`audition`] in raw mode; how many of the 16 slots the terminal reported.
+fn audition_raw(palette: &AnsiPalette, duration: Option<Duration>) -> Result<usize> {
+    // Replies and keys arrive on stdin; a thread reads it so both can be
+    // waited for with a timeout. It's left blocked on the read at the end.
+    let (sender, input) = mpsc::channel();
+    std::thread::spawn(move || {
+        let mut buf = [0u8; 256];
+        let mut stdin = io::stdin();
+        while let Ok(n @ 1..) = stdin.read(&mut buf) {
+            if sender.send(buf[..n].to_vec()).is_err() {
+                break;
+            }
+        }
+    });
+
+    let mut out = io::stdout();
+    out.write_all(query_sequence().as_bytes())
+        .and_then(|_| out.flush())
+        .context("failed to query the terminal's colors")?;
+    let deadline = Instant::now() + QUERY_TIMEOUT;
+    let mut replies = Vec::new();
+    while !replies_done(&replies) {
+        let left = deadline.saturating_duration_since(Instant::now());
+        match input.recv_timeout(left) {
+            Ok(bytes) => replies.extend(bytes),
+            Err(_) => break,
+        }
+    }
+    let saved = parse_replies(&String::from_utf8_lossy(&replies));
+    let reported = saved.slots.iter().filter(|c| c.is_some()).count();
+
+    apply(palette, &mut out)?;
+    let prompt = match duration {
+        Some(d) => format!("Trying the theme for {d:?}; press any key to restore sooner"),
+        None => "Trying the theme; press any key to restore your colors".to_string(),
+    };
+    let shown = write!(out, "{}  {prompt}\r\n", trial_sample()).and_then(|_| out.flush());
+    if shown.is_ok() {
+        // Replies that missed the timeout don't count as the key
+        let mut in_reply = false;
+        has_keypress(&replies, &mut in_reply);
+        let deadline = duration.map(|d| Instant::now() + d);
+        loop {
+            let read = match deadline {
+                Some(deadline) => input
+                    .recv_timeout(deadline.saturating_duration_since(Instant::now()))
+                    .ok(),
+                None => input.recv().ok(),
+            };
+            match read {
+                Some(bytes) if !has_keypress(&bytes, &mut in_reply) => {}
+                _ => break,
+            }
+        }
+    }
+    out.write_all(restore_sequence(&saved).as_bytes())
+        .and_then(|_| out.flush())
+        .context("failed to restore the terminal's colors")?;
+    Ok(reported)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::ThemeMode;
+    use crate::pipeline::assign::assign_slots;
+    use crate::pipeline::extract::ExtractedColor;
+    use palette::Oklch;
+
+    fn make_extracted(l: f32, chroma: f32, hue: f32, weight: f32) -> ExtractedColor {
+        ExtractedColor {
+            color: Color::from_oklch(Oklch::new(l, chroma, hue)),
+            weight,
+        }
+    }
+
+    fn test_palette() -> AnsiPalette {
+        let colors = vec![
+            make_extracted(0.60, 0.20, 25.0, 0.12),
+            make_extracted(0.60, 0.20, 145.0, 0.12),
+            make_extracted(0.70, 0.20, 90.0, 0.12),
+            make_extracted(0.55, 0.20, 260.0, 0.12),
+            make_extracted(0.60, 0.20, 325.0, 0.12),
+            make_extracted(0.65, 0.20, 195.0, 0.10),
+            make_extracted(0.10, 0.01, 0.0, 0.15),
+            make_extracted(0.95, 0.01, 0.0, 0.15),
+        ];
+        assign_slots(&colors, ThemeMode::Dark)
+    }
+
+    #[test]
+    fn rgb_spec_is_lowercase_hex() {
+        let c = Color {
+            r: 0xAB,
+            g: 0x0C,
+            b: 0xFF,
+        };
+        assert_eq!(rgb_spec(&c), "rgb:ab/0c/ff");
+    }
+
+    #[test]
+    fn apply_sequence_sets_every_slot_and_special_color() {
+        let palette = test_palette();
+        let seq = apply_sequence(&palette);
+
+        for (i, color) in palette.slots.iter().enumerate() {
+            let expected = format!("\x1b]4;{i};{}\x07", rgb_spec(color));
+            assert!(seq.contains(&expected), "missing OSC 4 for slot {i}");
+        }
+        assert!(seq.contains(&format!("\x1b]10;{}\x07", rgb_spec(&palette.foreground))));
+        assert!(seq.contains(&format!("\x1b]11;{}\x07", rgb_spec(&palette.background))));
+        assert!(seq.contains(&format!("\x1b]12;{}\x07", rgb_spec(&palette.cursor_color))));
+        assert_eq!(seq.matches("\x1b]").count(), 19);
+    }
+
+    #[test]
+    fn apply_and_reset_write_to_output() {
+        let palette = test_palette();
+        let mut buf = Vec::new();
+        apply(&palette, &mut buf).unwrap();
+        reset(&mut buf).unwrap();
+
+        let written = String::from_utf8(buf).unwrap();
+        assert!(written.starts_with(&apply_sequence(&palette)));
+        assert!(written.ends_with(reset_sequence()));
+    }
+
+    #[test]
+    fn parses_color_reports_in_any_terminator_and_precision() {
+        let replies = "\x1b]4;0;rgb:0000/0000/0000\x07\x1b]4;1;rgb:cdcd/0000/0000\x1b\\\
+                       \x1b]4;15;rgb:f/f/f\x07\x1b]10;rgb:e5e5/e5e5/e5e5\x07\
+                       \x1b]11;rgb:1e/1e/2e\x1b\\\x1b[?62;22c";
+        assert!(replies_done(replies.as_bytes()));
+        assert!(!replies_done(b"\x1b]4;0;rgb:0000/0000/0000\x07"));
+        let saved = parse_replies(replies);
+        assert_eq!(saved.slots[0], Some(Color::new(0, 0, 0)));
+        assert_eq!(saved.slots[1], Some(Color::new(0xcd, 0, 0)));
+        assert_eq!(saved.slots[15], Some(Color::new(255, 255, 255)));
+        assert_eq!(saved.slots[2], None);
+        assert_eq!(saved.foreground, Some(Color::new(0xe5, 0xe5, 0xe5)));
+        assert_eq!(saved.background, Some(Color::new(0x1e, 0x1e, 0x2e)));
+        assert_eq!(saved.cursor, None);
+        assert_eq!(parse_spec("rgb:12/34"), None);
+        assert_eq!(parse_spec("rgbi:1/0/0"), None);
+    }
+
+    #[test]
+    fn late_replies_are_not_a_keypress() {
+        let mut in_reply = false;
+        assert!(!has_keypress(
+            b"\x1b]4;1;rgb:cdcd/0000/0000\x1b\\\x1b]11;rgb:1e/1e/2e\x07\x1b[?62;22c",
+            &mut in_reply
+        ));
+        // A report split over two reads
+        assert!(!has_keypress(b"\x1b]10;rgb:e5e5/", &mut in_reply));
+        assert!(in_reply);
+        assert!(!has_keypress(b"e5e5/e5e5\x07", &mut in_reply));
+        assert!(!in_reply);
+
+        assert!(has_keypress(b"q", &mut in_reply));
+        assert!(has_keypress(b"\x1b", &mut in_reply));
+        assert!(has_keypress(b"\x1b[A", &mut in_reply));
+        assert!(has_keypress(b"\x1b]11;rgb:0/0/0\x07 ", &mut in_reply));
+    }
+
+    #[test]
+    fn restore_puts_back_reported_colors_and_resets_the_rest() {
+        let mut saved = SavedColors::default();
+        saved.slots[3] = Some(Color::new(0xcd, 0xcd, 0));
+        saved.background = Some(Color::new(0x10, 0x10, 0x14));
+        let seq = restore_sequence(&saved);
+        assert!(seq.contains("\x1b]4;3;rgb:cd/cd/00\x07"));
+        assert!(seq.contains("\x1b]104;0\x07"));
+        assert!(seq.contains("\x1b]11;rgb:10/10/14\x07"));
+        assert!(seq.contains("\x1b]110\x07") && seq.contains("\x1b]112\x07"));
+        assert_eq!(seq.matches("\x1b]").count(), 19);
+
+        let query = query_sequence();
+        assert_eq!(query.matches(";?\x07").count(), 19);
+        assert!(query.ends_with("\x1b[c"));
+    }
+}
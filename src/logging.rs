@@ -0,0 +1,119 @@
+//! Stderr logging for the CLI. By default nuri prints warnings and status
+//! lines; `-v` adds the pipeline's decisions (mode, slot candidates, contrast
+//! adjustments), `-vv` every candidate considered, and `--quiet` only errors.
+//!
+//! This is a `log` logger rather than a `tracing` subscriber on purpose:
+//! nuri emits flat messages and no spans, library users get its records
+//! through whichever `log` backend they install, and the output needs
+//! nuri's own handling anyway (the `error:`/`warning:` prefixes, clearing the
+//! progress line, staying quiet under the TUI).
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+
+use crate::{progress, term};
+
+/// Messages from other crates are dropped; their debug output is noise here.
+const TARGET: &str = "nuri";
+
+struct StderrLogger;
+
+static LOGGER: StderrLogger = StderrLogger;
+
+/// Level for `-v` repeated `verbose` times, or errors only with `quiet`.
+pub fn level(verbose: u8, quiet: bool) -> LevelFilter {
+    match (quiet, verbose) {
+        (true, _) => LevelFilter::Error,
+        (false, 0) => LevelFilter::Info,
+        (false, 1) => LevelFilter::Debug,
+        (false, _) => LevelFilter::Trace,
+    }
+}
+
+/// Install the stderr logger at `level`. Only the first call installs it;
+/// later ones just change the level.
+pub fn init(level: LevelFilter) {
+    let _ = log::set_logger(&LOGGER);
+    log::set_max_level(level);
+}
+
+impl Log for StderrLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level() && is_ours(metadata.target())
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let line = format_record(
+            record.level(),
+            record.target(),
+            &record.args().to_string(),
+            term::stderr_colored(),
+        );
+        progress::suspend(|| eprintln!("{line}"));
+    }
+
+    fn flush(&self) {}
+}
+
+fn is_ours(target: &str) -> bool {
+    target == TARGET || target.starts_with("nuri::")
+}
+
+/// `error: ...` and `warning: ...` as nuri always printed them, in bold red
+/// and yellow when `colored`, status lines as-is, and decisions prefixed with
+/// the module that made them, e.g. `debug assign: red: ...`.
+fn format_record(level: Level, target: &str, message: &str, colored: bool) -> String {
+    match (level, colored) {
+        (Level::Error, false) => format!("error: {message}"),
+        (Level::Error, true) => format!("\x1b[1;31merror:\x1b[0m {message}"),
+        (Level::Warn, false) => format!("warning: {message}"),
+        (Level::Warn, true) => format!("\x1b[1;33mwarning:\x1b[0m {message}"),
+        (Level::Info, _) => message.to_string(),
+        (Level::Debug | Level::Trace, _) => {
+            let module = target.rsplit("::").next().unwrap_or(target);
+            format!("{} {module}: {message}", level.as_str().to_lowercase())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verbosity_maps_to_levels() {
+        assert_eq!(level(0, false), LevelFilter::Info);
+        assert_eq!(level(1, false), LevelFilter::Debug);
+        assert_eq!(level(3, false), LevelFilter::Trace);
+        assert_eq!(level(2, true), LevelFilter::Error);
+    }
+
+    #[test]
+    fn records_keep_the_familiar_prefixes() {
+        assert_eq!(
+            format_record(Level::Warn, "nuri", "very small image", false),
+            "warning: very small image"
+        );
+        assert_eq!(
+            format_record(Level::Error, "nuri", "no colors", true),
+            "\x1b[1;31merror:\x1b[0m no colors"
+        );
+        assert_eq!(
+            format_record(Level::Info, "nuri", "Wrote theme to x", true),
+            "Wrote theme to x"
+        );
+        assert_eq!(
+            format_record(
+                Level::Debug,
+                "nuri::pipeline::assign",
+                "red: #ff0000",
+                false
+            ),
+            "debug assign: red: #ff0000"
+        );
+        assert!(!is_ours("ureq::unversioned"));
+        assert!(is_ours("nuri::pipeline::detect"));
+    }
+}
@@ -1,132 +1,1735 @@
-use anyhow::{bail, Result};
+use std::io::{self, IsTerminal};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
 use clap::Parser;
 
-use nuri::backends::{get_backend, ghostty, Target, ThemeBackend};
-use nuri::cli::Args;
-use nuri::pipeline::assign::assign_slots;
-use nuri::pipeline::contrast::enforce_contrast;
+use nuri::backends::{
+    generated_name, ghostty, installed_by, is_generated, uninstall, Overwrite, Target,
+    ThemeBackend, CONFIG_DIR_ENV,
+};
+use nuri::bench::BenchOptions;
+use nuri::cli::{
+    Args, BatchArgs, BenchArgs, CacheAction, Cli, Command, ImportArgs, ManageArgs, ThemeMode,
+};
+use nuri::color::{Color, ColorBlindness};
+use nuri::config::{slot_color, Config};
+use nuri::error::NuriError;
+use nuri::naming::{self, NameTemplate, NameValues};
+use nuri::pipeline::assign::{assign_slots_with, AnsiPalette};
+use nuri::pipeline::colorblind::separate_accents;
+use nuri::pipeline::contrast::{enforce_contrast, DEFAULT_ACCENT_CONTRAST};
+use nuri::pipeline::crop::Region;
 use nuri::pipeline::detect::detect_mode;
-use nuri::pipeline::extract::{extract_colors, load_and_prepare};
-use nuri::{preview, tui};
+use nuri::pipeline::extract::{
+    check_colors, check_max_dim, decode_frames_with, prepare_frames, refine, sample_pixels,
+    ExtractedColor, LoadOptions, PreparedImage, ResizeFilter, Sampling, DEFAULT_COLORS,
+    DEFAULT_FRAMES, DEFAULT_MAX_DIM, DEFAULT_MERGE_DELTA_E, DEFAULT_SAMPLE_SIZE, DEFAULT_SEED,
+};
+use nuri::pipeline::profile::Profile;
+use nuri::pipeline::quantize::Algorithm;
+use nuri::pipeline::style::{apply_styles, PaletteStyle};
+use nuri::pipeline::weighting::{emphasize, restore_area, Weighting};
+use nuri::pipeline::PaletteOptions;
+use nuri::progress::Progress;
+use nuri::provenance::{self, Provenance, Stamped};
+use nuri::report::{Extraction, ModeSource, Report, Stages, ThemeReport, Written};
+use nuri::{
+    batch, bench, cache, card, check, fetch, html, import, live, logging, preview, shell, term,
+    tui, wallpaper, watch,
+};
 
 fn main() -> Result<()> {
-    let args = Args::parse();
+    let cli = Cli::parse();
+    if let Some(dir) = &cli.config_dir {
+        // Before any thread starts; the library reads it from the environment
+        std::env::set_var(CONFIG_DIR_ENV, dir);
+    }
+    term::set_color_choice(cli.color);
+    let Some(command) = cli.into_command() else {
+        // arg_required_else_help prints help before we get here
+        return Ok(());
+    };
+    let level = match &command {
+        Command::Generate(args) | Command::Install(args) | Command::Watch(args) => {
+            logging::level(args.verbose, args.quiet)
+        }
+        Command::Batch(batch) => logging::level(batch.args.verbose, batch.args.quiet),
+        _ => logging::level(0, false),
+    };
+    logging::init(level);
+    match command {
+        Command::Generate(args) => generate(args).map(drop),
+        Command::Install(mut args) => {
+            args.install = true;
+            generate(args).map(drop)
+        }
+        Command::Watch(args) => watch(args),
+        Command::Batch(args) => batch(args),
+        Command::List(manage) => list(&manage),
+        Command::Browse(manage) => browse(&manage),
+        Command::Remove {
+            name,
+            force,
+            manage,
+        } => remove(&name, force, &manage),
+        Command::Targets { config } => targets(config.as_deref()),
+        Command::Import(args) => import_theme(args),
+        Command::Regenerate {
+            theme,
+            output,
+            config,
+        } => regenerate(&theme, output, config),
+        Command::Edit {
+            theme,
+            target,
+            config,
+        } => edit(theme, target, config.as_deref()),
+        Command::Preview { theme, simulate } => preview_file(&theme, simulate),
+        Command::Try {
+            theme,
+            duration,
+            config,
+        } => try_theme(&theme, duration, config.as_deref()),
+        Command::Check { theme, config } => check(&theme, config.as_deref()),
+        Command::Diff { a, b, config } => diff(&a, &b, config.as_deref()),
+        Command::Cache {
+            action: CacheAction::Clear,
+        } => clear_cache(),
+        Command::Completions { shell } => shell::write_completions(shell, &mut io::stdout()),
+        Command::Manpage { out_dir } => manpage(out_dir.as_deref()),
+        Command::Bench(args) => bench(&args),
+    }
+}
+
+/// Run the pipeline on an image and emit, install or edit the theme; the
+/// names of the themes made.
+fn generate(args: Args) -> Result<Vec<String>> {
+    let settings = Settings::resolve(&args)?;
+    let progress = Progress::new(!args.quiet);
+    let extracted = extract(&args, &settings, &progress)?;
+    let names = ThemeNames::new(&args, &settings.config, &extracted);
+    let provenance = base_provenance(&args, &settings, &extracted, &names.generated_at);
+    let mut report = args.report.map(|_| Report {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        source: extracted.input.source.clone(),
+        extraction: Extraction {
+            algorithm: settings.algorithm,
+            seed: settings.seed,
+            k: settings.k,
+            sampling: settings.sampling,
+            weighting: settings.weighting,
+            frames: extracted.image.frames,
+            pixels: extracted.image.pixels.len(),
+            sampled: extracted.sampled,
+            clusters: extracted.colors.clone(),
+        },
+        themes: Vec::new(),
+    });
+
+    // --both: a dark and a light theme from the same extraction
+    if args.both {
+        progress.finish();
+        let mut themes = Vec::new();
+        for (mode, suffix) in [(ThemeMode::Dark, "-dark"), (ThemeMode::Light, "-light")] {
+            let name = names.paired(mode, suffix);
+            let theme = build_theme(
+                &args,
+                &settings,
+                &extracted,
+                (mode, ModeSource::Set),
+                name,
+                suffix,
+            );
+            emit(
+                &args,
+                &settings,
+                &extracted,
+                &provenance,
+                &theme,
+                report.as_mut(),
+            )?;
+            themes.push(theme.name);
+        }
+        if args.activate {
+            // Ghostty switches between the pair with the system appearance
+            let dark = ghostty::theme_ref(&themes[0], &settings.config)?;
+            let light = ghostty::theme_ref(&themes[1], &settings.config)?;
+            activate_ghostty(&format!("light:{light},dark:{dark}"))?;
+        }
+        if let Some(report) = &report {
+            print_report(report)?;
+        }
+        return Ok(themes);
+    }
+
+    // Detect dark/light mode (respect --mode override)
+    let (mode, mode_source) = match args.mode.or(settings.config.mode) {
+        Some(mode) => {
+            log::debug!("{mode:?} mode set by --mode or the config");
+            (mode, ModeSource::Set)
+        }
+        None => (detect_mode(&extracted.image.pixels), ModeSource::Detected),
+    };
+    progress.stage(4, "Assigning slots");
+    let name = names.name(mode);
+    let theme = build_theme(&args, &settings, &extracted, (mode, mode_source), name, "");
+    progress.finish();
+
+    if args.tui {
+        return run_tui(&args, settings, extracted, theme);
+    }
+    emit(
+        &args,
+        &settings,
+        &extracted,
+        &provenance,
+        &theme,
+        report.as_mut(),
+    )?;
+    if args.activate {
+        activate_ghostty(&ghostty::theme_ref(&theme.name, &theme.config)?)?;
+    }
+    if let Some(report) = &report {
+        print_report(report)?;
+    }
+    Ok(vec![theme.name])
+}
 
-    // Validate --min-contrast
-    let min_contrast = validate_min_contrast(args.min_contrast);
+/// Everything a run needs from the config file and the command line, with
+/// CLI flags winning over the config.
+struct Settings {
+    /// The config, with `--set`, `--template`, `--profile` and the other
+    /// flags the backends and slot assignment read folded in.
+    config: Config,
+    targets: Vec<Target>,
+    k: usize,
+    min_contrast: f32,
+    styles: Vec<PaletteStyle>,
+    colorblind_safe: bool,
+    profile: Profile,
+    load_options: LoadOptions,
+    /// `--frames`, `--frame-stride`, `--frame-interval`, `--max-dim` and
+    /// `--resize-filter` as given, for the provenance header.
+    frames: usize,
+    frame_stride: Option<usize>,
+    frame_interval: Option<f64>,
+    max_dim: Option<u32>,
+    resize_filter: Option<ResizeFilter>,
+    weighting: Weighting,
+    sampling: Sampling,
+    sample_size: usize,
+    seed: u64,
+    algorithm: Algorithm,
+    merge_delta_e: Option<f32>,
+    min_weight: Option<f32>,
+}
 
-    // 1. Load and prepare image pixels
-    let pixels = load_and_prepare(&args.image)?;
+impl Settings {
+    /// Load the config, apply `args` over it and reject flag combinations
+    /// that cannot work, before any image is read.
+    fn resolve(args: &Args) -> Result<Settings> {
+        let mut config = Config::load(args.config.as_deref())?;
+        // --set pins win over the config's [slots]
+        config.pins = args.set.clone();
+        if let Some(template) = &args.template {
+            // Absolute, so the provenance header can find it again
+            config.template = Some(template.canonicalize().unwrap_or_else(|_| template.clone()));
+        }
+        // Resolved before extraction, so a mistyped target fails fast
+        let (cli_targets, picked) = config.resolve_targets(&args.target)?;
+        if let Some(path) = picked {
+            if args.template.is_some() {
+                bail!(
+                    "--target already names a template; drop --template or use --target template"
+                );
+            }
+            config.template = Some(path);
+        }
+
+        // CLI flags take precedence over the config file; clap has already
+        // checked -k, so only the config's count can fail here
+        let k = check_colors(args.colors.or(config.colors).unwrap_or(DEFAULT_COLORS))
+            .context("invalid `colors` in the config file")?;
+        let max_memory_mib = args.max_memory.or(config.max_memory).unwrap_or(256);
+
+        // Validate --min-contrast
+        let min_contrast = validate_min_contrast(
+            args.min_contrast
+                .or(config.min_contrast)
+                .unwrap_or(DEFAULT_ACCENT_CONTRAST),
+        );
+        let styles = if args.style.is_empty() {
+            config.style.clone()
+        } else {
+            args.style.clone()
+        };
+        let colorblind_safe = args.colorblind_safe || config.colorblind_safe;
+        if let Some(profile) = &args.profile {
+            // Absolute when it names a file, so regenerate can find it again
+            let path = Path::new(profile);
+            config.profile = Some(match path.is_file() {
+                true => path
+                    .canonicalize()
+                    .map_or_else(|_| profile.clone(), |p| p.to_string_lossy().into_owned()),
+                false => profile.clone(),
+            });
+        }
+        config.min_separation = args.min_separation.or(config.min_separation);
+        config.bg_from = args.bg_from.or(config.bg_from);
+        config.bg_color = args.bg_color.or(config.bg_color);
+        config.bg_lightness = args.bg_lightness.or(config.bg_lightness);
+        config.fg_lightness = args.fg_lightness.or(config.fg_lightness);
+        config.contrast_envelope = args.contrast_envelope.or(config.contrast_envelope);
+        config.extended |= args.extended;
+        let profile = config.assignment_profile()?;
+
+        let frames = args.frames.or(config.frames).unwrap_or(DEFAULT_FRAMES);
+        let frame_stride = args.frame_stride.or(config.frame_stride);
+        let frame_interval = args.frame_interval.or(config.frame_interval);
+        let interval = match frame_interval {
+            Some(seconds) if seconds > 0.0 => match Duration::try_from_secs_f64(seconds) {
+                Ok(interval) => Some(interval),
+                Err(_) => bail!("--frame-interval {seconds} is too long"),
+            },
+            Some(_) => bail!("--frame-interval must be a positive number of seconds"),
+            None => None,
+        };
+        let max_dim = args
+            .max_dim
+            .or(config.max_dim)
+            .map(check_max_dim)
+            .transpose()?;
+        let resize_filter = args.resize_filter.or(config.resize_filter);
+        let load_options = LoadOptions {
+            max_memory: max_memory_mib.saturating_mul(1024 * 1024),
+            frames,
+            frame_stride,
+            frame_interval: interval,
+            region: args
+                .crop
+                .map(Region::Crop)
+                .or(args.focus.map(Region::Focus))
+                .or_else(|| config.region()),
+            max_dim: max_dim.unwrap_or(DEFAULT_MAX_DIM),
+            resize_filter: resize_filter.unwrap_or_default(),
+        };
+
+        let merge_delta_e = args.merge_delta_e.or(config.merge_delta_e);
+        if merge_delta_e.is_some_and(|de| !(de.is_finite() && de >= 0.0)) {
+            bail!("--merge-delta-e must be a delta-E of 0 or more");
+        }
+        let min_weight = args.min_weight.or(config.min_weight);
+        if min_weight.is_some_and(|percent| !(0.0..100.0).contains(&percent)) {
+            bail!("--min-weight must be a percentage from 0 up to 100");
+        }
+
+        // --target wins over the config's default targets
+        let targets = if !cli_targets.is_empty() {
+            cli_targets
+        } else if args.template.is_some() {
+            vec![Target::Template]
+        } else {
+            config.targets.clone()
+        };
+        if args.template.is_some() && !targets.contains(&Target::Template) {
+            bail!("--template only applies to the template target; add it to --target");
+        }
+
+        let to_stdout = !args.install && args.output.is_none() && args.out_dir.is_none();
+        if args.report.is_some() && to_stdout {
+            bail!("--report prints to stdout, where the theme would go; use it with --output, --out-dir or --install");
+        }
+        if args.both && to_stdout {
+            bail!("--both writes two themes; use it with --install, --output or --out-dir");
+        }
+        if args.activate {
+            if !args.install {
+                bail!("--activate sets an installed theme; use it with --install");
+            }
+            if !targets.is_empty() && !targets.contains(&Target::Ghostty) {
+                bail!("--activate only applies to the ghostty target");
+            }
+        }
+
+        Ok(Settings {
+            weighting: args.weighting.or(config.weighting).unwrap_or_default(),
+            sampling: args.sampling.or(config.sampling).unwrap_or_default(),
+            sample_size: args
+                .sample_size
+                .or(config.sample_size)
+                .unwrap_or(DEFAULT_SAMPLE_SIZE),
+            seed: args.seed.unwrap_or(DEFAULT_SEED),
+            algorithm: args.algorithm.or(config.algorithm).unwrap_or_default(),
+            config,
+            targets,
+            k,
+            min_contrast,
+            styles,
+            colorblind_safe,
+            profile,
+            load_options,
+            frames,
+            frame_stride,
+            frame_interval,
+            max_dim,
+            resize_filter,
+            merge_delta_e,
+            min_weight,
+        })
+    }
+
+    /// `--merge-delta-e` and `--min-weight` as passed to [`refine`], the
+    /// weight as a fraction.
+    fn refinement(&self) -> (f32, f32) {
+        (
+            self.merge_delta_e.unwrap_or(DEFAULT_MERGE_DELTA_E),
+            self.min_weight.unwrap_or(0.0) / 100.0,
+        )
+    }
+}
+
+/// The image a run read and the colors extracted from it.
+struct Extracted {
+    input: InputImage,
+    image: PreparedImage,
+    /// Refined clusters, weighted by the share of the image they cover.
+    colors: Vec<ExtractedColor>,
+    /// Pixels clustered after sampling.
+    sampled: usize,
+    source_hash: String,
+    cache_key: Option<cache::Key>,
+}
+
+/// Load the image and extract its dominant colors via K-means, on a
+/// weighted sample if requested.
+fn extract(args: &Args, settings: &Settings, progress: &Progress) -> Result<Extracted> {
+    let input = input_image(args)?;
+    progress.stage(1, "Loading image");
+    let decoded = decode_frames_with(&input.path, &settings.load_options)?;
+    progress.stage(2, "Converting to Lab");
+    let image = prepare_frames(&decoded);
+    let pixels = &image.pixels;
 
     // Warn on tiny images
     if pixels.len() < 16 {
-        eprintln!(
-            "warning: very small image ({} pixels). Theme quality may be limited.",
+        log::warn!(
+            "very small image ({} pixels). Theme quality may be limited.",
             pixels.len()
         );
     }
 
-    // 2. Extract dominant colors via K-means
-    let colors = extract_colors(&pixels, args.colors);
+    let Settings {
+        k,
+        seed,
+        algorithm,
+        sampling,
+        sample_size,
+        weighting,
+        ..
+    } = *settings;
+    let weighted = emphasize(&image, weighting);
+    let sample = sample_pixels(&weighted, sampling, sample_size, seed);
+    progress.stage(3, format!("Clustering {} pixels (k={k})", sample.len()));
+    log::debug!(
+        "clustering {} of {} pixels with {algorithm:?} (k={k}, seed {seed}, {sampling:?} sampling, {weighting:?} weighting)",
+        sample.len(),
+        pixels.len()
+    );
+    let source_hash = provenance::hash_file(&input.path)?;
+    let load_options = &settings.load_options;
+    let cache_key = (!args.no_cache).then(|| cache::Key {
+        image_hash: source_hash.clone(),
+        max_dim: load_options.max_dim,
+        resize_filter: load_options.resize_filter,
+        max_memory: load_options.max_memory,
+        frames: image.frames,
+        frame_stride: settings.frame_stride.filter(|_| image.frames > 1),
+        frame_interval: load_options.frame_interval.filter(|_| image.frames > 1),
+        region: load_options.region,
+        algorithm,
+        sampling,
+        sample_size,
+        weighting,
+        k,
+        seed,
+    });
+    let mut colors = cache::cached(cache_key.as_ref(), || {
+        algorithm.quantizer().quantize(&sample, k, seed)
+    });
+    // Weighted clustering skews weights; report each color's true share
+    if weighting != Weighting::Area {
+        colors = restore_area(colors, pixels);
+    }
+    let (merge_delta_e, min_weight) = settings.refinement();
+    let colors = refine(colors, merge_delta_e, min_weight);
+    log::debug!("extracted {} colors", colors.len());
+    for color in &colors {
+        log::trace!("  {} {:.1}%", color.color.to_hex(), color.weight * 100.0);
+    }
 
     // Warn on few extracted colors
     if colors.len() < 6 {
-        eprintln!(
-            "warning: only {} distinct colors extracted (expected ≥ 6). \
+        log::warn!(
+            "only {} distinct colors extracted (expected ≥ 6). \
              Some palette slots will be synthesized.",
             colors.len()
         );
     }
 
-    // 3. Detect dark/light mode (respect --mode override)
-    let mode = args.mode.unwrap_or_else(|| detect_mode(&pixels));
+    Ok(Extracted {
+        sampled: sample.len(),
+        input,
+        image,
+        colors,
+        source_hash,
+        cache_key,
+    })
+}
 
-    // 4. Assign colors to ANSI palette slots
-    let mut palette = assign_slots(&colors, mode);
+/// Theme names: `--name` as given, or `--name-template` filled in per mode
+/// once it's known.
+struct ThemeNames {
+    explicit: Option<String>,
+    template: NameTemplate,
+    stem: String,
+    hash: String,
+    /// When the run started, also recorded in the provenance header.
+    generated_at: String,
+}
 
-    // 5. Enforce WCAG contrast minimums
-    enforce_contrast(&mut palette, min_contrast);
+impl ThemeNames {
+    fn new(args: &Args, config: &Config, extracted: &Extracted) -> ThemeNames {
+        ThemeNames {
+            explicit: args.name.as_deref().map(naming::sanitize_logged),
+            template: args
+                .name_template
+                .clone()
+                .or_else(|| config.name_template.clone())
+                .unwrap_or_default(),
+            stem: image_stem(&extracted.input.path),
+            hash: extracted.source_hash.clone(),
+            generated_at: provenance::timestamp_now(),
+        }
+    }
 
-    // 6. Derive theme name
-    let name = args.name.unwrap_or_else(|| default_theme_name(&args.image));
+    /// The name of the theme made in `mode`.
+    fn name(&self, mode: ThemeMode) -> String {
+        match &self.explicit {
+            Some(name) => name.clone(),
+            None => self.template.render(&NameValues {
+                stem: &self.stem,
+                mode,
+                date: &self.generated_at[..10],
+                hash: &self.hash,
+            }),
+        }
+    }
 
-    // 7. TUI mode: launch interactive editor
-    if args.tui {
-        let targets = args.target.clone();
-        let mut tui_app =
-            tui::TuiApp::new(palette, colors, args.image, mode, name, pixels, args.colors);
-        tui_app.set_targets(targets);
-        return tui::run(tui_app);
+    /// The name of the `mode` half of a `--both` pair, ending in `suffix`.
+    fn paired(&self, mode: ThemeMode, suffix: &str) -> String {
+        // A template with {mode} already tells the pair apart
+        match self.explicit.is_none() && self.template.uses_mode() {
+            true => self.name(mode),
+            false => format!("{}{suffix}", self.name(mode)),
+        }
+    }
+}
+
+/// Header recording how the theme was made; target, name and mode are
+/// filled in per written theme.
+fn base_provenance(
+    args: &Args,
+    settings: &Settings,
+    extracted: &Extracted,
+    generated_at: &str,
+) -> Provenance {
+    let animated = extracted.image.frames > 1;
+    let config = &settings.config;
+    Provenance {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        source: extracted.input.source.clone(),
+        source_hash: extracted.source_hash.clone(),
+        name: String::new(),
+        target: Target::Ghostty,
+        mode: ThemeMode::Dark,
+        algorithm: settings.algorithm,
+        seed: settings.seed,
+        colors: settings.k,
+        min_contrast: settings.min_contrast,
+        sampling: settings.sampling,
+        sample_size: settings.sample_size,
+        weighting: settings.weighting,
+        max_dim: settings.max_dim,
+        resize_filter: settings.resize_filter,
+        frames: animated.then_some(settings.frames),
+        frame_stride: settings.frame_stride.filter(|_| animated),
+        frame_interval: settings.frame_interval.filter(|_| animated),
+        region: settings.load_options.region,
+        merge_delta_e: settings.merge_delta_e,
+        min_weight: settings.min_weight,
+        styles: settings.styles.clone(),
+        colorblind_safe: settings.colorblind_safe,
+        profile: config.profile.clone(),
+        min_separation: config.min_separation,
+        bg_from: config.bg_from,
+        bg_color: config.bg_color,
+        bg_lightness: config.bg_lightness,
+        fg_lightness: config.fg_lightness,
+        contrast_envelope: config.contrast_envelope,
+        extended: config.extended,
+        named_colors: args.verbose > 0,
+        locks: args.lock.clone(),
+        template: None,
+        pins: args.set.clone(),
+        generated_at: generated_at.to_string(),
     }
+}
 
-    // 8. CLI mode: build theme and output
-    // Default to Ghostty when no --target specified in CLI mode
-    let targets = if args.target.is_empty() {
-        vec![Target::Ghostty]
-    } else {
-        args.target.clone()
-    };
-    let backends: Vec<Box<dyn ThemeBackend>> = targets.iter().map(|t| get_backend(*t)).collect();
+/// A palette built for one theme of the run.
+struct Theme {
+    name: String,
+    mode: ThemeMode,
+    mode_source: ModeSource,
+    /// Added to `--output`, `--export-card` and `--export-html` paths, so
+    /// the two themes of `--both` don't overwrite each other.
+    suffix: &'static str,
+    /// The run's config with the `--lock` pins for this theme.
+    config: Config,
+    palette: AnsiPalette,
+    stages: Stages,
+}
+
+impl Theme {
+    /// `path` with the theme's suffix.
+    fn path(&self, path: &Path) -> PathBuf {
+        theme_path(path, self.suffix)
+    }
+}
+
+/// `path` with `suffix` inserted, or as given when there is none.
+fn theme_path(path: &Path, suffix: &str) -> PathBuf {
+    match suffix {
+        "" => path.to_path_buf(),
+        suffix => suffixed_path(path, suffix),
+    }
+}
+
+/// Assign slots and enforce contrast for the theme called `name`.
+fn build_theme(
+    args: &Args,
+    settings: &Settings,
+    extracted: &Extracted,
+    (mode, mode_source): (ThemeMode, ModeSource),
+    name: String,
+    suffix: &'static str,
+) -> Theme {
+    let output = args.output.as_deref().map(|path| theme_path(path, suffix));
+    let config = lock_colors(
+        &settings.config,
+        args,
+        &settings.targets,
+        &name,
+        output.as_deref(),
+    );
+    let (palette, stages) = build_palette(
+        &extracted.colors,
+        mode,
+        &settings.styles,
+        &settings.profile,
+        settings.min_contrast,
+        settings.colorblind_safe,
+        &config,
+    );
+    Theme {
+        name,
+        mode,
+        mode_source,
+        suffix,
+        config,
+        palette,
+        stages,
+    }
+}
 
+/// Preview, push live, export and write `theme`, adding it to `report`.
+fn emit(
+    args: &Args,
+    settings: &Settings,
+    extracted: &Extracted,
+    provenance: &Provenance,
+    theme: &Theme,
+    report: Option<&mut Report>,
+) -> Result<()> {
+    let provenance = Provenance {
+        name: theme.name.clone(),
+        mode: theme.mode,
+        ..provenance.clone()
+    };
     if args.preview {
-        preview::print_preview(&palette);
+        print_preview(&theme.palette, args.simulate);
+    }
+    if args.preview_full {
+        let app = preview_app(
+            &theme.palette,
+            &extracted.colors,
+            &provenance,
+            theme.mode,
+            args,
+        );
+        print_preview_full(&app)?;
+    }
+    if args.verbose > 0 {
+        preview::print_named(&theme.palette, term::stderr_colored());
+    }
+    if args.apply_live {
+        apply_live(&theme.palette)?;
+    }
+
+    let mut written = Vec::new();
+    if let Some(path) = &args.export_card {
+        let path = theme.path(path);
+        export_card(&path, &theme.palette, &theme.name, &extracted.image)?;
+        written.push(Written::Card { path });
+    }
+    if let Some(path) = &args.export_html {
+        let path = theme.path(path);
+        export_html(&path, &theme.palette, &theme.name, &extracted.image)?;
+        written.push(Written::Html { path });
+    }
+    let output = Output {
+        targets: settings.targets.clone(),
+        install: args.install,
+        overwrite: overwrite_policy(args.no_clobber, args.force, &theme.config),
+        path: args.output.as_deref().map(|path| theme.path(path)),
+        dir: args.out_dir.clone(),
+        provenance: Some(provenance),
+    };
+    written.extend(emit_theme(
+        &theme.palette,
+        &theme.name,
+        &output,
+        &theme.config,
+    )?);
+
+    if let Some(report) = report {
+        let mut theme_report = ThemeReport::new(
+            &theme.name,
+            theme.mode,
+            theme.mode_source,
+            &extracted.colors,
+            &settings.profile,
+            &theme.stages,
+            &theme.palette,
+        );
+        theme_report.outputs = written;
+        report.themes.push(theme_report);
     }
+    Ok(())
+}
+
+/// Open `theme` in the interactive editor.
+fn run_tui(
+    args: &Args,
+    settings: Settings,
+    extracted: Extracted,
+    theme: Theme,
+) -> Result<Vec<String>> {
+    let pixels = emphasize(&extracted.image, settings.weighting).into_owned();
+    let (merge_delta_e, min_weight) = settings.refinement();
+    let mut tui_app = tui::TuiApp::new(
+        theme.palette,
+        extracted.colors,
+        extracted.input.source.clone(),
+        theme.mode,
+        theme.name.clone(),
+        pixels,
+        settings.k,
+    );
+    tui_app.set_targets(settings.targets);
+    tui_app.set_config(theme.config);
+    tui_app.set_live(args.apply_live);
+    tui_app.set_seed(settings.seed);
+    tui_app.set_sampling(settings.sampling, settings.sample_size);
+    tui_app.set_algorithm(settings.algorithm);
+    tui_app.set_refinement(merge_delta_e, min_weight);
+    tui_app.set_simulation(args.simulate);
+    tui_app.set_styles(settings.styles);
+    tui_app.set_colorblind_safe(settings.colorblind_safe);
+    tui_app.set_profile(settings.profile);
+    tui_app.set_min_contrast(settings.min_contrast);
+    tui_app.set_locked(&args.lock);
+    tui_app.set_cache(extracted.cache_key);
+    if let Some(theme) = &args.compare {
+        tui_app.compare_with(theme)?;
+    }
+    tui::run(tui_app)?;
+    Ok(vec![theme.name])
+}
+
+/// Print the `--report` JSON on stdout.
+fn print_report(report: &Report) -> Result<()> {
+    println!("{}", serde_json::to_string_pretty(report)?);
+    Ok(())
+}
+
+/// An image to run the pipeline on.
+struct InputImage {
+    /// Local file to read.
+    path: PathBuf,
+    /// Where the image came from (absolute path or URL), for the theme header.
+    source: PathBuf,
+    /// Keeps a downloaded or piped image's temporary file alive.
+    _temp: Option<fetch::TempImage>,
+}
 
-    if args.install {
-        // Check --no-clobber for Ghostty targets
-        if args.no_clobber && targets.contains(&Target::Ghostty) {
-            let theme_path = ghostty::theme_path(&name)?;
-            if theme_path.exists() {
+/// The image to read: the path or URL given, `-` for stdin, or the desktop's
+/// current wallpaper.
+fn input_image(args: &Args) -> Result<InputImage> {
+    let path = match &args.image {
+        Some(input) if fetch::is_url(input) || fetch::is_stdin(input) => {
+            let temp = if fetch::is_stdin(input) {
+                fetch::read_stdin(fetch::MAX_DOWNLOAD_BYTES)?
+            } else {
+                fetch::download(&input.to_string_lossy(), fetch::MAX_DOWNLOAD_BYTES)?
+            };
+            return Ok(InputImage {
+                path: temp.path.clone(),
+                source: input.clone(),
+                _temp: Some(temp),
+            });
+        }
+        Some(path) => path.clone(),
+        None if args.from_wallpaper => {
+            let path = wallpaper::current()?;
+            log::info!("Using wallpaper {}", path.display());
+            path
+        }
+        None => bail!("no image given"),
+    };
+    Ok(InputImage {
+        source: path.canonicalize().unwrap_or_else(|_| path.clone()),
+        path,
+        _temp: None,
+    })
+}
+
+/// Regenerate and install the theme each time the watched wallpaper changes.
+fn watch(args: Args) -> Result<()> {
+    if args.tui || args.output.is_some() {
+        bail!("watch installs themes; --tui and --output are not supported");
+    }
+    if args
+        .image
+        .as_deref()
+        .is_some_and(|input| fetch::is_url(input) || fetch::is_stdin(input))
+    {
+        bail!("watch needs a local file or directory, not a URL or stdin");
+    }
+    let watched = input_image(&args)?.path;
+    log::info!("Watching {} (Ctrl-C to stop)", watched.display());
+    watch::watch(&watched, |image| {
+        log::info!("Regenerating from {}", image.display());
+        let run = Args {
+            image: Some(image.to_path_buf()),
+            from_wallpaper: false,
+            install: true,
+            ..args.clone()
+        };
+        // Keep watching through unreadable or half-written images
+        if let Err(e) = generate(run) {
+            log::error!("{e:#}");
+        }
+    })
+}
+
+/// Generate a theme for every image in a directory, several at a time, then
+/// print a summary. Fails if any image did.
+fn batch(batch: BatchArgs) -> Result<()> {
+    let args = batch.args;
+    let Some(dir) = args.image.clone().filter(|dir| dir.is_dir()) else {
+        bail!("batch needs a directory of images");
+    };
+    if args.out_dir.is_none() && !args.install {
+        bail!("batch writes a theme per image; use it with --out-dir or --install");
+    }
+    let single = [
+        (args.name.is_some(), "--name"),
+        (args.tui, "--tui"),
+        (args.preview, "--preview"),
+        (args.preview_full, "--preview-full"),
+        (args.apply_live, "--apply-live"),
+        (args.activate, "--activate"),
+        (args.report.is_some(), "--report"),
+        (args.export_card.is_some(), "--export-card"),
+        (args.export_html.is_some(), "--export-html"),
+    ];
+    if let Some((_, flag)) = single.iter().find(|(set, _)| *set) {
+        bail!("{flag} applies to a single theme and is not supported by batch");
+    }
+
+    let images = batch::find_images(&dir, batch.recursive, batch.glob.as_deref())?;
+    if images.is_empty() {
+        bail!("no images found in {}", dir.display());
+    }
+    // Themes are named after file stems, which can repeat across directories
+    let name_template = match &args.name_template {
+        Some(template) => template.clone(),
+        None => Config::load(args.config.as_deref())?
+            .name_template
+            .unwrap_or_default(),
+    };
+    if !name_template.identifies_image() {
+        bail!(
+            "name template '{name_template}' would give every image the same name; \
+             add {{stem}} or {{hash}}"
+        );
+    }
+    if !name_template.uses_hash() {
+        let mut seen = std::collections::HashMap::new();
+        for image in &images {
+            let name = batch_theme_name(&name_template, image);
+            if let Some(other) = seen.insert(name.clone(), image) {
                 bail!(
-                    "theme '{}' already exists at {}. Remove it first or omit --no-clobber.",
-                    name,
-                    theme_path.display()
+                    "{} and {} would both be named '{name}'; narrow the batch with --glob",
+                    other.display(),
+                    image.display(),
                 );
             }
         }
-        for backend in &backends {
-            let installed_path = backend.install(&palette, &name)?;
-            eprintln!(
+    }
+
+    let jobs = batch
+        .jobs
+        .unwrap_or_else(|| std::thread::available_parallelism().map_or(1, |n| n.get()));
+    log::info!("Processing {} images, {jobs} at a time", images.len());
+    let outcomes = batch::run_parallel(&images, jobs, |image| {
+        let run = Args {
+            image: Some(image.clone()),
+            // One progress line per thread would fight over the terminal
+            quiet: true,
+            ..args.clone()
+        };
+        let (theme, error) = match generate(run) {
+            Ok(names) => (names.join(", "), None),
+            Err(e) => (
+                batch_theme_name(&name_template, image),
+                Some(format!("{e:#}")),
+            ),
+        };
+        batch::Outcome {
+            image: image.clone(),
+            theme,
+            error,
+        }
+    });
+    print!("{}", batch::summary(&outcomes, &dir));
+
+    let failed = outcomes.iter().filter(|o| o.error.is_some()).count();
+    if failed > 0 {
+        bail!("{failed} of {} images failed", outcomes.len());
+    }
+    Ok(())
+}
+
+/// The name `template` gives `image` in a batch, as far as it can be known
+/// before the image is read: dark mode and no hash.
+fn batch_theme_name(template: &NameTemplate, image: &Path) -> String {
+    template.render(&NameValues {
+        stem: &image_stem(image),
+        mode: ThemeMode::Dark,
+        date: &provenance::timestamp_now()[..10],
+        hash: "",
+    })
+}
+
+/// Print the man page, or write every page into `out_dir`.
+fn manpage(out_dir: Option<&Path>) -> Result<()> {
+    let Some(dir) = out_dir else {
+        return shell::write_manpage(&mut io::stdout());
+    };
+    let written = shell::write_manpages(dir)?;
+    log::info!("Wrote {} man pages to {}", written.len(), dir.display());
+    Ok(())
+}
+
+/// Empty the extracted-colors cache.
+fn clear_cache() -> Result<()> {
+    let removed = cache::clear()?;
+    log::info!(
+        "Removed {removed} cached extractions from {}",
+        cache::dir().display()
+    );
+    Ok(())
+}
+
+/// Time each pipeline stage on an image and print the table.
+fn bench(args: &BenchArgs) -> Result<()> {
+    if args.max_dim == Some(0) {
+        bail!("--max-dim must be at least 1 pixel");
+    }
+    let options = BenchOptions {
+        load: LoadOptions {
+            max_dim: args.max_dim.unwrap_or(DEFAULT_MAX_DIM),
+            resize_filter: args.resize_filter,
+            ..LoadOptions::default()
+        },
+        algorithm: args.algorithm,
+        k: args.colors,
+        sampling: args.sampling,
+        sample_size: DEFAULT_SAMPLE_SIZE,
+        weighting: args.weighting,
+        seed: DEFAULT_SEED,
+    };
+    let bench = bench::run(&args.image, &options, args.runs)?;
+    print!("{}", bench.table());
+    Ok(())
+}
+
+/// Point Ghostty's config at `theme` and ask running instances to reload.
+fn activate_ghostty(theme: &str) -> Result<()> {
+    let path = ghostty::config_path();
+    let backup = ghostty::activate(&path, theme)?;
+    log::info!("Set theme = {theme} in {}", path.display());
+    if let Some(backup) = backup {
+        log::info!("Previous config saved to {}", backup.display());
+    }
+    if ghostty::reload() {
+        log::info!("Reloaded Ghostty");
+    } else {
+        log::info!("Reload Ghostty's config (or restart it) to see the new theme");
+    }
+    Ok(())
+}
+
+/// Assign ANSI slots, apply styles, enforce WCAG contrast minimums, separate
+/// confusable accents if asked, then apply the config's exact `[slots]`
+/// colors, which win over generated ones.
+fn build_palette(
+    colors: &[ExtractedColor],
+    mode: ThemeMode,
+    styles: &[PaletteStyle],
+    profile: &Profile,
+    min_contrast: f32,
+    colorblind_safe: bool,
+    config: &Config,
+) -> (AnsiPalette, Stages) {
+    let mut palette = assign_slots_with(colors, mode, profile);
+    apply_styles(&mut palette, styles, colors);
+    let assigned = palette.clone();
+    enforce_contrast(&mut palette, min_contrast);
+    let contrast = palette.clone();
+    if colorblind_safe {
+        separate_accents(&mut palette, min_contrast);
+    }
+    let stages = Stages {
+        assigned,
+        contrast,
+        colorblind: palette.clone(),
+    };
+    config.apply_slot_overrides(&mut palette);
+    (palette, stages)
+}
+
+/// `config` with pins keeping the `--lock` colors of the theme this run
+/// replaces: the `output` file, else the first installed theme nuri can read
+/// back. `--set` pins still win. Nothing is kept on a first run.
+fn lock_colors(
+    config: &Config,
+    args: &Args,
+    targets: &[Target],
+    name: &str,
+    output: Option<&Path>,
+) -> Config {
+    let mut config = config.clone();
+    if args.lock.is_empty() {
+        return config;
+    }
+    let replaced = match args.lock_from.as_deref().or(output) {
+        Some(path) => Some(path.to_path_buf()),
+        None if args.install => {
+            let targets = if targets.is_empty() {
+                &[Target::Ghostty][..]
+            } else {
+                targets
+            };
+            targets
+                .iter()
+                .filter_map(|&target| config.install_path(target, name).ok())
+                .find(|path| import::is_theme_file(path) && path.exists())
+        }
+        None => None,
+    };
+    let Some(path) = replaced.filter(|path| path.exists()) else {
+        return config;
+    };
+    let old = match import::load_palette(&path) {
+        Ok(palette) => palette,
+        Err(e) => {
+            log::warn!("--lock: cannot read {}: {e:#}", path.display());
+            return config;
+        }
+    };
+    let locked = args
+        .lock
+        .iter()
+        .filter_map(|slot| Some((slot.clone(), slot_color(&old, slot)?)));
+    config.pins = locked.chain(args.set.iter().cloned()).collect();
+    config
+}
+
+/// Insert `suffix` before the extension: `theme.toml` -> `theme-dark.toml`.
+fn suffixed_path(path: &Path, suffix: &str) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let file_name = match path.extension() {
+        Some(ext) => format!("{stem}{suffix}.{}", ext.to_string_lossy()),
+        None => format!("{stem}{suffix}"),
+    };
+    path.with_file_name(file_name)
+}
+
+/// Write a palette card for `palette`, with the wallpaper it came from.
+fn export_card(
+    path: &Path,
+    palette: &AnsiPalette,
+    name: &str,
+    image: &PreparedImage,
+) -> Result<()> {
+    card::export(path, palette, name, Some(image))?;
+    log::info!("Wrote palette card to {}", path.display());
+    Ok(())
+}
+
+/// Write an HTML preview page for `palette`, with the wallpaper it came from.
+fn export_html(
+    path: &Path,
+    palette: &AnsiPalette,
+    name: &str,
+    image: &PreparedImage,
+) -> Result<()> {
+    html::export(path, palette, name, Some(image))?;
+    log::info!("Wrote HTML preview to {}", path.display());
+    Ok(())
+}
+
+/// Where a finished palette should go.
+struct Output {
+    targets: Vec<Target>,
+    install: bool,
+    overwrite: Overwrite,
+    path: Option<PathBuf>,
+    /// Directory to write each target's theme into.
+    dir: Option<PathBuf>,
+    /// Header written into each theme, when generated from an image.
+    provenance: Option<Provenance>,
+}
+
+/// Resolve `--no-clobber`/`--force` against the config's `no-clobber` default.
+fn overwrite_policy(no_clobber: bool, force: bool, config: &Config) -> Overwrite {
+    if no_clobber || (config.no_clobber && !force) {
+        Overwrite::Refuse
+    } else {
+        Overwrite::Allow
+    }
+}
+
+/// Print, write or install `palette` for each requested target.
+fn emit_theme(
+    palette: &AnsiPalette,
+    name: &str,
+    output: &Output,
+    config: &Config,
+) -> Result<Vec<Written>> {
+    // Default to Ghostty when no target is given on the CLI or in the config
+    let targets = if output.targets.is_empty() {
+        vec![Target::Ghostty]
+    } else {
+        output.targets.clone()
+    };
+    let backends: Vec<Box<dyn ThemeBackend>> = targets
+        .iter()
+        .map(|&target| -> Result<Box<dyn ThemeBackend>> {
+            let backend = config.backend(target)?;
+            Ok(match &output.provenance {
+                Some(provenance) => Box::new(Stamped::wrap(
+                    backend,
+                    Provenance {
+                        target,
+                        name: name.to_string(),
+                        // Only the template target reads the template
+                        template: config
+                            .template
+                            .clone()
+                            .filter(|_| target == Target::Template),
+                        ..provenance.clone()
+                    },
+                )),
+                None => backend,
+            })
+        })
+        .collect::<Result<_>>()?;
+
+    if output.install {
+        let paths = targets
+            .iter()
+            .map(|t| config.install_path(*t, name))
+            .collect::<Result<Vec<_>>>()?;
+        // Check every destination first so a refusal leaves nothing half-installed
+        if output.overwrite == Overwrite::Refuse {
+            if let Some(existing) = paths.iter().find(|p| p.exists()) {
+                return Err(NuriError::ThemeExists {
+                    path: existing.clone(),
+                }
+                .into());
+            }
+        }
+        let mut written = Vec::new();
+        for ((backend, path), &target) in backends.iter().zip(paths).zip(&targets) {
+            backend.install_at_with(palette, name, &path, output.overwrite)?;
+            log::info!(
                 "Installed {} theme '{name}' to {}",
                 backend.name(),
-                installed_path.display()
+                path.display()
             );
+            written.push(Written::theme(target, path));
         }
-    } else if let Some(ref path) = args.output {
+        Ok(written)
+    } else if let Some(ref path) = output.path {
         if backends.len() > 1 {
             bail!("cannot use --output with multiple targets; use --install instead");
         }
-        backends[0].write_to(&palette, &name, path)?;
-        eprintln!("Wrote theme to {}", path.display());
+        backends[0].write_to_with(palette, name, path, output.overwrite)?;
+        log::info!("Wrote theme to {}", path.display());
+        Ok(vec![Written::theme(targets[0], path.clone())])
+    } else if let Some(ref dir) = output.dir {
+        // A subdirectory per target: formats share extensions or have none,
+        // and companions like pywal's colors.sh would clash
+        let paths: Vec<PathBuf> = backends
+            .iter()
+            .zip(&targets)
+            .map(|(backend, target)| {
+                dir.join(target.key())
+                    .join(format!("{name}{}", backend.extension()))
+            })
+            .collect();
+        for (i, path) in paths.iter().enumerate() {
+            if paths[..i].contains(path) {
+                bail!(
+                    "--out-dir: {} is listed twice and would write {} twice",
+                    targets[i].key(),
+                    path.display()
+                );
+            }
+        }
+        if output.overwrite == Overwrite::Refuse {
+            if let Some(existing) = paths.iter().find(|p| p.exists()) {
+                return Err(NuriError::ThemeExists {
+                    path: existing.clone(),
+                }
+                .into());
+            }
+        }
+        let mut written = Vec::new();
+        for ((backend, path), &target) in backends.iter().zip(paths).zip(&targets) {
+            backend.install_at_with(palette, name, &path, output.overwrite)?;
+            log::info!("Wrote {} theme to {}", backend.name(), path.display());
+            written.push(Written::theme(target, path));
+        }
+        Ok(written)
     } else {
         if backends.len() > 1 {
             bail!(
                 "cannot output multiple targets to stdout; use --install or specify a single --target"
             );
         }
-        print!("{}", backends[0].serialize(&palette, &name));
+        print!("{}", backends[0].serialize(palette, name));
+        Ok(Vec::new())
     }
+}
 
+/// Print every target name, then the user templates usable with --template.
+fn targets(config: Option<&Path>) -> Result<()> {
+    let config = Config::load(config)?;
+    let registry = config.registry();
+    for &target in Target::all() {
+        println!("{:<18} {}", target.key(), target.name());
+    }
+    let dir = Config::templates_dir();
+    let templates: Vec<_> = registry.templates().collect();
+    if templates.is_empty() {
+        println!("\nNo templates in {}", dir.display());
+    } else {
+        println!(
+            "\nTemplates in {} (use with --target NAME or --template NAME):",
+            dir.display()
+        );
+        for (name, path) in templates {
+            let file = path.file_name().unwrap_or_default().to_string_lossy();
+            println!("  {name:<16} {file}");
+        }
+    }
     Ok(())
 }
 
+/// Convert an existing theme file to other targets.
+fn import_theme(args: ImportArgs) -> Result<()> {
+    let mut config = Config::load(args.config.as_deref())?;
+    let (targets, picked) = config.resolve_targets(&args.target)?;
+    if picked.is_some() {
+        config.template = picked;
+    }
+    let palette = import::load_palette(&args.theme)?;
+    let name = match &args.name {
+        Some(name) => naming::sanitize_logged(name),
+        None => default_theme_name(&args.theme),
+    };
+    let output = Output {
+        targets: if targets.is_empty() {
+            config.targets.clone()
+        } else {
+            targets
+        },
+        install: args.install,
+        overwrite: overwrite_policy(args.no_clobber, args.force, &config),
+        path: args.output,
+        dir: None,
+        provenance: None,
+    };
+    emit_theme(&palette, &name, &output, &config)?;
+    Ok(())
+}
+
+/// Re-run the pipeline for a theme using the provenance header in it,
+/// replacing the theme (or writing to `output`).
+fn regenerate(theme: &Path, output: Option<PathBuf>, config: Option<PathBuf>) -> Result<()> {
+    let text = std::fs::read_to_string(theme)
+        .with_context(|| format!("failed to read theme: {}", theme.display()))?;
+    let recorded = Provenance::parse(&text)
+        .with_context(|| format!("cannot regenerate {}", theme.display()))?;
+    if fetch::is_stdin(&recorded.source) {
+        bail!(
+            "{} was generated from stdin; pipe the image to `nuri -` again",
+            theme.display()
+        );
+    }
+    // URL sources are downloaded again by generate; only local files are checked
+    if !fetch::is_url(&recorded.source) {
+        if !recorded.source.is_file() {
+            bail!(
+                "source image {} no longer exists",
+                recorded.source.display()
+            );
+        }
+        if provenance::hash_file(&recorded.source)? != recorded.source_hash {
+            log::warn!(
+                "{} has changed since the theme was generated",
+                recorded.source.display()
+            );
+        }
+    }
+    let in_place = output.is_none();
+    generate(Args {
+        image: Some(recorded.source),
+        name: Some(recorded.name),
+        mode: Some(recorded.mode),
+        set: recorded.pins,
+        output: Some(output.unwrap_or_else(|| theme.to_path_buf())),
+        target: vec![recorded.target.key()],
+        algorithm: Some(recorded.algorithm),
+        colors: Some(recorded.colors),
+        min_contrast: Some(recorded.min_contrast),
+        seed: Some(recorded.seed),
+        sampling: Some(recorded.sampling),
+        sample_size: Some(recorded.sample_size),
+        weighting: Some(recorded.weighting),
+        max_dim: recorded.max_dim,
+        resize_filter: recorded.resize_filter,
+        frames: recorded.frames,
+        frame_stride: recorded.frame_stride,
+        frame_interval: recorded.frame_interval,
+        crop: match recorded.region {
+            Some(Region::Crop(crop)) => Some(crop),
+            _ => None,
+        },
+        focus: match recorded.region {
+            Some(Region::Focus(focus)) => Some(focus),
+            _ => None,
+        },
+        merge_delta_e: recorded.merge_delta_e,
+        min_weight: recorded.min_weight,
+        style: recorded.styles,
+        colorblind_safe: recorded.colorblind_safe,
+        profile: recorded.profile,
+        min_separation: recorded.min_separation,
+        bg_from: recorded.bg_from,
+        bg_color: recorded.bg_color,
+        bg_lightness: recorded.bg_lightness,
+        fg_lightness: recorded.fg_lightness,
+        contrast_envelope: recorded.contrast_envelope,
+        extended: recorded.extended,
+        verbose: recorded.named_colors.into(),
+        lock: recorded.locks,
+        lock_from: Some(theme.to_path_buf()),
+        template: recorded.template,
+        config,
+        force: in_place,
+        ..Default::default()
+    })
+    .map(drop)
+}
+
+/// List installed themes per target, tagging the ones nuri generated and,
+/// on a terminal, showing a swatch of each theme's colors.
+fn list(manage: &ManageArgs) -> Result<()> {
+    let config = Config::load(manage.config.as_deref())?;
+    let swatches = term::stdout_colored();
+    for (target, dir) in theme_dirs(manage, &config)? {
+        let backend = config.backend(target)?;
+        println!("{} ({}):", backend.name(), dir.display());
+        let themes = installed_by(backend.as_ref(), &dir);
+        if themes.is_empty() {
+            println!("  (none)");
+        }
+        let width = themes.iter().map(|(n, _)| n.len()).max().unwrap_or(0);
+        for (name, path) in themes {
+            let content = std::fs::read_to_string(&path).unwrap_or_default();
+            let mut line = format!("  {name:<width$}");
+            if swatches {
+                line.push_str("  ");
+                line.push_str(&preview::swatch(&theme_colors(&path, &content)));
+            }
+            if is_generated(&content) {
+                line.push_str("  nuri");
+            }
+            println!("{}", line.trim_end());
+        }
+    }
+    Ok(())
+}
+
+/// Colors to show for an installed theme: background and the 16 slots when the
+/// file can be imported, otherwise the first hex colors it contains.
+fn theme_colors(path: &Path, content: &str) -> Vec<Color> {
+    let palette = import::ThemeFormat::from_path(path)
+        .and_then(|format| import::parse_palette(content, format).ok());
+    match palette {
+        Some(p) => std::iter::once(p.background).chain(p.slots).collect(),
+        None => import::scan_colors(content).into_iter().take(17).collect(),
+    }
+}
+
+/// Remove an installed theme from each target's directory.
+///
+/// Files without nuri's header are left alone unless `force` is set. A
+/// fixed-path target's file is only removed when its header names the theme,
+/// `force` or not, since it holds whichever theme was installed last.
+fn remove(name: &str, force: bool, manage: &ManageArgs) -> Result<()> {
+    let config = Config::load(manage.config.as_deref())?;
+    // The name the theme was installed under
+    let name = &naming::sanitize(name);
+    let mut removed = 0;
+    let mut skipped = 0;
+    for target in manage_targets(manage, &config) {
+        let path = match config.install_path(target, name) {
+            Ok(path) => path,
+            Err(e) if manage.target.contains(&target) => return Err(e),
+            Err(_) => continue,
+        };
+        if !path.is_file() {
+            continue;
+        }
+        let backend = config.backend(target)?;
+        let content = std::fs::read_to_string(&path).unwrap_or_default();
+        if backend.fixed_path() {
+            if generated_name(&content).as_deref() != Some(name.as_str()) {
+                log::debug!("Skipping {}: holds another theme", path.display());
+                continue;
+            }
+        } else if !force && !is_generated(&content) {
+            log::info!(
+                "Skipping {}: not generated by nuri (use --force to remove it)",
+                path.display()
+            );
+            skipped += 1;
+            continue;
+        }
+        uninstall(backend.as_ref(), &path)
+            .with_context(|| format!("failed to remove {}", path.display()))?;
+        log::info!(
+            "Removed {} theme '{name}' from {}",
+            backend.name(),
+            path.display()
+        );
+        removed += 1;
+    }
+    if removed == 0 {
+        if skipped > 0 {
+            bail!("theme '{name}' was not generated by nuri; use --force to remove it");
+        }
+        bail!("no installed theme named '{name}' found");
+    }
+    Ok(())
+}
+
+/// Open an existing theme file in the TUI.
+fn edit(theme: PathBuf, target: Vec<Target>, config_path: Option<&Path>) -> Result<()> {
+    let config = Config::load(config_path)?;
+    let palette = import::load_palette(&theme)?;
+    let name = default_theme_name(&theme);
+    let targets = if target.is_empty() {
+        config.targets.clone()
+    } else {
+        target
+    };
+    edit_palette(palette, theme, name, targets, config)
+}
+
+/// Open a theme's palette in the TUI, saving under `name`.
+fn edit_palette(
+    palette: AnsiPalette,
+    theme: PathBuf,
+    name: String,
+    targets: Vec<Target>,
+    config: Config,
+) -> Result<()> {
+    let mode = if palette.background.relative_luminance() > 0.2 {
+        ThemeMode::Light
+    } else {
+        ThemeMode::Dark
+    };
+    // Without the source image, the palette's own colors are the candidates.
+    let colors: Vec<ExtractedColor> = palette
+        .slots
+        .iter()
+        .map(|&color| ExtractedColor {
+            color,
+            weight: 1.0 / 16.0,
+        })
+        .collect();
+
+    let mut tui_app = tui::TuiApp::new(palette, colors, theme, mode, name, Vec::new(), 16);
+    if let Some(min_contrast) = config.min_contrast {
+        tui_app.set_min_contrast(validate_min_contrast(min_contrast));
+    }
+    tui_app.set_targets(targets);
+    tui_app.set_config(config);
+    tui::run(tui_app)
+}
+
+/// Browse installed themes; duplicating one opens a copy in the editor and
+/// comes back to the gallery afterwards.
+fn browse(manage: &ManageArgs) -> Result<()> {
+    let config = Config::load(manage.config.as_deref())?;
+    let dirs = theme_dirs(manage, &config)?;
+    let mut browser = tui::browse::Browser::new(config.clone(), dirs);
+    while let Some(copy) = tui::browse::run(&mut browser)? {
+        let name = format!("{}-copy", copy.name);
+        edit_palette(
+            copy.palette,
+            copy.path,
+            name,
+            vec![copy.target],
+            config.clone(),
+        )?;
+        browser.rescan();
+    }
+    Ok(())
+}
+
+/// Preview a theme file, or generate and preview a palette from an image.
+fn preview_file(path: &Path, simulate: Option<ColorBlindness>) -> Result<()> {
+    let palette = if import::is_theme_file(path) {
+        import::load_palette(path)?
+    } else {
+        AnsiPalette::from_image(path, &PaletteOptions::default())?
+    };
+    print_preview(&palette, simulate);
+    Ok(())
+}
+
+/// Print the terminal preview, optionally through a color vision simulation.
+fn print_preview(palette: &AnsiPalette, simulate: Option<ColorBlindness>) {
+    match simulate {
+        Some(kind) => {
+            println!();
+            println!("  Simulating {}", kind.name());
+            preview::print_preview(&palette.simulate(kind), term::stdout_colored());
+        }
+        None => preview::print_preview(palette, term::stdout_colored()),
+    }
+}
+
+/// The TUI's state for a freshly generated palette, for --preview-full.
+fn preview_app(
+    palette: &AnsiPalette,
+    colors: &[ExtractedColor],
+    provenance: &Provenance,
+    mode: ThemeMode,
+    args: &Args,
+) -> tui::TuiApp {
+    let mut app = tui::TuiApp::new(
+        palette.clone(),
+        colors.to_vec(),
+        provenance.source.clone(),
+        mode,
+        provenance.name.clone(),
+        Vec::new(),
+        provenance.colors,
+    );
+    app.set_simulation(args.simulate);
+    app.set_min_contrast(provenance.min_contrast);
+    app.set_locked(&args.lock);
+    app
+}
+
+/// Print the TUI screen once, at the terminal's size or
+/// [`tui::snapshot::DEFAULT_SIZE`] when stdout is not a terminal.
+fn print_preview_full(app: &tui::TuiApp) -> Result<()> {
+    let (width, height) = if io::stdout().is_terminal() {
+        crossterm::terminal::size().unwrap_or(tui::snapshot::DEFAULT_SIZE)
+    } else {
+        tui::snapshot::DEFAULT_SIZE
+    };
+    print!(
+        "{}",
+        tui::snapshot::render(app, width, height, term::stdout_colored())?
+    );
+    Ok(())
+}
+
+/// Show a theme on the current terminal until a key is pressed or
+/// `duration` seconds pass, then restore the terminal's colors.
+fn try_theme(theme: &str, duration: Option<f64>, config_path: Option<&Path>) -> Result<()> {
+    let duration = match duration {
+        Some(seconds) if seconds > 0.0 => match Duration::try_from_secs_f64(seconds) {
+            Ok(duration) => Some(duration),
+            Err(_) => bail!("--duration {seconds} is too long"),
+        },
+        Some(_) => bail!("--duration must be a positive number of seconds"),
+        None => None,
+    };
+    let config = Config::load(config_path)?;
+    let palette = load_theme_or_image(theme, &config)?;
+    live::audition(&palette, duration)
+}
+
+/// The palette of a theme file or installed theme, or generated from an
+/// image with the default options.
+fn load_theme_or_image(theme: &str, config: &Config) -> Result<AnsiPalette> {
+    let path = Path::new(theme);
+    if path.is_file() && !import::is_theme_file(path) {
+        Ok(AnsiPalette::from_image(path, &PaletteOptions::default())?)
+    } else {
+        import::load_palette(&resolve_theme(theme, config)?)
+    }
+}
+
+/// Print the accessibility report for a theme or an image's generated
+/// palette, failing if any check does.
+fn check(theme: &str, config_path: Option<&Path>) -> Result<()> {
+    let config = Config::load(config_path)?;
+    let palette = load_theme_or_image(theme, &config)?;
+    let report = check::Report::new(&palette);
+    report.print(term::stdout_colored());
+    match report.failures() {
+        0 => Ok(()),
+        n => bail!("{theme}: {n} accessibility check(s) failed"),
+    }
+}
+
+/// Compare two themes, each a file or the name of an installed theme.
+fn diff(a: &str, b: &str, config_path: Option<&Path>) -> Result<()> {
+    let config = Config::load(config_path)?;
+    let old = import::load_palette(&resolve_theme(a, &config)?)?;
+    let new = import::load_palette(&resolve_theme(b, &config)?)?;
+    preview::print_diff(&old, &new, term::stdout_colored());
+    Ok(())
+}
+
+/// A theme argument as a path: the file itself if it exists, else the first
+/// installed theme of that name in a format nuri can read back.
+fn resolve_theme(theme: &str, config: &Config) -> Result<PathBuf> {
+    let path = Path::new(theme);
+    if path.is_file() {
+        return Ok(path.to_path_buf());
+    }
+    for target in [
+        Target::Ghostty,
+        Target::Alacritty,
+        Target::Base16,
+        Target::Json,
+    ] {
+        if let Ok(installed) = config.install_path(target, theme) {
+            if installed.is_file() {
+                return Ok(installed);
+            }
+        }
+    }
+    bail!("no theme file or installed theme named '{theme}' found")
+}
+
+/// Targets a management command acts on: --target, else the config's, else all.
+fn manage_targets(manage: &ManageArgs, config: &Config) -> Vec<Target> {
+    if !manage.target.is_empty() {
+        manage.target.clone()
+    } else if !config.targets.is_empty() {
+        config.targets.clone()
+    } else {
+        Target::all().to_vec()
+    }
+}
+
+/// Install directory of each target, skipping unavailable ones unless requested.
+fn theme_dirs(manage: &ManageArgs, config: &Config) -> Result<Vec<(Target, PathBuf)>> {
+    let mut dirs = Vec::new();
+    for target in manage_targets(manage, config) {
+        match config.install_path(target, "nuri") {
+            Ok(path) => {
+                if let Some(dir) = path.parent() {
+                    dirs.push((target, dir.to_path_buf()));
+                }
+            }
+            Err(e) if manage.target.contains(&target) => return Err(e),
+            Err(_) => {}
+        }
+    }
+    Ok(dirs)
+}
+
+/// Push the palette into whichever of stdout/stderr is attached to a terminal.
+fn apply_live(palette: &AnsiPalette) -> Result<()> {
+    if io::stdout().is_terminal() {
+        live::apply(palette, &mut io::stdout())
+    } else if io::stderr().is_terminal() {
+        live::apply(palette, &mut io::stderr())
+    } else {
+        log::warn!("--apply-live needs a terminal; skipping");
+        Ok(())
+    }
+}
+
 /// Validate and clamp --min-contrast to [1.0, 21.0].
 fn validate_min_contrast(value: f32) -> f32 {
     if value < 1.0 {
-        eprintln!("warning: --min-contrast {value} is below 1.0, clamping to 1.0");
+        log::warn!("--min-contrast {value} is below 1.0, clamping to 1.0");
         1.0
     } else if value > 21.0 {
-        eprintln!("warning: --min-contrast {value} exceeds 21.0, clamping to 21.0");
+        log::warn!("--min-contrast {value} exceeds 21.0, clamping to 21.0");
         21.0
     } else {
         value
     }
 }
 
-/// Derive a theme name from the image filename stem.
-fn default_theme_name(path: &std::path::Path) -> String {
+/// Derive a theme name from the image filename stem, sanitized.
+fn default_theme_name(path: &Path) -> String {
+    naming::sanitize(&image_stem(path))
+}
+
+/// The file name of `path` without its extension, as `{stem}` fills in.
+fn image_stem(path: &Path) -> String {
     path.file_stem()
-        .and_then(|s| s.to_str())
-        .unwrap_or("theme")
-        .to_string()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default()
 }
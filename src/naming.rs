@@ -0,0 +1,255 @@
+//! Theme names: every name is sanitized before it becomes part of a path,
+//! and `--name-template` builds names from the image stem, mode, date and
+//! content hash.
+
+use std::fmt;
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::cli::ThemeMode;
+
+/// Name used when sanitizing leaves nothing.
+pub const FALLBACK_NAME: &str = "theme";
+
+/// `name` made safe to use as a file name in any target's directory: path
+/// separators, whitespace and characters Windows forbids become `-`, runs
+/// of them collapse, and leading or trailing dots and dashes are dropped so
+/// the name can't be `..` or a hidden file.
+pub fn sanitize(name: &str) -> String {
+    let mut out = String::with_capacity(name.len());
+    for c in name.chars() {
+        let c = if c.is_alphanumeric() || matches!(c, '-' | '_' | '.' | '+') {
+            c
+        } else {
+            '-'
+        };
+        if !(c == '-' && out.ends_with('-')) {
+            out.push(c);
+        }
+    }
+    let trimmed = out.trim_matches(|c| c == '-' || c == '.');
+    if trimmed.is_empty() {
+        FALLBACK_NAME.to_string()
+    } else {
+        trimmed.to_string()
+    }
+}
+
+/// Like [`sanitize`], noting at info level when the name changed.
+pub fn sanitize_logged(name: &str) -> String {
+    let safe = sanitize(name);
+    if safe != name {
+        log::info!("theme name '{name}' sanitized to '{safe}'");
+    }
+    safe
+}
+
+/// A placeholder of a [`NameTemplate`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Placeholder {
+    Stem,
+    Mode,
+    Date,
+    Hash,
+}
+
+const PLACEHOLDERS: [(&str, Placeholder); 4] = [
+    ("stem", Placeholder::Stem),
+    ("mode", Placeholder::Mode),
+    ("date", Placeholder::Date),
+    ("hash", Placeholder::Hash),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Part {
+    Text(String),
+    Placeholder(Placeholder),
+}
+
+/// A theme name pattern such as `{stem}-{mode}` (`--name-template`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub struct NameTemplate {
+    source: String,
+    parts: Vec<Part>,
+}
+
+/// Values the placeholders of a [`NameTemplate`] stand for.
+#[derive(Debug, Clone, Copy)]
+pub struct NameValues<'a> {
+    /// File name of the image without its extension.
+    pub stem: &'a str,
+    /// `dark` or `light`.
+    pub mode: ThemeMode,
+    /// UTC date, `YYYY-MM-DD`.
+    pub date: &'a str,
+    /// Content hash of the image; the first 8 hex digits are used.
+    pub hash: &'a str,
+}
+
+impl NameTemplate {
+    /// Parse a template; `{stem}`, `{mode}`, `{date}` and `{hash}` are the
+    /// placeholders.
+    pub fn parse(s: &str) -> Result<NameTemplate> {
+        let mut parts = Vec::new();
+        let mut rest = s;
+        while let Some(open) = rest.find('{') {
+            if open > 0 {
+                parts.push(Part::Text(rest[..open].to_string()));
+            }
+            let Some(close) = rest[open..].find('}') else {
+                bail!("unclosed '{{' in name template '{s}'");
+            };
+            let key = &rest[open + 1..open + close];
+            let Some((_, placeholder)) = PLACEHOLDERS.iter().find(|(name, _)| *name == key) else {
+                bail!(
+                    "unknown placeholder '{{{key}}}' in name template '{s}'; use {{stem}}, \
+                     {{mode}}, {{date}} or {{hash}}"
+                );
+            };
+            parts.push(Part::Placeholder(*placeholder));
+            rest = &rest[open + close + 1..];
+        }
+        if rest.contains('}') {
+            bail!("unmatched '}}' in name template '{s}'");
+        }
+        if !rest.is_empty() {
+            parts.push(Part::Text(rest.to_string()));
+        }
+        if parts.is_empty() {
+            bail!("name template is empty");
+        }
+        Ok(NameTemplate {
+            source: s.to_string(),
+            parts,
+        })
+    }
+
+    /// The theme name for `values`, sanitized.
+    pub fn render(&self, values: &NameValues) -> String {
+        let mut name = String::new();
+        for part in &self.parts {
+            match part {
+                Part::Text(text) => name.push_str(text),
+                Part::Placeholder(Placeholder::Stem) => name.push_str(values.stem),
+                Part::Placeholder(Placeholder::Mode) => name.push_str(match values.mode {
+                    ThemeMode::Dark => "dark",
+                    ThemeMode::Light => "light",
+                }),
+                Part::Placeholder(Placeholder::Date) => name.push_str(values.date),
+                Part::Placeholder(Placeholder::Hash) => {
+                    let hex = values.hash.rsplit(':').next().unwrap_or(values.hash);
+                    name.extend(hex.chars().take(8));
+                }
+            }
+        }
+        sanitize(&name)
+    }
+
+    /// Whether names differ between the dark and light theme, so `--both`
+    /// needn't add `-dark` and `-light` itself.
+    pub fn uses_mode(&self) -> bool {
+        self.uses(Placeholder::Mode)
+    }
+
+    /// Whether names differ between images with different stems or
+    /// contents, as a batch needs.
+    pub fn identifies_image(&self) -> bool {
+        self.uses(Placeholder::Stem) || self.uses(Placeholder::Hash)
+    }
+
+    /// Whether names include the image's content hash.
+    pub fn uses_hash(&self) -> bool {
+        self.uses(Placeholder::Hash)
+    }
+
+    fn uses(&self, placeholder: Placeholder) -> bool {
+        self.parts.contains(&Part::Placeholder(placeholder))
+    }
+}
+
+impl Default for NameTemplate {
+    /// `{stem}`, the image's file name without its extension.
+    fn default() -> Self {
+        NameTemplate {
+            source: "{stem}".to_string(),
+            parts: vec![Part::Placeholder(Placeholder::Stem)],
+        }
+    }
+}
+
+impl fmt::Display for NameTemplate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+impl From<NameTemplate> for String {
+    fn from(template: NameTemplate) -> Self {
+        template.source
+    }
+}
+
+impl TryFrom<String> for NameTemplate {
+    type Error = anyhow::Error;
+
+    fn try_from(s: String) -> Result<Self> {
+        NameTemplate::parse(&s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sanitize_keeps_names_inside_the_directory() {
+        assert_eq!(sanitize("sunset"), "sunset");
+        assert_eq!(sanitize("Tokyo Night 2.0"), "Tokyo-Night-2.0");
+        assert_eq!(sanitize("../../etc/passwd"), "etc-passwd");
+        assert_eq!(sanitize("a/b\\c:d"), "a-b-c-d");
+        assert_eq!(sanitize(".hidden"), "hidden");
+        assert_eq!(sanitize("  spaced   out  "), "spaced-out");
+        assert_eq!(sanitize("夕焼け"), "夕焼け");
+        assert_eq!(sanitize(".."), FALLBACK_NAME);
+        assert_eq!(sanitize(""), FALLBACK_NAME);
+        assert_eq!(sanitize("tab\there\n"), "tab-here");
+    }
+
+    #[test]
+    fn template_fills_placeholders() {
+        let values = NameValues {
+            stem: "my wallpaper",
+            mode: ThemeMode::Light,
+            date: "2026-10-18",
+            hash: "fnv1a64:0123456789abcdef",
+        };
+        let template = NameTemplate::parse("{stem}-{mode}").unwrap();
+        assert_eq!(template.render(&values), "my-wallpaper-light");
+        assert!(template.uses_mode());
+        assert!(template.identifies_image());
+
+        let template = NameTemplate::parse("wall_{date}_{hash}").unwrap();
+        assert_eq!(template.render(&values), "wall_2026-10-18_01234567");
+        assert!(!template.uses_mode());
+        assert!(template.identifies_image());
+
+        // A template can't reach outside the directory either
+        let template = NameTemplate::parse("../{mode}").unwrap();
+        assert_eq!(template.render(&values), "light");
+        assert!(!template.identifies_image());
+
+        assert_eq!(NameTemplate::default().render(&values), "my-wallpaper");
+        assert_eq!(template.to_string(), "../{mode}");
+    }
+
+    #[test]
+    fn template_errors_name_the_problem() {
+        let err = |s: &str| NameTemplate::parse(s).unwrap_err().to_string();
+        assert!(err("{stem}-{size}").contains("unknown placeholder '{size}'"));
+        assert!(err("{stem").contains("unclosed"));
+        assert!(err("stem}").contains("unmatched"));
+        assert!(err("").contains("empty"));
+    }
+}
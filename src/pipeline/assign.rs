@@ -1,11 +1,14 @@
 use palette::Oklch;
+use serde::{Deserialize, Serialize};
 
 use crate::cli::ThemeMode;
-use crate::color::Color;
+use crate::color::{max_chroma, Color, ColorBlindness};
+use crate::config::SLOT_NAMES;
 use crate::pipeline::extract::ExtractedColor;
+use crate::pipeline::profile::{BgFrom, Profile};
 
 /// The full ANSI palette plus special Ghostty theme colors.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct AnsiPalette {
     /// ANSI colors 0-15.
     pub slots: [Color; 16],
@@ -17,6 +20,26 @@ pub struct AnsiPalette {
     pub selection_fg: Color,
 }
 
+impl AnsiPalette {
+    /// Apply `f` to every color in the palette.
+    pub fn map_colors(&self, f: impl Fn(Color) -> Color) -> AnsiPalette {
+        AnsiPalette {
+            slots: self.slots.map(&f),
+            background: f(self.background),
+            foreground: f(self.foreground),
+            cursor_color: f(self.cursor_color),
+            cursor_text: f(self.cursor_text),
+            selection_bg: f(self.selection_bg),
+            selection_fg: f(self.selection_fg),
+        }
+    }
+
+    /// The palette as seen with a color vision deficiency.
+    pub fn simulate(&self, kind: ColorBlindness) -> AnsiPalette {
+        self.map_colors(|c| c.simulate(kind))
+    }
+}
+
 /// Target Oklch hue angles (degrees) for the six ANSI accent slots.
 const TARGET_HUES: [(usize, f32); 6] = [
     (1, 25.0),  // Red
@@ -28,11 +51,25 @@ const TARGET_HUES: [(usize, f32); 6] = [
 ];
 
 /// Maximum hue distance (degrees) before we synthesize instead of using the candidate.
-const MAX_HUE_DISTANCE: f32 = 60.0;
+pub(crate) const MAX_HUE_DISTANCE: f32 = 60.0;
+
+/// Default minimum delta-E (CIE76) between any two accents (slots 1-6); the
+/// same bar `nuri check` holds accents to.
+pub const DEFAULT_MIN_SEPARATION: f32 = 10.0;
+
+/// Background lightness for the dominant background source, by mode.
+const DOMINANT_BG_L_DARK: f32 = 0.15;
+const DOMINANT_BG_L_LIGHT: f32 = 0.95;
+
+/// Hue rotation (degrees) per separation step.
+const SEPARATION_STEP: f32 = 2.0;
 
 /// Oklch lightness increase for bright variants (slots 9-14).
 const BRIGHT_L_DELTA: f32 = 0.12;
 
+/// Oklch lightness step when backing a bright variant off into gamut.
+const L_FIT_STEP: f32 = 0.005;
+
 /// Minimum Oklch chroma to consider a candidate chromatic (not gray).
 const MIN_CHROMA: f32 = 0.02;
 
@@ -42,8 +79,21 @@ const BASE_MAX_CHROMA: f32 = 0.04;
 /// Maximum chroma for text-emphasis slots to keep them near-neutral.
 const TEXT_MAX_CHROMA: f32 = 0.02;
 
+/// Target hue for an accent slot (1-6 or their bright variants 9-14).
+pub fn target_hue(slot: usize) -> Option<f32> {
+    let base = if (9..=14).contains(&slot) {
+        slot - 8
+    } else {
+        slot
+    };
+    TARGET_HUES
+        .iter()
+        .find(|(s, _)| *s == base)
+        .map(|&(_, hue)| hue)
+}
+
 /// Angular distance between two hue values, wrapped to [0, 180].
-fn hue_distance(a: f32, b: f32) -> f32 {
+pub fn hue_distance(a: f32, b: f32) -> f32 {
     let diff = (a - b).abs() % 360.0;
     if diff > 180.0 {
         360.0 - diff
@@ -54,51 +104,202 @@ fn hue_distance(a: f32, b: f32) -> f32 {
 
 /// Map extracted colors to the 16 ANSI palette slots plus special colors.
 pub fn assign_slots(colors: &[ExtractedColor], mode: ThemeMode) -> AnsiPalette {
+    assign_slots_with(colors, mode, &Profile::default())
+}
+
+/// [`assign_slots`] with accents placed by `profile` instead of the default
+/// target hues.
+pub fn assign_slots_with(
+    colors: &[ExtractedColor],
+    mode: ThemeMode,
+    profile: &Profile,
+) -> AnsiPalette {
     let mut slots = [Color::new(0, 0, 0); 16];
 
-    let oklch_colors: Vec<Oklch> = colors.iter().map(|ec| ec.color.to_oklch()).collect();
+    let weighted = weighted_oklch(colors);
+    let oklch_colors: Vec<Oklch> = weighted.iter().map(|&(c, _)| c).collect();
 
-    assign_accents(&oklch_colors, &mut slots);
+    assign_accents(&weighted, profile, &mut slots);
+    separate_hues(profile, &mut slots);
     assign_base_colors(&oklch_colors, mode, &mut slots);
+    tint_background(&weighted, mode, profile, &mut slots);
+    shape_text_colors(profile, &mut slots);
     assign_bright_variants(&mut slots);
     derive_special_colors(slots, mode)
 }
 
-/// Assign accent colors (slots 1-6) by hue proximity to target hues.
+/// How an accent slot got its color, before it was fitted into the target's
+/// lightness and chroma ranges.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(tag = "source", rename_all = "kebab-case")]
+pub enum AccentChoice {
+    /// The best-scoring extracted color within the target's hue tolerance.
+    Matched {
+        candidate: Color,
+        weight: f32,
+        hue_distance: f32,
+    },
+    /// No extracted color within tolerance: the one nearest in hue, rotated
+    /// onto the target.
+    Synthesized { nearest: Color, hue_distance: f32 },
+    /// No chromatic colors were extracted at all.
+    Fallback,
+}
+
+/// The choice [`assign_slots_with`] makes for each accent (red, green,
+/// yellow, blue, magenta, cyan).
+pub fn accent_choices(colors: &[ExtractedColor], profile: &Profile) -> [AccentChoice; 6] {
+    choose_accents(&weighted_oklch(colors), profile).map(|(choice, _)| choice)
+}
+
+fn weighted_oklch(colors: &[ExtractedColor]) -> Vec<(Oklch, f32)> {
+    colors
+        .iter()
+        .map(|ec| (ec.color.to_oklch(), ec.weight))
+        .collect()
+}
+
+/// Assign accent colors (slots 1-6) from the candidates near each of the
+/// profile's target hues; see [`choose_accents`]. The chosen color is clamped
+/// into the target's lightness and chroma ranges.
+fn assign_accents(candidates: &[(Oklch, f32)], profile: &Profile, slots: &mut [Color; 16]) {
+    let chosen = choose_accents(candidates, profile);
+    for (i, (target, (_, color))) in profile.accents.iter().zip(chosen).enumerate() {
+        slots[i + 1] = Color::from_oklch(target.fit(color));
+    }
+}
+
+/// Pick each accent's color among the candidates.
 ///
-/// If no candidate is within [`MAX_HUE_DISTANCE`] of a target, the nearest
-/// candidate's hue is rotated to the target in Oklch space (synthesis).
-fn assign_accents(candidates: &[Oklch], slots: &mut [Color; 16]) {
-    let chromatic: Vec<Oklch> = candidates
+/// Candidates within the target's tolerance are ranked by the profile's
+/// [`Scoring`](super::profile::Scoring): closeness in hue, chroma and cluster
+/// weight, each relative to the best available, so a tiny vivid artifact does
+/// not beat a dominant color of nearly the same hue. If none is within
+/// tolerance, the nearest candidate's hue is rotated to the target in Oklch
+/// space (synthesis).
+fn choose_accents(candidates: &[(Oklch, f32)], profile: &Profile) -> [(AccentChoice, Oklch); 6] {
+    let chromatic: Vec<(Oklch, f32)> = candidates
         .iter()
         .copied()
-        .filter(|c| c.chroma > MIN_CHROMA)
+        .filter(|(c, _)| c.chroma > MIN_CHROMA)
         .collect();
-
-    for &(slot, target_hue) in &TARGET_HUES {
-        if let Some(best) = find_closest_by_hue(&chromatic, target_hue) {
-            let dist = hue_distance(f32::from(best.hue), target_hue);
-            if dist <= MAX_HUE_DISTANCE {
-                slots[slot] = Color::from_oklch(best);
-            } else {
-                // Synthesize: rotate the nearest candidate's hue to the target
-                let synth = Oklch::new(best.l, best.chroma, target_hue);
-                slots[slot] = Color::from_oklch(synth);
+    let max_chroma = chromatic.iter().map(|(c, _)| c.chroma).fold(0.0, f32::max);
+    let max_weight = chromatic.iter().map(|&(_, w)| w).fold(0.0, f32::max);
+    let scoring = profile.scoring;
+
+    std::array::from_fn(|i| {
+        let target = &profile.accents[i];
+        let dist = |c: &Oklch| hue_distance(f32::from(c.hue), target.hue);
+        let score = |(c, weight): &(Oklch, f32)| {
+            -scoring.hue * dist(c) / target.tolerance.max(1.0)
+                + scoring.chroma * c.chroma / max_chroma.max(f32::EPSILON)
+                + scoring.weight * weight / max_weight.max(f32::EPSILON)
+        };
+        let slot = SLOT_NAMES[i + 1];
+        for candidate @ (c, weight) in &chromatic {
+            log::trace!(
+                "{slot}: candidate {} hue {:.0}° ({:.0}° off), weight {:.1}%, score {:.2}",
+                Color::from_oklch(*c).to_hex(),
+                c.hue.into_positive_degrees(),
+                dist(c),
+                weight * 100.0,
+                score(candidate)
+            );
+        }
+        let best = chromatic
+            .iter()
+            .filter(|(c, _)| dist(c) <= target.tolerance)
+            .max_by(|a, b| score(a).total_cmp(&score(b)));
+        let nearest = chromatic
+            .iter()
+            .map(|&(c, _)| c)
+            .min_by(|a, b| dist(a).total_cmp(&dist(b)));
+        match (best, nearest) {
+            (Some(&(best, weight)), _) => {
+                log::debug!(
+                    "{slot}: matched {} at hue {:.0}°, {:.0}° from target {:.0}° (tolerance {:.0}°), weight {:.1}%",
+                    Color::from_oklch(best).to_hex(),
+                    best.hue.into_positive_degrees(),
+                    dist(&best),
+                    target.hue,
+                    target.tolerance,
+                    weight * 100.0
+                );
+                let choice = AccentChoice::Matched {
+                    candidate: Color::from_oklch(best),
+                    weight,
+                    hue_distance: dist(&best),
+                };
+                (choice, best)
+            }
+            // Synthesize: rotate the nearest candidate's hue to the target
+            (None, Some(nearest)) => {
+                log::debug!(
+                    "{slot}: synthesized; nearest candidate {} is {:.0}° from target {:.0}° (tolerance {:.0}°), rotated onto it",
+                    Color::from_oklch(nearest).to_hex(),
+                    dist(&nearest),
+                    target.hue,
+                    target.tolerance
+                );
+                let choice = AccentChoice::Synthesized {
+                    nearest: Color::from_oklch(nearest),
+                    hue_distance: dist(&nearest),
+                };
+                (choice, Oklch::new(nearest.l, nearest.chroma, target.hue))
             }
-        } else {
             // No chromatic candidates — fully synthetic fallback
-            slots[slot] = Color::from_oklch(Oklch::new(0.65, 0.15, target_hue));
+            (None, None) => {
+                log::debug!("{slot}: synthesized; no chromatic candidates");
+                (AccentChoice::Fallback, Oklch::new(0.65, 0.15, target.hue))
+            }
         }
-    }
+    })
 }
 
-/// Find the candidate with the smallest hue distance to `target_hue`.
-fn find_closest_by_hue(candidates: &[Oklch], target_hue: f32) -> Option<Oklch> {
-    candidates.iter().copied().min_by(|a, b| {
-        hue_distance(f32::from(a.hue), target_hue)
-            .partial_cmp(&hue_distance(f32::from(b.hue), target_hue))
-            .unwrap()
-    })
+/// Rotate accents (slots 1-6) that sit closer than the profile's minimum
+/// separation toward their target hues, a step at a time, until every pair
+/// is far enough apart or the colliding accents have reached their targets.
+///
+/// A wallpaper dominated by one hue can hand the same color to neighboring
+/// slots; this pulls them back apart while keeping lightness and chroma.
+fn separate_hues(profile: &Profile, slots: &mut [Color; 16]) {
+    let min = profile.min_separation;
+    if min <= 0.0 {
+        return;
+    }
+    let mut accents: [Oklch; 6] = std::array::from_fn(|i| slots[i + 1].to_oklch());
+    let before = accents;
+    // Every accent reaches its target within 180 / SEPARATION_STEP steps
+    for _ in 0..=(180.0 / SEPARATION_STEP) as usize {
+        let colliding: Vec<usize> = (0..6)
+            .filter(|&i| (0..6).any(|j| j != i && slots[i + 1].delta_e(slots[j + 1]) < min))
+            .collect();
+        let mut stepped = false;
+        for i in colliding {
+            let hue = f32::from(accents[i].hue);
+            // Signed shortest rotation to the target, in [-180, 180)
+            let diff = (profile.accents[i].hue - hue + 540.0).rem_euclid(360.0) - 180.0;
+            if diff.abs() < 0.01 {
+                continue;
+            }
+            accents[i].hue = (hue + diff.clamp(-SEPARATION_STEP, SEPARATION_STEP)).into();
+            slots[i + 1] = Color::from_oklch(accents[i]);
+            stepped = true;
+        }
+        if !stepped {
+            break;
+        }
+    }
+    for (i, (old, new)) in before.iter().zip(&accents).enumerate() {
+        if old.hue != new.hue {
+            log::debug!(
+                "{}: rotated from {:.0}° to {:.0}° to keep accents ΔE {min} apart",
+                SLOT_NAMES[i + 1],
+                old.hue.into_positive_degrees(),
+                new.hue.into_positive_degrees()
+            );
+        }
+    }
 }
 
 /// Assign base colors (slots 0, 7, 8, 15) based on theme mode.
@@ -171,10 +372,82 @@ fn assign_base_colors(candidates: &[Oklch], mode: ThemeMode, slots: &mut [Color;
     }
 }
 
+/// Replace the background (slot 0) as the profile's background source asks,
+/// carrying its hue and tint over to bright black (slot 8) so dimmed text
+/// stays on-tone. The darkest source without a lightness keeps the base
+/// colors as assigned.
+fn tint_background(
+    candidates: &[(Oklch, f32)],
+    mode: ThemeMode,
+    profile: &Profile,
+    slots: &mut [Color; 16],
+) {
+    let current = slots[0].to_oklch();
+    let mut bg = match profile.bg_from {
+        BgFrom::Darkest if profile.bg_lightness.is_none() => return,
+        BgFrom::Darkest => current,
+        BgFrom::Dominant => {
+            let dominant = candidates
+                .iter()
+                .filter(|(c, _)| c.chroma > MIN_CHROMA)
+                .max_by(|a, b| a.1.total_cmp(&b.1))
+                .map_or(current, |&(c, _)| c);
+            let l = match mode {
+                ThemeMode::Dark => DOMINANT_BG_L_DARK,
+                ThemeMode::Light => DOMINANT_BG_L_LIGHT,
+            };
+            Oklch::new(l, dominant.chroma.min(BASE_MAX_CHROMA), dominant.hue)
+        }
+        BgFrom::Custom => profile.bg_color.map_or(current, Color::to_oklch),
+    };
+    if let Some(l) = profile.bg_lightness {
+        bg.l = l;
+    }
+    slots[0] = Color::from_oklch(bg);
+    let dim = slots[8].to_oklch();
+    slots[8] = Color::from_oklch(Oklch::new(dim.l, bg.chroma.min(BASE_MAX_CHROMA), bg.hue));
+}
+
+/// Apply the profile's foreground lightness and contrast envelope to the
+/// text colors: white (7), bright black (8) and bright white (15).
+///
+/// A foreground lightness moves bright white there and white by the same
+/// share of its distance from the background; the envelope then scales each
+/// text color's lightness distance from the background.
+fn shape_text_colors(profile: &Profile, slots: &mut [Color; 16]) {
+    if profile.fg_lightness.is_none() && profile.contrast_envelope == 1.0 {
+        return;
+    }
+    let bg = slots[0].to_oklch().l;
+    let mut text: [Oklch; 3] = [7, 8, 15].map(|i| slots[i].to_oklch());
+    if let Some(fg) = profile.fg_lightness {
+        let gap = text[2].l - bg;
+        if gap.abs() > f32::EPSILON {
+            text[0].l = bg + (text[0].l - bg) * (fg - bg) / gap;
+        }
+        text[2].l = fg;
+    }
+    for (i, mut c) in [7, 8, 15].into_iter().zip(text) {
+        c.l = (bg + (c.l - bg) * profile.contrast_envelope).clamp(0.0, 1.0);
+        slots[i] = Color::from_oklch(c);
+    }
+}
+
 /// Generate bright variants (slots 9-14) from normal accents (slots 1-6).
+///
+/// Lightening stops where the accent's chroma still fits in sRGB: near white
+/// gamut mapping would drain it to gray, and no later step could restore the
+/// hue.
 fn assign_bright_variants(slots: &mut [Color; 16]) {
     for i in 1..=6 {
-        slots[i + 8] = slots[i].adjust_lightness(BRIGHT_L_DELTA);
+        let mut c = slots[i].to_oklch();
+        let hue = c.hue.into_degrees();
+        let mut l = (c.l + BRIGHT_L_DELTA).min(1.0);
+        while l > c.l && max_chroma(l, hue) < c.chroma {
+            l -= L_FIT_STEP;
+        }
+        c.l = l.max(c.l);
+        slots[i + 8] = Color::from_oklch(c);
     }
 }
 
@@ -211,6 +484,7 @@ fn derive_special_colors(slots: [Color; 16], mode: ThemeMode) -> AnsiPalette {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::pipeline::profile::Scoring;
 
     fn make_extracted(l: f32, chroma: f32, hue: f32, weight: f32) -> ExtractedColor {
         ExtractedColor {
@@ -232,6 +506,16 @@ mod tests {
         ]
     }
 
+    #[test]
+    fn target_hue_covers_normal_and_bright_accents() {
+        assert_eq!(target_hue(1), Some(25.0));
+        assert_eq!(target_hue(9), Some(25.0));
+        assert_eq!(target_hue(14), Some(195.0));
+        assert_eq!(target_hue(0), None);
+        assert_eq!(target_hue(7), None);
+        assert_eq!(target_hue(15), None);
+    }
+
     #[test]
     fn diverse_hues_land_in_correct_slots() {
         let palette = assign_slots(&diverse_candidates(), ThemeMode::Dark);
@@ -239,7 +523,6 @@ mod tests {
         for &(slot, target_hue) in &TARGET_HUES {
             let oklch = palette.slots[slot].to_oklch();
             let dist = hue_distance(f32::from(oklch.hue), target_hue);
-            // Tolerance accounts for hue drift from sRGB gamut clamping
             assert!(
                 dist < 15.0,
                 "slot {slot} hue {:.1}° should be near target {target_hue}°, distance {dist:.1}°",
@@ -285,6 +568,28 @@ mod tests {
         );
     }
 
+    #[test]
+    fn accent_choices_explain_matches_and_synthesis() {
+        let red = make_extracted(0.60, 0.20, 25.0, 0.40);
+        let blue = make_extracted(0.55, 0.20, 260.0, 0.40);
+        let choices = accent_choices(&[red.clone(), blue.clone()], &Profile::default());
+        assert!(matches!(
+            choices[0],
+            AccentChoice::Matched { candidate, hue_distance, .. }
+                if candidate == red.color && hue_distance < 2.0
+        ));
+        // Green (145°) is 115° from blue and 120° from red
+        assert!(matches!(
+            choices[1],
+            AccentChoice::Synthesized { nearest, hue_distance }
+                if nearest == blue.color && (hue_distance - 115.0).abs() < 2.0
+        ));
+        assert_eq!(
+            accent_choices(&[], &Profile::default()),
+            [AccentChoice::Fallback; 6]
+        );
+    }
+
     #[test]
     fn bright_variants_are_lighter() {
         let palette = assign_slots(&diverse_candidates(), ThemeMode::Dark);
@@ -382,4 +687,163 @@ mod tests {
             let _ = format!("slot {i}: {color}");
         }
     }
+
+    #[test]
+    fn profile_moves_targets_and_clamps_accents() {
+        let nord = Profile::builtin("nord").unwrap();
+        let default = assign_slots(&diverse_candidates(), ThemeMode::Dark);
+        let palette = assign_slots_with(&diverse_candidates(), ThemeMode::Dark, &nord);
+        assert_ne!(palette.slots[1..=6], default.slots[1..=6]);
+        for (i, target) in nord.accents.iter().enumerate() {
+            let c = palette.slots[i + 1].to_oklch();
+            // sRGB rounding moves colors slightly off the clamped values
+            assert!(c.l >= target.lightness[0] - 0.01 && c.l <= target.lightness[1] + 0.01);
+            assert!(c.chroma <= target.chroma[1] + 0.01, "slot {}", i + 1);
+            assert!(hue_distance(f32::from(c.hue), target.hue) <= target.tolerance + 5.0);
+        }
+        assert_eq!(
+            assign_slots_with(&diverse_candidates(), ThemeMode::Dark, &Profile::default()),
+            default
+        );
+    }
+
+    #[test]
+    fn colliding_accents_are_pulled_apart() {
+        // One orange hue sits between the red and yellow targets
+        let colors = vec![
+            make_extracted(0.65, 0.15, 58.0, 0.80),
+            make_extracted(0.10, 0.01, 0.0, 0.10),
+            make_extracted(0.95, 0.01, 0.0, 0.10),
+        ];
+        let apart = Profile {
+            min_separation: 10.0,
+            ..Profile::default()
+        };
+        let palette = assign_slots_with(&colors, ThemeMode::Dark, &apart);
+        for i in 1..=6 {
+            for j in i + 1..=6 {
+                let delta_e = palette.slots[i].delta_e(palette.slots[j]);
+                assert!(delta_e >= 10.0, "slots {i} and {j}: {delta_e:.1}");
+            }
+        }
+
+        let off = Profile {
+            min_separation: 0.0,
+            ..Profile::default()
+        };
+        let palette = assign_slots_with(&colors, ThemeMode::Dark, &off);
+        assert_eq!(palette.slots[1], palette.slots[3]);
+    }
+
+    #[test]
+    fn dominant_color_beats_tiny_artifact() {
+        // A speck right on the cyan target against the image's dominant teal
+        let teal = make_extracted(0.60, 0.11, 215.0, 0.60);
+        let colors = vec![
+            teal.clone(),
+            make_extracted(0.65, 0.13, 195.0, 0.001),
+            make_extracted(0.10, 0.01, 0.0, 0.20),
+            make_extracted(0.95, 0.01, 0.0, 0.199),
+        ];
+        // Teal also lands in blue; keep separation from rotating it
+        let scored = Profile {
+            min_separation: 0.0,
+            ..Profile::default()
+        };
+        let palette = assign_slots_with(&colors, ThemeMode::Dark, &scored);
+        assert_eq!(palette.slots[6], teal.color);
+
+        // Hue alone still decides when weight and chroma are not scored
+        let hue_only = Profile {
+            scoring: Scoring {
+                hue: 1.0,
+                chroma: 0.0,
+                weight: 0.0,
+            },
+            ..scored
+        };
+        let palette = assign_slots_with(&colors, ThemeMode::Dark, &hue_only);
+        assert_eq!(palette.slots[6], colors[1].color);
+    }
+
+    #[test]
+    fn background_follows_the_dominant_hue() {
+        let colors = vec![
+            make_extracted(0.55, 0.12, 200.0, 0.70), // dominant teal
+            make_extracted(0.60, 0.20, 25.0, 0.10),
+            make_extracted(0.10, 0.01, 60.0, 0.10),
+            make_extracted(0.95, 0.01, 0.0, 0.10),
+        ];
+        let darkest = assign_slots(&colors, ThemeMode::Dark);
+        let dominant = Profile {
+            bg_from: BgFrom::Dominant,
+            ..Profile::default()
+        };
+        let palette = assign_slots_with(&colors, ThemeMode::Dark, &dominant);
+        let bg = palette.background.to_oklch();
+        assert!(hue_distance(f32::from(bg.hue), 200.0) < 15.0);
+        assert!((bg.l - DOMINANT_BG_L_DARK).abs() < 0.02);
+        assert_ne!(palette.background, darkest.background);
+        assert_eq!(palette.slots[0], palette.background);
+
+        let custom = Profile {
+            bg_from: BgFrom::Custom,
+            bg_color: Some(Color::new(40, 20, 60)),
+            bg_lightness: Some(0.3),
+            ..Profile::default()
+        };
+        let bg = assign_slots_with(&colors, ThemeMode::Dark, &custom)
+            .background
+            .to_oklch();
+        let purple = Color::new(40, 20, 60).to_oklch();
+        assert!((bg.l - 0.3).abs() < 0.02);
+        assert!(hue_distance(f32::from(bg.hue), f32::from(purple.hue)) < 5.0);
+    }
+
+    #[test]
+    fn envelope_and_foreground_lightness_shape_text_colors() {
+        let lightness = |p: &AnsiPalette, i: usize| p.slots[i].to_oklch().l;
+        let normal = assign_slots(&diverse_candidates(), ThemeMode::Dark);
+        let soft = Profile {
+            contrast_envelope: 0.7,
+            ..Profile::default()
+        };
+        let soft = assign_slots_with(&diverse_candidates(), ThemeMode::Dark, &soft);
+        let bg = lightness(&normal, 0);
+        for i in [7, 8, 15] {
+            let expected = bg + (lightness(&normal, i) - bg) * 0.7;
+            assert!((lightness(&soft, i) - expected).abs() < 0.01, "slot {i}");
+        }
+        assert_eq!(soft.slots[1..=6], normal.slots[1..=6]);
+
+        let dim = Profile {
+            fg_lightness: Some(0.8),
+            ..Profile::default()
+        };
+        let dim = assign_slots_with(&diverse_candidates(), ThemeMode::Dark, &dim);
+        assert!((lightness(&dim, 15) - 0.8).abs() < 0.01);
+        assert!(lightness(&dim, 7) < lightness(&dim, 15));
+        assert!(lightness(&dim, 7) < lightness(&normal, 7));
+        assert_eq!(dim.foreground, dim.slots[15]);
+    }
+
+    #[test]
+    fn light_bright_variants_keep_their_chroma() {
+        // Pale accents: lightening them fully would land on white
+        let mut slots = [Color::new(128, 128, 128); 16];
+        for (i, hue) in [25.0, 145.0, 100.0, 260.0, 330.0, 190.0]
+            .into_iter()
+            .enumerate()
+        {
+            slots[i + 1] = Color::from_oklch(Oklch::new(0.91, 0.06, hue));
+        }
+        assign_bright_variants(&mut slots);
+        for i in 1..=6 {
+            let (normal, bright) = (slots[i].to_oklch(), slots[i + 8].to_oklch());
+            assert!(bright.l >= normal.l - 0.01, "slot {i}");
+            assert!(bright.chroma > normal.chroma - 0.01, "slot {i}");
+            let drift = hue_distance(f32::from(bright.hue), f32::from(normal.hue));
+            assert!(drift < 5.0, "slot {i}: hue drifted {drift:.1}°");
+        }
+    }
 }
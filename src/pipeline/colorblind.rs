@@ -0,0 +1,136 @@
+use crate::color::{Color, ColorBlindness};
+use crate::config::SLOT_NAMES;
+use crate::pipeline::assign::AnsiPalette;
+
+/// Minimum delta-E between the colors of each confusable pair, as seen with
+/// the deficiency that brings them closest.
+pub const MIN_SIMULATED_DELTA_E: f32 = 12.0;
+
+/// Accent pairs that color vision deficiencies tend to merge: red/green and
+/// blue/magenta, normal and bright.
+const CONFUSABLE_PAIRS: [(usize, usize); 4] = [(1, 2), (4, 5), (9, 10), (12, 13)];
+
+/// Oklch lightness adjustment step per iteration.
+const L_STEP: f32 = 0.01;
+
+/// Oklch chroma adjustment step per iteration.
+const CHROMA_STEP: f32 = 0.005;
+
+/// Maximum adjustment iterations per pair.
+const MAX_ITERATIONS: usize = 100;
+
+/// Push confusable accent pairs apart until they stay distinguishable under
+/// deuteranopia, protanopia and tritanopia simulation.
+///
+/// Hues are kept; each step nudges the lightness or chroma of one color of the
+/// pair, whichever separates them most, and never lets an accent fall below
+/// `accent_min` contrast against the background. Pairs that cannot reach
+/// [`MIN_SIMULATED_DELTA_E`] end as far apart as the steps could get them.
+pub fn separate_accents(palette: &mut AnsiPalette, accent_min: f32) {
+    let bg = palette.background;
+    for (a, b) in CONFUSABLE_PAIRS {
+        let mut pair = [palette.slots[a], palette.slots[b]];
+        for _ in 0..MAX_ITERATIONS {
+            let current = simulated_delta_e(pair[0], pair[1]);
+            if current >= MIN_SIMULATED_DELTA_E {
+                break;
+            }
+            let best = (0..2)
+                .flat_map(|i| nudges(pair[i]).map(move |c| (i, c)))
+                .filter(|&(_, c)| Color::contrast_ratio(&c, &bg) >= accent_min)
+                .map(|(i, c)| {
+                    let mut moved = pair;
+                    moved[i] = c;
+                    (simulated_delta_e(moved[0], moved[1]), moved)
+                })
+                .max_by(|x, y| x.0.total_cmp(&y.0));
+            match best {
+                Some((separation, moved)) if separation > current => pair = moved,
+                _ => break,
+            }
+        }
+        if pair != [palette.slots[a], palette.slots[b]] {
+            log::debug!(
+                "{} / {}: {} / {} -> {} / {} for color vision deficiencies (simulated ΔE {:.1} -> {:.1})",
+                SLOT_NAMES[a],
+                SLOT_NAMES[b],
+                palette.slots[a].to_hex(),
+                palette.slots[b].to_hex(),
+                pair[0].to_hex(),
+                pair[1].to_hex(),
+                simulated_delta_e(palette.slots[a], palette.slots[b]),
+                simulated_delta_e(pair[0], pair[1])
+            );
+        }
+        [palette.slots[a], palette.slots[b]] = pair;
+    }
+}
+
+/// Smallest delta-E between two colors across the simulated deficiencies.
+pub fn simulated_delta_e(a: Color, b: Color) -> f32 {
+    ColorBlindness::ALL
+        .iter()
+        .map(|&kind| a.simulate(kind).delta_e(b.simulate(kind)))
+        .fold(f32::MAX, f32::min)
+}
+
+/// One step lighter, darker, more and less saturated.
+fn nudges(color: Color) -> [Color; 4] {
+    [
+        color.adjust_lightness(L_STEP),
+        color.adjust_lightness(-L_STEP),
+        color.adjust_chroma(CHROMA_STEP),
+        color.adjust_chroma(-CHROMA_STEP),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::assign::hue_distance;
+    use palette::Oklch;
+
+    fn palette_with(red: Color, green: Color) -> AnsiPalette {
+        let mut slots = [Color::new(128, 128, 128); 16];
+        slots[0] = Color::new(20, 20, 24);
+        slots[1] = red;
+        slots[2] = green;
+        AnsiPalette {
+            slots,
+            background: slots[0],
+            foreground: Color::new(230, 230, 230),
+            cursor_color: Color::new(230, 230, 230),
+            cursor_text: slots[0],
+            selection_bg: Color::new(60, 60, 80),
+            selection_fg: Color::new(230, 230, 230),
+        }
+    }
+
+    #[test]
+    fn separates_red_and_green_that_look_alike() {
+        // Equal lightness and chroma: only hue tells these apart
+        let red = Color::from_oklch(Oklch::new(0.7, 0.12, 25.0));
+        let green = Color::from_oklch(Oklch::new(0.7, 0.12, 145.0));
+        assert!(simulated_delta_e(red, green) < MIN_SIMULATED_DELTA_E);
+
+        let mut palette = palette_with(red, green);
+        separate_accents(&mut palette, 4.5);
+        let [new_red, new_green] = [palette.slots[1], palette.slots[2]];
+        assert!(simulated_delta_e(new_red, new_green) >= MIN_SIMULATED_DELTA_E);
+        for (before, after) in [(red, new_red), (green, new_green)] {
+            assert!(Color::contrast_ratio(&after, &palette.background) >= 4.5);
+            let hue = |c: Color| f32::from(c.to_oklch().hue);
+            assert!(hue_distance(hue(before), hue(after)) < 10.0);
+        }
+    }
+
+    #[test]
+    fn leaves_distinct_pairs_alone() {
+        let red = Color::from_oklch(Oklch::new(0.6, 0.15, 25.0));
+        let green = Color::from_oklch(Oklch::new(0.85, 0.15, 145.0));
+        let mut palette = palette_with(red, green);
+        let before = palette.clone();
+        separate_accents(&mut palette, 4.5);
+        assert_eq!(palette.slots[1..=2], before.slots[1..=2]);
+    }
+}
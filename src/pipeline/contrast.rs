@@ -1,14 +1,15 @@
 use crate::color::Color;
+use crate::config::SLOT_NAMES;
 use crate::pipeline::assign::AnsiPalette;
 
 /// Minimum contrast ratio for accent colors (slots 1-6, 9-14) vs background.
 const ACCENT_MIN_CONTRAST: f32 = 4.5;
 
 /// Minimum contrast ratio for foreground vs background.
-const FOREGROUND_MIN_CONTRAST: f32 = 7.0;
+pub const FOREGROUND_MIN_CONTRAST: f32 = 7.0;
 
 /// Minimum contrast ratio for bright black (slot 8) vs background.
-const BRIGHT_BLACK_MIN_CONTRAST: f32 = 3.0;
+pub const BRIGHT_BLACK_MIN_CONTRAST: f32 = 3.0;
 
 /// Oklch lightness adjustment step per iteration.
 const L_STEP: f32 = 0.01;
@@ -36,29 +37,52 @@ pub fn enforce_contrast(palette: &mut AnsiPalette, accent_min: f32) {
 
     // Accent colors (slots 1-6, 9-14) vs background: ≥ accent_min
     for slot in (1..=6).chain(9..=14) {
-        palette.slots[slot] = adjust_to_contrast(palette.slots[slot], bg, accent_min, l_direction);
+        enforce_slot(palette, slot, accent_min, l_direction);
     }
 
     // Foreground (slot 15) vs background: ≥ 7:1
-    palette.slots[15] =
-        adjust_to_contrast(palette.slots[15], bg, FOREGROUND_MIN_CONTRAST, l_direction);
+    enforce_slot(palette, 15, FOREGROUND_MIN_CONTRAST, l_direction);
     palette.foreground = palette.slots[15];
     palette.cursor_color = palette.foreground;
     palette.selection_fg = palette.foreground;
 
     // Bright black (slot 8) vs background: ≥ 3:1
-    palette.slots[8] =
-        adjust_to_contrast(palette.slots[8], bg, BRIGHT_BLACK_MIN_CONTRAST, l_direction);
+    enforce_slot(palette, 8, BRIGHT_BLACK_MIN_CONTRAST, l_direction);
+}
+
+/// Bring one slot up to `min_ratio` against the background, logging the
+/// adjustment if it needed one.
+fn enforce_slot(palette: &mut AnsiPalette, slot: usize, min_ratio: f32, l_step: f32) {
+    let bg = palette.background;
+    let before = palette.slots[slot];
+    let after = adjust_to_contrast(before, bg, min_ratio, l_step);
+    if after != before {
+        log::debug!(
+            "{}: {} -> {} to reach {min_ratio}:1 ({:.2}:1 -> {:.2}:1)",
+            SLOT_NAMES[slot],
+            before.to_hex(),
+            after.to_hex(),
+            Color::contrast_ratio(&before, &bg),
+            Color::contrast_ratio(&after, &bg)
+        );
+    }
+    palette.slots[slot] = after;
 }
 
 /// Iteratively adjust a color's Oklch lightness until it meets the contrast target.
+///
+/// Each step starts from the original chroma and hue, so gamut mapping at
+/// one lightness does not desaturate the steps after it.
 fn adjust_to_contrast(color: Color, background: Color, min_ratio: f32, l_step: f32) -> Color {
+    let oklch = color.to_oklch();
     let mut current = color;
-    for _ in 0..MAX_ITERATIONS {
+    for i in 1..=MAX_ITERATIONS {
         if Color::contrast_ratio(&current, &background) >= min_ratio {
             return current;
         }
-        current = current.adjust_lightness(l_step);
+        let mut next = oklch;
+        next.l = (oklch.l + l_step * i as f32).clamp(0.0, 1.0);
+        current = Color::from_oklch(next);
     }
     current
 }
@@ -0,0 +1,201 @@
+//! Region of interest (`--crop`, `--focus`): colors come only from the part
+//! of the wallpaper that shows behind the terminal, leaving out e.g. a
+//! bright moon in a corner.
+
+use std::fmt;
+
+use anyhow::{bail, Context, Result};
+use image::DynamicImage;
+use serde::{Deserialize, Serialize};
+
+/// A rectangle of the upright image, in its pixels (`--crop X,Y,W,H`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub struct Crop {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Crop {
+    /// Parse `X,Y,WIDTH,HEIGHT`.
+    pub fn parse(s: &str) -> Result<Crop> {
+        let values = s
+            .split(',')
+            .map(|v| v.trim().parse::<u32>())
+            .collect::<Result<Vec<_>, _>>()
+            .ok()
+            .filter(|values| values.len() == 4)
+            .with_context(|| format!("expected X,Y,WIDTH,HEIGHT in pixels, got '{s}'"))?;
+        let crop = Crop {
+            x: values[0],
+            y: values[1],
+            width: values[2],
+            height: values[3],
+        };
+        if crop.width == 0 || crop.height == 0 {
+            bail!("crop width and height must be positive, got '{s}'");
+        }
+        Ok(crop)
+    }
+}
+
+impl fmt::Display for Crop {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{},{},{}", self.x, self.y, self.width, self.height)
+    }
+}
+
+impl From<Crop> for String {
+    fn from(crop: Crop) -> Self {
+        crop.to_string()
+    }
+}
+
+impl TryFrom<String> for Crop {
+    type Error = anyhow::Error;
+
+    fn try_from(s: String) -> Result<Self> {
+        Crop::parse(&s)
+    }
+}
+
+/// A part of the image picked by position (`--focus`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Focus {
+    /// The middle half of the width and of the height.
+    Center,
+    /// The top half.
+    Top,
+    /// The bottom half.
+    Bottom,
+    /// The left half.
+    Left,
+    /// The right half.
+    Right,
+}
+
+impl Focus {
+    /// `[x, y, width, height]` as fractions of the image.
+    fn fractions(self) -> [f64; 4] {
+        match self {
+            Focus::Center => [0.25, 0.25, 0.5, 0.5],
+            Focus::Top => [0.0, 0.0, 1.0, 0.5],
+            Focus::Bottom => [0.0, 0.5, 1.0, 0.5],
+            Focus::Left => [0.0, 0.0, 0.5, 1.0],
+            Focus::Right => [0.5, 0.0, 0.5, 1.0],
+        }
+    }
+}
+
+/// The part of the image colors are extracted from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Region {
+    Crop(Crop),
+    Focus(Focus),
+}
+
+impl Region {
+    /// `[x, y, width, height]` as fractions of an upright `width`x`height`
+    /// image; a crop must lie inside it.
+    fn fractions(self, width: u32, height: u32) -> Result<[f64; 4]> {
+        let crop = match self {
+            Region::Focus(focus) => return Ok(focus.fractions()),
+            Region::Crop(crop) => crop,
+        };
+        if u64::from(crop.x) + u64::from(crop.width) > u64::from(width)
+            || u64::from(crop.y) + u64::from(crop.height) > u64::from(height)
+        {
+            bail!("--crop {crop} reaches outside the {width}x{height} image");
+        }
+        let (w, h) = (f64::from(width), f64::from(height));
+        Ok([
+            f64::from(crop.x) / w,
+            f64::from(crop.y) / h,
+            f64::from(crop.width) / w,
+            f64::from(crop.height) / h,
+        ])
+    }
+
+    /// Crop `img` to the region, given as part of an upright `width`x`height`
+    /// image; `img` may be a downscaled copy of it.
+    pub fn apply(self, img: &DynamicImage, width: u32, height: u32) -> Result<DynamicImage> {
+        let [fx, fy, fw, fh] = self.fractions(width, height)?;
+        let (w, h) = (img.width(), img.height());
+        if w == 0 || h == 0 {
+            return Ok(img.clone());
+        }
+        // At least one pixel, even for a sliver of a downscaled copy
+        let x = ((fx * f64::from(w)).round() as u32).min(w - 1);
+        let y = ((fy * f64::from(h)).round() as u32).min(h - 1);
+        let cw = ((fw * f64::from(w)).round() as u32).clamp(1, w - x);
+        let ch = ((fh * f64::from(h)).round() as u32).clamp(1, h - y);
+        log::debug!("extracting colors from {cw}x{ch} at {x},{y} of the {w}x{h} image");
+        Ok(img.crop_imm(x, y, cw, ch))
+    }
+
+    /// The region as an ffmpeg filter, for video frames.
+    pub fn ffmpeg_filter(self) -> String {
+        match self {
+            Region::Crop(crop) => {
+                format!("crop={}:{}:{}:{}", crop.width, crop.height, crop.x, crop.y)
+            }
+            Region::Focus(focus) => {
+                let [x, y, w, h] = focus.fractions();
+                format!("crop=iw*{w}:ih*{h}:iw*{x}:ih*{y}")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgb, RgbImage};
+
+    #[test]
+    fn parses_crop_rectangles() {
+        let crop = Crop::parse("10, 20,300,200").unwrap();
+        assert_eq!(
+            crop,
+            Crop {
+                x: 10,
+                y: 20,
+                width: 300,
+                height: 200
+            }
+        );
+        assert_eq!(crop.to_string(), "10,20,300,200");
+        assert!(Crop::parse("10,20,300").is_err());
+        assert!(Crop::parse("10,20,0,200").is_err());
+        assert!(Crop::parse("-1,20,300,200").is_err());
+    }
+
+    #[test]
+    fn crops_downscaled_copies_proportionally() {
+        // Left half red, right half blue
+        let img = DynamicImage::ImageRgb8(RgbImage::from_fn(100, 50, |x, _| {
+            if x < 50 {
+                Rgb([255, 0, 0])
+            } else {
+                Rgb([0, 0, 255])
+            }
+        }));
+
+        let right = Region::Focus(Focus::Right).apply(&img, 100, 50).unwrap();
+        assert_eq!(right.width(), 50);
+        assert!(right.to_rgb8().pixels().all(|p| p.0 == [0, 0, 255]));
+
+        // A crop of the 400x200 original applied to this quarter-size copy
+        let crop = Region::Crop(Crop::parse("0,0,200,200").unwrap());
+        let left = crop.apply(&img, 400, 200).unwrap();
+        assert_eq!((left.width(), left.height()), (50, 50));
+        assert!(left.to_rgb8().pixels().all(|p| p.0 == [255, 0, 0]));
+
+        let outside = Region::Crop(Crop::parse("300,0,200,200").unwrap());
+        let err = outside.apply(&img, 400, 200).unwrap_err();
+        assert!(err.to_string().contains("outside the 400x200 image"));
+    }
+}
@@ -16,11 +16,13 @@ pub fn detect_mode(pixels: &[Lab]) -> ThemeMode {
 
     let mean_l: f32 = pixels.iter().map(|p| p.l).sum::<f32>() / pixels.len() as f32;
 
-    if mean_l > LIGHT_THRESHOLD {
+    let mode = if mean_l > LIGHT_THRESHOLD {
         ThemeMode::Light
     } else {
         ThemeMode::Dark
-    }
+    };
+    log::debug!("mean lightness {mean_l:.1} (light above {LIGHT_THRESHOLD}): {mode:?} mode");
+    mode
 }
 
 #[cfg(test)]
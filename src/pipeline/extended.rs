@@ -0,0 +1,130 @@
+use super::assign::AnsiPalette;
+use crate::color::Color;
+
+/// Share of its lightness distance from the background a dim color keeps.
+const DIM_FACTOR: f32 = 0.66;
+
+/// Chroma multiplier for dim colors.
+const DIM_CHROMA: f32 = 0.8;
+
+/// Steps per axis of the 6x6x6 color cube (colors 16-231).
+const CUBE_STEPS: usize = 6;
+
+/// Colors in the grayscale ramp (232-255).
+const GRAY_STEPS: usize = 24;
+
+impl AnsiPalette {
+    /// Dim (faint) variants of colors 0-7: pulled toward the background in
+    /// Oklch lightness and slightly desaturated, keeping their hue.
+    pub fn dim_colors(&self) -> [Color; 8] {
+        std::array::from_fn(|i| dim(self.slots[i], self.background))
+    }
+
+    /// Dim variant of the foreground.
+    pub fn dim_foreground(&self) -> Color {
+        dim(self.foreground, self.background)
+    }
+
+    /// Colors 16-255 of the 256-color palette, derived from the theme.
+    ///
+    /// The 6x6x6 cube (16-231) interpolates in Oklab between the background at
+    /// its origin, the six accents at the corners the standard cube puts
+    /// them, and the foreground opposite the background, so cube colors
+    /// match the theme instead of the fixed xterm values. The grayscale ramp
+    /// (232-255) steps from background to foreground.
+    pub fn extended_colors(&self) -> Vec<Color> {
+        let p = self;
+        // Corners indexed by (red, green, blue) axis bits
+        let corner = |r: usize, g: usize, b: usize| match (r, g, b) {
+            (0, 0, 0) => p.background,
+            (1, 0, 0) => p.slots[1],
+            (0, 1, 0) => p.slots[2],
+            (1, 1, 0) => p.slots[3],
+            (0, 0, 1) => p.slots[4],
+            (1, 0, 1) => p.slots[5],
+            (0, 1, 1) => p.slots[6],
+            _ => p.foreground,
+        };
+        let max = (CUBE_STEPS - 1) as f32;
+        let mut colors = Vec::with_capacity(CUBE_STEPS.pow(3) + GRAY_STEPS);
+        for r in 0..CUBE_STEPS {
+            for g in 0..CUBE_STEPS {
+                for b in 0..CUBE_STEPS {
+                    let (tr, tg, tb) = (r as f32 / max, g as f32 / max, b as f32 / max);
+                    let face = |b: usize| {
+                        let low = corner(0, 0, b).mix(corner(1, 0, b), tr);
+                        let high = corner(0, 1, b).mix(corner(1, 1, b), tr);
+                        low.mix(high, tg)
+                    };
+                    colors.push(face(0).mix(face(1), tb));
+                }
+            }
+        }
+        for i in 0..GRAY_STEPS {
+            let t = (i + 1) as f32 / (GRAY_STEPS + 1) as f32;
+            colors.push(p.background.mix(p.foreground, t));
+        }
+        colors
+    }
+}
+
+fn dim(color: Color, background: Color) -> Color {
+    let bg = background.to_oklch();
+    let mut c = color.to_oklch();
+    c.l = bg.l + (c.l - bg.l) * DIM_FACTOR;
+    c.chroma *= DIM_CHROMA;
+    Color::from_oklch(c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::ThemeMode;
+    use crate::pipeline::assign::assign_slots;
+    use crate::pipeline::extract::ExtractedColor;
+
+    fn palette(mode: ThemeMode) -> AnsiPalette {
+        let colors = vec![
+            ExtractedColor {
+                color: Color::new(200, 60, 50),
+                weight: 0.5,
+            },
+            ExtractedColor {
+                color: Color::new(40, 120, 180),
+                weight: 0.5,
+            },
+        ];
+        assign_slots(&colors, mode)
+    }
+
+    #[test]
+    fn dim_colors_sit_between_background_and_color() {
+        for mode in [ThemeMode::Dark, ThemeMode::Light] {
+            let p = palette(mode);
+            let bg = p.background.to_oklch().l;
+            for (i, dim) in p.dim_colors().iter().enumerate() {
+                let (l, dim_l) = (p.slots[i].to_oklch().l, dim.to_oklch().l);
+                assert!((dim_l - bg).abs() <= (l - bg).abs() + 0.01, "slot {i}");
+            }
+            let fg = p.foreground.to_oklch().l;
+            let dim_fg = p.dim_foreground().to_oklch().l;
+            assert!((dim_fg - bg).abs() < (fg - bg).abs());
+        }
+    }
+
+    #[test]
+    fn extended_palette_matches_theme_corners() {
+        let p = palette(ThemeMode::Dark);
+        let colors = p.extended_colors();
+        assert_eq!(colors.len(), 240);
+        // Index into colors 16-255
+        let at = |index: usize| colors[index - 16];
+        assert!(at(16).delta_e(p.background) < 1.0);
+        assert!(at(231).delta_e(p.foreground) < 1.0);
+        assert!(at(196).delta_e(p.slots[1]) < 1.0); // 5,0,0: red
+        assert!(at(21).delta_e(p.slots[4]) < 1.0); // 0,0,5: blue
+        assert!(at(226).delta_e(p.slots[3]) < 1.0); // 5,5,0: yellow
+        let ramp: Vec<f32> = (232..=255).map(|i| at(i).to_oklch().l).collect();
+        assert!(ramp.windows(2).all(|w| w[1] > w[0]));
+    }
+}
@@ -1,61 +1,685 @@
+use std::io::BufReader;
 use std::path::Path;
+use std::time::Duration;
 
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
+use image::codecs::gif::GifDecoder;
+use image::codecs::png::PngDecoder;
+use image::codecs::webp::WebPDecoder;
 use image::imageops::FilterType;
-use kmeans_colors::get_kmeans_hamerly;
-use palette::{IntoColor, Lab, Srgb};
+use image::metadata::Orientation;
+use image::{
+    AnimationDecoder, DynamicImage, Frame, Frames, ImageDecoder, ImageError, ImageFormat,
+    ImageReader, ImageResult, Limits, Rgb, RgbImage,
+};
+use kmeans_colors::{get_kmeans_hamerly, Kmeans};
+use moxcms::{ColorProfile, DataColorSpace, Layout, ProfileText, TransformOptions};
+use palette::Lab;
+use serde::{Deserialize, Serialize};
 
-use crate::color::Color;
+use super::crop::Region;
+use super::formats;
+use crate::color::{srgb_to_lab_bulk, Color};
+use crate::error::{self, NuriError};
+use crate::watch::is_video;
 
 /// A color extracted from the image with its cluster weight.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExtractedColor {
     pub color: Color,
     pub weight: f32,
 }
 
-const MAX_DIM: u32 = 256;
 const MAX_ITER: usize = 20;
 const CONVERGE: f32 = 5.0;
 const DEDUP_THRESHOLD: f32 = 25.0; // ΔE² < 25 means ΔE < 5
 
+/// Longest side images are resized to unless `--max-dim` is given.
+pub const DEFAULT_MAX_DIM: u32 = 256;
+
+/// Default cap on decoded image memory (256 MiB).
+pub const DEFAULT_MAX_MEMORY: u64 = 256 * 1024 * 1024;
+
+/// Options controlling how an image is decoded before extraction.
+#[derive(Debug, Clone)]
+pub struct LoadOptions {
+    /// Maximum number of bytes the full decoded image may occupy. Larger PNGs
+    /// are decoded row by row and downscaled on the fly; other formats error.
+    pub max_memory: u64,
+    /// Frames of an animated GIF, PNG or WebP whose pixels are pooled.
+    pub frames: usize,
+    /// Take every n-th frame from the start instead of spreading `frames`
+    /// over the whole animation.
+    pub frame_stride: Option<usize>,
+    /// Take a video frame this often instead of spreading `frames` over the
+    /// whole video.
+    pub frame_interval: Option<Duration>,
+    /// Part of the image to keep (`--crop`, `--focus`); all of it when `None`.
+    pub region: Option<Region>,
+    /// Images are resized to fit within `max_dim` x `max_dim`.
+    pub max_dim: u32,
+    /// Filter for that resize. Streamed PNGs are always box-filtered.
+    pub resize_filter: ResizeFilter,
+}
+
+impl Default for LoadOptions {
+    fn default() -> Self {
+        Self {
+            max_memory: DEFAULT_MAX_MEMORY,
+            frames: DEFAULT_FRAMES,
+            frame_stride: None,
+            frame_interval: None,
+            region: None,
+            max_dim: DEFAULT_MAX_DIM,
+            resize_filter: ResizeFilter::default(),
+        }
+    }
+}
+
+/// Frames sampled from animated images unless `--frames` is given.
+pub const DEFAULT_FRAMES: usize = 8;
+
 /// Load an image, resize to fit within 256x256 (preserving aspect ratio),
 /// and convert all pixels to CIELAB space.
-pub fn load_and_prepare(path: &Path) -> Result<Vec<Lab>> {
-    let img = image::open(path).with_context(|| {
-        if !path.exists() {
-            format!("file not found: {}", path.display())
-        } else if path.metadata().map(|m| m.permissions().readonly()).unwrap_or(false)
-            || std::fs::File::open(path).is_err()
-        {
-            format!(
-                "permission denied: cannot read {}. Check file permissions.",
-                path.display()
-            )
-        } else {
-            format!(
-                "unsupported or corrupt image: {}. Supported formats: PNG, JPEG, WebP, BMP, TIFF, GIF",
-                path.display()
-            )
+pub fn load_and_prepare(path: &Path) -> error::Result<Vec<Lab>> {
+    load_and_prepare_with(path, &LoadOptions::default())
+}
+
+/// Load an image with explicit [`LoadOptions`].
+///
+/// Images whose decoded size fits within `options.max_memory` are decoded in
+/// full and resized with Lanczos3. Larger PNGs take a streaming path that
+/// box-filters rows into the target grid without ever holding the full image.
+pub fn load_and_prepare_with(path: &Path, options: &LoadOptions) -> error::Result<Vec<Lab>> {
+    Ok(load_image_with(path, options)?.pixels)
+}
+
+/// A resized image in CIELAB, row-major, for steps that need pixel neighbors.
+#[derive(Debug, Clone)]
+pub struct PreparedImage {
+    pub width: usize,
+    pub height: usize,
+    /// `frames` frames of `width` x `height`, one after another. Only
+    /// animated images have more than one.
+    pub pixels: Vec<Lab>,
+    pub frames: usize,
+}
+
+impl PreparedImage {
+    /// The pixels of each frame.
+    pub fn frames(&self) -> impl Iterator<Item = &[Lab]> {
+        self.pixels.chunks((self.width * self.height).max(1))
+    }
+}
+
+/// Like [`load_and_prepare_with`], keeping the image dimensions.
+pub fn load_image_with(path: &Path, options: &LoadOptions) -> error::Result<PreparedImage> {
+    Ok(prepare_frames(&decode_frames_with(path, options)?))
+}
+
+/// Like [`decode_image_with`], but animated GIF, PNG and WebP images give
+/// the frames picked by `options.frames` and `options.frame_stride`, and
+/// videos those picked by `options.frames` and `options.frame_interval`.
+/// Still images give one frame.
+pub fn decode_frames_with(path: &Path, options: &LoadOptions) -> error::Result<Vec<RgbImage>> {
+    check_max_dim(options.max_dim)?;
+    if is_video(path) {
+        return Ok(decode_video(path, options)?);
+    }
+    // HEIC, AVIF and JPEG XL are decoded as stills
+    if options.frames > 1 && formats::sniff(path).is_none() {
+        let (width, height) = ImageReader::open(path)
+            .and_then(|r| r.with_guessed_format())
+            .map_err(|e| NuriError::open(path, e))?
+            .into_dimensions()
+            .map_err(|e| NuriError::open(path, e))?;
+        // Frames are decoded one at a time, each as RGBA
+        if u64::from(width) * u64::from(height) * 4 <= options.max_memory {
+            if let Some(frames) = decode_animation(path, options)? {
+                return Ok(frames);
+            }
+        }
+    }
+    Ok(vec![decode_image_with(path, options)?])
+}
+
+/// The picked frames of an animated image, resized and converted like a
+/// still image, or `None` if it isn't animated.
+fn decode_animation(path: &Path, options: &LoadOptions) -> Result<Option<Vec<RgbImage>>> {
+    let Some((frames, icc)) = open_animation(path).ok().flatten() else {
+        return Ok(None);
+    };
+    let picked = pick_frames(frames, options.frames, options.frame_stride, |frame| {
+        let img = DynamicImage::ImageRgba8(frame.into_buffer());
+        fit(img, Orientation::NoTransforms, icc.as_deref(), options)
+    })
+    .with_context(|| format!("cannot decode the frames of {}", path.display()))?
+    .into_iter()
+    .collect::<Result<Vec<_>>>()?;
+    if picked.len() < 2 {
+        return Ok(None);
+    }
+    log::debug!("pooling the pixels of {} animation frames", picked.len());
+    Ok(Some(picked))
+}
+
+#[cfg(feature = "video")]
+fn decode_video(path: &Path, options: &LoadOptions) -> Result<Vec<RgbImage>> {
+    crate::video::decode_frames(path, options)
+}
+
+#[cfg(not(feature = "video"))]
+fn decode_video(path: &Path, _options: &LoadOptions) -> Result<Vec<RgbImage>> {
+    if !path.exists() {
+        return Err(NuriError::ImageNotFound { path: path.into() }.into());
+    }
+    Err(NuriError::MissingFeature {
+        path: path.into(),
+        format: "video".into(),
+        feature: "video",
+    }
+    .into())
+}
+
+/// Frame iterator and ICC profile for animated images; `None` for stills and
+/// formats that can't animate.
+fn open_animation(path: &Path) -> ImageResult<Option<(Frames<'static>, Option<Vec<u8>>)>> {
+    let format = ImageReader::open(path)?.with_guessed_format()?.format();
+    let file = BufReader::new(std::fs::File::open(path)?);
+    Ok(match format {
+        Some(ImageFormat::Gif) => Some((GifDecoder::new(file)?.into_frames(), None)),
+        Some(ImageFormat::Png) => {
+            let mut decoder = PngDecoder::new(file)?;
+            if !decoder.is_apng()? {
+                return Ok(None);
+            }
+            let icc = decoder.icc_profile()?;
+            Some((decoder.apng()?.into_frames(), icc))
+        }
+        Some(ImageFormat::WebP) => {
+            let mut decoder = WebPDecoder::new(file)?;
+            if !decoder.has_animation() {
+                return Ok(None);
+            }
+            let icc = decoder.icc_profile()?;
+            Some((decoder.into_frames(), icc))
         }
-    })?;
+        _ => None,
+    })
+}
+
+/// Choose up to `count` frames: every `stride`-th from the start, or spread
+/// evenly over the whole animation. Only chosen frames are passed to `keep`,
+/// and no more than `2 * count` of its results are held at a time.
+fn pick_frames<T>(
+    frames: impl IntoIterator<Item = ImageResult<Frame>>,
+    count: usize,
+    stride: Option<usize>,
+    mut keep: impl FnMut(Frame) -> T,
+) -> ImageResult<Vec<T>> {
+    let count = count.max(1);
+    let mut step = stride.unwrap_or(1).max(1);
+    let mut kept = Vec::new();
+    for (i, frame) in frames.into_iter().enumerate() {
+        // Every frame has to be decoded: later ones draw over earlier ones
+        let frame = frame?;
+        if i % step != 0 {
+            continue;
+        }
+        kept.push(keep(frame));
+        if stride.is_some() {
+            if kept.len() == count {
+                break;
+            }
+        } else if kept.len() == 2 * count {
+            // The animation is longer than expected; thin out and go on at
+            // twice the step
+            kept = kept.into_iter().step_by(2).collect();
+            step *= 2;
+        }
+    }
+    let n = count.min(kept.len());
+    let picks: Vec<usize> = (0..n).map(|j| j * kept.len() / n).collect();
+    Ok(kept
+        .into_iter()
+        .enumerate()
+        .filter(|(i, _)| picks.contains(i))
+        .map(|(_, frame)| frame)
+        .collect())
+}
 
-    let img = if img.width() > MAX_DIM || img.height() > MAX_DIM {
-        img.resize(MAX_DIM, MAX_DIM, FilterType::Lanczos3)
+/// The decoding half of [`load_image_with`]: the image resized to fit
+/// within `options.max_dim` squared, turned upright per its EXIF orientation and converted to
+/// sRGB from its embedded ICC profile.
+pub fn decode_image_with(path: &Path, options: &LoadOptions) -> error::Result<RgbImage> {
+    check_max_dim(options.max_dim)?;
+    if let Some(format) = formats::sniff(path) {
+        return Ok(formats::decode(path, format, options)?);
+    }
+    let reader = ImageReader::open(path)
+        .and_then(|r| r.with_guessed_format())
+        .map_err(|e| NuriError::open(path, e))?;
+    let format = reader.format();
+    let (width, height) = reader
+        .into_dimensions()
+        .map_err(|e| NuriError::open(path, e))?;
+
+    // Worst case for 8-bit images is RGBA: 4 bytes per pixel.
+    let estimated = u64::from(width) * u64::from(height) * 4;
+
+    if estimated <= options.max_memory {
+        Ok(decode_full(path, options)?)
+    } else if format == Some(ImageFormat::Png) {
+        Ok(decode_png_downscaled(path, options)?)
+    } else {
+        log::debug!(
+            "{} is {width}x{height}, ~{} MiB decoded",
+            path.display(),
+            estimated / (1024 * 1024)
+        );
+        Err(NuriError::ImageTooLarge {
+            path: path.into(),
+            max_memory: options.max_memory,
+        })
+    }
+}
+
+/// The conversion half of [`load_image_with`]: every pixel of a decoded
+/// image in CIELAB.
+pub fn prepare_image(rgb_img: &RgbImage) -> PreparedImage {
+    prepare_frames(std::slice::from_ref(rgb_img))
+}
+
+/// Like [`prepare_image`] for the frames of an animation, all the size of
+/// the first.
+pub fn prepare_frames(frames: &[RgbImage]) -> PreparedImage {
+    let first = &frames[0];
+    let mut pixels: Vec<Lab> = Vec::new();
+    for frame in frames {
+        if frame.dimensions() == first.dimensions() {
+            srgb_to_lab_bulk(frame.as_raw(), &mut pixels);
+        }
+    }
+
+    let (width, height) = (first.width() as usize, first.height() as usize);
+    PreparedImage {
+        width,
+        height,
+        frames: pixels.len() / (width * height).max(1),
+        pixels,
+    }
+}
+
+/// Decode the whole image (bounded by `options.max_memory`), crop it and
+/// resize with Lanczos3.
+fn decode_full(path: &Path, options: &LoadOptions) -> Result<RgbImage> {
+    let max_memory = options.max_memory;
+    let mut reader = ImageReader::open(path)
+        .and_then(|r| r.with_guessed_format())
+        .map_err(|e| NuriError::open(path, e))?;
+    let mut limits = Limits::default();
+    limits.max_alloc = Some(max_memory);
+    reader.limits(limits);
+
+    // Metadata is best effort: a bad EXIF block or profile shouldn't stop
+    // the image from being themed.
+    let decoded = reader.into_decoder().and_then(|mut decoder| {
+        let orientation = decoder.orientation().unwrap_or(Orientation::NoTransforms);
+        let icc = decoder.icc_profile().ok().flatten();
+        Ok((DynamicImage::from_decoder(decoder)?, orientation, icc))
+    });
+    let (img, orientation, icc) = match decoded {
+        Ok(decoded) => decoded,
+        Err(ImageError::Limits(_)) => {
+            return Err(NuriError::ImageTooLarge {
+                path: path.into(),
+                max_memory,
+            }
+            .into())
+        }
+        Err(e) => return Err(NuriError::open(path, e).into()),
+    };
+
+    fit(img, orientation, icc.as_deref(), options)
+}
+
+/// A decoded image made ready for extraction: cropped to `options.region`
+/// if set, downscaled, upright and in sRGB.
+pub(crate) fn fit(
+    mut img: DynamicImage,
+    orientation: Orientation,
+    icc: Option<&[u8]>,
+    options: &LoadOptions,
+) -> Result<RgbImage> {
+    let Some(region) = options.region else {
+        return Ok(upright_srgb(downscale(img, options), orientation, icc));
+    };
+    // Crops are in the coordinates of the image as displayed, so it's
+    // turned upright first, at full size
+    img.apply_orientation(orientation);
+    let (width, height) = (img.width(), img.height());
+    let cropped = region.apply(&img, width, height)?;
+    Ok(upright_srgb(
+        downscale(cropped, options),
+        Orientation::NoTransforms,
+        icc,
+    ))
+}
+
+/// Resize with `options.resize_filter` to fit within `options.max_dim`
+/// squared.
+pub(crate) fn downscale(img: DynamicImage, options: &LoadOptions) -> DynamicImage {
+    let max_dim = options.max_dim;
+    if img.width() > max_dim || img.height() > max_dim {
+        img.resize(max_dim, max_dim, options.resize_filter.filter_type())
     } else {
         img
+    }
+}
+
+/// Apply `orientation` and convert from the `icc` profile to sRGB. Both are
+/// done after downscaling, on at most `max_dim` squared pixels.
+pub(crate) fn upright_srgb(
+    mut img: DynamicImage,
+    orientation: Orientation,
+    icc: Option<&[u8]>,
+) -> RgbImage {
+    img.apply_orientation(orientation);
+    let mut rgb = img.into_rgb8();
+    if let Some(icc) = icc {
+        convert_to_srgb(&mut rgb, icc);
+    }
+    rgb
+}
+
+/// Convert pixels encoded in an embedded ICC profile (Display P3, Adobe RGB,
+/// ...) to sRGB, so wide-gamut photos keep their hues. Profiles that can't
+/// be applied leave the pixels as they are, i.e. read as sRGB.
+fn convert_to_srgb(img: &mut RgbImage, icc: &[u8]) {
+    let profile = match ColorProfile::new_from_slice(icc) {
+        Ok(profile) if profile.color_space == DataColorSpace::Rgb => profile,
+        Ok(_) => {
+            log::debug!("ignoring the embedded non-RGB ICC profile");
+            return;
+        }
+        Err(e) => {
+            log::warn!("ignoring the embedded ICC profile ({e}); reading colors as sRGB");
+            return;
+        }
     };
-    let rgb_img = img.to_rgb8();
+    let converted = profile
+        .create_transform_8bit(
+            Layout::Rgb,
+            &ColorProfile::new_srgb(),
+            Layout::Rgb,
+            TransformOptions::default(),
+        )
+        .and_then(|transform| {
+            let src = img.as_raw().clone();
+            transform.transform(&src, img)
+        });
+    match converted {
+        Ok(()) => log::debug!(
+            "converted colors from the embedded ICC profile ({}) to sRGB",
+            profile_name(&profile)
+        ),
+        Err(e) => log::warn!("cannot apply the embedded ICC profile ({e}); reading colors as sRGB"),
+    }
+}
 
-    let pixels: Vec<Lab> = rgb_img
-        .pixels()
-        .map(|p| {
-            let srgb: Srgb<f32> = Srgb::new(p[0], p[1], p[2]).into_format();
-            srgb.into_color()
-        })
-        .collect();
+/// The profile's description, e.g. `Display P3`.
+fn profile_name(profile: &ColorProfile) -> &str {
+    match &profile.description {
+        Some(ProfileText::PlainString(name)) => name,
+        Some(ProfileText::Localizable(names)) => names.first().map_or("unnamed", |n| &n.value),
+        Some(ProfileText::Description(description)) => &description.ascii_string,
+        None => "unnamed",
+    }
+}
+
+/// `max_dim` if images can be resized to it, else an error: nothing fits in
+/// 0 pixels.
+pub fn check_max_dim(max_dim: u32) -> Result<u32> {
+    if max_dim == 0 {
+        bail!("--max-dim must be at least 1 pixel");
+    }
+    Ok(max_dim)
+}
+
+/// Compute output dimensions that fit within `max_dim` while preserving aspect ratio.
+fn downscaled_dimensions(width: u32, height: u32, max_dim: u32) -> (u32, u32) {
+    if width <= max_dim && height <= max_dim {
+        return (width, height);
+    }
+    let scale = (max_dim as f64 / width as f64).min(max_dim as f64 / height as f64);
+    let w = ((width as f64 * scale).round() as u32).clamp(1, max_dim);
+    let h = ((height as f64 * scale).round() as u32).clamp(1, max_dim);
+    (w, h)
+}
+
+/// Decode a PNG row by row, box-filtering each source pixel into its target
+/// cell. Memory use is one source row plus the (small) output accumulator.
+fn decode_png_downscaled(path: &Path, options: &LoadOptions) -> Result<RgbImage> {
+    let (rgb, (width, height)) =
+        stream_png_downscaled(path, options.max_dim).map_err(|e| NuriError::open(path, e))?;
+    let Some(region) = options.region else {
+        return Ok(rgb);
+    };
+    // The crop is in full-size pixels; map it onto the downscaled copy
+    let cropped = region.apply(&DynamicImage::ImageRgb8(rgb), width, height)?;
+    Ok(cropped.into_rgb8())
+}
+
+/// The streaming half of [`decode_png_downscaled`], before cropping, with
+/// the full size of the upright image.
+fn stream_png_downscaled(path: &Path, max_dim: u32) -> Result<(RgbImage, (u32, u32))> {
+    let file = std::fs::File::open(path)?;
+    let mut decoder = png::Decoder::new(BufReader::new(file));
+    decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+    let mut reader = decoder.read_info()?;
+
+    let info = reader.info();
+    let (width, height) = (info.width, info.height);
+    let orientation = info
+        .exif_metadata
+        .as_deref()
+        .and_then(Orientation::from_exif_chunk)
+        .unwrap_or(Orientation::NoTransforms);
+    let icc = info.icc_profile.as_deref().map(<[u8]>::to_vec);
+    if info.interlaced {
+        bail!(
+            "interlaced PNGs cannot be streamed; re-save without interlacing or raise --max-memory"
+        );
+    }
+    let channels = reader.output_color_type().0.samples();
+    let (out_w, out_h) = downscaled_dimensions(width, height, max_dim);
+
+    // Per-cell channel sums and sample counts.
+    let mut sums = vec![[0u64; 3]; (out_w * out_h) as usize];
+    let mut counts = vec![0u64; (out_w * out_h) as usize];
+
+    let mut y: u32 = 0;
+    while let Some(row) = reader.next_row()? {
+        let oy = (u64::from(y) * u64::from(out_h) / u64::from(height)) as u32;
+        for (x, px) in row.data().chunks_exact(channels).enumerate() {
+            let ox = (x as u64 * u64::from(out_w) / u64::from(width)) as u32;
+            let idx = (oy * out_w + ox) as usize;
+            let rgb = if channels < 3 {
+                [px[0]; 3]
+            } else {
+                [px[0], px[1], px[2]]
+            };
+            for (sum, v) in sums[idx].iter_mut().zip(rgb) {
+                *sum += u64::from(v);
+            }
+            counts[idx] += 1;
+        }
+        y += 1;
+    }
+
+    let img = RgbImage::from_fn(out_w, out_h, |x, y| {
+        let idx = (y * out_w + x) as usize;
+        let n = counts[idx].max(1);
+        let [r, g, b] = sums[idx];
+        Rgb([
+            ((r + n / 2) / n) as u8,
+            ((g + n / 2) / n) as u8,
+            ((b + n / 2) / n) as u8,
+        ])
+    });
+    let upright = match orientation {
+        Orientation::Rotate90
+        | Orientation::Rotate270
+        | Orientation::Rotate90FlipH
+        | Orientation::Rotate270FlipH => (height, width),
+        _ => (width, height),
+    };
+    Ok((
+        upright_srgb(DynamicImage::ImageRgb8(img), orientation, icc.as_deref()),
+        upright,
+    ))
+}
+
+/// Filter for resizing images down to `--max-dim`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ResizeFilter {
+    /// Nearest neighbor: by far the fastest, for batch runs.
+    Nearest,
+    /// Bilinear.
+    Triangle,
+    /// Lanczos with a window of 3: the sharpest, and the slowest.
+    #[default]
+    Lanczos,
+}
+
+impl ResizeFilter {
+    fn filter_type(self) -> FilterType {
+        match self {
+            ResizeFilter::Nearest => FilterType::Nearest,
+            ResizeFilter::Triangle => FilterType::Triangle,
+            ResizeFilter::Lanczos => FilterType::Lanczos3,
+        }
+    }
+}
+
+/// How pixels are subsampled before K-means to bound clustering cost.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Sampling {
+    /// Cluster every pixel.
+    #[default]
+    All,
+    /// Every n-th pixel, spread evenly over the image.
+    Stride,
+    /// Uniform random subset.
+    Random,
+    /// Random subset favoring saturated pixels, so small vivid regions survive.
+    Saturation,
+}
+
+/// Seed for K-means initialization and pixel sampling unless `--seed` is given.
+pub const DEFAULT_SEED: u64 = 42;
+
+/// Default number of pixels kept by the subsampling strategies.
+pub const DEFAULT_SAMPLE_SIZE: usize = 16_384;
+
+/// Clusters extracted unless `-k/--colors` says otherwise.
+pub const DEFAULT_COLORS: usize = 16;
+
+/// Fewest clusters `-k/--colors` accepts: a background, a foreground and six
+/// accent hues each want a color of their own to start from.
+pub const MIN_COLORS: usize = 8;
+
+/// Most clusters `-k/--colors` accepts; past this they only split shades of
+/// colors already found.
+pub const MAX_COLORS: usize = 64;
+
+/// `k` if it is within [`MIN_COLORS`] and [`MAX_COLORS`], else an error
+/// saying why it isn't.
+pub fn check_colors(k: usize) -> Result<usize> {
+    if k < MIN_COLORS {
+        bail!(
+            "need at least {MIN_COLORS} colors, got {k}: a theme needs a background, a foreground \
+             and six accent hues, and with fewer clusters most accents are synthesized instead \
+             of taken from the image"
+        );
+    }
+    if k > MAX_COLORS {
+        bail!(
+            "need at most {MAX_COLORS} colors, got {k}: more clusters only split shades of the \
+             same colors and slow extraction down"
+        );
+    }
+    Ok(k)
+}
+
+/// Subsample `pixels` to at most `size` entries using `sampling`.
+///
+/// Returns all pixels unchanged for [`Sampling::All`] or when there are
+/// already `size` or fewer. Random strategies are deterministic for a seed.
+pub fn sample_pixels(pixels: &[Lab], sampling: Sampling, size: usize, seed: u64) -> Vec<Lab> {
+    if sampling == Sampling::All || size == 0 || pixels.len() <= size {
+        return pixels.to_vec();
+    }
+    let mut rng = SplitMix64(seed);
+    match sampling {
+        Sampling::All => pixels.to_vec(),
+        Sampling::Stride => {
+            let step = pixels.len() as f64 / size as f64;
+            (0..size)
+                .map(|i| pixels[(i as f64 * step) as usize])
+                .collect()
+        }
+        Sampling::Random => {
+            // Partial Fisher-Yates: the first `size` slots become the sample.
+            let mut indices: Vec<usize> = (0..pixels.len()).collect();
+            for i in 0..size {
+                let j = i + rng.below(indices.len() - i);
+                indices.swap(i, j);
+            }
+            indices[..size].iter().map(|&i| pixels[i]).collect()
+        }
+        Sampling::Saturation => {
+            // Weighted sampling without replacement (Efraimidis-Spirakis):
+            // keep the `size` largest keys u^(1/w), with w = LAB chroma + 1.
+            let mut keyed: Vec<(f64, usize)> = pixels
+                .iter()
+                .enumerate()
+                .map(|(i, p)| {
+                    let weight = (p.a as f64).hypot(p.b as f64) + 1.0;
+                    (rng.unit().powf(1.0 / weight), i)
+                })
+                .collect();
+            keyed.select_nth_unstable_by(size - 1, |a, b| b.0.total_cmp(&a.0));
+            let mut chosen: Vec<usize> = keyed[..size].iter().map(|&(_, i)| i).collect();
+            chosen.sort_unstable();
+            chosen.into_iter().map(|i| pixels[i]).collect()
+        }
+    }
+}
+
+/// Small seeded PRNG (SplitMix64) for reproducible sampling.
+struct SplitMix64(u64);
 
-    Ok(pixels)
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform integer in `0..n`.
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    /// Uniform float in `(0, 1]`.
+    fn unit(&mut self) -> f64 {
+        ((self.next() >> 11) + 1) as f64 / (1u64 << 53) as f64
+    }
 }
 
 /// Run K-means on LAB pixels to extract dominant colors.
@@ -63,12 +687,12 @@ pub fn load_and_prepare(path: &Path) -> Result<Vec<Lab>> {
 /// Returns deduplicated colors sorted by weight (descending).
 /// Uses Hamerly's algorithm with K-means++ initialization.
 pub fn extract_colors(pixels: &[Lab], k: usize) -> Vec<ExtractedColor> {
-    extract_colors_with_seed(pixels, k, 42)
+    extract_colors_with_seed(pixels, k, DEFAULT_SEED)
 }
 
 /// Run K-means with an explicit seed (for TUI regeneration).
 pub fn extract_colors_with_seed(pixels: &[Lab], k: usize, seed: u64) -> Vec<ExtractedColor> {
-    let result = get_kmeans_hamerly(k, MAX_ITER, CONVERGE, false, pixels, seed);
+    let result = kmeans(pixels, k, seed);
 
     let total = pixels.len() as f32;
 
@@ -78,7 +702,7 @@ pub fn extract_colors_with_seed(pixels: &[Lab], k: usize, seed: u64) -> Vec<Extr
         counts[idx as usize] += 1;
     }
 
-    let mut colors: Vec<ExtractedColor> = result
+    let colors: Vec<ExtractedColor> = result
         .centroids
         .iter()
         .enumerate()
@@ -89,6 +713,23 @@ pub fn extract_colors_with_seed(pixels: &[Lab], k: usize, seed: u64) -> Vec<Extr
         })
         .collect();
 
+    finish(colors)
+}
+
+/// K-means centroids and each pixel's cluster: on the GPU with the `gpu`
+/// feature when it's worth it and an adapter is available, otherwise
+/// Hamerly's algorithm on the CPU.
+fn kmeans(pixels: &[Lab], k: usize, seed: u64) -> Kmeans<Lab> {
+    #[cfg(feature = "gpu")]
+    if let Some(result) = super::gpu::kmeans(k, MAX_ITER, CONVERGE, pixels, seed) {
+        return result;
+    }
+    get_kmeans_hamerly(k, MAX_ITER, CONVERGE, false, pixels, seed)
+}
+
+/// Deduplicate near-identical colors (ΔE < 5) and sort by weight, descending.
+/// Shared by every quantizer.
+pub(crate) fn finish(mut colors: Vec<ExtractedColor>) -> Vec<ExtractedColor> {
     // Deduplicate centroids with ΔE < 5 (squared distance < 25)
     deduplicate(&mut colors);
 
@@ -98,6 +739,68 @@ pub fn extract_colors_with_seed(pixels: &[Lab], k: usize, seed: u64) -> Vec<Extr
     colors
 }
 
+/// Colors closer than this (CIE76 delta-E) are merged unless
+/// `--merge-delta-e` is given. Every quantizer already merges this close.
+pub const DEFAULT_MERGE_DELTA_E: f32 = 5.0;
+
+/// Clean up the candidate colors handed to slot assignment: merge each color
+/// within `merge_delta_e` of a heavier one into their weighted mean, then
+/// drop colors covering less than `min_weight` of the image. The heaviest
+/// color always stays. Returned sorted by weight, descending.
+pub fn refine(
+    mut colors: Vec<ExtractedColor>,
+    merge_delta_e: f32,
+    min_weight: f32,
+) -> Vec<ExtractedColor> {
+    colors.sort_by(|a, b| b.weight.total_cmp(&a.weight));
+    let found = colors.len();
+    let mut merged: Vec<(Lab, f32)> = Vec::with_capacity(found);
+    for color in colors {
+        let lab = color.color.to_lab();
+        let near = merged.iter_mut().find(|(kept, _)| {
+            let delta_e_sq =
+                (kept.l - lab.l).powi(2) + (kept.a - lab.a).powi(2) + (kept.b - lab.b).powi(2);
+            delta_e_sq < merge_delta_e * merge_delta_e
+        });
+        match near {
+            Some((kept, weight)) => {
+                let total = *weight + color.weight;
+                let t = if total > 0.0 {
+                    color.weight / total
+                } else {
+                    0.0
+                };
+                kept.l += (lab.l - kept.l) * t;
+                kept.a += (lab.a - kept.a) * t;
+                kept.b += (lab.b - kept.b) * t;
+                *weight = total;
+            }
+            None => merged.push((lab, color.weight)),
+        }
+    }
+    let mut refined: Vec<ExtractedColor> = merged
+        .into_iter()
+        .map(|(lab, weight)| ExtractedColor {
+            color: Color::from_lab(lab),
+            weight,
+        })
+        .collect();
+    refined.sort_by(|a, b| b.weight.total_cmp(&a.weight));
+    let distinct = refined.len();
+    let mut heaviest = true;
+    refined.retain(|c| std::mem::take(&mut heaviest) || c.weight >= min_weight);
+    if refined.len() < found {
+        log::debug!(
+            "merged {} near-duplicate colors (delta-E < {merge_delta_e}) and dropped {} \
+             below {}% of the image",
+            found - distinct,
+            distinct - refined.len(),
+            min_weight * 100.0
+        );
+    }
+    refined
+}
+
 /// Merge colors that are too similar (ΔE < 5 in LAB space).
 /// Keeps the first color and accumulates the weight.
 fn deduplicate(colors: &mut Vec<ExtractedColor>) {
@@ -124,6 +827,8 @@ fn deduplicate(colors: &mut Vec<ExtractedColor>) {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::pipeline::crop::{Crop, Focus};
+    use palette::{IntoColor, Srgb};
     use std::path::PathBuf;
 
     fn fixture_path(name: &str) -> PathBuf {
@@ -162,10 +867,55 @@ mod tests {
         assert_eq!(pixels.len(), 256 * 128);
     }
 
+    #[test]
+    fn max_dim_and_filter_are_configurable() {
+        let path = fixture_path("512x256_max_dim_test.png");
+        create_test_image_gradient(&path, 512, 256);
+
+        for resize_filter in [ResizeFilter::Nearest, ResizeFilter::Triangle] {
+            for max_memory in [DEFAULT_MAX_MEMORY, 1] {
+                let options = LoadOptions {
+                    max_memory,
+                    max_dim: 64,
+                    resize_filter,
+                    ..Default::default()
+                };
+                assert_eq!(
+                    decode_image_with(&path, &options).unwrap().dimensions(),
+                    (64, 32)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn zero_max_dim_is_an_error() {
+        let path = fixture_path("zero_max_dim_test.png");
+        create_test_image_gradient(&path, 64, 32);
+        for max_memory in [DEFAULT_MAX_MEMORY, 1] {
+            let options = LoadOptions {
+                max_memory,
+                max_dim: 0,
+                ..Default::default()
+            };
+            let err = load_image_with(&path, &options).unwrap_err();
+            assert!(matches!(err, NuriError::Other(_)), "{err:?}");
+            assert!(err.to_string().contains("--max-dim"), "{err}");
+            assert!(decode_image_with(&path, &options).is_err());
+        }
+        let options = crate::pipeline::PaletteOptions {
+            max_dim: 0,
+            ..Default::default()
+        };
+        assert!(crate::pipeline::AnsiPalette::from_image(&path, &options).is_err());
+    }
+
     #[test]
     fn load_file_not_found() {
         let result = load_and_prepare(Path::new("/nonexistent/image.png"));
-        let err = result.unwrap_err().to_string();
+        let err = result.unwrap_err();
+        assert!(matches!(err, NuriError::ImageNotFound { .. }), "{err:?}");
+        let err = err.to_string();
         assert!(
             err.contains("file not found") || err.contains("No such file"),
             "expected file-not-found error, got: {err}"
@@ -179,7 +929,12 @@ mod tests {
 
         let result = load_and_prepare(&path);
         assert!(result.is_err());
-        let err = result.unwrap_err().to_string();
+        let err = result.unwrap_err();
+        assert!(
+            matches!(err, NuriError::UnsupportedFormat { .. }),
+            "{err:?}"
+        );
+        let err = err.to_string();
         assert!(
             err.contains("unsupported") || err.contains("Unsupported"),
             "expected unsupported format error, got: {err}"
@@ -197,8 +952,284 @@ mod tests {
         }
     }
 
+    #[test]
+    fn oversized_png_streams_within_cap() {
+        let path = fixture_path("600x300_stream_test.png");
+        create_test_image_solid(&path, 600, 300, [200, 40, 40]);
+
+        // Cap well below the 600*300*4 byte estimate forces the streaming path
+        let options = LoadOptions {
+            max_memory: 1024,
+            ..Default::default()
+        };
+        let pixels = load_and_prepare_with(&path, &options).unwrap();
+        assert_eq!(pixels.len(), 256 * 128);
+
+        let expected = Color::new(200, 40, 40);
+        for lab in &pixels {
+            assert_eq!(Color::from_lab(*lab), expected);
+        }
+    }
+
+    #[test]
+    fn streaming_matches_full_decode_dimensions() {
+        let path = fixture_path("300x500_stream_test.png");
+        create_test_image_gradient(&path, 300, 500);
+
+        let full = load_and_prepare(&path).unwrap();
+        let streamed = load_and_prepare_with(
+            &path,
+            &LoadOptions {
+                max_memory: 1024,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(full.len(), streamed.len());
+    }
+
+    #[test]
+    fn oversized_non_png_errors() {
+        let path = fixture_path("300x300_cap_test.bmp");
+        create_test_image_solid(&path, 300, 300, [10, 10, 10]);
+
+        let err = load_and_prepare_with(
+            &path,
+            &LoadOptions {
+                max_memory: 1024,
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+        assert!(matches!(err, NuriError::ImageTooLarge { .. }), "{err:?}");
+        let err = err.to_string();
+        assert!(
+            err.contains("memory cap"),
+            "expected memory cap error, got: {err}"
+        );
+    }
+
+    /// Big-endian EXIF block holding just an orientation tag.
+    fn exif_orientation(value: u16) -> Vec<u8> {
+        let mut exif = b"MM\0\x2a\0\0\0\x08\0\x01\x01\x12\0\x03\0\0\0\x01".to_vec();
+        exif.extend_from_slice(&value.to_be_bytes());
+        exif.extend_from_slice(&[0; 6]);
+        exif
+    }
+
+    fn create_png_with(path: &Path, img: &RgbImage, exif: Option<Vec<u8>>, icc: Option<Vec<u8>>) {
+        let mut info = png::Info::with_size(img.width(), img.height());
+        info.color_type = png::ColorType::Rgb;
+        info.bit_depth = png::BitDepth::Eight;
+        info.exif_metadata = exif.map(Into::into);
+        info.icc_profile = icc.map(Into::into);
+        let file = std::fs::File::create(path).unwrap();
+        let mut writer = png::Encoder::with_info(file, info)
+            .unwrap()
+            .write_header()
+            .unwrap();
+        writer.write_image_data(img.as_raw()).unwrap();
+    }
+
+    #[test]
+    fn exif_orientation_turns_image_upright() {
+        let path = fixture_path("4x2_rotated_test.png");
+        // Left half red, right half blue; orientation 6 means rotate 90°
+        // clockwise to display, putting red on top
+        let img = RgbImage::from_fn(4, 2, |x, _| {
+            if x < 2 {
+                Rgb([255, 0, 0])
+            } else {
+                Rgb([0, 0, 255])
+            }
+        });
+        create_png_with(&path, &img, Some(exif_orientation(6)), None);
+
+        for options in [
+            LoadOptions::default(),
+            LoadOptions {
+                max_memory: 1,
+                ..Default::default()
+            },
+        ] {
+            let upright = decode_image_with(&path, &options).unwrap();
+            assert_eq!(upright.dimensions(), (2, 4));
+            assert_eq!(upright.get_pixel(0, 0), &Rgb([255, 0, 0]));
+            assert_eq!(upright.get_pixel(1, 3), &Rgb([0, 0, 255]));
+        }
+    }
+
+    #[test]
+    fn crop_applies_to_the_upright_image() {
+        let path = fixture_path("4x2_rotated_crop_test.png");
+        // Upright, red is the top half and blue the bottom half
+        let img = RgbImage::from_fn(4, 2, |x, _| {
+            if x < 2 {
+                Rgb([255, 0, 0])
+            } else {
+                Rgb([0, 0, 255])
+            }
+        });
+        create_png_with(&path, &img, Some(exif_orientation(6)), None);
+
+        for max_memory in [DEFAULT_MAX_MEMORY, 1] {
+            let decode = |region| {
+                let options = LoadOptions {
+                    max_memory,
+                    region: Some(region),
+                    ..Default::default()
+                };
+                decode_image_with(&path, &options)
+            };
+            let bottom = decode(Region::Crop(Crop::parse("0,2,2,2").unwrap())).unwrap();
+            assert_eq!(bottom.dimensions(), (2, 2));
+            assert!(bottom.pixels().all(|p| p.0 == [0, 0, 255]));
+
+            let top = decode(Region::Focus(Focus::Top)).unwrap();
+            assert!(top.pixels().all(|p| p.0 == [255, 0, 0]));
+
+            assert!(decode(Region::Crop(Crop::parse("0,0,4,2").unwrap())).is_err());
+        }
+    }
+
+    #[test]
+    fn display_p3_pixels_are_converted_to_srgb() {
+        let path = fixture_path("2x2_display_p3_test.png");
+        let img = RgbImage::from_fn(2, 2, |_, _| Rgb([200, 120, 100]));
+        let p3 = ColorProfile::new_display_p3().encode().unwrap();
+        create_png_with(&path, &img, None, Some(p3));
+
+        // The same color needs more extreme channel values in the smaller
+        // sRGB gamut
+        for options in [
+            LoadOptions::default(),
+            LoadOptions {
+                max_memory: 1,
+                ..Default::default()
+            },
+        ] {
+            let srgb = decode_image_with(&path, &options).unwrap();
+            let [r, _, b] = srgb.get_pixel(0, 0).0;
+            assert!(r > 200 && b < 100, "got {:?}", srgb.get_pixel(0, 0));
+        }
+    }
+
+    fn frame(i: u8) -> ImageResult<Frame> {
+        Ok(Frame::new(image::RgbaImage::from_pixel(
+            1,
+            1,
+            image::Rgba([i, 0, 0, 255]),
+        )))
+    }
+
+    fn picked(total: u8, count: usize, stride: Option<usize>) -> Vec<u8> {
+        pick_frames((0..total).map(frame), count, stride, |f| {
+            f.buffer()[(0, 0)][0]
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn frames_are_spread_over_the_animation() {
+        assert_eq!(picked(100, 4, None), [0, 16, 48, 80]);
+        assert_eq!(picked(8, 4, None), [0, 2, 4, 6]);
+        assert_eq!(picked(3, 8, None), [0, 1, 2]);
+        assert_eq!(picked(100, 4, Some(3)), [0, 3, 6, 9]);
+        assert_eq!(picked(5, 4, Some(3)), [0, 3]);
+    }
+
+    #[test]
+    fn animated_gif_pools_its_frames() {
+        use image::codecs::gif::GifEncoder;
+
+        let path = fixture_path("3_frame_test.gif");
+        let colors = [[220, 30, 30], [30, 200, 30], [30, 30, 220]];
+        let mut encoder = GifEncoder::new(std::fs::File::create(&path).unwrap());
+        for [r, g, b] in colors {
+            let buffer = image::RgbaImage::from_pixel(8, 8, image::Rgba([r, g, b, 255]));
+            encoder.encode_frame(Frame::new(buffer)).unwrap();
+        }
+        drop(encoder);
+
+        let image = load_image_with(&path, &LoadOptions::default()).unwrap();
+        assert_eq!((image.width, image.height, image.frames), (8, 8, 3));
+        let firsts: Vec<Color> = image.frames().map(|f| Color::from_lab(f[0])).collect();
+        for (color, [r, g, b]) in firsts.iter().zip(colors) {
+            // GIF palettes quantize slightly
+            assert!(color.delta_e(Color::new(r, g, b)) < 5.0, "{color:?}");
+        }
+
+        let first_only = LoadOptions {
+            frames: 1,
+            ..Default::default()
+        };
+        assert_eq!(load_image_with(&path, &first_only).unwrap().frames, 1);
+    }
+
     // --- extract_colors tests ---
 
+    // --- sampling tests ---
+
+    fn gradient_pixels(n: usize) -> Vec<Lab> {
+        (0..n)
+            .map(|i| Lab::new(50.0, (i % 100) as f32 - 50.0, 0.0))
+            .collect()
+    }
+
+    #[test]
+    fn sampling_all_keeps_every_pixel() {
+        let pixels = gradient_pixels(1000);
+        assert_eq!(sample_pixels(&pixels, Sampling::All, 10, 42).len(), 1000);
+    }
+
+    #[test]
+    fn sampling_returns_requested_size() {
+        let pixels = gradient_pixels(1000);
+        for sampling in [Sampling::Stride, Sampling::Random, Sampling::Saturation] {
+            assert_eq!(
+                sample_pixels(&pixels, sampling, 100, 42).len(),
+                100,
+                "{sampling:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn sampling_small_input_is_unchanged() {
+        let pixels = gradient_pixels(50);
+        for sampling in [Sampling::Stride, Sampling::Random, Sampling::Saturation] {
+            assert_eq!(sample_pixels(&pixels, sampling, 100, 42).len(), 50);
+        }
+    }
+
+    #[test]
+    fn random_sampling_is_deterministic_per_seed() {
+        let pixels = gradient_pixels(1000);
+        let a = sample_pixels(&pixels, Sampling::Random, 100, 7);
+        let b = sample_pixels(&pixels, Sampling::Random, 100, 7);
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn saturation_sampling_favors_chromatic_pixels() {
+        // 90% gray, 10% strongly saturated
+        let pixels: Vec<Lab> = (0..10_000)
+            .map(|i| {
+                if i % 10 == 0 {
+                    Lab::new(50.0, 80.0, 0.0)
+                } else {
+                    Lab::new(50.0, 0.0, 0.0)
+                }
+            })
+            .collect();
+        let sample = sample_pixels(&pixels, Sampling::Saturation, 1000, 42);
+        let saturated = sample.iter().filter(|p| p.a > 40.0).count();
+        assert!(
+            saturated > 300,
+            "expected saturated pixels to be over-represented, got {saturated}/1000"
+        );
+    }
+
     #[test]
     fn uniform_image_produces_one_dominant_color() {
         // All pixels are the same red color
@@ -307,6 +1338,36 @@ mod tests {
         );
     }
 
+    #[test]
+    fn refine_merges_near_duplicates_and_drops_specks() {
+        let color = |r, g, b, weight| ExtractedColor {
+            color: Color::new(r, g, b),
+            weight,
+        };
+        let colors = vec![
+            color(200, 40, 40, 0.5),
+            color(190, 45, 45, 0.3),
+            color(30, 60, 200, 0.195),
+            color(250, 250, 250, 0.005),
+        ];
+
+        // The default threshold keeps all of these apart
+        assert_eq!(refine(colors.clone(), DEFAULT_MERGE_DELTA_E, 0.0).len(), 4);
+
+        let refined = refine(colors, 10.0, 0.01);
+        assert_eq!(refined.len(), 2);
+        assert!((refined[0].weight - 0.8).abs() < 1e-6);
+        // The merged red sits between the two, nearer the heavier one
+        let red = refined[0].color;
+        assert!(red.delta_e(Color::new(200, 40, 40)) < red.delta_e(Color::new(190, 45, 45)));
+        assert_eq!(refined[1].color, Color::new(30, 60, 200));
+
+        // A floor above every color still leaves the heaviest
+        let only = refine(refined, DEFAULT_MERGE_DELTA_E, 0.9);
+        assert_eq!(only.len(), 1);
+        assert!((only[0].weight - 0.8).abs() < 1e-6);
+    }
+
     // --- test helpers ---
 
     fn create_test_image_solid(path: &Path, width: u32, height: u32, rgb: [u8; 3]) {
@@ -328,4 +1389,15 @@ mod tests {
         }
         img.save(path).unwrap();
     }
+
+    #[test]
+    fn cluster_count_limits() {
+        assert_eq!(check_colors(MIN_COLORS).unwrap(), MIN_COLORS);
+        assert_eq!(check_colors(DEFAULT_COLORS).unwrap(), DEFAULT_COLORS);
+        assert_eq!(check_colors(MAX_COLORS).unwrap(), MAX_COLORS);
+        let low = check_colors(4).unwrap_err().to_string();
+        assert!(low.contains("at least 8 colors, got 4"), "{low}");
+        assert!(low.contains("accent"), "{low}");
+        assert!(check_colors(MAX_COLORS + 1).is_err());
+    }
 }
@@ -0,0 +1,290 @@
+//! Wallpaper formats the image crate can't read: HEIC and AVIF through
+//! libheif with the `heif` feature, JPEG XL through libjxl's `djxl` with the
+//! `jxl` feature. Files are recognized by their magic numbers, so builds
+//! without a decoder can say what's missing instead of "corrupt image".
+
+use std::io::Read;
+use std::path::Path;
+
+use anyhow::Result;
+use image::RgbImage;
+
+use super::extract::LoadOptions;
+use crate::error::NuriError;
+
+/// A format decoded outside the image crate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Heic,
+    Avif,
+    Jxl,
+}
+
+impl Format {
+    /// Name for messages, e.g. `HEIC`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Format::Heic => "HEIC",
+            Format::Avif => "AVIF",
+            Format::Jxl => "JPEG XL",
+        }
+    }
+
+    /// File extension for images of this format.
+    pub fn extension(self) -> &'static str {
+        match self {
+            Format::Heic => "heic",
+            Format::Avif => "avif",
+            Format::Jxl => "jxl",
+        }
+    }
+
+    /// The cargo feature that compiles in a decoder.
+    fn feature(self) -> &'static str {
+        match self {
+            Format::Heic | Format::Avif => "heif",
+            Format::Jxl => "jxl",
+        }
+    }
+}
+
+/// HEIF brands of the `ftyp` box for HEIC stills, sequences and
+/// multi-image files.
+const HEIC_BRANDS: [&[u8]; 8] = [
+    b"heic", b"heix", b"heim", b"heis", b"hevc", b"hevx", b"mif1", b"msf1",
+];
+
+/// Container signature of JPEG XL files with metadata boxes.
+const JXL_CONTAINER: &[u8] = b"\0\0\0\x0cJXL \r\n\x87\n";
+
+/// The format of the file at `path` if it's one of ours; `None` for
+/// everything else, including unreadable files.
+pub fn sniff(path: &Path) -> Option<Format> {
+    let mut head = Vec::with_capacity(64);
+    std::fs::File::open(path)
+        .ok()?
+        .take(64)
+        .read_to_end(&mut head)
+        .ok()?;
+    sniff_bytes(&head)
+}
+
+/// Like [`sniff`] for the first bytes of a file.
+pub fn sniff_bytes(head: &[u8]) -> Option<Format> {
+    if head.starts_with(&[0xff, 0x0a]) || head.starts_with(JXL_CONTAINER) {
+        return Some(Format::Jxl);
+    }
+    // ISO base media: a box size, `ftyp`, the major brand, a minor version,
+    // then compatible brands. The major brand decides, but plenty of
+    // encoders write `mif1` there and name the codec only among the rest.
+    let size = u32::from_be_bytes(head.get(..4)?.try_into().ok()?) as usize;
+    if head.get(4..8)? != b"ftyp" {
+        return None;
+    }
+    let ftyp = &head[..size.clamp(8, head.len())];
+    let brands = ftyp[8..]
+        .chunks_exact(4)
+        .enumerate()
+        .filter(|(i, _)| *i != 1)
+        .map(|(_, brand)| brand);
+    let mut format = None;
+    for brand in brands {
+        if brand == b"avif" || brand == b"avis" {
+            return Some(Format::Avif);
+        }
+        if format.is_none() && HEIC_BRANDS.contains(&brand) {
+            format = Some(Format::Heic);
+        }
+    }
+    format
+}
+
+/// Formats this build can theme, for error messages.
+pub fn supported_formats() -> String {
+    let mut formats = String::from("PNG, JPEG, WebP, BMP, TIFF, GIF");
+    if cfg!(feature = "heif") {
+        formats.push_str(", HEIC, AVIF");
+    }
+    if cfg!(feature = "jxl") {
+        formats.push_str(", JPEG XL");
+    }
+    formats
+}
+
+/// Decode `path`, a `format` image, to fit within `options.max_dim` like
+/// [`decode_image_with`](super::extract::decode_image_with).
+#[cfg_attr(not(any(feature = "heif", feature = "jxl")), allow(unused_variables))]
+pub fn decode(path: &Path, format: Format, options: &LoadOptions) -> Result<RgbImage> {
+    match format {
+        #[cfg(feature = "heif")]
+        Format::Heic | Format::Avif => heif::decode(path, options),
+        #[cfg(feature = "jxl")]
+        Format::Jxl => jxl::decode(path, options),
+        #[allow(unreachable_patterns)]
+        _ => Err(NuriError::MissingFeature {
+            path: path.into(),
+            format: format!("{} image", format.name()),
+            feature: format.feature(),
+        }
+        .into()),
+    }
+}
+
+#[cfg(feature = "heif")]
+mod heif {
+    use std::path::Path;
+
+    use anyhow::{Context, Result};
+    use image::metadata::Orientation;
+    use image::{DynamicImage, RgbImage};
+    use libheif_rs::{ColorSpace, HeifContext, LibHeif, RgbChroma};
+
+    use crate::error::NuriError;
+    use crate::pipeline::extract::{fit, LoadOptions};
+
+    /// The primary image of a HEIF container. libheif applies the
+    /// container's rotation and mirroring while decoding.
+    pub(super) fn decode(path: &Path, options: &LoadOptions) -> Result<RgbImage> {
+        let cannot = || format!("cannot decode {}", path.display());
+        let ctx = HeifContext::read_from_file(&path.to_string_lossy()).with_context(cannot)?;
+        let handle = ctx.primary_image_handle().with_context(cannot)?;
+
+        let estimated = u64::from(handle.width()) * u64::from(handle.height()) * 4;
+        if estimated > options.max_memory {
+            return Err(NuriError::ImageTooLarge {
+                path: path.into(),
+                max_memory: options.max_memory,
+            }
+            .into());
+        }
+
+        let icc = handle.color_profile_raw().map(|profile| profile.data);
+        let image = LibHeif::new()
+            .decode(&handle, ColorSpace::Rgb(RgbChroma::Rgb), None)
+            .with_context(cannot)?;
+        let plane = image
+            .planes()
+            .interleaved
+            .with_context(|| format!("libheif returned no RGB plane for {}", path.display()))?;
+
+        // Rows may be padded past width * 3 bytes.
+        let row = plane.width as usize * 3;
+        let pixels = plane
+            .data
+            .chunks(plane.stride)
+            .take(plane.height as usize)
+            .flat_map(|line| &line[..row])
+            .copied()
+            .collect();
+        let rgb = RgbImage::from_raw(plane.width, plane.height, pixels)
+            .with_context(|| format!("malformed RGB plane from libheif for {}", path.display()))?;
+
+        fit(
+            DynamicImage::ImageRgb8(rgb),
+            Orientation::NoTransforms,
+            icc.as_deref(),
+            options,
+        )
+    }
+}
+
+#[cfg(feature = "jxl")]
+mod jxl {
+    use std::io;
+    use std::path::Path;
+    use std::process::Command;
+
+    use anyhow::{bail, Result};
+    use image::RgbImage;
+
+    use crate::fetch::TempImage;
+    use crate::pipeline::extract::{decode_image_with, LoadOptions};
+
+    /// Convert to PNG with `djxl`, then read that like any other image.
+    pub(super) fn decode(path: &Path, options: &LoadOptions) -> Result<RgbImage> {
+        let png = TempImage::new("decoded.png")?;
+        let output = Command::new("djxl")
+            .arg(path)
+            .arg(&png.path)
+            .arg("--quiet")
+            .output()
+            .map_err(|e| {
+                if e.kind() == io::ErrorKind::NotFound {
+                    anyhow::anyhow!("JPEG XL wallpapers need djxl (from libjxl) on PATH")
+                } else {
+                    anyhow::Error::new(e).context("cannot run djxl")
+                }
+            })?;
+        if !output.status.success() {
+            bail!(
+                "djxl cannot read {}: {}",
+                path.display(),
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        log::debug!("decoded {} to PNG with djxl", path.display());
+        Ok(decode_image_with(&png.path, options)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ftyp(major: &[u8; 4], compatible: &[&[u8; 4]]) -> Vec<u8> {
+        let size = 16 + 4 * compatible.len() as u32;
+        let mut head = size.to_be_bytes().to_vec();
+        head.extend_from_slice(b"ftyp");
+        head.extend_from_slice(major);
+        head.extend_from_slice(&[0; 4]);
+        for brand in compatible {
+            head.extend_from_slice(*brand);
+        }
+        // The next box, whose bytes mustn't be read as brands
+        head.extend_from_slice(b"\0\0\0\x08avif");
+        head
+    }
+
+    #[test]
+    fn recognizes_formats_by_magic_number() {
+        assert_eq!(sniff_bytes(&ftyp(b"heic", &[b"mif1"])), Some(Format::Heic));
+        assert_eq!(sniff_bytes(&ftyp(b"avif", &[])), Some(Format::Avif));
+        assert_eq!(
+            sniff_bytes(&ftyp(b"mif1", &[b"mif1", b"miaf", b"avif"])),
+            Some(Format::Avif)
+        );
+        assert_eq!(sniff_bytes(&ftyp(b"mif1", &[b"heic"])), Some(Format::Heic));
+        assert_eq!(sniff_bytes(&[0xff, 0x0a, 0xfa]), Some(Format::Jxl));
+        assert_eq!(sniff_bytes(JXL_CONTAINER), Some(Format::Jxl));
+
+        // MP4 video, PNG, and too little to tell
+        assert_eq!(sniff_bytes(&ftyp(b"isom", &[b"mp41"])), None);
+        assert_eq!(sniff_bytes(b"\x89PNG\r\n\x1a\n"), None);
+        assert_eq!(sniff_bytes(b"\0\0"), None);
+    }
+
+    #[test]
+    fn missing_decoder_names_feature_and_supported_formats() {
+        let dir = std::env::temp_dir().join("nuri_test_formats");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("wallpaper.heic");
+        std::fs::write(&path, ftyp(b"heic", &[b"mif1"])).unwrap();
+
+        assert_eq!(sniff(&path), Some(Format::Heic));
+        if !cfg!(feature = "heif") {
+            let err = decode(&path, Format::Heic, &LoadOptions::default()).unwrap_err();
+            let message = err.to_string();
+            assert!(matches!(
+                err.downcast_ref(),
+                Some(NuriError::MissingFeature {
+                    feature: "heif",
+                    ..
+                })
+            ));
+            assert!(message.contains("HEIC image"), "{message}");
+            assert!(message.contains("--features heif"), "{message}");
+            assert!(message.contains(&supported_formats()), "{message}");
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
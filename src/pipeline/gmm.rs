@@ -0,0 +1,258 @@
+//! Gaussian mixture extraction (`--algorithm gmm`): expectation-maximization
+//! over LAB pixels with a full covariance per component, started from
+//! K-means. Every pixel belongs to every component in proportion to its
+//! likelihood, so a smooth gradient is covered by a few broad components
+//! instead of being cut into K-means' hard-edged bands.
+
+use std::f64::consts::PI;
+
+use palette::Lab;
+
+use crate::color::Color;
+
+use super::extract::{extract_colors_with_seed, finish, ExtractedColor};
+use super::quantize::Quantizer;
+
+const MAX_ITER: usize = 50;
+/// Stop once the mean log-likelihood per pixel improves by less than this.
+const TOLERANCE: f64 = 1e-4;
+/// Added to each covariance diagonal (in squared delta-E), so flat areas
+/// don't collapse a component onto a single point.
+const REGULARIZATION: f64 = 1.0;
+/// Components left with less of the pixels than this are dropped.
+const MIN_SHARE: f64 = 1e-4;
+
+type Vec3 = [f64; 3];
+type Mat3 = [[f64; 3]; 3];
+
+/// Gaussian mixture model; see the module docs. Colors are the component
+/// means, weighted by each component's soft share of the pixels.
+pub struct Gmm;
+
+impl Quantizer for Gmm {
+    fn quantize(&self, pixels: &[Lab], k: usize, seed: u64) -> Vec<ExtractedColor> {
+        if pixels.is_empty() || k == 0 {
+            return Vec::new();
+        }
+        let start = extract_colors_with_seed(pixels, k, seed);
+        let points: Vec<Vec3> = pixels
+            .iter()
+            .map(|p| [f64::from(p.l), f64::from(p.a), f64::from(p.b)])
+            .collect();
+
+        // Responsibilities, one row of component probabilities per pixel,
+        // start as the hard K-means assignment
+        let means: Vec<Vec3> = start
+            .iter()
+            .map(|c| {
+                let lab = c.color.to_lab();
+                [f64::from(lab.l), f64::from(lab.a), f64::from(lab.b)]
+            })
+            .collect();
+        let mut resp = vec![0.0; points.len() * means.len()];
+        for (p, row) in points.iter().zip(resp.chunks_mut(means.len())) {
+            let nearest = (0..means.len())
+                .min_by(|&a, &b| distance_sq(p, &means[a]).total_cmp(&distance_sq(p, &means[b])))
+                .unwrap_or(0);
+            row[nearest] = 1.0;
+        }
+
+        let mut components = maximize(&points, &resp, means.len());
+        let mut previous = f64::NEG_INFINITY;
+        let mut iterations = 0;
+        while iterations < MAX_ITER && !components.is_empty() {
+            iterations += 1;
+            let likelihood = expect(&points, &components, &mut resp);
+            components = maximize(&points, &resp, components.len());
+            if likelihood - previous < TOLERANCE {
+                break;
+            }
+            previous = likelihood;
+        }
+        if components.is_empty() {
+            return start;
+        }
+        log::debug!(
+            "fitted {} Gaussian components in {iterations} EM iterations",
+            components.len()
+        );
+
+        let colors = components
+            .iter()
+            .map(|c| ExtractedColor {
+                color: Color::from_lab(Lab::new(
+                    c.mean[0] as f32,
+                    c.mean[1] as f32,
+                    c.mean[2] as f32,
+                )),
+                weight: c.share as f32,
+            })
+            .collect();
+        finish(colors)
+    }
+}
+
+/// One Gaussian of the mixture.
+struct Component {
+    /// Mixing weight: the component's share of the pixels.
+    share: f64,
+    mean: Vec3,
+    /// Inverse of the covariance.
+    precision: Mat3,
+    /// `ln share` plus the log of the Gaussian's normalizing constant.
+    log_scale: f64,
+}
+
+impl Component {
+    /// `ln (share * density(p))`.
+    fn log_weighted_density(&self, p: &Vec3) -> f64 {
+        let d = [
+            p[0] - self.mean[0],
+            p[1] - self.mean[1],
+            p[2] - self.mean[2],
+        ];
+        let mut mahalanobis = 0.0;
+        for (a, row) in self.precision.iter().enumerate() {
+            for (b, value) in row.iter().enumerate() {
+                mahalanobis += d[a] * value * d[b];
+            }
+        }
+        self.log_scale - 0.5 * mahalanobis
+    }
+}
+
+/// E-step: fill `resp` with each pixel's membership in each component and
+/// return the mean log-likelihood per pixel.
+fn expect(points: &[Vec3], components: &[Component], resp: &mut Vec<f64>) -> f64 {
+    let count = components.len();
+    resp.clear();
+    resp.resize(points.len() * count, 0.0);
+    let mut total = 0.0;
+    for (p, row) in points.iter().zip(resp.chunks_mut(count)) {
+        for (r, component) in row.iter_mut().zip(components) {
+            *r = component.log_weighted_density(p);
+        }
+        // Log-sum-exp, so far-off pixels don't underflow to all zeros
+        let max = row.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        let log_sum = max + row.iter().map(|r| (r - max).exp()).sum::<f64>().ln();
+        for r in row.iter_mut() {
+            *r = (*r - log_sum).exp();
+        }
+        total += log_sum;
+    }
+    total / points.len() as f64
+}
+
+/// M-step: the components that best explain the pixels given `resp`, which
+/// has `count` columns. Components with almost no pixels are dropped.
+fn maximize(points: &[Vec3], resp: &[f64], count: usize) -> Vec<Component> {
+    let n = points.len() as f64;
+    (0..count)
+        .filter_map(|j| {
+            let column = || resp.iter().skip(j).step_by(count);
+            let mass: f64 = column().sum();
+            if mass / n < MIN_SHARE {
+                return None;
+            }
+            let mut mean = [0.0; 3];
+            for (p, r) in points.iter().zip(column()) {
+                for d in 0..3 {
+                    mean[d] += r * p[d];
+                }
+            }
+            mean = mean.map(|m| m / mass);
+
+            let mut covariance = [[0.0; 3]; 3];
+            for (p, r) in points.iter().zip(column()) {
+                let d = [p[0] - mean[0], p[1] - mean[1], p[2] - mean[2]];
+                for a in 0..3 {
+                    for b in 0..3 {
+                        covariance[a][b] += r * d[a] * d[b];
+                    }
+                }
+            }
+            for (a, row) in covariance.iter_mut().enumerate() {
+                for value in row.iter_mut() {
+                    *value /= mass;
+                }
+                row[a] += REGULARIZATION;
+            }
+
+            let (precision, determinant) = invert(&covariance)?;
+            let share = mass / n;
+            Some(Component {
+                share,
+                mean,
+                precision,
+                log_scale: share.ln() - 0.5 * (3.0 * (2.0 * PI).ln() + determinant.ln()),
+            })
+        })
+        .collect()
+}
+
+/// Inverse and determinant of a covariance matrix; `None` if it isn't
+/// positive definite.
+fn invert(m: &Mat3) -> Option<(Mat3, f64)> {
+    let cofactor =
+        |r0: usize, r1: usize, c0: usize, c1: usize| m[r0][c0] * m[r1][c1] - m[r0][c1] * m[r1][c0];
+    let c00 = cofactor(1, 2, 1, 2);
+    let c01 = -cofactor(1, 2, 0, 2);
+    let c02 = cofactor(1, 2, 0, 1);
+    let determinant = m[0][0] * c00 + m[0][1] * c01 + m[0][2] * c02;
+    if !(determinant.is_finite() && determinant > 0.0) {
+        return None;
+    }
+    let adjugate = [
+        [c00, -cofactor(0, 2, 1, 2), cofactor(0, 1, 1, 2)],
+        [c01, cofactor(0, 2, 0, 2), -cofactor(0, 1, 0, 2)],
+        [c02, -cofactor(0, 2, 0, 1), cofactor(0, 1, 0, 1)],
+    ];
+    Some((
+        adjugate.map(|row| row.map(|v| v / determinant)),
+        determinant,
+    ))
+}
+
+fn distance_sq(a: &Vec3, b: &Vec3) -> f64 {
+    (a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2) + (a[2] - b[2]).powi(2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inverts_covariance_matrices() {
+        let m = [[4.0, 1.0, 0.5], [1.0, 3.0, 0.2], [0.5, 0.2, 2.0]];
+        let (inverse, determinant) = invert(&m).unwrap();
+        assert!((determinant - 21.29).abs() < 1e-9, "{determinant}");
+        for (a, row) in m.iter().enumerate() {
+            for b in 0..3 {
+                let product: f64 = row.iter().zip(&inverse).map(|(x, inv)| x * inv[b]).sum();
+                let identity = if a == b { 1.0 } else { 0.0 };
+                assert!((product - identity).abs() < 1e-9);
+            }
+        }
+        assert!(invert(&[[0.0; 3]; 3]).is_none());
+    }
+
+    #[test]
+    fn gradient_is_covered_by_soft_components() {
+        // A dark-to-light blue ramp with a patch of orange
+        let mut pixels: Vec<Lab> = (0..3000)
+            .map(|i| Lab::new(20.0 + (i % 1000) as f32 * 0.05, -5.0, -40.0))
+            .collect();
+        pixels.extend(vec![Lab::new(65.0, 40.0, 70.0); 1000]);
+
+        let colors = Gmm.quantize(&pixels, 8, 42);
+        let total: f32 = colors.iter().map(|c| c.weight).sum();
+        assert!((total - 1.0).abs() < 1e-3, "weights sum to {total}");
+        let orange = colors
+            .iter()
+            .find(|c| c.color.delta_e(Color::from_lab(Lab::new(65.0, 40.0, 70.0))) < 5.0)
+            .expect("orange patch lost");
+        assert!((orange.weight - 0.25).abs() < 0.02, "{}", orange.weight);
+        // The 50-unit ramp needs fewer components than K-means' bands
+        assert!(colors.len() < 8, "{} components", colors.len());
+    }
+}
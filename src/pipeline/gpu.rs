@@ -0,0 +1,326 @@
+//! K-means with the assignment step on the GPU (`gpu` feature): each
+//! iteration, a wgpu compute shader finds every pixel's nearest centroid and
+//! the CPU averages the clusters. Large samples (a high `--max-dim` over a
+//! batch of 4K wallpapers) spend nearly all their clustering time in that
+//! step. Without a usable adapter, or for small samples, the caller runs
+//! Hamerly's K-means on the CPU instead.
+
+use std::sync::{mpsc, OnceLock};
+
+use kmeans_colors::{init_plus_plus, Calculate, Kmeans};
+use palette::Lab;
+use rand_chacha::rand_core::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use wgpu::util::DeviceExt;
+
+/// Below this many pixels the CPU is faster than a round trip to the GPU.
+pub const MIN_PIXELS: usize = 1 << 17;
+
+/// Threads per workgroup, as declared in [`SHADER`].
+const WORKGROUP_SIZE: usize = 256;
+
+/// Nearest centroid of each pixel by squared LAB distance, the first on a
+/// tie, like kmeans_colors' CPU assignment.
+const SHADER: &str = r"
+@group(0) @binding(0) var<storage, read> pixels: array<vec4<f32>>;
+@group(0) @binding(1) var<storage, read> centroids: array<vec4<f32>>;
+@group(0) @binding(2) var<storage, read_write> indices: array<u32>;
+
+@compute @workgroup_size(256)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let i = id.x;
+    if i >= arrayLength(&indices) {
+        return;
+    }
+    let p = pixels[i].xyz;
+    var best = 0u;
+    var best_distance = 3.4e38;
+    for (var j = 0u; j < arrayLength(&centroids); j++) {
+        let d = p - centroids[j].xyz;
+        let distance = dot(d, d);
+        if distance < best_distance {
+            best_distance = distance;
+            best = j;
+        }
+    }
+    indices[i] = best;
+}
+";
+
+/// The device and compiled shader, set up once per process so `nuri batch`
+/// and `nuri watch` don't pay for it on every wallpaper.
+struct Gpu {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    pipeline: wgpu::ComputePipeline,
+}
+
+/// The shared [`Gpu`], or `None` if there is no hardware adapter.
+fn gpu() -> Option<&'static Gpu> {
+    static GPU: OnceLock<Option<Gpu>> = OnceLock::new();
+    GPU.get_or_init(|| {
+        let gpu = pollster::block_on(Gpu::new(false));
+        match &gpu {
+            Ok(_) => {}
+            Err(reason) => log::debug!("no GPU for K-means ({reason}), clustering on the CPU"),
+        }
+        gpu.ok()
+    })
+    .as_ref()
+}
+
+impl Gpu {
+    /// Set up the first adapter. Software renderers such as llvmpipe run on
+    /// the CPU and are slower than clustering there, so they're refused
+    /// unless `software` is set.
+    async fn new(software: bool) -> Result<Gpu, String> {
+        let instance = wgpu::Instance::default();
+        let adapter = instance
+            .request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference: wgpu::PowerPreference::HighPerformance,
+                ..Default::default()
+            })
+            .await
+            .map_err(|e| e.to_string())?;
+        let info = adapter.get_info();
+        if info.device_type == wgpu::DeviceType::Cpu && !software {
+            return Err(format!("{} is a software renderer", info.name));
+        }
+        let (device, queue) = adapter
+            .request_device(&wgpu::DeviceDescriptor {
+                label: Some("nuri"),
+                ..Default::default()
+            })
+            .await
+            .map_err(|e| e.to_string())?;
+        // Errors outside an error scope would otherwise panic
+        device.on_uncaptured_error(std::sync::Arc::new(|e| log::warn!("GPU error: {e}")));
+
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("nearest centroid"),
+            source: wgpu::ShaderSource::Wgsl(SHADER.into()),
+        });
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("nearest centroid"),
+            layout: None,
+            module: &module,
+            entry_point: Some("main"),
+            compilation_options: Default::default(),
+            cache: None,
+        });
+        log::debug!("K-means assignment on {}", info.name);
+        Ok(Gpu {
+            device,
+            queue,
+            pipeline,
+        })
+    }
+
+    /// Whether `count` pixels fit in one storage buffer and one dispatch.
+    fn fits(&self, count: usize) -> bool {
+        let limits = self.device.limits();
+        (count * 16) as u64 <= limits.max_storage_buffer_binding_size
+            && count.div_ceil(WORKGROUP_SIZE)
+                <= limits.max_compute_workgroups_per_dimension as usize
+    }
+}
+
+/// [`kmeans_colors::get_kmeans`] with the assignment step on the GPU: the
+/// same k-means++ start from `seed`, the same centroid updates and stopping
+/// rule. `None` when the sample is too small to be worth it, there is no
+/// adapter, or the GPU fails; the caller then clusters on the CPU.
+pub fn kmeans(
+    k: usize,
+    max_iter: usize,
+    converge: f32,
+    pixels: &[Lab],
+    seed: u64,
+) -> Option<Kmeans<Lab>> {
+    if pixels.len() < MIN_PIXELS || k == 0 || k > usize::from(u8::MAX) {
+        return None;
+    }
+    let gpu = gpu()?;
+    if !gpu.fits(pixels.len()) {
+        log::debug!(
+            "{} pixels are too many for one GPU dispatch, clustering on the CPU",
+            pixels.len()
+        );
+        return None;
+    }
+
+    let validation = gpu.device.push_error_scope(wgpu::ErrorFilter::Validation);
+    let result = run(gpu, k, max_iter, converge, pixels, seed);
+    if let Some(error) = pollster::block_on(validation.pop()) {
+        log::warn!("GPU K-means failed, clustering on the CPU: {error}");
+        return None;
+    }
+    result
+}
+
+fn run(
+    gpu: &Gpu,
+    k: usize,
+    max_iter: usize,
+    converge: f32,
+    pixels: &[Lab],
+    seed: u64,
+) -> Option<Kmeans<Lab>> {
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    let mut centroids: Vec<Lab> = Vec::with_capacity(k);
+    init_plus_plus(k, &mut rng, pixels, &mut centroids);
+
+    let storage = |label, contents: &[u8], usage| {
+        gpu.device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some(label),
+                contents,
+                usage: wgpu::BufferUsages::STORAGE | usage,
+            })
+    };
+    let pixel_buffer = storage("pixels", &vec4_bytes(pixels), wgpu::BufferUsages::empty());
+    let centroid_buffer = storage(
+        "centroids",
+        &vec4_bytes(&centroids),
+        wgpu::BufferUsages::COPY_DST,
+    );
+    let index_size = (pixels.len() * 4) as u64;
+    let index_buffer = gpu.device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("indices"),
+        size: index_size,
+        usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+        mapped_at_creation: false,
+    });
+    let readback = gpu.device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("indices readback"),
+        size: index_size,
+        usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+    let bind_group = gpu.device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("nearest centroid"),
+        layout: &gpu.pipeline.get_bind_group_layout(0),
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: pixel_buffer.as_entire_binding(),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: centroid_buffer.as_entire_binding(),
+            },
+            wgpu::BindGroupEntry {
+                binding: 2,
+                resource: index_buffer.as_entire_binding(),
+            },
+        ],
+    });
+    let workgroups = pixels.len().div_ceil(WORKGROUP_SIZE) as u32;
+
+    let mut iterations = 0;
+    let mut old_centroids = centroids.clone();
+    loop {
+        let mut encoder = gpu
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        {
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor::default());
+            pass.set_pipeline(&gpu.pipeline);
+            pass.set_bind_group(0, &bind_group, &[]);
+            pass.dispatch_workgroups(workgroups, 1, 1);
+        }
+        encoder.copy_buffer_to_buffer(&index_buffer, 0, &readback, 0, index_size);
+        gpu.queue.submit([encoder.finish()]);
+        let indices = read_indices(gpu, &readback, k)?;
+
+        Lab::recalculate_centroids(&mut rng, pixels, &mut centroids, &indices);
+        let score = Lab::check_loop(&centroids, &old_centroids);
+        if iterations >= max_iter || score <= converge {
+            log::debug!("K-means converged on the GPU after {iterations} iterations");
+            return Some(Kmeans {
+                score,
+                centroids,
+                indices,
+            });
+        }
+        iterations += 1;
+        old_centroids.clone_from(&centroids);
+        gpu.queue
+            .write_buffer(&centroid_buffer, 0, &vec4_bytes(&centroids));
+    }
+}
+
+/// Wait for the assignment and copy it back; `None` if mapping fails or an
+/// index is out of range.
+fn read_indices(gpu: &Gpu, readback: &wgpu::Buffer, k: usize) -> Option<Vec<u8>> {
+    let (sender, receiver) = mpsc::channel();
+    readback.map_async(wgpu::MapMode::Read, .., move |result| {
+        let _ = sender.send(result);
+    });
+    if let Err(e) = gpu.device.poll(wgpu::PollType::wait_indefinitely()) {
+        log::warn!("GPU K-means failed, clustering on the CPU: {e}");
+        return None;
+    }
+    if let Err(e) = receiver.recv().ok()? {
+        log::warn!("cannot read GPU K-means results, clustering on the CPU: {e}");
+        return None;
+    }
+    let indices = {
+        let view = readback.get_mapped_range(..).ok()?;
+        view.chunks_exact(4)
+            .map(|b| u32::from_ne_bytes([b[0], b[1], b[2], b[3]]))
+            .map(|i| u8::try_from(i).ok().filter(|&i| usize::from(i) < k))
+            .collect::<Option<Vec<u8>>>()
+    };
+    readback.unmap();
+    indices
+}
+
+/// LAB values as WGSL `vec4<f32>`s, padded to 16 bytes each.
+fn vec4_bytes(labs: &[Lab]) -> Vec<u8> {
+    labs.iter()
+        .flat_map(|lab| [lab.l, lab.a, lab.b, 0.0])
+        .flat_map(f32::to_ne_bytes)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kmeans_colors::get_kmeans;
+
+    #[test]
+    fn matches_cpu_kmeans() {
+        // Three well-separated blobs
+        let pixels: Vec<Lab> = (0..MIN_PIXELS)
+            .map(|i| {
+                let jitter = (i % 7) as f32 * 0.5;
+                match i % 3 {
+                    0 => Lab::new(20.0 + jitter, 0.0, 0.0),
+                    1 => Lab::new(60.0, 50.0 + jitter, 20.0),
+                    _ => Lab::new(80.0, -30.0, 60.0 + jitter),
+                }
+            })
+            .collect();
+        assert!(kmeans(3, 20, 5.0, &pixels[..100], 42).is_none());
+
+        // Any adapter will do here, llvmpipe included; without one there's
+        // only the CPU path to check
+        let Ok(gpu) = pollster::block_on(Gpu::new(true)) else {
+            return;
+        };
+        let result = run(&gpu, 3, 20, 5.0, &pixels, 42).unwrap();
+        let cpu = get_kmeans(3, 20, 5.0, false, &pixels, 42);
+        assert_eq!(result.indices, cpu.indices);
+        for (g, c) in result.centroids.iter().zip(&cpu.centroids) {
+            assert!(Lab::difference(g, c) < 1e-3, "{g:?} vs {c:?}");
+        }
+    }
+
+    #[test]
+    fn packs_lab_as_vec4() {
+        let bytes = vec4_bytes(&[Lab::new(50.0, -10.0, 20.0)]);
+        assert_eq!(bytes.len(), 16);
+        assert_eq!(bytes[4..8], (-10.0f32).to_ne_bytes());
+        assert_eq!(bytes[12..], 0.0f32.to_ne_bytes());
+    }
+}
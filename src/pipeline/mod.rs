@@ -1,4 +1,149 @@
 pub mod assign;
+pub mod colorblind;
 pub mod contrast;
+pub mod crop;
 pub mod detect;
+pub mod extended;
 pub mod extract;
+pub mod formats;
+pub mod gmm;
+#[cfg(feature = "gpu")]
+pub mod gpu;
+pub mod profile;
+pub mod quantize;
+pub mod style;
+pub mod weighting;
+
+use std::path::Path;
+use std::time::Duration;
+
+use crate::cli::ThemeMode;
+use crate::error::Result;
+
+use self::assign::{assign_slots_with, AnsiPalette};
+use self::colorblind::separate_accents;
+use self::contrast::{enforce_contrast, DEFAULT_ACCENT_CONTRAST};
+use self::crop::Region;
+use self::detect::detect_mode;
+use self::extract::{
+    check_colors, load_image_with, refine, sample_pixels, LoadOptions, ResizeFilter, Sampling,
+    DEFAULT_COLORS, DEFAULT_FRAMES, DEFAULT_MAX_DIM, DEFAULT_MAX_MEMORY, DEFAULT_MERGE_DELTA_E,
+    DEFAULT_SAMPLE_SIZE, DEFAULT_SEED,
+};
+use self::profile::Profile;
+use self::quantize::Algorithm;
+use self::style::{apply_styles, PaletteStyle};
+use self::weighting::{emphasize, restore_area, Weighting};
+
+/// Settings for running the full pipeline on an image.
+#[derive(Debug, Clone)]
+pub struct PaletteOptions {
+    /// Force dark or light mode; auto-detected when `None`.
+    pub mode: Option<ThemeMode>,
+    /// Number of K-means clusters.
+    pub colors: usize,
+    /// Minimum accent contrast ratio against the background, clamped to [1, 21].
+    pub min_contrast: f32,
+    /// Memory cap in bytes for decoding the image.
+    pub max_memory: u64,
+    /// Frames of an animated image whose pixels are pooled.
+    pub frames: usize,
+    /// Take every n-th frame instead of spreading `frames` over the animation.
+    pub frame_stride: Option<usize>,
+    /// Take a video frame this often instead of spreading `frames` over the
+    /// video.
+    pub frame_interval: Option<Duration>,
+    /// Part of the image colors come from; all of it when `None`.
+    pub region: Option<Region>,
+    /// Images are resized to fit within `max_dim` x `max_dim`.
+    pub max_dim: u32,
+    /// Filter for that resize.
+    pub resize_filter: ResizeFilter,
+    /// Color extraction algorithm.
+    pub algorithm: Algorithm,
+    /// Pixel sampling strategy applied before clustering.
+    pub sampling: Sampling,
+    /// Number of pixels kept by `sampling`.
+    pub sample_size: usize,
+    /// Pixel weighting applied before sampling and clustering.
+    pub weighting: Weighting,
+    /// Merge extracted colors closer than this delta-E.
+    pub merge_delta_e: f32,
+    /// Drop extracted colors covering less than this fraction of the image.
+    pub min_weight: f32,
+    /// Accent styles applied in order after slot assignment.
+    pub styles: Vec<PaletteStyle>,
+    /// Keep confusable accent pairs apart under colorblindness simulation.
+    pub colorblind_safe: bool,
+    /// Per-slot accent targets used by slot assignment.
+    pub profile: Profile,
+}
+
+impl Default for PaletteOptions {
+    fn default() -> Self {
+        Self {
+            mode: None,
+            colors: DEFAULT_COLORS,
+            min_contrast: DEFAULT_ACCENT_CONTRAST,
+            max_memory: DEFAULT_MAX_MEMORY,
+            frames: DEFAULT_FRAMES,
+            frame_stride: None,
+            frame_interval: None,
+            region: None,
+            max_dim: DEFAULT_MAX_DIM,
+            resize_filter: ResizeFilter::Lanczos,
+            algorithm: Algorithm::Kmeans,
+            sampling: Sampling::All,
+            sample_size: DEFAULT_SAMPLE_SIZE,
+            weighting: Weighting::Area,
+            merge_delta_e: DEFAULT_MERGE_DELTA_E,
+            min_weight: 0.0,
+            styles: Vec::new(),
+            colorblind_safe: false,
+            profile: Profile::default(),
+        }
+    }
+}
+
+impl AnsiPalette {
+    /// Run the whole pipeline (load, extract, detect, assign, contrast) on an
+    /// image file.
+    pub fn from_image(path: &Path, options: &PaletteOptions) -> Result<AnsiPalette> {
+        check_colors(options.colors)?;
+        let load_options = LoadOptions {
+            max_memory: options.max_memory,
+            frames: options.frames,
+            frame_stride: options.frame_stride,
+            frame_interval: options.frame_interval,
+            region: options.region,
+            max_dim: options.max_dim,
+            resize_filter: options.resize_filter,
+        };
+        let image = load_image_with(path, &load_options)?;
+        let weighted = emphasize(&image, options.weighting);
+        let sample = sample_pixels(
+            &weighted,
+            options.sampling,
+            options.sample_size,
+            DEFAULT_SEED,
+        );
+        let mut colors =
+            options
+                .algorithm
+                .quantizer()
+                .quantize(&sample, options.colors, DEFAULT_SEED);
+        if options.weighting != Weighting::Area {
+            colors = restore_area(colors, &image.pixels);
+        }
+        let colors = refine(colors, options.merge_delta_e, options.min_weight);
+        let mode = options.mode.unwrap_or_else(|| detect_mode(&image.pixels));
+        let mut palette = assign_slots_with(&colors, mode, &options.profile);
+        apply_styles(&mut palette, &options.styles, &colors);
+        let min_contrast = options.min_contrast.clamp(1.0, 21.0);
+        enforce_contrast(&mut palette, min_contrast);
+        if options.colorblind_safe {
+            separate_accents(&mut palette, min_contrast);
+        }
+        Ok(palette)
+    }
+}
@@ -0,0 +1,466 @@
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use palette::Oklch;
+use serde::Deserialize;
+
+use super::assign::{target_hue, DEFAULT_MIN_SEPARATION, MAX_HUE_DISTANCE};
+use crate::color::Color;
+
+/// Accent names in profile files, for slots 1-6.
+const ACCENT_NAMES: [&str; 6] = ["red", "green", "yellow", "blue", "magenta", "cyan"];
+
+/// Built-in profiles: name and TOML source. The scheme-inspired ones take
+/// each accent's hue, lightness and chroma from the original scheme, so the
+/// wallpaper's colors are pulled into its look.
+const BUILTIN_PROFILES: [(&str, &str); 4] = [
+    ("default", ""),
+    ("gruvbox", GRUVBOX),
+    ("nord", NORD),
+    ("solarized", SOLARIZED),
+];
+
+const GRUVBOX: &str = r#"
+[red]
+hue = 29
+tolerance = 45
+lightness = [0.50, 0.60]
+chroma = [0.16, 0.22]
+
+[green]
+hue = 109
+tolerance = 45
+lightness = [0.61, 0.71]
+chroma = [0.11, 0.15]
+
+[yellow]
+hue = 78
+tolerance = 45
+lightness = [0.68, 0.78]
+chroma = [0.11, 0.16]
+
+[blue]
+hue = 199
+tolerance = 45
+lightness = [0.53, 0.63]
+chroma = [0.05, 0.07]
+
+[magenta]
+hue = 352
+tolerance = 45
+lightness = [0.55, 0.65]
+chroma = [0.09, 0.12]
+
+[cyan]
+hue = 145
+tolerance = 45
+lightness = [0.60, 0.70]
+chroma = [0.08, 0.10]
+"#;
+
+const NORD: &str = r#"
+[red]
+hue = 15
+tolerance = 45
+lightness = [0.56, 0.66]
+chroma = [0.10, 0.13]
+
+[green]
+hue = 131
+tolerance = 45
+lightness = [0.72, 0.82]
+chroma = [0.06, 0.08]
+
+[yellow]
+hue = 84
+tolerance = 45
+lightness = [0.80, 0.90]
+chroma = [0.07, 0.10]
+
+[blue]
+hue = 249
+tolerance = 45
+lightness = [0.65, 0.75]
+chroma = [0.05, 0.065]
+
+[magenta]
+hue = 333
+tolerance = 45
+lightness = [0.64, 0.74]
+chroma = [0.05, 0.07]
+
+[cyan]
+hue = 217
+tolerance = 45
+lightness = [0.72, 0.82]
+chroma = [0.05, 0.07]
+"#;
+
+const SOLARIZED: &str = r#"
+[red]
+hue = 27
+tolerance = 45
+lightness = [0.54, 0.64]
+chroma = [0.16, 0.23]
+
+[green]
+hue = 119
+tolerance = 45
+lightness = [0.59, 0.69]
+chroma = [0.12, 0.17]
+
+[yellow]
+hue = 86
+tolerance = 45
+lightness = [0.60, 0.70]
+chroma = [0.11, 0.15]
+
+[blue]
+hue = 245
+tolerance = 45
+lightness = [0.56, 0.66]
+chroma = [0.11, 0.15]
+
+[magenta]
+hue = 356
+tolerance = 45
+lightness = [0.54, 0.64]
+chroma = [0.16, 0.22]
+
+[cyan]
+hue = 187
+tolerance = 45
+lightness = [0.59, 0.69]
+chroma = [0.08, 0.11]
+"#;
+
+/// Where one accent slot should land.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AccentTarget {
+    /// Target Oklch hue in degrees.
+    pub hue: f32,
+    /// Maximum hue distance (degrees) at which an extracted color is used
+    /// as-is; farther candidates are rotated to `hue`.
+    pub tolerance: f32,
+    /// Allowed Oklch lightness range.
+    pub lightness: [f32; 2],
+    /// Allowed Oklch chroma range.
+    pub chroma: [f32; 2],
+}
+
+impl AccentTarget {
+    /// Clamp `color`'s lightness and chroma into the target's ranges.
+    pub fn fit(&self, color: Oklch) -> Oklch {
+        Oklch::new(
+            color.l.clamp(self.lightness[0], self.lightness[1]),
+            color.chroma.clamp(self.chroma[0], self.chroma[1]),
+            color.hue,
+        )
+    }
+}
+
+/// Coefficients ranking the candidates within an accent's hue tolerance. Each
+/// term is relative: hue distance over the tolerance, chroma and cluster
+/// weight over the largest among the candidates.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Scoring {
+    /// Penalty for distance from the target hue.
+    pub hue: f32,
+    /// Reward for saturated candidates.
+    pub chroma: f32,
+    /// Reward for candidates covering more of the image.
+    pub weight: f32,
+}
+
+impl Default for Scoring {
+    fn default() -> Self {
+        Self {
+            hue: 1.0,
+            chroma: 0.5,
+            weight: 0.5,
+        }
+    }
+}
+
+/// Allowed contrast envelope factors.
+pub const ENVELOPE_RANGE: std::ops::RangeInclusive<f32> = 0.1..=3.0;
+
+/// Where the background color comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BgFrom {
+    /// The darkest extracted color (the lightest in light mode), clamped.
+    #[default]
+    Darkest,
+    /// A tint of the image's dominant hue: its heaviest chromatic color.
+    Dominant,
+    /// A tint of a given color (`bg-color`).
+    Custom,
+}
+
+/// Per-slot targets for accent assignment (slots 1-6).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Profile {
+    /// Targets for red, green, yellow, blue, magenta and cyan.
+    pub accents: [AccentTarget; 6],
+    /// Minimum delta-E (CIE76) between any two accents; closer ones are
+    /// rotated toward their target hues. 0 disables the check.
+    pub min_separation: f32,
+    /// How candidates for each accent are ranked.
+    pub scoring: Scoring,
+    /// Where the background's hue and chroma come from.
+    pub bg_from: BgFrom,
+    /// Color whose hue and chroma `BgFrom::Custom` uses.
+    pub bg_color: Option<Color>,
+    /// Oklch lightness of the background, replacing the source's.
+    pub bg_lightness: Option<f32>,
+    /// Oklch lightness of the foreground (bright white), replacing the
+    /// default; white moves with it.
+    pub fg_lightness: Option<f32>,
+    /// Scale of the lightness gap between the background and the text
+    /// colors (white, bright black, bright white): below 1 is softer, above
+    /// 1 harsher.
+    pub contrast_envelope: f32,
+}
+
+impl Default for Profile {
+    /// Evenly spread hues, no lightness or chroma limits.
+    fn default() -> Self {
+        Self {
+            accents: std::array::from_fn(|i| AccentTarget {
+                hue: target_hue(i + 1).expect("accent slot"),
+                tolerance: MAX_HUE_DISTANCE,
+                lightness: [0.0, 1.0],
+                chroma: [0.0, 0.4],
+            }),
+            min_separation: DEFAULT_MIN_SEPARATION,
+            scoring: Scoring::default(),
+            bg_from: BgFrom::Darkest,
+            bg_color: None,
+            bg_lightness: None,
+            fg_lightness: None,
+            contrast_envelope: 1.0,
+        }
+    }
+}
+
+/// One accent table in a profile file; unset keys keep the default.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct AccentEntry {
+    hue: Option<f32>,
+    tolerance: Option<f32>,
+    lightness: Option<[f32; 2]>,
+    chroma: Option<[f32; 2]>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+struct ProfileFile {
+    min_separation: Option<f32>,
+    scoring: Option<Scoring>,
+    bg_from: Option<BgFrom>,
+    bg_color: Option<Color>,
+    bg_lightness: Option<f32>,
+    fg_lightness: Option<f32>,
+    contrast_envelope: Option<f32>,
+    red: AccentEntry,
+    green: AccentEntry,
+    yellow: AccentEntry,
+    blue: AccentEntry,
+    magenta: AccentEntry,
+    cyan: AccentEntry,
+}
+
+impl Profile {
+    /// Names of the built-in profiles.
+    pub fn builtin_names() -> impl Iterator<Item = &'static str> {
+        BUILTIN_PROFILES.iter().map(|&(name, _)| name)
+    }
+
+    /// The built-in profile called `name`.
+    pub fn builtin(name: &str) -> Option<Profile> {
+        BUILTIN_PROFILES
+            .iter()
+            .find(|&&(n, _)| n == name)
+            .map(|&(_, text)| Self::parse(text).expect("valid built-in profile"))
+    }
+
+    /// Load a profile file.
+    pub fn load(path: &Path) -> Result<Profile> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read profile: {}", path.display()))?;
+        Self::parse(&text).with_context(|| format!("invalid profile: {}", path.display()))
+    }
+
+    /// Parse and validate profile TOML: optional top-level `min-separation`,
+    /// `bg-from`, `bg-color`, `bg-lightness`, `fg-lightness` and
+    /// `contrast-envelope`, an optional `[scoring]` table with `hue`, `chroma`
+    /// and `weight` coefficients, and optional `[red]` ... `[cyan]` tables
+    /// with `hue`, `tolerance`, `lightness = [min, max]` and
+    /// `chroma = [min, max]`.
+    pub fn parse(text: &str) -> Result<Profile> {
+        let file: ProfileFile = toml::from_str(text)?;
+        let entries = [
+            file.red,
+            file.green,
+            file.yellow,
+            file.blue,
+            file.magenta,
+            file.cyan,
+        ];
+        let mut profile = Profile::default();
+        if let Some(min_separation) = file.min_separation {
+            profile.min_separation = check_separation(min_separation)?;
+        }
+        if let Some(scoring) = file.scoring {
+            if [scoring.hue, scoring.chroma, scoring.weight]
+                .iter()
+                .any(|&k| k.is_nan() || k < 0.0)
+            {
+                bail!("[scoring] coefficients must not be negative");
+            }
+            profile.scoring = scoring;
+        }
+        profile.bg_from = file.bg_from.unwrap_or_default();
+        profile.bg_color = file.bg_color;
+        profile.bg_lightness = file.bg_lightness;
+        profile.fg_lightness = file.fg_lightness;
+        profile.contrast_envelope = file.contrast_envelope.unwrap_or(1.0);
+        profile.check_background()?;
+        for ((entry, target), name) in entries.iter().zip(&mut profile.accents).zip(ACCENT_NAMES) {
+            if let Some(hue) = entry.hue {
+                target.hue = hue.rem_euclid(360.0);
+            }
+            if let Some(tolerance) = entry.tolerance {
+                if !(0.0..=180.0).contains(&tolerance) {
+                    bail!("[{name}] tolerance must be between 0 and 180, got {tolerance}");
+                }
+                target.tolerance = tolerance;
+            }
+            if let Some(lightness) = entry.lightness {
+                check_range(name, "lightness", lightness, 1.0)?;
+                target.lightness = lightness;
+            }
+            if let Some(chroma) = entry.chroma {
+                check_range(name, "chroma", chroma, 0.4)?;
+                target.chroma = chroma;
+            }
+        }
+        Ok(profile)
+    }
+
+    /// Check the background and foreground settings: `custom` needs a
+    /// color, lightnesses must be valid Oklch lightnesses and the contrast
+    /// envelope within [`ENVELOPE_RANGE`].
+    pub fn check_background(&self) -> Result<()> {
+        if self.bg_from == BgFrom::Custom && self.bg_color.is_none() {
+            bail!("bg-from custom needs a bg-color");
+        }
+        for (key, value) in [("bg", self.bg_lightness), ("fg", self.fg_lightness)] {
+            if let Some(l) = value {
+                if !(0.0..=1.0).contains(&l) {
+                    bail!("{key}-lightness must be between 0 and 1, got {l}");
+                }
+            }
+        }
+        let envelope = self.contrast_envelope;
+        if !ENVELOPE_RANGE.contains(&envelope) {
+            bail!(
+                "contrast-envelope must be between {} and {}, got {envelope}",
+                ENVELOPE_RANGE.start(),
+                ENVELOPE_RANGE.end()
+            );
+        }
+        Ok(())
+    }
+
+    /// Target hue for an accent slot (1-6 or their bright variants 9-14).
+    pub fn target_hue(&self, slot: usize) -> Option<f32> {
+        let base = if (9..=14).contains(&slot) {
+            slot - 8
+        } else {
+            slot
+        };
+        (1..=6).contains(&base).then(|| self.accents[base - 1].hue)
+    }
+}
+
+/// Validate a minimum accent separation.
+pub fn check_separation(delta_e: f32) -> Result<f32> {
+    if !(0.0..=100.0).contains(&delta_e) {
+        bail!("min-separation must be a delta-E between 0 and 100, got {delta_e}");
+    }
+    Ok(delta_e)
+}
+
+fn check_range(name: &str, key: &str, [min, max]: [f32; 2], limit: f32) -> Result<()> {
+    if !(0.0 <= min && min <= max && max <= limit) {
+        bail!("[{name}] {key} must be [min, max] within 0..{limit}, got [{min}, {max}]");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtins_parse() {
+        for name in Profile::builtin_names() {
+            assert!(Profile::builtin(name).is_some(), "{name}");
+        }
+        assert_eq!(Profile::builtin("default"), Some(Profile::default()));
+        assert_eq!(Profile::builtin("monokai"), None);
+        let nord = Profile::builtin("nord").unwrap();
+        assert_eq!(nord.target_hue(4), Some(249.0));
+        assert_eq!(nord.target_hue(12), Some(249.0));
+        assert_eq!(nord.target_hue(7), None);
+    }
+
+    #[test]
+    fn unset_keys_keep_defaults() {
+        let profile = Profile::parse("[red]\nhue = -10\nchroma = [0.1, 0.2]\n").unwrap();
+        let default = Profile::default();
+        assert_eq!(profile.accents[0].hue, 350.0);
+        assert_eq!(profile.accents[0].chroma, [0.1, 0.2]);
+        assert_eq!(profile.accents[0].tolerance, default.accents[0].tolerance);
+        assert_eq!(profile.accents[1..], default.accents[1..]);
+    }
+
+    #[test]
+    fn rejects_bad_profiles() {
+        assert!(Profile::parse("[orange]\nhue = 50\n").is_err());
+        assert!(Profile::parse("[red]\nshade = 1\n").is_err());
+        assert!(Profile::parse("[red]\nlightness = [0.8, 0.2]\n").is_err());
+        assert!(Profile::parse("[red]\nchroma = [0.0, 0.9]\n").is_err());
+        assert!(Profile::parse("[red]\ntolerance = 200\n").is_err());
+        assert!(Profile::parse("min-separation = -1\n").is_err());
+        assert!(Profile::parse("[scoring]\nweight = -0.5\n").is_err());
+        assert!(Profile::parse("bg-from = \"custom\"\n").is_err());
+        assert!(Profile::parse("bg-lightness = 1.5\n").is_err());
+        assert!(Profile::parse("fg-lightness = -0.1\n").is_err());
+        assert!(Profile::parse("contrast-envelope = 0\n").is_err());
+        let custom = Profile::parse("bg-from = \"custom\"\nbg-color = \"#102030\"\n").unwrap();
+        assert_eq!(custom.bg_color, Some(Color::new(16, 32, 48)));
+        let scoring = Profile::parse("[scoring]\nweight = 2\n").unwrap().scoring;
+        assert_eq!(scoring.weight, 2.0);
+        assert_eq!(scoring.hue, Scoring::default().hue);
+        assert_eq!(
+            Profile::parse("min-separation = 0\n")
+                .unwrap()
+                .min_separation,
+            0.0
+        );
+    }
+
+    #[test]
+    fn fit_clamps_into_ranges() {
+        let target = Profile::builtin("gruvbox").unwrap().accents[3];
+        let fitted = target.fit(Oklch::new(0.9, 0.2, 200.0));
+        assert_eq!(fitted.l, target.lightness[1]);
+        assert_eq!(fitted.chroma, target.chroma[1]);
+        assert!((fitted.hue.into_positive_degrees() - 200.0).abs() < 1e-3);
+    }
+}
@@ -0,0 +1,367 @@
+use std::ops::Range;
+
+use palette::Lab;
+use serde::{Deserialize, Serialize};
+
+use crate::color::Color;
+
+use super::extract::{extract_colors_with_seed, finish, ExtractedColor};
+use super::gmm::Gmm;
+
+/// A color quantizer: reduces image pixels to at most `k` weighted colors.
+pub trait Quantizer {
+    /// Quantize `pixels` to at most `k` colors, deduplicated and sorted by
+    /// weight (descending). Deterministic quantizers ignore `seed`.
+    fn quantize(&self, pixels: &[Lab], k: usize, seed: u64) -> Vec<ExtractedColor>;
+}
+
+/// Selectable color extraction algorithms.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Algorithm {
+    /// K-means clustering (Hamerly) in LAB space.
+    #[default]
+    Kmeans,
+    /// Recursive median splits of the widest LAB channel.
+    MedianCut,
+    /// RGB octree, merging the smallest branches first.
+    Octree,
+    /// Gaussian mixture fitted by expectation-maximization: soft membership,
+    /// for smooth photographic gradients.
+    Gmm,
+}
+
+impl Algorithm {
+    /// The quantizer implementing this algorithm.
+    pub fn quantizer(self) -> Box<dyn Quantizer> {
+        match self {
+            Algorithm::Kmeans => Box::new(KMeans),
+            Algorithm::MedianCut => Box::new(MedianCut),
+            Algorithm::Octree => Box::new(Octree),
+            Algorithm::Gmm => Box::new(Gmm),
+        }
+    }
+}
+
+/// K-means clustering; see [`extract_colors_with_seed`].
+pub struct KMeans;
+
+impl Quantizer for KMeans {
+    fn quantize(&self, pixels: &[Lab], k: usize, seed: u64) -> Vec<ExtractedColor> {
+        extract_colors_with_seed(pixels, k, seed)
+    }
+}
+
+/// Median cut: repeatedly split the box with the widest channel extent at its
+/// median until there are `k` boxes, then average each box.
+pub struct MedianCut;
+
+impl Quantizer for MedianCut {
+    fn quantize(&self, pixels: &[Lab], k: usize, _seed: u64) -> Vec<ExtractedColor> {
+        if pixels.is_empty() {
+            return Vec::new();
+        }
+        let mut pixels = pixels.to_vec();
+        let mut boxes: Vec<Range<usize>> = Vec::with_capacity(k);
+        boxes.push(0..pixels.len());
+        while boxes.len() < k {
+            let widest = boxes
+                .iter()
+                .enumerate()
+                .filter(|(_, r)| r.len() > 1)
+                .map(|(i, r)| {
+                    let (channel, extent) = widest_channel(&pixels[r.clone()]);
+                    (i, channel, extent)
+                })
+                .max_by(|a, b| a.2.total_cmp(&b.2));
+            let Some((i, channel, extent)) = widest else {
+                break;
+            };
+            if extent <= 0.0 {
+                break;
+            }
+            let range = boxes.swap_remove(i);
+            let slice = &mut pixels[range.clone()];
+            slice.sort_unstable_by(|a, b| {
+                channel_value(a, channel).total_cmp(&channel_value(b, channel))
+            });
+            let cut = boundary_near_median(slice, channel);
+            boxes.push(range.start..range.start + cut);
+            boxes.push(range.start + cut..range.end);
+        }
+
+        let total = pixels.len() as f32;
+        let colors = boxes
+            .into_iter()
+            .map(|r| ExtractedColor {
+                color: mean_color(&pixels[r.clone()]),
+                weight: r.len() as f32 / total,
+            })
+            .collect();
+        finish(colors)
+    }
+}
+
+/// Split index in a slice sorted along `channel`: the value boundary closest
+/// to the median, so runs of identical pixels (flat skies, walls) are never cut
+/// in two while a small distinct cluster next to them stays merged.
+fn boundary_near_median(sorted: &[Lab], channel: usize) -> usize {
+    let mid = sorted.len() / 2;
+    let changes =
+        |i: &usize| channel_value(&sorted[i - 1], channel) < channel_value(&sorted[*i], channel);
+    let above = (mid.max(1)..sorted.len()).find(changes);
+    let below = (1..mid.max(1)).rev().find(changes);
+    match (below, above) {
+        (Some(b), Some(a)) => {
+            if mid - b <= a - mid {
+                b
+            } else {
+                a
+            }
+        }
+        (Some(b), None) => b,
+        (None, Some(a)) => a,
+        // Only reached for a zero-extent box, which is never split
+        (None, None) => mid,
+    }
+}
+
+/// The LAB channel (0 = L, 1 = a, 2 = b) with the largest extent, and that extent.
+fn widest_channel(pixels: &[Lab]) -> (usize, f32) {
+    (0..3)
+        .map(|channel| {
+            let (min, max) = pixels.iter().fold((f32::MAX, f32::MIN), |(lo, hi), p| {
+                let v = channel_value(p, channel);
+                (lo.min(v), hi.max(v))
+            });
+            (channel, max - min)
+        })
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .unwrap_or((0, 0.0))
+}
+
+fn channel_value(p: &Lab, channel: usize) -> f32 {
+    match channel {
+        0 => p.l,
+        1 => p.a,
+        _ => p.b,
+    }
+}
+
+/// Average of LAB pixels as a color.
+fn mean_color(pixels: &[Lab]) -> Color {
+    let n = pixels.len().max(1) as f32;
+    let (l, a, b) = pixels
+        .iter()
+        .fold((0.0, 0.0, 0.0), |(l, a, b), p| (l + p.l, a + p.a, b + p.b));
+    Color::from_lab(Lab::new(l / n, a / n, b / n))
+}
+
+/// Levels below the root; leaves start as 32 buckets per RGB channel.
+const OCTREE_DEPTH: usize = 5;
+
+/// Octree quantization: bucket pixels by the high bits of their RGB values,
+/// then fold the smallest deepest branches into their parents until at most
+/// `k` leaves remain. Leaves keep LAB sums so colors are averaged perceptually.
+pub struct Octree;
+
+#[derive(Default)]
+struct OctreeNode {
+    /// Child node indices; 0 means no child (the root is never a child).
+    children: [usize; 8],
+    count: u32,
+    sum: [f32; 3],
+    leaf: bool,
+}
+
+impl Quantizer for Octree {
+    fn quantize(&self, pixels: &[Lab], k: usize, _seed: u64) -> Vec<ExtractedColor> {
+        if pixels.is_empty() {
+            return Vec::new();
+        }
+        let mut nodes = vec![OctreeNode::default()];
+        // Internal nodes per level, for reduction
+        let mut levels: Vec<Vec<usize>> = vec![vec![0]; 1];
+        levels.resize(OCTREE_DEPTH, Vec::new());
+        let mut leaves = 0;
+
+        for p in pixels {
+            let rgb = Color::from_lab(*p);
+            let mut node = 0;
+            for level in 0..OCTREE_DEPTH {
+                let shift = 7 - level;
+                let idx = (((rgb.r >> shift) & 1) << 2
+                    | ((rgb.g >> shift) & 1) << 1
+                    | ((rgb.b >> shift) & 1)) as usize;
+                if nodes[node].children[idx] == 0 {
+                    nodes.push(OctreeNode::default());
+                    let child = nodes.len() - 1;
+                    nodes[node].children[idx] = child;
+                    if level + 1 < OCTREE_DEPTH {
+                        levels[level + 1].push(child);
+                    } else {
+                        nodes[child].leaf = true;
+                        leaves += 1;
+                    }
+                }
+                node = nodes[node].children[idx];
+            }
+            let leaf = &mut nodes[node];
+            leaf.count += 1;
+            leaf.sum[0] += p.l;
+            leaf.sum[1] += p.a;
+            leaf.sum[2] += p.b;
+        }
+
+        // Reduce deepest levels first; every node at a level being reduced
+        // only has leaf children, since deeper levels were fully folded.
+        let k = k.max(1);
+        for level in (0..OCTREE_DEPTH).rev() {
+            if leaves <= k {
+                break;
+            }
+            let mut candidates: Vec<(u32, usize)> = levels[level]
+                .iter()
+                .map(|&n| {
+                    let count = nodes[n]
+                        .children
+                        .iter()
+                        .filter(|&&c| c != 0)
+                        .map(|&c| nodes[c].count)
+                        .sum();
+                    (count, n)
+                })
+                .collect();
+            candidates.sort_unstable();
+            for (_, n) in candidates {
+                if leaves <= k {
+                    break;
+                }
+                let children = std::mem::take(&mut nodes[n].children);
+                let mut merged = 0;
+                for c in children.into_iter().filter(|&c| c != 0) {
+                    let (count, sum) = (nodes[c].count, nodes[c].sum);
+                    let node = &mut nodes[n];
+                    node.count += count;
+                    for (s, v) in node.sum.iter_mut().zip(sum) {
+                        *s += v;
+                    }
+                    merged += 1;
+                }
+                nodes[n].leaf = true;
+                leaves = leaves + 1 - merged;
+            }
+        }
+
+        // Collect leaves reachable from the root
+        let total = pixels.len() as f32;
+        let mut colors = Vec::new();
+        let mut stack = vec![0];
+        while let Some(n) = stack.pop() {
+            let node = &nodes[n];
+            if node.leaf {
+                let c = node.count.max(1) as f32;
+                colors.push(ExtractedColor {
+                    color: Color::from_lab(Lab::new(
+                        node.sum[0] / c,
+                        node.sum[1] / c,
+                        node.sum[2] / c,
+                    )),
+                    weight: node.count as f32 / total,
+                });
+            } else {
+                stack.extend(node.children.iter().filter(|&&c| c != 0));
+            }
+        }
+        finish(colors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Mostly gray with a small saturated red patch.
+    fn gray_with_accent() -> Vec<Lab> {
+        let gray = Color::new(120, 120, 120).to_lab();
+        let blue = Color::new(40, 60, 200).to_lab();
+        let red = Color::new(220, 30, 30).to_lab();
+        (0..10_000)
+            .map(|i| match i % 100 {
+                0..=2 => red,
+                3..=40 => blue,
+                _ => gray,
+            })
+            .collect()
+    }
+
+    fn has_color_near(colors: &[ExtractedColor], target: Color) -> bool {
+        let t = target.to_lab();
+        colors.iter().any(|c| {
+            let l = c.color.to_lab();
+            (l.l - t.l).powi(2) + (l.a - t.a).powi(2) + (l.b - t.b).powi(2) < 25.0
+        })
+    }
+
+    #[test]
+    fn every_algorithm_finds_small_accent() {
+        let pixels = gray_with_accent();
+        for algorithm in [
+            Algorithm::Kmeans,
+            Algorithm::MedianCut,
+            Algorithm::Octree,
+            Algorithm::Gmm,
+        ] {
+            let colors = algorithm.quantizer().quantize(&pixels, 8, 42);
+            assert!(
+                has_color_near(&colors, Color::new(220, 30, 30)),
+                "{algorithm:?} lost the red accent"
+            );
+            let total: f32 = colors.iter().map(|c| c.weight).sum();
+            assert!(
+                (total - 1.0).abs() < 1e-3,
+                "{algorithm:?} weights sum to {total}"
+            );
+            assert!(colors.windows(2).all(|w| w[0].weight >= w[1].weight));
+        }
+    }
+
+    #[test]
+    fn results_respect_k() {
+        let pixels: Vec<Lab> = (0..4096)
+            .map(|i| {
+                Color::new(
+                    (i % 16 * 16) as u8,
+                    (i / 16 % 16 * 16) as u8,
+                    (i / 256 * 16) as u8,
+                )
+                .to_lab()
+            })
+            .collect();
+        for algorithm in [Algorithm::MedianCut, Algorithm::Octree, Algorithm::Gmm] {
+            let colors = algorithm.quantizer().quantize(&pixels, 6, 42);
+            assert!(
+                !colors.is_empty() && colors.len() <= 6,
+                "{algorithm:?}: {}",
+                colors.len()
+            );
+        }
+    }
+
+    #[test]
+    fn single_color_image_yields_one_color() {
+        let pixels = vec![Color::new(10, 200, 10).to_lab(); 500];
+        for algorithm in [Algorithm::MedianCut, Algorithm::Octree, Algorithm::Gmm] {
+            let colors = algorithm.quantizer().quantize(&pixels, 16, 42);
+            assert_eq!(colors.len(), 1, "{algorithm:?}");
+            assert!((colors[0].weight - 1.0).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn empty_input_yields_nothing() {
+        for algorithm in [Algorithm::MedianCut, Algorithm::Octree, Algorithm::Gmm] {
+            assert!(algorithm.quantizer().quantize(&[], 16, 42).is_empty());
+        }
+    }
+}
@@ -0,0 +1,223 @@
+use palette::Oklch;
+use serde::Deserialize;
+
+use super::assign::AnsiPalette;
+use super::extract::ExtractedColor;
+use crate::color::{max_chroma, Color};
+
+/// Overall look applied to the accents after slot assignment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PaletteStyle {
+    /// Light, soft accents: lightness raised, chroma capped.
+    Pastel,
+    /// Saturated accents: chroma pushed toward the edge of the sRGB gamut.
+    Vibrant,
+    /// Subdued accents: chroma halved.
+    Muted,
+    /// Every accent takes the image's dominant hue, told apart by lightness.
+    Monochrome,
+}
+
+/// Accent slots the styles act on: normal 1-6 and bright 9-14.
+const ACCENT_SLOTS: [usize; 12] = [1, 2, 3, 4, 5, 6, 9, 10, 11, 12, 13, 14];
+
+/// Oklch lightness pastel accents are pulled halfway toward.
+const PASTEL_L: f32 = 0.88;
+
+/// Maximum Oklch chroma of a pastel accent.
+const PASTEL_MAX_CHROMA: f32 = 0.09;
+
+/// Fraction of the in-gamut maximum chroma a vibrant accent reaches.
+const VIBRANT_GAMUT_FRACTION: f32 = 0.9;
+
+/// Chroma multiplier for muted accents.
+const MUTED_CHROMA: f32 = 0.5;
+
+/// Monochrome accent chroma range.
+const MONO_CHROMA: (f32, f32) = (0.04, 0.14);
+
+/// Oklch lightness of the first monochrome accent and the step between
+/// consecutive ones (1-6). Both ramps stay inside the range that passes 4.5:1
+/// contrast, so contrast enforcement does not flatten them.
+const MONO_L_DARK: (f32, f32) = (0.66, 0.05);
+const MONO_L_LIGHT: (f32, f32) = (0.52, -0.05);
+
+/// Lightness step from a monochrome accent to its bright variant, away from
+/// the background.
+const MONO_BRIGHT_STEP: f32 = 0.05;
+
+/// Minimum Oklch chroma for an extracted color to count as having a hue.
+const MIN_CHROMA: f32 = 0.02;
+
+/// Apply `styles` in order. `colors` are the extracted colors the palette
+/// came from; monochrome takes its hue from the heaviest chromatic one.
+pub fn apply_styles(palette: &mut AnsiPalette, styles: &[PaletteStyle], colors: &[ExtractedColor]) {
+    for &style in styles {
+        style.apply(palette, colors);
+    }
+}
+
+impl PaletteStyle {
+    /// Restyle the accent slots in place.
+    pub fn apply(self, palette: &mut AnsiPalette, colors: &[ExtractedColor]) {
+        match self {
+            PaletteStyle::Pastel => map_accents(palette, |mut c| {
+                if c.l < PASTEL_L {
+                    c.l += (PASTEL_L - c.l) * 0.5;
+                }
+                c.chroma = c.chroma.min(PASTEL_MAX_CHROMA);
+                c
+            }),
+            PaletteStyle::Vibrant => map_accents(palette, |mut c| {
+                let target = max_chroma(c.l, c.hue.into_degrees()) * VIBRANT_GAMUT_FRACTION;
+                c.chroma = c.chroma.max(target);
+                c
+            }),
+            PaletteStyle::Muted => map_accents(palette, |mut c| {
+                c.chroma *= MUTED_CHROMA;
+                c
+            }),
+            PaletteStyle::Monochrome => monochrome(palette, colors),
+        }
+    }
+}
+
+fn map_accents(palette: &mut AnsiPalette, f: impl Fn(Oklch) -> Oklch) {
+    for slot in ACCENT_SLOTS {
+        palette.slots[slot] = Color::from_oklch(f(palette.slots[slot].to_oklch()));
+    }
+}
+
+/// Give every accent the dominant hue, with lightness stepping across the
+/// normal accents and each bright variant one step further from the
+/// background.
+fn monochrome(palette: &mut AnsiPalette, colors: &[ExtractedColor]) {
+    let hue = dominant_hue(palette, colors);
+    let dark = palette.background.relative_luminance() < 0.5;
+    let (start, step) = if dark { MONO_L_DARK } else { MONO_L_LIGHT };
+    let bright_step = if dark {
+        MONO_BRIGHT_STEP
+    } else {
+        -MONO_BRIGHT_STEP
+    };
+    for i in 1..=6 {
+        let chroma = palette.slots[i]
+            .to_oklch()
+            .chroma
+            .clamp(MONO_CHROMA.0, MONO_CHROMA.1);
+        let l = start + step * (i - 1) as f32;
+        let bright_l = (l + bright_step).clamp(0.0, 1.0);
+        palette.slots[i] = Color::from_oklch(Oklch::new(l, chroma, hue));
+        palette.slots[i + 8] = Color::from_oklch(Oklch::new(bright_l, chroma, hue));
+    }
+}
+
+/// Hue of the heaviest extracted color that has one, else of the most
+/// chromatic accent.
+fn dominant_hue(palette: &AnsiPalette, colors: &[ExtractedColor]) -> f32 {
+    let heaviest = colors
+        .iter()
+        .map(|c| (c.weight, c.color.to_oklch()))
+        .filter(|(_, c)| c.chroma > MIN_CHROMA)
+        .max_by(|a, b| a.0.total_cmp(&b.0))
+        .map(|(_, c)| c);
+    let oklch = heaviest.unwrap_or_else(|| {
+        (1..=6)
+            .map(|i| palette.slots[i].to_oklch())
+            .max_by(|a, b| a.chroma.total_cmp(&b.chroma))
+            .expect("six accent slots")
+    });
+    oklch.hue.into_degrees()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::ThemeMode;
+    use crate::pipeline::assign::{assign_slots, hue_distance};
+
+    fn colors() -> Vec<ExtractedColor> {
+        vec![
+            ExtractedColor {
+                color: Color::new(30, 110, 160),
+                weight: 0.7,
+            },
+            ExtractedColor {
+                color: Color::new(200, 80, 60),
+                weight: 0.3,
+            },
+        ]
+    }
+
+    fn palette() -> AnsiPalette {
+        assign_slots(&colors(), ThemeMode::Dark)
+    }
+
+    fn chroma(c: Color) -> f32 {
+        c.to_oklch().chroma
+    }
+
+    #[test]
+    fn pastel_lightens_and_softens() {
+        let before = palette();
+        let mut after = before.clone();
+        PaletteStyle::Pastel.apply(&mut after, &colors());
+        for slot in ACCENT_SLOTS {
+            let (b, a) = (before.slots[slot].to_oklch(), after.slots[slot].to_oklch());
+            assert!(a.l >= b.l - 0.01, "slot {slot}");
+            assert!(a.chroma <= PASTEL_MAX_CHROMA + 0.01, "slot {slot}");
+        }
+        assert_eq!(after.slots[0], before.slots[0]);
+        assert_eq!(after.slots[15], before.slots[15]);
+    }
+
+    #[test]
+    fn vibrant_and_muted_move_chroma_opposite_ways() {
+        let before = palette();
+        let mut vibrant = before.clone();
+        PaletteStyle::Vibrant.apply(&mut vibrant, &colors());
+        let mut muted = before.clone();
+        PaletteStyle::Muted.apply(&mut muted, &colors());
+        for slot in ACCENT_SLOTS {
+            let c = chroma(before.slots[slot]);
+            assert!(chroma(vibrant.slots[slot]) >= c - 0.005, "slot {slot}");
+            assert!(
+                chroma(muted.slots[slot]) <= c * MUTED_CHROMA + 0.01,
+                "slot {slot}"
+            );
+        }
+    }
+
+    #[test]
+    fn monochrome_shares_the_dominant_hue() {
+        let mut p = palette();
+        PaletteStyle::Monochrome.apply(&mut p, &colors());
+        let dominant = f32::from(Color::new(30, 110, 160).to_oklch().hue);
+        let mut lightness = Vec::new();
+        for slot in ACCENT_SLOTS {
+            let c = p.slots[slot].to_oklch();
+            assert!(
+                hue_distance(f32::from(c.hue), dominant) < 5.0,
+                "slot {slot}"
+            );
+            lightness.push(c.l);
+        }
+        // Normal accents step in lightness so they stay distinguishable
+        assert!(lightness[..6].windows(2).all(|w| w[1] > w[0] + 0.03));
+    }
+
+    #[test]
+    fn styles_compose_in_order() {
+        let mut p = palette();
+        apply_styles(
+            &mut p,
+            &[PaletteStyle::Monochrome, PaletteStyle::Muted],
+            &colors(),
+        );
+        let mut expected = palette();
+        PaletteStyle::Monochrome.apply(&mut expected, &colors());
+        PaletteStyle::Muted.apply(&mut expected, &colors());
+        assert_eq!(p, expected);
+    }
+}
@@ -0,0 +1,227 @@
+use std::borrow::Cow;
+
+use palette::Lab;
+use serde::{Deserialize, Serialize};
+
+use super::extract::{finish, ExtractedColor, PreparedImage};
+
+/// How much each pixel counts when clustering.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Weighting {
+    /// Every pixel counts the same, so large areas dominate.
+    #[default]
+    Area,
+    /// Saturated pixels count more.
+    Chroma,
+    /// Saturated pixels and pixels in detailed, edge-dense regions count more.
+    Saliency,
+}
+
+/// LAB chroma at which a pixel's weight doubles.
+const CHROMA_SCALE: f32 = 20.0;
+
+/// Mean local ΔE at which a pixel's weight doubles under saliency weighting.
+const EDGE_SCALE: f32 = 10.0;
+
+/// Pixels to cluster under `weighting`: the image's own pixels for
+/// [`Weighting::Area`], otherwise a same-sized resample in which each pixel
+/// appears in proportion to its weight.
+pub fn emphasize(image: &PreparedImage, weighting: Weighting) -> Cow<'_, [Lab]> {
+    let weights: Vec<f32> = match weighting {
+        Weighting::Area => return Cow::Borrowed(&image.pixels),
+        Weighting::Chroma => image.pixels.iter().map(chroma_weight).collect(),
+        Weighting::Saliency => {
+            let edges = image
+                .frames()
+                .flat_map(|frame| edge_density(frame, image.width, image.height));
+            image
+                .pixels
+                .iter()
+                .zip(edges)
+                .map(|(p, e)| chroma_weight(p) + e / EDGE_SCALE)
+                .collect()
+        }
+    };
+    Cow::Owned(resample(&image.pixels, &weights))
+}
+
+/// Replace clustering weights with each color's true share of `pixels`
+/// (nearest color in LAB), so reported weights reflect area again.
+pub fn restore_area(colors: Vec<ExtractedColor>, pixels: &[Lab]) -> Vec<ExtractedColor> {
+    if colors.is_empty() || pixels.is_empty() {
+        return colors;
+    }
+    let labs: Vec<Lab> = colors.iter().map(|c| c.color.to_lab()).collect();
+    let mut counts = vec![0usize; colors.len()];
+    for p in pixels {
+        let nearest = labs
+            .iter()
+            .enumerate()
+            .map(|(i, c)| (i, distance_sq(p, c)))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map_or(0, |(i, _)| i);
+        counts[nearest] += 1;
+    }
+    let total = pixels.len() as f32;
+    let colors = colors
+        .into_iter()
+        .zip(counts)
+        .map(|(c, n)| ExtractedColor {
+            weight: n as f32 / total,
+            ..c
+        })
+        .collect();
+    finish(colors)
+}
+
+fn chroma_weight(p: &Lab) -> f32 {
+    1.0 + p.a.hypot(p.b) / CHROMA_SCALE
+}
+
+fn distance_sq(a: &Lab, b: &Lab) -> f32 {
+    (a.l - b.l).powi(2) + (a.a - b.a).powi(2) + (a.b - b.b).powi(2)
+}
+
+/// Mean ΔE to the right and lower neighbors over each pixel's 3x3 block,
+/// for one `w` x `h` frame.
+fn edge_density(px: &[Lab], w: usize, h: usize) -> Vec<f32> {
+    let gradient: Vec<f32> = (0..w * h)
+        .map(|i| {
+            let (x, y) = (i % w, i / w);
+            let right = if x + 1 < w {
+                distance_sq(&px[i], &px[i + 1]).sqrt()
+            } else {
+                0.0
+            };
+            let down = if y + 1 < h {
+                distance_sq(&px[i], &px[i + w]).sqrt()
+            } else {
+                0.0
+            };
+            right + down
+        })
+        .collect();
+    (0..w * h)
+        .map(|i| {
+            let (x, y) = (i % w, i / w);
+            let (mut sum, mut n) = (0.0, 0);
+            for ny in y.saturating_sub(1)..(y + 2).min(h) {
+                for nx in x.saturating_sub(1)..(x + 2).min(w) {
+                    sum += gradient[ny * w + nx];
+                    n += 1;
+                }
+            }
+            sum / n as f32
+        })
+        .collect()
+}
+
+/// Systematic resampling: `pixels.len()` evenly spaced draws over the
+/// cumulative weights. Deterministic, so results are reproducible.
+fn resample(pixels: &[Lab], weights: &[f32]) -> Vec<Lab> {
+    let total: f64 = weights.iter().map(|&w| w as f64).sum();
+    let n = pixels.len();
+    if n == 0 || total <= 0.0 {
+        return pixels.to_vec();
+    }
+    let step = total / n as f64;
+    let mut out = Vec::with_capacity(n);
+    let mut cumulative = 0.0;
+    let mut i = 0;
+    for draw in 0..n {
+        let target = (draw as f64 + 0.5) * step;
+        while i + 1 < n && cumulative + weights[i] as f64 <= target {
+            cumulative += weights[i] as f64;
+            i += 1;
+        }
+        out.push(pixels[i]);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+
+    /// A flat gray image with a small, busy red-and-white checker patch.
+    fn wall_with_detail() -> PreparedImage {
+        let (width, height) = (64, 64);
+        let gray = Color::new(128, 128, 128).to_lab();
+        let red = Color::new(220, 30, 30).to_lab();
+        let white = Color::new(250, 250, 250).to_lab();
+        let pixels = (0..width * height)
+            .map(|i| {
+                let (x, y) = (i % width, i / width);
+                if x < 8 && y < 8 {
+                    if (x + y) % 2 == 0 {
+                        red
+                    } else {
+                        white
+                    }
+                } else {
+                    gray
+                }
+            })
+            .collect();
+        PreparedImage {
+            width,
+            height,
+            pixels,
+            frames: 1,
+        }
+    }
+
+    fn share_near(pixels: &[Lab], target: Color) -> f32 {
+        let t = target.to_lab();
+        pixels.iter().filter(|p| distance_sq(p, &t) < 25.0).count() as f32 / pixels.len() as f32
+    }
+
+    #[test]
+    fn area_keeps_pixels_unchanged() {
+        let image = wall_with_detail();
+        assert!(matches!(
+            emphasize(&image, Weighting::Area),
+            Cow::Borrowed(_)
+        ));
+    }
+
+    #[test]
+    fn chroma_and_saliency_boost_the_detail() {
+        let image = wall_with_detail();
+        let red = Color::new(220, 30, 30);
+        let area = share_near(&image.pixels, red);
+        let chroma = share_near(&emphasize(&image, Weighting::Chroma), red);
+        let saliency = share_near(&emphasize(&image, Weighting::Saliency), red);
+        assert!(chroma > area * 2.0, "chroma {chroma} vs area {area}");
+        assert!(saliency > chroma, "saliency {saliency} vs chroma {chroma}");
+        assert_eq!(
+            emphasize(&image, Weighting::Saliency).len(),
+            image.pixels.len()
+        );
+    }
+
+    #[test]
+    fn restore_area_reports_true_shares() {
+        let image = wall_with_detail();
+        let colors = vec![
+            ExtractedColor {
+                color: Color::new(220, 30, 30),
+                weight: 0.5,
+            },
+            ExtractedColor {
+                color: Color::new(128, 128, 128),
+                weight: 0.5,
+            },
+            ExtractedColor {
+                color: Color::new(250, 250, 250),
+                weight: 0.0,
+            },
+        ];
+        let restored = restore_area(colors, &image.pixels);
+        assert_eq!(restored[0].color, Color::new(128, 128, 128));
+        assert!((restored[0].weight - 4032.0 / 4096.0).abs() < 1e-6);
+        assert!((restored[1].weight - 32.0 / 4096.0).abs() < 1e-6);
+    }
+}
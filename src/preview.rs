@@ -1,38 +1,69 @@
 use crate::color::Color;
+use crate::config;
 use crate::pipeline::assign::AnsiPalette;
+use crate::term::{self, ColorDepth};
 
 const RESET: &str = "\x1b[0m";
 
 const SLOT_NAMES: [&str; 8] = ["Blk", "Red", "Grn", "Yel", "Blu", "Mag", "Cyn", "Wht"];
 
-/// Set 24-bit foreground color.
-fn fg(c: &Color) -> String {
-    format!("\x1b[38;2;{};{};{}m", c.r, c.g, c.b)
+/// Delta-E below which `print_diff` and the TUI compare mode treat two
+/// colors as unchanged: roughly a just-noticeable difference.
+pub const UNCHANGED_DELTA_E: f32 = 2.3;
+
+/// Set the foreground color, as near as `depth` allows.
+fn fg(c: &Color, depth: ColorDepth) -> String {
+    format!("\x1b[{}m", depth.sgr(*c, 30))
 }
 
-/// Set 24-bit background color.
-fn bg_esc(c: &Color) -> String {
-    format!("\x1b[48;2;{};{};{}m", c.r, c.g, c.b)
+/// Set the background color, as near as `depth` allows.
+fn bg_esc(c: &Color, depth: ColorDepth) -> String {
+    format!("\x1b[{}m", depth.sgr(*c, 40))
 }
 
 /// Choose black or white text for maximum contrast against `bg`.
-fn contrast_fg(bg: &Color) -> &'static str {
+fn contrast_fg(bg: &Color, depth: ColorDepth) -> String {
     if bg.relative_luminance() > 0.4 {
-        "\x1b[38;2;0;0;0m"
+        fg(&Color::new(0, 0, 0), depth)
     } else {
-        "\x1b[38;2;255;255;255m"
+        fg(&Color::new(255, 255, 255), depth)
+    }
+}
+
+/// A compact one-line swatch: two cells of each color, at the terminal's
+/// [`term::color_depth`].
+pub fn swatch(colors: &[Color]) -> String {
+    let depth = term::color_depth();
+    let mut out = String::new();
+    for c in colors {
+        out.push_str(&format!("{}  ", bg_esc(c, depth)));
+    }
+    if !colors.is_empty() {
+        out.push_str(RESET);
     }
+    out
 }
 
-/// Print a colored terminal preview of the generated palette.
-pub fn print_preview(palette: &AnsiPalette) {
+/// Print a terminal preview of the generated palette: colored cells when
+/// `colored`, in 24-bit color or the nearest the terminal has, else the hex
+/// codes.
+pub fn print_preview(palette: &AnsiPalette, colored: bool) {
     println!();
+    if !colored {
+        print_plain(palette);
+        return;
+    }
+    let depth = term::color_depth();
 
     // Row 1: normal colors (slots 0-7)
     print!("  ");
     for (i, name) in SLOT_NAMES.iter().enumerate() {
         let c = &palette.slots[i];
-        print!("{}{} {name:^5} {RESET}", bg_esc(c), contrast_fg(c));
+        print!(
+            "{}{} {name:^5} {RESET}",
+            bg_esc(c, depth),
+            contrast_fg(c, depth)
+        );
     }
     println!();
 
@@ -40,7 +71,11 @@ pub fn print_preview(palette: &AnsiPalette) {
     print!("  ");
     for (i, name) in SLOT_NAMES.iter().enumerate() {
         let c = &palette.slots[i + 8];
-        print!("{}{} {name:^5} {RESET}", bg_esc(c), contrast_fg(c));
+        print!(
+            "{}{} {name:^5} {RESET}",
+            bg_esc(c, depth),
+            contrast_fg(c, depth)
+        );
     }
     println!();
     println!();
@@ -50,20 +85,47 @@ pub fn print_preview(palette: &AnsiPalette) {
     let foreground = &palette.foreground;
     println!(
         "  {}{}  The quick brown fox jumps over the lazy dog  {RESET}",
-        bg_esc(background),
-        fg(foreground)
+        bg_esc(background, depth),
+        fg(foreground, depth)
     );
     println!();
 
     // Show accent colors on background
-    print!("  {}  ", bg_esc(background));
+    print!("  {}  ", bg_esc(background, depth));
     for (name, slot_color) in SLOT_NAMES[1..=6].iter().zip(&palette.slots[1..=6]) {
-        print!("{}{name}{RESET}{} ", fg(slot_color), bg_esc(background));
+        print!(
+            "{}{name}{RESET}{} ",
+            fg(slot_color, depth),
+            bg_esc(background, depth)
+        );
     }
     println!("{RESET}");
     println!();
 
-    // Contrast ratios
+    print_contrast(palette);
+}
+
+/// The preview without escapes: the slots as hex codes under their names.
+fn print_plain(palette: &AnsiPalette) {
+    let names: Vec<String> = SLOT_NAMES.iter().map(|name| format!("{name:<7}")).collect();
+    for row in [&palette.slots[..8], &palette.slots[8..]] {
+        let hexes: Vec<String> = row.iter().map(|c| c.to_hex()).collect();
+        println!("  {}", names.join(" ").trim_end());
+        println!("  {}", hexes.join(" "));
+    }
+    println!();
+    println!(
+        "  Background {}  Foreground {}",
+        palette.background.to_hex(),
+        palette.foreground.to_hex()
+    );
+    println!();
+    print_contrast(palette);
+}
+
+/// Foreground contrast and the dimmest accent's, below the preview.
+fn print_contrast(palette: &AnsiPalette) {
+    let (foreground, background) = (&palette.foreground, &palette.background);
     let fg_ratio = Color::contrast_ratio(foreground, background);
     let min_accent_ratio = (1..=6)
         .chain(9..=14)
@@ -74,3 +136,117 @@ pub fn print_preview(palette: &AnsiPalette) {
     println!("  Dimmest accent:      {min_accent_ratio:.1}:1");
     println!();
 }
+
+/// Print each slot's nearest named color and delta-E to stderr, so it stays
+/// out of a theme written to stdout. `swatches` adds colored cells.
+pub fn print_named(palette: &AnsiPalette, swatches: bool) {
+    let width = config::SLOT_NAMES
+        .iter()
+        .map(|n| n.len())
+        .max()
+        .unwrap_or(0);
+    eprintln!();
+    for (slot, color) in config::SLOT_NAMES.iter().zip(palette.slots) {
+        let (name, delta_e) = color.nearest_named();
+        let cell = if swatches {
+            swatch(&[color]) + " "
+        } else {
+            String::new()
+        };
+        eprintln!(
+            "  {slot:<width$}  {cell}{}  {name} (\u{394}E {delta_e:.1})",
+            color.to_hex()
+        );
+    }
+    eprintln!();
+}
+
+/// Colors compared by `print_diff`, labeled: the special colors, then the 16
+/// slots by name.
+fn diff_rows(a: &AnsiPalette, b: &AnsiPalette) -> Vec<(&'static str, Color, Color)> {
+    let mut rows = vec![
+        ("background", a.background, b.background),
+        ("foreground", a.foreground, b.foreground),
+        ("cursor", a.cursor_color, b.cursor_color),
+        ("cursor-text", a.cursor_text, b.cursor_text),
+        ("selection-bg", a.selection_bg, b.selection_bg),
+        ("selection-fg", a.selection_fg, b.selection_fg),
+    ];
+    for (i, name) in config::SLOT_NAMES.iter().enumerate() {
+        rows.push((name, a.slots[i], b.slots[i]));
+    }
+    rows
+}
+
+/// Print a per-color comparison of two palettes with delta-E distances,
+/// marking the colors that visibly changed. `swatches` adds colored cells.
+pub fn print_diff(a: &AnsiPalette, b: &AnsiPalette, swatches: bool) {
+    let rows = diff_rows(a, b);
+    let width = rows
+        .iter()
+        .map(|(name, _, _)| name.len())
+        .max()
+        .unwrap_or(0);
+    let mut changed = 0;
+    let mut total = 0.0;
+    let mut largest = ("", 0.0);
+    for &(name, old, new) in &rows {
+        let delta = old.delta_e(new);
+        total += delta;
+        if delta > largest.1 {
+            largest = (name, delta);
+        }
+        let mark = if delta >= UNCHANGED_DELTA_E {
+            changed += 1;
+            "  *"
+        } else {
+            ""
+        };
+        let (old_cell, new_cell) = if swatches {
+            (swatch(&[old]) + " ", swatch(&[new]) + " ")
+        } else {
+            (String::new(), String::new())
+        };
+        println!(
+            "  {name:<width$}  {old_cell}{old}  ->  {new_cell}{new}  \u{394}E {delta:>5.1}{mark}"
+        );
+    }
+    println!();
+    println!(
+        "  {changed} of {} colors changed, mean \u{394}E {:.1}",
+        rows.len(),
+        total / rows.len() as f32
+    );
+    if changed > 0 {
+        println!(
+            "  Largest change: {} (\u{394}E {:.1})",
+            largest.0, largest.1
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::ThemeMode;
+    use crate::pipeline::assign::assign_slots;
+    use crate::pipeline::extract::ExtractedColor;
+
+    fn palette(color: Color) -> AnsiPalette {
+        let colors = vec![ExtractedColor { color, weight: 1.0 }];
+        assign_slots(&colors, ThemeMode::Dark)
+    }
+
+    #[test]
+    fn diff_rows_cover_specials_and_slots() {
+        let a = palette(Color::new(200, 80, 60));
+        let b = palette(Color::new(60, 120, 200));
+        let rows = diff_rows(&a, &b);
+        assert_eq!(rows.len(), 22);
+        assert_eq!(rows[0], ("background", a.background, b.background));
+        assert_eq!(rows[6 + 9], ("bright-red", a.slots[9], b.slots[9]));
+        assert!(diff_rows(&a, &a)
+            .iter()
+            .all(|(_, old, new)| old.delta_e(*new) == 0.0));
+    }
+}
@@ -0,0 +1,175 @@
+//! Pipeline progress on stderr: the current stage with a spinner and its
+//! elapsed time, redrawn in place while the stage runs.
+
+use std::io::{self, IsTerminal, Write};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// Number of pipeline stages: load, convert, cluster, assign.
+pub const STAGES: usize = 4;
+
+/// Runs shorter than this finish without ever drawing the line.
+const SHOW_AFTER: Duration = Duration::from_millis(250);
+
+/// Time between redraws.
+const TICK: Duration = Duration::from_millis(80);
+
+/// Frames of the spinner, also used by the TUI's extraction status.
+pub const SPINNER: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+
+/// Whether a progress line is on screen. Held while anything writes to
+/// stderr, so log lines and the progress line don't interleave.
+static LINE: Mutex<bool> = Mutex::new(false);
+
+/// Run `write` with the progress line cleared; the ticker draws it again
+/// below the new output on its next tick.
+pub fn suspend<R>(write: impl FnOnce() -> R) -> R {
+    let mut drawn = LINE.lock().unwrap_or_else(|e| e.into_inner());
+    if *drawn {
+        eprint!("\r\x1b[2K");
+        *drawn = false;
+    }
+    write()
+}
+
+/// What the progress line shows.
+struct Status {
+    /// 1-based stage number, 0 before the first stage.
+    stage: usize,
+    name: String,
+    stage_started: Instant,
+    done: bool,
+}
+
+/// Progress line for one pipeline run. Does nothing when disabled or when
+/// stderr is not a terminal; the line is cleared when dropped.
+pub struct Progress {
+    shared: Option<Arc<(Mutex<Status>, Condvar)>>,
+    ticker: Option<JoinHandle<()>>,
+}
+
+impl Progress {
+    /// Start reporting progress if `enabled` (no `--quiet`) and stderr is a
+    /// terminal.
+    pub fn new(enabled: bool) -> Progress {
+        if !enabled || !io::stderr().is_terminal() {
+            return Progress {
+                shared: None,
+                ticker: None,
+            };
+        }
+        let shared = Arc::new((
+            Mutex::new(Status {
+                stage: 0,
+                name: String::new(),
+                stage_started: Instant::now(),
+                done: false,
+            }),
+            Condvar::new(),
+        ));
+        let ticker = {
+            let shared = Arc::clone(&shared);
+            thread::spawn(move || tick(&shared))
+        };
+        Progress {
+            shared: Some(shared),
+            ticker: Some(ticker),
+        }
+    }
+
+    /// Move on to stage `stage` of [`STAGES`], described by `name`.
+    pub fn stage(&self, stage: usize, name: impl Into<String>) {
+        if let Some(mut status) = self.lock() {
+            status.stage = stage;
+            status.name = name.into();
+            status.stage_started = Instant::now();
+        }
+    }
+
+    /// Clear the line and stop reporting.
+    pub fn finish(self) {}
+
+    fn lock(&self) -> Option<MutexGuard<'_, Status>> {
+        let (status, _) = &**self.shared.as_ref()?;
+        Some(status.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+}
+
+impl Drop for Progress {
+    fn drop(&mut self) {
+        let Some(shared) = self.shared.take() else {
+            return;
+        };
+        let (status, wake) = &*shared;
+        status.lock().unwrap_or_else(|e| e.into_inner()).done = true;
+        wake.notify_one();
+        if let Some(ticker) = self.ticker.take() {
+            let _ = ticker.join();
+        }
+    }
+}
+
+/// Redraw the line every [`TICK`] until the run is done, then clear it.
+fn tick(shared: &(Mutex<Status>, Condvar)) {
+    let (status, wake) = shared;
+    let started = Instant::now();
+    let mut frame = 0;
+    let mut status = status.lock().unwrap_or_else(|e| e.into_inner());
+    loop {
+        status = wake
+            .wait_timeout(status, TICK)
+            .unwrap_or_else(|e| e.into_inner())
+            .0;
+        if status.done {
+            suspend(|| {});
+            return;
+        }
+        if status.stage == 0 || started.elapsed() < SHOW_AFTER {
+            continue;
+        }
+        let mut drawn = LINE.lock().unwrap_or_else(|e| e.into_inner());
+        let mut stderr = io::stderr().lock();
+        let _ = write!(stderr, "\r\x1b[2K{}", line(&status, frame));
+        let _ = stderr.flush();
+        *drawn = true;
+        frame += 1;
+    }
+}
+
+/// Text of the progress line, e.g. `⠙ [3/4] Clustering (k=16)  1.2s`.
+fn line(status: &Status, frame: usize) -> String {
+    format!(
+        "{} [{}/{STAGES}] {}  {:.1}s",
+        SPINNER[frame % SPINNER.len()],
+        status.stage,
+        status.name,
+        status.stage_started.elapsed().as_secs_f32()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn line_shows_stage_name_and_time() {
+        let status = Status {
+            stage: 3,
+            name: "Clustering (k=16)".to_string(),
+            stage_started: Instant::now(),
+            done: false,
+        };
+        let line = line(&status, 12);
+        assert!(line.starts_with("⠹ [3/4] Clustering (k=16)  "), "{line}");
+        assert!(line.ends_with('s'));
+    }
+
+    #[test]
+    fn disabled_progress_is_a_no_op() {
+        let progress = Progress::new(false);
+        progress.stage(1, "Loading image");
+        assert!(progress.shared.is_none());
+        progress.finish();
+    }
+}
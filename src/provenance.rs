@@ -0,0 +1,600 @@
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{bail, Context, Result};
+use clap::ValueEnum;
+
+use crate::backends::{get_backend, header_fields, stamp_header, Target, ThemeBackend};
+use crate::cli::ThemeMode;
+use crate::color::Color;
+use crate::config::SLOT_NAMES;
+use crate::pipeline::assign::AnsiPalette;
+use crate::pipeline::crop::{Crop, Region};
+use crate::pipeline::extract::{ResizeFilter, Sampling};
+use crate::pipeline::profile::BgFrom;
+use crate::pipeline::quantize::Algorithm;
+use crate::pipeline::style::PaletteStyle;
+use crate::pipeline::weighting::Weighting;
+
+/// How a theme was generated, recorded in its header so it can be traced back
+/// to the source image and regenerated.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Provenance {
+    /// nuri version that wrote the theme.
+    pub version: String,
+    /// Absolute path of the source image.
+    pub source: PathBuf,
+    /// Content hash of the source image (see [`hash_file`]).
+    pub source_hash: String,
+    /// Theme name.
+    pub name: String,
+    /// Output target.
+    pub target: Target,
+    /// Dark or light mode the palette was built for.
+    pub mode: ThemeMode,
+    /// Color extraction algorithm.
+    pub algorithm: Algorithm,
+    /// K-means and sampling seed.
+    pub seed: u64,
+    /// Number of K-means clusters.
+    pub colors: usize,
+    /// Minimum accent contrast ratio.
+    pub min_contrast: f32,
+    /// Pixel sampling strategy.
+    pub sampling: Sampling,
+    /// Pixels kept by `sampling`.
+    pub sample_size: usize,
+    /// Pixel weighting before clustering.
+    pub weighting: Weighting,
+    /// `--max-dim`, when set by flag or config.
+    pub max_dim: Option<u32>,
+    /// `--resize-filter`, when set by flag or config.
+    pub resize_filter: Option<ResizeFilter>,
+    /// Animation frames pooled, for animated sources.
+    pub frames: Option<usize>,
+    /// `--frame-stride`, for animated sources.
+    pub frame_stride: Option<usize>,
+    /// `--frame-interval` in seconds, for video sources.
+    pub frame_interval: Option<f64>,
+    /// `--crop` or `--focus`, when set by flag or config.
+    pub region: Option<Region>,
+    /// `--merge-delta-e`, when set by flag or config.
+    pub merge_delta_e: Option<f32>,
+    /// `--min-weight` in percent, when set by flag or config.
+    pub min_weight: Option<f32>,
+    /// Accent styles, in the order applied.
+    pub styles: Vec<PaletteStyle>,
+    /// Accents separated for colorblindness (`--colorblind-safe`).
+    pub colorblind_safe: bool,
+    /// Accent assignment profile: a built-in name or a profile file.
+    pub profile: Option<String>,
+    /// Minimum accent delta-E, when set by flag or config.
+    pub min_separation: Option<f32>,
+    /// Background source, when set by flag or config.
+    pub bg_from: Option<BgFrom>,
+    /// Background tint color for the custom source.
+    pub bg_color: Option<Color>,
+    /// Background lightness, when set by flag or config.
+    pub bg_lightness: Option<f32>,
+    /// Foreground lightness, when set by flag or config.
+    pub fg_lightness: Option<f32>,
+    /// Contrast envelope, when set by flag or config.
+    pub contrast_envelope: Option<f32>,
+    /// Colors 16-255 written (`--extended`).
+    pub extended: bool,
+    /// Each slot's nearest named color noted in the header (`--verbose`).
+    pub named_colors: bool,
+    /// Slots kept from the theme being replaced (`--lock`).
+    pub locks: Vec<String>,
+    /// Template file, for the template target.
+    pub template: Option<PathBuf>,
+    /// `--set` pins applied after generation.
+    pub pins: Vec<(String, Color)>,
+    /// Generation time, RFC 3339 in UTC.
+    pub generated_at: String,
+}
+
+impl Provenance {
+    /// The header as `key: value` lines, in a fixed order.
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = vec![
+            format!("version: {}", self.version),
+            format!("source: {}", self.source.display()),
+            format!("source-hash: {}", self.source_hash),
+            format!("name: {}", self.name),
+            format!("target: {}", value_name(&self.target)),
+            format!("mode: {}", value_name(&self.mode)),
+            format!("algorithm: {}", value_name(&self.algorithm)),
+            format!("seed: {}", self.seed),
+            format!("colors: {}", self.colors),
+            format!("min-contrast: {}", self.min_contrast),
+            format!("sampling: {}", value_name(&self.sampling)),
+            format!("sample-size: {}", self.sample_size),
+            format!("weighting: {}", value_name(&self.weighting)),
+        ];
+        if let Some(max_dim) = self.max_dim {
+            lines.push(format!("max-dim: {max_dim}"));
+        }
+        if let Some(filter) = &self.resize_filter {
+            lines.push(format!("resize-filter: {}", value_name(filter)));
+        }
+        if let Some(frames) = self.frames {
+            lines.push(format!("frames: {frames}"));
+        }
+        if let Some(stride) = self.frame_stride {
+            lines.push(format!("frame-stride: {stride}"));
+        }
+        if let Some(interval) = self.frame_interval {
+            lines.push(format!("frame-interval: {interval}"));
+        }
+        match self.region {
+            Some(Region::Crop(crop)) => lines.push(format!("crop: {crop}")),
+            Some(Region::Focus(focus)) => lines.push(format!("focus: {}", value_name(&focus))),
+            None => {}
+        }
+        if let Some(merge_delta_e) = self.merge_delta_e {
+            lines.push(format!("merge-delta-e: {merge_delta_e}"));
+        }
+        if let Some(min_weight) = self.min_weight {
+            lines.push(format!("min-weight: {min_weight}"));
+        }
+        if !self.styles.is_empty() {
+            let styles: Vec<String> = self.styles.iter().map(value_name).collect();
+            lines.push(format!("style: {}", styles.join(",")));
+        }
+        if self.colorblind_safe {
+            lines.push("colorblind-safe: true".to_string());
+        }
+        if let Some(profile) = &self.profile {
+            lines.push(format!("profile: {profile}"));
+        }
+        if let Some(min_separation) = self.min_separation {
+            lines.push(format!("min-separation: {min_separation}"));
+        }
+        if let Some(bg_from) = &self.bg_from {
+            lines.push(format!("bg-from: {}", value_name(bg_from)));
+        }
+        if let Some(bg_color) = self.bg_color {
+            lines.push(format!("bg-color: {}", bg_color.to_hex()));
+        }
+        if let Some(bg_lightness) = self.bg_lightness {
+            lines.push(format!("bg-lightness: {bg_lightness}"));
+        }
+        if let Some(fg_lightness) = self.fg_lightness {
+            lines.push(format!("fg-lightness: {fg_lightness}"));
+        }
+        if let Some(envelope) = self.contrast_envelope {
+            lines.push(format!("contrast-envelope: {envelope}"));
+        }
+        if self.extended {
+            lines.push("extended: true".to_string());
+        }
+        if !self.locks.is_empty() {
+            lines.push(format!("lock: {}", self.locks.join(",")));
+        }
+        if let Some(template) = &self.template {
+            lines.push(format!("template: {}", template.display()));
+        }
+        if !self.pins.is_empty() {
+            let pins: Vec<String> = self
+                .pins
+                .iter()
+                .map(|(slot, color)| format!("{slot}={}", color.to_hex()))
+                .collect();
+            lines.push(format!("set: {}", pins.join(" ")));
+        }
+        lines.push(format!("generated-at: {}", self.generated_at));
+        lines
+    }
+
+    /// `nearest-<slot>: <name> (delta-E <d>)` lines for `palette`, when
+    /// [`named_colors`](Self::named_colors) is set.
+    pub fn named_lines(&self, palette: &AnsiPalette) -> Vec<String> {
+        if !self.named_colors {
+            return Vec::new();
+        }
+        SLOT_NAMES
+            .iter()
+            .zip(palette.slots)
+            .map(|(slot, color)| {
+                let (name, delta_e) = color.nearest_named();
+                format!("nearest-{slot}: {name} (delta-E {delta_e:.1})")
+            })
+            .collect()
+    }
+
+    /// Insert the header into serialized theme text, right after the
+    /// `Generated by nuri` marker line and in the same comment style. JSON
+    /// formats get the lines appended to their `$comment` string.
+    pub fn stamp(&self, theme: &str, palette: &AnsiPalette) -> String {
+        let mut lines = self.lines();
+        lines.extend(self.named_lines(palette));
+        stamp_header(theme, &lines)
+    }
+
+    /// Read the header back from theme text written by [`stamp`](Self::stamp).
+    pub fn parse(theme: &str) -> Result<Provenance> {
+        let fields = header_fields(theme);
+        if !fields.contains_key("source") {
+            bail!("no nuri provenance header found");
+        }
+
+        let get = |key: &str| -> Result<&str> {
+            fields
+                .get(key)
+                .map(String::as_str)
+                .with_context(|| format!("provenance header is missing '{key}'"))
+        };
+        let pins = match fields.get("set") {
+            Some(value) => value
+                .split_whitespace()
+                .map(|pin| {
+                    let (slot, hex) = pin
+                        .split_once('=')
+                        .with_context(|| format!("invalid pin '{pin}'"))?;
+                    Ok((slot.to_string(), Color::from_hex(hex)?))
+                })
+                .collect::<Result<Vec<_>>>()?,
+            None => Vec::new(),
+        };
+        Ok(Provenance {
+            version: get("version")?.to_string(),
+            source: PathBuf::from(get("source")?),
+            source_hash: get("source-hash")?.to_string(),
+            name: get("name")?.to_string(),
+            target: parse_value(get("target")?)?,
+            mode: parse_value(get("mode")?)?,
+            // Headers from before --algorithm existed were always K-means
+            algorithm: match fields.get("algorithm") {
+                Some(value) => parse_value(value)?,
+                None => Algorithm::Kmeans,
+            },
+            seed: get("seed")?.parse().context("invalid seed")?,
+            colors: get("colors")?.parse().context("invalid colors")?,
+            min_contrast: get("min-contrast")?
+                .parse()
+                .context("invalid min-contrast")?,
+            sampling: parse_value(get("sampling")?)?,
+            sample_size: get("sample-size")?.parse().context("invalid sample-size")?,
+            // Headers from before --weighting existed were always area-weighted
+            weighting: match fields.get("weighting") {
+                Some(value) => parse_value(value)?,
+                None => Weighting::Area,
+            },
+            max_dim: fields
+                .get("max-dim")
+                .map(|v| v.parse().context("invalid max-dim"))
+                .transpose()?,
+            resize_filter: fields
+                .get("resize-filter")
+                .map(|v| parse_value(v))
+                .transpose()?,
+            frames: fields
+                .get("frames")
+                .map(|v| v.parse().context("invalid frames"))
+                .transpose()?,
+            frame_stride: fields
+                .get("frame-stride")
+                .map(|v| v.parse().context("invalid frame-stride"))
+                .transpose()?,
+            frame_interval: fields
+                .get("frame-interval")
+                .map(|v| v.parse().context("invalid frame-interval"))
+                .transpose()?,
+            region: match (fields.get("crop"), fields.get("focus")) {
+                (Some(crop), _) => Some(Region::Crop(Crop::parse(crop)?)),
+                (None, Some(focus)) => Some(Region::Focus(parse_value(focus)?)),
+                (None, None) => None,
+            },
+            merge_delta_e: fields
+                .get("merge-delta-e")
+                .map(|v| v.parse().context("invalid merge-delta-e"))
+                .transpose()?,
+            min_weight: fields
+                .get("min-weight")
+                .map(|v| v.parse().context("invalid min-weight"))
+                .transpose()?,
+            styles: match fields.get("style") {
+                Some(value) => value
+                    .split(',')
+                    .map(parse_value)
+                    .collect::<Result<Vec<_>>>()?,
+                None => Vec::new(),
+            },
+            colorblind_safe: fields.get("colorblind-safe").is_some_and(|v| v == "true"),
+            profile: fields.get("profile").map(|v| v.to_string()),
+            min_separation: fields
+                .get("min-separation")
+                .map(|v| v.parse().context("invalid min-separation"))
+                .transpose()?,
+            bg_from: fields.get("bg-from").map(|v| parse_value(v)).transpose()?,
+            bg_color: fields
+                .get("bg-color")
+                .map(|v| Color::from_hex(v))
+                .transpose()?,
+            bg_lightness: fields
+                .get("bg-lightness")
+                .map(|v| v.parse().context("invalid bg-lightness"))
+                .transpose()?,
+            fg_lightness: fields
+                .get("fg-lightness")
+                .map(|v| v.parse().context("invalid fg-lightness"))
+                .transpose()?,
+            contrast_envelope: fields
+                .get("contrast-envelope")
+                .map(|v| v.parse().context("invalid contrast-envelope"))
+                .transpose()?,
+            extended: fields.get("extended").is_some_and(|v| v == "true"),
+            named_colors: fields.keys().any(|key| key.starts_with("nearest-")),
+            locks: fields
+                .get("lock")
+                .map(|v| v.split(',').map(str::to_string).collect())
+                .unwrap_or_default(),
+            template: fields.get("template").map(PathBuf::from),
+            pins,
+            generated_at: get("generated-at")?.to_string(),
+        })
+    }
+}
+
+/// A backend whose output carries a provenance header.
+pub struct Stamped {
+    backend: Box<dyn ThemeBackend>,
+    provenance: Provenance,
+}
+
+impl Stamped {
+    /// Wrap the backend for `provenance.target`.
+    pub fn new(provenance: Provenance) -> Result<Self> {
+        Ok(Self::wrap(get_backend(provenance.target)?, provenance))
+    }
+
+    /// Wrap an already-built backend, such as a loaded template.
+    pub fn wrap(backend: Box<dyn ThemeBackend>, provenance: Provenance) -> Self {
+        Self {
+            backend,
+            provenance,
+        }
+    }
+}
+
+impl ThemeBackend for Stamped {
+    fn name(&self) -> &str {
+        self.backend.name()
+    }
+
+    fn serialize(&self, palette: &AnsiPalette, theme_name: &str) -> String {
+        self.provenance
+            .stamp(&self.backend.serialize(palette, theme_name), palette)
+    }
+
+    fn install_path(&self, theme_name: &str) -> crate::error::Result<PathBuf> {
+        self.backend.install_path(theme_name)
+    }
+
+    fn fixed_path(&self) -> bool {
+        self.backend.fixed_path()
+    }
+
+    fn companions(&self, palette: &AnsiPalette, theme_name: &str) -> Vec<(String, String)> {
+        self.backend
+            .companions(palette, theme_name)
+            .into_iter()
+            .map(|(file_name, content)| (file_name, self.provenance.stamp(&content, palette)))
+            .collect()
+    }
+
+    fn companion_names(&self) -> Vec<&str> {
+        self.backend.companion_names()
+    }
+
+    fn extension(&self) -> &str {
+        self.backend.extension()
+    }
+}
+
+/// Hash a file's contents (64-bit FNV-1a), streaming so large images are not
+/// read into memory at once.
+pub fn hash_file(path: &Path) -> Result<String> {
+    let mut file =
+        std::fs::File::open(path).with_context(|| format!("failed to open {}", path.display()))?;
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    let mut buf = [0u8; 64 * 1024];
+    loop {
+        let n = file
+            .read(&mut buf)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        if n == 0 {
+            break;
+        }
+        for &byte in &buf[..n] {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+    Ok(format!("fnv1a64:{hash:016x}"))
+}
+
+/// Current time as RFC 3339 in UTC, e.g. `2024-05-01T12:00:00Z`.
+pub fn timestamp_now() -> String {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    format_timestamp(secs)
+}
+
+/// Format seconds since the Unix epoch as RFC 3339 in UTC.
+fn format_timestamp(secs: u64) -> String {
+    let days = (secs / 86_400) as i64;
+    let rem = secs % 86_400;
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        rem / 3600,
+        rem % 3600 / 60,
+        rem % 60
+    )
+}
+
+/// The CLI spelling of a value enum variant.
+pub(crate) fn value_name<T: ValueEnum>(value: &T) -> String {
+    value
+        .to_possible_value()
+        .map(|v| v.get_name().to_string())
+        .unwrap_or_default()
+}
+
+/// Parse a value enum from its CLI spelling.
+fn parse_value<T: ValueEnum>(s: &str) -> Result<T> {
+    match T::from_str(s, false) {
+        Ok(value) => Ok(value),
+        Err(e) => bail!("{e}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Provenance {
+        Provenance {
+            version: "0.2.0".to_string(),
+            source: PathBuf::from("/home/me/wallpapers/sunset: dusk.png"),
+            source_hash: "fnv1a64:0123456789abcdef".to_string(),
+            name: "sunset".to_string(),
+            target: Target::Ghostty,
+            mode: ThemeMode::Light,
+            algorithm: Algorithm::Octree,
+            seed: 43,
+            colors: 12,
+            min_contrast: 7.0,
+            sampling: Sampling::Stride,
+            sample_size: 4096,
+            weighting: Weighting::Chroma,
+            max_dim: Some(512),
+            resize_filter: Some(ResizeFilter::Triangle),
+            frames: Some(4),
+            frame_stride: Some(10),
+            frame_interval: Some(2.5),
+            region: Some(Region::Crop(Crop::parse("0,200,1920,880").unwrap())),
+            merge_delta_e: Some(8.0),
+            min_weight: Some(0.5),
+            styles: vec![PaletteStyle::Pastel, PaletteStyle::Monochrome],
+            colorblind_safe: true,
+            profile: Some("nord".to_string()),
+            min_separation: Some(6.5),
+            bg_from: Some(BgFrom::Custom),
+            bg_color: Some(Color::new(16, 32, 48)),
+            bg_lightness: Some(0.2),
+            fg_lightness: Some(0.85),
+            contrast_envelope: Some(0.8),
+            extended: true,
+            named_colors: true,
+            locks: vec!["red".to_string(), "background".to_string()],
+            template: Some(PathBuf::from("/home/me/templates/kitty.conf.tmpl")),
+            pins: vec![("red".to_string(), Color::new(255, 0, 0))],
+            generated_at: "2024-05-01T12:00:00Z".to_string(),
+        }
+    }
+
+    fn palette() -> AnsiPalette {
+        AnsiPalette {
+            slots: [Color::new(10, 20, 30); 16],
+            background: Color::new(0, 0, 0),
+            foreground: Color::new(255, 255, 255),
+            cursor_color: Color::new(255, 255, 255),
+            cursor_text: Color::new(0, 0, 0),
+            selection_bg: Color::new(50, 50, 50),
+            selection_fg: Color::new(255, 255, 255),
+        }
+    }
+
+    #[test]
+    fn notes_nearest_named_colors_when_asked() {
+        let output = Stamped::new(sample())
+            .unwrap()
+            .serialize(&palette(), "sunset");
+        let (name, _) = palette().slots[1].nearest_named();
+        assert!(output.contains(&format!("# nearest-red: {name} (delta-E ")));
+        assert_eq!(output.matches("# nearest-").count(), 16);
+
+        let plain = Provenance {
+            named_colors: false,
+            ..sample()
+        };
+        assert!(plain.named_lines(&palette()).is_empty());
+    }
+
+    #[test]
+    fn round_trips_through_every_backend() {
+        for &target in Target::all() {
+            let provenance = Provenance { target, ..sample() };
+            let output = Stamped::new(provenance.clone())
+                .unwrap()
+                .serialize(&palette(), "sunset");
+            assert_eq!(
+                Provenance::parse(&output).unwrap(),
+                provenance,
+                "{target:?}:\n{output}"
+            );
+        }
+    }
+
+    #[test]
+    fn stamped_themes_still_import() {
+        for target in [Target::Ghostty, Target::Alacritty, Target::Json] {
+            let provenance = Provenance { target, ..sample() };
+            let output = Stamped::new(provenance)
+                .unwrap()
+                .serialize(&palette(), "sunset");
+            let format = match target {
+                Target::Ghostty => crate::import::ThemeFormat::Ghostty,
+                Target::Alacritty => crate::import::ThemeFormat::Alacritty,
+                _ => crate::import::ThemeFormat::Json,
+            };
+            assert_eq!(
+                crate::import::parse_palette(&output, format).unwrap(),
+                palette()
+            );
+        }
+    }
+
+    #[test]
+    fn unstamped_theme_has_no_provenance() {
+        let output = get_backend(Target::Ghostty)
+            .unwrap()
+            .serialize(&palette(), "x");
+        assert!(Provenance::parse(&output).is_err());
+    }
+
+    #[test]
+    fn timestamps_are_rfc3339() {
+        assert_eq!(format_timestamp(0), "1970-01-01T00:00:00Z");
+        assert_eq!(format_timestamp(951_782_400), "2000-02-29T00:00:00Z");
+        assert_eq!(format_timestamp(1_714_564_800), "2024-05-01T12:00:00Z");
+    }
+
+    #[test]
+    fn hash_depends_on_contents() {
+        let dir = std::env::temp_dir().join("nuri-test-provenance-hash");
+        std::fs::create_dir_all(&dir).unwrap();
+        let a = dir.join("a");
+        let b = dir.join("b");
+        std::fs::write(&a, "one").unwrap();
+        std::fs::write(&b, "two").unwrap();
+        assert_eq!(hash_file(&a).unwrap(), hash_file(&a).unwrap());
+        assert_ne!(hash_file(&a).unwrap(), hash_file(&b).unwrap());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,342 @@
+//! Machine-readable generation report (`--report json`): what was extracted,
+//! how each slot got its color, contrast results and the files written, for
+//! scripts and GUIs built on nuri.
+
+use std::path::PathBuf;
+
+use serde::Serialize;
+
+use crate::backends::Target;
+use crate::check;
+use crate::cli::ThemeMode;
+use crate::color::Color;
+use crate::config::SLOT_NAMES;
+use crate::pipeline::assign::{accent_choices, AccentChoice, AnsiPalette};
+use crate::pipeline::extract::{ExtractedColor, Sampling};
+use crate::pipeline::profile::Profile;
+use crate::pipeline::quantize::Algorithm;
+use crate::pipeline::weighting::Weighting;
+use crate::provenance::value_name;
+
+/// Report formats for `--report`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ReportFormat {
+    /// Pretty-printed JSON on stdout.
+    Json,
+}
+
+/// One generation run: the shared extraction and one entry per theme
+/// (two with `--both`).
+#[derive(Debug, Clone, Serialize)]
+pub struct Report {
+    pub version: String,
+    /// Source image path or URL.
+    pub source: PathBuf,
+    pub extraction: Extraction,
+    pub themes: Vec<ThemeReport>,
+}
+
+/// Clustering settings and the colors it found.
+#[derive(Debug, Clone, Serialize)]
+pub struct Extraction {
+    pub algorithm: Algorithm,
+    pub seed: u64,
+    /// Clusters asked for.
+    pub k: usize,
+    pub sampling: Sampling,
+    pub weighting: Weighting,
+    /// Animation frames pooled; 1 for still images.
+    pub frames: usize,
+    /// Pixels in the prepared image, over all frames.
+    pub pixels: usize,
+    /// Pixels clustered after sampling.
+    pub sampled: usize,
+    /// Extracted colors with their share of the image, heaviest first.
+    pub clusters: Vec<ExtractedColor>,
+}
+
+/// Whether the theme's mode came from the image or was set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ModeSource {
+    Detected,
+    /// By `--mode`, the config, or `--both`.
+    Set,
+}
+
+/// One written theme and the decisions behind it.
+#[derive(Debug, Clone, Serialize)]
+pub struct ThemeReport {
+    pub name: String,
+    pub mode: String,
+    pub mode_source: ModeSource,
+    pub slots: Vec<SlotReport>,
+    pub palette: AnsiPalette,
+    pub contrast: Vec<ContrastResult>,
+    /// Accent pairs closer than [`check::SIMILAR_DELTA_E`].
+    pub similar: Vec<SimilarResult>,
+    pub outputs: Vec<Written>,
+}
+
+/// How one ANSI slot got its final color.
+#[derive(Debug, Clone, Serialize)]
+pub struct SlotReport {
+    pub index: usize,
+    pub slot: &'static str,
+    pub color: Color,
+    #[serde(flatten)]
+    pub source: SlotSource,
+    /// Color straight out of assignment (and `--style`), before the steps in
+    /// `adjustments`.
+    pub assigned: Color,
+    pub adjustments: Vec<Adjustment>,
+}
+
+/// Where a slot's assigned color came from.
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum SlotSource {
+    /// Red through cyan, from the extracted colors near the target hue.
+    Accent {
+        #[serde(flatten)]
+        choice: AccentChoice,
+        target_hue: f32,
+        tolerance: f32,
+    },
+    /// Black, white, bright black and bright white, from the darkest and
+    /// lightest colors.
+    Base { source: &'static str },
+    /// Bright accents, lightened from their normal accent.
+    Bright {
+        source: &'static str,
+        of: &'static str,
+    },
+}
+
+/// A pipeline step that changed a slot.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Adjustment {
+    /// `contrast`, `colorblind` or `override` (config `[slots]`, `--set`,
+    /// `--lock`).
+    pub step: &'static str,
+    pub from: Color,
+    pub to: Color,
+    /// Contrast against the background before and after.
+    pub contrast: [f32; 2],
+}
+
+/// The palette as it stood after each step that can move a slot.
+#[derive(Debug, Clone)]
+pub struct Stages {
+    /// Assigned slots with styles applied.
+    pub assigned: AnsiPalette,
+    /// After contrast enforcement.
+    pub contrast: AnsiPalette,
+    /// After `--colorblind-safe` separation, or the same as `contrast`.
+    pub colorblind: AnsiPalette,
+}
+
+/// One contrast check from `nuri check`.
+#[derive(Debug, Clone, Serialize)]
+pub struct ContrastResult {
+    pub label: String,
+    pub fg: Color,
+    pub bg: Color,
+    pub ratio: f32,
+    pub required: Option<f32>,
+    pub passes: bool,
+}
+
+/// Two accents that are hard to tell apart.
+#[derive(Debug, Clone, Serialize)]
+pub struct SimilarResult {
+    pub a: &'static str,
+    pub b: &'static str,
+    pub delta_e: f32,
+}
+
+/// A file written by the run.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum Written {
+    Theme { target: String, path: PathBuf },
+    Card { path: PathBuf },
+    Html { path: PathBuf },
+}
+
+impl Written {
+    pub fn theme(target: Target, path: PathBuf) -> Written {
+        Written::Theme {
+            target: target.key(),
+            path,
+        }
+    }
+}
+
+impl ThemeReport {
+    /// Describe `palette`, built from `colors` with `profile` by way of
+    /// `stages`. `outputs` are added as the theme is written.
+    pub fn new(
+        name: &str,
+        mode: ThemeMode,
+        mode_source: ModeSource,
+        colors: &[ExtractedColor],
+        profile: &Profile,
+        stages: &Stages,
+        palette: &AnsiPalette,
+    ) -> ThemeReport {
+        let choices = accent_choices(colors, profile);
+        let slots = (0..16)
+            .map(|i| {
+                let source = match i {
+                    1..=6 => SlotSource::Accent {
+                        choice: choices[i - 1],
+                        target_hue: profile.accents[i - 1].hue,
+                        tolerance: profile.accents[i - 1].tolerance,
+                    },
+                    9..=14 => SlotSource::Bright {
+                        source: "bright",
+                        of: SLOT_NAMES[i - 8],
+                    },
+                    _ => SlotSource::Base { source: "base" },
+                };
+                SlotReport {
+                    index: i,
+                    slot: SLOT_NAMES[i],
+                    color: palette.slots[i],
+                    source,
+                    assigned: stages.assigned.slots[i],
+                    adjustments: adjustments(i, stages, palette),
+                }
+            })
+            .collect();
+
+        let check = check::Report::new(palette);
+        let contrast = check
+            .contrast
+            .iter()
+            .map(|row| ContrastResult {
+                label: row.label.clone(),
+                fg: row.fg,
+                bg: row.bg,
+                ratio: row.ratio,
+                required: row.required,
+                passes: row.passes(),
+            })
+            .collect();
+        let similar = check
+            .similar
+            .iter()
+            .map(|pair| SimilarResult {
+                a: SLOT_NAMES[pair.a],
+                b: SLOT_NAMES[pair.b],
+                delta_e: pair.delta_e,
+            })
+            .collect();
+
+        ThemeReport {
+            name: name.to_string(),
+            mode: value_name(&mode),
+            mode_source,
+            slots,
+            palette: palette.clone(),
+            contrast,
+            similar,
+            outputs: Vec::new(),
+        }
+    }
+}
+
+/// The steps between `stages.assigned` and `palette` that changed slot `i`.
+fn adjustments(i: usize, stages: &Stages, palette: &AnsiPalette) -> Vec<Adjustment> {
+    [
+        ("contrast", &stages.assigned, &stages.contrast),
+        ("colorblind", &stages.contrast, &stages.colorblind),
+        ("override", &stages.colorblind, palette),
+    ]
+    .into_iter()
+    .filter(|(_, before, after)| before.slots[i] != after.slots[i])
+    .map(|(step, before, after)| Adjustment {
+        step,
+        from: before.slots[i],
+        to: after.slots[i],
+        contrast: [
+            Color::contrast_ratio(&before.slots[i], &before.background),
+            Color::contrast_ratio(&after.slots[i], &after.background),
+        ],
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::assign::assign_slots;
+    use crate::pipeline::contrast::enforce_contrast;
+    use palette::Oklch;
+
+    fn colors() -> Vec<ExtractedColor> {
+        [(0.3, 0.15, 25.0), (0.6, 0.2, 145.0), (0.1, 0.01, 0.0)]
+            .iter()
+            .map(|&(l, c, h)| ExtractedColor {
+                color: Color::from_oklch(Oklch::new(l, c, h)),
+                weight: 1.0 / 3.0,
+            })
+            .collect()
+    }
+
+    fn report() -> ThemeReport {
+        let colors = colors();
+        let assigned = assign_slots(&colors, ThemeMode::Dark);
+        let mut contrast = assigned.clone();
+        enforce_contrast(&mut contrast, 4.5);
+        let stages = Stages {
+            assigned,
+            contrast: contrast.clone(),
+            colorblind: contrast.clone(),
+        };
+        let mut palette = contrast;
+        palette.slots[3] = Color::new(0xff, 0xff, 0x00);
+        ThemeReport::new(
+            "test",
+            ThemeMode::Dark,
+            ModeSource::Detected,
+            &colors,
+            &Profile::default(),
+            &stages,
+            &palette,
+        )
+    }
+
+    #[test]
+    fn slots_record_source_and_adjustments() {
+        let report = report();
+        let red = &report.slots[1];
+        assert!(matches!(
+            red.source,
+            SlotSource::Accent {
+                choice: AccentChoice::Matched { .. },
+                ..
+            }
+        ));
+        // The dark red needed lightening to reach 4.5:1
+        assert_eq!(red.adjustments[0].step, "contrast");
+        assert!(red.adjustments[0].contrast[1] >= 4.5);
+        assert_eq!(report.slots[3].adjustments.last().unwrap().step, "override");
+        assert!(report.slots[0].adjustments.is_empty());
+    }
+
+    #[test]
+    fn serializes_flat_slot_entries() {
+        let json = serde_json::to_value(report()).unwrap();
+        let red = &json["slots"][1];
+        assert_eq!(red["slot"], "red");
+        assert_eq!(red["source"], "matched");
+        assert_eq!(red["target_hue"], 25.0);
+        assert_eq!(json["slots"][9]["source"], "bright");
+        assert_eq!(json["slots"][9]["of"], "red");
+        assert_eq!(json["slots"][0]["source"], "base");
+        assert_eq!(json["mode_source"], "detected");
+        assert_eq!(json["contrast"][0]["label"], "foreground");
+    }
+}
@@ -0,0 +1,87 @@
+//! `nuri completions <SHELL>` and `nuri manpage`: completion scripts and man
+//! pages generated from the clap definitions in [`crate::cli`], so they never
+//! fall behind the flags.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use clap::CommandFactory;
+use clap_complete::Shell;
+use clap_mangen::Man;
+
+use crate::cli::Cli;
+
+/// Write the completion script for `shell` to `out`.
+pub fn write_completions(shell: Shell, out: &mut dyn Write) -> Result<()> {
+    // clap_complete panics on write errors, such as a closed pipe
+    let mut script = Vec::new();
+    clap_complete::generate(shell, &mut Cli::command(), "nuri", &mut script);
+    out.write_all(&script)
+        .context("cannot write the completion script")
+}
+
+/// Write the `nuri(1)` man page to `out`.
+pub fn write_manpage(out: &mut dyn Write) -> Result<()> {
+    Man::new(Cli::command())
+        .render(out)
+        .context("cannot write the man page")
+}
+
+/// Write `nuri.1` and a page per subcommand (`nuri-generate.1`, ...) into
+/// `dir`, creating it if needed; the paths written.
+pub fn write_manpages(dir: &Path) -> Result<Vec<PathBuf>> {
+    std::fs::create_dir_all(dir).with_context(|| format!("cannot create {}", dir.display()))?;
+    let mut command = Cli::command().disable_help_subcommand(true);
+    command.build();
+    let mut written = Vec::new();
+    write_pages(command, dir, &mut written)?;
+    Ok(written)
+}
+
+fn write_pages(command: clap::Command, dir: &Path, written: &mut Vec<PathBuf>) -> Result<()> {
+    for sub in command.get_subcommands().filter(|s| !s.is_hide_set()) {
+        write_pages(sub.clone(), dir, written)?;
+    }
+    let path = Man::new(command)
+        .generate_to(dir)
+        .with_context(|| format!("cannot write a man page to {}", dir.display()))?;
+    written.push(path);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn completions_cover_subcommands_and_flags() {
+        let mut out = Vec::new();
+        write_completions(Shell::Bash, &mut out).unwrap();
+        let script = String::from_utf8(out).unwrap();
+        for word in ["nuri", "batch", "--name-template", "--config-dir"] {
+            assert!(script.contains(word), "{word} missing");
+        }
+        // Hidden commands stay hidden
+        assert!(!script.contains("nuri__bench"));
+    }
+
+    #[test]
+    fn man_pages_per_subcommand() {
+        let mut out = Vec::new();
+        write_manpage(&mut out).unwrap();
+        let page = String::from_utf8(out).unwrap();
+        assert!(page.starts_with(".ie"), "{}", &page[..40]);
+        assert!(page.contains(".TH nuri 1"));
+
+        let dir = std::env::temp_dir().join("nuri_test_manpages");
+        let _ = std::fs::remove_dir_all(&dir);
+        let written = write_manpages(&dir).unwrap();
+        assert!(dir.join("nuri.1").is_file());
+        assert!(dir.join("nuri-generate.1").is_file());
+        assert!(dir.join("nuri-cache-clear.1").is_file());
+        assert!(!dir.join("nuri-bench.1").exists());
+        assert_eq!(written.last(), Some(&dir.join("nuri.1")));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,278 @@
+//! Whether to color what nuri prints: `--color`, then `NO_COLOR`, then
+//! whether the stream is a terminal, so piping into a script or a log file
+//! gets plain text. Colors go out as 24-bit escapes where the terminal
+//! supports them and as the nearest of 256 or 16 colors elsewhere.
+
+use std::ffi::OsStr;
+use std::io::{self, IsTerminal};
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::OnceLock;
+
+use crate::color::Color;
+
+/// When to color output (`--color`).
+#[derive(clap::ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ColorChoice {
+    /// On a terminal, unless NO_COLOR is set
+    #[default]
+    Auto,
+    /// Always, even into a pipe or file
+    Always,
+    /// Never
+    Never,
+}
+
+/// The `--color` choice for this process; [`ColorChoice::Auto`] until set.
+static CHOICE: AtomicU8 = AtomicU8::new(ColorChoice::Auto as u8);
+
+/// Use `choice` for everything printed from now on.
+pub fn set_color_choice(choice: ColorChoice) {
+    CHOICE.store(choice as u8, Ordering::Relaxed);
+}
+
+/// The choice made with [`set_color_choice`].
+pub fn color_choice() -> ColorChoice {
+    match CHOICE.load(Ordering::Relaxed) {
+        1 => ColorChoice::Always,
+        2 => ColorChoice::Never,
+        _ => ColorChoice::Auto,
+    }
+}
+
+/// Whether to color what goes to stdout.
+pub fn stdout_colored() -> bool {
+    colored(
+        color_choice(),
+        std::env::var_os("NO_COLOR").as_deref(),
+        io::stdout().is_terminal(),
+    )
+}
+
+/// Whether to color what goes to stderr.
+pub fn stderr_colored() -> bool {
+    colored(
+        color_choice(),
+        std::env::var_os("NO_COLOR").as_deref(),
+        io::stderr().is_terminal(),
+    )
+}
+
+/// An explicit `--color` wins; otherwise a non-empty `NO_COLOR` turns color
+/// off (<https://no-color.org>) and a terminal turns it on.
+fn colored(choice: ColorChoice, no_color: Option<&OsStr>, terminal: bool) -> bool {
+    match choice {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => no_color.is_none_or(OsStr::is_empty) && terminal,
+    }
+}
+
+/// Colors a terminal can show.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorDepth {
+    /// 24-bit RGB escapes.
+    TrueColor,
+    /// The xterm 256-color palette.
+    Ansi256,
+    /// The 16 basic colors, as set by the terminal's own theme.
+    Ansi16,
+}
+
+/// Terminals known to take 24-bit escapes without advertising them in
+/// `COLORTERM`, by `TERM`.
+const TRUECOLOR_TERMS: [&str; 7] = [
+    "xterm-ghostty",
+    "xterm-kitty",
+    "alacritty",
+    "wezterm",
+    "foot",
+    "contour",
+    "iterm2",
+];
+
+/// Same, by `TERM_PROGRAM`.
+const TRUECOLOR_PROGRAMS: [&str; 4] = ["ghostty", "iTerm.app", "WezTerm", "vscode"];
+
+/// What this terminal can show, from `COLORTERM`, `TERM` and the terminal
+/// program; read once per process.
+pub fn color_depth() -> ColorDepth {
+    static DEPTH: OnceLock<ColorDepth> = OnceLock::new();
+    *DEPTH.get_or_init(|| {
+        let var = |name| std::env::var(name).ok();
+        let depth = depth_from(
+            var("COLORTERM").as_deref(),
+            var("TERM").as_deref(),
+            var("TERM_PROGRAM").as_deref(),
+            std::env::var_os("WT_SESSION").is_some(),
+        );
+        log::debug!("terminal colors: {depth:?}");
+        depth
+    })
+}
+
+/// `COLORTERM=truecolor` (or `24bit`) is the standard signal, and what mosh
+/// and tmux drop unless told to pass it on; otherwise the terminfo name in
+/// `TERM` says it: `*-direct` is 24-bit, `*-256color` 256 colors, anything
+/// else 16. Windows Terminal sets neither but has `WT_SESSION`.
+fn depth_from(
+    colorterm: Option<&str>,
+    term: Option<&str>,
+    program: Option<&str>,
+    windows_terminal: bool,
+) -> ColorDepth {
+    let term = term.unwrap_or_default();
+    if matches!(colorterm, Some("truecolor" | "24bit"))
+        || term.ends_with("-direct")
+        || TRUECOLOR_TERMS.contains(&term)
+        || program.is_some_and(|p| TRUECOLOR_PROGRAMS.contains(&p))
+        || windows_terminal
+    {
+        ColorDepth::TrueColor
+    } else if term.contains("256color") {
+        ColorDepth::Ansi256
+    } else {
+        ColorDepth::Ansi16
+    }
+}
+
+/// Channel values of the 6x6x6 cube in the xterm 256-color palette.
+const CUBE: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// The 16 basic colors as xterm draws them by default.
+const ANSI16: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+impl ColorDepth {
+    /// SGR parameters drawing `color` as the foreground with `base` 30, or
+    /// the background with 40, at this depth.
+    pub fn sgr(self, color: Color, base: u8) -> String {
+        match self {
+            ColorDepth::TrueColor => {
+                format!("{};2;{};{};{}", base + 8, color.r, color.g, color.b)
+            }
+            ColorDepth::Ansi256 => format!("{};5;{}", base + 8, ansi256(color)),
+            ColorDepth::Ansi16 => match ansi16(color) {
+                i @ 0..8 => (base + i).to_string(),
+                i => (base + 60 + i - 8).to_string(),
+            },
+        }
+    }
+}
+
+/// Index of the nearest color in the xterm 256-color palette: the closer
+/// of the nearest cube color and the nearest gray (232-255), by delta-E.
+/// The first 16 are left out; the terminal's theme decides those.
+pub fn ansi256(color: Color) -> u8 {
+    let level = |v: u8| {
+        (0..CUBE.len())
+            .min_by_key(|&i| CUBE[i].abs_diff(v))
+            .unwrap_or(0)
+    };
+    let (r, g, b) = (level(color.r), level(color.g), level(color.b));
+    let cube = Color::new(CUBE[r], CUBE[g], CUBE[b]);
+    let average = (u16::from(color.r) + u16::from(color.g) + u16::from(color.b)) / 3;
+    let step = ((average.saturating_sub(8) + 5) / 10).min(23) as u8;
+    let gray = Color::new(8 + step * 10, 8 + step * 10, 8 + step * 10);
+    if color.delta_e(gray) < color.delta_e(cube) {
+        232 + step
+    } else {
+        16 + 36 * r as u8 + 6 * g as u8 + b as u8
+    }
+}
+
+/// Index of the nearest of the 16 basic colors, by delta-E from xterm's
+/// defaults.
+pub fn ansi16(color: Color) -> u8 {
+    (0..16u8)
+        .min_by(|&a, &b| {
+            let distance = |i: u8| {
+                let (r, g, b) = ANSI16[usize::from(i)];
+                color.delta_e(Color::new(r, g, b))
+            };
+            distance(a).total_cmp(&distance(b))
+        })
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flag_then_no_color_then_terminal() {
+        let set = Some(OsStr::new("1"));
+        assert!(colored(ColorChoice::Auto, None, true));
+        assert!(!colored(ColorChoice::Auto, None, false));
+        assert!(!colored(ColorChoice::Auto, set, true));
+        // An empty NO_COLOR doesn't count
+        assert!(colored(ColorChoice::Auto, Some(OsStr::new("")), true));
+        assert!(colored(ColorChoice::Always, set, false));
+        assert!(!colored(ColorChoice::Never, None, true));
+    }
+
+    #[test]
+    fn depth_from_colorterm_then_terminfo_name() {
+        use ColorDepth::*;
+        assert_eq!(
+            depth_from(Some("truecolor"), Some("screen"), None, false),
+            TrueColor
+        );
+        assert_eq!(
+            depth_from(None, Some("xterm-ghostty"), None, false),
+            TrueColor
+        );
+        assert_eq!(
+            depth_from(None, Some("xterm-direct"), None, false),
+            TrueColor
+        );
+        assert_eq!(
+            depth_from(None, Some("xterm"), Some("WezTerm"), false),
+            TrueColor
+        );
+        // mosh and tmux without truecolor passthrough
+        assert_eq!(
+            depth_from(None, Some("xterm-256color"), None, false),
+            Ansi256
+        );
+        assert_eq!(
+            depth_from(None, Some("tmux-256color"), None, false),
+            Ansi256
+        );
+        assert_eq!(depth_from(None, Some("linux"), None, false), Ansi16);
+        assert_eq!(depth_from(None, None, None, false), Ansi16);
+        assert_eq!(depth_from(None, None, None, true), TrueColor);
+    }
+
+    #[test]
+    fn nearest_palette_colors() {
+        assert_eq!(ansi256(Color::new(255, 0, 0)), 196);
+        assert_eq!(ansi256(Color::new(0, 0, 0)), 16);
+        assert_eq!(ansi256(Color::new(128, 128, 128)), 244);
+        assert_eq!(ansi256(Color::new(0x1e, 0x1e, 0x2e)), 235);
+        assert_eq!(ansi16(Color::new(250, 10, 10)), 9);
+        assert_eq!(ansi16(Color::new(10, 10, 10)), 0);
+        assert_eq!(ansi16(Color::new(0, 150, 150)), 6);
+
+        let red = Color::new(255, 0, 0);
+        assert_eq!(ColorDepth::TrueColor.sgr(red, 30), "38;2;255;0;0");
+        assert_eq!(ColorDepth::Ansi256.sgr(red, 40), "48;5;196");
+        assert_eq!(ColorDepth::Ansi16.sgr(red, 30), "91");
+        assert_eq!(ColorDepth::Ansi16.sgr(Color::new(0, 0, 0), 40), "40");
+    }
+}
@@ -0,0 +1,429 @@
+use std::collections::HashSet;
+use std::io::{self, stdout};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use crossterm::terminal::{
+    disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
+};
+use crossterm::ExecutableCommand;
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Clear, Paragraph};
+
+use super::widgets::{PaletteWidget, PreviewWidget, Scene};
+use super::{centered_rect, Severity, Toast};
+use crate::backends::{ghostty, installed_by, is_generated, uninstall, Target};
+use crate::color::Color as AppColor;
+use crate::config::Config;
+use crate::import::{self, ThemeFormat};
+use crate::pipeline::assign::AnsiPalette;
+
+/// Lines the list moves on PageUp/PageDown.
+const PAGE: usize = 10;
+
+/// One installed theme in the gallery.
+struct Entry {
+    target: Target,
+    name: String,
+    path: PathBuf,
+    /// Carries nuri's header.
+    generated: bool,
+    /// Parsed colors, for the formats nuri can read back.
+    palette: Option<AnsiPalette>,
+    /// Background and slots, or the first colors found in the file.
+    colors: Vec<AppColor>,
+}
+
+impl Entry {
+    fn load(target: Target, name: String, path: PathBuf) -> Self {
+        let content = std::fs::read_to_string(&path).unwrap_or_default();
+        let palette = ThemeFormat::from_path(&path)
+            .and_then(|format| import::parse_palette(&content, format).ok());
+        let colors = match &palette {
+            Some(p) => std::iter::once(p.background).chain(p.slots).collect(),
+            None => import::scan_colors(&content).into_iter().take(17).collect(),
+        };
+        Self {
+            target,
+            name,
+            path,
+            generated: is_generated(&content),
+            palette,
+            colors,
+        }
+    }
+}
+
+/// A theme to open in the editor as a copy, returned by [`run`].
+pub struct Duplicate {
+    pub target: Target,
+    pub name: String,
+    pub path: PathBuf,
+    pub palette: AnsiPalette,
+}
+
+/// State of the `nuri browse` gallery; kept across editor round trips.
+pub struct Browser {
+    config: Config,
+    /// Install directory of each browsed target.
+    dirs: Vec<(Target, PathBuf)>,
+    entries: Vec<Entry>,
+    cursor: usize,
+    scene: Scene,
+    toast: Option<Toast>,
+    confirm_delete: bool,
+    /// Themes Ghostty's config currently selects.
+    active: Vec<String>,
+}
+
+impl Browser {
+    pub fn new(config: Config, dirs: Vec<(Target, PathBuf)>) -> Self {
+        let mut browser = Self {
+            config,
+            dirs,
+            entries: Vec::new(),
+            cursor: 0,
+            scene: Scene::default(),
+            toast: None,
+            confirm_delete: false,
+            active: Vec::new(),
+        };
+        browser.rescan();
+        browser
+    }
+
+    /// Re-read the install directories, keeping the cursor on the same theme
+    /// when it still exists.
+    pub fn rescan(&mut self) {
+        let current = self.entries.get(self.cursor).map(|e| e.path.clone());
+        let config = &self.config;
+        self.entries = self
+            .dirs
+            .iter()
+            .filter_map(|(target, dir)| Some((*target, dir, config.backend(*target).ok()?)))
+            .flat_map(|(target, dir, backend)| {
+                installed_by(backend.as_ref(), dir)
+                    .into_iter()
+                    .map(move |(name, path)| Entry::load(target, name, path))
+            })
+            .collect();
+        if let Some(i) = current.and_then(|p| self.entries.iter().position(|e| e.path == p)) {
+            self.cursor = i;
+        }
+        self.cursor = self.cursor.min(self.entries.len().saturating_sub(1));
+        self.active = std::fs::read_to_string(ghostty::config_path())
+            .map(|text| ghostty::active_themes(&text))
+            .unwrap_or_default();
+    }
+
+    fn notify(&mut self, severity: Severity, text: impl Into<String>) {
+        self.toast = Some(Toast::new(severity, text));
+    }
+
+    fn is_active(&self, entry: &Entry) -> bool {
+        entry.target == Target::Ghostty
+            && self
+                .active
+                .iter()
+                .any(|a| *a == entry.name || Path::new(a) == entry.path)
+    }
+}
+
+/// Run the gallery until the user quits, or picks a theme to duplicate into
+/// the editor.
+pub fn run(browser: &mut Browser) -> Result<Option<Duplicate>> {
+    enable_raw_mode()?;
+    stdout().execute(EnterAlternateScreen)?;
+
+    let backend = CrosstermBackend::new(stdout());
+    let mut terminal = Terminal::new(backend)?;
+
+    let result = run_event_loop(&mut terminal, browser);
+
+    // Always restore terminal, even on error
+    disable_raw_mode()?;
+    stdout().execute(LeaveAlternateScreen)?;
+
+    result
+}
+
+fn run_event_loop(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    browser: &mut Browser,
+) -> Result<Option<Duplicate>> {
+    loop {
+        if browser.toast.as_ref().is_some_and(Toast::expired) {
+            browser.toast = None;
+        }
+        terminal.draw(|f| draw(f, browser))?;
+
+        if !event::poll(Duration::from_millis(100))? {
+            continue;
+        }
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        if browser.confirm_delete {
+            browser.confirm_delete = false;
+            if key.code == KeyCode::Char('y') {
+                delete(browser);
+            }
+            continue;
+        }
+        let last = browser.entries.len().saturating_sub(1);
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return Ok(None),
+            KeyCode::Down | KeyCode::Char('j') => {
+                browser.cursor = (browser.cursor + 1).min(last);
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                browser.cursor = browser.cursor.saturating_sub(1);
+            }
+            KeyCode::PageDown => browser.cursor = (browser.cursor + PAGE).min(last),
+            KeyCode::PageUp => browser.cursor = browser.cursor.saturating_sub(PAGE),
+            KeyCode::Home | KeyCode::Char('g') => browser.cursor = 0,
+            KeyCode::End | KeyCode::Char('G') => browser.cursor = last,
+            KeyCode::Enter | KeyCode::Char('a') => activate(browser),
+            KeyCode::Char('e') => {
+                if let Some(copy) = duplicate(browser) {
+                    return Ok(Some(copy));
+                }
+            }
+            KeyCode::Char('d') | KeyCode::Delete => {
+                browser.confirm_delete = !browser.entries.is_empty();
+            }
+            KeyCode::Char('p') => browser.scene = browser.scene.next(),
+            _ => {}
+        }
+    }
+}
+
+/// Set the selected Ghostty theme in Ghostty's config and reload it.
+fn activate(browser: &mut Browser) {
+    let Some(entry) = browser.entries.get(browser.cursor) else {
+        return;
+    };
+    if entry.target != Target::Ghostty {
+        browser.notify(Severity::Warning, "Only Ghostty themes can be activated");
+        return;
+    }
+    let name = entry.name.clone();
+    let result = ghostty::theme_ref(&name, &browser.config)
+        .and_then(|theme| ghostty::activate(&ghostty::config_path(), &theme));
+    match result {
+        Ok(_) => {
+            let text = if ghostty::reload() {
+                format!("Activated {name} and reloaded Ghostty")
+            } else {
+                format!("Activated {name}; reload Ghostty to see it")
+            };
+            browser.notify(Severity::Success, text);
+            browser.rescan();
+        }
+        Err(e) => browser.notify(Severity::Error, format!("{e:#}")),
+    }
+}
+
+/// The selected theme as a copy to edit, if its colors can be read.
+fn duplicate(browser: &mut Browser) -> Option<Duplicate> {
+    let entry = browser.entries.get(browser.cursor)?;
+    let Some(palette) = entry.palette.clone() else {
+        browser.notify(
+            Severity::Warning,
+            "Can't read this format back; duplicate a Ghostty, Alacritty, base16 or JSON theme",
+        );
+        return None;
+    };
+    Some(Duplicate {
+        target: entry.target,
+        name: entry.name.clone(),
+        path: entry.path.clone(),
+        palette,
+    })
+}
+
+fn delete(browser: &mut Browser) {
+    let Some(entry) = browser.entries.get(browser.cursor) else {
+        return;
+    };
+    let (name, target) = (entry.name.clone(), entry.target);
+    let result = browser.config.backend(target).and_then(|backend| {
+        uninstall(backend.as_ref(), &entry.path)
+            .with_context(|| format!("failed to remove {}", entry.path.display()))
+    });
+    match result {
+        Ok(()) => {
+            browser.rescan();
+            browser.notify(
+                Severity::Success,
+                format!("Deleted {} theme {name}", target.name()),
+            );
+        }
+        Err(e) => browser.notify(Severity::Error, format!("{e:#}")),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Drawing
+// ---------------------------------------------------------------------------
+
+fn draw(f: &mut Frame, browser: &Browser) {
+    let rows = Layout::vertical([Constraint::Min(10), Constraint::Length(1)]).split(f.area());
+    let columns =
+        Layout::horizontal([Constraint::Percentage(40), Constraint::Percentage(60)]).split(rows[0]);
+
+    draw_list(f, browser, columns[0]);
+    draw_theme(f, browser, columns[1]);
+    draw_status_bar(f, browser, rows[1]);
+
+    if browser.confirm_delete {
+        if let Some(entry) = browser.entries.get(browser.cursor) {
+            draw_confirm_delete_overlay(f, entry);
+        }
+    }
+}
+
+fn draw_list(f: &mut Frame, browser: &Browser, area: Rect) {
+    let block = Block::bordered().title(format!("Themes ({})", browser.entries.len()));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    if browser.entries.is_empty() {
+        f.render_widget(Paragraph::new("  No installed themes found"), inner);
+        return;
+    }
+
+    // Scroll so the cursor stays visible
+    let height = inner.height.max(1) as usize;
+    let offset = browser.cursor.saturating_sub(height - 1);
+    let width = browser
+        .entries
+        .iter()
+        .map(|e| e.name.len())
+        .max()
+        .unwrap_or(0)
+        .min(24);
+    let lines: Vec<Line> = browser
+        .entries
+        .iter()
+        .enumerate()
+        .skip(offset)
+        .take(height)
+        .map(|(i, entry)| {
+            let selected = i == browser.cursor;
+            let name_style = if selected {
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+            let mut spans = vec![
+                Span::raw(if selected { " > " } else { "   " }),
+                Span::styled(format!("{:<width$} ", entry.name), name_style),
+            ];
+            for c in entry.colors.iter().take(9) {
+                spans.push(Span::styled(
+                    " ",
+                    Style::default().bg(Color::Rgb(c.r, c.g, c.b)),
+                ));
+            }
+            spans.push(Span::styled(
+                format!(" {}", entry.target.name()),
+                Style::default().fg(Color::DarkGray),
+            ));
+            if entry.generated {
+                spans.push(Span::styled(" nuri", Style::default().fg(Color::Cyan)));
+            }
+            if browser.is_active(entry) {
+                spans.push(Span::styled(" active", Style::default().fg(Color::Green)));
+            }
+            Line::from(spans)
+        })
+        .collect();
+    f.render_widget(Paragraph::new(lines), inner);
+}
+
+fn draw_theme(f: &mut Frame, browser: &Browser, area: Rect) {
+    let Some(entry) = browser.entries.get(browser.cursor) else {
+        f.render_widget(Block::bordered().title("Palette"), area);
+        return;
+    };
+    let rows = Layout::vertical([Constraint::Length(13), Constraint::Min(6)]).split(area);
+    match &entry.palette {
+        Some(palette) => {
+            f.render_widget(PaletteWidget::new(palette, None, &HashSet::new()), rows[0]);
+            let preview = PreviewWidget::new(palette, browser.scene).titled(&entry.name);
+            f.render_widget(preview, rows[1]);
+        }
+        None => {
+            let block = Block::bordered().title("Palette");
+            let inner = block.inner(area);
+            f.render_widget(block, area);
+            let swatch: Vec<Span> =
+                std::iter::once(Span::raw("  "))
+                    .chain(entry.colors.iter().map(|c| {
+                        Span::styled("  ", Style::default().bg(Color::Rgb(c.r, c.g, c.b)))
+                    }))
+                    .collect();
+            let lines = vec![
+                Line::from(""),
+                Line::from(format!("  {}", entry.path.display())),
+                Line::from(""),
+                Line::from("  Colors found in the file:"),
+                Line::from(swatch),
+                Line::from(""),
+                Line::styled(
+                    "  nuri reads Ghostty, Alacritty, base16 and JSON themes back; this one can't be previewed or duplicated",
+                    Style::default().fg(Color::DarkGray),
+                ),
+            ];
+            f.render_widget(Paragraph::new(lines), inner);
+        }
+    }
+}
+
+fn draw_status_bar(f: &mut Frame, browser: &Browser, area: Rect) {
+    let bar = Style::default().bg(Color::Rgb(20, 20, 20));
+    let line = match &browser.toast {
+        Some(toast) => {
+            let text = match toast.severity {
+                Severity::Error => format!(" Error: {}", toast.text),
+                _ => format!(" {}", toast.text),
+            };
+            Line::styled(text, toast.severity.style())
+        }
+        None => Line::styled(
+            " j/k: Move | Enter: Activate (Ghostty) | e: Duplicate into editor | d: Delete | p: Scene | q: Quit",
+            Style::default().fg(Color::DarkGray),
+        ),
+    };
+    f.render_widget(Paragraph::new(line).style(bar), area);
+}
+
+fn draw_confirm_delete_overlay(f: &mut Frame, entry: &Entry) {
+    let area = centered_rect(50, 25, f.area());
+    let mut lines = vec![
+        Line::from(""),
+        Line::from(format!("  Delete {}?", entry.name)),
+        Line::from(format!("  {}", entry.path.display())),
+    ];
+    if !entry.generated {
+        lines.push(Line::styled(
+            "  Not generated by nuri",
+            Style::default().fg(Color::Yellow),
+        ));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from("  y: Delete | any other key: Cancel"));
+    let popup = Paragraph::new(lines)
+        .block(Block::bordered().title(" Confirm Delete "))
+        .style(Style::default().bg(Color::Black).fg(Color::White));
+    f.render_widget(Clear, area);
+    f.render_widget(popup, area);
+}
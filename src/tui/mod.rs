@@ -1,11 +1,16 @@
+pub mod browse;
+pub mod snapshot;
 pub mod widgets;
 
+use std::collections::{HashMap, HashSet};
 use std::io::{self, stdout};
 use std::path::{Path, PathBuf};
-use std::time::Duration;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+use std::time::{Duration, Instant};
 
-use anyhow::Result;
-use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use anyhow::{bail, Result};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
 use crossterm::terminal::{
     disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
 };
@@ -14,13 +19,30 @@ use palette::Lab;
 use ratatui::prelude::*;
 use ratatui::widgets::{Block, Clear, Paragraph};
 
-use crate::backends::{get_backend, Target};
+use crate::backends::Target;
+use crate::cache;
+use crate::check::SIMILAR_DELTA_E;
 use crate::cli::ThemeMode;
-use crate::pipeline::assign::{assign_slots, AnsiPalette};
-use crate::pipeline::contrast::{enforce_contrast, DEFAULT_ACCENT_CONTRAST};
-use crate::pipeline::extract::{extract_colors_with_seed, ExtractedColor};
+use crate::clipboard::{Clipboard, CopyMethod};
+use crate::color::{Color as AppColor, ColorBlindness};
+use crate::config::{Config, SLOT_NAMES};
+use crate::import;
+use crate::live;
+use crate::pipeline::assign::{assign_slots_with, hue_distance, AnsiPalette};
+use crate::pipeline::colorblind::separate_accents;
+use crate::pipeline::contrast::{
+    enforce_contrast, BRIGHT_BLACK_MIN_CONTRAST, DEFAULT_ACCENT_CONTRAST, FOREGROUND_MIN_CONTRAST,
+};
+use crate::pipeline::extract::{
+    refine, sample_pixels, ExtractedColor, Sampling, DEFAULT_MERGE_DELTA_E, DEFAULT_SAMPLE_SIZE,
+    MAX_COLORS, MIN_COLORS,
+};
+use crate::pipeline::profile::Profile;
+use crate::pipeline::quantize::Algorithm;
+use crate::pipeline::style::{apply_styles, PaletteStyle};
+use crate::progress::SPINNER;
 
-use self::widgets::{PaletteWidget, PreviewWidget};
+use self::widgets::{CompareWidget, PaletteWidget, PreviewWidget, Scene};
 
 /// Input mode for the TUI.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -30,6 +52,122 @@ enum InputMode {
     NameInput,
     ConfirmQuit,
     ConfirmOverwrite,
+    CandidatePicker,
+    HexInput,
+    CompareInput,
+    Export,
+    OklchInput,
+}
+
+/// Size of the lightness, chroma and hue steps, cycled with `z`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum Step {
+    Fine,
+    #[default]
+    Normal,
+    Coarse,
+}
+
+impl Step {
+    fn name(self) -> &'static str {
+        match self {
+            Step::Fine => "fine",
+            Step::Normal => "normal",
+            Step::Coarse => "coarse",
+        }
+    }
+
+    fn next(self) -> Step {
+        match self {
+            Step::Normal => Step::Coarse,
+            Step::Coarse => Step::Fine,
+            Step::Fine => Step::Normal,
+        }
+    }
+
+    /// Factor applied to the normal lightness, chroma and hue steps.
+    fn scale(self) -> f32 {
+        match self {
+            Step::Fine => 0.25,
+            Step::Normal => 1.0,
+            Step::Coarse => 4.0,
+        }
+    }
+}
+
+/// Oklch lightness change of a normal `+`/`-` step.
+const LIGHTNESS_STEP: f32 = 0.02;
+
+/// Oklch chroma change of a normal `s`/`S` step.
+const CHROMA_STEP: f32 = 0.02;
+
+/// Hue rotation in degrees of a normal `h`/`H` step.
+const HUE_STEP: f32 = 5.0;
+
+/// How a toast is styled and how long it stays up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Info,
+    Success,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    /// Time before the toast expires; problems stay up longer.
+    fn duration(self) -> Duration {
+        match self {
+            Severity::Info | Severity::Success => Duration::from_secs(3),
+            Severity::Warning => Duration::from_secs(5),
+            Severity::Error => Duration::from_secs(8),
+        }
+    }
+
+    fn style(self) -> Style {
+        match self {
+            Severity::Info => Style::default().fg(Color::White),
+            Severity::Success => Style::default().fg(Color::Green),
+            Severity::Warning => Style::default().fg(Color::Yellow),
+            Severity::Error => Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+        }
+    }
+}
+
+/// Transient message shown in the status bar.
+#[derive(Debug, Clone)]
+pub struct Toast {
+    pub text: String,
+    pub severity: Severity,
+    shown: Instant,
+}
+
+impl Toast {
+    fn new(severity: Severity, text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            severity,
+            shown: Instant::now(),
+        }
+    }
+
+    fn expired(&self) -> bool {
+        self.shown.elapsed() >= self.severity.duration()
+    }
+}
+
+/// Another theme shown next to the palette being edited.
+struct Comparison {
+    name: String,
+    palette: AnsiPalette,
+}
+
+/// What confirming the backend picker does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SaveAction {
+    /// Prompt for a path and write there.
+    Write,
+    /// Install into each backend's standard config directory.
+    Install,
 }
 
 /// State for the interactive TUI application.
@@ -41,17 +179,97 @@ pub struct TuiApp {
     pub selected_slot: Option<usize>,
     pub theme_name: String,
     pub show_help: bool,
+    /// Contrast warnings overlay (`W`), kept up while colors are edited.
+    pub show_warnings: bool,
     pub dirty: bool,
-    pub status_message: Option<String>,
+    pub toast: Option<Toast>,
     input_mode: InputMode,
     name_input_buf: String,
+    /// Action the backend picker leads to.
+    save_action: SaveAction,
+    /// Existing file shown in the overwrite confirmation.
+    overwrite_path: String,
+    /// The image's (weighted) pixels; each re-extraction samples them with
+    /// its own seed, as the CLI would.
     pixels: Vec<Lab>,
+    /// `--sampling` and `--sample-size` for re-extractions.
+    sampling: (Sampling, usize),
     k: usize,
     seed: u64,
+    /// Extraction algorithm for regeneration.
+    algorithm: Algorithm,
+    /// `--merge-delta-e` and `--min-weight` (as a fraction), applied to
+    /// re-extracted colors.
+    refinement: (f32, f32),
+    /// Cache key of the initial extraction; re-extractions reuse it with
+    /// their own k and seed. `None` with --no-cache or without an image.
+    cache: Option<cache::Key>,
     /// Targets passed via --target CLI flag (empty = show picker).
     cli_targets: Vec<Target>,
-    /// Backend selection state for the picker popup.
-    selected_backends: [bool; 3],
+    /// Backend selection state for the picker popup, indexed like `Target::all()`.
+    selected_backends: Vec<bool>,
+    /// Highlighted row in the backend picker.
+    backend_cursor: usize,
+    /// Export dialog (`e`) selection, indexed like `Target::all()`; kept
+    /// between openings.
+    export_selection: Vec<bool>,
+    export_cursor: usize,
+    export_action: SaveAction,
+    /// Targets chosen in the export dialog, used instead of the CLI targets
+    /// or the picker for the save it starts.
+    save_override: Option<Vec<Target>>,
+    /// Text typed in the hex input prompt.
+    hex_input_buf: String,
+    /// Text typed in the Oklch input prompt.
+    oklch_input_buf: String,
+    /// Palette before hex or Oklch entry started, restored on cancel.
+    input_original: Option<AnsiPalette>,
+    /// Size of the lightness, chroma and hue steps.
+    step: Step,
+    /// Highlighted row in the candidate picker.
+    candidate_cursor: usize,
+    /// User config: install directories, slot overrides and key remaps.
+    config: Config,
+    /// Rebound key -> default key of the same action, from `[keys]`.
+    key_remap: HashMap<char, char>,
+    /// Push palette changes into the running terminal as they happen.
+    live: bool,
+    /// Palette last pushed to the terminal (None = terminal colors untouched).
+    live_applied: Option<AnsiPalette>,
+    /// Color vision deficiency the palette and preview are drawn through.
+    simulation: Option<ColorBlindness>,
+    /// Accent styles applied when the palette is rebuilt (--style).
+    styles: Vec<PaletteStyle>,
+    /// Separate confusable accents when the palette is rebuilt (--colorblind-safe).
+    colorblind_safe: bool,
+    /// Accent targets used when the palette is rebuilt (--profile).
+    profile: Profile,
+    /// Accent contrast enforced on rebuilds and checked by the warnings
+    /// (--min-contrast).
+    min_contrast: f32,
+    /// Edited cursor and selection colors, by selection index, kept through
+    /// the contrast pass that would otherwise re-derive them.
+    special_pins: HashMap<usize, AppColor>,
+    /// Re-extraction running in the background after `k`/`K`.
+    extraction: Option<Extraction>,
+    /// Selection indices of colors that rebuilds and contrast passes keep.
+    locked: HashSet<usize>,
+    /// Opened on the first copy and kept so the copied text stays available.
+    clipboard: Clipboard,
+    /// Sample content in the preview pane.
+    scene: Scene,
+    /// Theme shown side by side in compare mode (`C`).
+    comparison: Option<Comparison>,
+    compare_input_buf: String,
+}
+
+/// Color extraction with a new cluster count, running on its own thread so
+/// the UI stays responsive.
+struct Extraction {
+    k: usize,
+    seed: u64,
+    started: Instant,
+    result: Receiver<Vec<ExtractedColor>>,
 }
 
 impl TuiApp {
@@ -72,15 +290,48 @@ impl TuiApp {
             selected_slot: None,
             theme_name: theme_name.clone(),
             show_help: false,
+            show_warnings: false,
             dirty: false,
-            status_message: None,
+            toast: None,
             input_mode: InputMode::Normal,
             name_input_buf: format!("~/{theme_name}"),
+            save_action: SaveAction::Write,
+            overwrite_path: String::new(),
             pixels,
+            sampling: (Sampling::All, DEFAULT_SAMPLE_SIZE),
             k,
             seed: 42,
+            algorithm: Algorithm::Kmeans,
+            refinement: (DEFAULT_MERGE_DELTA_E, 0.0),
+            cache: None,
             cli_targets: Vec::new(),
-            selected_backends: [true, false, false],
+            selected_backends: default_backend_selection(),
+            backend_cursor: 0,
+            export_selection: Vec::new(),
+            export_cursor: 0,
+            export_action: SaveAction::Install,
+            save_override: None,
+            hex_input_buf: String::new(),
+            oklch_input_buf: String::new(),
+            input_original: None,
+            step: Step::default(),
+            candidate_cursor: 0,
+            config: Config::default(),
+            key_remap: HashMap::new(),
+            live: false,
+            live_applied: None,
+            simulation: None,
+            styles: Vec::new(),
+            colorblind_safe: false,
+            profile: Profile::default(),
+            min_contrast: DEFAULT_ACCENT_CONTRAST,
+            special_pins: HashMap::new(),
+            extraction: None,
+            locked: HashSet::new(),
+            clipboard: Clipboard::default(),
+            scene: Scene::default(),
+            comparison: None,
+            compare_input_buf: String::new(),
         }
     }
 
@@ -88,6 +339,93 @@ impl TuiApp {
     pub fn set_targets(&mut self, targets: Vec<Target>) {
         self.cli_targets = targets;
     }
+
+    /// Use the loaded config file for install paths, slot overrides and keys.
+    pub fn set_config(&mut self, config: Config) {
+        self.key_remap = config.key_remap();
+        self.config = config;
+    }
+
+    /// Start with live terminal preview on (--apply-live).
+    pub fn set_live(&mut self, live: bool) {
+        self.live = live;
+    }
+
+    /// Start with a color vision simulation on (--simulate).
+    pub fn set_simulation(&mut self, simulation: Option<ColorBlindness>) {
+        self.simulation = simulation;
+    }
+
+    /// Styles to apply when switching mode or regenerating.
+    pub fn set_styles(&mut self, styles: Vec<PaletteStyle>) {
+        self.styles = styles;
+    }
+
+    /// Keep accents colorblind-safe when switching mode or regenerating.
+    pub fn set_colorblind_safe(&mut self, colorblind_safe: bool) {
+        self.colorblind_safe = colorblind_safe;
+    }
+
+    /// Assignment profile to use when switching mode or regenerating.
+    pub fn set_profile(&mut self, profile: Profile) {
+        self.profile = profile;
+    }
+
+    /// Accent contrast for rebuilds and warnings (--min-contrast).
+    pub fn set_min_contrast(&mut self, min_contrast: f32) {
+        self.min_contrast = min_contrast;
+    }
+
+    /// Show a toast in the status bar, replacing the current one.
+    pub fn notify(&mut self, severity: Severity, text: impl Into<String>) {
+        self.toast = Some(Toast::new(severity, text));
+    }
+
+    /// Start in compare mode against an installed Ghostty theme or a theme
+    /// file (--compare).
+    pub fn compare_with(&mut self, theme: &str) -> Result<()> {
+        self.comparison = Some(load_comparison(&self.config, theme)?);
+        Ok(())
+    }
+
+    /// Colors locked from the start (--lock), by `[slots]` name.
+    pub fn set_locked(&mut self, names: &[String]) {
+        self.locked = names.iter().filter_map(|n| selection_index(n)).collect();
+    }
+
+    /// Cache re-extractions (`r`, `k`/`K`) under `key` with their k and seed.
+    pub fn set_cache(&mut self, key: Option<cache::Key>) {
+        self.cache = key;
+    }
+
+    /// How re-extractions sample the pixels (--sampling, --sample-size).
+    pub fn set_sampling(&mut self, sampling: Sampling, sample_size: usize) {
+        self.sampling = (sampling, sample_size);
+    }
+
+    /// Seed the initial palette was extracted with (--seed); `r` continues from it.
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
+    }
+
+    /// Extraction algorithm used when regenerating (--algorithm).
+    pub fn set_algorithm(&mut self, algorithm: Algorithm) {
+        self.algorithm = algorithm;
+    }
+
+    /// Cleanup of re-extracted colors (--merge-delta-e, --min-weight as a
+    /// fraction).
+    pub fn set_refinement(&mut self, merge_delta_e: f32, min_weight: f32) {
+        self.refinement = (merge_delta_e, min_weight);
+    }
+}
+
+/// Initial picker state: only Ghostty selected.
+fn default_backend_selection() -> Vec<bool> {
+    Target::all()
+        .iter()
+        .map(|&t| t == Target::Ghostty)
+        .collect()
 }
 
 /// Launch the TUI application.
@@ -98,9 +436,16 @@ pub fn run(mut app: TuiApp) -> Result<()> {
     let backend = CrosstermBackend::new(stdout());
     let mut terminal = Terminal::new(backend)?;
 
+    // Log lines would land on top of the screen; toasts report instead
+    let level = log::max_level();
+    log::set_max_level(log::LevelFilter::Off);
     let result = run_event_loop(&mut terminal, &mut app);
+    log::set_max_level(level);
 
     // Always restore terminal, even on error
+    if app.live_applied.is_some() {
+        live::reset(&mut stdout())?;
+    }
     disable_raw_mode()?;
     stdout().execute(LeaveAlternateScreen)?;
 
@@ -112,6 +457,15 @@ fn run_event_loop(
     app: &mut TuiApp,
 ) -> Result<()> {
     loop {
+        poll_extraction(app);
+        if let Err(e) = sync_live(app) {
+            app.live = false;
+            app.live_applied = None;
+            app.notify(Severity::Error, format!("Live preview off: {e}"));
+        }
+        if app.toast.as_ref().is_some_and(Toast::expired) {
+            app.toast = None;
+        }
         terminal.draw(|f| draw(f, app))?;
 
         if event::poll(Duration::from_millis(100))? {
@@ -129,8 +483,21 @@ fn run_event_loop(
                         InputMode::ConfirmOverwrite => {
                             handle_confirm_overwrite(app, key.code);
                         }
+                        InputMode::CandidatePicker => {
+                            handle_candidate_picker(app, key.code);
+                        }
+                        InputMode::HexInput => handle_hex_input(app, key.code),
+                        InputMode::CompareInput => handle_compare_input(app, key.code),
+                        InputMode::Export => handle_export(app, key.code),
+                        InputMode::OklchInput => handle_oklch_input(app, key.code),
                         InputMode::Normal => {
-                            if handle_normal_input(app, key.code) {
+                            let code = match key.code {
+                                KeyCode::Char(c) => {
+                                    KeyCode::Char(app.key_remap.get(&c).copied().unwrap_or(c))
+                                }
+                                code => code,
+                            };
+                            if handle_normal_input(app, code, key.modifiers) {
                                 return Ok(());
                             }
                         }
@@ -145,7 +512,7 @@ fn handle_name_input(app: &mut TuiApp, code: KeyCode) {
     match code {
         KeyCode::Enter => {
             if let Err(e) = try_save(app) {
-                app.status_message = Some(format!("Error: {e}"));
+                app.notify(Severity::Error, e.to_string());
                 app.input_mode = InputMode::Normal;
             }
         }
@@ -161,8 +528,12 @@ fn handle_name_input(app: &mut TuiApp, code: KeyCode) {
 fn handle_confirm_overwrite(app: &mut TuiApp, code: KeyCode) {
     match code {
         KeyCode::Char('y') => {
-            if let Err(e) = do_save(app) {
-                app.status_message = Some(format!("Error: {e}"));
+            let result = match app.save_action {
+                SaveAction::Write => do_save(app),
+                SaveAction::Install => do_install(app),
+            };
+            if let Err(e) = result {
+                app.notify(Severity::Error, e.to_string());
             }
             app.input_mode = InputMode::Normal;
         }
@@ -171,8 +542,21 @@ fn handle_confirm_overwrite(app: &mut TuiApp, code: KeyCode) {
 }
 
 /// Handle key input in normal mode. Returns true if the app should quit.
-fn handle_normal_input(app: &mut TuiApp, code: KeyCode) -> bool {
-    app.status_message = None;
+fn handle_normal_input(app: &mut TuiApp, code: KeyCode, modifiers: KeyModifiers) -> bool {
+    // Keys dismiss news, but problems stay up until they expire
+    if app
+        .toast
+        .as_ref()
+        .is_some_and(|t| matches!(t.severity, Severity::Info | Severity::Success))
+    {
+        app.toast = None;
+    }
+    // Alt makes a single fine step, whatever the step size
+    let scale = if modifiers.contains(KeyModifiers::ALT) {
+        Step::Fine.scale()
+    } else {
+        app.step.scale()
+    };
     match code {
         KeyCode::Char('q') => {
             if app.dirty {
@@ -182,14 +566,22 @@ fn handle_normal_input(app: &mut TuiApp, code: KeyCode) -> bool {
             }
         }
         KeyCode::Char('?') => app.show_help = !app.show_help,
+        KeyCode::Char('W') => app.show_warnings = !app.show_warnings,
         KeyCode::Tab => cycle_slot(app),
         KeyCode::BackTab => cycle_slot_reverse(app),
-        KeyCode::Char(c @ '1'..='6') => {
-            app.selected_slot = Some((c as u8 - b'0') as usize);
-        }
+        KeyCode::Char(c @ '0'..='7') => select_pair(app, (c as u8 - b'0') as usize, 8),
+        KeyCode::Char('b') => app.selected_slot = Some(BACKGROUND),
+        KeyCode::Char('f') => app.selected_slot = Some(FOREGROUND),
+        KeyCode::Char('u') => select_pair(app, CURSOR, 1),
+        KeyCode::Char('x') => select_pair(app, SELECTION_BG, 1),
+        KeyCode::Char('L') => toggle_lock(app),
+        KeyCode::Char('y') => copy_selected(app),
+        KeyCode::Char('Y') => copy_palette(app),
         KeyCode::Esc => {
             if app.show_help {
                 app.show_help = false;
+            } else if app.show_warnings {
+                app.show_warnings = false;
             } else {
                 app.selected_slot = None;
             }
@@ -197,155 +589,948 @@ fn handle_normal_input(app: &mut TuiApp, code: KeyCode) -> bool {
         KeyCode::Char('d') => switch_mode(app, ThemeMode::Dark),
         KeyCode::Char('l') => switch_mode(app, ThemeMode::Light),
         KeyCode::Char('r') => regenerate(app),
-        KeyCode::Char('+') | KeyCode::Char('=') => adjust_lightness(app, 0.02),
-        KeyCode::Char('-') => adjust_lightness(app, -0.02),
-        KeyCode::Char('s') => adjust_chroma(app, -0.02),
-        KeyCode::Char('S') => adjust_chroma(app, 0.02),
+        KeyCode::Char('k') => change_cluster_count(app, -1),
+        KeyCode::Char('K') => change_cluster_count(app, 1),
+        KeyCode::Char('+') | KeyCode::Char('=') => adjust_lightness(app, LIGHTNESS_STEP * scale),
+        KeyCode::Char('-') => adjust_lightness(app, -LIGHTNESS_STEP * scale),
+        KeyCode::Char('s') => adjust_chroma(app, -CHROMA_STEP * scale),
+        KeyCode::Char('S') => adjust_chroma(app, CHROMA_STEP * scale),
+        KeyCode::Char('h') => adjust_hue(app, -HUE_STEP * scale),
+        KeyCode::Char('H') => adjust_hue(app, HUE_STEP * scale),
+        KeyCode::Char('z') => {
+            app.step = app.step.next();
+            app.notify(Severity::Info, format!("Step size: {}", app.step.name()));
+        }
+        KeyCode::Char('c') => open_candidate_picker(app),
+        KeyCode::Char('#') => open_hex_input(app),
+        KeyCode::Char('o') => open_oklch_input(app),
         KeyCode::Left => cycle_candidate(app, false),
         KeyCode::Right => cycle_candidate(app, true),
-        KeyCode::Enter => {
-            if app.cli_targets.is_empty() {
-                // No --target specified: show backend picker
-                app.selected_backends = [true, false, false];
-                app.input_mode = InputMode::BackendSelect;
-            } else {
-                // --target specified: skip picker, go straight to name input
-                app.name_input_buf = format!("~/{}", app.theme_name);
-                app.input_mode = InputMode::NameInput;
-            }
-        }
+        KeyCode::Enter => start_save(app, SaveAction::Write),
+        KeyCode::Char('w') => start_save(app, SaveAction::Install),
+        KeyCode::Char('a') => toggle_live(app),
+        KeyCode::Char('v') => cycle_simulation(app),
+        KeyCode::Char('p') => app.scene = app.scene.next(),
+        KeyCode::Char('C') => toggle_compare(app),
+        KeyCode::Char('e') => open_export(app),
         _ => {}
     }
     false
 }
 
+/// Begin a save: show the backend picker, or skip it when --target was given.
+fn start_save(app: &mut TuiApp, action: SaveAction) {
+    app.save_action = action;
+    app.save_override = None;
+    if app.cli_targets.is_empty() {
+        app.selected_backends = default_backend_selection();
+        app.backend_cursor = 0;
+        app.input_mode = InputMode::BackendSelect;
+    } else {
+        confirm_backends(app);
+    }
+}
+
+/// Continue after the backends are chosen.
+fn confirm_backends(app: &mut TuiApp) {
+    match app.save_action {
+        SaveAction::Write => {
+            app.name_input_buf = format!("~/{}", app.theme_name);
+            app.input_mode = InputMode::NameInput;
+        }
+        SaveAction::Install => {
+            if let Err(e) = try_install(app) {
+                app.notify(Severity::Error, e.to_string());
+                app.input_mode = InputMode::Normal;
+            }
+        }
+    }
+}
+
 fn handle_backend_select(app: &mut TuiApp, code: KeyCode) {
+    let count = app.selected_backends.len();
     match code {
-        KeyCode::Char('g') => app.selected_backends[0] = !app.selected_backends[0],
-        KeyCode::Char('z') => app.selected_backends[1] = !app.selected_backends[1],
-        KeyCode::Char('n') => app.selected_backends[2] = !app.selected_backends[2],
+        KeyCode::Char('g') => toggle_backend(app, Target::Ghostty),
+        KeyCode::Char('z') => toggle_backend(app, Target::Zellij),
+        KeyCode::Char('n') => toggle_backend(app, Target::Neovim),
+        KeyCode::Up | KeyCode::Char('k') => {
+            app.backend_cursor = (app.backend_cursor + count - 1) % count;
+        }
+        KeyCode::Down | KeyCode::Char('j') => {
+            app.backend_cursor = (app.backend_cursor + 1) % count;
+        }
+        KeyCode::Char(' ') => {
+            let i = app.backend_cursor;
+            app.selected_backends[i] = !app.selected_backends[i];
+        }
         KeyCode::Char('a') => {
             let all_selected = app.selected_backends.iter().all(|&b| b);
-            app.selected_backends = [!all_selected; 3];
+            app.selected_backends = vec![!all_selected; count];
         }
         KeyCode::Enter => {
             if !app.selected_backends.iter().any(|&b| b) {
-                app.status_message = Some("Select at least one backend".to_string());
+                app.notify(Severity::Warning, "Select at least one backend");
                 return;
             }
-            app.name_input_buf = format!("~/{}", app.theme_name);
-            app.input_mode = InputMode::NameInput;
+            confirm_backends(app);
+        }
+        KeyCode::Esc => app.input_mode = InputMode::Normal,
+        _ => {}
+    }
+}
+
+/// Open the export dialog, starting from the CLI targets the first time.
+fn open_export(app: &mut TuiApp) {
+    if app.export_selection.is_empty() {
+        app.export_selection = if app.cli_targets.is_empty() {
+            default_backend_selection()
+        } else {
+            Target::all()
+                .iter()
+                .map(|t| app.cli_targets.contains(t))
+                .collect()
+        };
+    }
+    app.input_mode = InputMode::Export;
+}
+
+fn handle_export(app: &mut TuiApp, code: KeyCode) {
+    let count = app.export_selection.len();
+    match code {
+        KeyCode::Up | KeyCode::Char('k') => {
+            app.export_cursor = (app.export_cursor + count - 1) % count;
+        }
+        KeyCode::Down | KeyCode::Char('j') => {
+            app.export_cursor = (app.export_cursor + 1) % count;
+        }
+        KeyCode::Char(' ') => {
+            let i = app.export_cursor;
+            app.export_selection[i] = !app.export_selection[i];
+        }
+        KeyCode::Char('a') => {
+            let all_selected = app.export_selection.iter().all(|&b| b);
+            app.export_selection = vec![!all_selected; count];
+        }
+        KeyCode::Tab | KeyCode::BackTab => {
+            app.export_action = match app.export_action {
+                SaveAction::Install => SaveAction::Write,
+                SaveAction::Write => SaveAction::Install,
+            };
+        }
+        KeyCode::Enter => {
+            let targets = export_targets(app);
+            if targets.is_empty() {
+                app.notify(Severity::Warning, "Select at least one backend");
+                return;
+            }
+            app.save_override = Some(targets);
+            app.save_action = app.export_action;
+            confirm_backends(app);
         }
         KeyCode::Esc => app.input_mode = InputMode::Normal,
         _ => {}
     }
 }
 
+/// Targets checked in the export dialog.
+fn export_targets(app: &TuiApp) -> Vec<Target> {
+    Target::all()
+        .iter()
+        .zip(app.export_selection.iter())
+        .filter(|(_, &selected)| selected)
+        .map(|(&t, _)| t)
+        .collect()
+}
+
+/// Where exporting to `target` would write, or why it can't.
+fn export_destination(app: &TuiApp, target: Target) -> Result<PathBuf> {
+    match app.export_action {
+        SaveAction::Install => app.config.install_path(target, &app.theme_name),
+        SaveAction::Write => {
+            let base = expand_tilde(&format!("~/{}", app.theme_name));
+            Ok(save_path_for_backend(
+                &base,
+                app.config.backend(target)?.extension(),
+            ))
+        }
+    }
+}
+
+fn toggle_backend(app: &mut TuiApp, target: Target) {
+    if let Some(i) = Target::all().iter().position(|&t| t == target) {
+        app.selected_backends[i] = !app.selected_backends[i];
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Live terminal preview
+// ---------------------------------------------------------------------------
+
+fn toggle_live(app: &mut TuiApp) {
+    app.live = !app.live;
+    app.notify(
+        Severity::Info,
+        if app.live {
+            "Live preview on".to_string()
+        } else {
+            "Live preview off, terminal colors restored".to_string()
+        },
+    );
+}
+
+/// Step through the color vision simulations, then back to normal vision.
+fn cycle_simulation(app: &mut TuiApp) {
+    let all = ColorBlindness::ALL;
+    app.simulation = match app.simulation {
+        None => Some(all[0]),
+        Some(kind) => all
+            .iter()
+            .position(|&k| k == kind)
+            .and_then(|i| all.get(i + 1))
+            .copied(),
+    };
+    app.notify(
+        Severity::Info,
+        match app.simulation {
+            Some(kind) => format!("Simulating {} (display only)", kind.name()),
+            None => "Simulation off".to_string(),
+        },
+    );
+}
+
+/// Bring the terminal's colors in line with the live-preview state.
+fn sync_live(app: &mut TuiApp) -> Result<()> {
+    if app.live {
+        if app.live_applied.as_ref() != Some(&app.palette) {
+            live::apply(&app.palette, &mut stdout())?;
+            app.live_applied = Some(app.palette.clone());
+        }
+    } else if app.live_applied.take().is_some() {
+        live::reset(&mut stdout())?;
+    }
+    Ok(())
+}
+
 // ---------------------------------------------------------------------------
 // Slot navigation
 // ---------------------------------------------------------------------------
 
 fn cycle_slot(app: &mut TuiApp) {
     app.selected_slot = Some(match app.selected_slot {
-        None | Some(15) => 0,
-        Some(n) => n + 1,
+        Some(n) if n + 1 < SELECTABLE => n + 1,
+        _ => 0,
     });
 }
 
 fn cycle_slot_reverse(app: &mut TuiApp) {
     app.selected_slot = Some(match app.selected_slot {
-        None | Some(0) => 15,
+        None | Some(0) => SELECTABLE - 1,
         Some(n) => n - 1,
     });
 }
 
+/// Select `first`, or `first + offset` when `first` is already selected:
+/// a digit twice picks the bright variant, `u`/`x` twice the text color.
+fn select_pair(app: &mut TuiApp, first: usize, offset: usize) {
+    app.selected_slot = Some(if app.selected_slot == Some(first) {
+        first + offset
+    } else {
+        first
+    });
+}
+
+// ---------------------------------------------------------------------------
+// Selectable colors
+// ---------------------------------------------------------------------------
+
+/// Special colors selectable after the 16 slots, as selection indices 16-21,
+/// named as in `[slots]`.
+const SPECIAL_NAMES: [&str; 6] = [
+    "background",
+    "foreground",
+    "cursor",
+    "cursor-text",
+    "selection-bg",
+    "selection-fg",
+];
+
+pub(crate) const BACKGROUND: usize = 16;
+pub(crate) const FOREGROUND: usize = 17;
+pub(crate) const CURSOR: usize = 18;
+pub(crate) const CURSOR_TEXT: usize = 19;
+pub(crate) const SELECTION_BG: usize = 20;
+pub(crate) const SELECTION_FG: usize = 21;
+
+/// Number of selectable colors: the 16 slots and the special colors.
+pub(crate) const SELECTABLE: usize = 16 + SPECIAL_NAMES.len();
+
+/// Selection index of a `[slots]` name: `0`-`15`, an ANSI name or a
+/// special color.
+fn selection_index(name: &str) -> Option<usize> {
+    if let Ok(i) = name.parse::<usize>() {
+        return (i < 16).then_some(i);
+    }
+    SLOT_NAMES.iter().position(|&n| n == name).or_else(|| {
+        SPECIAL_NAMES
+            .iter()
+            .position(|&n| n == name)
+            .map(|i| i + 16)
+    })
+}
+
+/// `[slots]` name of a selectable color.
+pub(crate) fn color_name(index: usize) -> &'static str {
+    match index {
+        0..=15 => SLOT_NAMES[index],
+        _ => SPECIAL_NAMES[index - 16],
+    }
+}
+
+/// Current value of a selectable color.
+pub(crate) fn color_at(palette: &AnsiPalette, index: usize) -> AppColor {
+    match index {
+        0..=15 => palette.slots[index],
+        BACKGROUND => palette.background,
+        FOREGROUND => palette.foreground,
+        CURSOR => palette.cursor_color,
+        CURSOR_TEXT => palette.cursor_text,
+        SELECTION_BG => palette.selection_bg,
+        _ => palette.selection_fg,
+    }
+}
+
+/// Color a selectable color is read against: the foreground for the
+/// background, the cursor or selection for their text, else the background.
+pub(crate) fn contrast_partner(palette: &AnsiPalette, index: usize) -> AppColor {
+    match index {
+        BACKGROUND => palette.foreground,
+        CURSOR_TEXT => palette.cursor_color,
+        SELECTION_FG => palette.selection_bg,
+        _ => palette.background,
+    }
+}
+
+/// Set a selectable color. Background and foreground are slots 0 and 15 and
+/// change with them; cursor and selection colors are pinned.
+fn set_color_at(app: &mut TuiApp, index: usize, color: AppColor) {
+    match index {
+        0 | BACKGROUND => {
+            app.palette.slots[0] = color;
+            app.palette.background = color;
+        }
+        15 | FOREGROUND => {
+            app.palette.slots[15] = color;
+            app.palette.foreground = color;
+        }
+        1..=14 => app.palette.slots[index] = color,
+        _ => {
+            app.special_pins.insert(index, color);
+        }
+    }
+    apply_special_pins(app);
+}
+
+/// A contrast or distinctness problem with one selectable color.
+struct Warning {
+    index: usize,
+    text: String,
+}
+
+/// Check `palette` the way it is built: accents and the cursor and selection
+/// text need `min_contrast`, the foreground 7:1 and bright black 3:1, and
+/// accents must stay [`SIMILAR_DELTA_E`] apart within the normal and bright
+/// ranges. Sorted by selection index.
+fn contrast_warnings(palette: &AnsiPalette, min_contrast: f32) -> Vec<Warning> {
+    let mut required: Vec<(usize, f32)> = (1..=6)
+        .chain(9..=14)
+        .map(|index| (index, min_contrast))
+        .collect();
+    required.extend([
+        (8, BRIGHT_BLACK_MIN_CONTRAST),
+        (FOREGROUND, FOREGROUND_MIN_CONTRAST),
+        (CURSOR_TEXT, min_contrast),
+        (SELECTION_FG, min_contrast),
+    ]);
+
+    let mut warnings = Vec::new();
+    for (index, min) in required {
+        let color = color_at(palette, index);
+        let ratio = AppColor::contrast_ratio(&color, &contrast_partner(palette, index));
+        if ratio < min {
+            let against = match index {
+                CURSOR_TEXT => "cursor",
+                SELECTION_FG => "selection",
+                _ => "background",
+            };
+            warnings.push(Warning {
+                index,
+                text: format!("{ratio:.2}:1 on {against}, needs {min:.1}:1"),
+            });
+        }
+    }
+    for range in [1..=6, 9..=14] {
+        for a in range.clone() {
+            for b in a + 1..=*range.end() {
+                let delta_e = palette.slots[a].delta_e(palette.slots[b]);
+                if delta_e < SIMILAR_DELTA_E {
+                    for (index, other) in [(a, b), (b, a)] {
+                        warnings.push(Warning {
+                            index,
+                            text: format!(
+                                "\u{394}E {delta_e:.1} from {}, needs {SIMILAR_DELTA_E:.0}",
+                                SLOT_NAMES[other]
+                            ),
+                        });
+                    }
+                }
+            }
+        }
+    }
+    warnings.sort_by_key(|w| w.index);
+    warnings
+}
+
+/// Selection indices the palette pane marks with `!`. Slot 15 is the
+/// foreground and shares its warnings.
+fn flagged_colors(warnings: &[Warning]) -> HashSet<usize> {
+    let mut flagged: HashSet<usize> = warnings.iter().map(|w| w.index).collect();
+    if flagged.contains(&FOREGROUND) {
+        flagged.insert(15);
+    }
+    flagged
+}
+
+/// Current values of the locked colors, for [`restore_locked`].
+fn locked_colors(app: &TuiApp) -> Vec<(usize, AppColor)> {
+    app.locked
+        .iter()
+        .map(|&index| (index, color_at(&app.palette, index)))
+        .collect()
+}
+
+/// Put locked colors back after a rebuild or contrast pass changed them.
+fn restore_locked(app: &mut TuiApp, locked: &[(usize, AppColor)]) {
+    for &(index, color) in locked {
+        set_color_at(app, index, color);
+    }
+}
+
+/// Copy the selected color's hex value.
+fn copy_selected(app: &mut TuiApp) {
+    let Some(index) = app.selected_slot else {
+        app.notify(Severity::Warning, "Select a slot first");
+        return;
+    };
+    let hex = color_at(&app.palette, index).to_hex();
+    copy_text(app, &hex, &hex);
+}
+
+/// Copy the 16 slots as newline-separated hex values.
+fn copy_palette(app: &mut TuiApp) {
+    let text = app
+        .palette
+        .slots
+        .iter()
+        .map(|c| c.to_hex())
+        .collect::<Vec<_>>()
+        .join("\n");
+    copy_text(app, &text, "palette");
+}
+
+fn copy_text(app: &mut TuiApp, text: &str, what: &str) {
+    match app.clipboard.copy(text, &mut stdout()) {
+        Ok(CopyMethod::System) => app.notify(Severity::Success, format!("Copied {what}")),
+        Ok(CopyMethod::Osc52) => {
+            app.notify(Severity::Success, format!("Copied {what} (via terminal)"))
+        }
+        Err(e) => app.notify(Severity::Error, e.to_string()),
+    }
+}
+
+/// Load a theme to compare with: a theme file, else the installed Ghostty
+/// theme of that name.
+fn load_comparison(config: &Config, theme: &str) -> Result<Comparison> {
+    let path = Path::new(theme);
+    let (path, name) = if path.is_file() {
+        let name = path
+            .file_name()
+            .map_or(theme.into(), |n| n.to_string_lossy());
+        (path.to_path_buf(), name.into_owned())
+    } else {
+        let installed = config.install_path(Target::Ghostty, theme)?;
+        if !installed.is_file() {
+            bail!("no installed Ghostty theme named '{theme}'");
+        }
+        (installed, theme.to_string())
+    };
+    Ok(Comparison {
+        name,
+        palette: import::load_palette(&path)?,
+    })
+}
+
+/// Leave compare mode, or ask which theme to compare with.
+fn toggle_compare(app: &mut TuiApp) {
+    if app.comparison.take().is_some() {
+        app.notify(Severity::Info, "Compare off");
+        return;
+    }
+    // The installed theme this one would replace is the usual candidate
+    app.compare_input_buf = app.theme_name.clone();
+    app.input_mode = InputMode::CompareInput;
+}
+
+fn handle_compare_input(app: &mut TuiApp, code: KeyCode) {
+    match code {
+        KeyCode::Enter => {
+            let theme = app.compare_input_buf.trim().to_string();
+            match load_comparison(&app.config, &theme) {
+                Ok(comparison) => {
+                    app.comparison = Some(comparison);
+                    app.notify(Severity::Info, format!("Comparing with {theme}"));
+                }
+                Err(e) => app.notify(Severity::Error, format!("{e:#}")),
+            }
+            app.input_mode = InputMode::Normal;
+        }
+        KeyCode::Esc => app.input_mode = InputMode::Normal,
+        KeyCode::Backspace => {
+            app.compare_input_buf.pop();
+        }
+        KeyCode::Char(c) => app.compare_input_buf.push(c),
+        _ => {}
+    }
+}
+
+/// Lock or unlock the selected color.
+fn toggle_lock(app: &mut TuiApp) {
+    let Some(index) = app.selected_slot else {
+        app.notify(Severity::Warning, "Select a slot first");
+        return;
+    };
+    let verb = if app.locked.remove(&index) {
+        "Unlocked"
+    } else {
+        app.locked.insert(index);
+        "Locked"
+    };
+    app.notify(Severity::Info, format!("{verb} {}", color_name(index)));
+}
+
+/// Write the pinned cursor and selection colors into the palette.
+fn apply_special_pins(app: &mut TuiApp) {
+    for (&index, &color) in &app.special_pins {
+        match index {
+            CURSOR => app.palette.cursor_color = color,
+            CURSOR_TEXT => app.palette.cursor_text = color,
+            SELECTION_BG => app.palette.selection_bg = color,
+            _ => app.palette.selection_fg = color,
+        }
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Pipeline re-run helpers
 // ---------------------------------------------------------------------------
 
+/// Re-run slot assignment and contrast on the extracted colors.
+/// Locked colors are kept.
+fn rebuild_palette(app: &mut TuiApp) {
+    let locked = locked_colors(app);
+    app.palette = assign_slots_with(&app.extracted_colors, app.mode, &app.profile);
+    apply_styles(&mut app.palette, &app.styles, &app.extracted_colors);
+    app.special_pins.clear();
+    // Before contrast enforcement, so accents are checked against a locked
+    // background
+    restore_locked(app, &locked);
+    enforce_contrast(&mut app.palette, app.min_contrast);
+    if app.colorblind_safe {
+        separate_accents(&mut app.palette, app.min_contrast);
+    }
+    app.config.apply_slot_overrides(&mut app.palette);
+    restore_locked(app, &locked);
+}
+
 fn switch_mode(app: &mut TuiApp, mode: ThemeMode) {
     if app.mode == mode {
         return;
     }
     app.mode = mode;
-    app.palette = assign_slots(&app.extracted_colors, app.mode);
-    enforce_contrast(&mut app.palette, DEFAULT_ACCENT_CONTRAST);
+    rebuild_palette(app);
     app.dirty = true;
     app.selected_slot = None;
-    app.status_message = Some(format!("Switched to {mode:?} mode"));
+    app.notify(Severity::Info, format!("Switched to {mode:?} mode"));
 }
 
+/// Re-extract with the next seed in the background.
 fn regenerate(app: &mut TuiApp) {
-    app.seed = app.seed.wrapping_add(1);
-    app.extracted_colors = extract_colors_with_seed(&app.pixels, app.k, app.seed);
-    app.palette = assign_slots(&app.extracted_colors, app.mode);
-    enforce_contrast(&mut app.palette, DEFAULT_ACCENT_CONTRAST);
-    app.dirty = true;
-    app.selected_slot = None;
-    app.status_message = Some("Regenerated palette".to_string());
+    if app.pixels.is_empty() {
+        app.notify(Severity::Warning, "No source image to regenerate from");
+        return;
+    }
+    let (k, seed) = app
+        .extraction
+        .as_ref()
+        .map_or((app.k, app.seed), |e| (e.k, e.seed));
+    start_extraction(app, k, seed.wrapping_add(1));
+}
+
+/// Smallest and largest cluster count `k`/`K` reach, as `-k` allows.
+const CLUSTER_RANGE: (usize, usize) = (MIN_COLORS, MAX_COLORS);
+
+/// Re-extract with `delta` more clusters in the background. A newer request
+/// replaces a running one, whose result is then dropped.
+fn change_cluster_count(app: &mut TuiApp, delta: isize) {
+    if app.pixels.is_empty() {
+        app.notify(Severity::Warning, "No source image to re-extract from");
+        return;
+    }
+    let current = app.extraction.as_ref().map_or(app.k, |e| e.k);
+    let k = current
+        .saturating_add_signed(delta)
+        .clamp(CLUSTER_RANGE.0, CLUSTER_RANGE.1);
+    if k == current {
+        app.notify(
+            Severity::Warning,
+            format!(
+                "Cluster count stays within {}-{}",
+                CLUSTER_RANGE.0, CLUSTER_RANGE.1
+            ),
+        );
+        return;
+    }
+    let seed = app.extraction.as_ref().map_or(app.seed, |e| e.seed);
+    start_extraction(app, k, seed);
+}
+
+/// Run extraction with `k` clusters and `seed` on a background thread,
+/// replacing any running one.
+fn start_extraction(app: &mut TuiApp, k: usize, seed: u64) {
+    let (sender, result) = mpsc::channel();
+    let pixels = app.pixels.clone();
+    let sampling = app.sampling;
+    let algorithm = app.algorithm;
+    let (merge_delta_e, min_weight) = app.refinement;
+    let key = app.cache.clone().map(|key| cache::Key {
+        algorithm,
+        k,
+        seed,
+        ..key
+    });
+    thread::spawn(move || {
+        let colors = cache::cached(key.as_ref(), || {
+            cluster(&pixels, sampling, algorithm, k, seed)
+        });
+        let colors = refine(colors, merge_delta_e, min_weight);
+        // The receiver is gone if a newer extraction replaced this one
+        let _ = sender.send(colors);
+    });
+    app.extraction = Some(Extraction {
+        k,
+        seed,
+        started: Instant::now(),
+        result,
+    });
+}
+
+/// Cluster a sample of `pixels` drawn with `seed`, the colors the CLI
+/// caches under the same key.
+fn cluster(
+    pixels: &[Lab],
+    (sampling, sample_size): (Sampling, usize),
+    algorithm: Algorithm,
+    k: usize,
+    seed: u64,
+) -> Vec<ExtractedColor> {
+    let sample = sample_pixels(pixels, sampling, sample_size, seed);
+    algorithm.quantizer().quantize(&sample, k, seed)
+}
+
+/// Take the finished background extraction, if any, and rebuild the palette
+/// from its colors.
+fn poll_extraction(app: &mut TuiApp) {
+    let Some(extraction) = &app.extraction else {
+        return;
+    };
+    match extraction.result.try_recv() {
+        Ok(colors) => {
+            let reseeded = extraction.seed != app.seed;
+            app.k = extraction.k;
+            app.seed = extraction.seed;
+            app.extracted_colors = colors;
+            app.extraction = None;
+            rebuild_palette(app);
+            app.dirty = true;
+            app.candidate_cursor = 0;
+            if reseeded {
+                app.selected_slot = None;
+            }
+            app.notify(
+                Severity::Info,
+                format!(
+                    "{} {} colors (k={}, seed {})",
+                    if reseeded { "Regenerated" } else { "Extracted" },
+                    app.extracted_colors.len(),
+                    app.k,
+                    app.seed
+                ),
+            );
+        }
+        Err(TryRecvError::Empty) => {}
+        Err(TryRecvError::Disconnected) => {
+            app.extraction = None;
+            app.notify(Severity::Error, "color extraction failed");
+        }
+    }
 }
 
 fn adjust_lightness(app: &mut TuiApp, delta: f32) {
-    if let Some(slot) = app.selected_slot {
-        if slot < 16 {
-            app.palette.slots[slot] = app.palette.slots[slot].adjust_lightness(delta);
-            recompute_after_tweak(app);
+    if let Some(index) = app.selected_slot {
+        let color = color_at(&app.palette, index).adjust_lightness(delta);
+        set_color_at(app, index, color);
+        recompute_after_tweak(app);
+    }
+}
+
+fn adjust_chroma(app: &mut TuiApp, delta: f32) {
+    if let Some(index) = app.selected_slot {
+        let color = color_at(&app.palette, index).adjust_chroma(delta);
+        set_color_at(app, index, color);
+        recompute_after_tweak(app);
+    }
+}
+
+fn adjust_hue(app: &mut TuiApp, degrees: f32) {
+    if let Some(index) = app.selected_slot {
+        let color = color_at(&app.palette, index).adjust_hue(degrees);
+        set_color_at(app, index, color);
+        recompute_after_tweak(app);
+    }
+}
+
+/// Cycle the selected slot through extracted candidate colors.
+fn cycle_candidate(app: &mut TuiApp, forward: bool) {
+    let Some(slot) = app.selected_slot else {
+        return;
+    };
+    if app.extracted_colors.is_empty() {
+        return;
+    }
+
+    let current = color_at(&app.palette, slot);
+    let n = app.extracted_colors.len();
+
+    // Find the extracted color closest to the current slot color (by ΔE² in Lab)
+    let closest_idx = app
+        .extracted_colors
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, ec)| {
+            let lab1 = current.to_lab();
+            let lab2 = ec.color.to_lab();
+            let de_sq =
+                (lab1.l - lab2.l).powi(2) + (lab1.a - lab2.a).powi(2) + (lab1.b - lab2.b).powi(2);
+            (de_sq * 1000.0) as i64
+        })
+        .map(|(i, _)| i)
+        .unwrap_or(0);
+
+    let next_idx = if forward {
+        (closest_idx + 1) % n
+    } else {
+        (closest_idx + n - 1) % n
+    };
+
+    set_color_at(app, slot, app.extracted_colors[next_idx].color);
+    recompute_after_tweak(app);
+}
+
+fn open_candidate_picker(app: &mut TuiApp) {
+    if app.selected_slot.is_none() {
+        app.notify(Severity::Warning, "Select a slot first");
+        return;
+    }
+    if app.extracted_colors.is_empty() {
+        app.notify(Severity::Warning, "No extracted colors");
+        return;
+    }
+    app.candidate_cursor = 0;
+    app.input_mode = InputMode::CandidatePicker;
+}
+
+fn handle_candidate_picker(app: &mut TuiApp, code: KeyCode) {
+    let count = app.extracted_colors.len();
+    match code {
+        KeyCode::Up | KeyCode::Char('k') => {
+            app.candidate_cursor = (app.candidate_cursor + count - 1) % count;
+        }
+        KeyCode::Down | KeyCode::Char('j') => {
+            app.candidate_cursor = (app.candidate_cursor + 1) % count;
+        }
+        KeyCode::Enter => {
+            if let Some(slot) = app.selected_slot {
+                set_color_at(app, slot, app.extracted_colors[app.candidate_cursor].color);
+                recompute_after_tweak(app);
+                app.notify(
+                    Severity::Info,
+                    format!(
+                        "{} set to {}",
+                        selection_label(slot),
+                        color_at(&app.palette, slot).to_hex()
+                    ),
+                );
+            }
+            app.input_mode = InputMode::Normal;
+        }
+        KeyCode::Esc => app.input_mode = InputMode::Normal,
+        _ => {}
+    }
+}
+
+fn open_hex_input(app: &mut TuiApp) {
+    let Some(slot) = app.selected_slot else {
+        app.notify(Severity::Warning, "Select a slot first");
+        return;
+    };
+    app.hex_input_buf = color_at(&app.palette, slot).to_hex();
+    app.input_original = Some(app.palette.clone());
+    app.input_mode = InputMode::HexInput;
+}
+
+fn handle_hex_input(app: &mut TuiApp, code: KeyCode) {
+    match code {
+        KeyCode::Enter => {
+            // On a parse error stay in the prompt; the overlay shows why.
+            if let Ok(color) = AppColor::from_hex(app.hex_input_buf.trim()) {
+                let slot = app.selected_slot.unwrap_or(0);
+                set_slot_exact(app, slot, color);
+                app.input_original = None;
+                app.notify(
+                    Severity::Info,
+                    format!("{} set to {}", selection_label(slot), color.to_hex()),
+                );
+                app.input_mode = InputMode::Normal;
+            }
+        }
+        KeyCode::Esc => {
+            if let Some(original) = app.input_original.take() {
+                app.palette = original;
+            }
+            app.input_mode = InputMode::Normal;
+        }
+        KeyCode::Backspace => {
+            app.hex_input_buf.pop();
+            preview_hex_input(app);
+        }
+        KeyCode::Char('#') if app.hex_input_buf.is_empty() => app.hex_input_buf.push('#'),
+        KeyCode::Char(c)
+            if c.is_ascii_hexdigit() && app.hex_input_buf.trim_start_matches('#').len() < 6 =>
+        {
+            app.hex_input_buf.push(c);
+            preview_hex_input(app);
+        }
+        _ => {}
+    }
+}
+
+/// Show the typed color live while it parses; otherwise show the original.
+fn preview_hex_input(app: &mut TuiApp) {
+    let color = AppColor::from_hex(&app.hex_input_buf).ok();
+    preview_input(app, color);
+}
+
+/// Show `color` in the selected slot, or the palette from before the
+/// prompt opened when there is none.
+fn preview_input(app: &mut TuiApp, color: Option<AppColor>) {
+    let (Some(slot), Some(original)) = (app.selected_slot, app.input_original.clone()) else {
+        return;
+    };
+    app.palette = original;
+    if let Some(color) = color {
+        set_slot_exact(app, slot, color);
+    }
+}
+
+fn open_oklch_input(app: &mut TuiApp) {
+    let Some(slot) = app.selected_slot else {
+        app.notify(Severity::Warning, "Select a slot first");
+        return;
+    };
+    app.oklch_input_buf = color_at(&app.palette, slot).to_oklch_string();
+    app.input_original = Some(app.palette.clone());
+    app.input_mode = InputMode::OklchInput;
+}
+
+fn handle_oklch_input(app: &mut TuiApp, code: KeyCode) {
+    match code {
+        KeyCode::Enter => {
+            // On a parse error stay in the prompt; the overlay shows why.
+            if let Ok(color) = AppColor::from_oklch_str(&app.oklch_input_buf) {
+                let slot = app.selected_slot.unwrap_or(0);
+                set_slot_exact(app, slot, color);
+                app.input_original = None;
+                app.notify(
+                    Severity::Info,
+                    format!("{} set to {}", selection_label(slot), color.to_hex()),
+                );
+                app.input_mode = InputMode::Normal;
+            }
+        }
+        KeyCode::Esc => {
+            if let Some(original) = app.input_original.take() {
+                app.palette = original;
+            }
+            app.input_mode = InputMode::Normal;
         }
-    }
-}
-
-fn adjust_chroma(app: &mut TuiApp, delta: f32) {
-    if let Some(slot) = app.selected_slot {
-        if slot < 16 {
-            app.palette.slots[slot] = app.palette.slots[slot].adjust_chroma(delta);
-            recompute_after_tweak(app);
+        KeyCode::Backspace => {
+            app.oklch_input_buf.pop();
+            let color = AppColor::from_oklch_str(&app.oklch_input_buf).ok();
+            preview_input(app, color);
         }
+        KeyCode::Char(c) if c.is_ascii_digit() || c == '.' || c == ' ' => {
+            app.oklch_input_buf.push(c);
+            let color = AppColor::from_oklch_str(&app.oklch_input_buf).ok();
+            preview_input(app, color);
+        }
+        _ => {}
     }
 }
 
-/// Cycle the selected slot through extracted candidate colors.
-fn cycle_candidate(app: &mut TuiApp, forward: bool) {
-    let slot = match app.selected_slot {
-        Some(s) if s < 16 => s,
-        _ => return,
-    };
-    if app.extracted_colors.is_empty() {
-        return;
-    }
-
-    let current = app.palette.slots[slot];
-    let n = app.extracted_colors.len();
-
-    // Find the extracted color closest to the current slot color (by ΔE² in Lab)
-    let closest_idx = app
-        .extracted_colors
-        .iter()
-        .enumerate()
-        .min_by_key(|(_, ec)| {
-            let lab1 = current.to_lab();
-            let lab2 = ec.color.to_lab();
-            let de_sq =
-                (lab1.l - lab2.l).powi(2) + (lab1.a - lab2.a).powi(2) + (lab1.b - lab2.b).powi(2);
-            (de_sq * 1000.0) as i64
-        })
-        .map(|(i, _)| i)
-        .unwrap_or(0);
+/// Set a slot to an exact color, syncing special colors but skipping contrast
+/// enforcement so the typed value is kept as-is.
+fn set_slot_exact(app: &mut TuiApp, slot: usize, color: AppColor) {
+    set_color_at(app, slot, color);
+    app.palette.background = app.palette.slots[0];
+    app.palette.cursor_text = app.palette.background;
+    apply_special_pins(app);
+    app.dirty = true;
+}
 
-    let next_idx = if forward {
-        (closest_idx + 1) % n
+/// "Slot N" for the 16 slots, the color's name for special colors.
+fn selection_label(index: usize) -> String {
+    if index < 16 {
+        format!("Slot {index}")
     } else {
-        (closest_idx + n - 1) % n
-    };
+        color_name(index).to_string()
+    }
+}
 
-    app.palette.slots[slot] = app.extracted_colors[next_idx].color;
-    recompute_after_tweak(app);
+/// Hue the candidate picker measures distance from: the slot's target hue
+/// for accents, otherwise the slot's current hue.
+fn reference_hue(app: &TuiApp, slot: usize) -> f32 {
+    app.profile
+        .target_hue(slot)
+        .unwrap_or_else(|| f32::from(color_at(&app.palette, slot).to_oklch().hue))
 }
 
 /// Sync special colors from base slots and re-enforce contrast.
 fn recompute_after_tweak(app: &mut TuiApp) {
+    let locked = locked_colors(app);
     app.palette.background = app.palette.slots[0];
     app.palette.cursor_text = app.palette.background;
-    enforce_contrast(&mut app.palette, DEFAULT_ACCENT_CONTRAST);
+    enforce_contrast(&mut app.palette, app.min_contrast);
+    apply_special_pins(app);
+    restore_locked(app, &locked);
     app.dirty = true;
 }
 
@@ -355,11 +1540,13 @@ fn recompute_after_tweak(app: &mut TuiApp) {
 
 /// Get the effective targets for saving.
 fn save_targets(app: &TuiApp) -> Vec<Target> {
+    if let Some(targets) = &app.save_override {
+        return targets.clone();
+    }
     if !app.cli_targets.is_empty() {
         return app.cli_targets.clone();
     }
-    let all_targets = [Target::Ghostty, Target::Zellij, Target::Neovim];
-    all_targets
+    Target::all()
         .iter()
         .zip(app.selected_backends.iter())
         .filter(|(_, &selected)| selected)
@@ -398,7 +1585,7 @@ fn save_path_for_backend(base: &Path, ext: &str) -> PathBuf {
 fn try_save(app: &mut TuiApp) -> Result<()> {
     let raw_path = app.name_input_buf.trim().to_string();
     if raw_path.is_empty() {
-        app.status_message = Some("Path cannot be empty".to_string());
+        app.notify(Severity::Warning, "Path cannot be empty");
         app.input_mode = InputMode::Normal;
         return Ok(());
     }
@@ -408,14 +1595,16 @@ fn try_save(app: &mut TuiApp) -> Result<()> {
 
     // Check for existing files (overwrite confirmation)
     for target in &targets {
-        let backend = get_backend(*target);
+        let backend = app.config.backend(*target)?;
         let path = save_path_for_backend(&base, backend.extension());
         if path.exists() {
+            app.overwrite_path = path.display().to_string();
             app.input_mode = InputMode::ConfirmOverwrite;
             return Ok(());
         }
     }
 
+    app.input_mode = InputMode::Normal;
     do_save(app)
 }
 
@@ -432,7 +1621,13 @@ fn do_save(app: &mut TuiApp) -> Result<()> {
     let mut errors = Vec::new();
 
     for target in &targets {
-        let backend = get_backend(*target);
+        let backend = match app.config.backend(*target) {
+            Ok(backend) => backend,
+            Err(e) => {
+                errors.push(format!("{}: {e:#}", target.name()));
+                continue;
+            }
+        };
         let path = save_path_for_backend(&base, backend.extension());
 
         if let Some(parent) = path.parent() {
@@ -449,18 +1644,86 @@ fn do_save(app: &mut TuiApp) -> Result<()> {
     }
 
     app.theme_name = theme_name;
-    app.dirty = false;
+    if !saved.is_empty() {
+        app.dirty = false;
+    }
 
     if errors.is_empty() {
         let msg = saved.join(", ");
-        app.status_message = Some(format!("Saved {msg}"));
+        app.notify(Severity::Success, format!("Saved {msg}"));
     } else {
         let err_str = errors.join("; ");
         if saved.is_empty() {
-            app.status_message = Some(format!("Error: {err_str}"));
+            app.notify(Severity::Error, err_str);
         } else {
             let ok_str = saved.join(", ");
-            app.status_message = Some(format!("Saved {ok_str}; errors: {err_str}"));
+            app.notify(
+                Severity::Warning,
+                format!("Saved {ok_str}; errors: {err_str}"),
+            );
+        }
+    }
+
+    Ok(())
+}
+
+fn try_install(app: &mut TuiApp) -> Result<()> {
+    // Check for existing files (overwrite confirmation)
+    for target in save_targets(app) {
+        let path = app.config.install_path(target, &app.theme_name)?;
+        if path.exists() {
+            app.overwrite_path = path.display().to_string();
+            app.input_mode = InputMode::ConfirmOverwrite;
+            return Ok(());
+        }
+    }
+
+    app.input_mode = InputMode::Normal;
+    do_install(app)
+}
+
+fn do_install(app: &mut TuiApp) -> Result<()> {
+    let mut installed = Vec::new();
+    let mut errors = Vec::new();
+
+    for target in save_targets(app) {
+        let backend = match app.config.backend(target) {
+            Ok(backend) => backend,
+            Err(e) => {
+                errors.push(format!("{}: {e:#}", target.name()));
+                continue;
+            }
+        };
+        let result = app
+            .config
+            .install_path(target, &app.theme_name)
+            .and_then(|path| {
+                backend.install_at(&app.palette, &app.theme_name, &path)?;
+                Ok(path)
+            });
+        match result {
+            Ok(path) => installed.push(format!("{} -> {}", backend.name(), path.display())),
+            Err(e) => errors.push(format!("{}: {e}", backend.name())),
+        }
+    }
+
+    if !installed.is_empty() {
+        app.dirty = false;
+    }
+
+    if errors.is_empty() {
+        let msg = installed.join(", ");
+        app.notify(Severity::Success, format!("Installed {msg}"));
+    } else {
+        let err_str = errors.join("; ");
+        if installed.is_empty() {
+            app.notify(Severity::Error, err_str);
+        } else {
+            let ok_str = installed.join(", ");
+            app.notify(
+                Severity::Warning,
+                format!("Installed {ok_str}; errors: {err_str}"),
+            );
         }
     }
 
@@ -474,7 +1737,7 @@ fn do_save(app: &mut TuiApp) -> Result<()> {
 fn draw(f: &mut Frame, app: &TuiApp) {
     // Main layout: top section, preview, status bar
     let main_layout = Layout::vertical([
-        Constraint::Min(10),
+        Constraint::Min(16),
         Constraint::Percentage(40),
         Constraint::Length(1),
     ])
@@ -484,14 +1747,42 @@ fn draw(f: &mut Frame, app: &TuiApp) {
     let top_layout = Layout::horizontal([Constraint::Percentage(30), Constraint::Percentage(70)])
         .split(main_layout[0]);
 
-    draw_image_pane(f, app, top_layout[0]);
-    draw_palette_pane(f, app, top_layout[1]);
+    // The live palette is edited; the panes show it through any simulation
+    let shown = match app.simulation {
+        Some(kind) => app.palette.simulate(kind),
+        None => app.palette.clone(),
+    };
 
-    let preview = PreviewWidget::new(&app.palette);
-    f.render_widget(preview, main_layout[1]);
+    draw_image_pane(f, app, top_layout[0]);
+    match &app.comparison {
+        Some(comparison) => {
+            let other = match app.simulation {
+                Some(kind) => comparison.palette.simulate(kind),
+                None => comparison.palette.clone(),
+            };
+            let widget = CompareWidget::new(&shown, &other, &comparison.name, app.selected_slot);
+            f.render_widget(widget, top_layout[1]);
+
+            let halves = Layout::horizontal([Constraint::Percentage(50); 2]).split(main_layout[1]);
+            let preview = PreviewWidget::new(&shown, app.scene).titled("New");
+            f.render_widget(preview, halves[0]);
+            let preview = PreviewWidget::new(&other, app.scene).titled(&comparison.name);
+            f.render_widget(preview, halves[1]);
+        }
+        None => {
+            draw_palette_pane(f, app, &shown, top_layout[1]);
+            let preview = PreviewWidget::new(&shown, app.scene);
+            f.render_widget(preview, main_layout[1]);
+        }
+    }
 
     draw_status_bar(f, app, main_layout[2]);
 
+    // Over the preview, so the palette stays in view while fixing colors
+    if app.show_warnings {
+        draw_warnings_overlay(f, app, main_layout[1]);
+    }
+
     // Overlays
     match app.input_mode {
         InputMode::Normal => {
@@ -503,8 +1794,13 @@ fn draw(f: &mut Frame, app: &TuiApp) {
         InputMode::NameInput => draw_name_input_overlay(f, app),
         InputMode::ConfirmQuit => draw_confirm_quit_overlay(f),
         InputMode::ConfirmOverwrite => {
-            draw_confirm_overwrite_overlay(f, &app.name_input_buf);
+            draw_confirm_overwrite_overlay(f, &app.overwrite_path);
         }
+        InputMode::CandidatePicker => draw_candidate_picker_overlay(f, app),
+        InputMode::HexInput => draw_hex_input_overlay(f, app),
+        InputMode::CompareInput => draw_compare_input_overlay(f, app),
+        InputMode::Export => draw_export_overlay(f, app),
+        InputMode::OklchInput => draw_oklch_input_overlay(f, app),
     }
 }
 
@@ -519,9 +1815,19 @@ fn draw_image_pane(f: &mut Frame, app: &TuiApp, area: Rect) {
         Line::from(""),
         Line::from(format!("  Mode: {:?}", app.mode)),
         Line::from(format!("  Theme: {}", app.theme_name)),
-        Line::from(format!("  Colors: {}", app.extracted_colors.len())),
-        Line::from(""),
+        Line::from(format!(
+            "  Colors: {} (k={})",
+            app.extracted_colors.len(),
+            app.k
+        )),
     ];
+    if let Some(kind) = app.simulation {
+        lines.push(Line::from(Span::styled(
+            format!("  Simulating: {}", kind.name()),
+            Style::default().fg(Color::Yellow),
+        )));
+    }
+    lines.push(Line::from(""));
 
     // Show extracted color swatches
     let mut swatch_spans = vec![Span::raw("  ")];
@@ -543,30 +1849,57 @@ fn draw_image_pane(f: &mut Frame, app: &TuiApp, area: Rect) {
     f.render_widget(Paragraph::new(lines), inner);
 }
 
-fn draw_palette_pane(f: &mut Frame, app: &TuiApp, area: Rect) {
-    let widget = PaletteWidget::new(&app.palette, app.selected_slot);
+fn draw_palette_pane(f: &mut Frame, app: &TuiApp, palette: &AnsiPalette, area: Rect) {
+    // Checked on the real colors, not the simulated ones on screen
+    let flagged = flagged_colors(&contrast_warnings(&app.palette, app.min_contrast));
+    let widget = PaletteWidget::new(palette, app.selected_slot, &app.locked).badges(&flagged);
     f.render_widget(widget, area);
 }
 
 fn draw_status_bar(f: &mut Frame, app: &TuiApp, area: Rect) {
-    let text = if let Some(msg) = &app.status_message {
-        format!(" {msg}")
-    } else if app.selected_slot.is_some() {
-        " +/-: Lightness | s/S: Chroma | Left/Right: Cycle | Enter: Save | q: Quit".to_string()
+    let bar = Style::default().bg(Color::Rgb(20, 20, 20));
+    // Unsaved changes
+    let modified = if app.dirty {
+        Span::styled(" \u{25cf}", bar.fg(Color::Yellow))
+    } else {
+        Span::raw("")
+    };
+    if let Some(toast) = &app.toast {
+        let text = match toast.severity {
+            Severity::Error => format!(" Error: {}", toast.text),
+            _ => format!(" {}", toast.text),
+        };
+        let line = Line::from(vec![modified, Span::styled(text, toast.severity.style())]);
+        f.render_widget(Paragraph::new(line).style(bar), area);
+        return;
+    }
+    let text = if let Some(extraction) = &app.extraction {
+        let frame = extraction.started.elapsed().as_millis() / 100;
+        format!(
+            " {} Extracting colors with k={} (seed {})...",
+            SPINNER[frame as usize % SPINNER.len()],
+            extraction.k,
+            extraction.seed
+        )
+    } else if let Some(slot) = app.selected_slot {
+        let color = color_at(&app.palette, slot);
+        let (name, delta_e) = color.nearest_named();
+        format!(
+            " {} {} \u{2248} {name} (\u{394}E {delta_e:.1}) | +/-: Lightness | s/S: Chroma | h/H: Hue | z: Step ({}) | Left/Right: Cycle | c: Candidates | #: Hex | o: Oklch | L: Lock | y: Copy | Enter: Save | w: Install | q: Quit",
+            color_name(slot),
+            color.to_hex(),
+            app.step.name()
+        )
     } else {
-        " d/l: Mode | r: Regen | Tab: Cycle | 1-6: Select | Enter: Save | ?: Help | q: Quit"
+        " d/l: Mode | r: Regen | k/K: Clusters | Tab: Cycle | 0-7/b/f/u/x: Select | p: Scene | W: Warnings | e: Export | Enter: Save | w: Install | ?: Help | q: Quit"
             .to_string()
     };
-    let bar = Paragraph::new(text).style(
-        Style::default()
-            .fg(Color::DarkGray)
-            .bg(Color::Rgb(20, 20, 20)),
-    );
-    f.render_widget(bar, area);
+    let line = Line::from(vec![modified, Span::raw(text)]);
+    f.render_widget(Paragraph::new(line).style(bar.fg(Color::DarkGray)), area);
 }
 
 fn draw_help_overlay(f: &mut Frame) {
-    let area = centered_rect(60, 70, f.area());
+    let area = centered_rect(60, 90, f.area());
     let lines = vec![
         Line::from(""),
         Line::from("  Keybindings:"),
@@ -575,16 +1908,35 @@ fn draw_help_overlay(f: &mut Frame) {
         Line::from("  ?             Toggle this help"),
         Line::from("  Tab           Next slot"),
         Line::from("  Shift+Tab     Previous slot"),
-        Line::from("  1-6           Select accent slot"),
+        Line::from("  0-7           Select a slot; again for its bright variant"),
+        Line::from("  b / f         Select background / foreground"),
+        Line::from("  u / x         Select cursor / selection; again for its text"),
         Line::from("  Esc           Deselect / close"),
         Line::from("  d / l         Switch to dark / light mode"),
         Line::from("  r             Regenerate palette (new seed)"),
+        Line::from("  k / K         Re-extract with fewer / more clusters"),
+        Line::from("  a             Toggle live preview in this terminal"),
+        Line::from("  v             Cycle colorblind simulation (display only)"),
+        Line::from("  p             Next preview scene (shell, code, git, htop, man)"),
+        Line::from("  C             Compare with an installed theme side by side"),
+        Line::from("  W             Contrast warnings, updated as you edit"),
+        Line::from("  Y             Copy the 16 colors, one hex value per line"),
+        Line::from("  e             Export to several backends (install or write)"),
         Line::from("  Enter         Save theme"),
+        Line::from("  w             Install theme to config directories"),
         Line::from(""),
         Line::from("  When a slot is selected:"),
         Line::from("  + / -         Adjust lightness"),
         Line::from("  s / S         Adjust chroma"),
+        Line::from("  h / H         Rotate hue"),
         Line::from("  Left / Right  Cycle through extracted colors"),
+        Line::from("  c             Pick from extracted colors"),
+        Line::from("  #             Type an exact hex value"),
+        Line::from("  o             Type exact Oklch lightness, chroma and hue"),
+        Line::from("  z             Step size: normal, coarse (x4), fine (/4)"),
+        Line::from("  Alt+key       One fine step with + - s S h H"),
+        Line::from("  L             Lock: keep through regenerate, k/K and mode switches"),
+        Line::from("  y             Copy the hex value"),
         Line::from(""),
         Line::from("  Press ? or Esc to close"),
     ];
@@ -595,6 +1947,41 @@ fn draw_help_overlay(f: &mut Frame) {
     f.render_widget(popup, area);
 }
 
+fn draw_warnings_overlay(f: &mut Frame, app: &TuiApp, area: Rect) {
+    let warnings = contrast_warnings(&app.palette, app.min_contrast);
+    let mut lines = vec![Line::from("")];
+    if warnings.is_empty() {
+        lines.push(Line::from(Span::styled(
+            format!(
+                "  \u{2713} Every color passes (accents {:.1}:1, accents \u{394}E {SIMILAR_DELTA_E:.0} apart)",
+                app.min_contrast
+            ),
+            Style::default().fg(Color::Green),
+        )));
+    }
+    for warning in &warnings {
+        lines.push(Line::from(vec![
+            Span::styled("  ! ", Style::default().fg(Color::Red)),
+            Span::styled(
+                format!("{:<14}", color_name(warning.index)),
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(warning.text.clone()),
+        ]));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from("  Press W or Esc to close"));
+    let title = match warnings.len() {
+        0 => " Contrast ".to_string(),
+        n => format!(" Contrast: {n} warning(s) "),
+    };
+    let popup = Paragraph::new(lines)
+        .block(Block::bordered().title(title))
+        .style(Style::default().bg(Color::Black).fg(Color::White));
+    f.render_widget(Clear, area);
+    f.render_widget(popup, area);
+}
+
 fn draw_name_input_overlay(f: &mut Frame, app: &TuiApp) {
     let area = centered_rect(50, 25, f.area());
     let lines = vec![
@@ -656,20 +2043,22 @@ fn draw_confirm_overwrite_overlay(f: &mut Frame, path: &str) {
 }
 
 fn draw_backend_select_overlay(f: &mut Frame, app: &TuiApp) {
-    let area = centered_rect(50, 30, f.area());
-    let labels = ["Ghostty", "Zellij", "Neovim"];
-    let keys = ['G', 'Z', 'N'];
+    let area = centered_rect(50, 40, f.area());
     let mut lines = vec![
         Line::from(""),
-        Line::from("  Select backends to save:"),
+        Line::from(match app.save_action {
+            SaveAction::Write => "  Select backends to save:",
+            SaveAction::Install => "  Select backends to install:",
+        }),
         Line::from(""),
     ];
-    for (i, (label, key)) in labels.iter().zip(keys.iter()).enumerate() {
+    for (i, target) in Target::all().iter().enumerate() {
         let marker = if app.selected_backends[i] {
             "[x]"
         } else {
             "[ ]"
         };
+        let cursor = if i == app.backend_cursor { ">" } else { " " };
         let style = if app.selected_backends[i] {
             Style::default()
                 .fg(Color::Green)
@@ -678,12 +2067,14 @@ fn draw_backend_select_overlay(f: &mut Frame, app: &TuiApp) {
             Style::default().fg(Color::DarkGray)
         };
         lines.push(Line::from(vec![
-            Span::raw("  "),
-            Span::styled(format!("{marker} [{key}] {label}"), style),
+            Span::raw(format!(" {cursor}")),
+            Span::styled(format!("{marker} {}", target.name()), style),
         ]));
     }
     lines.push(Line::from(""));
-    lines.push(Line::from("  a: Toggle all | Enter: Confirm | Esc: Cancel"));
+    lines.push(Line::from(
+        "  j/k: Move | Space: Toggle | a: All | Enter: Confirm | Esc: Cancel",
+    ));
     let popup = Paragraph::new(lines)
         .block(Block::bordered().title(" Save Target "))
         .style(Style::default().bg(Color::Black).fg(Color::White));
@@ -691,6 +2082,255 @@ fn draw_backend_select_overlay(f: &mut Frame, app: &TuiApp) {
     f.render_widget(popup, area);
 }
 
+fn draw_export_overlay(f: &mut Frame, app: &TuiApp) {
+    let area = centered_rect(80, 80, f.area());
+    let block = Block::bordered()
+        .title(" Export ")
+        .style(Style::default().bg(Color::Black).fg(Color::White));
+    let inner = block.inner(area);
+    f.render_widget(Clear, area);
+    f.render_widget(block, area);
+    let rows = Layout::vertical([Constraint::Min(3), Constraint::Length(2)]).split(inner);
+    let columns = Layout::horizontal([Constraint::Length(30), Constraint::Min(20)]).split(rows[0]);
+
+    // Backend checkboxes, scrolled to keep the cursor in view
+    let height = columns[0].height.saturating_sub(1).max(1) as usize;
+    let offset = app.export_cursor.saturating_sub(height - 1);
+    let mut lines = vec![Line::from("")];
+    for (i, target) in Target::all().iter().enumerate().skip(offset).take(height) {
+        let selected = app.export_selection[i];
+        let marker = if selected { "[x]" } else { "[ ]" };
+        let cursor = if i == app.export_cursor { ">" } else { " " };
+        let style = if selected {
+            Style::default()
+                .fg(Color::Green)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(Color::DarkGray)
+        };
+        lines.push(Line::from(vec![
+            Span::raw(format!(" {cursor}")),
+            Span::styled(format!("{marker} {}", target.name()), style),
+        ]));
+    }
+    f.render_widget(Paragraph::new(lines), columns[0]);
+
+    // Action and where each checked backend would go
+    let choice = |action: SaveAction, label: &'static str| {
+        if app.export_action == action {
+            Span::styled(
+                format!("(\u{2022}) {label}"),
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            )
+        } else {
+            Span::styled(format!("( ) {label}"), Style::default().fg(Color::DarkGray))
+        }
+    };
+    let mut lines = vec![
+        Line::from(""),
+        Line::from(vec![
+            Span::raw("Action: "),
+            choice(SaveAction::Install, "Install"),
+            Span::raw("  "),
+            choice(SaveAction::Write, "Write to file"),
+        ]),
+        Line::from(""),
+        Line::from(match app.export_action {
+            SaveAction::Install => "Destinations:",
+            SaveAction::Write => "Destinations (path asked next):",
+        }),
+    ];
+    let targets = export_targets(app);
+    if targets.is_empty() {
+        lines.push(Line::styled(
+            "  nothing selected",
+            Style::default().fg(Color::DarkGray),
+        ));
+    }
+    for target in targets {
+        let name = format!("  {:<18}", target.name());
+        lines.push(match export_destination(app, target) {
+            Ok(path) => {
+                let mut spans = vec![Span::raw(name), Span::raw(path.display().to_string())];
+                if path.exists() {
+                    spans.push(Span::styled(
+                        "  (exists, asks first)",
+                        Style::default().fg(Color::Yellow),
+                    ));
+                }
+                Line::from(spans)
+            }
+            Err(e) => Line::from(vec![
+                Span::raw(name),
+                Span::styled(format!("{e:#}"), Style::default().fg(Color::Red)),
+            ]),
+        });
+    }
+    f.render_widget(Paragraph::new(lines), columns[1]);
+
+    f.render_widget(
+        Paragraph::new(
+            " j/k: Move | Space: Toggle | a: All | Tab: Install/Write | Enter: Export | Esc: Cancel",
+        ),
+        rows[1],
+    );
+}
+
+fn draw_hex_input_overlay(f: &mut Frame, app: &TuiApp) {
+    let area = centered_rect(40, 25, f.area());
+    let slot = app.selected_slot.unwrap_or(0);
+    let hint = match AppColor::from_hex(&app.hex_input_buf) {
+        Ok(_) => Line::from("  Enter: Apply | Esc: Cancel"),
+        Err(e) => Line::styled(format!("  {e}"), Style::default().fg(Color::Red)),
+    };
+    let lines = vec![
+        Line::from(""),
+        Line::from(format!(
+            "  Hex color for {}:",
+            selection_label(slot).to_lowercase()
+        )),
+        Line::from(""),
+        Line::from(vec![
+            Span::raw("  > "),
+            Span::styled(
+                app.hex_input_buf.clone(),
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::styled("\u{2588}", Style::default().fg(Color::Yellow)),
+        ]),
+        Line::from(""),
+        hint,
+    ];
+    let popup = Paragraph::new(lines)
+        .block(Block::bordered().title(" Set Hex "))
+        .style(Style::default().bg(Color::Black).fg(Color::White));
+    f.render_widget(Clear, area);
+    f.render_widget(popup, area);
+}
+
+fn draw_oklch_input_overlay(f: &mut Frame, app: &TuiApp) {
+    let area = centered_rect(40, 25, f.area());
+    let slot = app.selected_slot.unwrap_or(0);
+    let hint = match AppColor::from_oklch_str(&app.oklch_input_buf) {
+        Ok(color) => Line::from(format!("  {} | Enter: Apply | Esc: Cancel", color.to_hex())),
+        Err(e) => Line::styled(format!("  {e}"), Style::default().fg(Color::Red)),
+    };
+    let lines = vec![
+        Line::from(""),
+        Line::from(format!(
+            "  Oklch lightness, chroma and hue for {}:",
+            selection_label(slot).to_lowercase()
+        )),
+        Line::from(""),
+        Line::from(vec![
+            Span::raw("  > "),
+            Span::styled(
+                app.oklch_input_buf.clone(),
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::styled("\u{2588}", Style::default().fg(Color::Yellow)),
+        ]),
+        Line::from(""),
+        hint,
+    ];
+    let popup = Paragraph::new(lines)
+        .block(Block::bordered().title(" Set Oklch "))
+        .style(Style::default().bg(Color::Black).fg(Color::White));
+    f.render_widget(Clear, area);
+    f.render_widget(popup, area);
+}
+
+fn draw_compare_input_overlay(f: &mut Frame, app: &TuiApp) {
+    let area = centered_rect(50, 25, f.area());
+    let lines = vec![
+        Line::from(""),
+        Line::from("  Compare with installed Ghostty theme or file:"),
+        Line::from(""),
+        Line::from(vec![
+            Span::raw("  > "),
+            Span::styled(
+                app.compare_input_buf.clone(),
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::styled("\u{2588}", Style::default().fg(Color::Yellow)),
+        ]),
+        Line::from(""),
+        Line::from("  Enter: Compare | Esc: Cancel"),
+    ];
+    let popup = Paragraph::new(lines)
+        .block(Block::bordered().title(" Compare "))
+        .style(Style::default().bg(Color::Black).fg(Color::White));
+    f.render_widget(Clear, area);
+    f.render_widget(popup, area);
+}
+
+fn draw_candidate_picker_overlay(f: &mut Frame, app: &TuiApp) {
+    let Some(slot) = app.selected_slot else {
+        return;
+    };
+    let area = centered_rect(50, 60, f.area());
+    let ref_hue = reference_hue(app, slot);
+
+    let mut lines = vec![
+        Line::from(""),
+        Line::from(format!(
+            "  Assign to {} (Δhue from {ref_hue:.0}°):",
+            selection_label(slot).to_lowercase()
+        )),
+        Line::from(""),
+    ];
+    // Keep the cursor visible when there are more candidates than rows.
+    let rows = (area.height as usize).saturating_sub(6).max(1);
+    let start = app.candidate_cursor.saturating_sub(rows - 1);
+    for (i, ec) in app
+        .extracted_colors
+        .iter()
+        .enumerate()
+        .skip(start)
+        .take(rows)
+    {
+        let oklch = ec.color.to_oklch();
+        let dist = hue_distance(f32::from(oklch.hue), ref_hue);
+        let cursor = if i == app.candidate_cursor { ">" } else { " " };
+        let swatch = Color::Rgb(ec.color.r, ec.color.g, ec.color.b);
+        let style = if i == app.candidate_cursor {
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default()
+        };
+        lines.push(Line::from(vec![
+            Span::raw(format!(" {cursor} ")),
+            Span::styled("\u{2588}\u{2588}", Style::default().fg(swatch)),
+            Span::styled(
+                format!(
+                    " {}  {:>5.1}%  Δhue {:>5.1}°",
+                    ec.color.to_hex(),
+                    ec.weight * 100.0,
+                    dist
+                ),
+                style,
+            ),
+        ]));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from("  j/k: Move | Enter: Assign | Esc: Cancel"));
+    let popup = Paragraph::new(lines)
+        .block(Block::bordered().title(" Candidates "))
+        .style(Style::default().bg(Color::Black).fg(Color::White));
+    f.render_widget(Clear, area);
+    f.render_widget(popup, area);
+}
+
 fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
     let v = Layout::vertical([
         Constraint::Percentage((100 - percent_y) / 2),
@@ -705,3 +2345,108 @@ fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
     ])
     .split(v[1])[1]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 64x64 gradient over hue and lightness.
+    fn gradient() -> Vec<Lab> {
+        (0..4096)
+            .map(|i| {
+                let angle = (i % 64) as f32 / 64.0 * std::f32::consts::TAU;
+                Lab::new(
+                    20.0 + (i / 64) as f32,
+                    60.0 * angle.cos(),
+                    60.0 * angle.sin(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn reseeded_extraction_samples_with_its_seed() {
+        let pixels = gradient();
+        let sampling = (Sampling::Random, 256);
+        let reseeded = cluster(&pixels, sampling, Algorithm::Kmeans, 8, 43);
+        // What `nuri img --sampling random --sample-size 256 -k 8 --seed 43`
+        // clusters and caches under the same key
+        let sample = sample_pixels(&pixels, Sampling::Random, 256, 43);
+        let cli = Algorithm::Kmeans.quantizer().quantize(&sample, 8, 43);
+        let hex = |colors: &[ExtractedColor]| {
+            colors
+                .iter()
+                .map(|c| (c.color.to_hex(), c.weight.to_bits()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(hex(&reseeded), hex(&cli));
+        // Not the sample the first extraction drew with seed 42
+        let stale = sample_pixels(&pixels, Sampling::Random, 256, 42);
+        let stale = Algorithm::Kmeans.quantizer().quantize(&stale, 8, 43);
+        assert_ne!(hex(&reseeded), hex(&stale));
+    }
+
+    fn locked_app() -> TuiApp {
+        let colors: Vec<ExtractedColor> = gradient()
+            .chunks(512)
+            .map(|chunk| ExtractedColor {
+                color: AppColor::from_lab(chunk[0]),
+                weight: 1.0 / 8.0,
+            })
+            .collect();
+        let palette = assign_slots_with(&colors, ThemeMode::Dark, &Profile::default());
+        let mut app = TuiApp::new(
+            palette,
+            colors,
+            PathBuf::from("wallpaper.png"),
+            ThemeMode::Dark,
+            "test".to_string(),
+            Vec::new(),
+            8,
+        );
+        app.set_locked(&[
+            "1".to_string(),
+            "bright-blue".to_string(),
+            "cursor".to_string(),
+        ]);
+        app
+    }
+
+    #[test]
+    fn lock_names_select_slots_and_special_colors() {
+        assert_eq!(selection_index("1"), Some(1));
+        assert_eq!(selection_index("bright-blue"), Some(12));
+        assert_eq!(selection_index("cursor"), Some(CURSOR));
+        assert_eq!(selection_index("16"), None);
+        assert_eq!(selection_index("orange"), None);
+        let app = locked_app();
+        assert_eq!(app.locked, HashSet::from([1, 12, CURSOR]));
+    }
+
+    #[test]
+    fn locked_colors_survive_rebuilds_styles_and_mode_switches() {
+        let mut app = locked_app();
+        let red = AppColor::new(0xd0, 0x40, 0x40);
+        let blue = AppColor::new(0x60, 0x80, 0xf0);
+        let cursor = AppColor::new(0xff, 0xcc, 0x00);
+        set_color_at(&mut app, 1, red);
+        set_color_at(&mut app, 12, blue);
+        set_color_at(&mut app, CURSOR, cursor);
+        let unlocked = app.palette.slots[2];
+        let check = |app: &TuiApp| {
+            assert_eq!(app.palette.slots[1], red);
+            assert_eq!(app.palette.slots[12], blue);
+            assert_eq!(app.palette.cursor_color, cursor);
+        };
+
+        rebuild_palette(&mut app);
+        check(&app);
+        app.styles = vec![PaletteStyle::Monochrome];
+        rebuild_palette(&mut app);
+        check(&app);
+        switch_mode(&mut app, ThemeMode::Light);
+        check(&app);
+        // Unlocked slots did follow along
+        assert_ne!(app.palette.slots[2], unlocked);
+    }
+}
@@ -0,0 +1,148 @@
+//! The TUI layout drawn once as ANSI text (--preview-full), for terminals
+//! without an alternate screen, pipes, CI logs and screenshots.
+
+use std::fmt::Write;
+
+use anyhow::Result;
+use ratatui::backend::TestBackend;
+use ratatui::buffer::Buffer;
+use ratatui::style::{Color, Modifier};
+use ratatui::text::Span;
+use ratatui::Terminal;
+
+use super::{draw, TuiApp};
+use crate::term::{self, ColorDepth};
+
+/// Size drawn when stdout is not a terminal.
+pub const DEFAULT_SIZE: (u16, u16) = (120, 40);
+
+/// Draw `app` the way the TUI would on a `width` x `height` terminal and
+/// return the screen as lines of text, with ANSI colors at the terminal's
+/// [`term::color_depth`] when `colored`.
+pub fn render(app: &TuiApp, width: u16, height: u16, colored: bool) -> Result<String> {
+    let mut terminal = Terminal::new(TestBackend::new(width, height))?;
+    terminal.draw(|f| draw(f, app))?;
+    Ok(to_ansi(
+        terminal.backend().buffer(),
+        colored.then(term::color_depth),
+    ))
+}
+
+/// Lines of `buffer` with an SGR sequence at `depth` wherever the style
+/// changes and a reset at the end of each line, or just the text without a
+/// depth.
+fn to_ansi(buffer: &Buffer, depth: Option<ColorDepth>) -> String {
+    let area = buffer.area;
+    let mut out = String::new();
+    for y in area.top()..area.bottom() {
+        let mut current = (Color::Reset, Color::Reset, Modifier::empty());
+        // Columns still covered by the last wide character
+        let mut covered = 0;
+        for x in area.left()..area.right() {
+            let cell = &buffer[(x, y)];
+            if covered > 0 {
+                covered -= 1;
+                continue;
+            }
+            if cell.skip {
+                continue;
+            }
+            let style = (cell.fg, cell.bg, cell.modifier);
+            if let Some(depth) = depth.filter(|_| style != current) {
+                out.push_str(&sgr(style, depth));
+                current = style;
+            }
+            out.push_str(cell.symbol());
+            covered = Span::raw(cell.symbol()).width().saturating_sub(1);
+        }
+        out.push_str(if depth.is_some() { "\x1b[0m\n" } else { "\n" });
+    }
+    out
+}
+
+/// SGR sequence that resets and then sets a cell's colors and modifiers.
+fn sgr((fg, bg, modifier): (Color, Color, Modifier), depth: ColorDepth) -> String {
+    let mut codes = String::from("0");
+    for (flag, code) in [
+        (Modifier::BOLD, 1),
+        (Modifier::DIM, 2),
+        (Modifier::ITALIC, 3),
+        (Modifier::UNDERLINED, 4),
+        (Modifier::REVERSED, 7),
+        (Modifier::CROSSED_OUT, 9),
+    ] {
+        if modifier.contains(flag) {
+            let _ = write!(codes, ";{code}");
+        }
+    }
+    if let Some(fg) = color_code(fg, 30, depth) {
+        let _ = write!(codes, ";{fg}");
+    }
+    if let Some(bg) = color_code(bg, 40, depth) {
+        let _ = write!(codes, ";{bg}");
+    }
+    format!("\x1b[{codes}m")
+}
+
+/// SGR parameters for `color`, with `base` 30 for the foreground and 40 for
+/// the background and RGB colors brought down to `depth`; `None` for the
+/// terminal default.
+fn color_code(color: Color, base: u8, depth: ColorDepth) -> Option<String> {
+    let (offset, bright) = match color {
+        Color::Reset => return None,
+        Color::Rgb(r, g, b) => return Some(depth.sgr(crate::color::Color::new(r, g, b), base)),
+        Color::Indexed(i) => return Some(format!("{};5;{i}", base + 8)),
+        Color::Black => (0, false),
+        Color::Red => (1, false),
+        Color::Green => (2, false),
+        Color::Yellow => (3, false),
+        Color::Blue => (4, false),
+        Color::Magenta => (5, false),
+        Color::Cyan => (6, false),
+        Color::Gray => (7, false),
+        Color::DarkGray => (0, true),
+        Color::LightRed => (1, true),
+        Color::LightGreen => (2, true),
+        Color::LightYellow => (3, true),
+        Color::LightBlue => (4, true),
+        Color::LightMagenta => (5, true),
+        Color::LightCyan => (6, true),
+        Color::White => (7, true),
+    };
+    let base = if bright { base + 60 } else { base };
+    Some((base + offset).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::layout::Rect;
+    use ratatui::style::{Style, Stylize};
+
+    #[test]
+    fn styles_change_only_where_the_cells_do() {
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 4, 1));
+        buffer.set_string(0, 0, "ab", Style::default().fg(Color::Rgb(255, 0, 0)));
+        buffer.set_string(2, 0, "c", Style::default().bg(Color::DarkGray).bold());
+        assert_eq!(
+            to_ansi(&buffer, Some(ColorDepth::TrueColor)),
+            "\x1b[0;38;2;255;0;0mab\x1b[0;1;100mc\x1b[0m \x1b[0m\n"
+        );
+        // Without truecolor, RGB falls back to the nearest palette color
+        assert_eq!(
+            to_ansi(&buffer, Some(ColorDepth::Ansi256)),
+            "\x1b[0;38;5;196mab\x1b[0;1;100mc\x1b[0m \x1b[0m\n"
+        );
+        assert_eq!(to_ansi(&buffer, None), "abc \n");
+    }
+
+    #[test]
+    fn wide_characters_keep_the_line_width() {
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 3, 1));
+        buffer.set_string(0, 0, "\u{4e16}x", Style::default());
+        assert_eq!(
+            to_ansi(&buffer, Some(ColorDepth::TrueColor)),
+            "\u{4e16}x\x1b[0m\n"
+        );
+    }
+}
@@ -1,25 +1,54 @@
+use std::collections::HashSet;
+
 use ratatui::prelude::*;
 use ratatui::widgets::{Block, Paragraph, Widget};
 
+use super::{color_at, color_name, contrast_partner, BACKGROUND, SELECTABLE};
 use crate::color::Color as AppColor;
 use crate::pipeline::assign::AnsiPalette;
+use crate::preview::UNCHANGED_DELTA_E;
 
 const SLOT_NAMES: [&str; 8] = ["Blk", "Red", "Grn", "Yel", "Blu", "Mag", "Cyn", "Wht"];
 
+/// Short labels of the special colors (selection indices 16-21).
+const SPECIAL_LABELS: [&str; 6] = ["Bg", "Fg", "Cur", "CurTx", "SelBg", "SelFg"];
+
+/// Keys selecting the special colors, shown under their swatches.
+const SPECIAL_KEYS: [&str; 6] = ["b", "f", "u", "uu", "x", "xx"];
+
 // ---------------------------------------------------------------------------
 // PaletteWidget
 // ---------------------------------------------------------------------------
 
 /// A widget that renders the 16-color ANSI palette as an 8x2 grid of colored
-/// swatches with labels. Highlights the currently selected slot.
+/// swatches with labels, followed by the special colors. Highlights the
+/// currently selected color and brackets the labels of locked ones.
 pub struct PaletteWidget<'a> {
     palette: &'a AnsiPalette,
     selected: Option<usize>,
+    locked: &'a HashSet<usize>,
+    /// Colors with contrast warnings; `None` draws no badges.
+    flagged: Option<&'a HashSet<usize>>,
 }
 
 impl<'a> PaletteWidget<'a> {
-    pub fn new(palette: &'a AnsiPalette, selected: Option<usize>) -> Self {
-        Self { palette, selected }
+    pub fn new(
+        palette: &'a AnsiPalette,
+        selected: Option<usize>,
+        locked: &'a HashSet<usize>,
+    ) -> Self {
+        Self {
+            palette,
+            selected,
+            locked,
+            flagged: None,
+        }
+    }
+
+    /// Badge every index with `!` if it is in `flagged`, else a check mark.
+    pub fn badges(mut self, flagged: &'a HashSet<usize>) -> Self {
+        self.flagged = Some(flagged);
+        self
     }
 }
 
@@ -40,21 +69,31 @@ fn slot_name(index: usize) -> &'static str {
     SLOT_NAMES[index % 8]
 }
 
-/// Build a row of colored swatches. Each swatch is 6 chars wide with the slot
-/// name centered on the colored background. Selected slot gets bold + underline.
+/// Short label of any selectable color.
+fn label(index: usize) -> &'static str {
+    if index < 16 {
+        slot_name(index)
+    } else {
+        SPECIAL_LABELS[index - 16]
+    }
+}
+
+/// Build a row of colored swatches for the selectable colors `indices`. Each
+/// swatch is 6 chars wide with the label centered on the colored background.
+/// Selected color gets bold + underline.
 fn build_swatch_row(
-    slots: &[AppColor; 16],
-    start: usize,
+    palette: &AnsiPalette,
+    indices: std::ops::Range<usize>,
     selected: Option<usize>,
 ) -> Line<'static> {
     let mut spans = vec![Span::raw("  ")];
-    for (offset, c) in slots[start..start + 8].iter().enumerate() {
-        let i = start + offset;
+    for i in indices {
+        let c = &color_at(palette, i);
         let bg = to_color(c);
         let fg = contrast_fg(c);
         let is_selected = selected == Some(i);
 
-        let label = format!("{:^6}", slot_name(i));
+        let label = format!("{:^6}", label(i));
         let mut style = Style::default().bg(bg).fg(fg);
         if is_selected {
             style = style.add_modifier(Modifier::BOLD | Modifier::UNDERLINED);
@@ -65,12 +104,34 @@ fn build_swatch_row(
     Line::from(spans)
 }
 
-/// Build a row of slot index labels below the swatches.
-fn build_index_row(start: usize, selected: Option<usize>) -> Line<'static> {
+/// Build a row of slot indices (or, for special colors, their keys) below
+/// the swatches. Locked colors are shown as `[4]`; with `flagged`, each
+/// index is followed by a `!` or check mark badge.
+fn build_index_row(
+    indices: std::ops::Range<usize>,
+    selected: Option<usize>,
+    locked: &HashSet<usize>,
+    flagged: Option<&HashSet<usize>>,
+) -> Line<'static> {
     let mut spans = vec![Span::raw("  ")];
-    for i in start..start + 8 {
+    for i in indices {
         let is_selected = selected == Some(i);
-        let label = format!("{:^6}", i);
+        let key = match i {
+            0..=15 => i.to_string(),
+            _ => SPECIAL_KEYS[i - 16].to_string(),
+        };
+        let label = if locked.contains(&i) {
+            format!("[{key}]")
+        } else {
+            key
+        };
+        let badge = flagged.map(|flagged| {
+            if flagged.contains(&i) {
+                ("!", Color::Red)
+            } else {
+                ("\u{2713}", Color::Green)
+            }
+        });
         let style = if is_selected {
             Style::default()
                 .fg(Color::Yellow)
@@ -78,8 +139,16 @@ fn build_index_row(start: usize, selected: Option<usize>) -> Line<'static> {
         } else {
             Style::default().fg(Color::DarkGray)
         };
+        // Centered in the 6-column swatch above, like `{:^6}`
+        let width = label.len() + if badge.is_some() { 2 } else { 0 };
+        let pad = 6usize.saturating_sub(width);
+        spans.push(Span::raw(" ".repeat(pad / 2)));
         spans.push(Span::styled(label, style));
-        spans.push(Span::raw(" "));
+        if let Some((mark, color)) = badge {
+            spans.push(Span::raw(" "));
+            spans.push(Span::styled(mark, Style::default().fg(color)));
+        }
+        spans.push(Span::raw(" ".repeat(pad - pad / 2 + 1)));
     }
     Line::from(spans)
 }
@@ -93,38 +162,154 @@ impl Widget for PaletteWidget<'_> {
         let mut lines = vec![
             // Normal colors (slots 0-7)
             Line::from("  Normal"),
-            build_swatch_row(&self.palette.slots, 0, self.selected),
-            build_index_row(0, self.selected),
+            build_swatch_row(self.palette, 0..8, self.selected),
+            build_index_row(0..8, self.selected, self.locked, self.flagged),
             Line::from(""),
             // Bright colors (slots 8-15)
             Line::from("  Bright"),
-            build_swatch_row(&self.palette.slots, 8, self.selected),
-            build_index_row(8, self.selected),
+            build_swatch_row(self.palette, 8..16, self.selected),
+            build_index_row(8..16, self.selected, self.locked, self.flagged),
+            Line::from(""),
+            // Background, foreground, cursor and selection
+            Line::from("  Special"),
+            build_swatch_row(self.palette, BACKGROUND..SELECTABLE, self.selected),
+            build_index_row(
+                BACKGROUND..SELECTABLE,
+                self.selected,
+                self.locked,
+                self.flagged,
+            ),
         ];
 
-        // Info line for the selected slot
-        if let Some(slot) = self.selected {
-            if slot < 16 {
-                let color = &self.palette.slots[slot];
-                let hex = color.to_hex();
-                let ratio = AppColor::contrast_ratio(color, &self.palette.background);
-                lines.push(Line::from(""));
-                lines.push(Line::from(vec![
-                    Span::raw("  "),
-                    Span::styled(
-                        format!("  {}  ", slot_name(slot)),
-                        Style::default().bg(to_color(color)).fg(contrast_fg(color)),
-                    ),
-                    Span::raw(format!(
-                        "  {}:{}  {}  contrast {ratio:.1}:1",
-                        slot,
-                        slot_name(slot),
-                        hex,
-                    )),
-                ]));
+        // Info line for the selected color
+        if let Some(index) = self.selected {
+            let color = &color_at(self.palette, index);
+            let hex = color.to_hex();
+            let ratio = AppColor::contrast_ratio(color, &contrast_partner(self.palette, index));
+            let id = if index < 16 {
+                format!("{index}:{}", slot_name(index))
+            } else {
+                color_name(index).to_string()
+            };
+            let mut info = vec![
+                Span::raw("  "),
+                Span::styled(
+                    format!("  {}  ", label(index)),
+                    Style::default().bg(to_color(color)).fg(contrast_fg(color)),
+                ),
+                Span::raw(format!("  {id}  {hex}  contrast {ratio:.1}:1")),
+            ];
+            if self.locked.contains(&index) {
+                info.push(Span::raw("  locked"));
             }
+            lines.push(Line::from(""));
+            lines.push(Line::from(info));
+            let oklch = color.to_oklch();
+            lines.push(Line::from(vec![
+                Span::raw("          "),
+                Span::styled(
+                    format!(
+                        "  L {:.3}  C {:.3}  H {:.1}\u{b0}",
+                        oklch.l,
+                        oklch.chroma,
+                        oklch.hue.into_positive_degrees()
+                    ),
+                    Style::default().fg(Color::DarkGray),
+                ),
+            ]));
+        }
+
+        Paragraph::new(lines).render(inner, buf);
+    }
+}
+
+// ---------------------------------------------------------------------------
+// CompareWidget
+// ---------------------------------------------------------------------------
+
+/// Delta-E from which a change is drawn as large.
+const LARGE_DELTA_E: f32 = 10.0;
+
+/// The palette being edited above another theme's, group by group, with the
+/// delta-E of each color below the pair.
+pub struct CompareWidget<'a> {
+    palette: &'a AnsiPalette,
+    other: &'a AnsiPalette,
+    name: &'a str,
+    selected: Option<usize>,
+}
+
+impl<'a> CompareWidget<'a> {
+    pub fn new(
+        palette: &'a AnsiPalette,
+        other: &'a AnsiPalette,
+        name: &'a str,
+        selected: Option<usize>,
+    ) -> Self {
+        Self {
+            palette,
+            other,
+            name,
+            selected,
+        }
+    }
+}
+
+/// Build a row of delta-E values, dim when unchanged and red when large.
+fn build_delta_row(
+    a: &AnsiPalette,
+    b: &AnsiPalette,
+    indices: std::ops::Range<usize>,
+) -> Line<'static> {
+    let mut spans = vec![Span::raw("  ")];
+    for i in indices {
+        let delta = color_at(a, i).delta_e(color_at(b, i));
+        let style = if delta < UNCHANGED_DELTA_E {
+            Style::default().fg(Color::DarkGray)
+        } else if delta < LARGE_DELTA_E {
+            Style::default().fg(Color::Yellow)
+        } else {
+            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)
+        };
+        spans.push(Span::styled(format!("{delta:^6.1}"), style));
+        spans.push(Span::raw(" "));
+    }
+    Line::from(spans)
+}
+
+impl Widget for CompareWidget<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let title = format!("Compare: new / {} / \u{394}E", self.name);
+        let block = Block::bordered().title(title);
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        let mut lines = Vec::new();
+        for (title, indices) in [
+            ("Normal", 0..8),
+            ("Bright", 8..16),
+            ("Special", BACKGROUND..SELECTABLE),
+        ] {
+            lines.push(Line::from(format!("  {title}")));
+            lines.push(build_swatch_row(
+                self.palette,
+                indices.clone(),
+                self.selected,
+            ));
+            lines.push(build_swatch_row(self.other, indices.clone(), None));
+            lines.push(build_delta_row(self.palette, self.other, indices));
         }
 
+        let deltas: Vec<f32> = (0..SELECTABLE)
+            .map(|i| color_at(self.palette, i).delta_e(color_at(self.other, i)))
+            .collect();
+        let changed = deltas.iter().filter(|&&d| d >= UNCHANGED_DELTA_E).count();
+        let mean = deltas.iter().sum::<f32>() / deltas.len() as f32;
+        lines.push(Line::from(format!(
+            "  {changed} of {} colors changed, mean \u{394}E {mean:.1}",
+            deltas.len()
+        )));
+
         Paragraph::new(lines).render(inner, buf);
     }
 }
@@ -133,120 +318,505 @@ impl Widget for PaletteWidget<'_> {
 // PreviewWidget
 // ---------------------------------------------------------------------------
 
+/// Sample content shown in the preview pane, cycled with `p`. Each scene
+/// leans on a different group of colors.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Scene {
+    /// Prompt, `ls` and a short diff: the normal accents.
+    #[default]
+    Shell,
+    /// Rust and Python with syntax highlighting, a selection and the cursor.
+    Code,
+    /// `git log --graph` decorations and a full diff: bright accents.
+    Git,
+    /// htop-like meters and process list: meter colors on black, header bars.
+    Monitor,
+    /// A man page in `less` with colored bold, underline and standout.
+    Manual,
+}
+
+impl Scene {
+    pub const ALL: [Scene; 5] = [
+        Scene::Shell,
+        Scene::Code,
+        Scene::Git,
+        Scene::Monitor,
+        Scene::Manual,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Scene::Shell => "shell",
+            Scene::Code => "code",
+            Scene::Git => "git",
+            Scene::Monitor => "htop",
+            Scene::Manual => "man page",
+        }
+    }
+
+    /// The scene after this one, wrapping around.
+    pub fn next(self) -> Scene {
+        let i = Scene::ALL.iter().position(|&s| s == self).unwrap_or(0);
+        Scene::ALL[(i + 1) % Scene::ALL.len()]
+    }
+}
+
 /// A widget that renders a simulated terminal session using the theme colors.
 pub struct PreviewWidget<'a> {
     palette: &'a AnsiPalette,
+    scene: Scene,
+    title: &'a str,
 }
 
 impl<'a> PreviewWidget<'a> {
-    pub fn new(palette: &'a AnsiPalette) -> Self {
-        Self { palette }
+    pub fn new(palette: &'a AnsiPalette, scene: Scene) -> Self {
+        Self {
+            palette,
+            scene,
+            title: "Preview",
+        }
+    }
+
+    /// Replace "Preview" in the title, e.g. with a theme name.
+    pub fn titled(mut self, title: &'a str) -> Self {
+        self.title = title;
+        self
+    }
+}
+
+/// Styles of the theme's colors on its background.
+struct Ink {
+    base: Style,
+    slots: [Color; 16],
+}
+
+impl Ink {
+    fn new(p: &AnsiPalette) -> Self {
+        Self {
+            base: Style::default()
+                .bg(to_color(&p.background))
+                .fg(to_color(&p.foreground)),
+            slots: p.slots.map(|c| to_color(&c)),
+        }
     }
+
+    /// Text in ANSI color `slot`.
+    fn fg(&self, slot: usize) -> Style {
+        self.base.fg(self.slots[slot])
+    }
+
+    /// Bold text in ANSI color `slot`.
+    fn bold(&self, slot: usize) -> Style {
+        self.fg(slot).add_modifier(Modifier::BOLD)
+    }
+}
+
+/// Fill the rest of each line with the background so the pane reads as one
+/// terminal.
+fn pad_lines(lines: Vec<Vec<Span<'static>>>, width: u16, base: Style) -> Vec<Line<'static>> {
+    lines
+        .into_iter()
+        .map(|mut spans| {
+            let used: usize = spans.iter().map(|s| s.width()).sum();
+            let remaining = (width as usize).saturating_sub(used);
+            spans.push(Span::styled(" ".repeat(remaining), base));
+            Line::from(spans)
+        })
+        .collect()
+}
+
+fn span(text: &'static str, style: Style) -> Span<'static> {
+    Span::styled(text, style)
+}
+
+fn prompt(ink: &Ink, command: &'static str) -> Vec<Span<'static>> {
+    vec![
+        span("  ", ink.base),
+        span("user@host", ink.fg(2)),
+        span(":", ink.base),
+        span("~/projects", ink.fg(4)),
+        span("$ ", ink.base),
+        span(command, ink.base),
+    ]
+}
+
+fn shell_scene(ink: &Ink) -> Vec<Vec<Span<'static>>> {
+    let base = ink.base;
+    vec![
+        vec![],
+        prompt(ink, "ls"),
+        // Directories (blue), files (fg), config (yellow), executables (green)
+        vec![
+            span("  ", base),
+            span("src/", ink.fg(4)),
+            span("  README.md  ", base),
+            span("Cargo.toml", ink.fg(3)),
+            span("  ", base),
+            span("run.sh", ink.fg(2)),
+        ],
+        prompt(ink, "git diff"),
+        vec![span("  - old line removed", ink.fg(1))],
+        vec![span("  + new line added", ink.fg(2))],
+        vec![span("  // comment in code", ink.fg(8))],
+        vec![
+            span("  ", base),
+            span("fn", ink.fg(6)),
+            span(" main() {", base),
+        ],
+        vec![
+            span("      ", base),
+            span("println!", ink.fg(5)),
+            span("(", base),
+            span("\"hello\"", ink.fg(2)),
+            span(");", base),
+        ],
+        vec![
+            span("      ", base),
+            span("let", ink.fg(6)),
+            span(" x = ", base),
+            span("42", ink.fg(3)),
+            span(";", base),
+        ],
+        vec![span("  }", base)],
+    ]
 }
 
-/// Create padding to fill the rest of a line with the base style.
-fn pad_line(total_width: u16, used: u16, style: Style) -> Span<'static> {
-    let remaining = total_width.saturating_sub(used) as usize;
-    Span::styled(" ".repeat(remaining), style)
+fn code_scene(ink: &Ink, p: &AnsiPalette) -> Vec<Vec<Span<'static>>> {
+    let base = ink.base;
+    let selection = Style::default()
+        .bg(to_color(&p.selection_bg))
+        .fg(to_color(&p.selection_fg));
+    let cursor = Style::default()
+        .bg(to_color(&p.cursor_color))
+        .fg(to_color(&p.cursor_text));
+    vec![
+        vec![span("  // greet.rs", ink.fg(8))],
+        vec![
+            span("  ", base),
+            span("use", ink.fg(5)),
+            span(" std::io::{", base),
+            span("self", ink.fg(1)),
+            span(", ", base),
+            span("Write", ink.fg(3)),
+            span("};", base),
+        ],
+        vec![span("  /// Greets everyone in `names`.", ink.fg(8))],
+        vec![
+            span("  ", base),
+            span("pub fn", ink.fg(5)),
+            span(" ", base),
+            span("greet", ink.fg(4)),
+            span("(names: &[", base),
+            span("&str", ink.fg(3)),
+            span("]) -> io::", base),
+            span("Result", ink.fg(3)),
+            span("<()> {", base),
+        ],
+        vec![
+            span("      ", base),
+            span("for", ink.fg(5)),
+            span(" (i, name) ", base),
+            span("in", ink.fg(5)),
+            span(" names.iter().", base),
+            span("enumerate", ink.fg(4)),
+            span("() {", base),
+        ],
+        vec![
+            span("          ", base),
+            span("writeln!(io::stdout(), \"{i}: hello {name}\")?;", selection),
+        ],
+        vec![span("      }", base)],
+        vec![
+            span("      ", base),
+            span("Ok", ink.fg(6)),
+            span("(())", base),
+        ],
+        vec![span("  }", base)],
+        vec![span("  # theme.py", ink.fg(8))],
+        vec![span("  ", base), span("@dataclass", ink.fg(3))],
+        vec![
+            span("  ", base),
+            span("class", ink.fg(5)),
+            span(" ", base),
+            span("Theme", ink.fg(3)),
+            span(":", base),
+        ],
+        vec![
+            span("      name: ", base),
+            span("str", ink.fg(3)),
+            span(" = ", base),
+            span("\"sunset\"", ink.fg(2)),
+            span("  ", base),
+            span("# default", ink.fg(8)),
+        ],
+        vec![
+            span("      ", base),
+            span("def", ink.fg(5)),
+            span(" ", base),
+            span("hues", ink.fg(4)),
+            span("(", base),
+            span("self", ink.fg(1)),
+            span(") -> list[", base),
+            span("int", ink.fg(3)),
+            span("]:", base),
+        ],
+        vec![
+            span("          ", base),
+            span("return", ink.fg(5)),
+            span(" [h % ", base),
+            span("360", ink.fg(6)),
+            span(" ", base),
+            span("for", ink.fg(5)),
+            span(" h ", base),
+            span("in", ink.fg(5)),
+            span(" self.raw ", base),
+            span("if", ink.fg(5)),
+            span(" h ", base),
+            span("is not", ink.fg(5)),
+            span(" ", base),
+            span("None", ink.fg(6)),
+            span("]", base),
+            span(" ", cursor),
+        ],
+    ]
+}
+
+fn git_scene(ink: &Ink) -> Vec<Vec<Span<'static>>> {
+    let base = ink.base;
+    let bold = base.add_modifier(Modifier::BOLD);
+    vec![
+        prompt(ink, "git log --oneline --graph"),
+        vec![
+            span("  ", base),
+            span("*", ink.fg(1)),
+            span(" ", base),
+            span("a1b2c3d", ink.fg(3)),
+            span(" (", ink.fg(3)),
+            span("HEAD -> ", ink.bold(14)),
+            span("main", ink.bold(10)),
+            span(", ", ink.fg(3)),
+            span("origin/main", ink.bold(9)),
+            span(")", ink.fg(3)),
+            span(" Add sunset theme", base),
+        ],
+        vec![
+            span("  ", base),
+            span("|", ink.fg(1)),
+            span(" ", base),
+            span("*", ink.fg(2)),
+            span(" ", base),
+            span("9f8e7d6", ink.fg(3)),
+            span(" (", ink.fg(3)),
+            span("feature", ink.bold(10)),
+            span(")", ink.fg(3)),
+            span(" Tweak contrast", base),
+        ],
+        vec![span("  ", base), span("|", ink.fg(1)), span("/", ink.fg(2))],
+        vec![
+            span("  ", base),
+            span("*", ink.fg(1)),
+            span(" ", base),
+            span("4c5d6e7", ink.fg(3)),
+            span(" (", ink.fg(3)),
+            span("tag: v0.2.0", ink.bold(11)),
+            span(")", ink.fg(3)),
+            span(" Release 0.2.0", base),
+        ],
+        prompt(ink, "git diff"),
+        vec![span("  diff --git a/theme.toml b/theme.toml", bold)],
+        vec![span("  index 3f2a1b0..8c9d4e2 100644", bold)],
+        vec![span("  --- a/theme.toml", bold)],
+        vec![span("  +++ b/theme.toml", bold)],
+        vec![
+            span("  ", base),
+            span("@@ -1,3 +1,3 @@", ink.fg(6)),
+            span(" [colors]", base),
+        ],
+        vec![span("   background = \"#1e1e2e\"", base)],
+        vec![span("  -red = \"#e06c75\"", ink.fg(1))],
+        vec![span("  +red = \"#f38ba8\"", ink.fg(2))],
+    ]
+}
+
+/// One htop meter: `label[||||      value]`, with `segments` of bars in
+/// the given colors.
+fn meter(
+    ink: &Ink,
+    label: &'static str,
+    segments: &[(usize, usize)],
+    value: &'static str,
+) -> Vec<Span<'static>> {
+    const WIDTH: usize = 30;
+    let mut spans = vec![span(label, ink.fg(6)), span("[", ink.bold(15))];
+    let mut used = 0;
+    for &(count, slot) in segments {
+        spans.push(Span::styled("|".repeat(count), ink.fg(slot)));
+        used += count;
+    }
+    let gap = WIDTH.saturating_sub(used + value.len());
+    spans.push(Span::styled(" ".repeat(gap), ink.base));
+    spans.push(span(value, ink.fg(8)));
+    spans.push(span("]", ink.bold(15)));
+    spans
+}
+
+fn monitor_scene(ink: &Ink, p: &AnsiPalette) -> Vec<Vec<Span<'static>>> {
+    let base = ink.base;
+    let header = Style::default()
+        .bg(ink.slots[2])
+        .fg(ink.slots[0])
+        .add_modifier(Modifier::BOLD);
+    let selected = Style::default()
+        .bg(to_color(&p.selection_bg))
+        .fg(to_color(&p.selection_fg));
+    let key = Style::default().bg(ink.slots[0]).fg(ink.slots[15]);
+    let key_label = Style::default().bg(ink.slots[6]).fg(ink.slots[0]);
+    let mut cpu0 = meter(ink, "    0", &[(12, 2), (4, 1), (2, 4)], "52.3%");
+    cpu0.extend([
+        span("   Tasks: ", ink.fg(6)),
+        span("142", ink.bold(15)),
+        span(", 389 thr; ", ink.fg(6)),
+        span("2", ink.bold(2)),
+        span(" running", ink.fg(6)),
+    ]);
+    let mut cpu1 = meter(ink, "    1", &[(18, 2), (5, 1)], "81.0%");
+    cpu1.extend([
+        span("   Load average: ", ink.fg(6)),
+        span("1.42 ", ink.bold(15)),
+        span("0.98 ", ink.bold(6)),
+        span("0.77", ink.fg(6)),
+    ]);
+    let mut mem = meter(ink, "  Mem", &[(10, 2), (2, 4), (6, 3)], "3.21G/15.5G");
+    mem.extend([
+        span("   Uptime: ", ink.fg(6)),
+        span("3 days, 04:12:55", ink.bold(15)),
+    ]);
+    vec![
+        cpu0,
+        cpu1,
+        mem,
+        meter(ink, "  Swp", &[(1, 1)], "12.0M/8.00G"),
+        vec![],
+        vec![span(
+            "    PID USER      PRI  NI  VIRT   RES S CPU% MEM% Command",
+            header,
+        )],
+        vec![span(
+            "   1234 user       20   0 2104M  312M R 48.2  2.0 firefox",
+            selected,
+        )],
+        vec![
+            span("   2345 ", base),
+            span("root     ", ink.fg(1)),
+            span("  20   0  ", base),
+            span("812M", ink.fg(6)),
+            span("   96M S 12.5  0.6 ", base),
+            span("/usr/bin/", ink.fg(8)),
+            span("Xorg", base),
+        ],
+        vec![
+            span("   3456 user       ", base),
+            span("39  19", ink.fg(4)),
+            span("  450M   64M ", base),
+            span("D", ink.fg(1)),
+            span("  3.1  0.4 ", base),
+            span("ghostty", ink.fg(2)),
+        ],
+        vec![
+            span("F1", key),
+            span("Help  ", key_label),
+            span("F2", key),
+            span("Setup ", key_label),
+            span("F3", key),
+            span("Search", key_label),
+            span("F9", key),
+            span("Kill  ", key_label),
+            span("F10", key),
+            span("Quit  ", key_label),
+        ],
+    ]
+}
+
+fn manual_scene(ink: &Ink) -> Vec<Vec<Span<'static>>> {
+    let base = ink.base;
+    // The common "colored man pages" setup: bold in bright red, underline in
+    // bright green, standout in bright yellow on blue
+    let bold = ink.bold(9);
+    let under = ink.bold(10).add_modifier(Modifier::UNDERLINED);
+    let standout = Style::default()
+        .bg(ink.slots[4])
+        .fg(ink.slots[11])
+        .add_modifier(Modifier::BOLD);
+    vec![
+        vec![span(
+            "  LS(1)                      User Commands                      LS(1)",
+            base,
+        )],
+        vec![],
+        vec![span("  NAME", bold)],
+        vec![span("         ls - list directory contents", base)],
+        vec![],
+        vec![span("  SYNOPSIS", bold)],
+        vec![
+            span("         ", base),
+            span("ls", bold),
+            span(" [", base),
+            span("OPTION", under),
+            span("]... [", base),
+            span("FILE", under),
+            span("]...", base),
+        ],
+        vec![],
+        vec![span("  DESCRIPTION", bold)],
+        vec![
+            span("         ", base),
+            span("-a", bold),
+            span(", ", base),
+            span("--all", bold),
+        ],
+        vec![span(
+            "                do not ignore entries starting with .",
+            base,
+        )],
+        vec![
+            span("         ", base),
+            span("--color", bold),
+            span("[=", base),
+            span("WHEN", under),
+            span("]", base),
+        ],
+        vec![span(
+            "                color the output WHEN; more info below",
+            base,
+        )],
+        vec![span(
+            " Manual page ls(1) line 1 (press h for help or q to quit)",
+            standout,
+        )],
+    ]
 }
 
 impl Widget for PreviewWidget<'_> {
     fn render(self, area: Rect, buf: &mut Buffer) {
-        let block = Block::bordered().title("Preview");
+        let title = format!("{}: {} (p: next)", self.title, self.scene.name());
+        let block = Block::bordered().title(title);
         let inner = block.inner(area);
         block.render(area, buf);
 
-        let p = self.palette;
-        let bg_c = to_color(&p.background);
-        let fg_c = to_color(&p.foreground);
-        let base = Style::default().bg(bg_c).fg(fg_c);
-
-        let red = to_color(&p.slots[1]);
-        let green = to_color(&p.slots[2]);
-        let yellow = to_color(&p.slots[3]);
-        let blue = to_color(&p.slots[4]);
-        let magenta = to_color(&p.slots[5]);
-        let cyan = to_color(&p.slots[6]);
-        let bright_black = to_color(&p.slots[8]);
-
-        let w = inner.width;
-
-        let lines = vec![
-            // Blank background line
-            Line::from(Span::styled(" ".repeat(w as usize), base)),
-            // Shell prompt: user@host:~/projects$ ls
-            Line::from(vec![
-                Span::styled("  ", base),
-                Span::styled("user@host", base.fg(green)),
-                Span::styled(":", base),
-                Span::styled("~/projects", base.fg(blue)),
-                Span::styled("$ ls", base),
-                pad_line(w, 28, base),
-            ]),
-            // ls output — directories (blue), files (fg), config (yellow), exec (green)
-            Line::from(vec![
-                Span::styled("  ", base),
-                Span::styled("src/", base.fg(blue)),
-                Span::styled("  ", base),
-                Span::styled("README.md", base.fg(fg_c)),
-                Span::styled("  ", base),
-                Span::styled("Cargo.toml", base.fg(yellow)),
-                Span::styled("  ", base),
-                Span::styled("run.sh", base.fg(green)),
-                pad_line(w, 39, base),
-            ]),
-            // Second prompt: git diff
-            Line::from(vec![
-                Span::styled("  ", base),
-                Span::styled("user@host", base.fg(green)),
-                Span::styled(":", base),
-                Span::styled("~/projects", base.fg(blue)),
-                Span::styled("$ git diff", base),
-                pad_line(w, 34, base),
-            ]),
-            // Diff deletion (red)
-            Line::from(vec![
-                Span::styled("  - old line removed", base.fg(red)),
-                pad_line(w, 20, base),
-            ]),
-            // Diff addition (green)
-            Line::from(vec![
-                Span::styled("  + new line added", base.fg(green)),
-                pad_line(w, 18, base),
-            ]),
-            // Comment (bright black)
-            Line::from(vec![
-                Span::styled("  // comment in code", base.fg(bright_black)),
-                pad_line(w, 20, base),
-            ]),
-            // Code: fn definition (cyan keyword, magenta macro)
-            Line::from(vec![
-                Span::styled("  ", base),
-                Span::styled("fn", base.fg(cyan)),
-                Span::styled(" main() {", base),
-                pad_line(w, 14, base),
-            ]),
-            // Code: println macro (magenta), string literal (green)
-            Line::from(vec![
-                Span::styled("      ", base),
-                Span::styled("println!", base.fg(magenta)),
-                Span::styled("(", base),
-                Span::styled("\"hello\"", base.fg(green)),
-                Span::styled(");", base),
-                pad_line(w, 25, base),
-            ]),
-            // Code: let binding (cyan keyword, yellow number)
-            Line::from(vec![
-                Span::styled("      ", base),
-                Span::styled("let", base.fg(cyan)),
-                Span::styled(" x = ", base),
-                Span::styled("42", base.fg(yellow)),
-                Span::styled(";", base),
-                pad_line(w, 17, base),
-            ]),
-            // Closing brace
-            Line::from(vec![Span::styled("  }", base), pad_line(w, 3, base)]),
-        ];
+        let ink = Ink::new(self.palette);
+        let lines = match self.scene {
+            Scene::Shell => shell_scene(&ink),
+            Scene::Code => code_scene(&ink, self.palette),
+            Scene::Git => git_scene(&ink),
+            Scene::Monitor => monitor_scene(&ink, self.palette),
+            Scene::Manual => manual_scene(&ink),
+        };
+        // Blank lines keep the terminal background down to the bottom
+        let mut lines = pad_lines(lines, inner.width, ink.base);
+        let blank = pad_lines(vec![vec![]], inner.width, ink.base);
+        while lines.len() < inner.height as usize {
+            lines.extend(blank.iter().cloned());
+        }
 
         Paragraph::new(lines).render(inner, buf);
     }
@@ -0,0 +1,149 @@
+//! Video wallpapers (mp4, webm, ...) by way of ffmpeg, with the `video`
+//! feature: frames are sampled across the video, scaled down by ffmpeg and
+//! read back as PPM, then pooled like the frames of an animated GIF.
+
+use std::io;
+use std::path::Path;
+use std::process::Command;
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use image::RgbImage;
+
+use crate::error::NuriError;
+use crate::pipeline::extract::{LoadOptions, ResizeFilter};
+
+/// Up to `options.frames` frames of the video at `path`, spread over its
+/// whole length or one every `options.frame_interval`, each cropped to
+/// `options.region` and fitting within `options.max_dim` squared.
+pub fn decode_frames(path: &Path, options: &LoadOptions) -> Result<Vec<RgbImage>> {
+    if !path.exists() {
+        return Err(NuriError::ImageNotFound { path: path.into() }.into());
+    }
+    let count = options.frames.max(1);
+    let rate = match options.frame_interval {
+        Some(interval) => format!("1/{}", interval.as_secs_f64()),
+        None => format!("{count}/{}", duration(path)?.as_secs_f64()),
+    };
+    let crop = options
+        .region
+        .map_or_else(String::new, |region| format!("{},", region.ffmpeg_filter()));
+    let filter = format!(
+        "fps={rate},{crop}scale='min({max_dim},iw)':'min({max_dim},ih)':force_original_aspect_ratio=decrease:flags={flags}",
+        max_dim = options.max_dim,
+        flags = match options.resize_filter {
+            ResizeFilter::Nearest => "neighbor",
+            ResizeFilter::Triangle => "bilinear",
+            ResizeFilter::Lanczos => "lanczos",
+        }
+    );
+    let output = Command::new("ffmpeg")
+        .args(["-v", "error", "-nostdin", "-i"])
+        .arg(path)
+        .args(["-vf", &filter, "-frames:v", &count.to_string()])
+        .args(["-c:v", "ppm", "-f", "image2pipe", "-"])
+        .output()
+        .map_err(|e| not_found("ffmpeg", e))?;
+    if !output.status.success() {
+        bail!(
+            "ffmpeg cannot read {}: {}",
+            path.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    let frames = parse_ppm_stream(&output.stdout)?;
+    if frames.is_empty() {
+        bail!("ffmpeg found no video frames in {}", path.display());
+    }
+    log::debug!("sampled {} video frames with ffmpeg", frames.len());
+    Ok(frames)
+}
+
+/// Length of the video, from ffprobe.
+fn duration(path: &Path) -> Result<Duration> {
+    let output = Command::new("ffprobe")
+        .args(["-v", "error", "-show_entries", "format=duration"])
+        .args(["-of", "default=noprint_wrappers=1:nokey=1"])
+        .arg(path)
+        .output()
+        .map_err(|e| not_found("ffprobe", e))?;
+    String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|seconds| *seconds > 0.0)
+        .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok())
+        .with_context(|| format!("ffprobe cannot tell how long {} is", path.display()))
+}
+
+/// A readable error when `program` isn't installed.
+fn not_found(program: &str, error: io::Error) -> anyhow::Error {
+    if error.kind() == io::ErrorKind::NotFound {
+        anyhow::anyhow!("video wallpapers need {program} on PATH")
+    } else {
+        anyhow::Error::new(error).context(format!("cannot run {program}"))
+    }
+}
+
+/// Split ffmpeg's `image2pipe` output, binary PPMs (`P6`) back to back, into
+/// frames.
+fn parse_ppm_stream(mut bytes: &[u8]) -> Result<Vec<RgbImage>> {
+    let mut frames = Vec::new();
+    while !bytes.is_empty() {
+        let header = bytes
+            .strip_prefix(b"P6")
+            .context("ffmpeg did not write PPM frames")?;
+        let mut pos = 0;
+        let mut number = || -> Result<u32> {
+            pos += header[pos..]
+                .iter()
+                .take_while(|b| b.is_ascii_whitespace())
+                .count();
+            let digits = header[pos..]
+                .iter()
+                .take_while(|b| b.is_ascii_digit())
+                .count();
+            let value = std::str::from_utf8(&header[pos..pos + digits])?.parse()?;
+            pos += digits;
+            Ok(value)
+        };
+        let (width, height, max) = (number()?, number()?, number()?);
+        if max != 255 {
+            bail!("ffmpeg wrote {max}-level PPM frames; expected 8-bit");
+        }
+        // A single whitespace byte ends the header
+        let data = header.get(pos + 1..).unwrap_or_default();
+        let size = width as usize * height as usize * 3;
+        let Some(pixels) = data.get(..size) else {
+            bail!("ffmpeg output ends in the middle of a frame");
+        };
+        frames.push(
+            RgbImage::from_raw(width, height, pixels.to_vec())
+                .context("malformed PPM frame from ffmpeg")?,
+        );
+        bytes = &data[size..];
+    }
+    Ok(frames)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_back_to_back_ppm_frames() {
+        let mut stream = b"P6\n2 1\n255\n".to_vec();
+        stream.extend_from_slice(&[255, 0, 0, 0, 255, 0]);
+        stream.extend_from_slice(b"P6 1 1 255\n");
+        stream.extend_from_slice(&[0, 0, 255]);
+
+        let frames = parse_ppm_stream(&stream).unwrap();
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0].dimensions(), (2, 1));
+        assert_eq!(frames[0].get_pixel(1, 0).0, [0, 255, 0]);
+        assert_eq!(frames[1].get_pixel(0, 0).0, [0, 0, 255]);
+
+        assert!(parse_ppm_stream(&stream[..stream.len() - 1]).is_err());
+        assert!(parse_ppm_stream(b"GIF89a").is_err());
+    }
+}
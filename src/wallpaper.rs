@@ -0,0 +1,231 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{bail, Result};
+
+use crate::backends::config_home;
+
+/// Find the image the desktop is currently showing, asking each supported
+/// source in turn: macOS, GNOME, hyprpaper, swww, swaybg, then the state files
+/// feh and nitrogen leave behind.
+pub fn current() -> Result<PathBuf> {
+    let sources: [fn() -> Option<PathBuf>; 7] =
+        [macos, gnome, hyprpaper, swww, swaybg, fehbg, nitrogen];
+    match sources.iter().find_map(|source| source()) {
+        Some(path) if path.is_file() => Ok(path),
+        Some(path) => bail!("current wallpaper {} does not exist", path.display()),
+        None => bail!("could not detect the current wallpaper; pass an image path instead"),
+    }
+}
+
+/// Stdout of `program args`, if it ran and succeeded.
+fn run(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn home() -> Option<PathBuf> {
+    std::env::var_os("HOME").map(PathBuf::from)
+}
+
+fn macos() -> Option<PathBuf> {
+    if !cfg!(target_os = "macos") {
+        return None;
+    }
+    let picture = run(
+        "osascript",
+        &[
+            "-e",
+            "tell application \"System Events\" to get picture of current desktop",
+        ],
+    )
+    .map(|out| out.trim().to_string())
+    .filter(|out| !out.is_empty());
+    // The Dock's database records the picture even when scripting is denied
+    let picture = picture.or_else(|| {
+        let db = home()?.join("Library/Application Support/Dock/desktoppicture.db");
+        run(
+            "sqlite3",
+            &[
+                db.to_str()?,
+                "SELECT value FROM data ORDER BY rowid DESC LIMIT 1",
+            ],
+        )
+        .map(|out| out.trim().to_string())
+        .filter(|out| !out.is_empty())
+    })?;
+    Some(expand_home(&picture))
+}
+
+fn gnome() -> Option<PathBuf> {
+    let get = |key: &str| run("gsettings", &["get", "org.gnome.desktop.background", key]);
+    // GNOME 42+ keeps a separate picture for the dark style
+    let dark = run(
+        "gsettings",
+        &["get", "org.gnome.desktop.interface", "color-scheme"],
+    )
+    .is_some_and(|scheme| scheme.contains("prefer-dark"));
+    let uri = if dark {
+        get("picture-uri-dark")
+            .filter(|uri| parse_gsettings_uri(uri).is_some())
+            .or_else(|| get("picture-uri"))
+    } else {
+        get("picture-uri")
+    }?;
+    parse_gsettings_uri(&uri)
+}
+
+fn hyprpaper() -> Option<PathBuf> {
+    std::env::var_os("HYPRLAND_INSTANCE_SIGNATURE")?;
+    parse_hyprpaper(&run("hyprctl", &["hyprpaper", "listactive"])?)
+}
+
+fn swww() -> Option<PathBuf> {
+    parse_swww(&run("swww", &["query"])?)
+}
+
+fn swaybg() -> Option<PathBuf> {
+    std::env::var_os("SWAYSOCK")?;
+    parse_swaybg(&run("pgrep", &["-a", "swaybg"])?)
+}
+
+fn fehbg() -> Option<PathBuf> {
+    parse_fehbg(&std::fs::read_to_string(home()?.join(".fehbg")).ok()?)
+}
+
+fn nitrogen() -> Option<PathBuf> {
+    let saved = config_home().join("nitrogen").join("bg-saved.cfg");
+    parse_nitrogen(&std::fs::read_to_string(saved).ok()?)
+}
+
+/// `'file:///home/me/My%20Walls/a.jpg'` as printed by `gsettings get`.
+fn parse_gsettings_uri(value: &str) -> Option<PathBuf> {
+    let uri = value.trim().trim_matches('\'');
+    let path = uri.strip_prefix("file://").unwrap_or(uri);
+    (!path.is_empty()).then(|| PathBuf::from(percent_decode(path)))
+}
+
+/// `hyprctl hyprpaper listactive`: one `monitor = path` line per output.
+fn parse_hyprpaper(output: &str) -> Option<PathBuf> {
+    output
+        .lines()
+        .find_map(|line| line.split_once(" = "))
+        .map(|(_, path)| PathBuf::from(path.trim()))
+}
+
+/// `swww query`: `eDP-1: 1920x1080, scale: 1, currently displaying: image: /path`.
+fn parse_swww(output: &str) -> Option<PathBuf> {
+    output
+        .lines()
+        .find_map(|line| line.split_once("image: "))
+        .map(|(_, path)| PathBuf::from(path.trim()))
+}
+
+/// `pgrep -a swaybg`: `1234 swaybg -o * -i /path/wall.png -m fill`.
+fn parse_swaybg(output: &str) -> Option<PathBuf> {
+    let line = output.lines().next()?;
+    let mut words = line.split_whitespace();
+    words.find(|&w| w == "-i" || w == "--image")?;
+    words.next().map(PathBuf::from)
+}
+
+/// `~/.fehbg`: `feh --no-fehbg --bg-fill '/path/wall.jpg'`.
+fn parse_fehbg(script: &str) -> Option<PathBuf> {
+    let line = script
+        .lines()
+        .rfind(|line| line.trim_start().starts_with("feh "))?;
+    let start = line.find('\'')? + 1;
+    let end = start + line[start..].find('\'')?;
+    Some(expand_home(&line[start..end]))
+}
+
+/// nitrogen's `bg-saved.cfg`: `file=/path` under each output's section.
+fn parse_nitrogen(config: &str) -> Option<PathBuf> {
+    config
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("file="))
+        .map(expand_home)
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), home()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => Path::new(path).to_path_buf(),
+    }
+}
+
+/// Decode `%XX` escapes in a file URI path.
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                out.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                out.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_gnome_uris() {
+        assert_eq!(
+            parse_gsettings_uri("'file:///home/me/My%20Walls/caf%C3%A9.jpg'\n"),
+            Some(PathBuf::from("/home/me/My Walls/café.jpg"))
+        );
+        assert_eq!(
+            parse_gsettings_uri("'/usr/share/backgrounds/a.png'"),
+            Some(PathBuf::from("/usr/share/backgrounds/a.png"))
+        );
+        assert_eq!(parse_gsettings_uri("''"), None);
+    }
+
+    #[test]
+    fn parses_wayland_daemons() {
+        assert_eq!(
+            parse_hyprpaper("DP-1 = /walls/a.png\nDP-2 = /walls/b.png\n"),
+            Some(PathBuf::from("/walls/a.png"))
+        );
+        assert_eq!(
+            parse_swww("eDP-1: 1920x1080, scale: 1, currently displaying: image: /walls/c.jpg\n"),
+            Some(PathBuf::from("/walls/c.jpg"))
+        );
+        assert_eq!(
+            parse_swww("eDP-1: currently displaying: color: 000000"),
+            None
+        );
+        assert_eq!(
+            parse_swaybg("812 swaybg -o * -i /walls/d.png -m fill\n"),
+            Some(PathBuf::from("/walls/d.png"))
+        );
+    }
+
+    #[test]
+    fn parses_x11_state_files() {
+        let fehbg = "#!/bin/sh\nfeh --no-fehbg --bg-fill '/walls/e.jpg' \n";
+        assert_eq!(parse_fehbg(fehbg), Some(PathBuf::from("/walls/e.jpg")));
+        let nitrogen = "[xin_-1]\nfile=/walls/f.png\nmode=5\nbgcolor=#000000\n";
+        assert_eq!(
+            parse_nitrogen(nitrogen),
+            Some(PathBuf::from("/walls/f.png"))
+        );
+    }
+}
@@ -0,0 +1,184 @@
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::time::{Duration, SystemTime};
+
+use anyhow::{Context, Result};
+use notify::{EventKind, RecursiveMode, Watcher};
+
+/// File extensions treated as wallpapers when watching a directory.
+const IMAGE_EXTENSIONS: [&str; 8] = ["png", "jpg", "jpeg", "webp", "gif", "bmp", "tif", "tiff"];
+
+/// File extensions of videos, themed with the `video` feature.
+const VIDEO_EXTENSIONS: [&str; 6] = ["mp4", "webm", "mkv", "mov", "m4v", "avi"];
+
+/// File extensions of HEIC and AVIF images, themed with the `heif` feature.
+const HEIF_EXTENSIONS: [&str; 3] = ["heic", "heif", "avif"];
+
+/// Quiet period after the last filesystem event before regenerating, so a
+/// wallpaper written in chunks or swapped in by rename triggers a single run.
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Whether `path` has an image file extension.
+pub fn is_image(path: &Path) -> bool {
+    has_extension(path, &IMAGE_EXTENSIONS)
+}
+
+/// Whether `path` has a video file extension.
+pub fn is_video(path: &Path) -> bool {
+    has_extension(path, &VIDEO_EXTENSIONS)
+}
+
+/// Whether `path` can be themed: an image, or a video, HEIC, AVIF or JPEG XL
+/// file in builds with the matching feature.
+pub fn is_wallpaper(path: &Path) -> bool {
+    is_image(path)
+        || (cfg!(feature = "video") && is_video(path))
+        || (cfg!(feature = "heif") && has_extension(path, &HEIF_EXTENSIONS))
+        || (cfg!(feature = "jxl") && has_extension(path, &["jxl"]))
+}
+
+/// Whether the extension of `path` is one of `extensions`, in any case.
+fn has_extension(path: &Path, extensions: &[&str]) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| {
+            extensions
+                .iter()
+                .any(|known| known.eq_ignore_ascii_case(ext))
+        })
+}
+
+/// The wallpaper `watched` refers to: the file itself, or the most recently
+/// modified image in a directory.
+pub fn current_wallpaper(watched: &Path) -> Option<PathBuf> {
+    if !watched.is_dir() {
+        return watched.is_file().then(|| watched.to_path_buf());
+    }
+    std::fs::read_dir(watched)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && is_wallpaper(path))
+        .filter_map(|path| Some((modified(&path)?, path)))
+        .max()
+        .map(|(_, path)| path)
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Call `on_change` with the current wallpaper, then again every time it
+/// changes. A watched file is tracked through its parent directory so
+/// wallpaper setters that replace it by rename are still noticed. Runs until
+/// the watcher fails.
+pub fn watch(watched: &Path, mut on_change: impl FnMut(&Path)) -> Result<()> {
+    let watched = watched
+        .canonicalize()
+        .with_context(|| format!("cannot watch {}", watched.display()))?;
+    let dir = if watched.is_dir() {
+        watched.clone()
+    } else {
+        watched
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|| watched.clone())
+    };
+
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx).context("failed to start file watcher")?;
+    watcher
+        .watch(&dir, RecursiveMode::NonRecursive)
+        .with_context(|| format!("cannot watch {}", dir.display()))?;
+
+    // (path, mtime) of the last wallpaper processed, to skip no-op events
+    let mut last = None;
+    let mut refresh = |last: &mut Option<(PathBuf, Option<SystemTime>)>| {
+        let Some(wallpaper) = current_wallpaper(&watched) else {
+            return;
+        };
+        let seen = Some((wallpaper.clone(), modified(&wallpaper)));
+        if *last != seen {
+            on_change(&wallpaper);
+            *last = seen;
+        }
+    };
+
+    if current_wallpaper(&watched).is_none() {
+        log::info!("Waiting for an image in {}", watched.display());
+    }
+    refresh(&mut last);
+    loop {
+        let event = rx.recv().context("file watcher stopped")?;
+        let relevant = match event {
+            Ok(event) => {
+                !matches!(event.kind, EventKind::Access(_))
+                    && event.paths.iter().any(|path| {
+                        if watched.is_dir() {
+                            is_wallpaper(path)
+                        } else {
+                            *path == watched
+                        }
+                    })
+            }
+            Err(e) => {
+                log::warn!("file watcher error: {e}");
+                false
+            }
+        };
+        if !relevant {
+            continue;
+        }
+        // Let the burst of events for one change settle
+        loop {
+            match rx.recv_timeout(DEBOUNCE) {
+                Ok(_) => continue,
+                Err(RecvTimeoutError::Timeout) => break,
+                Err(RecvTimeoutError::Disconnected) => return Ok(()),
+            }
+        }
+        refresh(&mut last);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recognizes_image_extensions() {
+        assert!(is_image(Path::new("sunset.png")));
+        assert!(is_image(Path::new("/walls/Forest.JPG")));
+        assert!(!is_image(Path::new("sunset.png.part")));
+        assert!(!is_image(Path::new("notes.txt")));
+        assert!(!is_image(Path::new("wallpaper")));
+        assert!(is_video(Path::new("/walls/rain.MP4")));
+        assert!(!is_video(Path::new("sunset.png")));
+    }
+
+    #[test]
+    fn picks_most_recent_image_in_directory() {
+        let dir = std::env::temp_dir().join("nuri-test-watch-latest");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        assert_eq!(current_wallpaper(&dir), None);
+
+        let old = dir.join("old.png");
+        let new = dir.join("new.jpg");
+        std::fs::write(&old, b"old").unwrap();
+        std::fs::write(&new, b"new").unwrap();
+        std::fs::write(dir.join("later.txt"), b"not an image").unwrap();
+        let past = SystemTime::now() - Duration::from_secs(60);
+        std::fs::File::options()
+            .write(true)
+            .open(&old)
+            .unwrap()
+            .set_modified(past)
+            .unwrap();
+
+        assert_eq!(current_wallpaper(&dir), Some(new.clone()));
+        assert_eq!(current_wallpaper(&old), Some(old));
+        assert_eq!(current_wallpaper(&dir.join("missing.png")), None);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -1,6 +1,7 @@
 use std::path::{Path, PathBuf};
 use std::process::Command;
 
+use nuri::backends::alacritty::AlacrittyBackend;
 use nuri::backends::ghostty::GhosttyBackend;
 use nuri::backends::neovim::NeovimBackend;
 use nuri::backends::zellij::ZellijBackend;
@@ -108,36 +109,45 @@ fn run_pipeline(fixture_name: &str, mode: Option<ThemeMode>) -> String {
 }
 
 /// Validate the structural correctness of a theme output string.
+///
+/// Themes written by the CLI carry extra `# key: value` provenance lines after
+/// the marker; those are skipped so library and CLI output check alike.
 fn validate_theme_structure(output: &str) {
-    let lines: Vec<&str> = output.lines().collect();
+    let mut lines: Vec<&str> = output.lines().collect();
+    assert_eq!(lines[0], "# Generated by nuri");
+    let header_len = lines[1..]
+        .iter()
+        .take_while(|l| l.starts_with("# "))
+        .count();
+    lines.drain(1..1 + header_len);
     assert_eq!(
         lines.len(),
-        22,
-        "theme should have exactly 22 lines, got {}",
+        23,
+        "theme should have exactly 23 lines besides provenance, got {}",
         lines.len()
     );
 
-    // First 6 lines: special colors
-    assert!(lines[0].starts_with("background = #"));
-    assert!(lines[1].starts_with("foreground = #"));
-    assert!(lines[2].starts_with("cursor-color = #"));
-    assert!(lines[3].starts_with("cursor-text = #"));
-    assert!(lines[4].starts_with("selection-background = #"));
-    assert!(lines[5].starts_with("selection-foreground = #"));
+    // Header comment, then 6 special colors
+    assert!(lines[1].starts_with("background = #"));
+    assert!(lines[2].starts_with("foreground = #"));
+    assert!(lines[3].starts_with("cursor-color = #"));
+    assert!(lines[4].starts_with("cursor-text = #"));
+    assert!(lines[5].starts_with("selection-background = #"));
+    assert!(lines[6].starts_with("selection-foreground = #"));
 
     // 16 palette lines
     for i in 0..16 {
-        let line = lines[6 + i];
+        let line = lines[7 + i];
         let prefix = format!("palette = {}=#", i);
         assert!(
             line.starts_with(&prefix),
             "line {} should start with '{prefix}', got '{line}'",
-            6 + i
+            7 + i
         );
     }
 
     // All hex values valid and lowercase
-    for line in &lines {
+    for line in &lines[1..] {
         if let Some(pos) = line.find('#') {
             let hex = &line[pos..pos + 7];
             assert_eq!(hex.len(), 7);
@@ -228,6 +238,29 @@ fn colorful_produces_valid_theme() {
     validate_theme_structure(&output);
 }
 
+#[test]
+fn library_from_image_matches_pipeline() {
+    ensure_fixtures();
+    let path = fixture_dir().join("colorful.png");
+    let palette = nuri::Palette::from_image(&path, &nuri::PaletteOptions::default()).unwrap();
+    assert_eq!(
+        GhosttyBackend.serialize(&palette, "test"),
+        run_pipeline("colorful.png", None)
+    );
+}
+
+#[test]
+fn library_from_image_respects_mode() {
+    ensure_fixtures();
+    let path = fixture_dir().join("dark-photo.png");
+    let options = nuri::PaletteOptions {
+        mode: Some(ThemeMode::Light),
+        ..Default::default()
+    };
+    let palette = nuri::Palette::from_image(&path, &options).unwrap();
+    assert!(palette.background.relative_luminance() > palette.foreground.relative_luminance());
+}
+
 #[test]
 fn contrast_ratios_met_for_all_fixtures() {
     ensure_fixtures();
@@ -300,7 +333,7 @@ mod property_tests {
 
     proptest! {
         #[test]
-        fn theme_always_has_22_lines(pixels in arb_pixel_buffer()) {
+        fn theme_always_has_23_lines(pixels in arb_pixel_buffer()) {
             let lab_pixels = pixels_to_lab(&pixels);
             let colors = extract_colors(&lab_pixels, 16);
             let mode = detect_mode(&lab_pixels);
@@ -308,7 +341,7 @@ mod property_tests {
             enforce_contrast(&mut palette, DEFAULT_ACCENT_CONTRAST);
             let output = GhosttyBackend.serialize(&palette, "test");
             let line_count = output.lines().count();
-            prop_assert_eq!(line_count, 22, "expected 22 lines, got {}", line_count);
+            prop_assert_eq!(line_count, 23, "expected 23 lines, got {}", line_count);
         }
 
         #[test]
@@ -321,7 +354,7 @@ mod property_tests {
             let output = GhosttyBackend.serialize(&palette, "test");
 
             let hex_re = regex::Regex::new(r"#[0-9a-f]{6}").unwrap();
-            for line in output.lines() {
+            for line in output.lines().skip(1) {
                 if let Some(pos) = line.find('#') {
                     let hex = &line[pos..pos + 7];
                     prop_assert!(hex_re.is_match(hex), "invalid hex: '{}'", hex);
@@ -386,6 +419,58 @@ fn cli_stdout_produces_valid_theme() {
     validate_theme_structure(&stdout);
 }
 
+#[test]
+fn cli_algorithms_produce_valid_themes() {
+    ensure_fixtures();
+    let bin = cargo_bin();
+    for algorithm in ["kmeans", "median-cut", "octree"] {
+        let output = Command::new(&bin)
+            .arg(fixture_dir().join("colorful.png"))
+            .args(["--algorithm", algorithm])
+            .output()
+            .expect("failed to run binary");
+
+        assert!(output.status.success(), "--algorithm {algorithm} failed");
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        validate_theme_structure(&stdout);
+        assert!(stdout.contains(&format!("# algorithm: {algorithm}\n")));
+    }
+}
+
+#[test]
+fn cli_weightings_produce_valid_themes() {
+    ensure_fixtures();
+    let bin = cargo_bin();
+    for weighting in ["area", "chroma", "saliency"] {
+        let output = Command::new(&bin)
+            .arg(fixture_dir().join("dark-photo.png"))
+            .args(["--weighting", weighting])
+            .output()
+            .expect("failed to run binary");
+
+        assert!(output.status.success(), "--weighting {weighting} failed");
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        validate_theme_structure(&stdout);
+        assert!(stdout.contains(&format!("# weighting: {weighting}\n")));
+    }
+}
+
+#[test]
+fn cli_sampling_strategies_produce_valid_themes() {
+    ensure_fixtures();
+    let bin = cargo_bin();
+    for strategy in ["stride", "random", "saturation"] {
+        let output = Command::new(&bin)
+            .arg(fixture_dir().join("colorful.png"))
+            .args(["--sampling", strategy, "--sample-size", "2000"])
+            .output()
+            .expect("failed to run binary");
+
+        assert!(output.status.success(), "--sampling {strategy} failed");
+        validate_theme_structure(&String::from_utf8_lossy(&output.stdout));
+    }
+}
+
 #[test]
 fn cli_mode_flag_works() {
     ensure_fixtures();
@@ -443,6 +528,481 @@ fn cli_output_flag_writes_file() {
     std::fs::remove_dir_all(&tmp).unwrap();
 }
 
+#[test]
+fn cli_report_describes_the_run() {
+    ensure_fixtures();
+    let bin = cargo_bin();
+    let tmp = std::env::temp_dir().join("nuri-test-cli-report");
+    let _ = std::fs::remove_dir_all(&tmp);
+    std::fs::create_dir_all(&tmp).unwrap();
+    let out_path = tmp.join("theme");
+    let image = fixture_dir().join("colorful.png");
+
+    let output = Command::new(&bin)
+        .arg(&image)
+        .args(["--report", "json", "--output", out_path.to_str().unwrap()])
+        .output()
+        .expect("failed to run binary");
+    assert!(output.status.success());
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["extraction"]["algorithm"], "kmeans");
+    assert!(!report["extraction"]["clusters"]
+        .as_array()
+        .unwrap()
+        .is_empty());
+    let theme = &report["themes"][0];
+    assert_eq!(theme["mode_source"], "detected");
+    assert_eq!(theme["slots"].as_array().unwrap().len(), 16);
+    assert_eq!(theme["slots"][4]["slot"], "blue");
+    assert!(theme["contrast"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .all(|row| row["ratio"].as_f64().is_some()));
+    assert_eq!(theme["outputs"][0]["path"], out_path.to_str().unwrap());
+
+    // The theme would share stdout with the report
+    let output = Command::new(&bin)
+        .arg(&image)
+        .args(["--report", "json"])
+        .output()
+        .expect("failed to run binary");
+    assert!(!output.status.success());
+
+    std::fs::remove_dir_all(&tmp).unwrap();
+}
+
+#[test]
+fn cli_batch_processes_a_directory() {
+    ensure_fixtures();
+    let bin = cargo_bin();
+    let tmp = std::env::temp_dir().join("nuri-test-cli-batch");
+    let _ = std::fs::remove_dir_all(&tmp);
+    let images = tmp.join("walls");
+    std::fs::create_dir_all(images.join("more")).unwrap();
+    for fixture in ["dark-photo.png", "light-photo.png"] {
+        std::fs::copy(fixture_dir().join(fixture), images.join(fixture)).unwrap();
+    }
+    std::fs::copy(
+        fixture_dir().join("colorful.png"),
+        images.join("more/colorful.png"),
+    )
+    .unwrap();
+    std::fs::write(images.join("broken.png"), "not an image").unwrap();
+    let out = tmp.join("themes");
+
+    let run = |extra: &[&str]| {
+        Command::new(&bin)
+            .args(["batch", images.to_str().unwrap(), "--out-dir"])
+            .arg(&out)
+            .args(["-t", "ghostty,alacritty", "-j", "2"])
+            .args(extra)
+            .output()
+            .expect("failed to run binary")
+    };
+
+    let output = run(&["--recursive", "--glob", "*-photo.png"]);
+    assert!(output.status.success());
+    let summary = String::from_utf8_lossy(&output.stdout);
+    assert!(summary.contains("2 generated, 0 failed"), "{summary}");
+    validate_theme_structure(&std::fs::read_to_string(out.join("ghostty/dark-photo")).unwrap());
+    assert!(out.join("alacritty/light-photo.toml").exists());
+    assert!(!out.join("ghostty/colorful").exists());
+
+    // Failures are listed and make the command fail, without stopping the rest
+    let output = run(&["--recursive"]);
+    assert!(!output.status.success());
+    let summary = String::from_utf8_lossy(&output.stdout);
+    assert!(summary.contains("more/colorful.png"), "{summary}");
+    assert!(summary.contains("3 generated, 1 failed"), "{summary}");
+    assert!(out.join("alacritty/colorful.toml").exists());
+
+    std::fs::remove_dir_all(&tmp).unwrap();
+}
+
+#[test]
+fn cli_out_dir_keeps_targets_apart() {
+    ensure_fixtures();
+    let bin = cargo_bin();
+    let out = std::env::temp_dir().join("nuri-test-cli-out-dir");
+    let _ = std::fs::remove_dir_all(&out);
+    let run = |targets: &str| {
+        Command::new(&bin)
+            .arg(fixture_dir().join("colorful.png"))
+            .args(["-n", "sunset", "-q", "-t", targets, "--out-dir"])
+            .arg(&out)
+            .output()
+            .expect("failed to run binary")
+    };
+
+    // Formats that share an extension, have none, or come with companions
+    let output = run("ghostty,zathura,waybar,gtk,pywal,env");
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    validate_theme_structure(&std::fs::read_to_string(out.join("ghostty/sunset")).unwrap());
+    assert!(std::fs::read_to_string(out.join("zathura/sunset"))
+        .unwrap()
+        .contains("set default-bg"));
+    assert!(std::fs::read_to_string(out.join("waybar/sunset.css"))
+        .unwrap()
+        .contains("@define-color accent "));
+    assert!(std::fs::read_to_string(out.join("gtk/sunset.css"))
+        .unwrap()
+        .contains("@define-color accent_bg_color "));
+    for file in [
+        "pywal/sunset.json",
+        "pywal/colors.sh",
+        "pywal/colors",
+        "env/sunset.sh",
+        "env/colors.fish",
+    ] {
+        assert!(out.join(file).is_file(), "missing {file}");
+    }
+    assert!(std::fs::read_to_string(out.join("pywal/colors.sh"))
+        .unwrap()
+        .contains("export color4="));
+
+    // A target listed twice would overwrite itself
+    let output = run("ghostty,ghostty");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("listed twice"));
+
+    let _ = std::fs::remove_dir_all(&out);
+}
+
+#[test]
+fn cli_config_dir_moves_install_directories() {
+    ensure_fixtures();
+    let bin = cargo_bin();
+    let tmp = std::env::temp_dir().join("nuri-test-cli-config-dir");
+    let _ = std::fs::remove_dir_all(&tmp);
+    let image = fixture_dir().join("colorful.png");
+    let install = |name: &str, extra: &[&str], env: Option<&Path>| {
+        let mut command = Command::new(&bin);
+        command
+            .args(["install"])
+            .arg(&image)
+            .args(["-t", "json", "-t", "neovim", "-n", name, "-q"])
+            .args(extra)
+            .env("XDG_CONFIG_HOME", tmp.join("xdg"))
+            .env_remove("NURI_CONFIG_DIR");
+        if let Some(dir) = env {
+            command.env("NURI_CONFIG_DIR", dir);
+        }
+        command.output().expect("failed to run binary")
+    };
+
+    let flag = tmp.join("flag");
+    assert!(
+        install("a", &["--config-dir", flag.to_str().unwrap()], None)
+            .status
+            .success()
+    );
+    assert!(flag.join("nuri/themes/a.json").exists());
+    assert!(flag.join("nvim/lua/plugins/a.lua").exists());
+
+    let env = tmp.join("env");
+    assert!(install("b", &[], Some(&env)).status.success());
+    assert!(env.join("nuri/themes/b.json").exists());
+    assert!(!tmp.join("xdg").exists());
+
+    // Without either, XDG_CONFIG_HOME still applies
+    assert!(install("c", &[], None).status.success());
+    assert!(tmp.join("xdg/nuri/themes/c.json").exists());
+
+    // Targets installing into the data home don't move
+    let output = Command::new(&bin)
+        .arg("install")
+        .arg(&image)
+        .args(["-t", "konsole", "-n", "d", "-q", "--config-dir"])
+        .arg(&flag)
+        .env("XDG_DATA_HOME", tmp.join("data"))
+        .output()
+        .expect("failed to run binary");
+    assert!(output.status.success(), "{output:?}");
+    assert!(tmp.join("data/konsole/d.colorscheme").exists());
+    assert!(!flag.join("konsole").exists());
+    let _ = std::fs::remove_dir_all(&tmp);
+}
+
+#[test]
+fn cli_sanitizes_and_templates_theme_names() {
+    ensure_fixtures();
+    let bin = cargo_bin();
+    let tmp = std::env::temp_dir().join("nuri-test-cli-names");
+    let _ = std::fs::remove_dir_all(&tmp);
+    let image = fixture_dir().join("colorful.png");
+    let install = |extra: &[&str]| {
+        Command::new(&bin)
+            .arg("install")
+            .arg(&image)
+            .args(["-t", "json", "-q"])
+            .args(extra)
+            .env("NURI_CONFIG_DIR", tmp.join("config"))
+            .output()
+            .expect("failed to run binary")
+    };
+    let themes = tmp.join("config/nuri/themes");
+
+    // A name can't climb out of the themes directory
+    assert!(install(&["-n", "../../escaped theme"]).status.success());
+    assert!(themes.join("escaped-theme.json").exists());
+    assert!(!tmp.join("escaped theme.json").exists());
+
+    let output = install(&["--name-template", "{stem}-{mode}", "--mode", "light"]);
+    assert!(output.status.success());
+    assert!(themes.join("colorful-light.json").exists());
+
+    // With {mode} in the template, --both adds no suffix of its own
+    let output = install(&["--name-template", "wall-{mode}", "--both"]);
+    assert!(output.status.success());
+    assert!(themes.join("wall-dark.json").exists());
+    assert!(themes.join("wall-light.json").exists());
+
+    let output = install(&["--name-template", "{size}"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("unknown placeholder '{size}'"), "{stderr}");
+    let _ = std::fs::remove_dir_all(&tmp);
+}
+
+#[test]
+fn cli_caches_extractions() {
+    ensure_fixtures();
+    let bin = cargo_bin();
+    let tmp = std::env::temp_dir().join("nuri-test-cli-cache");
+    let _ = std::fs::remove_dir_all(&tmp);
+    let fixture = fixture_dir().join("colorful.png");
+    let cached = || -> usize {
+        std::fs::read_dir(tmp.join("nuri"))
+            .map(|entries| entries.count())
+            .unwrap_or(0)
+    };
+    let run = |extra: &[&str]| {
+        Command::new(&bin)
+            .arg(&fixture)
+            .args(["-v"])
+            .args(extra)
+            .env("XDG_CACHE_HOME", &tmp)
+            .output()
+            .expect("failed to run binary")
+    };
+
+    let first = run(&["--no-cache"]);
+    assert!(first.status.success());
+    assert_eq!(cached(), 0);
+
+    run(&[]);
+    assert_eq!(cached(), 1);
+    let second = run(&[]);
+    assert!(String::from_utf8_lossy(&second.stderr).contains("using cached colors"));
+    // The same theme, but for when it was generated
+    let theme = |stdout: &[u8]| -> String {
+        String::from_utf8_lossy(stdout)
+            .lines()
+            .filter(|line| !line.starts_with("# generated-at:"))
+            .collect()
+    };
+    assert_eq!(theme(&first.stdout), theme(&second.stdout));
+
+    let output = Command::new(&bin)
+        .args(["cache", "clear"])
+        .env("XDG_CACHE_HOME", &tmp)
+        .output()
+        .expect("failed to run binary");
+    assert!(output.status.success());
+    assert_eq!(cached(), 0);
+
+    std::fs::remove_dir_all(&tmp).unwrap();
+}
+
+#[test]
+fn cli_no_clobber_and_force() {
+    ensure_fixtures();
+    let bin = cargo_bin();
+    let tmp = std::env::temp_dir().join("nuri-test-cli-no-clobber");
+    let _ = std::fs::remove_dir_all(&tmp);
+    std::fs::create_dir_all(&tmp).unwrap();
+    let out_path = tmp.join("theme.kdl");
+    std::fs::write(&out_path, "keep me").unwrap();
+    let image = fixture_dir().join("dark-photo.png");
+
+    let run = |extra: &[&str]| {
+        Command::new(&bin)
+            .arg(&image)
+            .args(["--target", "zellij", "--output", out_path.to_str().unwrap()])
+            .args(extra)
+            .env("XDG_CONFIG_HOME", &tmp)
+            .output()
+            .expect("failed to run binary")
+    };
+
+    let output = run(&["--no-clobber"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("already exists"));
+    assert_eq!(std::fs::read_to_string(&out_path).unwrap(), "keep me");
+
+    // no-clobber from the config file is overridden by --force
+    std::fs::create_dir_all(tmp.join("nuri")).unwrap();
+    std::fs::write(tmp.join("nuri/config.toml"), "no-clobber = true\n").unwrap();
+    assert!(!run(&[]).status.success());
+    assert_eq!(std::fs::read_to_string(&out_path).unwrap(), "keep me");
+
+    assert!(run(&["--force"]).status.success());
+    assert!(std::fs::read_to_string(&out_path)
+        .unwrap()
+        .contains("themes {"));
+
+    assert!(!run(&["--force", "--no-clobber"]).status.success());
+
+    std::fs::remove_dir_all(&tmp).unwrap();
+}
+
+#[test]
+#[cfg(not(feature = "video"))]
+fn cli_video_needs_the_video_feature() {
+    let bin = cargo_bin();
+    let tmp = std::env::temp_dir().join("nuri-test-cli-video");
+    std::fs::create_dir_all(&tmp).unwrap();
+    let video = tmp.join("rain.mp4");
+    std::fs::write(&video, "").unwrap();
+
+    let output = Command::new(&bin)
+        .arg(&video)
+        .output()
+        .expect("failed to run binary");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("--features video"), "{stderr}");
+
+    std::fs::remove_dir_all(&tmp).unwrap();
+}
+
+#[test]
+fn cli_rejects_overlong_frame_interval() {
+    ensure_fixtures();
+    let bin = cargo_bin();
+    let image = fixture_dir().join("colorful.png");
+    let output = Command::new(&bin)
+        .arg(&image)
+        .args(["--frame-interval", "1e30"])
+        .output()
+        .expect("failed to run binary");
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("--frame-interval"), "{stderr}");
+
+    // The same from the config file
+    let tmp = std::env::temp_dir().join("nuri-test-cli-frame-interval");
+    std::fs::create_dir_all(&tmp).unwrap();
+    let config = tmp.join("config.toml");
+    std::fs::write(&config, "frame-interval = 1e30\n").unwrap();
+    let output = Command::new(&bin)
+        .arg(&image)
+        .arg("--config")
+        .arg(&config)
+        .output()
+        .expect("failed to run binary");
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("too long"), "{stderr}");
+
+    std::fs::remove_dir_all(&tmp).unwrap();
+}
+
+#[test]
+fn cli_crop_is_recorded_and_checked() {
+    ensure_fixtures();
+    let bin = cargo_bin();
+    let image = fixture_dir().join("dark-photo.png");
+    let tmp = std::env::temp_dir().join("nuri-test-cli-crop");
+    std::fs::create_dir_all(&tmp).unwrap();
+    let theme = tmp.join("cropped");
+
+    let output = Command::new(&bin)
+        .arg(&image)
+        .args(["--crop", "0,32,64,32", "--no-cache", "--quiet", "--output"])
+        .arg(&theme)
+        .output()
+        .expect("failed to run binary");
+    assert!(output.status.success(), "{output:?}");
+    let written = std::fs::read_to_string(&theme).unwrap();
+    assert!(written.contains("crop: 0,32,64,32"), "{written}");
+
+    let output = Command::new(&bin)
+        .arg(&image)
+        .args(["--crop", "32,0,64,64", "--no-cache"])
+        .output()
+        .expect("failed to run binary");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("outside the 64x64 image"), "{stderr}");
+
+    let output = Command::new(&bin)
+        .arg(&image)
+        .args(["--crop", "0,0,8,8", "--focus", "top"])
+        .output()
+        .expect("failed to run binary");
+    assert!(!output.status.success());
+
+    std::fs::remove_dir_all(&tmp).unwrap();
+}
+
+#[test]
+fn cli_bench_times_stages_without_being_listed() {
+    ensure_fixtures();
+    let bin = cargo_bin();
+    let image = fixture_dir().join("colorful.png");
+
+    let output = Command::new(&bin)
+        .arg("bench")
+        .arg(&image)
+        .args(["--runs", "2", "-k", "8"])
+        .output()
+        .expect("failed to run binary");
+    assert!(output.status.success(), "{output:?}");
+    let table = String::from_utf8_lossy(&output.stdout);
+    assert!(table.contains("2 runs"), "{table}");
+    for stage in ["decode", "cluster", "palette", "total"] {
+        assert!(
+            table.lines().any(|line| line.starts_with(stage)),
+            "{stage} missing:\n{table}"
+        );
+    }
+
+    let help = Command::new(&bin)
+        .arg("--help")
+        .output()
+        .expect("failed to run binary");
+    assert!(!String::from_utf8_lossy(&help.stdout).contains("bench"));
+}
+
+#[test]
+fn cli_activate_requires_ghostty_install() {
+    ensure_fixtures();
+    let bin = cargo_bin();
+    let image = fixture_dir().join("dark-photo.png");
+
+    let output = Command::new(&bin)
+        .arg(&image)
+        .arg("--activate")
+        .output()
+        .expect("failed to run binary");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--install"));
+
+    let output = Command::new(&bin)
+        .arg(&image)
+        .args(["--activate", "--install", "--target", "zellij"])
+        .output()
+        .expect("failed to run binary");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("ghostty"));
+}
+
 #[test]
 fn cli_help_output() {
     let bin = cargo_bin();
@@ -551,45 +1111,454 @@ fn cli_target_zellij_stdout() {
 }
 
 #[test]
-fn cli_target_neovim_stdout() {
+fn cli_config_file_sets_defaults() {
     ensure_fixtures();
     let bin = cargo_bin();
+    let dir = std::env::temp_dir().join("nuri-test-cli-config");
+    std::fs::create_dir_all(&dir).unwrap();
+    let config = dir.join("config.toml");
+    std::fs::write(
+        &config,
+        "targets = [\"zellij\"]\n[slots]\nred = \"#123456\"\n",
+    )
+    .unwrap();
+    let image = fixture_dir().join("dark-photo.png");
+
     let output = Command::new(&bin)
-        .args([
-            fixture_dir().join("dark-photo.png").to_str().unwrap(),
-            "--target",
-            "neovim",
-        ])
+        .arg(&image)
+        .arg("--config")
+        .arg(&config)
         .output()
         .expect("failed to run binary");
-
     assert!(output.status.success());
     let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("themes {"), "config target should apply");
     assert!(
-        stdout.contains("vim.g.colors_name"),
-        "neovim output should contain vim.g.colors_name"
+        stdout.contains("red \"#123456\""),
+        "slot override should apply"
     );
-}
 
-#[test]
-fn cli_multiple_targets_no_install_errors() {
-    ensure_fixtures();
-    let bin = cargo_bin();
+    // --target takes precedence over the config's targets
     let output = Command::new(&bin)
-        .args([
-            fixture_dir().join("dark-photo.png").to_str().unwrap(),
-            "--target",
-            "ghostty,zellij",
-        ])
+        .arg(&image)
+        .arg("--config")
+        .arg(&config)
+        .args(["--target", "ghostty"])
         .output()
         .expect("failed to run binary");
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("palette = 1=#123456"));
 
-    assert!(!output.status.success());
-    let stderr = String::from_utf8_lossy(&output.stderr);
-    assert!(
-        stderr.contains("cannot output multiple targets"),
-        "expected multi-target error, got: {stderr}"
-    );
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn cli_generate_subcommand_matches_default() {
+    ensure_fixtures();
+    let bin = cargo_bin();
+    let image = fixture_dir().join("dark-photo.png");
+
+    let bare = Command::new(&bin).arg(&image).output().unwrap();
+    let sub = Command::new(&bin)
+        .arg("generate")
+        .arg(&image)
+        .output()
+        .unwrap();
+    assert!(bare.status.success() && sub.status.success());
+    // Everything but the generation time must match
+    let strip = |out: &[u8]| -> String {
+        String::from_utf8_lossy(out)
+            .lines()
+            .filter(|l| !l.starts_with("# generated-at: "))
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+    assert_eq!(strip(&bare.stdout), strip(&sub.stdout));
+}
+
+#[test]
+fn cli_regenerate_reproduces_theme_from_header() {
+    ensure_fixtures();
+    let bin = cargo_bin();
+    let tmp = std::env::temp_dir().join("nuri-test-cli-regenerate");
+    let _ = std::fs::remove_dir_all(&tmp);
+    std::fs::create_dir_all(&tmp).unwrap();
+    let image = tmp.join("wall.png");
+    std::fs::copy(fixture_dir().join("colorful.png"), &image).unwrap();
+    let theme = tmp.join("wall.toml");
+
+    let output = Command::new(&bin)
+        .arg(&image)
+        .args([
+            "-t",
+            "alacritty",
+            "--mode",
+            "light",
+            "-k",
+            "10",
+            "--seed",
+            "7",
+        ])
+        .args(["--set", "red=#ff0000", "-o"])
+        .arg(&theme)
+        .env("XDG_CONFIG_HOME", &tmp)
+        .output()
+        .expect("failed to run binary");
+    assert!(output.status.success());
+    let original = std::fs::read_to_string(&theme).unwrap();
+    assert!(original.contains("# source-hash: fnv1a64:"), "{original}");
+    assert!(original.contains("# seed: 7\n"), "{original}");
+
+    let colors = |text: &str| -> Vec<String> {
+        text.lines()
+            .filter(|l| !l.starts_with('#'))
+            .map(str::to_string)
+            .collect()
+    };
+
+    // In place: same colors, same settings
+    let tampered = original.replace("red = \"#ff0000\"", "red = \"#00ff00\"");
+    assert_ne!(tampered, original);
+    std::fs::write(&theme, tampered).unwrap();
+    let output = Command::new(&bin)
+        .arg("regenerate")
+        .arg(&theme)
+        .env("XDG_CONFIG_HOME", &tmp)
+        .output()
+        .expect("failed to run binary");
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let regenerated = std::fs::read_to_string(&theme).unwrap();
+    assert_eq!(colors(&regenerated), colors(&original));
+
+    // A changed wallpaper is reported
+    std::fs::copy(fixture_dir().join("dark-photo.png"), &image).unwrap();
+    let output = Command::new(&bin)
+        .arg("regenerate")
+        .arg(&theme)
+        .arg("-o")
+        .arg(tmp.join("copy.toml"))
+        .env("XDG_CONFIG_HOME", &tmp)
+        .output()
+        .expect("failed to run binary");
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("has changed"));
+
+    // Themes without a header cannot be regenerated
+    std::fs::write(tmp.join("plain"), "background = #000000\n").unwrap();
+    let output = Command::new(&bin)
+        .arg("regenerate")
+        .arg(tmp.join("plain"))
+        .output()
+        .expect("failed to run binary");
+    assert!(!output.status.success());
+
+    std::fs::remove_dir_all(&tmp).unwrap();
+}
+
+#[test]
+fn cli_install_list_remove_subcommands() {
+    ensure_fixtures();
+    let bin = cargo_bin();
+    let home = std::env::temp_dir().join("nuri-test-cli-subcommands");
+    let _ = std::fs::remove_dir_all(&home);
+    let run = |args: &[&str]| {
+        Command::new(&bin)
+            .args(args)
+            .env("XDG_CONFIG_HOME", &home)
+            .output()
+            .expect("failed to run binary")
+    };
+    let image = fixture_dir().join("dark-photo.png");
+    let image = image.to_str().unwrap();
+
+    let output = run(&["install", image, "-t", "zellij", "-n", "sub-test"]);
+    assert!(output.status.success());
+    assert!(home.join("zellij/themes/sub-test.kdl").exists());
+
+    // A hand-written theme next to the generated one
+    let manual = home.join("zellij/themes/manual.kdl");
+    std::fs::write(&manual, "themes {\n    manual {\n    }\n}\n").unwrap();
+
+    let output = run(&["list", "-t", "zellij"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("  manual\n"), "{stdout}");
+    assert!(stdout.contains("  sub-test  nuri\n"), "{stdout}");
+
+    let output = run(&["remove", "manual", "-t", "zellij"]);
+    assert!(!output.status.success(), "hand-written themes need --force");
+    assert!(manual.exists());
+    let output = run(&["remove", "manual", "--force", "-t", "zellij"]);
+    assert!(output.status.success());
+    assert!(!manual.exists());
+
+    let output = run(&["remove", "sub-test", "-t", "zellij"]);
+    assert!(output.status.success());
+    assert!(!home.join("zellij/themes/sub-test.kdl").exists());
+
+    let output = run(&["remove", "sub-test", "-t", "zellij"]);
+    assert!(
+        !output.status.success(),
+        "removing a missing theme should fail"
+    );
+
+    let _ = std::fs::remove_dir_all(&home);
+}
+
+#[test]
+fn cli_remove_leaves_fixed_path_files_of_other_themes() {
+    ensure_fixtures();
+    let bin = cargo_bin();
+    let tmp = std::env::temp_dir().join("nuri-test-cli-remove-fixed");
+    let _ = std::fs::remove_dir_all(&tmp);
+    let run = |args: &[&str]| {
+        Command::new(&bin)
+            .args(args)
+            .env("HOME", tmp.join("home"))
+            .env("XDG_CONFIG_HOME", tmp.join("config"))
+            .env("PYWAL_CACHE_DIR", tmp.join("wal"))
+            .env_remove("NURI_CONFIG_DIR")
+            .output()
+            .expect("failed to run binary")
+    };
+    let image = fixture_dir().join("dark-photo.png");
+    let image = image.to_str().unwrap();
+    let targets = ["-t", "waybar", "-t", "pywal", "-t", "ghostty"];
+
+    let output = run(&[&["install", image, "-n", "alpha", "-q"], &targets[..]].concat());
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    // The user's own stylesheet next to the installed colors
+    let style = tmp.join("config/waybar/style.css");
+    std::fs::write(&style, "@import \"colors.css\";\n").unwrap();
+    let files = [
+        tmp.join("config/waybar/colors.css"),
+        tmp.join("wal/colors.json"),
+        tmp.join("wal/colors.sh"),
+        tmp.join("wal/colors"),
+        tmp.join("config/ghostty/themes/alpha"),
+        style.clone(),
+    ];
+    let snapshot = || {
+        files
+            .iter()
+            .map(|f| std::fs::read(f).ok())
+            .collect::<Vec<_>>()
+    };
+    let before = snapshot();
+    assert!(before.iter().all(Option::is_some));
+
+    let output = run(&[&["list"], &targets[..]].concat());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("  alpha  nuri\n"), "{stdout}");
+    assert!(!stdout.contains("style"), "{stdout}");
+
+    for force in [false, true] {
+        let mut args = [&["remove", "totally-unrelated"], &targets[..]].concat();
+        if force {
+            args.push("--force");
+        }
+        let output = run(&args);
+        assert!(!output.status.success());
+        assert_eq!(snapshot(), before, "--force: {force}");
+    }
+
+    let output = run(&[&["remove", "alpha"], &targets[..]].concat());
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    for file in &files[..5] {
+        assert!(!file.exists(), "{} was left behind", file.display());
+    }
+    assert!(style.exists());
+
+    let _ = std::fs::remove_dir_all(&tmp);
+}
+
+#[test]
+fn cli_import_converts_ghostty_theme() {
+    ensure_fixtures();
+    let bin = cargo_bin();
+    let dir = std::env::temp_dir().join("nuri-test-cli-import");
+    std::fs::create_dir_all(&dir).unwrap();
+    let ghostty_theme = dir.join("sunset");
+
+    let output = Command::new(&bin)
+        .arg(fixture_dir().join("colorful.png"))
+        .arg("-o")
+        .arg(&ghostty_theme)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let output = Command::new(&bin)
+        .arg("import")
+        .arg(&ghostty_theme)
+        .args(["--target", "zellij"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("    sunset {"),
+        "theme name comes from the file"
+    );
+    assert_eq!(
+        stdout,
+        ZellijBackend.serialize(
+            &nuri::import::load_palette(&ghostty_theme).unwrap(),
+            "sunset"
+        )
+    );
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn cli_both_writes_dark_and_light_pair() {
+    ensure_fixtures();
+    let bin = cargo_bin();
+    let dir = std::env::temp_dir().join("nuri-test-cli-both");
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let output = Command::new(&bin)
+        .arg(fixture_dir().join("colorful.png"))
+        .arg("--both")
+        .arg("-o")
+        .arg(dir.join("pair.conf"))
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let dark = nuri::import::load_palette(&dir.join("pair-dark.conf")).unwrap();
+    let light = nuri::import::load_palette(&dir.join("pair-light.conf")).unwrap();
+    assert!(dark.background.relative_luminance() < light.background.relative_luminance());
+    for slot in 1..=6 {
+        let d = f32::from(dark.slots[slot].to_oklch().hue);
+        let l = f32::from(light.slots[slot].to_oklch().hue);
+        let dist = nuri::pipeline::assign::hue_distance(d, l);
+        assert!(
+            dist < 15.0,
+            "slot {slot} hue drifted {dist:.1}° between modes"
+        );
+    }
+
+    // Two themes cannot share stdout
+    let output = Command::new(&bin)
+        .arg(fixture_dir().join("colorful.png"))
+        .arg("--both")
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn cli_both_preview_full_names_each_theme_once() {
+    ensure_fixtures();
+    let dir = std::env::temp_dir().join("nuri-test-cli-both-preview-full");
+    let _ = std::fs::remove_dir_all(&dir);
+
+    let output = Command::new(cargo_bin())
+        .arg(fixture_dir().join("colorful.png"))
+        .args(["--both", "--preview-full", "--name", "pair", "--out-dir"])
+        .arg(&dir)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Theme: pair-dark "), "{stdout}");
+    assert!(stdout.contains("Theme: pair-light "), "{stdout}");
+    assert!(!stdout.contains("pair-dark-dark"), "{stdout}");
+    assert!(!stdout.contains("pair-light-light"), "{stdout}");
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn cli_lock_keeps_slots_of_the_replaced_theme() {
+    ensure_fixtures();
+    let bin = cargo_bin();
+    let dir = std::env::temp_dir().join("nuri-test-cli-lock");
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let theme = dir.join("locked");
+
+    let run = |image: &str, extra: &[&str]| {
+        let output = Command::new(&bin)
+            .arg(fixture_dir().join(image))
+            .args(["--mode", "dark", "--quiet", "--force"])
+            .args(extra)
+            .arg("-o")
+            .arg(&theme)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{output:?}");
+        nuri::import::load_palette(&theme).unwrap()
+    };
+    let first = run("colorful.png", &[]);
+    let second = run("monochrome.png", &["--lock", "1"]);
+    assert_eq!(second.slots[1], first.slots[1]);
+    // The rest comes from the new image
+    let fresh = run("monochrome.png", &[]);
+    assert_ne!(fresh.slots[1], first.slots[1]);
+    assert_eq!(second.slots[4], fresh.slots[4]);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn cli_target_neovim_stdout() {
+    ensure_fixtures();
+    let bin = cargo_bin();
+    let output = Command::new(&bin)
+        .args([
+            fixture_dir().join("dark-photo.png").to_str().unwrap(),
+            "--target",
+            "neovim",
+        ])
+        .output()
+        .expect("failed to run binary");
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        stdout.contains("vim.g.colors_name"),
+        "neovim output should contain vim.g.colors_name"
+    );
+}
+
+#[test]
+fn cli_multiple_targets_no_install_errors() {
+    ensure_fixtures();
+    let bin = cargo_bin();
+    let output = Command::new(&bin)
+        .args([
+            fixture_dir().join("dark-photo.png").to_str().unwrap(),
+            "--target",
+            "ghostty,zellij",
+        ])
+        .output()
+        .expect("failed to run binary");
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("cannot output multiple targets"),
+        "expected multi-target error, got: {stderr}"
+    );
 }
 
 #[test]
@@ -709,6 +1678,11 @@ fn snapshot_neovim_dark_photo() {
     snapshot_test_backend("dark-photo.png", &NeovimBackend, "neovim");
 }
 
+#[test]
+fn snapshot_alacritty_colorful() {
+    snapshot_test_backend("colorful.png", &AlacrittyBackend, "alacritty");
+}
+
 // ---------------------------------------------------------------------------
 // Trait-level tests
 // ---------------------------------------------------------------------------
@@ -737,8 +1711,8 @@ fn make_test_palette() -> nuri::pipeline::assign::AnsiPalette {
 #[test]
 fn all_backends_serialize_nonempty() {
     let palette = make_test_palette();
-    for target in [Target::Ghostty, Target::Zellij, Target::Neovim] {
-        let backend = get_backend(target);
+    for &target in Target::all() {
+        let backend = get_backend(target).unwrap();
         let output = backend.serialize(&palette, "test");
         assert!(
             !output.is_empty(),
@@ -758,9 +1732,13 @@ fn all_backends_write_to_matches_serialize() {
         (Target::Ghostty, ""),
         (Target::Zellij, ".kdl"),
         (Target::Neovim, ".lua"),
+        (Target::Alacritty, ".toml"),
+        (Target::Wezterm, ".lua"),
+        (Target::WindowsTerminal, ".json"),
+        (Target::Json, ".json"),
     ] {
-        let backend = get_backend(target);
-        let filename = format!("test{ext}");
+        let backend = get_backend(target).unwrap();
+        let filename = format!("test-{}{ext}", backend.name());
         let path = tmp.join(&filename);
         backend.write_to(&palette, "test", &path).unwrap();
         let content = std::fs::read_to_string(&path).unwrap();
@@ -782,8 +1760,15 @@ fn all_backends_install_creates_file() {
     let _ = std::fs::remove_dir_all(&tmp);
     std::env::set_var("XDG_CONFIG_HOME", &tmp);
 
-    for target in [Target::Ghostty, Target::Zellij, Target::Neovim] {
-        let backend = get_backend(target);
+    for target in [
+        Target::Ghostty,
+        Target::Zellij,
+        Target::Neovim,
+        Target::Alacritty,
+        Target::Wezterm,
+        Target::Json,
+    ] {
+        let backend = get_backend(target).unwrap();
         let path = backend.install(&palette, "test_theme").unwrap();
         assert!(
             path.exists(),
@@ -830,8 +1815,8 @@ mod multi_backend_property_tests {
             let mut palette = assign_slots(&colors, mode);
             enforce_contrast(&mut palette, DEFAULT_ACCENT_CONTRAST);
 
-            for target in [Target::Ghostty, Target::Zellij, Target::Neovim] {
-                let backend = get_backend(target);
+            for &target in Target::all() {
+                let backend = get_backend(target).unwrap();
                 let output = backend.serialize(&palette, "test");
                 prop_assert!(!output.is_empty(), "{} produced empty output", backend.name());
             }
@@ -863,3 +1848,482 @@ mod multi_backend_property_tests {
         }
     }
 }
+
+#[test]
+fn cli_watch_reinstalls_when_wallpaper_changes() {
+    ensure_fixtures();
+    let tmp = std::env::temp_dir().join("nuri-test-cli-watch");
+    let _ = std::fs::remove_dir_all(&tmp);
+    let walls = tmp.join("walls");
+    std::fs::create_dir_all(&walls).unwrap();
+    std::fs::copy(
+        fixture_dir().join("dark-photo.png"),
+        walls.join("current.png"),
+    )
+    .unwrap();
+    let theme = tmp.join("ghostty/themes/wall");
+
+    let mut child = Command::new(cargo_bin())
+        .arg("watch")
+        .arg(&walls)
+        .args(["--name", "wall"])
+        .env("XDG_CONFIG_HOME", &tmp)
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .expect("failed to run binary");
+
+    let wait_for = |done: &dyn Fn(&str) -> bool| {
+        for _ in 0..200 {
+            if let Ok(text) = std::fs::read_to_string(&theme) {
+                if done(&text) {
+                    return text;
+                }
+            }
+            std::thread::sleep(std::time::Duration::from_millis(50));
+        }
+        panic!("theme was not (re)installed in time");
+    };
+    let first = wait_for(&|text| text.contains("current.png"));
+
+    std::fs::copy(
+        fixture_dir().join("light-photo.png"),
+        walls.join("next.png"),
+    )
+    .unwrap();
+    let second = wait_for(&|text| text.contains("next.png"));
+    child.kill().unwrap();
+    let _ = child.wait();
+
+    validate_theme_structure(&second);
+    assert_ne!(first, second);
+    let _ = std::fs::remove_dir_all(&tmp);
+}
+
+#[test]
+fn cli_from_wallpaper_reads_desktop_state() {
+    ensure_fixtures();
+    let bin = cargo_bin();
+    let tmp = std::env::temp_dir().join("nuri-test-cli-from-wallpaper");
+    let _ = std::fs::remove_dir_all(&tmp);
+    std::fs::create_dir_all(&tmp).unwrap();
+    let image = fixture_dir().join("colorful.png").canonicalize().unwrap();
+    std::fs::write(
+        tmp.join(".fehbg"),
+        format!(
+            "#!/bin/sh\nfeh --no-fehbg --bg-fill '{}'\n",
+            image.display()
+        ),
+    )
+    .unwrap();
+
+    // An empty PATH keeps desktop tools on the host from answering first
+    let run = |args: &[&str]| {
+        Command::new(&bin)
+            .args(args)
+            .env("HOME", &tmp)
+            .env("XDG_CONFIG_HOME", &tmp)
+            .env("PATH", &tmp)
+            .env_remove("HYPRLAND_INSTANCE_SIGNATURE")
+            .env_remove("SWAYSOCK")
+            .output()
+            .expect("failed to run binary")
+    };
+
+    let output = run(&["--from-wallpaper"]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    validate_theme_structure(&stdout);
+    assert!(stdout.contains(&format!("# source: {}\n", image.display())));
+
+    let output = run(&["--from-wallpaper", image.to_str().unwrap()]);
+    assert!(!output.status.success());
+
+    std::fs::remove_file(tmp.join(".fehbg")).unwrap();
+    let output = run(&["--from-wallpaper"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("could not detect"));
+    let _ = std::fs::remove_dir_all(&tmp);
+}
+
+/// Serve each `(content type, body)` response once, in order, on a local port.
+fn serve(responses: Vec<(&'static str, Vec<u8>)>) -> String {
+    use std::io::{BufRead, BufReader, Write};
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    std::thread::spawn(move || {
+        for (content_type, body) in responses {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap() > 2 {
+                line.clear();
+            }
+            let header = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                body.len()
+            );
+            stream.write_all(header.as_bytes()).unwrap();
+            stream.write_all(&body).unwrap();
+        }
+    });
+    format!("http://{addr}")
+}
+
+#[test]
+fn cli_downloads_images_from_urls() {
+    ensure_fixtures();
+    let png = std::fs::read(fixture_dir().join("colorful.png")).unwrap();
+    let base = serve(vec![
+        ("image/png", png),
+        ("text/html", b"<html></html>".to_vec()),
+    ]);
+    let bin = cargo_bin();
+
+    let url = format!("{base}/walls/sunset.png?w=1920");
+    let output = Command::new(&bin)
+        .arg(&url)
+        .output()
+        .expect("failed to run binary");
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    validate_theme_structure(&stdout);
+    assert!(stdout.contains(&format!("# source: {url}\n")));
+    assert!(stdout.contains("# name: sunset\n"));
+
+    let output = Command::new(&bin)
+        .arg(format!("{base}/page"))
+        .output()
+        .expect("failed to run binary");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("not an image"));
+}
+
+#[test]
+fn cli_reads_image_from_stdin() {
+    use std::io::Write;
+    ensure_fixtures();
+    let bin = cargo_bin();
+    let run = |bytes: &[u8]| {
+        let mut child = Command::new(&bin)
+            .arg("-")
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .spawn()
+            .expect("failed to run binary");
+        child.stdin.take().unwrap().write_all(bytes).unwrap();
+        child.wait_with_output().unwrap()
+    };
+
+    let output = run(&std::fs::read(fixture_dir().join("light-photo.png")).unwrap());
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    validate_theme_structure(&stdout);
+    assert!(stdout.contains("# source: -\n"));
+
+    let output = run(b"not an image");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("not a supported image"));
+}
+
+#[test]
+fn cli_template_target_renders_user_template() {
+    ensure_fixtures();
+    let bin = cargo_bin();
+    let tmp = std::env::temp_dir().join("nuri-test-cli-template");
+    let _ = std::fs::remove_dir_all(&tmp);
+    std::fs::create_dir_all(&tmp).unwrap();
+    let template = tmp.join("kitty.conf.tmpl");
+    std::fs::write(
+        &template,
+        "# Generated by nuri\nbackground {{background}}\ncolor4 {{ color4.strip }}\nname {{name}}\n",
+    )
+    .unwrap();
+
+    let output = Command::new(&bin)
+        .arg(fixture_dir().join("colorful.png"))
+        .arg("--template")
+        .arg(&template)
+        .args(["--name", "kit"])
+        .output()
+        .expect("failed to run binary");
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let body: Vec<&str> = stdout.lines().filter(|l| !l.starts_with('#')).collect();
+    assert_eq!(body.len(), 3, "{stdout}");
+    let re = regex::Regex::new(r"^background #[0-9a-f]{6}$").unwrap();
+    assert!(re.is_match(body[0]), "{}", body[0]);
+    let re = regex::Regex::new(r"^color4 [0-9a-f]{6}$").unwrap();
+    assert!(re.is_match(body[1]), "{}", body[1]);
+    assert_eq!(body[2], "name kit");
+    assert!(stdout.contains("# template: "), "{stdout}");
+
+    // --template with a target list that leaves it out is a mistake
+    let output = Command::new(&bin)
+        .arg(fixture_dir().join("colorful.png"))
+        .arg("--template")
+        .arg(&template)
+        .args(["-t", "ghostty"])
+        .output()
+        .expect("failed to run binary");
+    assert!(!output.status.success());
+
+    std::fs::write(&template, "{{colour1}}\n").unwrap();
+    let output = Command::new(&bin)
+        .arg(fixture_dir().join("colorful.png"))
+        .arg("--template")
+        .arg(&template)
+        .output()
+        .expect("failed to run binary");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("unknown placeholder 'colour1'"));
+    let _ = std::fs::remove_dir_all(&tmp);
+}
+
+#[test]
+fn cli_discovers_user_templates_by_name() {
+    ensure_fixtures();
+    let bin = cargo_bin();
+    let tmp = std::env::temp_dir().join("nuri-test-cli-template-dir");
+    let _ = std::fs::remove_dir_all(&tmp);
+    std::fs::create_dir_all(tmp.join("nuri/templates")).unwrap();
+    std::fs::write(
+        tmp.join("nuri/templates/kitty.conf.tmpl"),
+        "# Generated by nuri\nbackground {{background}}\n",
+    )
+    .unwrap();
+
+    let output = Command::new(&bin)
+        .arg("targets")
+        .env("XDG_CONFIG_HOME", &tmp)
+        .output()
+        .expect("failed to run binary");
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("windows-terminal"), "{stdout}");
+    assert!(stdout.contains("kitty.conf.tmpl"), "{stdout}");
+
+    let out_path = tmp.join("out");
+    let output = Command::new(&bin)
+        .arg(fixture_dir().join("colorful.png"))
+        .args(["--template", "kitty", "-o"])
+        .arg(&out_path)
+        .env("XDG_CONFIG_HOME", &tmp)
+        .output()
+        .expect("failed to run binary");
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let theme = std::fs::read_to_string(&out_path).unwrap();
+    assert!(theme.contains("# template: kitty\n"), "{theme}");
+    assert!(
+        theme.lines().any(|l| l.starts_with("background #")),
+        "{theme}"
+    );
+
+    // Discovered templates are targets too
+    std::fs::remove_file(&out_path).unwrap();
+    let output = Command::new(&bin)
+        .arg(fixture_dir().join("colorful.png"))
+        .args(["-t", "kitty", "-o"])
+        .arg(&out_path)
+        .env("XDG_CONFIG_HOME", &tmp)
+        .output()
+        .expect("failed to run binary");
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let theme = std::fs::read_to_string(&out_path).unwrap();
+    assert!(
+        theme.lines().any(|l| l.starts_with("background #")),
+        "{theme}"
+    );
+
+    let output = Command::new(&bin)
+        .arg(fixture_dir().join("colorful.png"))
+        .args(["-t", "bogus"])
+        .env("XDG_CONFIG_HOME", &tmp)
+        .output()
+        .expect("failed to run binary");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("unknown target 'bogus'"), "{stderr}");
+    assert!(stderr.contains("kitty"), "{stderr}");
+    let _ = std::fs::remove_dir_all(&tmp);
+}
+
+#[test]
+fn cli_prints_completions_and_man_page() {
+    let bin = cargo_bin();
+    for (shell, marker) in [
+        ("bash", "_nuri()"),
+        ("zsh", "#compdef nuri"),
+        ("fish", "complete -c nuri"),
+    ] {
+        let output = Command::new(&bin)
+            .args(["completions", shell])
+            .output()
+            .expect("failed to run binary");
+        assert!(output.status.success());
+        let script = String::from_utf8_lossy(&output.stdout);
+        assert!(script.contains(marker), "{shell}: {marker} missing");
+    }
+
+    let output = Command::new(&bin)
+        .arg("manpage")
+        .output()
+        .expect("failed to run binary");
+    assert!(output.status.success());
+    let page = String::from_utf8_lossy(&output.stdout);
+    assert!(page.contains(".TH nuri 1"), "{page}");
+    assert!(page.contains("completions"), "{page}");
+
+    let output = Command::new(&bin)
+        .args(["completions", "tcsh"])
+        .output()
+        .expect("failed to run binary");
+    assert!(!output.status.success());
+}
+
+#[test]
+fn cli_color_follows_flag_and_no_color() {
+    ensure_fixtures();
+    let bin = cargo_bin();
+    let preview = |extra: &[&str], no_color: Option<&str>| {
+        let mut command = Command::new(&bin);
+        command
+            .arg("preview")
+            .arg(fixture_dir().join("colorful.png"))
+            .args(extra)
+            .env("COLORTERM", "truecolor")
+            .env_remove("NO_COLOR");
+        if let Some(value) = no_color {
+            command.env("NO_COLOR", value);
+        }
+        let output = command.output().expect("failed to run binary");
+        assert!(output.status.success());
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    };
+
+    // Piped, so plain unless forced
+    let plain = preview(&[], None);
+    assert!(!plain.contains('\x1b'), "{plain}");
+    assert!(plain.contains("Blk"), "{plain}");
+    assert!(
+        plain.lines().any(|l| l.trim_start().starts_with('#')),
+        "{plain}"
+    );
+    assert!(preview(&["--color", "always"], None).contains("\x1b[48;2;"));
+    assert!(preview(&["--color", "always"], Some("1")).contains('\x1b'));
+    assert!(!preview(&["--color", "never"], None).contains('\x1b'));
+    assert!(!preview(&["--color", "auto"], Some("1")).contains('\x1b'));
+}
+
+#[test]
+fn cli_preview_falls_back_without_truecolor() {
+    ensure_fixtures();
+    let bin = cargo_bin();
+    let preview = |term: &str, colorterm: Option<&str>| {
+        let mut command = Command::new(&bin);
+        command
+            .arg("preview")
+            .arg(fixture_dir().join("colorful.png"))
+            .args(["--color", "always"])
+            .env("TERM", term)
+            .env_remove("COLORTERM")
+            .env_remove("TERM_PROGRAM")
+            .env_remove("WT_SESSION");
+        if let Some(value) = colorterm {
+            command.env("COLORTERM", value);
+        }
+        let output = command.output().expect("failed to run binary");
+        assert!(output.status.success());
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    };
+
+    let truecolor = preview("tmux-256color", Some("truecolor"));
+    assert!(truecolor.contains("\x1b[48;2;"), "{truecolor}");
+    // tmux or mosh without truecolor passthrough
+    let indexed = preview("tmux-256color", None);
+    assert!(indexed.contains("\x1b[48;5;"), "{indexed}");
+    assert!(!indexed.contains(";2;"), "{indexed}");
+    let basic = preview("linux", None);
+    assert!(!basic.contains(";2;") && !basic.contains(";5;"), "{basic}");
+    assert!(basic.contains('\x1b'), "{basic}");
+}
+
+#[test]
+fn cli_try_needs_a_terminal() {
+    ensure_fixtures();
+    let output = Command::new(cargo_bin())
+        .arg("try")
+        .arg(fixture_dir().join("colorful.png"))
+        .output()
+        .expect("failed to run binary");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("recolors the terminal"), "{stderr}");
+    // Nothing was sent to recolor it
+    assert!(output.stdout.is_empty());
+
+    // An absurd duration is an error, not a panic
+    let output = Command::new(cargo_bin())
+        .arg("try")
+        .arg(fixture_dir().join("colorful.png"))
+        .args(["--duration", "1e30"])
+        .output()
+        .expect("failed to run binary");
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("too long"), "{stderr}");
+}
+
+#[test]
+fn cli_checks_color_count() {
+    ensure_fixtures();
+    let output = Command::new(cargo_bin())
+        .args(["-k", "4"])
+        .arg(fixture_dir().join("colorful.png"))
+        .output()
+        .expect("failed to run binary");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("at least 8 colors, got 4"), "{stderr}");
+    assert!(stderr.contains("accent"), "{stderr}");
+
+    let output = Command::new(cargo_bin())
+        .args(["--colors", "65"])
+        .arg(fixture_dir().join("colorful.png"))
+        .output()
+        .expect("failed to run binary");
+    assert!(!output.status.success());
+
+    // --help explains what a larger k does
+    let output = Command::new(cargo_bin())
+        .arg("--help")
+        .output()
+        .expect("failed to run binary");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("8 to 64"), "{stdout}");
+    assert!(stdout.contains("accents are more diverse"), "{stdout}");
+}